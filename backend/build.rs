@@ -0,0 +1,6 @@
+fn main() {
+    // Needs protoc on the PATH
+    tonic_build::compile_protos("proto/rwa.proto").expect("Failed to compile proto/rwa.proto");
+    // storage::migrations embeds these files at compile time; pick up edits
+    println!("cargo:rerun-if-changed=migrations");
+}
@@ -0,0 +1,65 @@
+-- Tables the mirror created before migrations were tracked; IF NOT EXISTS
+-- adopts databases that already have them.
+
+CREATE TABLE IF NOT EXISTS assets (
+    asset_id TEXT PRIMARY KEY,
+    asset_pda TEXT NOT NULL,
+    asset_type TEXT NOT NULL,
+    valuation BIGINT NOT NULL,
+    owner TEXT NOT NULL,
+    risk_score SMALLINT NOT NULL,
+    is_active BOOLEAN NOT NULL,
+    updated_at BIGINT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS loans (
+    loan_pda TEXT PRIMARY KEY,
+    asset_id TEXT NOT NULL,
+    borrower TEXT NOT NULL,
+    principal BIGINT NOT NULL,
+    interest_rate BIGINT NOT NULL,
+    duration BIGINT NOT NULL,
+    status TEXT NOT NULL,
+    updated_at BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS loans_asset_id_idx ON loans (asset_id);
+
+CREATE TABLE IF NOT EXISTS risk_events (
+    id BIGSERIAL PRIMARY KEY,
+    asset_id TEXT NOT NULL,
+    risk_score SMALLINT NOT NULL,
+    source TEXT NOT NULL,
+    confidence REAL,
+    tx_signature TEXT,
+    recorded_at BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS risk_events_asset_idx ON risk_events (asset_id, recorded_at);
+
+CREATE TABLE IF NOT EXISTS screenings (
+    id BIGSERIAL PRIMARY KEY,
+    address TEXT NOT NULL,
+    context TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    provider TEXT NOT NULL,
+    matched BOOLEAN NOT NULL,
+    action TEXT NOT NULL,
+    detail TEXT,
+    screened_at BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS screenings_address_idx ON screenings (address, screened_at);
+
+CREATE TABLE IF NOT EXISTS jobs (
+    id BIGSERIAL PRIMARY KEY,
+    kind TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    status TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    run_at BIGINT NOT NULL,
+    last_error TEXT,
+    updated_at BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS jobs_due_idx ON jobs (status, run_at);
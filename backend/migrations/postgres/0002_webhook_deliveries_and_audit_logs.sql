@@ -0,0 +1,32 @@
+-- Each attempt to deliver a webhook event to a subscriber.
+CREATE TABLE webhook_deliveries (
+    id BIGSERIAL PRIMARY KEY,
+    delivery_id TEXT NOT NULL,
+    endpoint_id TEXT NOT NULL,
+    event TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    test BOOLEAN NOT NULL,
+    status_code SMALLINT,
+    delivered BOOLEAN NOT NULL,
+    error TEXT,
+    attempted_at BIGINT NOT NULL
+);
+
+CREATE INDEX webhook_deliveries_endpoint_idx ON webhook_deliveries (endpoint_id, attempted_at);
+CREATE INDEX webhook_deliveries_delivery_idx ON webhook_deliveries (delivery_id);
+
+-- Who changed what, through which route, and how it ended.
+CREATE TABLE audit_logs (
+    id BIGSERIAL PRIMARY KEY,
+    actor TEXT NOT NULL,
+    role TEXT NOT NULL,
+    action TEXT NOT NULL,
+    resource TEXT NOT NULL,
+    status_code SMALLINT NOT NULL,
+    detail TEXT,
+    tx_signature TEXT,
+    recorded_at BIGINT NOT NULL
+);
+
+CREATE INDEX audit_logs_resource_idx ON audit_logs (resource, recorded_at);
+CREATE INDEX audit_logs_actor_idx ON audit_logs (actor, recorded_at);
@@ -0,0 +1,7 @@
+-- Keys whose signatures the Chainlink webhook accepts.
+CREATE TABLE oracle_signers (
+    pubkey TEXT PRIMARY KEY,
+    label TEXT,
+    added_by TEXT NOT NULL,
+    added_at BIGINT NOT NULL
+);
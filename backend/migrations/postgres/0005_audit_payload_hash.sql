@@ -0,0 +1,15 @@
+-- Every mutation is audited with a hash of its request body; the log is
+-- append-only.
+ALTER TABLE audit_logs ADD COLUMN payload_hash TEXT;
+
+CREATE INDEX audit_logs_action_idx ON audit_logs (action, recorded_at);
+
+CREATE FUNCTION audit_logs_append_only() RETURNS trigger AS $$
+BEGIN
+    RAISE EXCEPTION 'audit_logs is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER audit_logs_append_only
+    BEFORE UPDATE OR DELETE ON audit_logs
+    FOR EACH ROW EXECUTE FUNCTION audit_logs_append_only();
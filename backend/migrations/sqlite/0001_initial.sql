@@ -0,0 +1,65 @@
+-- Tables the mirror created before migrations were tracked; IF NOT EXISTS
+-- adopts databases that already have them.
+
+CREATE TABLE IF NOT EXISTS assets (
+    asset_id TEXT PRIMARY KEY,
+    asset_pda TEXT NOT NULL,
+    asset_type TEXT NOT NULL,
+    valuation INTEGER NOT NULL,
+    owner TEXT NOT NULL,
+    risk_score INTEGER NOT NULL,
+    is_active INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS loans (
+    loan_pda TEXT PRIMARY KEY,
+    asset_id TEXT NOT NULL,
+    borrower TEXT NOT NULL,
+    principal INTEGER NOT NULL,
+    interest_rate INTEGER NOT NULL,
+    duration INTEGER NOT NULL,
+    status TEXT NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS loans_asset_id_idx ON loans (asset_id);
+
+CREATE TABLE IF NOT EXISTS risk_events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    asset_id TEXT NOT NULL,
+    risk_score INTEGER NOT NULL,
+    source TEXT NOT NULL,
+    confidence REAL,
+    tx_signature TEXT,
+    recorded_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS risk_events_asset_idx ON risk_events (asset_id, recorded_at);
+
+CREATE TABLE IF NOT EXISTS screenings (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    address TEXT NOT NULL,
+    context TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    provider TEXT NOT NULL,
+    matched INTEGER NOT NULL,
+    action TEXT NOT NULL,
+    detail TEXT,
+    screened_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS screenings_address_idx ON screenings (address, screened_at);
+
+CREATE TABLE IF NOT EXISTS jobs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    status TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    run_at INTEGER NOT NULL,
+    last_error TEXT,
+    updated_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS jobs_due_idx ON jobs (status, run_at);
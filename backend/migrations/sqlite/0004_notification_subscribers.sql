@@ -0,0 +1,14 @@
+-- Who the notifier tells about risk thresholds, maturities and liquidations.
+-- events is a comma-separated list.
+CREATE TABLE notification_subscribers (
+    id TEXT PRIMARY KEY,
+    channel TEXT NOT NULL,
+    target TEXT NOT NULL,
+    secret TEXT,
+    events TEXT NOT NULL,
+    risk_threshold INTEGER,
+    maturity_notice_secs INTEGER NOT NULL,
+    asset_id TEXT,
+    borrower TEXT,
+    created_at INTEGER NOT NULL
+);
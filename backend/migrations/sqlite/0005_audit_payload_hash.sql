@@ -0,0 +1,15 @@
+-- Every mutation is audited with a hash of its request body; the log is
+-- append-only.
+ALTER TABLE audit_logs ADD COLUMN payload_hash TEXT;
+
+CREATE INDEX audit_logs_action_idx ON audit_logs (action, recorded_at);
+
+CREATE TRIGGER audit_logs_no_update BEFORE UPDATE ON audit_logs
+BEGIN
+    SELECT RAISE(ABORT, 'audit_logs is append-only');
+END;
+
+CREATE TRIGGER audit_logs_no_delete BEFORE DELETE ON audit_logs
+BEGIN
+    SELECT RAISE(ABORT, 'audit_logs is append-only');
+END;
@@ -0,0 +1,11 @@
+-- Which tenant created each asset and loan, so list endpoints show a
+-- tenant's callers only their own.
+CREATE TABLE tenant_resources (
+    kind TEXT NOT NULL,
+    id TEXT NOT NULL,
+    tenant TEXT NOT NULL,
+    assigned_at INTEGER NOT NULL,
+    PRIMARY KEY (kind, id)
+);
+
+CREATE INDEX tenant_resources_tenant_idx ON tenant_resources (tenant, kind);
@@ -0,0 +1,71 @@
+use dashmap::DashMap;
+use solana_sdk::account::Account;
+use solana_sdk::message::VersionedMessage;
+use solana_sdk::pubkey::Pubkey;
+use std::env;
+use std::time::{Duration, Instant};
+
+const DEFAULT_TTL_SECS: u64 = 10;
+
+// ==================== Account Cache ====================
+/// Recently fetched program accounts, kept until they expire or something
+/// may have written them: a transaction sent by the service or a pubsub
+/// update for the address.
+pub struct AccountCache {
+    entries: DashMap<Pubkey, (Instant, Account)>,
+    ttl: Duration,
+}
+
+impl AccountCache {
+    /// `ACCOUNT_CACHE_TTL_SECS` (default 10) bounds how stale an account
+    /// missed by the pubsub stream can get; 0 disables caching.
+    pub fn from_env() -> Self {
+        let ttl = env::var("ACCOUNT_CACHE_TTL_SECS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_TTL_SECS);
+        Self {
+            entries: DashMap::new(),
+            ttl: Duration::from_secs(ttl),
+        }
+    }
+
+    pub fn get(&self, address: &Pubkey) -> Option<Account> {
+        let entry = self.entries.get(address)?;
+        let (fetched_at, account) = entry.value();
+        if fetched_at.elapsed() < self.ttl {
+            return Some(account.clone());
+        }
+        drop(entry);
+        self.entries.remove(address);
+        None
+    }
+
+    pub fn insert(&self, address: Pubkey, account: Account) {
+        if !self.ttl.is_zero() {
+            self.entries.insert(address, (Instant::now(), account));
+        }
+    }
+
+    pub fn invalidate(&self, address: &Pubkey) {
+        self.entries.remove(address);
+    }
+
+    /// Drop every account `message` may write. Addresses it loads from
+    /// lookup tables aren't resolved here, so those messages clear the cache.
+    pub fn invalidate_writes(&self, message: &VersionedMessage) {
+        if message.address_table_lookups().is_some_and(|lookups| !lookups.is_empty()) {
+            self.clear();
+            return;
+        }
+        for (index, address) in message.static_account_keys().iter().enumerate() {
+            if message.is_maybe_writable(index) {
+                self.invalidate(address);
+            }
+        }
+    }
+
+    pub fn clear(&self) {
+        self.entries.clear();
+    }
+}
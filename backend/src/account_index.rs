@@ -0,0 +1,207 @@
+use anyhow::Result;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::env;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use crate::solana_client::{AssetAccount, AssetFilter, AssetResponse, LoanAccount, SolanaService};
+
+const DEFAULT_REFRESH_SECS: u64 = 30;
+/// Risk bands of 20, matching the program's LTV tiers.
+const RISK_BUCKETS: usize = 5;
+
+fn risk_bucket(risk_score: u8) -> usize {
+    (risk_score.saturating_sub(1) as usize / 20).min(RISK_BUCKETS - 1)
+}
+
+// ==================== Snapshot ====================
+/// Every Asset and Loan account as of one pair of `getProgramAccounts`
+/// calls, with the lookups list and search endpoints need. Index entries
+/// are positions into `assets` and `loans`, kept in ascending order.
+struct Snapshot {
+    taken_at: i64,
+    /// Ordered by asset id.
+    assets: Vec<AssetAccount>,
+    asset_ids: HashMap<String, usize>,
+    owners: HashMap<Pubkey, Vec<usize>>,
+    risk_buckets: [Vec<usize>; RISK_BUCKETS],
+    loans: Vec<(Pubkey, LoanAccount)>,
+    borrowers: HashMap<Pubkey, Vec<usize>>,
+}
+
+impl Snapshot {
+    fn build(mut assets: Vec<AssetAccount>, loans: Vec<(Pubkey, LoanAccount)>, taken_at: i64) -> Self {
+        assets.sort_by(|a, b| a.asset_id.cmp(&b.asset_id));
+
+        let mut asset_ids = HashMap::with_capacity(assets.len());
+        let mut owners: HashMap<Pubkey, Vec<usize>> = HashMap::new();
+        let mut risk_buckets: [Vec<usize>; RISK_BUCKETS] = Default::default();
+        for (position, asset) in assets.iter().enumerate() {
+            asset_ids.insert(asset.asset_id.clone(), position);
+            owners.entry(asset.owner).or_default().push(position);
+            risk_buckets[risk_bucket(asset.risk_score)].push(position);
+        }
+
+        let mut borrowers: HashMap<Pubkey, Vec<usize>> = HashMap::new();
+        for (position, (_, loan)) in loans.iter().enumerate() {
+            borrowers.entry(loan.borrower).or_default().push(position);
+        }
+
+        Self { taken_at, assets, asset_ids, owners, risk_buckets, loans, borrowers }
+    }
+
+    /// Positions of the assets that could match `filter`, from its most
+    /// selective indexed field.
+    fn candidates(&self, filter: &AssetFilter) -> Vec<usize> {
+        if let Some(owner) = &filter.owner {
+            return self.owners.get(owner).cloned().unwrap_or_default();
+        }
+        if filter.min_risk.is_some() || filter.max_risk.is_some() {
+            let first = risk_bucket(filter.min_risk.unwrap_or(0));
+            let last = risk_bucket(filter.max_risk.unwrap_or(u8::MAX));
+            let mut positions: Vec<usize> = self.risk_buckets[first..=last].concat();
+            positions.sort_unstable();
+            return positions;
+        }
+        (0..self.assets.len()).collect()
+    }
+}
+
+// ==================== Account Index ====================
+/// In-memory index of the program's Asset and Loan accounts, rebuilt from
+/// `getProgramAccounts` every `ACCOUNT_INDEX_REFRESH_SECS` (default 30; 0
+/// disables it). A snapshot older than two refresh intervals is treated as
+/// missing, so callers fall back to RPC while refreshes are failing.
+pub struct AccountIndex {
+    refresh: Duration,
+    snapshot: RwLock<Option<Arc<Snapshot>>>,
+}
+
+impl AccountIndex {
+    pub fn from_env() -> Self {
+        let refresh = env::var("ACCOUNT_INDEX_REFRESH_SECS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_REFRESH_SECS);
+        Self {
+            refresh: Duration::from_secs(refresh),
+            snapshot: RwLock::new(None),
+        }
+    }
+
+    fn current(&self) -> Option<Arc<Snapshot>> {
+        let snapshot = self.snapshot.read().unwrap().clone()?;
+        let age = chrono::Utc::now().timestamp() - snapshot.taken_at;
+        (age <= 2 * self.refresh.as_secs() as i64).then_some(snapshot)
+    }
+
+    pub async fn refresh(&self, solana: &SolanaService) -> Result<()> {
+        let taken_at = chrono::Utc::now().timestamp();
+        let assets = solana.fetch_assets(None).await?;
+        let loans = solana.list_loans().await?;
+        let snapshot = Snapshot::build(assets, loans, taken_at);
+        tracing::debug!("🗂️ Indexed {} assets and {} loans", snapshot.assets.len(), snapshot.loans.len());
+        *self.snapshot.write().unwrap() = Some(Arc::new(snapshot));
+        Ok(())
+    }
+
+    /// When the current snapshot was taken, if there is one.
+    pub fn taken_at(&self) -> Option<i64> {
+        self.current().map(|snapshot| snapshot.taken_at)
+    }
+
+    pub fn asset(&self, asset_id: &str) -> Option<AssetResponse> {
+        let snapshot = self.current()?;
+        let position = *snapshot.asset_ids.get(asset_id)?;
+        Some(AssetResponse::from(snapshot.assets[position].clone()))
+    }
+
+    /// Assets matching `filter`, ordered by asset id; None without a
+    /// current snapshot.
+    pub fn assets(&self, filter: &AssetFilter) -> Option<Vec<AssetResponse>> {
+        let snapshot = self.current()?;
+        Some(snapshot.candidates(filter)
+            .into_iter()
+            .map(|position| &snapshot.assets[position])
+            .filter(|asset| filter.matches(asset))
+            .cloned()
+            .map(AssetResponse::from)
+            .collect())
+    }
+
+    /// Loans, all of them or only `borrower`'s; None without a current snapshot.
+    pub fn loans(&self, borrower: Option<&Pubkey>) -> Option<Vec<(Pubkey, LoanAccount)>> {
+        let snapshot = self.current()?;
+        Some(match borrower {
+            Some(borrower) => snapshot.borrowers.get(borrower)
+                .map(|positions| positions.iter().map(|&position| snapshot.loans[position].clone()).collect())
+                .unwrap_or_default(),
+            None => snapshot.loans.clone(),
+        })
+    }
+}
+
+/// Rebuild the index every refresh interval, unless it's disabled.
+pub fn spawn(index: Arc<AccountIndex>, solana: Arc<SolanaService>) {
+    if index.refresh.is_zero() {
+        tracing::info!("⏭️ Account index disabled");
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(index.refresh);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = index.refresh(&solana).await {
+                tracing::warn!("⚠️ Account index refresh failed: {}", e);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn asset(asset_id: &str, owner: Pubkey, risk_score: u8) -> AssetAccount {
+        AssetAccount {
+            asset_id: asset_id.to_string(),
+            asset_type: "real_estate".to_string(),
+            valuation: 1_000_000,
+            metadata_uri: String::new(),
+            owner,
+            is_active: true,
+            risk_score,
+            bump: 255,
+            active_loans: 0,
+            delegate: Pubkey::default(),
+            valuation_refresh_requested_at: 0,
+            last_risk_report: 0,
+            risk_frozen_until: 0,
+            breaker_confirmers: Vec::new(),
+            last_risk_update: 0,
+            jurisdiction: [0; 2],
+            portfolio: Pubkey::default(),
+        }
+    }
+
+    #[test]
+    fn candidates_narrow_by_owner_and_risk_bucket() {
+        let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let snapshot = Snapshot::build(vec![
+            asset("c", alice, 90),
+            asset("a", alice, 20),
+            asset("b", bob, 21),
+            asset("d", bob, 100),
+        ], Vec::new(), 0);
+
+        assert_eq!(snapshot.asset_ids["a"], 0);
+        let owned = AssetFilter { owner: Some(bob), ..Default::default() };
+        assert_eq!(snapshot.candidates(&owned), vec![1, 3]);
+        let risky = AssetFilter { min_risk: Some(81), ..Default::default() };
+        assert_eq!(snapshot.candidates(&risky), vec![2, 3]);
+        let low = AssetFilter { max_risk: Some(40), ..Default::default() };
+        assert_eq!(snapshot.candidates(&low), vec![0, 1]);
+    }
+}
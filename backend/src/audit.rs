@@ -0,0 +1,119 @@
+use axum::{
+    body::{self, Body},
+    extract::{MatchedPath, OriginalUri, Request, State},
+    http::{header, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use crate::auth::Caller;
+use crate::routes::AppState;
+use crate::storage::AuditRecord;
+use crate::visibility::Role;
+
+/// POST routes that only compute, and change nothing.
+const READ_ONLY: &[&str] = &["/loans/preview", "/capacity/simulate"];
+
+// ==================== Mutation Audit ====================
+/// Record every state-changing call in the network's audit log: caller,
+/// route, a hash of the body, the signature it sent and how it ended.
+/// Runs inside authorization, so refused calls are recorded too.
+pub async fn record_mutations(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let path = request.uri().path().to_string();
+    if matches!(*request.method(), Method::GET | Method::HEAD | Method::OPTIONS) || READ_ONLY.contains(&path.as_str()) {
+        return next.run(request).await;
+    }
+
+    let caller = request.extensions().get::<Caller>();
+    let actor = caller.map_or("anonymous", |caller| caller.subject.as_str());
+    let role = caller.map_or(Role::Public, |caller| caller.role);
+    let mut entry = AuditRecord::now(actor, role.name(), &action(&request), path);
+
+    let (parts, body) = request.into_parts();
+    let payload = match body::to_bytes(body, usize::MAX).await {
+        Ok(payload) => payload,
+        // Past the body limit, or the client went away mid-body
+        Err(e) => {
+            entry.status_code = StatusCode::PAYLOAD_TOO_LARGE.as_u16();
+            entry.detail = Some(json!({ "message": e.to_string() }));
+            record(&state, &entry).await;
+            return StatusCode::PAYLOAD_TOO_LARGE.into_response();
+        }
+    };
+    if !payload.is_empty() {
+        entry.payload_hash = Some(hex::encode(Sha256::digest(&payload)));
+    }
+
+    let response = next.run(Request::from_parts(parts, Body::from(payload))).await;
+    entry.status_code = response.status().as_u16();
+    let is_json = response.headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|value| value.as_bytes().starts_with(b"application/json"));
+    if !is_json {
+        record(&state, &entry).await;
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let Ok(bytes) = body::to_bytes(body, usize::MAX).await else {
+        entry.status_code = StatusCode::INTERNAL_SERVER_ERROR.as_u16();
+        record(&state, &entry).await;
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    if let Ok(value) = serde_json::from_slice::<Value>(&bytes) {
+        outcome(&mut entry, &value);
+    }
+    record(&state, &entry).await;
+    Response::from_parts(parts, Body::from(bytes))
+}
+
+/// `METHOD /route/:param`, without the `/<network>` prefix, so one route's
+/// entries share an action however the network was chosen.
+fn action(request: &Request) -> String {
+    let path = request.uri().path();
+    let route = request.extensions().get::<MatchedPath>().map_or(path, |route| route.as_str());
+    let prefix = request.extensions()
+        .get::<OriginalUri>()
+        .and_then(|original| original.path().strip_suffix(path))
+        .unwrap_or("");
+    format!("{} {}", request.method(), route.strip_prefix(prefix).unwrap_or(route))
+}
+
+/// The signature a successful call sent, or the error a failed one got.
+fn outcome(entry: &mut AuditRecord, response: &Value) {
+    if entry.succeeded() {
+        entry.transaction = ["transaction", "signature"]
+            .iter()
+            .find_map(|field| response[field].as_str())
+            .map(str::to_string);
+    } else {
+        entry.detail = Some(json!({ "code": response["code"], "message": response["message"] }));
+    }
+}
+
+async fn record(state: &AppState, entry: &AuditRecord) {
+    if let Err(e) = state.storage.audit.record_audit(entry).await {
+        tracing::error!("❌ Failed to record audit entry for {} {}: {}", entry.action, entry.resource, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_signature_or_the_error() {
+        let mut entry = AuditRecord::now("key:abc", "admin", "POST /loans/:loan_pda/repay", "/loans/x/repay".to_string());
+        entry.status_code = 200;
+        outcome(&mut entry, &json!({ "success": true, "transaction": "5sig", "unsigned_transaction": null }));
+        assert_eq!(entry.transaction.as_deref(), Some("5sig"));
+
+        entry.status_code = 422;
+        entry.transaction = None;
+        outcome(&mut entry, &json!({ "code": "program_rejected", "message": "LoanInactive", "retryable": false }));
+        assert!(entry.transaction.is_none());
+        assert_eq!(entry.detail, Some(json!({ "code": "program_rejected", "message": "LoanInactive" })));
+    }
+}
@@ -0,0 +1,345 @@
+use anyhow::{anyhow, Result};
+use axum::{
+    extract::{Request, State},
+    http::{header, Method},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::env;
+
+use crate::error::ApiError;
+use crate::routes::AppState;
+use crate::tenants::TenantRegistry;
+use crate::visibility::{Role, RoleRegistry};
+
+/// Admin-only routes, by leading path segment; everything else is a read
+/// (GET) or a write.
+const ADMIN_PREFIXES: &[&str] = &["/admin", "/compliance", "/screening", "/nonces", "/lookup-tables", "/webhooks", "/idl", "/devnet"];
+
+/// Browsers can't set headers on a WebSocket upgrade, so sockets may
+/// authenticate in their first message instead.
+pub const SOCKET_PATH: &str = "/ws";
+
+fn has_prefix(path: &str, prefix: &str) -> bool {
+    path == prefix || path.strip_prefix(prefix).is_some_and(|rest| rest.starts_with('/'))
+}
+
+// ==================== Route Groups ====================
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteGroup {
+    Reads,
+    Writes,
+    Admin,
+}
+
+impl RouteGroup {
+    fn of(method: &Method, path: &str) -> Self {
+        if ADMIN_PREFIXES.iter().any(|prefix| has_prefix(path, prefix)) {
+            RouteGroup::Admin
+        } else if method == Method::GET {
+            RouteGroup::Reads
+        } else {
+            RouteGroup::Writes
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            RouteGroup::Reads => "READS",
+            RouteGroup::Writes => "WRITES",
+            RouteGroup::Admin => "ADMIN",
+        }
+    }
+}
+
+/// Credentials a route group accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthMode {
+    /// Open; credentials are still checked when sent.
+    None,
+    ApiKey,
+    Jwt,
+    Any,
+}
+
+impl AuthMode {
+    fn parse(value: &str) -> Result<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "none" => Ok(AuthMode::None),
+            "api_key" => Ok(AuthMode::ApiKey),
+            "jwt" => Ok(AuthMode::Jwt),
+            "any" => Ok(AuthMode::Any),
+            other => Err(anyhow!("Unknown auth mode: {}", other)),
+        }
+    }
+
+    fn accepts(self, method: AuthMethod) -> bool {
+        match self {
+            AuthMode::None | AuthMode::Any => true,
+            AuthMode::ApiKey => method == AuthMethod::ApiKey,
+            AuthMode::Jwt => method == AuthMethod::Jwt,
+        }
+    }
+}
+
+// ==================== Callers ====================
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthMethod {
+    ApiKey,
+    Jwt,
+}
+
+/// Who made an authenticated request, left in the request's extensions for
+/// handlers and the `Role` extractor.
+#[derive(Debug, Clone)]
+pub struct Caller {
+    /// The JWT subject, or `key:` and a fingerprint of the API key.
+    pub subject: String,
+    pub method: AuthMethod,
+    pub role: Role,
+    /// The tenant whose key or token this is, if any.
+    pub tenant: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Claims {
+    sub: String,
+    role: Option<String>,
+    tenant: Option<String>,
+}
+
+// ==================== Authenticator ====================
+/// Checks API keys (`X-Api-Key`, from `API_KEY_ROLES`) and bearer JWTs on
+/// every route but the allowlisted ones.
+///
+/// `AUTH_READS`, `AUTH_WRITES` and `AUTH_ADMIN` set what each route group
+/// accepts: none, api_key, jwt or any (defaults none, any and api_key).
+/// JWTs are verified with `JWT_SECRET` (HS256) or the PEM public key at
+/// `JWT_PUBLIC_KEY_PATH` (RS256), against `JWT_ISSUER` and `JWT_AUDIENCE`
+/// when set; a token's `role` claim defaults to borrower, and its optional
+/// `tenant` claim must name a configured tenant.
+pub struct Authenticator {
+    reads: AuthMode,
+    writes: AuthMode,
+    admin: AuthMode,
+    jwt: Option<(DecodingKey, Validation)>,
+    /// `AUTH_ALLOWLIST`, default the three health routes.
+    allowlist: Vec<String>,
+}
+
+impl Authenticator {
+    pub fn from_env() -> Result<Self> {
+        let mode = |group: RouteGroup, default: AuthMode| -> Result<AuthMode> {
+            env::var(format!("AUTH_{}", group.name()))
+                .ok()
+                .map(|value| AuthMode::parse(&value))
+                .transpose()
+                .map(|mode| mode.unwrap_or(default))
+        };
+
+        let key = match (env::var("JWT_SECRET").ok(), env::var("JWT_PUBLIC_KEY_PATH").ok()) {
+            (Some(_), Some(_)) => return Err(anyhow!("Set JWT_SECRET or JWT_PUBLIC_KEY_PATH, not both")),
+            (Some(secret), None) => Some((DecodingKey::from_secret(secret.as_bytes()), Algorithm::HS256)),
+            (None, Some(path)) => {
+                let pem = std::fs::read(&path)
+                    .map_err(|e| anyhow!("Failed to read JWT public key {}: {}", path, e))?;
+                let key = DecodingKey::from_rsa_pem(&pem)
+                    .map_err(|e| anyhow!("Invalid JWT public key {}: {}", path, e))?;
+                Some((key, Algorithm::RS256))
+            }
+            (None, None) => None,
+        };
+        let jwt = key.map(|(key, algorithm)| {
+            let mut validation = Validation::new(algorithm);
+            if let Ok(issuer) = env::var("JWT_ISSUER") {
+                validation.set_issuer(&[issuer]);
+            }
+            match env::var("JWT_AUDIENCE") {
+                Ok(audience) => validation.set_audience(&[audience]),
+                Err(_) => validation.validate_aud = false,
+            }
+            (key, validation)
+        });
+
+        let allowlist = env::var("AUTH_ALLOWLIST")
+            .unwrap_or_else(|_| "/health,/health/live,/health/ready".to_string())
+            .split(',')
+            .map(|path| path.trim().to_string())
+            .filter(|path| !path.is_empty())
+            .collect();
+
+        let authenticator = Self {
+            reads: mode(RouteGroup::Reads, AuthMode::None)?,
+            writes: mode(RouteGroup::Writes, AuthMode::Any)?,
+            admin: mode(RouteGroup::Admin, AuthMode::ApiKey)?,
+            jwt,
+            allowlist,
+        };
+        let needs_jwt = [authenticator.reads, authenticator.writes, authenticator.admin].contains(&AuthMode::Jwt);
+        if needs_jwt && authenticator.jwt.is_none() {
+            return Err(anyhow!("A route group requires JWTs but neither JWT_SECRET nor JWT_PUBLIC_KEY_PATH is set"));
+        }
+        Ok(authenticator)
+    }
+
+    fn mode(&self, group: RouteGroup) -> AuthMode {
+        match group {
+            RouteGroup::Reads => self.reads,
+            RouteGroup::Writes => self.writes,
+            RouteGroup::Admin => self.admin,
+        }
+    }
+
+    fn verify_jwt(&self, tenants: &TenantRegistry, token: &str) -> Result<Caller> {
+        let (key, validation) = self.jwt.as_ref().ok_or_else(|| anyhow!("Bearer tokens are not accepted"))?;
+        let claims = jsonwebtoken::decode::<Claims>(token, key, validation)
+            .map_err(|e| anyhow!("Invalid token: {}", e))?
+            .claims;
+        let role = match claims.role.as_deref() {
+            Some(role) => Role::parse(role).ok_or_else(|| anyhow!("Invalid token: unknown role {}", role))?,
+            None => Role::Borrower,
+        };
+        if let Some(tenant) = &claims.tenant {
+            if tenants.get(tenant).is_none() {
+                return Err(anyhow!("Invalid token: unknown tenant {}", tenant));
+            }
+            if role == Role::Admin {
+                return Err(anyhow!("Invalid token: tenants can't hold the admin role"));
+            }
+        }
+        Ok(Caller { subject: claims.sub, method: AuthMethod::Jwt, role, tenant: claims.tenant })
+    }
+
+    /// The caller behind an API key or bearer token, if either is sent.
+    /// Tenant keys are looked up before `API_KEY_ROLES`.
+    pub fn identify(
+        &self,
+        roles: &RoleRegistry,
+        tenants: &TenantRegistry,
+        api_key: Option<&str>,
+        bearer: Option<&str>,
+    ) -> Result<Option<Caller>> {
+        match (api_key, bearer) {
+            (Some(_), Some(_)) => Err(anyhow!("Send an API key or a bearer token, not both")),
+            (Some(api_key), None) => {
+                let (role, tenant) = match tenants.lookup(api_key) {
+                    Some((tenant, role)) => (role, Some(tenant.to_string())),
+                    None => (roles.lookup(api_key).ok_or_else(|| anyhow!("Unknown API key"))?, None),
+                };
+                Ok(Some(Caller { subject: key_fingerprint(api_key), method: AuthMethod::ApiKey, role, tenant }))
+            }
+            (None, Some(token)) => self.verify_jwt(tenants, token.trim()).map(Some),
+            (None, None) => Ok(None),
+        }
+    }
+
+    /// Check `caller` has the credentials `group` requires.
+    pub fn admit(&self, group: RouteGroup, caller: Option<&Caller>) -> Result<()> {
+        let mode = self.mode(group);
+        match caller {
+            Some(caller) if !mode.accepts(caller.method) => {
+                let expected = if mode == AuthMode::ApiKey { "an API key" } else { "a bearer token" };
+                Err(anyhow!("This route requires {}", expected))
+            }
+            None if mode != AuthMode::None => Err(anyhow!("Authentication required")),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn key_fingerprint(api_key: &str) -> String {
+    format!("key:{}", &hex::encode(Sha256::digest(api_key.as_bytes()))[..12])
+}
+
+fn unauthorized(message: impl Into<String>) -> Response {
+    ApiError::Unauthenticated(message.into()).into_response()
+}
+
+/// Authenticate the request for its route group. Credentials that are sent
+/// must be valid even where the group doesn't require any.
+pub async fn authenticate(State(state): State<AppState>, mut request: Request, next: Next) -> Response {
+    let path = request.uri().path();
+    if state.auth.allowlist.iter().any(|allowed| allowed == path) {
+        return next.run(request).await;
+    }
+    let group = RouteGroup::of(request.method(), path);
+    let deferred = path == SOCKET_PATH;
+
+    let headers = request.headers();
+    let api_key = headers.get("x-api-key").and_then(|value| value.to_str().ok());
+    let bearer = headers.get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    let caller = match state.auth.identify(&state.roles, &state.tenants, api_key, bearer) {
+        Ok(caller) => caller,
+        Err(e) => return unauthorized(e.to_string()),
+    };
+    // A socket without credentials gets to send them once connected
+    if caller.is_some() || !deferred {
+        if let Err(e) = state.auth.admit(group, caller.as_ref()) {
+            return unauthorized(e.to_string());
+        }
+    }
+    if let Some(caller) = caller {
+        request.extensions_mut().insert(caller);
+    }
+    next.run(request).await
+}
+
+// ==================== Authorization ====================
+/// The role a route is reserved for, if any: risk writes for oracles,
+/// admin routes for admins.
+fn required_role(method: &Method, path: &str) -> Option<Role> {
+    let risk_write = method == Method::POST && (
+        has_prefix(path, "/risk")
+            || has_prefix(path, "/chainlink")
+            || path.strip_prefix("/assets/").is_some_and(|rest| rest.ends_with("/risk") && rest.matches('/').count() == 1)
+    );
+    if risk_write {
+        Some(Role::Oracle)
+    } else if RouteGroup::of(method, path) == RouteGroup::Admin {
+        Some(Role::Admin)
+    } else {
+        None
+    }
+}
+
+/// Refuse requests to a reserved route from any other role. Runs after
+/// `authenticate`; anonymous callers are public.
+pub async fn authorize(request: Request, next: Next) -> Response {
+    if let Some(required) = required_role(request.method(), request.uri().path()) {
+        let role = request.extensions().get::<Caller>().map_or(Role::Public, |caller| caller.role);
+        if role != required {
+            return ApiError::Forbidden(format!("This route is restricted to the {} role", required.name())).into_response();
+        }
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn routes_fall_into_groups_by_prefix_and_method() {
+        assert_eq!(RouteGroup::of(&Method::GET, "/assets/asset-001"), RouteGroup::Reads);
+        assert_eq!(RouteGroup::of(&Method::POST, "/assets/asset-001/risk"), RouteGroup::Writes);
+        assert_eq!(RouteGroup::of(&Method::GET, "/admin/wallet"), RouteGroup::Admin);
+        assert_eq!(RouteGroup::of(&Method::DELETE, "/nonces/abc"), RouteGroup::Admin);
+        assert_eq!(RouteGroup::of(&Method::GET, "/idl/status"), RouteGroup::Admin);
+        // Only whole segments match
+        assert_eq!(RouteGroup::of(&Method::GET, "/idlers"), RouteGroup::Reads);
+    }
+
+    #[test]
+    fn risk_writes_are_reserved_for_oracles() {
+        assert_eq!(required_role(&Method::POST, "/assets/asset-001/risk"), Some(Role::Oracle));
+        assert_eq!(required_role(&Method::POST, "/risk/reports/7/apply"), Some(Role::Oracle));
+        assert_eq!(required_role(&Method::GET, "/assets/asset-001/risk/latest"), None);
+        assert_eq!(required_role(&Method::POST, "/assets/asset-001/transfer"), None);
+        assert_eq!(required_role(&Method::POST, "/admin/payer/rotate"), Some(Role::Admin));
+    }
+}
@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+
+const SECONDS_PER_DAY: f64 = 24.0 * 60.0 * 60.0;
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+/// Fallback when the cluster cannot be asked for the current base fee.
+pub const DEFAULT_LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+
+/// RPC calls made by one risk update: blockhash, send, and the status polls
+/// issued until it reaches its write commitment (about four at `confirmed`).
+pub const DEFAULT_RPC_CALLS_PER_UPDATE: u64 = 6;
+
+/// Compute units requested when a priority fee is attached.
+pub const DEFAULT_COMPUTE_UNIT_LIMIT: u64 = 200_000;
+
+// ==================== Simulation Inputs ====================
+#[derive(Debug, Clone, Deserialize)]
+pub struct CapacityScenario {
+    pub asset_count: u64,
+    /// Seconds between risk refreshes of one asset.
+    pub update_interval_secs: u64,
+    /// Share of refreshes that run a Chainlink workflow, 0.0 to 1.0.
+    #[serde(default = "default_chainlink_share")]
+    pub chainlink_share: f64,
+    /// Base fee; the cluster's current fee is used when omitted.
+    pub lamports_per_signature: Option<u64>,
+    /// Priority fee; the median recent fee for the program is used when omitted.
+    pub priority_fee_micro_lamports: Option<u64>,
+    #[serde(default = "default_compute_unit_limit")]
+    pub compute_unit_limit: u64,
+    #[serde(default = "default_rpc_calls_per_update")]
+    pub rpc_calls_per_update: u64,
+    /// Asset and loan reads served over RPC per day, on top of the refreshes.
+    #[serde(default)]
+    pub rpc_reads_per_day: u64,
+}
+
+fn default_chainlink_share() -> f64 {
+    1.0
+}
+
+fn default_compute_unit_limit() -> u64 {
+    DEFAULT_COMPUTE_UNIT_LIMIT
+}
+
+fn default_rpc_calls_per_update() -> u64 {
+    DEFAULT_RPC_CALLS_PER_UPDATE
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct FeeConditions {
+    pub lamports_per_signature: u64,
+    pub priority_fee_micro_lamports: u64,
+    /// Where the fees came from: request, rpc, or default.
+    pub source: &'static str,
+}
+
+// ==================== Projection ====================
+#[derive(Debug, Clone, Serialize)]
+pub struct CapacityProjection {
+    pub fees: FeeConditions,
+    pub transactions_per_day: u64,
+    pub lamports_per_transaction: u64,
+    pub lamports_per_day: u64,
+    pub sol_per_day: f64,
+    pub sol_per_30_days: f64,
+    pub chainlink_executions_per_day: u64,
+    pub rpc_requests_per_day: u64,
+    /// Refresh transactions per second the payer must sustain on average.
+    pub average_tps: f64,
+}
+
+/// Project the daily cost of refreshing every asset's risk at the given cadence.
+/// Each refresh is one single-signature `update_risk_score` transaction.
+pub fn project(scenario: &CapacityScenario, fees: FeeConditions) -> CapacityProjection {
+    let updates_per_asset = SECONDS_PER_DAY / scenario.update_interval_secs.max(1) as f64;
+    let transactions_per_day = (scenario.asset_count as f64 * updates_per_asset).ceil() as u64;
+
+    let priority_lamports = scenario.compute_unit_limit
+        .saturating_mul(fees.priority_fee_micro_lamports)
+        / 1_000_000;
+    let lamports_per_transaction = fees.lamports_per_signature.saturating_add(priority_lamports);
+    let lamports_per_day = transactions_per_day.saturating_mul(lamports_per_transaction);
+
+    let chainlink_share = scenario.chainlink_share.clamp(0.0, 1.0);
+
+    CapacityProjection {
+        fees,
+        transactions_per_day,
+        lamports_per_transaction,
+        lamports_per_day,
+        sol_per_day: lamports_per_day as f64 / LAMPORTS_PER_SOL,
+        sol_per_30_days: lamports_per_day as f64 * 30.0 / LAMPORTS_PER_SOL,
+        chainlink_executions_per_day: (transactions_per_day as f64 * chainlink_share).round() as u64,
+        rpc_requests_per_day: transactions_per_day
+            .saturating_mul(scenario.rpc_calls_per_update)
+            .saturating_add(scenario.rpc_reads_per_day),
+        average_tps: transactions_per_day as f64 / SECONDS_PER_DAY,
+    }
+}
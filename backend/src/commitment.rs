@@ -0,0 +1,82 @@
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use solana_sdk::commitment_config::CommitmentConfig;
+use std::collections::HashMap;
+use std::env;
+use std::str::FromStr;
+use utoipa::ToSchema;
+
+// ==================== Commitment Levels ====================
+/// How settled the state a caller reads, or waits for, must be.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Commitment {
+    Processed,
+    Confirmed,
+    Finalized,
+}
+
+impl Commitment {
+    pub fn config(self) -> CommitmentConfig {
+        match self {
+            Commitment::Processed => CommitmentConfig::processed(),
+            Commitment::Confirmed => CommitmentConfig::confirmed(),
+            Commitment::Finalized => CommitmentConfig::finalized(),
+        }
+    }
+}
+
+impl FromStr for Commitment {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.trim() {
+            "processed" => Ok(Commitment::Processed),
+            "confirmed" => Ok(Commitment::Confirmed),
+            "finalized" => Ok(Commitment::Finalized),
+            other => Err(anyhow!("Unknown commitment level: {}", other)),
+        }
+    }
+}
+
+/// Level a sent transaction is confirmed at before the service answers.
+#[derive(Debug, Clone)]
+pub struct WriteCommitments {
+    default: Commitment,
+    overrides: HashMap<String, Commitment>,
+}
+
+impl WriteCommitments {
+    /// `WRITE_COMMITMENT` (default confirmed) applies to every operation but
+    /// those listed in `WRITE_COMMITMENT_OVERRIDES`, given as
+    /// `approve_loan=finalized,liquidate_loan=processed`.
+    pub fn from_env() -> Result<Self> {
+        let default = env::var("WRITE_COMMITMENT")
+            .ok()
+            .map(|level| level.parse())
+            .transpose()?
+            .unwrap_or(Commitment::Confirmed);
+        let overrides = env::var("WRITE_COMMITMENT_OVERRIDES")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                let (operation, level) = entry.split_once('=')
+                    .ok_or_else(|| anyhow!("Invalid WRITE_COMMITMENT_OVERRIDES entry: {}", entry))?;
+                Ok((operation.trim().to_string(), level.parse()?))
+            })
+            .collect::<Result<HashMap<_, _>>>()?;
+        Ok(Self { default, overrides })
+    }
+
+    /// The strictest level asked of any of `operations`; a transaction
+    /// carrying several is only as settled as its most demanding one needs.
+    pub fn for_operations<'a>(&self, operations: impl IntoIterator<Item = &'a str>) -> Commitment {
+        operations
+            .into_iter()
+            .map(|operation| self.overrides.get(operation).copied().unwrap_or(self.default))
+            .max()
+            .unwrap_or(self.default)
+    }
+}
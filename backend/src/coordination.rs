@@ -0,0 +1,443 @@
+use anyhow::{anyhow, Result};
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    http::{header, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use dashmap::DashMap;
+use redis::aio::ConnectionManager;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::env;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::auth::Caller;
+use crate::error::ApiError;
+use crate::live_events::EventHub;
+use crate::network::Network;
+use crate::routes::AppState;
+use crate::visibility::Role;
+
+const DEFAULT_LOCK_TTL_SECS: u64 = 60;
+const DEFAULT_IDEMPOTENCY_TTL_SECS: u64 = 24 * 60 * 60;
+const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+/// Bodies buffered to fingerprint a request or keep a response; axum's own
+/// default request limit.
+const MAX_BUFFERED_BODY: usize = 2 * 1024 * 1024;
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+pub const REPLAYED_HEADER: &str = "idempotent-replayed";
+
+/// Compare-and-delete, so a lock or reservation is only released by its holder.
+const RELEASE_SCRIPT: &str = r#"
+if redis.call("GET", KEYS[1]) == ARGV[1] then
+    return redis.call("DEL", KEYS[1])
+end
+return 0
+"#;
+
+// ==================== Shared Store ====================
+/// Redis when `REDIS_URL` is set, shared by every replica; otherwise a map
+/// in this process, which only coordinates a single replica.
+#[derive(Clone)]
+enum Store {
+    Redis(ConnectionManager),
+    Local(Arc<DashMap<String, (Instant, String)>>),
+}
+
+impl Store {
+    fn live(entry: &(Instant, String)) -> bool {
+        entry.0 > Instant::now()
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<String>> {
+        match self {
+            Store::Redis(conn) => Ok(redis::cmd("GET").arg(key).query_async(&mut conn.clone()).await?),
+            Store::Local(map) => Ok(map.get(key).filter(|entry| Self::live(entry)).map(|entry| entry.1.clone())),
+        }
+    }
+
+    async fn set(&self, key: &str, value: &str, ttl: Duration) -> Result<()> {
+        match self {
+            Store::Redis(conn) => {
+                redis::cmd("SET").arg(key).arg(value).arg("PX").arg(ttl.as_millis() as u64)
+                    .query_async::<_, ()>(&mut conn.clone()).await?;
+            }
+            Store::Local(map) => {
+                map.insert(key.to_string(), (Instant::now() + ttl, value.to_string()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Set `key` unless it holds an unexpired value; whether it was set.
+    async fn set_new(&self, key: &str, value: &str, ttl: Duration) -> Result<bool> {
+        match self {
+            Store::Redis(conn) => {
+                let set: Option<String> = redis::cmd("SET").arg(key).arg(value).arg("NX").arg("PX").arg(ttl.as_millis() as u64)
+                    .query_async(&mut conn.clone()).await?;
+                Ok(set.is_some())
+            }
+            Store::Local(map) => {
+                let mut entry = map.entry(key.to_string()).or_insert((Instant::now(), String::new()));
+                if Self::live(&entry) {
+                    return Ok(false);
+                }
+                *entry = (Instant::now() + ttl, value.to_string());
+                Ok(true)
+            }
+        }
+    }
+
+    /// Delete `key` if it still holds `value`.
+    async fn release(&self, key: &str, value: &str) -> Result<()> {
+        match self {
+            Store::Redis(conn) => {
+                redis::Script::new(RELEASE_SCRIPT).key(key).arg(value)
+                    .invoke_async::<_, i64>(&mut conn.clone()).await?;
+            }
+            Store::Local(map) => {
+                map.remove_if(key, |_, entry| entry.1 == value);
+            }
+        }
+        Ok(())
+    }
+
+    async fn increment(&self, key: &str) -> Result<()> {
+        match self {
+            Store::Redis(conn) => {
+                redis::cmd("INCR").arg(key).query_async::<_, i64>(&mut conn.clone()).await?;
+            }
+            Store::Local(map) => {
+                let mut entry = map.entry(key.to_string()).or_insert((far_future(), "0".to_string()));
+                let next = entry.1.parse::<i64>().unwrap_or(0) + 1;
+                entry.1 = next.to_string();
+            }
+        }
+        Ok(())
+    }
+
+    fn prune(&self) {
+        if let Store::Local(map) = self {
+            map.retain(|_, entry| Self::live(entry));
+        }
+    }
+}
+
+fn far_future() -> Instant {
+    Instant::now() + Duration::from_secs(100 * 365 * 24 * 60 * 60)
+}
+
+/// Connect to `REDIS_URL`, when set; every network shares the connection.
+pub async fn connect_redis() -> Result<Option<ConnectionManager>> {
+    let Ok(url) = env::var("REDIS_URL") else {
+        return Ok(None);
+    };
+    let client = redis::Client::open(url.as_str()).map_err(|e| anyhow!("Invalid REDIS_URL: {}", e))?;
+    let conn = ConnectionManager::new(client).await.map_err(|e| anyhow!("Failed to connect to Redis: {}", e))?;
+    tracing::info!("🧷 Connected to Redis");
+    Ok(Some(conn))
+}
+
+// ==================== Coordination ====================
+/// State replicas of the backend share: locks around transactions that
+/// must not be sent twice, idempotency keys on writes, and cached reads.
+/// Keys are prefixed per network.
+///
+/// `LOCK_TTL_SECS` (default 60) bounds how long a crashed holder keeps a
+/// lock; `IDEMPOTENCY_TTL_SECS` (default a day) how long a key's response
+/// is replayed; `RESPONSE_CACHE_TTL_SECS` (default 0, off) how long a GET
+/// response is served again. Any write through the backend and any live
+/// event the network's subscription sees invalidates every cached read.
+pub struct Coordination {
+    store: Store,
+    prefix: String,
+    lock_ttl: Duration,
+    idempotency_ttl: Duration,
+    cache_ttl: Duration,
+}
+
+impl Coordination {
+    pub fn new(network: &Network, redis: Option<ConnectionManager>) -> Self {
+        let secs = |name: &str, default: u64| {
+            let secs = env::var(name).ok().and_then(|value| value.parse().ok()).unwrap_or(default);
+            Duration::from_secs(secs)
+        };
+        Self {
+            store: match redis {
+                Some(conn) => Store::Redis(conn),
+                None => Store::Local(Arc::new(DashMap::new())),
+            },
+            prefix: format!("rwa:{}:", network.name),
+            lock_ttl: secs("LOCK_TTL_SECS", DEFAULT_LOCK_TTL_SECS),
+            idempotency_ttl: secs("IDEMPOTENCY_TTL_SECS", DEFAULT_IDEMPOTENCY_TTL_SECS),
+            cache_ttl: secs("RESPONSE_CACHE_TTL_SECS", 0),
+        }
+    }
+
+    pub fn backend(&self) -> &'static str {
+        match self.store {
+            Store::Redis(_) => "redis",
+            Store::Local(_) => "local",
+        }
+    }
+
+    /// Run `work` holding the lock on `resource`, across every replica.
+    /// Refused with 409 while another holder has it.
+    pub async fn exclusive<T>(&self, resource: &str, work: impl Future<Output = T>) -> Result<T, ApiError> {
+        let key = format!("{}lock:{}", self.prefix, resource);
+        let token = token();
+        let acquired = self.store.set_new(&key, &token, self.lock_ttl).await
+            .map_err(|e| ApiError::Unavailable(format!("Lock store unavailable: {}", e)))?;
+        if !acquired {
+            return Err(ApiError::Conflict(format!("{} is already being submitted", resource)));
+        }
+        let output = work.await;
+        if let Err(e) = self.store.release(&key, &token).await {
+            tracing::warn!("⚠️ Failed to release lock on {}: {}", resource, e);
+        }
+        Ok(output)
+    }
+
+    async fn cache_generation(&self) -> Result<String> {
+        let generation = self.store.get(&format!("{}cache:generation", self.prefix)).await?;
+        Ok(generation.unwrap_or_else(|| "0".to_string()))
+    }
+
+    async fn invalidate_cache(&self) {
+        if self.cache_ttl.is_zero() {
+            return;
+        }
+        if let Err(e) = self.store.increment(&format!("{}cache:generation", self.prefix)).await {
+            tracing::warn!("⚠️ Failed to invalidate the response cache: {}", e);
+        }
+    }
+}
+
+/// Unique per lock holder or reservation.
+fn token() -> String {
+    let now = chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default();
+    format!("{}:{}", std::process::id(), now)
+}
+
+/// Invalidate `network`'s cached reads on each live event, and prune the
+/// in-process store.
+pub fn spawn(coordination: Arc<Coordination>, hub: Arc<EventHub>) {
+    let mut events = hub.subscribe();
+    tokio::spawn(async move {
+        let mut prune = tokio::time::interval(Duration::from_secs(60));
+        loop {
+            tokio::select! {
+                event = events.recv() => match event {
+                    Ok(_) | Err(RecvError::Lagged(_)) => coordination.invalidate_cache().await,
+                    Err(RecvError::Closed) => return,
+                },
+                _ = prune.tick() => coordination.store.prune(),
+            }
+        }
+    });
+}
+
+// ==================== Idempotency Keys ====================
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+enum Idempotent {
+    /// Reserved by the request still running; `token` releases it.
+    Pending { fingerprint: String, token: String },
+    Completed {
+        fingerprint: String,
+        status: u16,
+        content_type: Option<String>,
+        /// Base64 response body.
+        body: String,
+    },
+}
+
+fn hash(parts: &[&[u8]]) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update((part.len() as u64).to_be_bytes());
+        hasher.update(part);
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Writes sent with an `Idempotency-Key` run once per caller and key: a
+/// retry gets the first response back, flagged `Idempotent-Replayed`,
+/// until the key expires. 409 while the first is still running; 400 if the
+/// key comes back with a different request. Responses with a 5xx status
+/// aren't kept, so those writes can be retried under the same key.
+pub async fn idempotency(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    if request.method() == Method::GET {
+        return next.run(request).await;
+    }
+    let Some(key) = request.headers().get(IDEMPOTENCY_KEY_HEADER).cloned() else {
+        return next.run(request).await;
+    };
+    let key = match key.to_str() {
+        Ok(key) if !key.is_empty() && key.len() <= MAX_IDEMPOTENCY_KEY_LEN => key.to_string(),
+        _ => {
+            let message = format!("Idempotency-Key must be 1 to {} visible ASCII characters", MAX_IDEMPOTENCY_KEY_LEN);
+            return ApiError::InvalidRequest(message).into_response();
+        }
+    };
+    let coordination = &state.coordination;
+    let subject = request.extensions().get::<Caller>().map_or("anonymous", |caller| caller.subject.as_str()).to_string();
+    let (parts, body) = request.into_parts();
+    let body = match to_bytes(body, MAX_BUFFERED_BODY).await {
+        Ok(body) => body,
+        Err(e) => return ApiError::InvalidRequest(format!("Unreadable body: {}", e)).into_response(),
+    };
+    let store_key = format!(
+        "{}idempotency:{}",
+        coordination.prefix,
+        hash(&[subject.as_bytes(), parts.method.as_str().as_bytes(), parts.uri.path().as_bytes(), key.as_bytes()])
+    );
+    let fingerprint = hash(&[parts.uri.query().unwrap_or_default().as_bytes(), &body]);
+
+    let token = token();
+    let pending = serde_json::to_string(&Idempotent::Pending { fingerprint: fingerprint.clone(), token: token.clone() }).unwrap();
+    let reserved = match coordination.store.set_new(&store_key, &pending, coordination.lock_ttl).await {
+        Ok(reserved) => reserved,
+        Err(e) => return ApiError::Unavailable(format!("Idempotency store unavailable: {}", e)).into_response(),
+    };
+    if !reserved {
+        let existing = match coordination.store.get(&store_key).await {
+            Ok(existing) => existing.and_then(|raw| serde_json::from_str(&raw).ok()),
+            Err(e) => return ApiError::Unavailable(format!("Idempotency store unavailable: {}", e)).into_response(),
+        };
+        return match existing {
+            Some(Idempotent::Pending { fingerprint: first, .. } | Idempotent::Completed { fingerprint: first, .. }) if first != fingerprint => {
+                ApiError::InvalidRequest("Idempotency-Key was already used for a different request".to_string()).into_response()
+            }
+            Some(Idempotent::Completed { status, content_type, body, .. }) => replay(status, content_type, &body),
+            // Pending, or released between the two reads
+            _ => ApiError::Conflict("A request with this Idempotency-Key is in progress".to_string()).into_response(),
+        };
+    }
+
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+    if response.status().is_server_error() {
+        if let Err(e) = coordination.store.release(&store_key, &pending).await {
+            tracing::warn!("⚠️ Failed to release idempotency key: {}", e);
+        }
+        return response;
+    }
+    let (parts, body) = response.into_parts();
+    let body = match to_bytes(body, MAX_BUFFERED_BODY).await {
+        Ok(body) => body,
+        Err(e) => return ApiError::Internal(format!("Unreadable response: {}", e)).into_response(),
+    };
+    let completed = Idempotent::Completed {
+        fingerprint,
+        status: parts.status.as_u16(),
+        content_type: parts.headers.get(header::CONTENT_TYPE).and_then(|value| value.to_str().ok()).map(str::to_string),
+        body: BASE64.encode(&body),
+    };
+    let completed = serde_json::to_string(&completed).unwrap();
+    if let Err(e) = coordination.store.set(&store_key, &completed, coordination.idempotency_ttl).await {
+        tracing::warn!("⚠️ Failed to keep the response for an idempotency key: {}", e);
+    }
+    Response::from_parts(parts, Body::from(body))
+}
+
+fn replay(status: u16, content_type: Option<String>, body: &str) -> Response {
+    let status = StatusCode::from_u16(status).unwrap_or(StatusCode::OK);
+    let body = BASE64.decode(body).unwrap_or_default();
+    let mut response = (status, body).into_response();
+    if let Some(content_type) = content_type.and_then(|value| HeaderValue::from_str(&value).ok()) {
+        response.headers_mut().insert(header::CONTENT_TYPE, content_type);
+    }
+    response.headers_mut().insert(REPLAYED_HEADER, HeaderValue::from_static("true"));
+    response
+}
+
+// ==================== Response Cache ====================
+#[derive(Debug, Serialize, Deserialize)]
+struct Cached {
+    content_type: String,
+    body: String,
+}
+
+/// Serve GET responses again for `RESPONSE_CACHE_TTL_SECS`, per role and
+/// tenant since roles see differently redacted bodies and tenants list
+/// different assets and loans. Only successful JSON is cached;
+/// `Cache-Control: no-cache` skips the cache. Successful writes invalidate
+/// it. Cache failures fall through to the handler.
+pub async fn cache_reads(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let coordination = state.coordination.clone();
+    if coordination.cache_ttl.is_zero() {
+        return next.run(request).await;
+    }
+    if request.method() != Method::GET {
+        let response = next.run(request).await;
+        if response.status().is_success() {
+            coordination.invalidate_cache().await;
+        }
+        return response;
+    }
+    let no_cache = request.headers().get(header::CACHE_CONTROL)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.contains("no-cache"));
+    let generation = match coordination.cache_generation().await {
+        Ok(generation) if !no_cache => generation,
+        _ => return next.run(request).await,
+    };
+    let caller = request.extensions().get::<Caller>();
+    let role = caller.map_or(Role::Public, |caller| caller.role);
+    let tenant = caller.and_then(|caller| caller.tenant.as_deref()).unwrap_or("");
+    let key = format!("{}cache:{}:{}:{}:{}", coordination.prefix, generation, role.name(), tenant, request.uri());
+
+    if let Ok(Some(cached)) = coordination.store.get(&key).await {
+        if let Ok(cached) = serde_json::from_str::<Cached>(&cached) {
+            let body = BASE64.decode(&cached.body).unwrap_or_default();
+            let mut response = (StatusCode::OK, body).into_response();
+            if let Ok(content_type) = HeaderValue::from_str(&cached.content_type) {
+                response.headers_mut().insert(header::CONTENT_TYPE, content_type);
+            }
+            return response;
+        }
+    }
+
+    let response = next.run(request).await;
+    // Streams never end, so only buffer what can be cached
+    let json = response.headers().get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| value.starts_with("application/json"))
+        .map(str::to_string);
+    let Some(content_type) = json.filter(|_| response.status() == StatusCode::OK) else {
+        return response;
+    };
+    let (parts, body) = response.into_parts();
+    let body = match to_bytes(body, MAX_BUFFERED_BODY).await {
+        Ok(body) => body,
+        Err(e) => return ApiError::Internal(format!("Unreadable response: {}", e)).into_response(),
+    };
+    let cached = serde_json::to_string(&Cached { content_type, body: BASE64.encode(&body) }).unwrap();
+    if let Err(e) = coordination.store.set(&key, &cached, coordination.cache_ttl).await {
+        tracing::warn!("⚠️ Failed to cache response: {}", e);
+    }
+    Response::from_parts(parts, Body::from(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn locks_and_reservations_are_released_only_by_their_holder() {
+        let store = Store::Local(Arc::new(DashMap::new()));
+        let ttl = Duration::from_secs(60);
+        assert!(store.set_new("lock:loan", "first", ttl).await.unwrap());
+        assert!(!store.set_new("lock:loan", "second", ttl).await.unwrap());
+        store.release("lock:loan", "second").await.unwrap();
+        assert_eq!(store.get("lock:loan").await.unwrap().as_deref(), Some("first"));
+        store.release("lock:loan", "first").await.unwrap();
+        assert!(store.set_new("lock:loan", "second", ttl).await.unwrap());
+    }
+}
@@ -0,0 +1,107 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::env;
+
+use crate::solana_client::LoanAccount;
+
+// Smallest group size ever released, regardless of what the caller asks for
+const DEFAULT_MIN_K: usize = 5;
+
+// ==================== Research View Types ====================
+#[derive(Debug, Clone, Serialize)]
+pub struct LoanBookCell {
+    pub principal_bucket: &'static str,
+    pub rate_bucket: &'static str,
+    pub duration_bucket: &'static str,
+    pub status: &'static str,
+    pub loan_count: usize,
+    pub avg_risk_score_at_creation: u8,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LoanBookView {
+    pub k: usize,
+    pub total_loans: usize,
+    pub suppressed_loans: usize,
+    pub cells: Vec<LoanBookCell>,
+}
+
+// ==================== Bucketing ====================
+fn principal_bucket(principal: u64) -> &'static str {
+    match principal {
+        0..=999_999 => "<1M",
+        1_000_000..=9_999_999 => "1M-10M",
+        10_000_000..=99_999_999 => "10M-100M",
+        _ => ">=100M",
+    }
+}
+
+fn rate_bucket(interest_rate_bps: u64) -> &'static str {
+    match interest_rate_bps {
+        0..=499 => "<5%",
+        500..=999 => "5%-10%",
+        _ => ">=10%",
+    }
+}
+
+fn duration_bucket(seconds: i64) -> &'static str {
+    const DAY: i64 = 24 * 60 * 60;
+    match seconds {
+        s if s < 30 * DAY => "<30d",
+        s if s < 90 * DAY => "30d-90d",
+        s if s < 365 * DAY => "90d-1y",
+        _ => ">=1y",
+    }
+}
+
+/// Minimum k enforced for every research query, from `RESEARCH_MIN_K`.
+pub fn min_k() -> usize {
+    env::var("RESEARCH_MIN_K")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_MIN_K)
+        .max(2)
+}
+
+/// Build a k-anonymized aggregate of the loan book. Loans are reduced to
+/// coarse buckets with no pubkeys or exact amounts, and any bucket holding
+/// fewer than `k` loans is suppressed entirely.
+pub fn loan_book_view(loans: &[LoanAccount], requested_k: Option<usize>) -> LoanBookView {
+    let k = requested_k.unwrap_or(0).max(min_k());
+
+    let mut groups: BTreeMap<(&str, &str, &str, &str), Vec<&LoanAccount>> = BTreeMap::new();
+    for loan in loans {
+        let key = (
+            principal_bucket(loan.principal),
+            rate_bucket(loan.interest_rate),
+            duration_bucket(loan.end_time - loan.start_time),
+            loan.status.as_str(),
+        );
+        groups.entry(key).or_default().push(loan);
+    }
+
+    let mut cells = Vec::new();
+    let mut suppressed_loans = 0;
+    for ((principal_bucket, rate_bucket, duration_bucket, status), members) in groups {
+        if members.len() < k {
+            suppressed_loans += members.len();
+            continue;
+        }
+        let risk_sum: u64 = members.iter().map(|l| l.risk_score_at_creation as u64).sum();
+        cells.push(LoanBookCell {
+            principal_bucket,
+            rate_bucket,
+            duration_bucket,
+            status,
+            loan_count: members.len(),
+            avg_risk_score_at_creation: (risk_sum / members.len() as u64) as u8,
+        });
+    }
+
+    LoanBookView {
+        k,
+        total_loans: loans.len(),
+        suppressed_loans,
+        cells,
+    }
+}
@@ -0,0 +1,291 @@
+use axum::{
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use serde_json::json;
+use solana_client::client_error::{ClientError, ClientErrorKind};
+use solana_sdk::transaction::TransactionError;
+use utoipa::ToSchema;
+
+use crate::chainlink_client::ChainlinkError;
+use crate::idl::{self, ProgramError};
+use crate::solana_client::Rejection;
+use crate::validation::FieldError;
+
+// ==================== Error Codes ====================
+/// What went wrong, for clients to branch on; each code has one HTTP status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// 400: malformed or out-of-range input.
+    InvalidRequest,
+    /// 401: missing or invalid credentials.
+    Unauthenticated,
+    /// 403: the caller's role may not do or see this.
+    Forbidden,
+    /// 404: no such account or resource.
+    NotFound,
+    /// 409: another request is already doing this: the same idempotency
+    /// key, or a transaction for the same account on any replica.
+    Conflict,
+    /// 422: the program rejected the transaction, or would have;
+    /// `details.program_error` names its `ErrorCode`.
+    ProgramRejected,
+    /// 422: fields out of bounds or malformed, caught before anything was
+    /// sent; `details.fields` lists each with its JSON path.
+    ValidationFailed,
+    /// 429: over the caller's rate limit; `details.retry_after` in seconds.
+    RateLimited,
+    /// 500: account data that doesn't decode as the expected type.
+    AccountDecodeFailed,
+    /// 500: a bug or unclassified failure.
+    Internal,
+    /// 502: the Solana RPC is unreachable or failing.
+    RpcUnavailable,
+    /// 502: the Chainlink API is unreachable or failing.
+    ChainlinkUnavailable,
+    /// 503: the transaction's blockhash expired before it landed; send again.
+    BlockhashExpired,
+    /// 503: another dependency is down: screening, storage, webhook targets.
+    DependencyUnavailable,
+}
+
+impl ErrorCode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::InvalidRequest => "invalid_request",
+            ErrorCode::Unauthenticated => "unauthenticated",
+            ErrorCode::Forbidden => "forbidden",
+            ErrorCode::NotFound => "not_found",
+            ErrorCode::Conflict => "conflict",
+            ErrorCode::ProgramRejected => "program_rejected",
+            ErrorCode::ValidationFailed => "validation_failed",
+            ErrorCode::RateLimited => "rate_limited",
+            ErrorCode::AccountDecodeFailed => "account_decode_failed",
+            ErrorCode::Internal => "internal",
+            ErrorCode::RpcUnavailable => "rpc_unavailable",
+            ErrorCode::ChainlinkUnavailable => "chainlink_unavailable",
+            ErrorCode::BlockhashExpired => "blockhash_expired",
+            ErrorCode::DependencyUnavailable => "dependency_unavailable",
+        }
+    }
+
+    pub fn status(self) -> StatusCode {
+        match self {
+            ErrorCode::InvalidRequest => StatusCode::BAD_REQUEST,
+            ErrorCode::Unauthenticated => StatusCode::UNAUTHORIZED,
+            ErrorCode::Forbidden => StatusCode::FORBIDDEN,
+            ErrorCode::NotFound => StatusCode::NOT_FOUND,
+            ErrorCode::Conflict => StatusCode::CONFLICT,
+            ErrorCode::ProgramRejected | ErrorCode::ValidationFailed => StatusCode::UNPROCESSABLE_ENTITY,
+            ErrorCode::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+            ErrorCode::AccountDecodeFailed | ErrorCode::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+            ErrorCode::RpcUnavailable | ErrorCode::ChainlinkUnavailable => StatusCode::BAD_GATEWAY,
+            ErrorCode::BlockhashExpired | ErrorCode::DependencyUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+
+    /// Whether the same request may succeed if sent again later.
+    pub fn retryable(self) -> bool {
+        matches!(
+            self,
+            ErrorCode::Conflict
+                | ErrorCode::RateLimited
+                | ErrorCode::RpcUnavailable
+                | ErrorCode::ChainlinkUnavailable
+                | ErrorCode::BlockhashExpired
+                | ErrorCode::DependencyUnavailable
+        )
+    }
+}
+
+/// Body of every error response.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ErrorResponse {
+    pub code: ErrorCode,
+    pub message: String,
+    /// Structured context, depending on the code.
+    #[schema(value_type = Object)]
+    pub details: Option<serde_json::Value>,
+    pub retryable: bool,
+}
+
+// ==================== API Errors ====================
+/// Why a request failed, rendered as an `ErrorResponse`.
+#[derive(Debug)]
+pub enum ApiError {
+    InvalidRequest(String),
+    Unauthenticated(String),
+    Forbidden(String),
+    NotFound(String),
+    Conflict(String),
+    /// Failures the program rejected, or would have, come back as its
+    /// `ErrorCode`, not the RPC text.
+    Program { error: ProgramError, details: String },
+    Validation(Vec<FieldError>),
+    RateLimited { retry_after: u64 },
+    Decode(String),
+    Internal(String),
+    Rpc(String),
+    Chainlink(String),
+    BlockhashExpired(String),
+    Unavailable(String),
+}
+
+impl ApiError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ApiError::InvalidRequest(_) => ErrorCode::InvalidRequest,
+            ApiError::Unauthenticated(_) => ErrorCode::Unauthenticated,
+            ApiError::Forbidden(_) => ErrorCode::Forbidden,
+            ApiError::NotFound(_) => ErrorCode::NotFound,
+            ApiError::Conflict(_) => ErrorCode::Conflict,
+            ApiError::Program { .. } => ErrorCode::ProgramRejected,
+            ApiError::Validation(_) => ErrorCode::ValidationFailed,
+            ApiError::RateLimited { .. } => ErrorCode::RateLimited,
+            ApiError::Decode(_) => ErrorCode::AccountDecodeFailed,
+            ApiError::Internal(_) => ErrorCode::Internal,
+            ApiError::Rpc(_) => ErrorCode::RpcUnavailable,
+            ApiError::Chainlink(_) => ErrorCode::ChainlinkUnavailable,
+            ApiError::BlockhashExpired(_) => ErrorCode::BlockhashExpired,
+            ApiError::Unavailable(_) => ErrorCode::DependencyUnavailable,
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            ApiError::Program { error, .. } => error.message.clone(),
+            ApiError::Validation(fields) => fields
+                .iter()
+                .map(|field| format!("{}: {}", field.field, field.message))
+                .collect::<Vec<_>>()
+                .join("; "),
+            ApiError::RateLimited { retry_after } => format!("Rate limit exceeded; retry in {}s", retry_after),
+            ApiError::InvalidRequest(message)
+            | ApiError::Unauthenticated(message)
+            | ApiError::Forbidden(message)
+            | ApiError::NotFound(message)
+            | ApiError::Conflict(message)
+            | ApiError::Decode(message)
+            | ApiError::Internal(message)
+            | ApiError::Rpc(message)
+            | ApiError::Chainlink(message)
+            | ApiError::BlockhashExpired(message)
+            | ApiError::Unavailable(message) => message.clone(),
+        }
+    }
+
+    /// A lookup's failure: not found, unless the RPC itself failed.
+    pub fn not_found(e: anyhow::Error) -> Self {
+        match Self::from(e) {
+            ApiError::Internal(message) => ApiError::NotFound(message),
+            other => other,
+        }
+    }
+
+    /// Input the program or client-side checks refused.
+    pub fn invalid(e: anyhow::Error) -> Self {
+        match Self::from(e) {
+            ApiError::Internal(message) => ApiError::InvalidRequest(message),
+            other => other,
+        }
+    }
+}
+
+/// Sort an error by its cause: a program rejection, a Solana RPC, Borsh or
+/// Chainlink failure, or else internal.
+impl From<anyhow::Error> for ApiError {
+    fn from(e: anyhow::Error) -> Self {
+        let details = format!("{:#}", e);
+        if let Some(rejection) = e.downcast_ref::<Rejection>() {
+            return ApiError::Program { error: rejection.error.clone(), details };
+        }
+        if let Some(error) = idl::program().decode_error(&details) {
+            return ApiError::Program { error, details };
+        }
+        for cause in e.chain() {
+            if cause.is::<ChainlinkError>() {
+                return ApiError::Chainlink(details);
+            }
+            if let Some(client_error) = cause.downcast_ref::<ClientError>() {
+                match client_error.kind() {
+                    ClientErrorKind::TransactionError(TransactionError::BlockhashNotFound) => {
+                        return ApiError::BlockhashExpired(details);
+                    }
+                    ClientErrorKind::Io(_) | ClientErrorKind::Reqwest(_) => return ApiError::Rpc(details),
+                    _ => {}
+                }
+            }
+            // Borsh reports malformed data as I/O errors
+            if cause.is::<std::io::Error>() {
+                return ApiError::Decode(details);
+            }
+        }
+        classify(details)
+    }
+}
+
+/// Most RPC failures reach `ApiError` already formatted into a message.
+fn classify(details: String) -> ApiError {
+    const UNREACHABLE: &[&str] = &["error sending request", "operation timed out", "Connection refused", "429 Too Many Requests", "Failed to connect"];
+    if details.contains("Blockhash not found") || details.contains("BlockhashNotFound") || details.contains("block height exceeded") {
+        ApiError::BlockhashExpired(details)
+    } else if details.contains("Malformed ") || details.contains("account data is too short") {
+        ApiError::Decode(details)
+    } else if details.contains("AccountNotFound") {
+        ApiError::NotFound(details)
+    } else if UNREACHABLE.iter().any(|marker| details.contains(marker)) {
+        ApiError::Rpc(details)
+    } else {
+        ApiError::Internal(details)
+    }
+}
+
+impl From<&ApiError> for ErrorResponse {
+    fn from(error: &ApiError) -> Self {
+        let code = error.code();
+        let details = match error {
+            ApiError::Program { error, details } => Some(json!({ "program_error": error, "cause": details })),
+            ApiError::Validation(fields) => Some(json!({ "fields": fields })),
+            ApiError::RateLimited { retry_after } => Some(json!({ "retry_after": retry_after })),
+            _ => None,
+        };
+        ErrorResponse { code, message: error.message(), details, retryable: code.retryable() }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let mut response = (self.code().status(), Json(ErrorResponse::from(&self))).into_response();
+        match self {
+            ApiError::Unauthenticated(_) => {
+                response.headers_mut().insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+            }
+            ApiError::RateLimited { retry_after } => {
+                response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+            }
+            _ => {}
+        }
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    #[test]
+    fn errors_are_sorted_by_cause() {
+        assert_eq!(ApiError::from(anyhow!("Failed to get blockhash: error sending request for url")).code(), ErrorCode::RpcUnavailable);
+        assert_eq!(ApiError::from(anyhow!("Transaction failed: Blockhash not found")).code(), ErrorCode::BlockhashExpired);
+        assert_eq!(ApiError::from(anyhow!("Malformed Asset account: unexpected length")).code(), ErrorCode::AccountDecodeFailed);
+        assert_eq!(ApiError::from(anyhow::Error::new(ChainlinkError::Status("Trigger failed: 500".to_string()))).code(), ErrorCode::ChainlinkUnavailable);
+        assert_eq!(ApiError::not_found(anyhow!("AccountNotFound: pubkey=abc")).code(), ErrorCode::NotFound);
+        assert_eq!(ApiError::invalid(anyhow!("Nothing to compose")).code(), ErrorCode::InvalidRequest);
+        assert!(ErrorCode::BlockhashExpired.retryable());
+        assert!(!ErrorCode::ProgramRejected.retryable());
+    }
+}
@@ -0,0 +1,262 @@
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use borsh::BorshDeserialize;
+use serde::{Serialize, Serializer};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::idl;
+use crate::solana_client::LoanStatus;
+
+fn base58<S: Serializer>(key: &Pubkey, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+    serializer.collect_str(key)
+}
+
+// ==================== Program Events ====================
+// Field order mirrors the program's `#[event]` structs; Borsh reads them in
+// declaration order.
+
+#[derive(Debug, Clone, PartialEq, Serialize, BorshDeserialize)]
+pub struct LoanWrittenOff {
+    #[serde(serialize_with = "base58")]
+    pub loan: Pubkey,
+    #[serde(serialize_with = "base58")]
+    pub asset: Pubkey,
+    pub amount: u64,
+    #[serde(serialize_with = "base58")]
+    pub written_off_by: Pubkey,
+    pub written_off_at: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, BorshDeserialize)]
+pub struct LoanRepriced {
+    #[serde(serialize_with = "base58")]
+    pub loan: Pubkey,
+    pub benchmark_bps: i64,
+    pub previous_rate: u64,
+    pub interest_rate: u64,
+    pub repriced_at: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, BorshDeserialize)]
+pub struct RepaidInAcceptedMint {
+    #[serde(serialize_with = "base58")]
+    pub loan: Pubkey,
+    #[serde(serialize_with = "base58")]
+    pub payment_mint: Pubkey,
+    pub debt: u64,
+    pub amount_paid: u64,
+    pub haircut_bps: u16,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, BorshDeserialize)]
+pub struct RiskCircuitBreakerTripped {
+    #[serde(serialize_with = "base58")]
+    pub asset: Pubkey,
+    pub previous_score: u8,
+    pub risk_score: u8,
+    pub frozen_until: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, BorshDeserialize)]
+pub struct OracleSlashed {
+    #[serde(serialize_with = "base58")]
+    pub oracle: Pubkey,
+    #[serde(serialize_with = "base58")]
+    pub report: Pubkey,
+    #[serde(serialize_with = "base58")]
+    pub challenger: Pubkey,
+    pub amount: u64,
+    pub slashed_at: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, BorshDeserialize)]
+pub struct CollateralSeized {
+    #[serde(serialize_with = "base58")]
+    pub loan: Pubkey,
+    #[serde(serialize_with = "base58")]
+    pub asset: Pubkey,
+    #[serde(serialize_with = "base58")]
+    pub previous_owner: Pubkey,
+    #[serde(serialize_with = "base58")]
+    pub recipient: Pubkey,
+    pub surplus: u64,
+    pub deficiency: u64,
+    pub settled_at: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, BorshDeserialize)]
+pub struct AssetTransferred {
+    #[serde(serialize_with = "base58")]
+    pub asset: Pubkey,
+    #[serde(serialize_with = "base58")]
+    pub previous_owner: Pubkey,
+    #[serde(serialize_with = "base58")]
+    pub new_owner: Pubkey,
+    pub transferred_at: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, BorshDeserialize)]
+pub struct LoanStateCranked {
+    #[serde(serialize_with = "base58")]
+    pub loan: Pubkey,
+    pub previous_status: LoanStatus,
+    pub status: LoanStatus,
+    pub late_fees: u64,
+    pub liquidation_eligible: bool,
+    pub cranked_at: i64,
+}
+
+macro_rules! program_events {
+    ($($name:ident),* $(,)?) => {
+        /// An event the program emitted with `emit!`.
+        #[derive(Debug, Clone, PartialEq, Serialize)]
+        #[serde(tag = "name", content = "data")]
+        pub enum ProgramEvent {
+            $($name($name),)*
+        }
+
+        impl ProgramEvent {
+            /// Decode an event payload: the discriminator the IDL declares
+            /// for the event followed by the Borsh-encoded struct. None for
+            /// unknown discriminators.
+            pub fn decode(data: &[u8]) -> Result<Option<Self>> {
+                let Some((discriminator, mut body)) = data.split_first_chunk::<8>() else {
+                    return Ok(None);
+                };
+                let idl = idl::program();
+                $(
+                    if idl.event_discriminator(stringify!($name)) == Some(*discriminator) {
+                        return $name::deserialize(&mut body)
+                            .map(|event| Some(Self::$name(event)))
+                            .map_err(|e| anyhow!("Malformed {} event: {}", stringify!($name), e));
+                    }
+                )*
+                Ok(None)
+            }
+        }
+    };
+}
+
+program_events! {
+    LoanWrittenOff,
+    LoanRepriced,
+    RepaidInAcceptedMint,
+    RiskCircuitBreakerTripped,
+    OracleSlashed,
+    CollateralSeized,
+    AssetTransferred,
+    LoanStateCranked,
+}
+
+impl ProgramEvent {
+    /// The asset and loan the event concerns, as far as it names them.
+    pub fn subjects(&self) -> (Option<Pubkey>, Option<Pubkey>) {
+        match self {
+            Self::LoanWrittenOff(event) => (Some(event.asset), Some(event.loan)),
+            Self::LoanRepriced(event) => (None, Some(event.loan)),
+            Self::RepaidInAcceptedMint(event) => (None, Some(event.loan)),
+            Self::RiskCircuitBreakerTripped(event) => (Some(event.asset), None),
+            Self::OracleSlashed(_) => (None, None),
+            Self::CollateralSeized(event) => (Some(event.asset), Some(event.loan)),
+            Self::AssetTransferred(event) => (Some(event.asset), None),
+            Self::LoanStateCranked(event) => (None, Some(event.loan)),
+        }
+    }
+}
+
+// ==================== Log Parsing ====================
+
+/// One program invocation in a transaction's logs, CPIs included.
+#[derive(Debug, Clone, Serialize)]
+pub struct Invocation {
+    pub program_id: String,
+    /// 1 for top-level instructions, one more for each level of CPI.
+    pub depth: usize,
+    /// Instruction name, for Anchor programs that log it.
+    pub instruction: Option<String>,
+    /// False when the invocation failed or its logs were truncated.
+    pub succeeded: bool,
+    pub error: Option<String>,
+    pub compute_units: Option<u64>,
+    /// `msg!` output of the invocation itself, without its CPIs.
+    pub messages: Vec<String>,
+}
+
+/// What a transaction did, as far as its logs tell.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ParsedLogs {
+    /// In the order they were invoked.
+    pub invocations: Vec<Invocation>,
+    /// Events emitted by `program_id`, in emission order.
+    pub events: Vec<ProgramEvent>,
+    /// Whether the runtime cut the logs short.
+    pub truncated: bool,
+}
+
+/// Walk the runtime's log lines, tracking the invocation stack so that
+/// `Program data:` lines are only decoded while `program_id` is executing.
+pub fn parse_logs(program_id: &Pubkey, logs: &[String]) -> ParsedLogs {
+    let program = program_id.to_string();
+    let mut parsed = ParsedLogs::default();
+    // Indices into `parsed.invocations` of the invocations still executing
+    let mut stack: Vec<usize> = Vec::new();
+
+    for line in logs {
+        if line == "Log truncated" {
+            parsed.truncated = true;
+            break;
+        }
+        if let Some(message) = line.strip_prefix("Program log: ") {
+            if let Some(&current) = stack.last() {
+                let invocation = &mut parsed.invocations[current];
+                match message.strip_prefix("Instruction: ") {
+                    Some(name) if invocation.instruction.is_none() => invocation.instruction = Some(name.to_string()),
+                    _ => invocation.messages.push(message.to_string()),
+                }
+            }
+            continue;
+        }
+        if let Some(data) = line.strip_prefix("Program data: ") {
+            let emitted_by_program = stack.last().is_some_and(|&current| parsed.invocations[current].program_id == program);
+            if emitted_by_program {
+                for chunk in data.split_whitespace() {
+                    match BASE64.decode(chunk).map_err(anyhow::Error::from).and_then(|bytes| ProgramEvent::decode(&bytes)) {
+                        Ok(Some(event)) => parsed.events.push(event),
+                        Ok(None) => {}
+                        Err(e) => tracing::warn!("⚠️ Skipping undecodable event: {}", e),
+                    }
+                }
+            }
+            continue;
+        }
+
+        let Some(rest) = line.strip_prefix("Program ") else { continue };
+        let Some((id, outcome)) = rest.split_once(' ') else { continue };
+        if let Some(depth) = outcome.strip_prefix("invoke [").and_then(|depth| depth.strip_suffix(']')) {
+            parsed.invocations.push(Invocation {
+                program_id: id.to_string(),
+                depth: depth.parse().unwrap_or(stack.len() + 1),
+                instruction: None,
+                succeeded: false,
+                error: None,
+                compute_units: None,
+                messages: Vec::new(),
+            });
+            stack.push(parsed.invocations.len() - 1);
+        } else if let Some(consumed) = outcome.strip_prefix("consumed ") {
+            if let Some(&current) = stack.last() {
+                parsed.invocations[current].compute_units =
+                    consumed.split_whitespace().next().and_then(|units| units.parse().ok());
+            }
+        } else if outcome == "success" {
+            if let Some(current) = stack.pop() {
+                parsed.invocations[current].succeeded = true;
+            }
+        } else if let Some(error) = outcome.strip_prefix("failed: ") {
+            if let Some(current) = stack.pop() {
+                parsed.invocations[current].error = Some(error.to_string());
+            }
+        }
+    }
+    parsed
+}
@@ -0,0 +1,502 @@
+// tonic::Status is the error every service method returns, large or not
+#![allow(clippy::result_large_err)]
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use futures::stream::{self, Stream};
+use serde::de::DeserializeOwned;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::env;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tonic::metadata::MetadataValue;
+use tonic::{Code, Request, Response, Status};
+
+use crate::auth::RouteGroup;
+use crate::live_events::{Channel, LiveEvent};
+use crate::network::NETWORK_HEADER;
+use crate::error::{ApiError, ErrorCode};
+use crate::routes::{self, AppState};
+use crate::solana_client::{AssetFilter, AssetResponse, LoanResponse, LoanStatus};
+use crate::visibility::Role;
+
+pub mod proto {
+    tonic::include_proto!("rwa.v1");
+}
+
+use proto::collateral_server::{Collateral, CollateralServer};
+use proto::event::Kind;
+
+type ResponseStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send>>;
+
+// ==================== Conversions ====================
+impl From<LoanStatus> for proto::LoanStatus {
+    fn from(status: LoanStatus) -> Self {
+        match status {
+            LoanStatus::Pending => proto::LoanStatus::Pending,
+            LoanStatus::Active => proto::LoanStatus::Active,
+            LoanStatus::Defaulted => proto::LoanStatus::Defaulted,
+            LoanStatus::Liquidating => proto::LoanStatus::Liquidating,
+            LoanStatus::Repaid => proto::LoanStatus::Repaid,
+            LoanStatus::Liquidated => proto::LoanStatus::Liquidated,
+            LoanStatus::WrittenOff => proto::LoanStatus::WrittenOff,
+            LoanStatus::Overdue => proto::LoanStatus::Overdue,
+        }
+    }
+}
+
+fn loan_status(status: i32) -> Result<LoanStatus, Status> {
+    match proto::LoanStatus::try_from(status) {
+        Ok(proto::LoanStatus::Pending) => Ok(LoanStatus::Pending),
+        Ok(proto::LoanStatus::Active) => Ok(LoanStatus::Active),
+        Ok(proto::LoanStatus::Defaulted) => Ok(LoanStatus::Defaulted),
+        Ok(proto::LoanStatus::Liquidating) => Ok(LoanStatus::Liquidating),
+        Ok(proto::LoanStatus::Repaid) => Ok(LoanStatus::Repaid),
+        Ok(proto::LoanStatus::Liquidated) => Ok(LoanStatus::Liquidated),
+        Ok(proto::LoanStatus::WrittenOff) => Ok(LoanStatus::WrittenOff),
+        Ok(proto::LoanStatus::Overdue) => Ok(LoanStatus::Overdue),
+        Ok(proto::LoanStatus::Unspecified) | Err(_) => Err(Status::invalid_argument(format!("Invalid loan status: {}", status))),
+    }
+}
+
+/// An enum option by its REST spelling.
+fn parse_enum<T: DeserializeOwned>(name: &str, value: Option<String>) -> Result<Option<T>, Status> {
+    value
+        .map(|value| serde_json::from_value(serde_json::Value::String(value.clone()))
+            .map_err(|_| Status::invalid_argument(format!("Invalid {}: {}", name, value))))
+        .transpose()
+}
+
+/// `value`, unless `field` is hidden from `role`.
+fn visible<T>(role: Role, field: &str, value: T) -> Option<T> {
+    role.can_see(field).then_some(value)
+}
+
+fn asset(address: String, asset: AssetResponse, role: Role) -> proto::Asset {
+    proto::Asset {
+        asset_id: asset.asset_id,
+        address,
+        asset_type: asset.asset_type,
+        valuation: visible(role, "valuation", asset.valuation),
+        metadata_uri: visible(role, "metadata_uri", asset.metadata_uri),
+        owner: visible(role, "owner", asset.owner),
+        is_active: asset.is_active,
+        risk_score: asset.risk_score.into(),
+        last_update: asset.last_update,
+        active_loans: asset.active_loans,
+        encumbered: asset.encumbered,
+        jurisdiction: asset.jurisdiction,
+        portfolio: asset.portfolio,
+    }
+}
+
+fn loan(address: String, loan: LoanResponse, role: Role) -> proto::Loan {
+    proto::Loan {
+        address,
+        asset: loan.asset,
+        borrower: visible(role, "borrower", loan.borrower),
+        principal: visible(role, "principal", loan.principal),
+        interest_rate: loan.interest_rate,
+        start_time: loan.start_time,
+        end_time: loan.end_time,
+        status: proto::LoanStatus::from(loan.status).into(),
+        risk_score_at_creation: loan.risk_score_at_creation.into(),
+        lender: loan.lender,
+        repayment_mint: loan.repayment_mint,
+        late_fees: loan.late_fees,
+        liquidation_eligible: loan.liquidation_eligible,
+        liquidation_debt: loan.liquidation_debt,
+    }
+}
+
+fn loan_changed(
+    loan: String,
+    asset: String,
+    borrower: String,
+    status: LoanStatus,
+    previous_status: Option<LoanStatus>,
+    principal: Option<u64>,
+    role: Role,
+) -> proto::LoanChanged {
+    proto::LoanChanged {
+        loan,
+        asset,
+        borrower: visible(role, "borrower", borrower),
+        status: proto::LoanStatus::from(status).into(),
+        previous_status: previous_status.map(|status| proto::LoanStatus::from(status).into()),
+        principal: principal.and_then(|principal| visible(role, "principal", principal)),
+    }
+}
+
+fn event(event: LiveEvent, channels: Vec<String>, role: Role) -> proto::Event {
+    let (slot, kind) = match event {
+        LiveEvent::RiskUpdated { asset_id, asset, risk_score, previous_risk_score, slot } => (slot, Kind::RiskUpdated(proto::RiskUpdated {
+            asset_id,
+            asset,
+            risk_score: risk_score.into(),
+            previous_risk_score: previous_risk_score.map(Into::into),
+        })),
+        LiveEvent::LoanCreated { loan, asset, borrower, principal, slot } => (slot, Kind::LoanCreated(
+            loan_changed(loan, asset, borrower, LoanStatus::Pending, None, Some(principal), role),
+        )),
+        LiveEvent::LoanRepaid { loan, asset, borrower, slot } => (slot, Kind::LoanRepaid(
+            loan_changed(loan, asset, borrower, LoanStatus::Repaid, None, None, role),
+        )),
+        LiveEvent::LoanLiquidation { loan, asset, borrower, status, slot } => (slot, Kind::LoanLiquidation(
+            loan_changed(loan, asset, borrower, status, None, None, role),
+        )),
+        LiveEvent::LoanUpdated { loan, asset, borrower, status, previous_status, slot } => (slot, Kind::LoanUpdated(
+            loan_changed(loan, asset, borrower, status, previous_status, None, role),
+        )),
+        LiveEvent::ProgramEvent { signature, asset, loan, borrower, event, slot } => {
+            let mut event = serde_json::to_value(&event).unwrap();
+            role.redact(&mut event);
+            (slot, Kind::ProgramEvent(proto::ProgramEvent {
+                signature,
+                asset,
+                loan,
+                borrower: borrower.and_then(|borrower| visible(role, "borrower", borrower)),
+                name: event["name"].as_str().unwrap_or_default().to_string(),
+                json: event["data"].to_string(),
+            }))
+        }
+    };
+    proto::Event { channels, slot, kind: Some(kind) }
+}
+
+/// The gRPC status for a REST handler's error, carrying the REST error
+/// code in `x-error-code` metadata. Program rejections keep their
+/// `ErrorCode` name and number in the message.
+fn status(error: ApiError) -> Status {
+    let code = error.code();
+    let grpc_code = match code {
+        ErrorCode::InvalidRequest | ErrorCode::ValidationFailed => Code::InvalidArgument,
+        ErrorCode::Unauthenticated => Code::Unauthenticated,
+        ErrorCode::Forbidden => Code::PermissionDenied,
+        ErrorCode::NotFound => Code::NotFound,
+        ErrorCode::Conflict => Code::Aborted,
+        ErrorCode::ProgramRejected => Code::FailedPrecondition,
+        ErrorCode::RateLimited => Code::ResourceExhausted,
+        ErrorCode::AccountDecodeFailed => Code::DataLoss,
+        ErrorCode::Internal => Code::Internal,
+        ErrorCode::RpcUnavailable
+        | ErrorCode::ChainlinkUnavailable
+        | ErrorCode::BlockhashExpired
+        | ErrorCode::DependencyUnavailable => Code::Unavailable,
+    };
+    let message = match &error {
+        ApiError::Program { error, details } => format!("{} ({}): {}; {}", error.name, error.code, error.message, details),
+        other => other.message(),
+    };
+    let mut status = Status::new(grpc_code, message);
+    status.metadata_mut().insert("x-error-code", MetadataValue::from_static(code.as_str()));
+    status
+}
+
+// ==================== Service ====================
+/// The REST API's asset, loan and risk operations over gRPC, for internal
+/// services that want typed, streaming calls. Credentials travel as the
+/// same `x-api-key` or `authorization: Bearer` metadata and are held to
+/// the same route groups and roles; `x-network` picks the network.
+pub struct GrpcService {
+    networks: HashMap<String, AppState>,
+    default_network: String,
+}
+
+impl GrpcService {
+    /// Pick the request's network and check its credentials for `group`,
+    /// returning the caller's role.
+    fn admit<T>(&self, request: &Request<T>, group: RouteGroup, required: Option<Role>) -> Result<(AppState, Role), Status> {
+        let metadata = request.metadata();
+        let network = metadata.get(NETWORK_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or(&self.default_network);
+        let state = self.networks.get(network)
+            .ok_or_else(|| Status::not_found(format!("Unknown network: {}", network)))?;
+
+        let api_key = metadata.get("x-api-key").and_then(|value| value.to_str().ok());
+        let bearer = metadata.get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        let caller = state.auth.identify(&state.roles, &state.tenants, api_key, bearer)
+            .map_err(|e| Status::unauthenticated(e.to_string()))?;
+        // Tenant scoping and payers apply to the REST routes only
+        if caller.as_ref().is_some_and(|caller| caller.tenant.is_some()) {
+            return Err(Status::permission_denied("Tenant credentials are accepted over REST only"));
+        }
+        state.auth.admit(group, caller.as_ref())
+            .map_err(|e| Status::unauthenticated(e.to_string()))?;
+
+        let role = caller.map_or(Role::Public, |caller| caller.role);
+        if let Some(required) = required {
+            if role != required {
+                return Err(Status::permission_denied(format!("This call is restricted to the {} role", required.name())));
+            }
+        }
+        Ok((state.clone(), role))
+    }
+}
+
+#[tonic::async_trait]
+impl Collateral for GrpcService {
+    type ListAssetsStream = ResponseStream<proto::Asset>;
+    type ListLoansStream = ResponseStream<proto::Loan>;
+    type WatchEventsStream = ResponseStream<proto::Event>;
+
+    async fn get_asset(&self, request: Request<proto::GetAssetRequest>) -> Result<Response<proto::Asset>, Status> {
+        let (state, role) = self.admit(&request, RouteGroup::Reads, None)?;
+        let asset_id = request.into_inner().asset_id;
+        let found = state.solana.get_asset(&asset_id).await
+            .map_err(|e| Status::not_found(format!("Asset not found: {}", e)))?;
+        let address = state.solana.asset_address(&asset_id).0.to_string();
+        Ok(Response::new(asset(address, found, role)))
+    }
+
+    async fn list_assets(&self, request: Request<proto::ListAssetsRequest>) -> Result<Response<Self::ListAssetsStream>, Status> {
+        let (state, role) = self.admit(&request, RouteGroup::Reads, None)?;
+        let request = request.into_inner();
+        // Filtering on a hidden field would reveal it one call at a time
+        if request.owner.is_some() && !role.can_see("owner") {
+            return Err(Status::permission_denied("Filtering by owner is restricted for this role"));
+        }
+        let owner = request.owner
+            .map(|owner| Pubkey::from_str(&owner))
+            .transpose()
+            .map_err(|e| Status::invalid_argument(format!("Invalid owner: {}", e)))?;
+        let risk = |risk: Option<u32>| risk.map(u8::try_from).transpose()
+            .map_err(|_| Status::invalid_argument("Invalid risk bound"));
+        let filter = AssetFilter {
+            owner,
+            active: request.active,
+            min_risk: risk(request.min_risk)?,
+            max_risk: risk(request.max_risk)?,
+        };
+
+        let assets = match state.index.assets(&filter) {
+            Some(assets) => assets,
+            None => state.solana.list_assets(&filter, None).await
+                .map_err(|e| Status::internal(e.to_string()))?,
+        };
+        let assets: Vec<_> = assets.into_iter()
+            .map(|found| {
+                let address = state.solana.asset_address(&found.asset_id).0.to_string();
+                Ok(asset(address, found, role))
+            })
+            .collect();
+        Ok(Response::new(Box::pin(stream::iter(assets))))
+    }
+
+    async fn create_asset(&self, request: Request<proto::CreateAssetRequest>) -> Result<Response<proto::TransactionResult>, Status> {
+        let (state, _) = self.admit(&request, RouteGroup::Writes, None)?;
+        let request = request.into_inner();
+        let Json(created) = routes::create_asset(State(state), None, Json(routes::CreateAssetRequest {
+            asset_id: request.asset_id,
+            asset_type: request.asset_type,
+            valuation: request.valuation,
+            metadata_uri: request.metadata_uri,
+            owner: request.owner,
+        })).await.map_err(status)?;
+        Ok(Response::new(proto::TransactionResult {
+            address: created.asset_pda,
+            signature: created.transaction,
+            unsigned_transaction: created.unsigned_transaction,
+        }))
+    }
+
+    async fn update_risk(&self, request: Request<proto::UpdateRiskRequest>) -> Result<Response<proto::TransactionResult>, Status> {
+        let (state, _) = self.admit(&request, RouteGroup::Writes, Some(Role::Oracle))?;
+        let request = request.into_inner();
+        let risk_score = u8::try_from(request.risk_score)
+            .map_err(|_| Status::invalid_argument("Invalid risk_score"))?;
+        let address = state.solana.asset_address(&request.asset_id).0.to_string();
+        let Json(updated) = routes::update_risk(State(state), Path(request.asset_id), Json(routes::UpdateRiskRequest {
+            risk_score,
+            source: request.source,
+            confidence: request.confidence,
+        })).await.map_err(status)?;
+        Ok(Response::new(proto::TransactionResult {
+            address,
+            signature: Some(updated.transaction),
+            unsigned_transaction: None,
+        }))
+    }
+
+    async fn get_loan(&self, request: Request<proto::GetLoanRequest>) -> Result<Response<proto::Loan>, Status> {
+        let (state, role) = self.admit(&request, RouteGroup::Reads, None)?;
+        let loan_pda = request.into_inner().loan_pda;
+        let loan_pubkey = Pubkey::from_str(&loan_pda)
+            .map_err(|e| Status::invalid_argument(format!("Invalid loan PDA: {}", e)))?;
+        let found = state.solana.get_loan(loan_pubkey, None).await
+            .map_err(|e| Status::not_found(format!("Loan not found: {}", e)))?;
+        Ok(Response::new(loan(loan_pda, found, role)))
+    }
+
+    async fn list_loans(&self, request: Request<proto::ListLoansRequest>) -> Result<Response<Self::ListLoansStream>, Status> {
+        let (state, role) = self.admit(&request, RouteGroup::Reads, None)?;
+        let request = request.into_inner();
+        if request.borrower.is_some() && !role.can_see("borrower") {
+            return Err(Status::permission_denied("Filtering by borrower is restricted for this role"));
+        }
+        let borrower = request.borrower
+            .map(|borrower| Pubkey::from_str(&borrower))
+            .transpose()
+            .map_err(|e| Status::invalid_argument(format!("Invalid borrower: {}", e)))?;
+        let asset = request.asset_id.map(|asset_id| state.solana.asset_address(&asset_id).0);
+        let status = request.status.map(loan_status).transpose()?;
+
+        let loans = match state.index.loans(borrower.as_ref()) {
+            Some(loans) => loans,
+            None => state.solana.list_loans().await
+                .map_err(|e| Status::internal(e.to_string()))?
+                .into_iter()
+                .filter(|(_, loan)| borrower.is_none_or(|borrower| loan.borrower == borrower))
+                .collect(),
+        };
+        let loans: Vec<_> = loans.into_iter()
+            .filter(|(_, loan)| asset.is_none_or(|asset| loan.asset == asset))
+            .filter(|(_, loan)| status.is_none_or(|status| loan.status == status))
+            .map(|(loan_pda, found)| Ok(loan(loan_pda.to_string(), LoanResponse::from(found), role)))
+            .collect();
+        Ok(Response::new(Box::pin(stream::iter(loans))))
+    }
+
+    async fn create_loan(&self, request: Request<proto::CreateLoanRequest>) -> Result<Response<proto::TransactionResult>, Status> {
+        let (state, _) = self.admit(&request, RouteGroup::Writes, None)?;
+        let request = request.into_inner();
+        let Json(created) = routes::create_loan(State(state), None, Json(routes::CreateLoanRequest {
+            asset_id: request.asset_id,
+            borrower: request.borrower,
+            loan_amount: request.loan_amount,
+            interest_rate: request.interest_rate,
+            duration: request.duration,
+            mint: request.mint,
+            interest_mode: parse_enum("interest_mode", request.interest_mode)?.unwrap_or_default(),
+            product: parse_enum("product", request.product)?.unwrap_or_default(),
+            floating_rate: None,
+        })).await.map_err(status)?;
+        Ok(Response::new(proto::TransactionResult {
+            address: created.loan_pda,
+            signature: created.transaction,
+            unsigned_transaction: created.unsigned_transaction,
+        }))
+    }
+
+    async fn repay_loan(&self, request: Request<proto::LoanActionRequest>) -> Result<Response<proto::TransactionResult>, Status> {
+        let (state, _) = self.admit(&request, RouteGroup::Writes, None)?;
+        let loan_pda = request.into_inner().loan_pda;
+        let Json(repaid) = routes::repay_loan(State(state), Path(loan_pda)).await.map_err(status)?;
+        Ok(Response::new(proto::TransactionResult {
+            address: repaid.loan_pda,
+            signature: repaid.transaction,
+            unsigned_transaction: repaid.unsigned_transaction,
+        }))
+    }
+
+    async fn liquidate_loan(&self, request: Request<proto::LoanActionRequest>) -> Result<Response<proto::TransactionResult>, Status> {
+        let (state, _) = self.admit(&request, RouteGroup::Writes, None)?;
+        let loan_pda = request.into_inner().loan_pda;
+        let Json(liquidated) = routes::liquidate_loan(State(state), Path(loan_pda)).await.map_err(status)?;
+        Ok(Response::new(proto::TransactionResult {
+            address: liquidated.loan_pda,
+            signature: Some(liquidated.transaction),
+            unsigned_transaction: None,
+        }))
+    }
+
+    async fn watch_events(&self, request: Request<proto::WatchEventsRequest>) -> Result<Response<Self::WatchEventsStream>, Status> {
+        let (state, role) = self.admit(&request, RouteGroup::Reads, None)?;
+        let channels = request.into_inner().channels.into_iter()
+            .map(|name| Channel::parse(&name, &state, role).map(|channel| (name, channel)))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(Status::invalid_argument)?;
+        if channels.is_empty() {
+            return Err(Status::invalid_argument("Name at least one channel"));
+        }
+        let channels = Arc::new(channels);
+
+        // Read as the client pulls; a client slower than the broadcast
+        // buffer is told how many events it missed
+        let events = stream::unfold(state.events.subscribe(), move |mut events| {
+            let channels = channels.clone();
+            async move {
+                loop {
+                    let next = match events.recv().await {
+                        Ok(live) => {
+                            let matched: Vec<String> = channels.iter()
+                                .filter(|(_, channel)| channel.matches(&live))
+                                .map(|(name, _)| name.clone())
+                                .collect();
+                            if matched.is_empty() {
+                                continue;
+                            }
+                            event(live, matched, role)
+                        }
+                        Err(RecvError::Lagged(missed)) => proto::Event { channels: Vec::new(), slot: 0, kind: Some(Kind::Lagged(missed)) },
+                        Err(RecvError::Closed) => return None,
+                    };
+                    return Some((Ok(next), events));
+                }
+            }
+        });
+        Ok(Response::new(Box::pin(events)))
+    }
+}
+
+/// Serve gRPC on `GRPC_PORT`, apart from the REST port; unset, gRPC is off.
+/// Calls aren't rate-limited, so keep the port internal.
+pub fn spawn(networks: HashMap<String, AppState>, default_network: String) {
+    let Ok(port) = env::var("GRPC_PORT") else { return };
+    let addr: SocketAddr = match format!("0.0.0.0:{}", port).parse() {
+        Ok(addr) => addr,
+        Err(e) => {
+            tracing::error!("❌ Invalid GRPC_PORT {}: {}", port, e);
+            return;
+        }
+    };
+    let service = CollateralServer::new(GrpcService { networks, default_network });
+    tokio::spawn(async move {
+        tracing::info!("📡 gRPC listening on {}", addr);
+        if let Err(e) = tonic::transport::Server::builder().add_service(service).serve(addr).await {
+            tracing::error!("❌ gRPC server failed: {}", e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hidden_fields_are_left_unset() {
+        let changed = loan_changed(
+            "loan".to_string(),
+            "asset".to_string(),
+            "borrower".to_string(),
+            LoanStatus::Pending,
+            None,
+            Some(1_000),
+            Role::Public,
+        );
+        assert_eq!(changed.borrower, None);
+        assert_eq!(changed.principal, None);
+        assert_eq!(changed.status, proto::LoanStatus::Pending as i32);
+
+        let changed = loan_changed(
+            "loan".to_string(),
+            "asset".to_string(),
+            "borrower".to_string(),
+            LoanStatus::Active,
+            Some(LoanStatus::Pending),
+            None,
+            Role::Lender,
+        );
+        assert_eq!(changed.borrower.as_deref(), Some("borrower"));
+        assert_eq!(loan_status(changed.previous_status.unwrap()).unwrap(), LoanStatus::Pending);
+    }
+}
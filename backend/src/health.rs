@@ -0,0 +1,214 @@
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::env;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use crate::routes::AppState;
+
+const DEFAULT_PROBE_TIMEOUT_MS: u64 = 2_000;
+const DEFAULT_MAX_SLOT_LAG: u64 = 50;
+
+// ==================== Dependency Status ====================
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DependencyStatus {
+    Ok,
+    /// Reachable but behind, or short of funds.
+    Degraded,
+    Down,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DependencyCheck {
+    pub name: &'static str,
+    pub status: DependencyStatus,
+    /// Whether the service can't serve traffic while this is down.
+    pub critical: bool,
+    pub latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(skip_serializing_if = "Value::is_null")]
+    pub data: Value,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Readiness {
+    pub status: DependencyStatus,
+    pub checked_at: i64,
+    pub checks: Vec<DependencyCheck>,
+}
+
+impl Readiness {
+    /// Whether a load balancer should route here: every critical
+    /// dependency answers, however slowly.
+    pub fn is_ready(&self) -> bool {
+        self.status != DependencyStatus::Down
+    }
+}
+
+/// Down if a critical dependency is, degraded if anything else isn't ok.
+fn overall(checks: &[DependencyCheck]) -> DependencyStatus {
+    if checks.iter().any(|check| check.critical && check.status == DependencyStatus::Down) {
+        DependencyStatus::Down
+    } else if checks.iter().any(|check| check.status != DependencyStatus::Ok) {
+        DependencyStatus::Degraded
+    } else {
+        DependencyStatus::Ok
+    }
+}
+
+// ==================== Probes ====================
+pub struct HealthProbes {
+    timeout: Duration,
+    max_slot_lag: u64,
+}
+
+impl HealthProbes {
+    /// `HEALTH_PROBE_TIMEOUT_MS` (default 2000) bounds each probe and
+    /// `HEALTH_MAX_SLOT_LAG` (default 50) is how many slots the RPC node
+    /// may trail the cluster before it counts as degraded.
+    pub fn from_env() -> Self {
+        let timeout = env::var("HEALTH_PROBE_TIMEOUT_MS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_PROBE_TIMEOUT_MS);
+        let max_slot_lag = env::var("HEALTH_MAX_SLOT_LAG")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_MAX_SLOT_LAG);
+        Self {
+            timeout: Duration::from_millis(timeout.max(1)),
+            max_slot_lag,
+        }
+    }
+
+    /// Probe the RPC node, the database, the Chainlink API and the payer
+    /// together. The first two are critical; the others only degrade.
+    pub async fn readiness(&self, state: &AppState) -> Readiness {
+        let (rpc, database, chainlink, payer) = tokio::join!(
+            self.probe_rpc(state),
+            self.probe_database(state),
+            self.probe_chainlink(state),
+            self.probe_payer(state),
+        );
+        let checks = vec![rpc, database, chainlink, payer];
+        Readiness {
+            status: overall(&checks),
+            checked_at: chrono::Utc::now().timestamp(),
+            checks,
+        }
+    }
+
+    async fn timed<T>(&self, probe: impl Future<Output = Result<T>>) -> (Result<T>, u64) {
+        let started = Instant::now();
+        let result = match tokio::time::timeout(self.timeout, probe).await {
+            Ok(result) => result,
+            Err(_) => Err(anyhow!("Timed out after {}ms", self.timeout.as_millis())),
+        };
+        (result, started.elapsed().as_millis() as u64)
+    }
+
+    async fn probe_rpc(&self, state: &AppState) -> DependencyCheck {
+        let (result, latency_ms) = self.timed(state.solana.rpc_slots()).await;
+        let mut check = check("solana_rpc", true, latency_ms);
+        match result {
+            Ok((slot, tip)) => {
+                let lag = tip - slot;
+                if lag > self.max_slot_lag {
+                    check.status = DependencyStatus::Degraded;
+                    check.detail = Some(format!("{} slots behind, more than {}", lag, self.max_slot_lag));
+                }
+                check.data = json!({ "slot": slot, "tip": tip, "slot_lag": lag });
+            }
+            Err(e) => check.fail(e),
+        }
+        check
+    }
+
+    async fn probe_database(&self, state: &AppState) -> DependencyCheck {
+        let (result, latency_ms) = self.timed(state.storage.health.ping()).await;
+        let mut check = check("database", true, latency_ms);
+        check.data = json!({ "backend": state.storage.backend });
+        if let Err(e) = result {
+            check.fail(e);
+        }
+        check
+    }
+
+    async fn probe_chainlink(&self, state: &AppState) -> DependencyCheck {
+        let (result, latency_ms) = self.timed(state.chainlink.ping()).await;
+        let mut check = check("chainlink", false, latency_ms);
+        if let Err(e) = result {
+            check.fail(e);
+        }
+        check
+    }
+
+    /// The wallet monitor's last status, checking now if it hasn't run yet.
+    async fn probe_payer(&self, state: &AppState) -> DependencyCheck {
+        let (result, latency_ms) = match state.wallet.latest() {
+            Some(status) => (Ok(status), 0),
+            None => self.timed(state.wallet.check(&state.solana, &state.webhooks)).await,
+        };
+        let mut check = check("payer_balance", false, latency_ms);
+        match result {
+            Ok(status) => {
+                if status.low_balance {
+                    check.status = DependencyStatus::Degraded;
+                    check.detail = Some(format!("{} lamports available, below {}", status.available, status.threshold));
+                }
+                check.data = json!({
+                    "payer": status.wallet.payer,
+                    "available": status.available,
+                    "threshold": status.threshold,
+                    "checked_at": status.checked_at,
+                });
+            }
+            Err(e) => check.fail(e),
+        }
+        check
+    }
+}
+
+fn check(name: &'static str, critical: bool, latency_ms: u64) -> DependencyCheck {
+    DependencyCheck {
+        name,
+        status: DependencyStatus::Ok,
+        critical,
+        latency_ms,
+        detail: None,
+        data: Value::Null,
+    }
+}
+
+impl DependencyCheck {
+    fn fail(&mut self, error: anyhow::Error) {
+        tracing::warn!("⚠️ Health probe {} failed: {}", self.name, error);
+        self.status = DependencyStatus::Down;
+        self.detail = Some(error.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_critical_outages_fail_readiness() {
+        let with = |name, critical, status| DependencyCheck { status, ..check(name, critical, 0) };
+        let mut checks = vec![
+            with("solana_rpc", true, DependencyStatus::Degraded),
+            with("database", true, DependencyStatus::Ok),
+            with("chainlink", false, DependencyStatus::Down),
+        ];
+        assert_eq!(overall(&checks), DependencyStatus::Degraded);
+
+        checks[1].status = DependencyStatus::Down;
+        assert_eq!(overall(&checks), DependencyStatus::Down);
+
+        checks.iter_mut().for_each(|check| check.status = DependencyStatus::Ok);
+        assert_eq!(overall(&checks), DependencyStatus::Ok);
+    }
+}
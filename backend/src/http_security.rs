@@ -0,0 +1,167 @@
+use anyhow::{anyhow, Result};
+use axum::{
+    extract::{DefaultBodyLimit, Request, State},
+    http::{header, HeaderName, HeaderValue, Method},
+    middleware::{self, Next},
+    response::Response,
+    Router,
+};
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use tower_http::limit::RequestBodyLimitLayer;
+
+use crate::coordination::{IDEMPOTENCY_KEY_HEADER, REPLAYED_HEADER};
+use crate::network::NETWORK_HEADER;
+use crate::read_routing::READ_SOURCE_HEADER;
+
+const DEFAULT_MAX_BODY_BYTES: usize = 1024 * 1024;
+const DEFAULT_CORS_MAX_AGE_SECS: u64 = 600;
+
+// ==================== Configuration ====================
+#[derive(Debug, Clone, PartialEq)]
+enum AllowedOrigins {
+    /// No CORS headers; browsers only call from the API's own origin.
+    None,
+    Any,
+    List(Vec<HeaderValue>),
+}
+
+impl AllowedOrigins {
+    fn parse(value: &str) -> Result<Self> {
+        let origins: Vec<&str> = value.split(',').map(str::trim).filter(|origin| !origin.is_empty()).collect();
+        if origins.is_empty() {
+            return Ok(Self::None);
+        }
+        if origins.contains(&"*") {
+            return Ok(Self::Any);
+        }
+        origins.into_iter()
+            .map(|origin| {
+                let url = url::Url::parse(origin).map_err(|e| anyhow!("Invalid CORS origin {:?}: {}", origin, e))?;
+                if !matches!(url.scheme(), "http" | "https") || url.path() != "/" {
+                    return Err(anyhow!("CORS origin {:?} must be a bare http(s) origin", origin));
+                }
+                Ok(HeaderValue::from_str(origin.trim_end_matches('/'))?)
+            })
+            .collect::<Result<Vec<_>>>()
+            .map(Self::List)
+    }
+}
+
+/// CORS, response security headers and the request body limit, applied
+/// once around every network's routes.
+pub struct HttpSecurity {
+    origins: AllowedOrigins,
+    allow_credentials: bool,
+    cors_max_age: Duration,
+    max_body_bytes: usize,
+    /// `Strict-Transport-Security` max-age; 0 leaves the header off.
+    hsts_max_age: u64,
+}
+
+impl HttpSecurity {
+    /// `CORS_ALLOWED_ORIGINS` lists the browser origins allowed to call the
+    /// API (comma-separated, `*` for any, unset for none), with
+    /// `CORS_ALLOW_CREDENTIALS` (default false) and `CORS_MAX_AGE_SECS`
+    /// (default 600) for preflights. `MAX_BODY_BYTES` (default 1 MiB) caps
+    /// request bodies and `HSTS_MAX_AGE_SECS` (default 0, off) sets HSTS.
+    pub fn from_env() -> Result<Self> {
+        let origins = AllowedOrigins::parse(&env::var("CORS_ALLOWED_ORIGINS").unwrap_or_default())?;
+        let allow_credentials = env::var("CORS_ALLOW_CREDENTIALS").is_ok_and(|value| value == "true");
+        if allow_credentials && origins == AllowedOrigins::Any {
+            return Err(anyhow!("CORS_ALLOW_CREDENTIALS needs explicit CORS_ALLOWED_ORIGINS, not *"));
+        }
+        let parse = |name: &str, default: u64| -> u64 {
+            env::var(name).ok().and_then(|value| value.parse().ok()).unwrap_or(default)
+        };
+        Ok(Self {
+            origins,
+            allow_credentials,
+            cors_max_age: Duration::from_secs(parse("CORS_MAX_AGE_SECS", DEFAULT_CORS_MAX_AGE_SECS)),
+            max_body_bytes: parse("MAX_BODY_BYTES", DEFAULT_MAX_BODY_BYTES as u64) as usize,
+            hsts_max_age: parse("HSTS_MAX_AGE_SECS", 0),
+        })
+    }
+
+    fn cors(&self) -> Option<CorsLayer> {
+        let allow_origin = match &self.origins {
+            AllowedOrigins::None => return None,
+            AllowedOrigins::Any => AllowOrigin::any(),
+            AllowedOrigins::List(origins) => AllowOrigin::list(origins.clone()),
+        };
+        Some(CorsLayer::new()
+            .allow_origin(allow_origin)
+            .allow_methods([Method::GET, Method::POST, Method::DELETE])
+            .allow_headers([
+                header::AUTHORIZATION,
+                header::CONTENT_TYPE,
+                HeaderName::from_static("x-api-key"),
+                HeaderName::from_static(IDEMPOTENCY_KEY_HEADER),
+                HeaderName::from_static(NETWORK_HEADER),
+            ])
+            .expose_headers([
+                header::RETRY_AFTER,
+                HeaderName::from_static(READ_SOURCE_HEADER),
+                HeaderName::from_static(REPLAYED_HEADER),
+            ])
+            .allow_credentials(self.allow_credentials)
+            .max_age(self.cors_max_age))
+    }
+
+    /// Wrap `app`. CORS sits outermost so preflights are answered before
+    /// authentication, and its rejections still carry the security headers.
+    pub fn apply(self: Arc<Self>, app: Router) -> Router {
+        let app = app
+            .layer(DefaultBodyLimit::disable())
+            .layer(RequestBodyLimitLayer::new(self.max_body_bytes))
+            .layer(middleware::from_fn_with_state(self.clone(), security_headers));
+        match self.cors() {
+            Some(cors) => app.layer(cors),
+            None => app,
+        }
+    }
+}
+
+// ==================== Security Headers ====================
+/// Headers a JSON API wants on every response; a handler that sets its own
+/// keeps it.
+async fn security_headers(State(security): State<Arc<HttpSecurity>>, request: Request, next: Next) -> Response {
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+    let mut set = |name: HeaderName, value: HeaderValue| {
+        headers.entry(name).or_insert(value);
+    };
+    set(header::X_CONTENT_TYPE_OPTIONS, HeaderValue::from_static("nosniff"));
+    set(header::X_FRAME_OPTIONS, HeaderValue::from_static("DENY"));
+    set(header::REFERRER_POLICY, HeaderValue::from_static("no-referrer"));
+    // Only framing: the Swagger UI at /docs runs its own scripts
+    set(header::CONTENT_SECURITY_POLICY, HeaderValue::from_static("frame-ancestors 'none'"));
+    if security.hsts_max_age > 0 {
+        if let Ok(value) = HeaderValue::from_str(&format!("max-age={}; includeSubDomains", security.hsts_max_age)) {
+            set(header::STRICT_TRANSPORT_SECURITY, value);
+        }
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_allowed_origins() {
+        assert_eq!(AllowedOrigins::parse(" ").unwrap(), AllowedOrigins::None);
+        assert_eq!(AllowedOrigins::parse("https://app.example.com, *").unwrap(), AllowedOrigins::Any);
+        assert_eq!(
+            AllowedOrigins::parse("https://app.example.com/,http://localhost:3000").unwrap(),
+            AllowedOrigins::List(vec![
+                HeaderValue::from_static("https://app.example.com"),
+                HeaderValue::from_static("http://localhost:3000"),
+            ]),
+        );
+        assert!(AllowedOrigins::parse("https://app.example.com/login").is_err());
+        assert!(AllowedOrigins::parse("app.example.com").is_err());
+    }
+}
@@ -0,0 +1,513 @@
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use serde_json::Value;
+use solana_sdk::{hash::hash, pubkey::Pubkey};
+use std::collections::HashMap;
+use std::env;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use utoipa::ToSchema;
+
+// Used unless `PROGRAM_IDL_PATH` points at another copy; refresh it from
+// target/idl/ whenever the program changes
+const BUNDLED_IDL: &str = include_str!("idl/rwa_collateral.json");
+
+static PROGRAM_IDL: OnceLock<ProgramIdl> = OnceLock::new();
+
+/// Load the IDL from `PROGRAM_IDL_PATH`, or the bundled copy when unset.
+/// Called once at startup; later calls return the IDL already loaded.
+pub fn load() -> Result<&'static ProgramIdl> {
+    if let Some(idl) = PROGRAM_IDL.get() {
+        return Ok(idl);
+    }
+    let idl = match env::var("PROGRAM_IDL_PATH") {
+        Ok(path) => read(&path)?,
+        Err(_) => ProgramIdl::parse(BUNDLED_IDL).map_err(|e| anyhow!("Invalid bundled IDL: {}", e))?,
+    };
+    Ok(PROGRAM_IDL.get_or_init(|| idl))
+}
+
+/// An IDL for one network's deployment, loaded from `path` at startup and
+/// kept for the life of the process.
+pub fn load_file(path: &str) -> Result<&'static ProgramIdl> {
+    Ok(Box::leak(Box::new(read(path)?)))
+}
+
+fn read(path: &str) -> Result<ProgramIdl> {
+    let source = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read IDL {}: {}", path, e))?;
+    ProgramIdl::parse(&source).map_err(|e| anyhow!("Invalid IDL {}: {}", path, e))
+}
+
+/// The IDL `load` chose, or the bundled copy if nothing has loaded one yet.
+pub fn program() -> &'static ProgramIdl {
+    PROGRAM_IDL.get_or_init(|| ProgramIdl::parse(BUNDLED_IDL).expect("bundled IDL is valid"))
+}
+
+/// Anchor's discriminator: sha256("<namespace>:<name>")[..8].
+fn anchor_discriminator(namespace: &str, name: &str) -> [u8; 8] {
+    hash(format!("{}:{}", namespace, name).as_bytes()).to_bytes()[..8].try_into().unwrap()
+}
+
+// ==================== IDL Types ====================
+#[derive(Debug, Clone, PartialEq)]
+pub enum IdlType {
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    String,
+    Bytes,
+    Pubkey,
+    Option(Box<IdlType>),
+    Vec(Box<IdlType>),
+    Array(Box<IdlType>, usize),
+    Defined(String),
+}
+
+impl IdlType {
+    fn parse(value: &Value) -> Result<Self> {
+        if let Some(name) = value.as_str() {
+            return Ok(match name {
+                "bool" => Self::Bool,
+                "u8" => Self::U8,
+                "u16" => Self::U16,
+                "u32" => Self::U32,
+                "u64" => Self::U64,
+                "u128" => Self::U128,
+                "i8" => Self::I8,
+                "i16" => Self::I16,
+                "i32" => Self::I32,
+                "i64" => Self::I64,
+                "i128" => Self::I128,
+                "string" => Self::String,
+                "bytes" => Self::Bytes,
+                // Anchor 0.29 IDLs spell it `publicKey`
+                "pubkey" | "publicKey" => Self::Pubkey,
+                other => return Err(anyhow!("unsupported type `{}`", other)),
+            });
+        }
+        if let Some(inner) = value.get("option") {
+            return Ok(Self::Option(Box::new(Self::parse(inner)?)));
+        }
+        if let Some(inner) = value.get("vec") {
+            return Ok(Self::Vec(Box::new(Self::parse(inner)?)));
+        }
+        if let Some([inner, len]) = value.get("array").and_then(Value::as_array).map(Vec::as_slice) {
+            let len = len.as_u64().ok_or_else(|| anyhow!("array length is not a number: {}", len))?;
+            return Ok(Self::Array(Box::new(Self::parse(inner)?), len as usize));
+        }
+        if let Some(defined) = value.get("defined") {
+            // Anchor 0.30 nests the name; earlier IDLs give it directly
+            let name = defined.get("name").unwrap_or(defined).as_str()
+                .ok_or_else(|| anyhow!("malformed defined type: {}", defined))?;
+            return Ok(Self::Defined(name.to_string()));
+        }
+        Err(anyhow!("unsupported type {}", value))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct IdlField {
+    pub name: String,
+    pub ty: IdlType,
+}
+
+impl IdlField {
+    fn parse_all(fields: Option<&Value>) -> Result<Vec<Self>> {
+        let Some(fields) = fields.and_then(Value::as_array) else { return Ok(Vec::new()) };
+        fields.iter()
+            .enumerate()
+            .map(|(index, field)| match field.get("name").and_then(Value::as_str) {
+                Some(name) => Ok(Self { name: name.to_string(), ty: IdlType::parse(&field["type"])? }),
+                // Tuple fields are bare types, named here by position
+                None => Ok(Self { name: index.to_string(), ty: IdlType::parse(field)? }),
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+enum TypeDef {
+    Struct(Vec<IdlField>),
+    /// Variants in tag order, with their fields.
+    Enum(Vec<(String, Vec<IdlField>)>),
+}
+
+#[derive(Debug, Clone)]
+pub struct IdlInstruction {
+    pub name: String,
+    pub discriminator: [u8; 8],
+    pub args: Vec<IdlField>,
+}
+
+/// One of the program's `ErrorCode` variants.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct ProgramError {
+    pub code: u32,
+    pub name: String,
+    pub message: String,
+}
+
+/// Discriminator an IDL entry declares, or Anchor's derivation from its name
+/// for IDLs that predate explicit discriminators.
+fn declared_discriminator(entry: &Value, namespace: &str, name: &str) -> Result<[u8; 8]> {
+    match entry.get("discriminator") {
+        Some(bytes) => serde_json::from_value(bytes.clone())
+            .map_err(|e| anyhow!("`{}` has a malformed discriminator: {}", name, e)),
+        None => Ok(anchor_discriminator(namespace, name)),
+    }
+}
+
+// ==================== Program IDL ====================
+/// The program's Anchor IDL: instruction and account discriminators, and the
+/// argument layouts instruction data is encoded with.
+pub struct ProgramIdl {
+    /// Program the IDL was generated for.
+    pub address: Pubkey,
+    json: Value,
+    instructions: Vec<IdlInstruction>,
+    accounts: HashMap<String, [u8; 8]>,
+    events: HashMap<String, [u8; 8]>,
+    types: HashMap<String, TypeDef>,
+    errors: HashMap<u32, ProgramError>,
+}
+
+impl ProgramIdl {
+    pub fn parse(source: &str) -> Result<Self> {
+        let json: Value = serde_json::from_str(source)?;
+        let address = json.get("address")
+            .or_else(|| json.pointer("/metadata/address"))
+            .and_then(Value::as_str)
+            .ok_or_else(|| anyhow!("IDL does not name a program address"))?;
+        let address = Pubkey::from_str(address)
+            .map_err(|e| anyhow!("IDL program address is invalid: {}", e))?;
+
+        let section = |name: &str| json.get(name).and_then(Value::as_array).cloned().unwrap_or_default();
+        let entry_name = |entry: &Value| -> Result<String> {
+            entry.get("name").and_then(Value::as_str).map(str::to_string)
+                .ok_or_else(|| anyhow!("IDL entry without a name: {}", entry))
+        };
+
+        let instructions = section("instructions").iter()
+            .map(|entry| {
+                let name = entry_name(entry)?;
+                Ok(IdlInstruction {
+                    discriminator: declared_discriminator(entry, "global", &name)?,
+                    args: IdlField::parse_all(entry.get("args"))
+                        .map_err(|e| anyhow!("instruction `{}`: {}", name, e))?,
+                    name,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let discriminators = |section_name: &str, namespace: &str| -> Result<HashMap<String, [u8; 8]>> {
+            section(section_name).iter()
+                .map(|entry| {
+                    let name = entry_name(entry)?;
+                    Ok((name.clone(), declared_discriminator(entry, namespace, &name)?))
+                })
+                .collect()
+        };
+        let accounts = discriminators("accounts", "account")?;
+        let events = discriminators("events", "event")?;
+
+        let types = section("types").iter()
+            .map(|entry| {
+                let name = entry_name(entry)?;
+                let ty = &entry["type"];
+                let definition = match ty.get("kind").and_then(Value::as_str) {
+                    Some("struct") => TypeDef::Struct(IdlField::parse_all(ty.get("fields"))?),
+                    Some("enum") => TypeDef::Enum(
+                        ty.get("variants").and_then(Value::as_array).cloned().unwrap_or_default().iter()
+                            .map(|variant| Ok((entry_name(variant)?, IdlField::parse_all(variant.get("fields"))?)))
+                            .collect::<Result<Vec<_>>>()?,
+                    ),
+                    _ => return Err(anyhow!("type `{}` is neither a struct nor an enum", name)),
+                };
+                Ok((name, definition))
+            })
+            .collect::<Result<HashMap<_, _>>>()?;
+
+        let errors = section("errors").iter()
+            .map(|entry| {
+                let code = entry.get("code").and_then(Value::as_u64)
+                    .ok_or_else(|| anyhow!("IDL error without a code: {}", entry))? as u32;
+                let message = entry.get("msg").and_then(Value::as_str).unwrap_or_default().to_string();
+                Ok((code, ProgramError { code, name: entry_name(entry)?, message }))
+            })
+            .collect::<Result<HashMap<_, _>>>()?;
+
+        Ok(Self { address, json, instructions, accounts, events, types, errors })
+    }
+
+    /// Refuse to run against a program other than the one the IDL describes,
+    /// or with an IDL missing instructions or accounts the service relies on.
+    pub fn verify(&self, program_id: &Pubkey, instructions: &[&str], accounts: &[&str]) -> Result<()> {
+        if self.address != *program_id {
+            return Err(anyhow!("IDL is for program {}, not {}", self.address, program_id));
+        }
+        for name in instructions {
+            self.instruction(name)?;
+        }
+        for name in accounts {
+            self.account_discriminator(name)?;
+        }
+        Ok(())
+    }
+
+    /// The IDL as loaded, for comparison with the deployed one.
+    pub fn json(&self) -> &Value {
+        &self.json
+    }
+
+    pub fn instruction(&self, name: &str) -> Result<&IdlInstruction> {
+        self.instructions.iter()
+            .find(|instruction| instruction.name == name)
+            .ok_or_else(|| anyhow!("IDL has no `{}` instruction", name))
+    }
+
+    /// Name of the instruction `data` invokes, from its leading discriminator.
+    pub fn instruction_name(&self, data: &[u8]) -> Option<&str> {
+        let discriminator = data.get(..8)?;
+        self.instructions.iter()
+            .find(|instruction| instruction.discriminator[..] == *discriminator)
+            .map(|instruction| instruction.name.as_str())
+    }
+
+    pub fn account_discriminator(&self, name: &str) -> Result<[u8; 8]> {
+        self.accounts.get(name).copied()
+            .ok_or_else(|| anyhow!("IDL has no `{}` account", name))
+    }
+
+    pub fn event_discriminator(&self, name: &str) -> Option<[u8; 8]> {
+        self.events.get(name).copied()
+    }
+
+    /// The program error declared as `name`.
+    pub fn error_named(&self, name: &str) -> Result<ProgramError> {
+        self.errors.values()
+            .find(|error| error.name == name)
+            .cloned()
+            .ok_or_else(|| anyhow!("IDL has no error `{}`", name))
+    }
+
+    /// The program error a failed transaction reports, found from the
+    /// `custom program error: 0x1772` the runtime words it as. None for
+    /// failures that aren't one of the program's own errors.
+    pub fn decode_error(&self, failure: &str) -> Option<ProgramError> {
+        failure.match_indices("custom program error: 0x").find_map(|(start, marker)| {
+            let hex: String = failure[start + marker.len()..]
+                .chars()
+                .take_while(char::is_ascii_hexdigit)
+                .collect();
+            let code = u32::from_str_radix(&hex, 16).ok()?;
+            self.errors.get(&code).cloned()
+        })
+    }
+
+    /// Instruction data for `name`: its discriminator followed by `args`,
+    /// given in declaration order and Borsh-encoded by their IDL types.
+    /// Defined enums are given by variant name, in either case.
+    pub fn instruction_data(&self, name: &str, args: &[Value]) -> Result<Vec<u8>> {
+        let instruction = self.instruction(name)?;
+        if args.len() != instruction.args.len() {
+            return Err(anyhow!(
+                "`{}` takes {} arguments, got {}",
+                name, instruction.args.len(), args.len(),
+            ));
+        }
+        let mut data = instruction.discriminator.to_vec();
+        for (field, value) in instruction.args.iter().zip(args) {
+            self.encode(&field.ty, value, &mut data)
+                .map_err(|e| anyhow!("`{}` argument `{}`: {}", name, field.name, e))?;
+        }
+        Ok(data)
+    }
+
+    fn encode(&self, ty: &IdlType, value: &Value, out: &mut Vec<u8>) -> Result<()> {
+        let mismatch = || anyhow!("expected {:?}, got {}", ty, value);
+        match ty {
+            IdlType::Bool => out.push(value.as_bool().ok_or_else(mismatch)? as u8),
+            IdlType::U8 => out.push(integer(value)?),
+            IdlType::U16 => out.extend_from_slice(&integer::<u16>(value)?.to_le_bytes()),
+            IdlType::U32 => out.extend_from_slice(&integer::<u32>(value)?.to_le_bytes()),
+            IdlType::U64 => out.extend_from_slice(&integer::<u64>(value)?.to_le_bytes()),
+            IdlType::U128 => out.extend_from_slice(&integer::<u128>(value)?.to_le_bytes()),
+            IdlType::I8 => out.extend_from_slice(&integer::<i8>(value)?.to_le_bytes()),
+            IdlType::I16 => out.extend_from_slice(&integer::<i16>(value)?.to_le_bytes()),
+            IdlType::I32 => out.extend_from_slice(&integer::<i32>(value)?.to_le_bytes()),
+            IdlType::I64 => out.extend_from_slice(&integer::<i64>(value)?.to_le_bytes()),
+            IdlType::I128 => out.extend_from_slice(&integer::<i128>(value)?.to_le_bytes()),
+            IdlType::String => {
+                let string = value.as_str().ok_or_else(mismatch)?;
+                out.extend_from_slice(&(string.len() as u32).to_le_bytes());
+                out.extend_from_slice(string.as_bytes());
+            }
+            IdlType::Bytes => {
+                let bytes = value.as_array().ok_or_else(mismatch)?;
+                out.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+                for byte in bytes {
+                    out.push(integer(byte)?);
+                }
+            }
+            IdlType::Pubkey => {
+                let key = value.as_str().ok_or_else(mismatch)?;
+                let key = Pubkey::from_str(key).map_err(|e| anyhow!("invalid pubkey {}: {}", key, e))?;
+                out.extend_from_slice(key.as_ref());
+            }
+            IdlType::Option(inner) => match value {
+                Value::Null => out.push(0),
+                value => {
+                    out.push(1);
+                    self.encode(inner, value, out)?;
+                }
+            },
+            IdlType::Vec(inner) => {
+                let items = value.as_array().ok_or_else(mismatch)?;
+                out.extend_from_slice(&(items.len() as u32).to_le_bytes());
+                for item in items {
+                    self.encode(inner, item, out)?;
+                }
+            }
+            IdlType::Array(inner, len) => {
+                let items = value.as_array().filter(|items| items.len() == *len).ok_or_else(mismatch)?;
+                for item in items {
+                    self.encode(inner, item, out)?;
+                }
+            }
+            IdlType::Defined(name) => match self.types.get(name) {
+                Some(TypeDef::Struct(fields)) => self.encode_fields(fields, value, out)?,
+                Some(TypeDef::Enum(variants)) => {
+                    // A bare name for unit variants, {"Variant": fields} otherwise
+                    let (variant, fields) = match value {
+                        Value::String(variant) => (variant.as_str(), &Value::Null),
+                        Value::Object(object) if object.len() == 1 => {
+                            let (variant, fields) = object.iter().next().unwrap();
+                            (variant.as_str(), fields)
+                        }
+                        _ => return Err(mismatch()),
+                    };
+                    let (tag, (_, variant_fields)) = variants.iter()
+                        .enumerate()
+                        .find(|(_, (name, _))| name == variant || snake_case(name) == variant)
+                        .ok_or_else(|| anyhow!("`{}` has no variant `{}`", name, variant))?;
+                    out.push(tag as u8);
+                    if !variant_fields.is_empty() {
+                        self.encode_fields(variant_fields, fields, out)?;
+                    }
+                }
+                None => return Err(anyhow!("IDL has no type `{}`", name)),
+            },
+        }
+        Ok(())
+    }
+
+    /// Fields from an object keyed by name or, for tuples, an array.
+    fn encode_fields(&self, fields: &[IdlField], value: &Value, out: &mut Vec<u8>) -> Result<()> {
+        for (index, field) in fields.iter().enumerate() {
+            let field_value = match value {
+                Value::Object(object) => object.get(&field.name),
+                Value::Array(items) => items.get(index),
+                _ => None,
+            }
+            .ok_or_else(|| anyhow!("missing field `{}`", field.name))?;
+            self.encode(&field.ty, field_value, out)
+                .map_err(|e| anyhow!("field `{}`: {}", field.name, e))?;
+        }
+        Ok(())
+    }
+}
+
+/// An integer given as a JSON number, or as a string for values beyond 2^53.
+fn integer<T: TryFrom<i128>>(value: &Value) -> Result<T> {
+    let wide = match value {
+        Value::Number(number) => number.as_i64().map(i128::from).or_else(|| number.as_u64().map(i128::from)),
+        Value::String(digits) => digits.parse().ok(),
+        _ => None,
+    }
+    .ok_or_else(|| anyhow!("expected an integer, got {}", value))?;
+    T::try_from(wide).map_err(|_| anyhow!("{} is out of range", wide))
+}
+
+/// `DailyCompounding` -> `daily_compounding`, as the API spells variants.
+fn snake_case(name: &str) -> String {
+    let mut snake = String::with_capacity(name.len() + 4);
+    for (index, ch) in name.chars().enumerate() {
+        if ch.is_uppercase() && index > 0 {
+            snake.push('_');
+        }
+        snake.push(ch.to_ascii_lowercase());
+    }
+    snake
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn bundled_idl_matches_anchor_discriminators() {
+        let idl = ProgramIdl::parse(BUNDLED_IDL).unwrap();
+        assert_eq!(idl.instruction("request_loan").unwrap().discriminator, anchor_discriminator("global", "request_loan"));
+        assert_eq!(idl.account_discriminator("Loan").unwrap(), anchor_discriminator("account", "Loan"));
+        assert_eq!(idl.event_discriminator("LoanRepriced"), Some(anchor_discriminator("event", "LoanRepriced")));
+        assert_eq!(idl.instruction_name(&anchor_discriminator("global", "repay_loan")), Some("repay_loan"));
+    }
+
+    #[test]
+    fn encodes_arguments_by_idl_type() {
+        let idl = ProgramIdl::parse(BUNDLED_IDL).unwrap();
+        let benchmark = Pubkey::new_unique();
+        let data = idl.instruction_data("request_loan", &[
+            json!(1_000u64),
+            json!(850u64),
+            json!(86_400i64),
+            json!("daily_compounding"),
+            json!("Amortizing"),
+            json!({ "benchmark": benchmark.to_string(), "spread_bps": 200, "floor_bps": 300, "cap_bps": 1_500 }),
+        ]).unwrap();
+
+        let mut expected = anchor_discriminator("global", "request_loan").to_vec();
+        expected.extend_from_slice(&1_000u64.to_le_bytes());
+        expected.extend_from_slice(&850u64.to_le_bytes());
+        expected.extend_from_slice(&86_400i64.to_le_bytes());
+        expected.extend_from_slice(&[1, 2, 1]);
+        expected.extend_from_slice(benchmark.as_ref());
+        for bps in [200u64, 300, 1_500] {
+            expected.extend_from_slice(&bps.to_le_bytes());
+        }
+        assert_eq!(data, expected);
+    }
+
+    #[test]
+    fn decodes_custom_program_errors() {
+        let idl = ProgramIdl::parse(BUNDLED_IDL).unwrap();
+        let failure = "Update failed: RPC response error -32002: Transaction simulation failed: \
+            Error processing Instruction 2: custom program error: 0x1772";
+        let error = idl.decode_error(failure).unwrap();
+        assert_eq!((error.code, error.name.as_str()), (6002, "LoanTooHigh"));
+        assert_eq!(error.message, "Loan amount exceeds maximum LTV");
+
+        assert!(idl.decode_error("Error processing Instruction 0: custom program error: 0x1").is_none());
+        assert!(idl.decode_error("Transaction not confirmed").is_none());
+    }
+
+    #[test]
+    fn rejects_mismatched_arguments_and_programs() {
+        let idl = ProgramIdl::parse(BUNDLED_IDL).unwrap();
+        assert!(idl.instruction_data("update_risk_score", &[json!(300)]).is_err());
+        assert!(idl.instruction_data("update_risk_score", &[]).is_err());
+        assert!(idl.instruction_data("no_such_instruction", &[]).is_err());
+        assert!(idl.verify(&Pubkey::new_unique(), &[], &[]).is_err());
+        assert!(idl.verify(&idl.address, &["initialize_asset"], &["Asset"]).is_ok());
+        assert!(idl.verify(&idl.address, &["initialize_asset"], &["NoSuchAccount"]).is_err());
+    }
+}
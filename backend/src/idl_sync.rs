@@ -0,0 +1,169 @@
+use axum::{
+    extract::{Request, State},
+    http::Method,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::env;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use crate::error::ApiError;
+use crate::routes::AppState;
+use crate::solana_client::SolanaService;
+
+const DEFAULT_INTERVAL_SECS: u64 = 600;
+
+// ==================== Sync Status ====================
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IdlSyncStatus {
+    /// `None` until a check has completed against the on-chain IDL.
+    pub in_sync: Option<bool>,
+    pub checked_at: Option<i64>,
+    pub drift: Vec<String>,
+    pub error: Option<String>,
+    pub enforced: bool,
+}
+
+pub struct IdlSync {
+    status: RwLock<IdlSyncStatus>,
+    enforce: bool,
+}
+
+impl IdlSync {
+    /// With `IDL_SYNC_ENFORCE=true`, mutating routes are refused while drift is detected.
+    pub fn from_env() -> Self {
+        let enforce = env::var("IDL_SYNC_ENFORCE")
+            .map(|v| v == "true" || v == "1")
+            .unwrap_or(false);
+        Self {
+            status: RwLock::new(IdlSyncStatus { enforced: enforce, ..Default::default() }),
+            enforce,
+        }
+    }
+
+    pub fn status(&self) -> IdlSyncStatus {
+        self.status.read().unwrap().clone()
+    }
+
+    pub fn blocks_writes(&self) -> bool {
+        self.enforce && self.status.read().unwrap().in_sync == Some(false)
+    }
+
+    pub async fn check(&self, solana: &SolanaService) {
+        let now = chrono::Utc::now().timestamp();
+        // The IDL the service encodes instructions and decodes accounts with
+        let loaded = solana.idl().json();
+        let result = solana.get_onchain_idl().await.map(|onchain| diff_idl(loaded, &onchain));
+
+        let mut status = self.status.write().unwrap();
+        status.checked_at = Some(now);
+        match result {
+            Ok(drift) if drift.is_empty() => {
+                tracing::info!("✅ On-chain IDL matches the loaded IDL");
+                status.in_sync = Some(true);
+                status.drift = drift;
+                status.error = None;
+            }
+            Ok(drift) => {
+                for change in &drift {
+                    tracing::error!("🚨 IDL drift: {}", change);
+                }
+                status.in_sync = Some(false);
+                status.drift = drift;
+                status.error = None;
+            }
+            Err(e) => {
+                // An unreadable IDL is not evidence of drift; keep the last verdict
+                tracing::warn!("⚠️ IDL sync check failed: {}", e);
+                status.error = Some(e.to_string());
+            }
+        }
+    }
+}
+
+/// Run the check at startup and then every `IDL_SYNC_INTERVAL_SECS`.
+pub fn spawn(sync: Arc<IdlSync>, solana: Arc<SolanaService>) {
+    let interval = env::var("IDL_SYNC_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_INTERVAL_SECS);
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(interval.max(1)));
+        loop {
+            ticker.tick().await;
+            sync.check(&solana).await;
+        }
+    });
+}
+
+/// Refuse state-changing requests while enforcement is on and the IDL has drifted.
+pub async fn guard_mutations(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    if request.method() != Method::GET && state.idl_sync.blocks_writes() {
+        return ApiError::Unavailable(
+            "Deployed program IDL differs from the loaded IDL; writes are disabled".to_string(),
+        )
+            .into_response();
+    }
+    next.run(request).await
+}
+
+// ==================== Diffing ====================
+fn by_name(idl: &Value, section: &str) -> BTreeMap<String, Value> {
+    idl.get(section)
+        .and_then(Value::as_array)
+        .map(|items| {
+            items.iter()
+                .filter_map(|item| Some((item.get("name")?.as_str()?.to_string(), item.clone())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Describe every instruction or account the backend relies on whose
+/// discriminator, arguments, or layout differ between the two IDLs.
+pub fn diff_idl(loaded: &Value, onchain: &Value) -> Vec<String> {
+    let mut drift = Vec::new();
+
+    let onchain_instructions = by_name(onchain, "instructions");
+    for (name, expected) in by_name(loaded, "instructions") {
+        match onchain_instructions.get(&name) {
+            None => drift.push(format!("instruction `{}` is missing on-chain", name)),
+            Some(actual) => {
+                if expected.get("discriminator") != actual.get("discriminator") {
+                    drift.push(format!("instruction `{}` discriminator changed", name));
+                }
+                if expected.get("args") != actual.get("args") {
+                    drift.push(format!("instruction `{}` arguments changed", name));
+                }
+                // IDLs refreshed from the program source may leave the account lists out
+                if expected.get("accounts").is_some() && expected.get("accounts") != actual.get("accounts") {
+                    drift.push(format!("instruction `{}` accounts changed", name));
+                }
+            }
+        }
+    }
+
+    let onchain_accounts = by_name(onchain, "accounts");
+    let loaded_types = by_name(loaded, "types");
+    let onchain_types = by_name(onchain, "types");
+    for (name, expected) in by_name(loaded, "accounts") {
+        match onchain_accounts.get(&name) {
+            None => drift.push(format!("account `{}` is missing on-chain", name)),
+            Some(actual) => {
+                if expected.get("discriminator") != actual.get("discriminator") {
+                    drift.push(format!("account `{}` discriminator changed", name));
+                }
+                if loaded_types.get(&name) != onchain_types.get(&name) {
+                    drift.push(format!("account `{}` layout changed", name));
+                }
+            }
+        }
+    }
+
+    drift
+}
@@ -0,0 +1,113 @@
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use reqwest::Client as HttpClient;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::VersionedTransaction;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::sync::OnceCell;
+
+use crate::network::Network;
+
+/// The block engine drops bundles tipping less than this.
+const MIN_TIP_LAMPORTS: u64 = 1_000;
+const DEFAULT_TIP_LAMPORTS: u64 = 10_000;
+const REQUEST_TIMEOUT_SECS: u64 = 10;
+
+#[derive(Deserialize)]
+struct RpcResponse {
+    result: Option<Value>,
+    error: Option<Value>,
+}
+
+// ==================== Jito Block Engine ====================
+/// Submits time-sensitive transactions to a Jito block engine as bundles.
+/// Each bundled transaction carries its own tip, so it lands with the tip
+/// or not at all.
+pub struct JitoClient {
+    url: String,
+    tip_lamports: u64,
+    http_client: HttpClient,
+    /// Fetched from the block engine on first use.
+    tip_accounts: OnceCell<Vec<Pubkey>>,
+    /// Tips rotate across the accounts to spread write locks.
+    next_tip_account: AtomicUsize,
+}
+
+impl JitoClient {
+    /// Enabled by `JITO_BLOCK_ENGINE_URL` (e.g.
+    /// `https://mainnet.block-engine.jito.wtf`), tipping `JITO_TIP_LAMPORTS`
+    /// (default 10000) per bundle.
+    pub fn for_network(network: &Network) -> Result<Option<Self>> {
+        let Some(url) = network.var("JITO_BLOCK_ENGINE_URL") else { return Ok(None) };
+        let tip_lamports = network.var("JITO_TIP_LAMPORTS")
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_TIP_LAMPORTS);
+        if tip_lamports < MIN_TIP_LAMPORTS {
+            return Err(anyhow!("JITO_TIP_LAMPORTS must be at least {}", MIN_TIP_LAMPORTS));
+        }
+        let http_client = HttpClient::builder()
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .build()
+            .map_err(|e| anyhow!("Failed to build HTTP client: {}", e))?;
+        Ok(Some(Self {
+            url: format!("{}/api/v1/bundles", url.trim_end_matches('/')),
+            tip_lamports,
+            http_client,
+            tip_accounts: OnceCell::new(),
+            next_tip_account: AtomicUsize::new(0),
+        }))
+    }
+
+    async fn call(&self, method: &str, params: Value) -> Result<Value> {
+        let response = self.http_client.post(&self.url)
+            .json(&json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }))
+            .send()
+            .await
+            .map_err(|e| anyhow!("Block engine unreachable: {}", e))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(anyhow!("Block engine returned {}: {}", status, text));
+        }
+        let response: RpcResponse = response.json().await?;
+        match (response.result, response.error) {
+            (_, Some(error)) => Err(anyhow!("{} failed: {}", method, error)),
+            (Some(result), None) => Ok(result),
+            (None, None) => Err(anyhow!("{} returned nothing", method)),
+        }
+    }
+
+    /// Transfer of the tip from `payer` to one of the block engine's tip accounts.
+    pub async fn tip_instruction(&self, payer: &Pubkey) -> Result<Instruction> {
+        let accounts = self.tip_accounts.get_or_try_init(|| async {
+            let accounts: Vec<String> = serde_json::from_value(self.call("getTipAccounts", json!([])).await?)?;
+            let accounts = accounts.iter()
+                .map(|account| Pubkey::from_str(account).map_err(|e| anyhow!("Invalid tip account {}: {}", account, e)))
+                .collect::<Result<Vec<_>>>()?;
+            if accounts.is_empty() {
+                return Err(anyhow!("Block engine has no tip accounts"));
+            }
+            Ok(accounts)
+        }).await?;
+        let tip_account = accounts[self.next_tip_account.fetch_add(1, Ordering::Relaxed) % accounts.len()];
+        Ok(system_instruction::transfer(payer, &tip_account, self.tip_lamports))
+    }
+
+    /// Submit signed `transactions` as one bundle, returning its id.
+    pub async fn send_bundle(&self, transactions: &[&VersionedTransaction]) -> Result<String> {
+        let encoded = transactions.iter()
+            .map(|transaction| bincode::serialize(transaction).map(|bytes| BASE64.encode(bytes)))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| anyhow!("Failed to serialize transaction: {}", e))?;
+        let bundle_id = self.call("sendBundle", json!([encoded, { "encoding": "base64" }])).await?;
+        bundle_id.as_str()
+            .map(str::to_string)
+            .ok_or_else(|| anyhow!("sendBundle returned {}", bundle_id))
+    }
+}
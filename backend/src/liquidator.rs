@@ -0,0 +1,284 @@
+use anyhow::Result;
+use futures::StreamExt;
+use serde::Serialize;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::env;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use crate::coordination::Coordination;
+use crate::error::ApiError;
+use crate::network::Network;
+use crate::solana_client::{self, AssetAccount, LiquidationTrigger, SolanaService};
+
+const DEFAULT_INTERVAL_SECS: u64 = 60;
+const DEFAULT_CONCURRENCY: usize = 4;
+
+// ==================== Configuration ====================
+/// What the liquidator does with the loans it finds eligible.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LiquidatorMode {
+    /// No scans.
+    Off,
+    /// Scan and report eligible loans, sending nothing.
+    DryRun,
+    /// Liquidate eligible loans with the backend payer.
+    Execute,
+}
+
+impl LiquidatorMode {
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "off" => Some(Self::Off),
+            "dry_run" | "dry-run" => Some(Self::DryRun),
+            "execute" => Some(Self::Execute),
+            _ => None,
+        }
+    }
+}
+
+// ==================== Scan Results ====================
+/// Counters since startup.
+#[derive(Debug, Default)]
+struct Counters {
+    scans: AtomicU64,
+    failed_scans: AtomicU64,
+    eligible: AtomicU64,
+    liquidated: AtomicU64,
+    dry_runs: AtomicU64,
+    /// Another replica or an API caller held the loan's lock.
+    contended: AtomicU64,
+    failed: AtomicU64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LiquidatorMetrics {
+    pub scans: u64,
+    pub failed_scans: u64,
+    pub eligible: u64,
+    pub liquidated: u64,
+    pub dry_runs: u64,
+    pub contended: u64,
+    pub failed: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LiquidationCandidate {
+    pub loan_pda: String,
+    pub asset_id: String,
+    #[serde(flatten)]
+    pub trigger: LiquidationTrigger,
+    /// `liquidated`, `dry_run`, `contended` or `failed`.
+    pub outcome: &'static str,
+    pub signature: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanReport {
+    pub started_at: i64,
+    pub finished_at: i64,
+    pub loans_scanned: usize,
+    pub candidates: Vec<LiquidationCandidate>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LiquidatorStatus {
+    pub mode: LiquidatorMode,
+    pub interval_secs: u64,
+    pub concurrency: usize,
+    pub metrics: LiquidatorMetrics,
+    pub last_scan: Option<ScanReport>,
+}
+
+// ==================== Liquidator ====================
+pub struct Liquidator {
+    mode: LiquidatorMode,
+    interval: Duration,
+    concurrency: usize,
+    counters: Counters,
+    last_scan: RwLock<Option<ScanReport>>,
+}
+
+impl Liquidator {
+    /// `LIQUIDATOR_MODE` is off (default), dry_run or execute;
+    /// `LIQUIDATOR_INTERVAL_SECS` (default 60) spaces the scans and
+    /// `LIQUIDATOR_CONCURRENCY` (default 4) caps liquidations in flight.
+    pub fn for_network(network: &Network) -> Self {
+        let mode = match network.var("LIQUIDATOR_MODE") {
+            Some(value) => LiquidatorMode::parse(&value).unwrap_or_else(|| {
+                tracing::warn!("⚠️ Unknown LIQUIDATOR_MODE {:?}, liquidator off", value);
+                LiquidatorMode::Off
+            }),
+            None => LiquidatorMode::Off,
+        };
+        let interval = env::var("LIQUIDATOR_INTERVAL_SECS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_INTERVAL_SECS);
+        let concurrency = env::var("LIQUIDATOR_CONCURRENCY")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_CONCURRENCY);
+        Self {
+            mode,
+            interval: Duration::from_secs(interval.max(1)),
+            concurrency: concurrency.max(1),
+            counters: Counters::default(),
+            last_scan: RwLock::new(None),
+        }
+    }
+
+    pub fn status(&self) -> LiquidatorStatus {
+        let count = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+        LiquidatorStatus {
+            mode: self.mode,
+            interval_secs: self.interval.as_secs(),
+            concurrency: self.concurrency,
+            metrics: LiquidatorMetrics {
+                scans: count(&self.counters.scans),
+                failed_scans: count(&self.counters.failed_scans),
+                eligible: count(&self.counters.eligible),
+                liquidated: count(&self.counters.liquidated),
+                dry_runs: count(&self.counters.dry_runs),
+                contended: count(&self.counters.contended),
+                failed: count(&self.counters.failed),
+            },
+            last_scan: self.last_scan.read().unwrap().clone(),
+        }
+    }
+
+    /// Evaluate every open loan against its collateral and act on the
+    /// eligible ones as `mode` says.
+    pub async fn scan(&self, solana: &SolanaService, coordination: &Coordination) -> Result<ScanReport> {
+        self.counters.scans.fetch_add(1, Ordering::Relaxed);
+        let started_at = chrono::Utc::now().timestamp();
+        let loans = solana.list_loans().await?;
+        let assets: HashMap<Pubkey, AssetAccount> = solana.fetch_assets(None).await?
+            .into_iter()
+            .map(|asset| (solana.asset_address(&asset.asset_id).0, asset))
+            .collect();
+
+        let loans_scanned = loans.len();
+        let eligible: Vec<(Pubkey, String, LiquidationTrigger)> = loans.iter()
+            .filter_map(|(loan_pda, loan)| {
+                let asset = assets.get(&loan.asset)?;
+                // The program refuses a pledged top-up that isn't supplied
+                let supplemental = match loan.supplemental_asset == Pubkey::default() {
+                    true => None,
+                    false => Some(assets.get(&loan.supplemental_asset)?),
+                };
+                let trigger = solana_client::liquidation_trigger(loan, asset, supplemental, started_at)?;
+                Some((*loan_pda, asset.asset_id.clone(), trigger))
+            })
+            .collect();
+        self.counters.eligible.fetch_add(eligible.len() as u64, Ordering::Relaxed);
+
+        let candidates = futures::stream::iter(eligible)
+            .map(|(loan_pda, asset_id, trigger)| self.act(solana, coordination, loan_pda, asset_id, trigger))
+            .buffer_unordered(self.concurrency)
+            .collect::<Vec<_>>()
+            .await;
+
+        let report = ScanReport {
+            started_at,
+            finished_at: chrono::Utc::now().timestamp(),
+            loans_scanned,
+            candidates,
+        };
+        *self.last_scan.write().unwrap() = Some(report.clone());
+        Ok(report)
+    }
+
+    async fn act(
+        &self,
+        solana: &SolanaService,
+        coordination: &Coordination,
+        loan_pda: Pubkey,
+        asset_id: String,
+        trigger: LiquidationTrigger,
+    ) -> LiquidationCandidate {
+        let mut candidate = LiquidationCandidate {
+            loan_pda: loan_pda.to_string(),
+            asset_id,
+            trigger,
+            outcome: "dry_run",
+            signature: None,
+            error: None,
+        };
+        if self.mode != LiquidatorMode::Execute {
+            tracing::info!("🔎 Loan {} is eligible for liquidation ({:?}), dry run", loan_pda, trigger);
+            self.counters.dry_runs.fetch_add(1, Ordering::Relaxed);
+            return candidate;
+        }
+
+        // The same lock the liquidate route takes, so replicas and API
+        // callers don't race the scanner for one loan
+        let lock = format!("liquidate:{}", loan_pda);
+        match coordination.exclusive(&lock, solana.liquidate_loan(loan_pda)).await {
+            Ok(Ok(signature)) => {
+                tracing::info!("✅ Liquidated loan {} ({:?}): {}", loan_pda, trigger, signature);
+                self.counters.liquidated.fetch_add(1, Ordering::Relaxed);
+                candidate.outcome = "liquidated";
+                candidate.signature = Some(signature);
+            }
+            Err(ApiError::Conflict(_)) => {
+                self.counters.contended.fetch_add(1, Ordering::Relaxed);
+                candidate.outcome = "contended";
+            }
+            Ok(Err(e)) => {
+                tracing::error!("❌ Failed to liquidate loan {}: {}", loan_pda, e);
+                self.counters.failed.fetch_add(1, Ordering::Relaxed);
+                candidate.outcome = "failed";
+                candidate.error = Some(e.to_string());
+            }
+            Err(e) => {
+                tracing::error!("❌ Failed to liquidate loan {}: {}", loan_pda, e.message());
+                self.counters.failed.fetch_add(1, Ordering::Relaxed);
+                candidate.outcome = "failed";
+                candidate.error = Some(e.message());
+            }
+        }
+        candidate
+    }
+}
+
+/// Scan every `LIQUIDATOR_INTERVAL_SECS` unless the liquidator is off.
+pub fn spawn(liquidator: Arc<Liquidator>, solana: Arc<SolanaService>, coordination: Arc<Coordination>) {
+    if liquidator.mode == LiquidatorMode::Off {
+        return;
+    }
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(liquidator.interval);
+        loop {
+            ticker.tick().await;
+            match liquidator.scan(&solana, &coordination).await {
+                Ok(report) if !report.candidates.is_empty() => tracing::info!(
+                    "🔎 Liquidation scan: {} of {} loans eligible",
+                    report.candidates.len(), report.loans_scanned,
+                ),
+                Ok(_) => {}
+                Err(e) => {
+                    liquidator.counters.failed_scans.fetch_add(1, Ordering::Relaxed);
+                    tracing::warn!("⚠️ Liquidation scan failed: {}", e);
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_modes() {
+        assert_eq!(LiquidatorMode::parse("execute"), Some(LiquidatorMode::Execute));
+        assert_eq!(LiquidatorMode::parse(" Dry-Run "), Some(LiquidatorMode::DryRun));
+        assert_eq!(LiquidatorMode::parse("OFF"), Some(LiquidatorMode::Off));
+        assert_eq!(LiquidatorMode::parse("yes"), None);
+    }
+}
@@ -0,0 +1,283 @@
+use serde::Serialize;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::account_index::AccountIndex;
+use crate::events::ProgramEvent;
+use crate::routes::AppState;
+use crate::solana_client::{ChainUpdate, LoanStatus, SolanaService};
+use crate::visibility::Role;
+
+// Events buffered per client before the slowest starts missing them
+const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+// ==================== Live Events ====================
+/// A change clients are pushed as it lands, derived from the pubsub
+/// account and log updates. Addresses are base58.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LiveEvent {
+    RiskUpdated {
+        asset_id: String,
+        asset: String,
+        risk_score: u8,
+        /// None when the backend hadn't seen the asset's score yet.
+        previous_risk_score: Option<u8>,
+        slot: u64,
+    },
+    LoanCreated { loan: String, asset: String, borrower: String, principal: u64, slot: u64 },
+    LoanRepaid { loan: String, asset: String, borrower: String, slot: u64 },
+    /// Queued for liquidation, or liquidated.
+    LoanLiquidation { loan: String, asset: String, borrower: String, status: LoanStatus, slot: u64 },
+    /// Any other loan status change.
+    LoanUpdated {
+        loan: String,
+        asset: String,
+        borrower: String,
+        status: LoanStatus,
+        previous_status: Option<LoanStatus>,
+        slot: u64,
+    },
+    /// An event the program emitted, attributed to the asset and loan it
+    /// names, or the loan's asset and borrower.
+    ProgramEvent {
+        signature: String,
+        asset: Option<String>,
+        loan: Option<String>,
+        borrower: Option<String>,
+        event: ProgramEvent,
+        slot: u64,
+    },
+}
+
+impl LiveEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            LiveEvent::RiskUpdated { .. } => "risk_updated",
+            LiveEvent::LoanCreated { .. } => "loan_created",
+            LiveEvent::LoanRepaid { .. } => "loan_repaid",
+            LiveEvent::LoanLiquidation { .. } => "loan_liquidation",
+            LiveEvent::LoanUpdated { .. } => "loan_updated",
+            LiveEvent::ProgramEvent { .. } => "program_event",
+        }
+    }
+
+    /// Address of the asset the event concerns, if known.
+    pub fn asset(&self) -> Option<&str> {
+        match self {
+            LiveEvent::RiskUpdated { asset, .. }
+            | LiveEvent::LoanCreated { asset, .. }
+            | LiveEvent::LoanRepaid { asset, .. }
+            | LiveEvent::LoanLiquidation { asset, .. }
+            | LiveEvent::LoanUpdated { asset, .. } => Some(asset),
+            LiveEvent::ProgramEvent { asset, .. } => asset.as_deref(),
+        }
+    }
+
+    /// Address of the borrower of the loan the event concerns, if known.
+    pub fn borrower(&self) -> Option<&str> {
+        match self {
+            LiveEvent::RiskUpdated { .. } => None,
+            LiveEvent::LoanCreated { borrower, .. }
+            | LiveEvent::LoanRepaid { borrower, .. }
+            | LiveEvent::LoanLiquidation { borrower, .. }
+            | LiveEvent::LoanUpdated { borrower, .. } => Some(borrower),
+            LiveEvent::ProgramEvent { borrower, .. } => borrower.as_deref(),
+        }
+    }
+
+    /// A loan queued for or put through liquidation, or collateral seized.
+    pub fn is_liquidation(&self) -> bool {
+        matches!(
+            self,
+            LiveEvent::LoanLiquidation { .. }
+                | LiveEvent::ProgramEvent { event: ProgramEvent::CollateralSeized(_), .. }
+        )
+    }
+}
+
+// ==================== Channels ====================
+/// A subscription to live events: `asset:{id}`, `borrower:{pubkey}` or
+/// `liquidations`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Channel {
+    /// By asset id, matched on the asset's address.
+    Asset(String),
+    Borrower(String),
+    Liquidations,
+}
+
+impl Channel {
+    pub fn parse(name: &str, state: &AppState, role: Role) -> Result<Self, String> {
+        match name.split_once(':') {
+            Some(("asset", asset_id)) if !asset_id.is_empty() => {
+                Ok(Channel::Asset(state.solana.asset_address(asset_id).0.to_string()))
+            }
+            Some(("borrower", borrower)) => {
+                if !role.can_see("borrower") {
+                    return Err("Borrower channels are restricted for this role".to_string());
+                }
+                let borrower = Pubkey::from_str(borrower).map_err(|_| format!("Invalid borrower: {}", borrower))?;
+                Ok(Channel::Borrower(borrower.to_string()))
+            }
+            None if name == "liquidations" => Ok(Channel::Liquidations),
+            _ => Err(format!("Unknown channel: {}", name)),
+        }
+    }
+
+    pub fn matches(&self, event: &LiveEvent) -> bool {
+        match self {
+            Channel::Asset(asset) => event.asset() == Some(asset.as_str()),
+            Channel::Borrower(borrower) => event.borrower() == Some(borrower.as_str()),
+            Channel::Liquidations => event.is_liquidation(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct LoanSeen {
+    asset: String,
+    borrower: String,
+    status: LoanStatus,
+}
+
+/// What the hub has seen so far, to tell a change from a repeat.
+#[derive(Default)]
+struct Seen {
+    /// Risk score by asset address.
+    risk_scores: HashMap<String, u8>,
+    /// By loan address.
+    loans: HashMap<String, LoanSeen>,
+}
+
+// ==================== Event Hub ====================
+/// Turns one network's raw pubsub updates into `LiveEvent`s, once for every
+/// client: the SSE and WebSocket streams each subscribe and filter.
+pub struct EventHub {
+    events: broadcast::Sender<LiveEvent>,
+    seen: Mutex<Seen>,
+}
+
+impl Default for EventHub {
+    fn default() -> Self {
+        Self {
+            events: broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
+            seen: Mutex::new(Seen::default()),
+        }
+    }
+}
+
+impl EventHub {
+    pub fn subscribe(&self) -> broadcast::Receiver<LiveEvent> {
+        self.events.subscribe()
+    }
+
+    fn derive(&self, update: ChainUpdate, index: &AccountIndex) -> Vec<LiveEvent> {
+        let mut seen = self.seen.lock().unwrap();
+        match update {
+            ChainUpdate::Asset { address, slot, asset } => {
+                // Before the first pubsub sighting, the index knows the last score
+                let previous = seen.risk_scores.get(&address).copied()
+                    .or_else(|| index.asset(&asset.asset_id).map(|indexed| indexed.risk_score));
+                seen.risk_scores.insert(address.clone(), asset.risk_score);
+                if previous == Some(asset.risk_score) {
+                    return Vec::new();
+                }
+                vec![LiveEvent::RiskUpdated {
+                    asset_id: asset.asset_id,
+                    asset: address,
+                    risk_score: asset.risk_score,
+                    previous_risk_score: previous,
+                    slot,
+                }]
+            }
+            ChainUpdate::Loan { address, slot, loan } => {
+                let previous = seen.loans
+                    .insert(address.clone(), LoanSeen {
+                        asset: loan.asset.clone(),
+                        borrower: loan.borrower.clone(),
+                        status: loan.status,
+                    })
+                    .map(|seen| seen.status);
+                if previous == Some(loan.status) {
+                    return Vec::new();
+                }
+                let (asset, borrower) = (loan.asset, loan.borrower);
+                vec![match loan.status {
+                    LoanStatus::Pending if previous.is_none() => LiveEvent::LoanCreated {
+                        loan: address, asset, borrower, principal: loan.principal, slot,
+                    },
+                    LoanStatus::Repaid => LiveEvent::LoanRepaid { loan: address, asset, borrower, slot },
+                    LoanStatus::Liquidating | LoanStatus::Liquidated => LiveEvent::LoanLiquidation {
+                        loan: address, asset, borrower, status: loan.status, slot,
+                    },
+                    status => LiveEvent::LoanUpdated {
+                        loan: address, asset, borrower, status, previous_status: previous, slot,
+                    },
+                }]
+            }
+            // Events of failed transactions were rolled back with them
+            ChainUpdate::Logs { error: Some(_), .. } => Vec::new(),
+            ChainUpdate::Logs { signature, slot, events, .. } => events
+                .into_iter()
+                .map(|event| {
+                    let (asset, loan) = event.subjects();
+                    let loan = loan.map(|loan| loan.to_string());
+                    let loan_seen = loan.as_deref().and_then(|loan| seen.loans.get(loan));
+                    let asset = asset.map(|asset| asset.to_string())
+                        .or_else(|| loan_seen.map(|seen| seen.asset.clone()));
+                    let borrower = loan_seen.map(|seen| seen.borrower.clone());
+                    LiveEvent::ProgramEvent { signature: signature.clone(), asset, loan, borrower, event, slot }
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Feed the hub from the network's pubsub updates for as long as they flow.
+pub fn spawn(hub: Arc<EventHub>, solana: Arc<SolanaService>, index: Arc<AccountIndex>) {
+    let mut updates = solana.subscribe_updates();
+    tokio::spawn(async move {
+        loop {
+            match updates.recv().await {
+                Ok(update) => {
+                    for event in hub.derive(update, &index) {
+                        // Nobody listening is not an error; the event is simply dropped
+                        let _ = hub.events.send(event);
+                    }
+                }
+                Err(RecvError::Lagged(missed)) => tracing::warn!("⚠️ Live events skipped {} pubsub updates", missed),
+                Err(RecvError::Closed) => return,
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn liquidations_channel_matches_only_liquidations() {
+        let liquidation = LiveEvent::LoanLiquidation {
+            loan: "loan".to_string(),
+            asset: "asset".to_string(),
+            borrower: "borrower".to_string(),
+            status: LoanStatus::Liquidating,
+            slot: 1,
+        };
+        let repaid = LiveEvent::LoanRepaid {
+            loan: "loan".to_string(),
+            asset: "asset".to_string(),
+            borrower: "borrower".to_string(),
+            slot: 2,
+        };
+        assert!(Channel::Liquidations.matches(&liquidation));
+        assert!(!Channel::Liquidations.matches(&repaid));
+        assert!(Channel::Borrower("borrower".to_string()).matches(&repaid));
+        assert!(!Channel::Asset("other".to_string()).matches(&repaid));
+    }
+}
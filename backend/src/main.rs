@@ -1,15 +1,72 @@
 mod routes;
+mod error;
+mod validation;
 mod solana_client;
 mod chainlink_client;
+mod data_sharing;
+mod visibility;
+mod storage;
+mod idl_sync;
+mod webhooks;
+mod read_routing;
+mod screening;
+mod capacity;
+mod portfolio;
+mod health;
+mod risk_reports;
+mod priority_fees;
+mod jito;
+mod account_cache;
+mod account_index;
+mod live_events;
+mod risk_history;
+mod pagination;
+mod events;
+mod idl;
+mod signer;
+mod commitment;
+mod wallet_monitor;
+mod liquidator;
+mod notifier;
+mod network;
+mod http_security;
+mod audit;
+mod auth;
+mod rate_limit;
+mod tenants;
+mod coordination;
+mod openapi;
+mod grpc;
+mod ws;
 
+use std::collections::HashMap;
+use std::net::SocketAddr;
 use std::sync::Arc;
+use axum::{middleware, Router};
 use dotenv::dotenv;
-use tracing_subscriber;
 use std::env;
 
 use routes::{AppState, create_router};
 use solana_client::SolanaService;
 use chainlink_client::ChainlinkService;
+use visibility::RoleRegistry;
+use storage::{MigrationMode, Storage};
+use idl_sync::IdlSync;
+use webhooks::WebhookRegistry;
+use read_routing::ReadPlanner;
+use screening::Screener;
+use wallet_monitor::WalletMonitor;
+use liquidator::Liquidator;
+use notifier::Notifier;
+use health::HealthProbes;
+use account_index::AccountIndex;
+use live_events::EventHub;
+use network::Network;
+use http_security::HttpSecurity;
+use auth::Authenticator;
+use rate_limit::RateLimiter;
+use tenants::TenantRegistry;
+use coordination::Coordination;
 
 #[tokio::main]
 async fn main() {
@@ -23,25 +80,167 @@ async fn main() {
 
     tracing::info!("🚀 Starting RWA Backend Service");
 
-    // Initialize services
-    let solana = match SolanaService::new().await {
-        Ok(service) => {
-            tracing::info!("✅ Solana service initialized");
-            Arc::new(service)
-        },
+    let networks = match Network::all_from_env() {
+        Ok(networks) => networks,
         Err(e) => {
-            tracing::error!("❌ Failed to initialize Solana service: {}", e);
+            tracing::error!("❌ Invalid network configuration: {}", e);
             std::process::exit(1);
         }
     };
     
+    // `backend migrate` applies pending migrations to every network's storage and exits
+    if env::args().nth(1).as_deref() == Some("migrate") {
+        let mut failed = false;
+        for network in &networks {
+            match Storage::connect(network, MigrationMode::Apply).await {
+                Ok(storage) => tracing::info!("✅ Storage migrated for {} ({})", network.name, storage.backend),
+                Err(e) => {
+                    tracing::error!("❌ Failed to migrate storage for {}: {}", network.name, e);
+                    failed = true;
+                }
+            }
+        }
+        std::process::exit(i32::from(failed));
+    }
+    
+    // Shared by every network
     let chainlink = Arc::new(ChainlinkService::new());
     tracing::info!("✅ Chainlink service initialized");
     
-    let state = AppState { solana, chainlink };
-
-    // Build router
-    let app = create_router(state);
+    let roles = Arc::new(RoleRegistry::from_env());
+    
+    let auth = match Authenticator::from_env() {
+        Ok(auth) => Arc::new(auth),
+        Err(e) => {
+            tracing::error!("❌ Invalid auth configuration: {}", e);
+            std::process::exit(1);
+        }
+    };
+    
+    let tenants = match TenantRegistry::from_env() {
+        Ok(tenants) => Arc::new(tenants),
+        Err(e) => {
+            tracing::error!("❌ Invalid tenant configuration: {}", e);
+            std::process::exit(1);
+        }
+    };
+    
+    let security = match HttpSecurity::from_env() {
+        Ok(security) => Arc::new(security),
+        Err(e) => {
+            tracing::error!("❌ Invalid CORS configuration: {}", e);
+            std::process::exit(1);
+        }
+    };
+    
+    let limiter = Arc::new(RateLimiter::from_env());
+    rate_limit::spawn(limiter.clone());
+    
+    let webhooks = Arc::new(WebhookRegistry::from_env());
+    
+    let reads = Arc::new(ReadPlanner::from_env());
+    
+    let screener = Arc::new(Screener::from_env());
+    
+    let health = Arc::new(HealthProbes::from_env());
+    
+    let redis = match coordination::connect_redis().await {
+        Ok(redis) => redis,
+        Err(e) => {
+            tracing::error!("❌ {}", e);
+            std::process::exit(1);
+        }
+    };
+    
+    // Build router: the default network answers unprefixed routes, and
+    // every network its own under /<name>
+    let mut app = Router::new();
+    let mut states = HashMap::new();
+    for network in &networks {
+        let solana = match SolanaService::new(network).await {
+            Ok(service) => {
+                tracing::info!("✅ Solana service initialized for {}", network.name);
+                Arc::new(service)
+            },
+            Err(e) => {
+                tracing::error!("❌ Failed to initialize Solana service for {}: {}", network.name, e);
+                std::process::exit(1);
+            }
+        };
+        
+        solana_client::spawn_subscriptions(solana.clone());
+        
+        let storage = match Storage::for_network(network).await {
+            Ok(storage) => {
+                tracing::info!("✅ Storage initialized for {} ({})", network.name, storage.backend);
+                Arc::new(storage)
+            },
+            Err(e) => {
+                tracing::error!("❌ Failed to initialize storage for {}: {}", network.name, e);
+                std::process::exit(1);
+            }
+        };
+        
+        let idl_sync = Arc::new(IdlSync::from_env());
+        idl_sync::spawn(idl_sync.clone(), solana.clone());
+        
+        screening::spawn(screener.clone(), storage.clone());
+        
+        let wallet = Arc::new(WalletMonitor::from_env());
+        wallet_monitor::spawn(wallet.clone(), solana.clone(), webhooks.clone());
+        
+        let index = Arc::new(AccountIndex::from_env());
+        account_index::spawn(index.clone(), solana.clone());
+        
+        let events = Arc::new(EventHub::default());
+        live_events::spawn(events.clone(), solana.clone(), index.clone());
+        risk_history::spawn(storage.clone(), events.clone());
+        
+        let coordination = Arc::new(Coordination::new(network, redis.clone()));
+        tracing::info!("✅ Coordination initialized for {} ({})", network.name, coordination.backend());
+        coordination::spawn(coordination.clone(), events.clone());
+        
+        let liquidator = Arc::new(Liquidator::for_network(network));
+        liquidator::spawn(liquidator.clone(), solana.clone(), coordination.clone());
+        
+        let notifier = Arc::new(Notifier::from_env());
+        notifier::spawn(notifier.clone(), events.clone(), solana.clone(), storage.clone());
+        
+        let state = AppState {
+            solana,
+            chainlink: chainlink.clone(),
+            roles: roles.clone(),
+            auth: auth.clone(),
+            limiter: limiter.clone(),
+            storage,
+            idl_sync,
+            webhooks: webhooks.clone(),
+            reads: reads.clone(),
+            screener: screener.clone(),
+            wallet,
+            index,
+            events,
+            coordination,
+            liquidator,
+            notifier,
+            health: health.clone(),
+            tenants: tenants.clone(),
+        };
+        states.insert(network.name.clone(), state.clone());
+        let router = create_router(state);
+        if network.is_default {
+            app = app.merge(router.clone());
+        }
+        app = app.nest(&format!("/{}", network.name), router);
+    }
+    
+    grpc::spawn(states, networks[0].name.clone());
+    
+    // X-Network selects a network without the path prefix
+    let app = Router::new()
+        .fallback_service(app)
+        .layer(middleware::from_fn(network::select_by_header));
+    let app = security.apply(app);
 
     // Start server
     let port = env::var("PORT").unwrap_or_else(|_| "3001".to_string());
@@ -50,5 +249,5 @@ async fn main() {
     let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
     tracing::info!("📡 Server listening on http://{}", addr);
     
-    axum::serve(listener, app).await.unwrap();
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await.unwrap();
 }
@@ -1,3 +1,11 @@
 pub mod routes;
 pub mod solana_client;
 pub mod chainlink_client;
+pub mod data_sharing;
+pub mod visibility;
+pub mod storage;
+pub mod idl_sync;
+pub mod webhooks;
+pub mod read_routing;
+pub mod screening;
+pub mod capacity;
\ No newline at end of file
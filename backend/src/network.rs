@@ -0,0 +1,105 @@
+use anyhow::{anyhow, Result};
+use axum::{
+    extract::Request,
+    http::{HeaderName, Uri},
+    middleware::Next,
+    response::Response,
+};
+use std::env;
+
+use crate::signer::SignerSource;
+
+pub const NETWORK_HEADER: &str = "x-network";
+const DEFAULT_NETWORK: &str = "devnet";
+
+// ==================== Networks ====================
+/// A cluster the backend serves, with its own RPC endpoints, program
+/// deployment, payer and storage mirror.
+///
+/// A network's settings are the usual variables prefixed with its
+/// upper-cased name (`MAINNET_SOLANA_RPC_URL`, `MAINNET_PROGRAM_ID`). The
+/// default network, the first listed, also reads them unprefixed, so a
+/// single-network deployment needs no prefixes at all.
+#[derive(Debug, Clone)]
+pub struct Network {
+    pub name: String,
+    pub is_default: bool,
+}
+
+impl Network {
+    /// `NETWORKS` lists the networks served (`devnet,mainnet`); unset, the
+    /// backend serves the single network `SOLANA_CLUSTER` (default devnet).
+    pub fn all_from_env() -> Result<Vec<Network>> {
+        let names = env::var("NETWORKS")
+            .or_else(|_| env::var("SOLANA_CLUSTER"))
+            .unwrap_or_else(|_| DEFAULT_NETWORK.to_string());
+        let mut networks: Vec<Network> = Vec::new();
+        for name in names.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+            if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+                return Err(anyhow!("Invalid network name: {}", name));
+            }
+            if networks.iter().any(|network| network.name == name) {
+                return Err(anyhow!("Network {} is listed twice", name));
+            }
+            networks.push(Network { name: name.to_string(), is_default: networks.is_empty() });
+        }
+        if networks.is_empty() {
+            return Err(anyhow!("NETWORKS names no network"));
+        }
+        Ok(networks)
+    }
+
+    /// The network's value for `name`: prefixed first, then unprefixed for
+    /// the default network only, so one network's payer or program never
+    /// leaks into another's.
+    pub fn var(&self, name: &str) -> Option<String> {
+        let prefix = self.name.to_uppercase().replace('-', "_");
+        env::var(format!("{}_{}", prefix, name)).ok()
+            .or_else(|| self.is_default.then(|| env::var(name).ok()).flatten())
+    }
+
+    /// Where this network's payer key lives. `SIGNER_BACKEND` picks the
+    /// backend as for a single network; the key itself comes from
+    /// `WALLET_KEYPAIR_PATH`, `KMS_KEY_ID`, `VAULT_TRANSIT_KEY` or
+    /// `LEDGER_DERIVATION_PATH`. Credentials for KMS and Vault are shared.
+    pub fn signer_source(&self) -> Result<SignerSource> {
+        if self.is_default && self.var("SIGNER_BACKEND").is_none() {
+            return SignerSource::from_env();
+        }
+        let backend = self.var("SIGNER_BACKEND").unwrap_or_else(|| "keypair".to_string());
+        let key = |name: &str| self.var(name).ok_or_else(|| anyhow!("Network {} needs {}_{}", self.name, self.name.to_uppercase(), name));
+        match backend.as_str() {
+            "keypair" if self.is_default => Ok(SignerSource::Keypair { path: self.var("WALLET_KEYPAIR_PATH") }),
+            "keypair" => Ok(SignerSource::Keypair { path: Some(key("WALLET_KEYPAIR_PATH")?) }),
+            "kms" => Ok(SignerSource::Kms { key_id: Some(key("KMS_KEY_ID")?) }),
+            "vault" => Ok(SignerSource::Vault { key: Some(key("VAULT_TRANSIT_KEY")?) }),
+            "ledger" => Ok(SignerSource::Ledger { derivation_path: self.var("LEDGER_DERIVATION_PATH") }),
+            other => Err(anyhow!("Unknown SIGNER_BACKEND for {}: {}", self.name, other)),
+        }
+    }
+}
+
+// ==================== Network Selection ====================
+/// Route a request carrying `X-Network: <name>` to that network's routes,
+/// as if it had been sent under the `/<name>` prefix.
+pub async fn select_by_header(mut request: Request, next: Next) -> Response {
+    let network = request.headers()
+        .get(HeaderName::from_static(NETWORK_HEADER))
+        .and_then(|value| value.to_str().ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty());
+    if let Some(network) = network {
+        let prefix = format!("/{}", network);
+        let path_and_query = request.uri().path_and_query().map_or("/", |path| path.as_str());
+        // Already addressed to the network by path
+        let prefixed = path_and_query == prefix
+            || path_and_query.starts_with(&format!("{}/", prefix))
+            || path_and_query.starts_with(&format!("{}?", prefix));
+        if !prefixed {
+            if let Ok(uri) = format!("{}{}", prefix, path_and_query).parse::<Uri>() {
+                *request.uri_mut() = uri;
+            }
+        }
+    }
+    next.run(request).await
+}
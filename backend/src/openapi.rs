@@ -0,0 +1,115 @@
+use axum::response::Html;
+use axum::routing::get;
+use axum::{Json, Router};
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+
+use crate::commitment::Commitment;
+use crate::error::{ErrorCode, ErrorResponse};
+use crate::idl::ProgramError;
+use crate::pagination::Order;
+use crate::routes::*;
+use crate::solana_client::{FloatingRateTerms, InterestMode, LoanProduct, LoanStatus, PayoffQuote};
+use crate::validation::FieldError;
+
+// ==================== API Contract ====================
+/// The OpenAPI document, generated from the handler and type annotations in
+/// `routes`; a route missing from `paths` here is missing from the spec.
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "RWA Backend", description = "Real-world asset collateral and lending on Solana"),
+    paths(
+        health_check, health_live, health_ready, create_asset, create_assets, get_asset, list_assets, stream_asset, update_risk,
+        post_risk_report,
+        apply_risk_report_entry, deactivate_asset, reactivate_asset, set_delegate, transfer_asset,
+        revoke_delegate, get_latest_risk, create_loan, preview_loan, submit_transaction,
+        rotate_payer, get_wallet, get_liquidator, create_nonce_account, get_nonce_account,
+        close_nonce_account, get_audit_log,
+        create_lookup_table, get_lookup_table, extend_lookup_table, devnet_airdrop,
+        compose_transaction, estimate_asset, estimate_loan, asset_pda, loan_pda,
+        get_transaction_status, get_owner_portfolio, get_loan, list_loans, approve_loan, cancel_loan_request,
+        repay_loan, liquidate_loan, get_loan_schedule, get_loan_payoff, chainlink_webhook, get_risk_history,
+        get_research_loan_book, get_stats, get_blacklist, get_blacklist_status, add_to_blacklist,
+        remove_from_blacklist, get_screening_history, simulate_capacity, get_webhook_events,
+        send_test_webhook, get_idl_status, list_oracles, add_oracle, remove_oracle,
+        list_subscribers, add_subscriber, remove_subscriber, crate::ws::upgrade,
+    ),
+    components(schemas(
+        CreateAssetRequest, CreateAssetResponse, BatchCreateAssetRequest, BatchAssetResult,
+        BatchCreateAssetResponse, UpdateRiskRequest, RiskReportRequest,
+        RiskReportEntry, RiskReportResponse, ProvenRiskEntry, UpdateRiskResponse,
+        AssetStatusResponse, SetDelegateRequest, DelegateResponse, TransferAssetRequest,
+        TransferAssetResponse, AssetResponse, AssetSort, AssetListResponse, CreateLoanRequest,
+        CreateLoanResponse, PayerRotationResponse, WalletResponse, LiquidatorResponse, AirdropRequest, AirdropResponse,
+        ComposeStep, ComposeRequest, ComposeResponse, PdaResponse, TransactionStatusResponse,
+        RepayLoanResponse, EstimateResponse, LoanPreviewResponse, NonceAccountResponse,
+        LookupTableRequest, LookupTableResponse, SubmitTransactionRequest,
+        SubmitTransactionResponse, LoanActionResponse, LoanResponse, LoanSort, LoanListResponse,
+        ScheduleResponse, PayoffResponse, PortfolioResponse, RiskHistoryResponse, ChainlinkWebhookRequest, TestDeliveryRequest,
+        ScreeningHistoryResponse, BlacklistRequest, BlacklistResponse, BlacklistStatusResponse,
+        BlacklistUpdateResponse, AddOracleRequest, OraclesResponse, OracleUpdateResponse,
+        SubscriberRequest, SubscribersResponse, SubscriberUpdateResponse, AuditLogResponse,
+        StatsResponse, ErrorResponse, ErrorCode, FieldError,
+        Commitment, Order, ProgramError, FloatingRateTerms, InterestMode, LoanProduct, LoanStatus, PayoffQuote,
+    )),
+    modifiers(&Credentials),
+    security((), ("api_key" = []), ("bearer" = [])),
+    tags(
+        (name = "assets", description = "Asset registration, ownership and lifecycle"),
+        (name = "risk", description = "Risk score writes, reserved for the oracle role"),
+        (name = "loans", description = "Loan origination, servicing and listings"),
+        (name = "transactions", description = "Relaying and composing client-signed transactions"),
+        (name = "estimates", description = "Rent and fee estimates"),
+        (name = "pda", description = "Program-derived addresses"),
+        (name = "compliance", description = "Blacklist and sanctions screening"),
+        (name = "admin", description = "Payer, oracle, liquidator, notification, audit log, nonce and lookup table management"),
+        (name = "webhooks", description = "Outgoing webhook catalog and test deliveries"),
+        (name = "research", description = "Aggregate views and capacity planning"),
+        (name = "system", description = "Health, stats and IDL status"),
+    ),
+)]
+pub struct ApiDoc;
+
+/// The API key and bearer token schemes `auth` accepts.
+struct Credentials;
+
+impl Modify for Credentials {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme("api_key", SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("X-Api-Key"))));
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).bearer_format("JWT").build()),
+        );
+    }
+}
+
+/// Swagger UI page pointed at `/openapi.json`. The UI's assets come from a
+/// CDN rather than being bundled, so the page needs the browser to reach it.
+const DOCS_PAGE: &str = r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+  <meta charset="utf-8" />
+  <title>RWA Backend API</title>
+  <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@5/swagger-ui.css" />
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="https://unpkg.com/swagger-ui-dist@5/swagger-ui-bundle.js" crossorigin></script>
+  <script>
+    window.ui = SwaggerUIBundle({ url: "/openapi.json", dom_id: "#swagger-ui" });
+  </script>
+</body>
+</html>
+"##;
+
+/// `/openapi.json` and a Swagger UI at `/docs`.
+pub fn routes<S: Clone + Send + Sync + 'static>() -> Router<S> {
+    let spec = ApiDoc::openapi();
+    Router::new()
+        .route("/openapi.json", get(move || {
+            let spec = spec.clone();
+            async move { Json(spec) }
+        }))
+        .route("/docs", get(|| async { Html(DOCS_PAGE) }))
+}
@@ -0,0 +1,153 @@
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD as BASE64, Engine as _};
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+pub const DEFAULT_LIMIT: usize = 50;
+pub const MAX_LIMIT: usize = 200;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Order {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl Order {
+    fn as_str(self) -> &'static str {
+        match self {
+            Order::Asc => "asc",
+            Order::Desc => "desc",
+        }
+    }
+}
+
+// ==================== Cursors ====================
+/// Where a page ended: the sort key and id of its last item. Items are
+/// ordered by (key, id), so a cursor stays valid when accounts are created
+/// or closed between pages, which an offset would not.
+///
+/// Encoded as opaque url-safe base64 of `sort:order:key:id`; a cursor
+/// only continues the listing it came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cursor {
+    sort: String,
+    order: Order,
+    key: i128,
+    id: String,
+}
+
+impl Cursor {
+    pub fn encode(&self) -> String {
+        BASE64.encode(format!("{}:{}:{}:{}", self.sort, self.order.as_str(), self.key, self.id))
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self> {
+        let bytes = BASE64.decode(cursor).map_err(|_| anyhow!("Malformed cursor"))?;
+        let text = String::from_utf8(bytes).map_err(|_| anyhow!("Malformed cursor"))?;
+        let mut parts = text.splitn(4, ':');
+        let (Some(sort), Some(order), Some(key), Some(id)) = (parts.next(), parts.next(), parts.next(), parts.next()) else {
+            return Err(anyhow!("Malformed cursor"));
+        };
+        let order = match order {
+            "asc" => Order::Asc,
+            "desc" => Order::Desc,
+            _ => return Err(anyhow!("Malformed cursor")),
+        };
+        let key = key.parse().map_err(|_| anyhow!("Malformed cursor"))?;
+        Ok(Self { sort: sort.to_string(), order, key, id: id.to_string() })
+    }
+
+    /// The end of a page of a listing the store pages itself.
+    pub fn new(sort: &str, order: Order, key: i128, id: impl Into<String>) -> Self {
+        Self { sort: sort.to_string(), order, key, id: id.into() }
+    }
+
+    /// The key and id to resume after, provided `cursor` came from the
+    /// listing sorted by `sort` in `order`.
+    pub fn resume(cursor: &str, sort: &str, order: Order) -> Result<(i128, String)> {
+        let cursor = Self::decode(cursor)?;
+        if cursor.sort != sort || cursor.order != order {
+            return Err(anyhow!("Cursor belongs to a listing sorted by {} {}", cursor.sort, cursor.order.as_str()));
+        }
+        Ok((cursor.key, cursor.id))
+    }
+}
+
+// ==================== Pages ====================
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Items matching before the cursor was applied.
+    pub total: usize,
+    /// Continues after `items`; None on the last page.
+    pub next_cursor: Option<String>,
+}
+
+/// One page of `items` sorted by `sort`, whose key and id `key` extracts,
+/// starting after `cursor`. `limit` defaults to `DEFAULT_LIMIT` and is
+/// capped at `MAX_LIMIT`.
+pub fn paginate<T, K>(
+    mut items: Vec<T>,
+    sort: &str,
+    order: Order,
+    cursor: Option<&str>,
+    limit: Option<usize>,
+    key: K,
+) -> Result<Page<T>>
+where
+    K: Fn(&T) -> (i128, &str),
+{
+    let limit = limit.unwrap_or(DEFAULT_LIMIT);
+    if limit == 0 || limit > MAX_LIMIT {
+        return Err(anyhow!("limit must be between 1 and {}", MAX_LIMIT));
+    }
+    let after = cursor.map(|cursor| Cursor::resume(cursor, sort, order)).transpose()?;
+
+    let total = items.len();
+    items.sort_by(|a, b| {
+        let ordering = key(a).cmp(&key(b));
+        if order == Order::Desc { ordering.reverse() } else { ordering }
+    });
+    let start = match &after {
+        Some((after_key, after_id)) => items.partition_point(|item| {
+            let (item_key, item_id) = key(item);
+            let ordering = (item_key, item_id).cmp(&(*after_key, after_id.as_str()));
+            if order == Order::Desc { ordering.is_ge() } else { ordering.is_le() }
+        }),
+        None => 0,
+    };
+    let mut items: Vec<T> = items.into_iter().skip(start).collect();
+    let next_cursor = (items.len() > limit).then(|| {
+        let (last_key, last_id) = key(&items[limit - 1]);
+        Cursor::new(sort, order, last_key, last_id).encode()
+    });
+    items.truncate(limit);
+
+    Ok(Page { items, total, next_cursor })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key<'a>(item: &'a (&'static str, i128)) -> (i128, &'a str) {
+        (item.1, item.0)
+    }
+
+    #[test]
+    fn cursor_resumes_after_last_item_when_items_change() {
+        let items = vec![("a", 30), ("b", 10), ("c", 20), ("d", 20)];
+        let first = paginate(items.clone(), "valuation", Order::Desc, None, Some(2), key).unwrap();
+        assert_eq!(first.items, vec![("a", 30), ("d", 20)]);
+        assert_eq!(first.total, 4);
+
+        // "a" closes and "e" lands before the cursor; neither shifts the next page
+        let changed = vec![("b", 10), ("c", 20), ("d", 20), ("e", 40)];
+        let second = paginate(changed, "valuation", Order::Desc, first.next_cursor.as_deref(), Some(2), key).unwrap();
+        assert_eq!(second.items, vec![("c", 20), ("b", 10)]);
+        assert!(second.next_cursor.is_none());
+
+        assert!(paginate(items, "risk_score", Order::Desc, first.next_cursor.as_deref(), None, key).is_err());
+    }
+}
@@ -0,0 +1,185 @@
+use serde::Serialize;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::solana_client::{outstanding_debt, AssetResponse, LoanAccount, LoanStatus};
+
+// ==================== Owner Portfolio ====================
+/// One wallet's positions: the assets it owns and the loans it has open
+/// against them, with what it owes as of `as_of`.
+#[derive(Debug, Clone, Serialize)]
+pub struct OwnerPortfolio {
+    pub owner: String,
+    pub as_of: i64,
+    pub asset_count: usize,
+    pub total_valuation: u64,
+    /// Assets a loan request, open loan or top-up pledge keeps from moving.
+    pub encumbered_assets: usize,
+    pub active_loans: usize,
+    /// Summed across loans in their own repayment mints, as the program's
+    /// stats sum principal.
+    pub total_outstanding_debt: u64,
+    pub assets: Vec<PortfolioAsset>,
+    pub loans: Vec<PortfolioLoan>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PortfolioAsset {
+    #[serde(flatten)]
+    pub asset: AssetResponse,
+    /// Open loans with this asset as primary collateral.
+    pub loans: Vec<String>,
+    pub outstanding_debt: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PortfolioLoan {
+    pub loan_pda: String,
+    /// Collateral's asset id; None if the wallet no longer owns it.
+    pub asset_id: Option<String>,
+    pub status: LoanStatus,
+    pub repayment_mint: String,
+    pub principal: u64,
+    pub outstanding_debt: u64,
+    pub end_time: i64,
+}
+
+/// Aggregate `owner`'s `assets` and the open loans among `loans`, pricing
+/// debt at `now`. `asset_address` maps an asset id to its PDA, which is how
+/// loans name their collateral.
+pub fn build(
+    owner: &Pubkey,
+    assets: Vec<AssetResponse>,
+    loans: Vec<(Pubkey, LoanAccount)>,
+    asset_address: impl Fn(&str) -> Pubkey,
+    now: i64,
+) -> OwnerPortfolio {
+    let mut assets: Vec<(Pubkey, PortfolioAsset)> = assets.into_iter()
+        .map(|asset| (asset_address(&asset.asset_id), PortfolioAsset { asset, loans: Vec::new(), outstanding_debt: 0 }))
+        .collect();
+
+    let mut open_loans = Vec::new();
+    for (loan_pda, loan) in loans {
+        if loan.borrower != *owner || !loan.status.is_open() {
+            continue;
+        }
+        // Past u64 the program itself can't settle the loan; count what it can
+        let debt = outstanding_debt(&loan, now).unwrap_or(u64::MAX);
+        let collateral = assets.iter_mut().find(|(address, _)| *address == loan.asset).map(|(_, asset)| asset);
+        let asset_id = collateral.map(|asset| {
+            asset.loans.push(loan_pda.to_string());
+            asset.outstanding_debt = asset.outstanding_debt.saturating_add(debt);
+            asset.asset.asset_id.clone()
+        });
+        open_loans.push(PortfolioLoan {
+            loan_pda: loan_pda.to_string(),
+            asset_id,
+            status: loan.status,
+            repayment_mint: loan.repayment_mint.to_string(),
+            principal: loan.principal,
+            outstanding_debt: debt,
+            end_time: loan.end_time,
+        });
+    }
+    open_loans.sort_by_key(|loan| loan.end_time);
+
+    let assets: Vec<PortfolioAsset> = assets.into_iter().map(|(_, asset)| asset).collect();
+    OwnerPortfolio {
+        owner: owner.to_string(),
+        as_of: now,
+        asset_count: assets.len(),
+        total_valuation: assets.iter().fold(0, |total, asset| total.saturating_add(asset.asset.valuation)),
+        encumbered_assets: assets.iter().filter(|asset| asset.asset.encumbered).count(),
+        active_loans: open_loans.len(),
+        total_outstanding_debt: open_loans.iter().fold(0, |total, loan| total.saturating_add(loan.outstanding_debt)),
+        assets,
+        loans: open_loans,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solana_client::{CollateralRules, InterestMode, LoanProduct};
+
+    fn asset(asset_id: &str, valuation: u64, active_loans: u32) -> AssetResponse {
+        AssetResponse {
+            asset_id: asset_id.to_string(),
+            asset_type: "real_estate".to_string(),
+            valuation,
+            metadata_uri: String::new(),
+            owner: String::new(),
+            is_active: true,
+            risk_score: 30,
+            last_update: 0,
+            active_loans,
+            encumbered: active_loans > 0,
+            jurisdiction: None,
+            portfolio: None,
+        }
+    }
+
+    fn loan(borrower: Pubkey, asset: Pubkey, status: LoanStatus) -> LoanAccount {
+        LoanAccount {
+            borrower,
+            asset,
+            principal: 100_000,
+            interest_rate: 0,
+            start_time: 0,
+            end_time: 1_000,
+            status,
+            risk_score_at_creation: 30,
+            bump: 255,
+            forborne_seconds: 0,
+            forbearance_start: 0,
+            forbearance_end: 0,
+            modification_count: 0,
+            lender: Pubkey::new_unique(),
+            request_expiry: 0,
+            repayment_mint: Pubkey::new_unique(),
+            supplemental_asset: Pubkey::default(),
+            written_off_amount: 0,
+            interest_mode: InterestMode::Simple,
+            disputed: false,
+            dispute_raised_by: Pubkey::default(),
+            dispute_raised_at: 0,
+            dispute_outcome: None,
+            dispute_resolved_at: 0,
+            late_fees: 0,
+            liquidation_eligible: false,
+            liquidator: Pubkey::default(),
+            liquidation_debt: 0,
+            referral: Pubkey::default(),
+            rollover_count: 0,
+            product: LoanProduct::Bullet,
+            floating_rate: None,
+            interest_checkpoint: 0,
+            checkpoint_seconds: 0,
+            last_repriced_at: 0,
+            collateral_rules: CollateralRules::default(),
+            supplemental_rules: CollateralRules::default(),
+            principal_repaid: 0,
+        }
+    }
+
+    #[test]
+    fn counts_only_the_owners_open_loans() {
+        let owner = Pubkey::new_unique();
+        let (house, boat) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let address = |asset_id: &str| if asset_id == "house" { house } else { boat };
+        let loans = vec![
+            (Pubkey::new_unique(), loan(owner, house, LoanStatus::Active)),
+            (Pubkey::new_unique(), loan(owner, boat, LoanStatus::Repaid)),
+            (Pubkey::new_unique(), loan(Pubkey::new_unique(), boat, LoanStatus::Active)),
+        ];
+
+        // Two days past maturity adds a 20 bps late fee
+        let portfolio = build(&owner, vec![asset("house", 500_000, 1), asset("boat", 80_000, 0)], loans, address, 1_000 + 2 * 86_400);
+        assert_eq!(portfolio.total_valuation, 580_000);
+        assert_eq!(portfolio.encumbered_assets, 1);
+        assert_eq!(portfolio.active_loans, 1);
+        assert_eq!(portfolio.total_outstanding_debt, 100_200);
+        assert_eq!(portfolio.assets[0].outstanding_debt, 100_200);
+        assert!(portfolio.assets[1].loans.is_empty());
+        assert_eq!(portfolio.loans[0].asset_id.as_deref(), Some("house"));
+    }
+}
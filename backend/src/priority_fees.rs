@@ -0,0 +1,70 @@
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::instruction::Instruction;
+use std::env;
+
+use crate::capacity::DEFAULT_COMPUTE_UNIT_LIMIT;
+
+/// Ceiling the runtime puts on a single transaction.
+const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+const DEFAULT_PERCENTILE: usize = 75;
+const DEFAULT_MAX_MICRO_LAMPORTS: u64 = 1_000_000;
+
+// ==================== Priority Fee Policy ====================
+/// Compute budget attached to every transaction the service sends.
+#[derive(Debug, Clone)]
+pub struct PriorityFees {
+    compute_unit_limit: Option<u32>,
+    fixed_price: Option<u64>,
+    percentile: usize,
+    max_price: u64,
+}
+
+impl PriorityFees {
+    /// `COMPUTE_UNIT_LIMIT` fixes the units requested per transaction, which
+    /// otherwise follow the runtime default of 200k per instruction.
+    /// `PRIORITY_FEE_MICRO_LAMPORTS` pins the unit price; without it the price
+    /// is the `PRIORITY_FEE_PERCENTILE` (default 75) of fees recently paid to
+    /// write the same accounts, capped at `PRIORITY_FEE_MAX_MICRO_LAMPORTS`.
+    pub fn from_env() -> Self {
+        let parsed = |name: &str| env::var(name).ok().and_then(|value| value.parse::<u64>().ok());
+        Self {
+            compute_unit_limit: parsed("COMPUTE_UNIT_LIMIT")
+                .map(|limit| limit.min(MAX_COMPUTE_UNIT_LIMIT as u64) as u32),
+            fixed_price: parsed("PRIORITY_FEE_MICRO_LAMPORTS"),
+            percentile: parsed("PRIORITY_FEE_PERCENTILE")
+                .map_or(DEFAULT_PERCENTILE, |percentile| percentile.min(100) as usize),
+            max_price: parsed("PRIORITY_FEE_MAX_MICRO_LAMPORTS").unwrap_or(DEFAULT_MAX_MICRO_LAMPORTS),
+        }
+    }
+
+    /// The configured unit price, when recent fees need not be looked up.
+    pub fn fixed_price(&self) -> Option<u64> {
+        self.fixed_price
+    }
+
+    /// Unit price in micro-lamports given the fees recently paid per slot.
+    pub fn price_from(&self, mut recent_fees: Vec<u64>) -> u64 {
+        if let Some(price) = self.fixed_price {
+            return price;
+        }
+        if recent_fees.is_empty() {
+            return 0;
+        }
+        recent_fees.sort_unstable();
+        let index = (recent_fees.len() - 1) * self.percentile / 100;
+        recent_fees[index].min(self.max_price)
+    }
+
+    /// Compute budget instructions to prepend to `instruction_count` instructions.
+    pub fn instructions(&self, instruction_count: usize, price: u64) -> [Instruction; 2] {
+        let limit = self.compute_unit_limit.unwrap_or_else(|| {
+            (DEFAULT_COMPUTE_UNIT_LIMIT as u32)
+                .saturating_mul(instruction_count as u32)
+                .min(MAX_COMPUTE_UNIT_LIMIT)
+        });
+        [
+            ComputeBudgetInstruction::set_compute_unit_limit(limit),
+            ComputeBudgetInstruction::set_compute_unit_price(price),
+        ]
+    }
+}
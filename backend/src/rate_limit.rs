@@ -0,0 +1,180 @@
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::Method,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use dashmap::DashMap;
+use std::env;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::auth::Caller;
+use crate::error::ApiError;
+use crate::routes::AppState;
+
+const DEFAULT_READS_PER_MINUTE: u32 = 600;
+const DEFAULT_WRITES_PER_MINUTE: u32 = 30;
+const PRUNE_INTERVAL_SECS: u64 = 60;
+
+/// A token bucket holding up to a minute's allowance, refilled continuously.
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+    per_minute: u32,
+}
+
+impl Bucket {
+    /// Take a token, or say how long until one is available.
+    fn take(&mut self, per_minute: u32, now: Instant) -> Result<(), Duration> {
+        let rate = per_minute as f64 / 60.0;
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate).min(per_minute as f64);
+        self.updated = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / rate))
+        }
+    }
+}
+
+// ==================== Rate Limiter ====================
+/// Per-caller request limits: authenticated callers are counted by API key
+/// or JWT subject, anonymous ones by IP. Reads (GET) and writes have
+/// separate allowances, `RATE_LIMIT_READS_PER_MINUTE` (default 600) and
+/// `RATE_LIMIT_WRITES_PER_MINUTE` (default 30), since every write spends
+/// payer lamports; 0 lifts a limit.
+///
+/// Tenant callers share one allowance per tenant, with the tenant's own
+/// limits where `TENANTS` sets them.
+///
+/// Behind a proxy set `RATE_LIMIT_TRUST_FORWARDED=true` to count the
+/// client address from `X-Forwarded-For` rather than the proxy's.
+pub struct RateLimiter {
+    reads_per_minute: u32,
+    writes_per_minute: u32,
+    trust_forwarded: bool,
+    buckets: DashMap<(String, bool), Bucket>,
+}
+
+impl RateLimiter {
+    pub fn from_env() -> Self {
+        let limit = |name: &str, default: u32| {
+            env::var(name).ok().and_then(|value| value.parse().ok()).unwrap_or(default)
+        };
+        Self {
+            reads_per_minute: limit("RATE_LIMIT_READS_PER_MINUTE", DEFAULT_READS_PER_MINUTE),
+            writes_per_minute: limit("RATE_LIMIT_WRITES_PER_MINUTE", DEFAULT_WRITES_PER_MINUTE),
+            trust_forwarded: env::var("RATE_LIMIT_TRUST_FORWARDED").map(|value| value == "true").unwrap_or(false),
+            buckets: DashMap::new(),
+        }
+    }
+
+    fn per_minute(&self, write: bool) -> u32 {
+        if write { self.writes_per_minute } else { self.reads_per_minute }
+    }
+
+    /// Count a request from `caller`, returning how long to wait when it's
+    /// over the limit.
+    fn check(&self, caller: String, write: bool, now: Instant) -> Result<(), Duration> {
+        self.check_limit(caller, write, self.per_minute(write), now)
+    }
+
+    fn check_limit(&self, caller: String, write: bool, per_minute: u32, now: Instant) -> Result<(), Duration> {
+        if per_minute == 0 {
+            return Ok(());
+        }
+        self.buckets
+            .entry((caller, write))
+            .or_insert(Bucket { tokens: per_minute as f64, updated: now, per_minute })
+            .take(per_minute, now)
+    }
+
+    /// Forget callers whose buckets have refilled; they'd start full anyway.
+    fn prune(&self, now: Instant) {
+        self.buckets.retain(|_, bucket| {
+            let per_minute = bucket.per_minute;
+            let refill = Duration::from_secs_f64((per_minute as f64 - bucket.tokens).max(0.0) * 60.0 / per_minute.max(1) as f64);
+            now.saturating_duration_since(bucket.updated) < refill
+        });
+    }
+
+    fn client_address(&self, request: &Request) -> String {
+        let forwarded = self.trust_forwarded
+            .then(|| request.headers().get("x-forwarded-for"))
+            .flatten()
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split(',').next())
+            .map(|address| address.trim().to_string())
+            .filter(|address| !address.is_empty());
+        forwarded
+            .or_else(|| request.extensions().get::<ConnectInfo<SocketAddr>>().map(|info| info.0.ip().to_string()))
+            .unwrap_or_else(|| "unknown".to_string())
+    }
+}
+
+/// Refuse requests over the caller's allowance with 429 and `Retry-After`.
+/// Runs after `authenticate`, so keyed callers get their own allowance.
+pub async fn limit(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let write = request.method() != Method::GET;
+    let caller = request.extensions().get::<Caller>();
+    let checked = match state.tenants.for_caller(caller) {
+        Some(tenant) => {
+            let per_minute = if write { tenant.writes_per_minute } else { tenant.reads_per_minute };
+            let per_minute = per_minute.unwrap_or_else(|| state.limiter.per_minute(write));
+            state.limiter.check_limit(format!("tenant:{}", tenant.id), write, per_minute, Instant::now())
+        }
+        None => {
+            let caller = match caller {
+                Some(caller) => caller.subject.clone(),
+                None => format!("ip:{}", state.limiter.client_address(&request)),
+            };
+            state.limiter.check(caller, write, Instant::now())
+        }
+    };
+    if let Err(wait) = checked {
+        let retry_after = wait.as_secs_f64().ceil().max(1.0) as u64;
+        return ApiError::RateLimited { retry_after }.into_response();
+    }
+    next.run(request).await
+}
+
+/// Drop idle buckets every minute so the map doesn't grow with every IP seen.
+pub fn spawn(limiter: Arc<RateLimiter>) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(PRUNE_INTERVAL_SECS));
+        loop {
+            ticker.tick().await;
+            limiter.prune(Instant::now());
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_run_out_before_reads_and_refill_over_time() {
+        let limiter = RateLimiter {
+            reads_per_minute: 600,
+            writes_per_minute: 2,
+            trust_forwarded: false,
+            buckets: DashMap::new(),
+        };
+        let start = Instant::now();
+        assert!(limiter.check("ip:1.2.3.4".to_string(), true, start).is_ok());
+        assert!(limiter.check("ip:1.2.3.4".to_string(), true, start).is_ok());
+        let wait = limiter.check("ip:1.2.3.4".to_string(), true, start).unwrap_err();
+        assert_eq!(wait.as_secs(), 30);
+        // Other callers and reads are counted separately
+        assert!(limiter.check("ip:5.6.7.8".to_string(), true, start).is_ok());
+        assert!(limiter.check("ip:1.2.3.4".to_string(), false, start).is_ok());
+
+        assert!(limiter.check("ip:1.2.3.4".to_string(), true, start + Duration::from_secs(31)).is_ok());
+    }
+}
@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+use std::env;
+
+// ==================== Read Sources ====================
+pub const READ_SOURCE_HEADER: &str = "x-read-source";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadSource {
+    /// The storage mirror written after each confirmed transaction.
+    Indexer,
+    /// The in-memory snapshot of every program account.
+    Index,
+    Rpc,
+}
+
+impl ReadSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ReadSource::Indexer => "indexer",
+            ReadSource::Index => "index",
+            ReadSource::Rpc => "rpc",
+        }
+    }
+
+    /// Response header naming the source that answered.
+    pub fn header(self) -> [(&'static str, &'static str); 1] {
+        [(READ_SOURCE_HEADER, self.as_str())]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReadEndpoint {
+    Asset,
+    Loan,
+}
+
+impl ReadEndpoint {
+    fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "asset" => Some(ReadEndpoint::Asset),
+            "loan" => Some(ReadEndpoint::Loan),
+            _ => None,
+        }
+    }
+}
+
+// ==================== Query Planner ====================
+
+/// Decides per endpoint whether a mirrored record is recent enough to serve
+/// instead of an RPC round trip. Budgets come from `READ_STALENESS_BUDGETS`
+/// ("asset:30,loan:10", in seconds); endpoints without a budget always use RPC.
+#[derive(Debug, Default)]
+pub struct ReadPlanner {
+    budgets: HashMap<ReadEndpoint, i64>,
+}
+
+impl ReadPlanner {
+    pub fn from_env() -> Self {
+        let mut budgets = HashMap::new();
+        for entry in env::var("READ_STALENESS_BUDGETS").unwrap_or_default().split(',') {
+            let Some((endpoint, seconds)) = entry.split_once(':') else { continue };
+            match (ReadEndpoint::parse(endpoint), seconds.trim().parse::<i64>()) {
+                (Some(endpoint), Ok(seconds)) if seconds >= 0 => {
+                    budgets.insert(endpoint, seconds);
+                }
+                _ => tracing::warn!("⚠️ Ignoring invalid staleness budget: {}", entry),
+            }
+        }
+        Self { budgets }
+    }
+
+    /// Whether the indexer should be consulted at all for this endpoint.
+    pub fn uses_indexer(&self, endpoint: ReadEndpoint) -> bool {
+        self.budgets.get(&endpoint).is_some_and(|budget| *budget > 0)
+    }
+
+    /// Whether a record last written at `updated_at` may answer a read at `now`.
+    pub fn is_fresh(&self, endpoint: ReadEndpoint, updated_at: i64, now: i64) -> bool {
+        self.uses_indexer(endpoint) && now - updated_at <= self.budgets[&endpoint]
+    }
+}
@@ -0,0 +1,57 @@
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::live_events::{EventHub, LiveEvent};
+use crate::storage::{RiskEvent, RiskEventFilter, Storage};
+
+/// A score change seen on-chain is recorded this long after it lands,
+/// unless a write through this backend recorded it meanwhile.
+const OBSERVED_GRACE_SECS: u64 = 60;
+
+/// Record score changes written by other oracles or clients, as the live
+/// events observe them, with source `chain`. Changes written through this
+/// backend were recorded by the route with their signature, confidence and
+/// source, and are skipped.
+pub fn spawn(storage: Arc<Storage>, hub: Arc<EventHub>) {
+    let mut events = hub.subscribe();
+    tokio::spawn(async move {
+        loop {
+            let (asset_id, risk_score) = match events.recv().await {
+                Ok(LiveEvent::RiskUpdated { asset_id, risk_score, .. }) => (asset_id, risk_score),
+                Ok(_) => continue,
+                Err(RecvError::Lagged(missed)) => {
+                    tracing::warn!("⚠️ Risk history skipped {} live events", missed);
+                    continue;
+                }
+                Err(RecvError::Closed) => return,
+            };
+            let observed_at = chrono::Utc::now().timestamp();
+            let storage = storage.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_secs(OBSERVED_GRACE_SECS)).await;
+                let filter = RiskEventFilter {
+                    from: Some(observed_at - OBSERVED_GRACE_SECS as i64),
+                    ..RiskEventFilter::latest(10)
+                };
+                let recorded = match storage.risk_history.list_risk_events(&asset_id, &filter).await {
+                    Ok(recorded) => recorded,
+                    Err(e) => {
+                        tracing::warn!("⚠️ Failed to read risk history for {}: {}", asset_id, e);
+                        return;
+                    }
+                };
+                if recorded.iter().any(|event| event.risk_score == risk_score) {
+                    return;
+                }
+                let event = RiskEvent {
+                    recorded_at: observed_at,
+                    ..RiskEvent::now(&asset_id, risk_score, "chain", None, None)
+                };
+                if let Err(e) = storage.risk_history.record_risk_event(&event).await {
+                    tracing::warn!("⚠️ Failed to persist risk event: {}", e);
+                }
+            });
+        }
+    });
+}
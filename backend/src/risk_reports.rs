@@ -0,0 +1,45 @@
+use solana_sdk::hash::hashv;
+use solana_sdk::pubkey::Pubkey;
+
+// Must match the program's domain separation for leaves and inner nodes
+const LEAF_PREFIX: &[u8] = &[0];
+const NODE_PREFIX: &[u8] = &[1];
+
+/// Leaf committing to one asset's score, as hashed on-chain.
+pub fn leaf(asset: &Pubkey, risk_score: u8) -> [u8; 32] {
+    hashv(&[LEAF_PREFIX, asset.as_ref(), &[risk_score]]).to_bytes()
+}
+
+fn node(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+    hashv(&[NODE_PREFIX, first, second]).to_bytes()
+}
+
+/// Merkle root over `leaves` and the proof for each leaf, in input order.
+/// Pairs are hashed sorted, so proofs carry no left/right flags; an odd node
+/// out is carried up to the next level unchanged.
+pub fn build(leaves: &[[u8; 32]]) -> ([u8; 32], Vec<Vec<[u8; 32]>>) {
+    let mut proofs = vec![Vec::new(); leaves.len()];
+    // Position of each original leaf within the current level
+    let mut positions: Vec<usize> = (0..leaves.len()).collect();
+    let mut level = leaves.to_vec();
+
+    while level.len() > 1 {
+        for (proof, position) in proofs.iter_mut().zip(positions.iter_mut()) {
+            if let Some(sibling) = level.get(*position ^ 1) {
+                proof.push(*sibling);
+            }
+            *position /= 2;
+        }
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] => node(a, b),
+                [a] => *a,
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+
+    (level.first().copied().unwrap_or_default(), proofs)
+}
@@ -1,26 +1,77 @@
 use axum::{
     Router,
-    routing::{get, post},
-    response::Json,
-    extract::{Path, State},
-    http::StatusCode,
+    middleware,
+    routing::{delete, get, post},
+    response::{sse::{Event, KeepAlive, Sse}, Json},
+    extract::{Path, Query, State},
+    body::Bytes,
+    http::{HeaderMap, StatusCode},
+    Extension,
 };
+use futures::stream::{self, Stream};
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
 use std::str::FromStr;
+use tokio::sync::broadcast::error::RecvError;
 
-use crate::solana_client::SolanaService;
+use crate::solana_client::{AssetFilter, CostEstimate, FloatingRateTerms, InterestMode, LoanPreview, LoanStatus, LoanProduct, LoanTerms, LookupTableInfo, NewAsset, NonceAccountInfo, OracleBond, PayerRotation, PayoffQuote, ProtocolStats, RevenueStats, SolanaService, TransactionStatus};
+use crate::signer::SignerSource;
+use crate::commitment::Commitment;
 use crate::chainlink_client::ChainlinkService;
+use crate::data_sharing::{self, LoanBookView};
+use crate::visibility::{Role, RoleRegistry};
+use crate::storage::{AssetRecord, AuditFilter, AuditRecord, LoanRecord, OracleSignerRecord, RiskEvent, RiskEventFilter, ScreeningRecord, Storage, SubscriberRecord};
+use crate::idl_sync::{self, IdlSync, IdlSyncStatus};
+use crate::webhooks::{self, DeliveryResult, WebhookRegistry};
+use crate::read_routing::{ReadEndpoint, ReadPlanner, ReadSource};
+use crate::screening::{ScreeningDecision, Screener};
+use crate::wallet_monitor::{WalletMonitor, WalletStatus};
+use crate::liquidator::{Liquidator, LiquidatorStatus};
+use crate::notifier::{self, Notifier};
+use crate::capacity::{self, CapacityProjection, CapacityScenario, FeeConditions};
+use crate::portfolio;
+use crate::health::{HealthProbes, Readiness};
+use crate::error::{ApiError, ErrorResponse};
+use crate::validation::Validate;
+use crate::account_index::AccountIndex;
+use crate::live_events::EventHub;
+use crate::coordination::{self, Coordination};
+use crate::ws;
+use crate::pagination::{self, Cursor, Order};
+use crate::audit;
+use crate::auth::{self, Authenticator, Caller};
+use crate::rate_limit::{self, RateLimiter};
+use crate::tenants::{self, Tenant, TenantRegistry};
+use crate::openapi;
 
 #[derive(Clone)]
 pub struct AppState {
     pub solana: Arc<SolanaService>,
     pub chainlink: Arc<ChainlinkService>,
+    pub roles: Arc<RoleRegistry>,
+    pub auth: Arc<Authenticator>,
+    pub limiter: Arc<RateLimiter>,
+    pub storage: Arc<Storage>,
+    pub idl_sync: Arc<IdlSync>,
+    pub webhooks: Arc<WebhookRegistry>,
+    pub reads: Arc<ReadPlanner>,
+    pub screener: Arc<Screener>,
+    pub wallet: Arc<WalletMonitor>,
+    pub index: Arc<AccountIndex>,
+    pub events: Arc<EventHub>,
+    pub coordination: Arc<Coordination>,
+    pub liquidator: Arc<Liquidator>,
+    pub notifier: Arc<Notifier>,
+    pub health: Arc<HealthProbes>,
+    pub tenants: Arc<TenantRegistry>,
 }
 
 // Request/Response Types
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateAssetRequest {
     pub asset_id: String,
     pub asset_type: String,
@@ -29,21 +80,94 @@ pub struct CreateAssetRequest {
     pub owner: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct CreateAssetResponse {
     pub success: bool,
     pub asset_pda: String,
-    pub transaction: String,
+    /// Signature, when the service signed and sent the transaction itself.
+    pub transaction: Option<String>,
+    /// Base64 transaction for the owner's wallet to sign and pass to
+    /// `POST /transactions/submit`, when the service doesn't hold their key.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unsigned_transaction: Option<String>,
+    pub asset_id: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BatchCreateAssetRequest {
+    pub assets: Vec<CreateAssetRequest>,
+}
+
+/// Outcome of one asset of a batch, in request order.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BatchAssetResult {
     pub asset_id: String,
+    pub success: bool,
+    pub asset_pda: Option<String>,
+    /// Signature of the transaction that created the asset, shared with the
+    /// other assets packed into it.
+    pub transaction: Option<String>,
+    /// Base64 transaction for the owner's wallet to sign and pass to
+    /// `POST /transactions/submit`, shared like `transaction`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unsigned_transaction: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorResponse>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BatchCreateAssetResponse {
+    /// Whether every asset succeeded.
+    pub success: bool,
+    pub succeeded: usize,
+    pub failed: usize,
+    /// Transactions sent or prepared.
+    pub transactions: usize,
+    pub results: Vec<BatchAssetResult>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateRiskRequest {
     pub risk_score: u8,
+    /// Recorded in the risk history; "chainlink" also triggers the
+    /// Chainlink workflow. Defaults to api.
     pub source: Option<String>,
+    /// The assessment's confidence, 0 to 1, for the risk history.
+    #[serde(default)]
+    pub confidence: Option<f32>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RiskReportRequest {
+    pub report_id: u64,
+    pub entries: Vec<RiskReportEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RiskReportEntry {
+    pub asset_id: String,
+    pub risk_score: u8,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RiskReportResponse {
+    pub success: bool,
+    pub transaction: String,
+    pub report_id: u64,
+    /// Hex-encoded Merkle root anchored on-chain.
+    pub root: String,
+    pub entries: Vec<ProvenRiskEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ProvenRiskEntry {
+    pub asset_id: String,
+    pub risk_score: u8,
+    /// Hex-encoded sibling hashes from the leaf up to the root.
+    pub proof: Vec<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct UpdateRiskResponse {
     pub success: bool,
     pub transaction: String,
@@ -51,43 +175,453 @@ pub struct UpdateRiskResponse {
     pub new_risk_score: u8,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AssetStatusResponse {
+    pub success: bool,
+    pub transaction: String,
+    pub asset_id: String,
+    pub is_active: bool,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SetDelegateRequest {
+    pub delegate: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DelegateResponse {
+    pub success: bool,
+    pub transaction: String,
+    pub asset_id: String,
+    pub delegate: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TransferAssetRequest {
+    pub new_owner: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TransferAssetResponse {
+    pub success: bool,
+    pub transaction: String,
+    pub asset_id: String,
+    pub owner: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct AssetResponse {
     pub success: bool,
     pub asset: serde_json::Value,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AssetListQuery {
+    pub owner: Option<String>,
+    pub active: Option<bool>,
+    pub min_risk: Option<u8>,
+    pub max_risk: Option<u8>,
+    #[serde(default)]
+    pub sort: AssetSort,
+    #[serde(default)]
+    pub order: Order,
+    /// `next_cursor` of the previous page.
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+    pub commitment: Option<Commitment>,
+}
+
+/// Assets carry no creation time on-chain, so they can't be listed in
+/// creation order.
+#[derive(Debug, Clone, Copy, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AssetSort {
+    #[default]
+    AssetId,
+    Valuation,
+    RiskScore,
+}
+
+impl AssetSort {
+    fn as_str(self) -> &'static str {
+        match self {
+            AssetSort::AssetId => "asset_id",
+            AssetSort::Valuation => "valuation",
+            AssetSort::RiskScore => "risk_score",
+        }
+    }
+}
+
+/// Commitment a read is made at. Reads that name one skip the indexer and
+/// the account cache.
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CommitmentQuery {
+    pub commitment: Option<Commitment>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AssetListResponse {
+    pub success: bool,
+    pub total: usize,
+    pub next_cursor: Option<String>,
+    pub assets: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateLoanRequest {
     pub asset_id: String,
     pub borrower: String,
     pub loan_amount: u64,
     pub interest_rate: u64,
     pub duration: i64,
+    pub mint: String,
+    #[serde(default)]
+    pub interest_mode: InterestMode,
+    #[serde(default)]
+    pub product: LoanProduct,
+    /// Benchmark + spread pricing; omit for a fixed-rate loan.
+    #[serde(default)]
+    pub floating_rate: Option<FloatingRateTerms>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct CreateLoanResponse {
     pub success: bool,
     pub loan_pda: String,
-    pub transaction: String,
+    /// Signature, when the service signed and sent the transaction itself.
+    pub transaction: Option<String>,
+    /// Base64 transaction for the borrower's wallet to sign and pass to
+    /// `POST /transactions/submit`, when the service doesn't hold their key.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unsigned_transaction: Option<String>,
+    pub asset_id: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PayerRotationResponse {
+    pub success: bool,
+    #[serde(flatten)]
+    #[schema(value_type = Object)]
+    pub rotation: PayerRotation,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AuditQuery {
+    /// Caller subject, e.g. `key:<fingerprint>`, or `anonymous`.
+    pub actor: Option<String>,
+    /// Action prefix, e.g. `POST /loans`.
+    pub action: Option<String>,
+    /// Path prefix, e.g. `/loans/<pda>`.
+    pub resource: Option<String>,
+    /// success or failure.
+    pub outcome: Option<String>,
+    /// Recorded at or after, unix seconds.
+    pub from: Option<i64>,
+    /// Recorded before, unix seconds.
+    pub to: Option<i64>,
+    /// `next_cursor` of the previous page.
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AuditLogResponse {
+    pub success: bool,
+    /// Newest first.
+    #[schema(value_type = Vec<Object>)]
+    pub entries: Vec<AuditRecord>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LiquidatorResponse {
+    pub success: bool,
+    /// Mode, limits, counters since startup and the last scan's candidates.
+    #[serde(flatten)]
+    #[schema(value_type = Object)]
+    pub liquidator: LiquidatorStatus,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WalletResponse {
+    pub success: bool,
+    /// True when the live check failed and this is the last good one.
+    pub stale: bool,
+    #[serde(flatten)]
+    #[schema(value_type = Object)]
+    pub wallet: WalletStatus,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AirdropRequest {
+    pub wallet: String,
+    /// Defaults to 1 SOL; devnet grants at most 2 per request.
+    #[serde(default = "default_airdrop_lamports")]
+    pub lamports: u64,
+    /// Base units of the `FAUCET_MINT` test token to mint as well.
+    #[serde(default)]
+    pub token_amount: Option<u64>,
+}
+
+fn default_airdrop_lamports() -> u64 {
+    1_000_000_000
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AirdropResponse {
+    pub success: bool,
+    pub wallet: String,
+    pub sol_signature: Option<String>,
+    pub token_signature: Option<String>,
+}
+
+/// One operation of a composed transaction, tagged by `op`.
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum ComposeStep {
+    InitializeAsset(CreateAssetRequest),
+    UpdateRiskScore {
+        asset_id: String,
+        risk_score: u8,
+        /// "chainlink" also triggers the Chainlink risk workflow for the asset.
+        #[serde(default)]
+        source: Option<String>,
+    },
+    RequestLoan(CreateLoanRequest),
+    ApproveLoan { loan_pda: String },
+    RepayLoan { loan_pda: String },
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ComposeRequest {
+    pub steps: Vec<ComposeStep>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ComposeResponse {
+    pub success: bool,
+    /// Address each step created or acted on, in step order.
+    pub addresses: Vec<String>,
+    /// Signature, when the service payer was the only signer.
+    pub transaction: Option<String>,
+    /// Base64 transaction for `signers` to sign and pass to
+    /// `POST /transactions/submit`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unsigned_transaction: Option<String>,
+    pub signers: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct LoanPdaQuery {
+    pub asset_id: String,
+    pub borrower: String,
+}
+
+/// A derived program address, so clients need not replicate the seeds.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PdaResponse {
+    pub success: bool,
+    pub address: String,
+    pub bump: u8,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TransactionStatusResponse {
+    pub success: bool,
+    #[schema(value_type = Object)]
+    pub transaction: TransactionStatus,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RepayLoanResponse {
+    pub success: bool,
+    pub loan_pda: String,
+    /// Signature, when the service signed and sent the repayment itself.
+    pub transaction: Option<String>,
+    /// Base64 transaction for the borrower's wallet to sign and pass to
+    /// `POST /transactions/submit`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unsigned_transaction: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AssetEstimateQuery {
+    pub asset_id: String,
+    pub asset_type: String,
+    pub owner: String,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct LoanEstimateQuery {
     pub asset_id: String,
+    pub borrower: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct EstimateResponse {
+    pub success: bool,
+    #[schema(value_type = Object)]
+    pub estimate: CostEstimate,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LoanPreviewResponse {
+    pub success: bool,
+    #[schema(value_type = Object)]
+    pub preview: LoanPreview,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct NonceAccountResponse {
+    pub success: bool,
+    #[schema(value_type = Object)]
+    pub nonce_account: NonceAccountInfo,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct LookupTableRequest {
+    #[serde(default)]
+    pub addresses: Vec<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LookupTableResponse {
+    pub success: bool,
+    #[schema(value_type = Object)]
+    pub lookup_table: LookupTableInfo,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub transactions: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SubmitTransactionRequest {
+    /// Base64 bincode transaction carrying every required signature.
+    pub transaction: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SubmitTransactionResponse {
+    pub success: bool,
+    pub transaction: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LoanActionResponse {
+    pub success: bool,
+    pub transaction: String,
+    pub loan_pda: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct LoanResponse {
     pub success: bool,
     pub loan: serde_json::Value,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct LoanListQuery {
+    pub borrower: Option<String>,
+    pub asset_id: Option<String>,
+    pub status: Option<LoanStatus>,
+    #[serde(default)]
+    pub sort: LoanSort,
+    #[serde(default)]
+    pub order: Order,
+    /// `next_cursor` of the previous page.
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+}
+
+/// `start_time` is when the loan was requested, moved to approval once
+/// it's approved.
+#[derive(Debug, Clone, Copy, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LoanSort {
+    #[default]
+    StartTime,
+    Principal,
+    RiskScore,
+}
+
+impl LoanSort {
+    fn as_str(self) -> &'static str {
+        match self {
+            LoanSort::StartTime => "start_time",
+            LoanSort::Principal => "principal",
+            LoanSort::RiskScore => "risk_score",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LoanListResponse {
+    pub success: bool,
+    pub total: usize,
+    pub next_cursor: Option<String>,
+    pub loans: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ScheduleResponse {
+    pub success: bool,
+    pub schedule: serde_json::Value,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PortfolioResponse {
+    pub success: bool,
+    /// Totals, the wallet's assets with the debt against each, and its open loans.
+    #[schema(value_type = Object)]
+    pub portfolio: serde_json::Value,
+}
+
+/// When to price a payoff; now if omitted.
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PayoffQuery {
+    /// Unix timestamp, no earlier than now.
+    pub at: Option<i64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PayoffResponse {
+    pub success: bool,
+    pub quote: PayoffQuote,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct RiskHistoryResponse {
     pub success: bool,
     pub asset_id: String,
+    /// Newest first.
     pub history: Vec<serde_json::Value>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RiskHistoryQuery {
+    /// api, chainlink, risk_report or chain.
+    pub source: Option<String>,
+    /// Recorded at or after, unix seconds.
+    pub from: Option<i64>,
+    /// Recorded before, unix seconds.
+    pub to: Option<i64>,
+    /// `next_cursor` of the previous page.
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct ChainlinkWebhookRequest {
     pub workflow_id: String,
     pub asset_id: String,
@@ -96,7 +630,195 @@ pub struct ChainlinkWebhookRequest {
     pub sources: Vec<String>,
 }
 
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TestDeliveryRequest {
+    pub event: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ScreeningHistoryResponse {
+    pub success: bool,
+    pub address: String,
+    #[schema(value_type = Vec<Object>)]
+    pub screenings: Vec<ScreeningRecord>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BlacklistRequest {
+    pub address: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BlacklistResponse {
+    pub success: bool,
+    pub addresses: Vec<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BlacklistStatusResponse {
+    pub success: bool,
+    pub address: String,
+    pub blacklisted: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BlacklistUpdateResponse {
+    pub success: bool,
+    pub transaction: String,
+    pub address: String,
+    pub blacklisted: bool,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AddOracleRequest {
+    pub pubkey: String,
+    pub label: Option<String>,
+    /// Also appoint it the program's risk oracle, replacing the current one.
+    #[serde(default)]
+    pub risk_oracle: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct OraclesResponse {
+    pub success: bool,
+    /// Appointed in the program config to post risk reports.
+    pub risk_oracle: String,
+    /// Oracles that registered a bond, and may vote on report disputes.
+    #[schema(value_type = Vec<Object>)]
+    pub bonded: Vec<OracleBond>,
+    /// Keys the Chainlink webhook accepts signed reports from.
+    #[schema(value_type = Vec<Object>)]
+    pub webhook_signers: Vec<OracleSignerRecord>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct OracleUpdateResponse {
+    pub success: bool,
+    pub pubkey: String,
+    /// The set_risk_oracle signature, when one was sent.
+    pub transaction: Option<String>,
+    pub webhook_signer: bool,
+    pub risk_oracle: bool,
+}
+
+/// Where and when to send notifications; replaces any subscriber with the same id.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SubscriberRequest {
+    pub id: String,
+    /// webhook, email, slack or telegram.
+    pub channel: String,
+    /// Webhook URL, email address, Slack incoming webhook URL or Telegram chat id.
+    pub target: String,
+    /// Signs webhook deliveries as `X-Webhook-Signature`.
+    pub secret: Option<String>,
+    /// Any of risk_threshold, loan_maturity and liquidation.
+    pub events: Vec<String>,
+    /// Required for risk_threshold: the score whose crossing is notified.
+    pub risk_threshold: Option<u8>,
+    /// Defaults to three days.
+    pub maturity_notice_secs: Option<i64>,
+    /// Only events about this asset.
+    pub asset_id: Option<String>,
+    /// Only loans of this borrower.
+    pub borrower: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SubscribersResponse {
+    pub success: bool,
+    /// Channels configured on this deployment.
+    pub channels: Vec<&'static str>,
+    #[schema(value_type = Vec<Object>)]
+    pub subscribers: Vec<SubscriberRecord>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SubscriberUpdateResponse {
+    pub success: bool,
+    pub id: String,
+    pub subscribed: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StatsResponse {
+    pub success: bool,
+    #[schema(value_type = Object)]
+    pub protocol_revenue: RevenueStats,
+    /// None until the on-chain stats account has been initialized.
+    #[schema(value_type = Option<Object>)]
+    pub protocol: Option<ProtocolStats>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct LoanBookQuery {
+    pub k: Option<usize>,
+}
+
+// The chain is the source of truth; a failed mirror write is logged, never surfaced
+fn log_storage_error(what: &str, result: anyhow::Result<()>) {
+    if let Err(e) = result {
+        tracing::warn!("⚠️ Failed to persist {}: {}", what, e);
+    }
+}
+
+// Scope a created asset or loan to the caller's tenant, so it shows in their lists
+async fn assign_to_tenant(state: &AppState, tenant: Option<&Tenant>, kind: &str, id: &str) {
+    if let Some(tenant) = tenant {
+        let assigned = state.storage.tenants.assign_to_tenant(&tenant.id, kind, id, chrono::Utc::now().timestamp()).await;
+        log_storage_error(&format!("tenant {} {}", kind, id), assigned);
+    }
+}
+
+// The ids a tenant's caller may list, or None when the caller isn't a tenant's
+async fn tenant_scope(state: &AppState, tenant: Option<&Tenant>, kind: &str) -> Result<Option<HashSet<String>>, ApiError> {
+    let Some(tenant) = tenant else {
+        return Ok(None);
+    };
+    let ids = state.storage.tenants.tenant_resources(&tenant.id, kind).await
+        .map_err(|e| ApiError::Unavailable(format!("Tenant resources unavailable: {}", e)))?;
+    Ok(Some(ids.into_iter().collect()))
+}
+
+// Screen a party before any transaction is sent on its behalf
+async fn screen_party(state: &AppState, address: &str, context: &str, subject: &str) -> Result<(), ApiError> {
+    match state.screener.check(&state.storage, address, context, subject).await {
+        Ok(ScreeningDecision::Blocked) => Err(ApiError::Forbidden(
+            format!("Address {} failed sanctions screening", address),
+        )),
+        Ok(_) => Ok(()),
+        Err(e) => {
+            tracing::error!("❌ Sanctions screening unavailable: {}", e);
+            Err(ApiError::Unavailable(format!("Sanctions screening unavailable: {}", e)))
+        }
+    }
+}
+
+async fn set_asset_active(storage: &Storage, asset_id: &str, is_active: bool) -> anyhow::Result<()> {
+    if let Some(mut asset) = storage.assets.get_asset(asset_id).await? {
+        asset.is_active = is_active;
+        asset.updated_at = chrono::Utc::now().timestamp();
+        storage.assets.upsert_asset(&asset).await?;
+    }
+    Ok(())
+}
+
+async fn set_asset_risk(storage: &Storage, asset_id: &str, risk_score: u8) -> anyhow::Result<()> {
+    if let Some(mut asset) = storage.assets.get_asset(asset_id).await? {
+        asset.risk_score = risk_score;
+        asset.updated_at = chrono::Utc::now().timestamp();
+        storage.assets.upsert_asset(&asset).await?;
+    }
+    Ok(())
+}
+
 // Route Handlers
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "system",
+    responses((status = 200, body = Object))
+)]
 pub async fn health_check() -> Json<serde_json::Value> {
     Json(serde_json::json!({
         "status": "healthy",
@@ -106,14 +828,72 @@ pub async fn health_check() -> Json<serde_json::Value> {
     }))
 }
 
+/// Whether the process is up, without touching any dependency; for
+/// restart decisions.
+#[utoipa::path(
+    get,
+    path = "/health/live",
+    tag = "system",
+    responses((status = 200, body = Object))
+)]
+pub async fn health_live() -> Json<serde_json::Value> {
+    Json(serde_json::json!({
+        "status": "alive",
+        "timestamp": chrono::Utc::now().timestamp(),
+        "version": env!("CARGO_PKG_VERSION")
+    }))
+}
+
+/// Per-dependency status, 503 while the RPC node or the database is down
+/// so load balancers route elsewhere. A lagging node, an unreachable
+/// Chainlink API or a low payer balance report degraded but stay ready.
+#[utoipa::path(
+    get,
+    path = "/health/ready",
+    tag = "system",
+    responses(
+        (status = 200, description = "Ready, possibly degraded", body = Object),
+        (status = 503, description = "A critical dependency is down", body = Object)
+    )
+)]
+pub async fn health_ready(
+    State(state): State<AppState>,
+) -> (StatusCode, Json<Readiness>) {
+    let readiness = state.health.readiness(&state).await;
+    let status = match readiness.is_ready() {
+        true => StatusCode::OK,
+        false => StatusCode::SERVICE_UNAVAILABLE,
+    };
+    (status, Json(readiness))
+}
+
+#[utoipa::path(
+    post,
+    path = "/assets",
+    tag = "assets",
+    request_body = CreateAssetRequest,
+    responses(
+        (status = 200, body = CreateAssetResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 403, description = "Owner failed sanctions screening, or asset type not allowed for the tenant", body = ErrorResponse),
+        (status = 422, description = "Invalid fields, or rejected by the program", body = ErrorResponse)
+    )
+)]
 pub async fn create_asset(
     State(state): State<AppState>,
+    caller: Option<Extension<Caller>>,
     Json(req): Json<CreateAssetRequest>,
-) -> Result<Json<CreateAssetResponse>, (StatusCode, String)> {
+) -> Result<Json<CreateAssetResponse>, ApiError> {
+    req.validate()?;
     tracing::info!("📝 Creating asset: {}", req.asset_id);
     
+    let tenant = state.tenants.for_caller(caller.as_deref());
+    if let Some(tenant) = tenant {
+        tenant.check_asset_type(&req.asset_type)?;
+    }
     let owner = Pubkey::from_str(&req.owner)
-        .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid owner: {}", e)))?;
+        .map_err(|e| ApiError::InvalidRequest(format!("Invalid owner: {}", e)))?;
+    screen_party(&state, &req.owner, "asset_creation", &req.asset_id).await?;
     
     match state.solana.initialize_asset(
         &req.asset_id,
@@ -123,46 +903,363 @@ pub async fn create_asset(
         owner,
     ).await {
         Ok(result) => {
-            tracing::info!("✅ Asset created: {}", req.asset_id);
+            let (transaction, unsigned_transaction) = result.transaction.into_parts();
+            // Nothing exists on-chain to mirror until the wallet submits
+            if transaction.is_some() {
+                tracing::info!("✅ Asset created: {}", req.asset_id);
+                log_storage_error("asset", state.storage.assets.upsert_asset(&new_asset_record(&req, &result.asset_pda)).await);
+            }
+            assign_to_tenant(&state, tenant, tenants::ASSET, &req.asset_id).await;
             Ok(Json(CreateAssetResponse {
                 success: true,
                 asset_pda: result.asset_pda,
-                transaction: result.transaction,
+                transaction,
+                unsigned_transaction,
                 asset_id: req.asset_id,
             }))
         },
         Err(e) => {
             tracing::error!("❌ Failed to create asset: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            Err(ApiError::from(e))
         }
     }
 }
 
-pub async fn get_asset(
+// New assets start at the program's default risk score
+fn new_asset_record(req: &CreateAssetRequest, asset_pda: &str) -> AssetRecord {
+    AssetRecord {
+        asset_id: req.asset_id.clone(),
+        asset_pda: asset_pda.to_string(),
+        asset_type: req.asset_type.clone(),
+        valuation: req.valuation,
+        owner: req.owner.clone(),
+        risk_score: 50,
+        is_active: true,
+        updated_at: chrono::Utc::now().timestamp(),
+    }
+}
+
+/// Create many assets at once, packing each owner's into as few
+/// transactions as fit them. Assets fail on their own (invalid fields, a
+/// blocked owner) or with the transaction they were packed into; the rest
+/// go ahead.
+#[utoipa::path(
+    post,
+    path = "/assets/batch",
+    tag = "assets",
+    request_body = BatchCreateAssetRequest,
+    responses(
+        (status = 200, description = "Per-asset results, in request order", body = BatchCreateAssetResponse),
+        (status = 422, description = "Empty or oversized batch, or a repeated asset id", body = ErrorResponse)
+    )
+)]
+pub async fn create_assets(
     State(state): State<AppState>,
-    Path(asset_id): Path<String>,
-) -> Result<Json<AssetResponse>, (StatusCode, String)> {
-    tracing::info!("🔍 Fetching asset: {}", asset_id);
+    caller: Option<Extension<Caller>>,
+    Json(req): Json<BatchCreateAssetRequest>,
+) -> Result<Json<BatchCreateAssetResponse>, ApiError> {
+    req.validate()?;
+    tracing::info!("📦 Creating a batch of {} assets", req.assets.len());
     
-    match state.solana.get_asset(&asset_id).await {
-        Ok(asset) => {
-            Ok(Json(AssetResponse {
-                success: true,
-                asset: serde_json::to_value(asset).unwrap(),
-            }))
-        },
-        Err(e) => {
-            tracing::error!("❌ Asset not found: {}", e);
-            Err((StatusCode::NOT_FOUND, format!("Asset not found: {}", e)))
+    let mut results: Vec<BatchAssetResult> = req.assets
+        .iter()
+        .map(|asset| BatchAssetResult {
+            asset_id: asset.asset_id.clone(),
+            success: false,
+            asset_pda: None,
+            transaction: None,
+            unsigned_transaction: None,
+            error: None,
+        })
+        .collect();
+    
+    let tenant = state.tenants.for_caller(caller.as_deref());
+    // Each owner is screened once, and a blocked owner fails all its assets
+    let mut screened: HashMap<&str, Option<ErrorResponse>> = HashMap::new();
+    let mut batch_indices = Vec::new();
+    let mut new_assets = Vec::new();
+    for (index, asset) in req.assets.iter().enumerate() {
+        let checked = asset.validate()
+            .and_then(|()| tenant.map_or(Ok(()), |tenant| tenant.check_asset_type(&asset.asset_type)))
+            .and_then(|()| {
+                Pubkey::from_str(&asset.owner).map_err(|e| ApiError::InvalidRequest(format!("Invalid owner: {}", e)))
+            });
+        let owner = match checked {
+            Ok(owner) => owner,
+            Err(e) => {
+                results[index].error = Some(ErrorResponse::from(&e));
+                continue;
+            }
+        };
+        let blocked = match screened.get(asset.owner.as_str()) {
+            Some(blocked) => blocked.clone(),
+            None => {
+                let screening = screen_party(&state, &asset.owner, "asset_creation", &asset.asset_id).await;
+                let blocked = screening.err().map(|e| ErrorResponse::from(&e));
+                screened.insert(&asset.owner, blocked.clone());
+                blocked
+            }
+        };
+        if let Some(error) = blocked {
+            results[index].error = Some(error);
+            continue;
+        }
+        batch_indices.push(index);
+        new_assets.push(NewAsset {
+            asset_id: asset.asset_id.clone(),
+            asset_type: asset.asset_type.clone(),
+            valuation: asset.valuation,
+            metadata_uri: asset.metadata_uri.clone(),
+            owner,
+        });
+    }
+    
+    let chunks = state.solana.initialize_assets(&new_assets);
+    let transactions = chunks.iter().filter(|chunk| chunk.transaction.is_ok()).count();
+    for chunk in chunks {
+        match chunk.transaction {
+            Ok(submission) => {
+                let (transaction, unsigned_transaction) = submission.into_parts();
+                for (position, asset_pda) in chunk.assets {
+                    let index = batch_indices[position];
+                    let asset_pda = asset_pda.to_string();
+                    // Nothing exists on-chain to mirror until the wallet submits
+                    if transaction.is_some() {
+                        log_storage_error("asset", state.storage.assets.upsert_asset(&new_asset_record(&req.assets[index], &asset_pda)).await);
+                    }
+                    assign_to_tenant(&state, tenant, tenants::ASSET, &req.assets[index].asset_id).await;
+                    let result = &mut results[index];
+                    result.success = true;
+                    result.asset_pda = Some(asset_pda);
+                    result.transaction = transaction.clone();
+                    result.unsigned_transaction = unsigned_transaction.clone();
+                }
+            }
+            Err(e) => {
+                tracing::error!("❌ Failed to create {} batched assets: {}", chunk.assets.len(), e);
+                let error = ErrorResponse::from(&ApiError::from(e));
+                for (position, asset_pda) in chunk.assets {
+                    let result = &mut results[batch_indices[position]];
+                    result.asset_pda = Some(asset_pda.to_string());
+                    result.error = Some(error.clone());
+                }
+            }
         }
     }
+    
+    let succeeded = results.iter().filter(|result| result.success).count();
+    let failed = results.len() - succeeded;
+    tracing::info!("✅ Batch created {} assets in {} transactions, {} failed", succeeded, transactions, failed);
+    Ok(Json(BatchCreateAssetResponse {
+        success: failed == 0,
+        succeeded,
+        failed,
+        transactions,
+        results,
+    }))
 }
 
-pub async fn update_risk(
+#[utoipa::path(
+    get,
+    path = "/assets/{asset_id}",
+    tag = "assets",
+    params(("asset_id" = String, Path, description = "Asset id"), CommitmentQuery),
+    responses(
+        (status = 200, body = AssetResponse, headers(("x-read-source" = String, description = "Where the answer came from: rpc, indexer or index"))),
+        (status = 404, description = "Asset not found", body = ErrorResponse)
+    )
+)]
+pub async fn get_asset(
+    State(state): State<AppState>,
+    role: Role,
+    Path(asset_id): Path<String>,
+    Query(query): Query<CommitmentQuery>,
+) -> Result<([(&'static str, &'static str); 1], Json<AssetResponse>), ApiError> {
+    tracing::info!("🔍 Fetching asset: {}", asset_id);
+    
+    if query.commitment.is_none() && state.reads.uses_indexer(ReadEndpoint::Asset) {
+        let now = chrono::Utc::now().timestamp();
+        let indexed = state.index.taken_at()
+            .filter(|taken_at| state.reads.is_fresh(ReadEndpoint::Asset, *taken_at, now))
+            .and_then(|_| state.index.asset(&asset_id));
+        if let Some(asset) = indexed {
+            let mut asset = serde_json::to_value(asset).unwrap();
+            role.redact(&mut asset);
+            return Ok((ReadSource::Index.header(), Json(AssetResponse {
+                success: true,
+                asset,
+            })));
+        }
+        match state.storage.assets.get_asset(&asset_id).await {
+            Ok(Some(record)) if state.reads.is_fresh(ReadEndpoint::Asset, record.updated_at, now) => {
+                let mut asset = serde_json::to_value(record).unwrap();
+                role.redact(&mut asset);
+                return Ok((ReadSource::Indexer.header(), Json(AssetResponse {
+                    success: true,
+                    asset,
+                })));
+            },
+            Ok(_) => {},
+            Err(e) => tracing::warn!("⚠️ Indexer read failed, falling back to RPC: {}", e),
+        }
+    }
+    
+    match state.solana.get_asset_at(&asset_id, query.commitment.map(Commitment::config)).await {
+        Ok(asset) => {
+            let mut asset = serde_json::to_value(asset).unwrap();
+            role.redact(&mut asset);
+            Ok((ReadSource::Rpc.header(), Json(AssetResponse {
+                success: true,
+                asset,
+            })))
+        },
+        Err(e) => {
+            tracing::error!("❌ Asset not found: {}", e);
+            Err(ApiError::not_found(e.context("Asset not found")))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/assets",
+    tag = "assets",
+    params(AssetListQuery),
+    responses(
+        (status = 200, body = AssetListResponse),
+        (status = 400, description = "Invalid filter or cursor", body = ErrorResponse),
+        (status = 403, description = "Filter or sort hidden from this role", body = ErrorResponse)
+    )
+)]
+pub async fn list_assets(
+    State(state): State<AppState>,
+    role: Role,
+    caller: Option<Extension<Caller>>,
+    Query(query): Query<AssetListQuery>,
+) -> Result<Json<AssetListResponse>, ApiError> {
+    tracing::info!("📋 Listing assets");
+    
+    // Filtering on a hidden field would reveal it one query at a time
+    if query.owner.is_some() && !role.can_see("owner") {
+        return Err(ApiError::Forbidden("Filtering by owner is restricted for this role".to_string()));
+    }
+    let owner = query.owner
+        .map(|owner| Pubkey::from_str(&owner))
+        .transpose()
+        .map_err(|e| ApiError::InvalidRequest(format!("Invalid owner: {}", e)))?;
+    if query.min_risk.zip(query.max_risk).is_some_and(|(min, max)| min > max) {
+        return Err(ApiError::InvalidRequest("min_risk exceeds max_risk".to_string()));
+    }
+    // So would ordering by one
+    if matches!(query.sort, AssetSort::Valuation) && !role.can_see("valuation") {
+        return Err(ApiError::Forbidden("Sorting by valuation is restricted for this role".to_string()));
+    }
+    
+    let filter = AssetFilter {
+        owner,
+        active: query.active,
+        min_risk: query.min_risk,
+        max_risk: query.max_risk,
+    };
+    // A tenant's callers see only the assets it created
+    let scope = tenant_scope(&state, state.tenants.for_caller(caller.as_deref()), tenants::ASSET).await?;
+    // The index tracks the default commitment only
+    let indexed = query.commitment.is_none().then(|| state.index.assets(&filter)).flatten();
+    let listed = match indexed {
+        Some(assets) => Ok(assets),
+        None => state.solana.list_assets(&filter, query.commitment.map(Commitment::config)).await,
+    };
+    match listed {
+        Ok(mut assets) => {
+            if let Some(scope) = &scope {
+                assets.retain(|asset| scope.contains(&asset.asset_id));
+            }
+            let page = pagination::paginate(assets, query.sort.as_str(), query.order, query.cursor.as_deref(), query.limit, |asset| {
+                let key = match query.sort {
+                    AssetSort::AssetId => 0,
+                    AssetSort::Valuation => asset.valuation as i128,
+                    AssetSort::RiskScore => asset.risk_score as i128,
+                };
+                (key, asset.asset_id.as_str())
+            }).map_err(|e| ApiError::InvalidRequest(e.to_string()))?;
+            let mut assets = serde_json::to_value(page.items).unwrap();
+            role.redact(&mut assets);
+            let serde_json::Value::Array(assets) = assets else { unreachable!() };
+            Ok(Json(AssetListResponse {
+                success: true,
+                total: page.total,
+                next_cursor: page.next_cursor,
+                assets,
+            }))
+        },
+        Err(e) => {
+            tracing::error!("❌ Failed to list assets: {}", e);
+            Err(ApiError::from(e))
+        }
+    }
+}
+
+/// Push the asset's risk score changes, loan lifecycle and program events
+/// as they land. A slow client that falls behind gets a `lagged` event
+/// saying how many it missed.
+#[utoipa::path(
+    get,
+    path = "/stream/assets/{asset_id}",
+    tag = "assets",
+    params(("asset_id" = String, Path, description = "Asset id")),
+    responses(
+        (status = 200, description = "Server-sent events: risk_updated, loan_created, loan_repaid, loan_liquidation, loan_updated, program_event and lagged", body = String, content_type = "text/event-stream")
+    )
+)]
+pub async fn stream_asset(
+    State(state): State<AppState>,
+    role: Role,
+    Path(asset_id): Path<String>,
+) -> Sse<impl Stream<Item = Result<Event, axum::Error>>> {
+    tracing::info!("📡 Streaming events for asset: {}", asset_id);
+    
+    let asset = state.solana.asset_address(&asset_id).0.to_string();
+    let events = stream::unfold(state.events.subscribe(), move |mut events| {
+        let asset = asset.clone();
+        async move {
+            loop {
+                let event = match events.recv().await {
+                    Ok(event) if event.asset() == Some(asset.as_str()) => {
+                        let mut data = serde_json::to_value(&event).unwrap();
+                        role.redact(&mut data);
+                        Event::default().event(event.name()).json_data(data)
+                    },
+                    Ok(_) => continue,
+                    Err(RecvError::Lagged(missed)) => Event::default()
+                        .event("lagged")
+                        .json_data(serde_json::json!({ "missed": missed })),
+                    Err(RecvError::Closed) => return None,
+                };
+                return Some((event, events));
+            }
+        }
+    });
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+#[utoipa::path(
+    post,
+    path = "/assets/{asset_id}/risk",
+    tag = "risk",
+    params(("asset_id" = String, Path, description = "Asset id")),
+    request_body = UpdateRiskRequest,
+    responses(
+        (status = 200, body = UpdateRiskResponse),
+        (status = 403, description = "Caller lacks the oracle role", body = ErrorResponse),
+        (status = 422, description = "Invalid fields, or rejected by the program", body = ErrorResponse)
+    )
+)]
+pub async fn update_risk(
     State(state): State<AppState>,
     Path(asset_id): Path<String>,
     Json(req): Json<UpdateRiskRequest>,
-) -> Result<Json<UpdateRiskResponse>, (StatusCode, String)> {
+) -> Result<Json<UpdateRiskResponse>, ApiError> {
+    req.validate()?;
     tracing::info!("🔄 Updating risk for {} to {}", asset_id, req.risk_score);
     
     // Optional: Call Chainlink workflow
@@ -180,9 +1277,15 @@ pub async fn update_risk(
         }
     }
     
-    match state.solana.update_risk_score(&asset_id, req.risk_score).await {
+    // One oracle write per asset at a time, across replicas
+    let lock = format!("risk:{}", asset_id);
+    match state.coordination.exclusive(&lock, state.solana.update_risk_score(&asset_id, req.risk_score)).await? {
         Ok(transaction) => {
             tracing::info!("✅ Risk updated for {}", asset_id);
+            log_storage_error("asset", set_asset_risk(&state.storage, &asset_id, req.risk_score).await);
+            let source = req.source.as_deref().unwrap_or("api");
+            let event = RiskEvent::now(&asset_id, req.risk_score, source, req.confidence, Some(transaction.clone()));
+            log_storage_error("risk event", state.storage.risk_history.record_risk_event(&event).await);
             Ok(Json(UpdateRiskResponse {
                 success: true,
                 transaction,
@@ -192,147 +1295,2131 @@ pub async fn update_risk(
         },
         Err(e) => {
             tracing::error!("❌ Failed to update risk: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            Err(ApiError::from(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/risk/reports",
+    tag = "risk",
+    request_body = RiskReportRequest,
+    responses(
+        (status = 200, body = RiskReportResponse),
+        (status = 400, description = "Empty or invalid report", body = ErrorResponse),
+        (status = 403, description = "Caller lacks the oracle role", body = ErrorResponse),
+        (status = 422, description = "Invalid fields, or rejected by the program", body = ErrorResponse)
+    )
+)]
+pub async fn post_risk_report(
+    State(state): State<AppState>,
+    Json(req): Json<RiskReportRequest>,
+) -> Result<Json<RiskReportResponse>, ApiError> {
+    req.validate()?;
+    
+    tracing::info!("🌳 Posting risk report {} with {} entries", req.report_id, req.entries.len());
+    
+    let scores: Vec<(String, u8)> = req.entries
+        .iter()
+        .map(|entry| (entry.asset_id.clone(), entry.risk_score))
+        .collect();
+    
+    match state.solana.post_risk_report(req.report_id, &scores).await {
+        Ok((transaction, root, proofs)) => {
+            tracing::info!("✅ Risk report {} anchored", req.report_id);
+            Ok(Json(RiskReportResponse {
+                success: true,
+                transaction,
+                report_id: req.report_id,
+                root: hex::encode(root),
+                entries: req.entries
+                    .into_iter()
+                    .zip(proofs)
+                    .map(|(entry, proof)| ProvenRiskEntry {
+                        asset_id: entry.asset_id,
+                        risk_score: entry.risk_score,
+                        proof: proof.iter().map(hex::encode).collect(),
+                    })
+                    .collect(),
+            }))
+        },
+        Err(e) => {
+            tracing::error!("❌ Failed to post risk report: {}", e);
+            Err(ApiError::from(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/risk/reports/{report_id}/apply",
+    tag = "risk",
+    params(("report_id" = u64, Path, description = "Report id")),
+    request_body = ProvenRiskEntry,
+    responses(
+        (status = 200, body = UpdateRiskResponse),
+        (status = 403, description = "Caller lacks the oracle role", body = ErrorResponse),
+        (status = 422, description = "Invalid fields, or rejected by the program", body = ErrorResponse)
+    )
+)]
+pub async fn apply_risk_report_entry(
+    State(state): State<AppState>,
+    Path(report_id): Path<u64>,
+    Json(req): Json<ProvenRiskEntry>,
+) -> Result<Json<UpdateRiskResponse>, ApiError> {
+    req.validate()?;
+    tracing::info!("🌿 Applying report {} entry for {}", report_id, req.asset_id);
+    
+    let proof = req.proof
+        .iter()
+        .map(|node| {
+            hex::decode(node)
+                .ok()
+                .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+                .ok_or_else(|| ApiError::InvalidRequest(format!("Invalid proof node: {}", node)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    
+    let lock = format!("risk:{}", req.asset_id);
+    let applied = state.solana.apply_risk_report_entry(report_id, &req.asset_id, req.risk_score, &proof);
+    match state.coordination.exclusive(&lock, applied).await? {
+        Ok(transaction) => {
+            tracing::info!("✅ Risk updated for {} from report {}", req.asset_id, report_id);
+            log_storage_error("asset", set_asset_risk(&state.storage, &req.asset_id, req.risk_score).await);
+            let event = RiskEvent::now(&req.asset_id, req.risk_score, "risk_report", None, Some(transaction.clone()));
+            log_storage_error("risk event", state.storage.risk_history.record_risk_event(&event).await);
+            Ok(Json(UpdateRiskResponse {
+                success: true,
+                transaction,
+                asset_id: req.asset_id,
+                new_risk_score: req.risk_score,
+            }))
+        },
+        Err(e) => {
+            tracing::error!("❌ Failed to apply risk report entry: {}", e);
+            Err(ApiError::from(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/assets/{asset_id}/deactivate",
+    tag = "assets",
+    params(("asset_id" = String, Path, description = "Asset id")),
+    responses(
+        (status = 200, body = AssetStatusResponse),
+        (status = 422, description = "Rejected by the program", body = ErrorResponse)
+    )
+)]
+pub async fn deactivate_asset(
+    State(state): State<AppState>,
+    Path(asset_id): Path<String>,
+) -> Result<Json<AssetStatusResponse>, ApiError> {
+    tracing::info!("⏸️ Deactivating asset: {}", asset_id);
+    
+    match state.solana.deactivate_asset(&asset_id).await {
+        Ok(transaction) => {
+            tracing::info!("✅ Asset deactivated: {}", asset_id);
+            log_storage_error("asset", set_asset_active(&state.storage, &asset_id, false).await);
+            Ok(Json(AssetStatusResponse {
+                success: true,
+                transaction,
+                asset_id,
+                is_active: false,
+            }))
+        },
+        Err(e) => {
+            tracing::error!("❌ Failed to deactivate asset: {}", e);
+            Err(ApiError::from(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/assets/{asset_id}/reactivate",
+    tag = "assets",
+    params(("asset_id" = String, Path, description = "Asset id")),
+    responses(
+        (status = 200, body = AssetStatusResponse),
+        (status = 422, description = "Rejected by the program", body = ErrorResponse)
+    )
+)]
+pub async fn reactivate_asset(
+    State(state): State<AppState>,
+    Path(asset_id): Path<String>,
+) -> Result<Json<AssetStatusResponse>, ApiError> {
+    tracing::info!("▶️ Reactivating asset: {}", asset_id);
+    
+    match state.solana.reactivate_asset(&asset_id).await {
+        Ok(transaction) => {
+            tracing::info!("✅ Asset reactivated: {}", asset_id);
+            log_storage_error("asset", set_asset_active(&state.storage, &asset_id, true).await);
+            Ok(Json(AssetStatusResponse {
+                success: true,
+                transaction,
+                asset_id,
+                is_active: true,
+            }))
+        },
+        Err(e) => {
+            tracing::error!("❌ Failed to reactivate asset: {}", e);
+            Err(ApiError::from(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/assets/{asset_id}/delegate",
+    tag = "assets",
+    params(("asset_id" = String, Path, description = "Asset id")),
+    request_body = SetDelegateRequest,
+    responses(
+        (status = 200, body = DelegateResponse),
+        (status = 400, description = "Invalid delegate", body = ErrorResponse),
+        (status = 422, description = "Invalid fields, or rejected by the program", body = ErrorResponse)
+    )
+)]
+pub async fn set_delegate(
+    State(state): State<AppState>,
+    Path(asset_id): Path<String>,
+    Json(req): Json<SetDelegateRequest>,
+) -> Result<Json<DelegateResponse>, ApiError> {
+    req.validate()?;
+    tracing::info!("🤝 Setting delegate for {}: {}", asset_id, req.delegate);
+    
+    let delegate = Pubkey::from_str(&req.delegate)
+        .map_err(|e| ApiError::InvalidRequest(format!("Invalid delegate: {}", e)))?;
+    
+    match state.solana.set_delegate(&asset_id, delegate).await {
+        Ok(transaction) => {
+            tracing::info!("✅ Delegate set for {}", asset_id);
+            Ok(Json(DelegateResponse {
+                success: true,
+                transaction,
+                asset_id,
+                delegate: Some(req.delegate),
+            }))
+        },
+        Err(e) => {
+            tracing::error!("❌ Failed to set delegate: {}", e);
+            Err(ApiError::from(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/assets/{asset_id}/transfer",
+    tag = "assets",
+    params(("asset_id" = String, Path, description = "Asset id")),
+    request_body = TransferAssetRequest,
+    responses(
+        (status = 200, body = TransferAssetResponse),
+        (status = 400, description = "Invalid owner", body = ErrorResponse),
+        (status = 422, description = "Invalid fields, or rejected by the program", body = ErrorResponse)
+    )
+)]
+pub async fn transfer_asset(
+    State(state): State<AppState>,
+    Path(asset_id): Path<String>,
+    Json(req): Json<TransferAssetRequest>,
+) -> Result<Json<TransferAssetResponse>, ApiError> {
+    req.validate()?;
+    tracing::info!("🔑 Transferring {} to {}", asset_id, req.new_owner);
+    
+    let new_owner = Pubkey::from_str(&req.new_owner)
+        .map_err(|e| ApiError::InvalidRequest(format!("Invalid new owner: {}", e)))?;
+    
+    match state.solana.transfer_asset(&asset_id, new_owner).await {
+        Ok(transaction) => {
+            tracing::info!("✅ Asset {} transferred", asset_id);
+            Ok(Json(TransferAssetResponse {
+                success: true,
+                transaction,
+                asset_id,
+                owner: req.new_owner,
+            }))
+        },
+        Err(e) => {
+            tracing::error!("❌ Failed to transfer asset: {}", e);
+            Err(ApiError::from(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/assets/{asset_id}/delegate/revoke",
+    tag = "assets",
+    params(("asset_id" = String, Path, description = "Asset id")),
+    responses(
+        (status = 200, body = DelegateResponse),
+        (status = 422, description = "Rejected by the program", body = ErrorResponse)
+    )
+)]
+pub async fn revoke_delegate(
+    State(state): State<AppState>,
+    Path(asset_id): Path<String>,
+) -> Result<Json<DelegateResponse>, ApiError> {
+    tracing::info!("✂️ Revoking delegate for {}", asset_id);
+    
+    match state.solana.revoke_delegate(&asset_id).await {
+        Ok(transaction) => {
+            tracing::info!("✅ Delegate revoked for {}", asset_id);
+            Ok(Json(DelegateResponse {
+                success: true,
+                transaction,
+                asset_id,
+                delegate: None,
+            }))
+        },
+        Err(e) => {
+            tracing::error!("❌ Failed to revoke delegate: {}", e);
+            Err(ApiError::from(e))
         }
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/assets/{asset_id}/risk/latest",
+    tag = "risk",
+    params(("asset_id" = String, Path, description = "Asset id")),
+    responses(
+        (status = 200, body = Object),
+        (status = 404, description = "Asset not found", body = ErrorResponse)
+    )
+)]
 pub async fn get_latest_risk(
     State(state): State<AppState>,
+    role: Role,
     Path(asset_id): Path<String>,
-) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+) -> Result<Json<serde_json::Value>, ApiError> {
     tracing::info!("📊 Fetching latest risk for: {}", asset_id);
     
     match state.solana.get_asset(&asset_id).await {
         Ok(asset) => {
-            Ok(Json(serde_json::json!({
+            let mut response = serde_json::json!({
                 "success": true,
                 "asset_id": asset_id,
                 "risk_score": asset.risk_score,
                 "last_update": asset.last_update,
                 "asset_type": asset.asset_type,
                 "valuation": asset.valuation
-            })))
+            });
+            role.redact(&mut response);
+            Ok(Json(response))
         },
         Err(e) => {
-            Err((StatusCode::NOT_FOUND, format!("Asset not found: {}", e)))
+            Err(ApiError::not_found(e.context("Asset not found")))
         }
     }
 }
 
+// Borrower, repayment mint and terms of a loan request body
+fn parse_loan_request(req: &CreateLoanRequest) -> Result<(Pubkey, Pubkey, LoanTerms), ApiError> {
+    let borrower = Pubkey::from_str(&req.borrower)
+        .map_err(|e| ApiError::InvalidRequest(format!("Invalid borrower: {}", e)))?;
+    let mint = Pubkey::from_str(&req.mint)
+        .map_err(|e| ApiError::InvalidRequest(format!("Invalid mint: {}", e)))?;
+    let floating_rate = req.floating_rate.as_ref()
+        .map(FloatingRateTerms::parse)
+        .transpose()
+        .map_err(|e| ApiError::InvalidRequest(e.to_string()))?;
+    
+    Ok((borrower, mint, LoanTerms {
+        loan_amount: req.loan_amount,
+        interest_rate: req.interest_rate,
+        duration: req.duration,
+        interest_mode: req.interest_mode,
+        product: req.product,
+        floating_rate,
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/loans",
+    tag = "loans",
+    request_body = CreateLoanRequest,
+    responses(
+        (status = 200, body = CreateLoanResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 403, description = "Borrower failed sanctions screening", body = ErrorResponse),
+        (status = 422, description = "Invalid fields, or rejected by the program", body = ErrorResponse)
+    )
+)]
 pub async fn create_loan(
     State(state): State<AppState>,
+    caller: Option<Extension<Caller>>,
     Json(req): Json<CreateLoanRequest>,
-) -> Result<Json<CreateLoanResponse>, (StatusCode, String)> {
+) -> Result<Json<CreateLoanResponse>, ApiError> {
+    req.validate()?;
     tracing::info!("💰 Creating loan for asset: {}", req.asset_id);
     
-    let borrower = Pubkey::from_str(&req.borrower)
-        .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid borrower: {}", e)))?;
+    let (borrower, mint, terms) = parse_loan_request(&req)?;
+    screen_party(&state, &req.borrower, "loan_creation", &req.asset_id).await?;
     
-    match state.solana.create_loan(
-        &req.asset_id,
-        borrower,
-        req.loan_amount,
-        req.interest_rate,
-        req.duration,
-    ).await {
+    match state.solana.request_loan(&req.asset_id, borrower, terms, mint).await {
         Ok(result) => {
-            tracing::info!("✅ Loan created: {}", result.loan_pda);
+            let (transaction, unsigned_transaction) = result.transaction.into_parts();
+            // Nothing exists on-chain to mirror until the wallet submits
+            if transaction.is_some() {
+                tracing::info!("✅ Loan requested: {}", result.loan_pda);
+                log_storage_error("loan", state.storage.loans.upsert_loan(&LoanRecord {
+                    loan_pda: result.loan_pda.clone(),
+                    asset_id: req.asset_id.clone(),
+                    borrower: req.borrower.clone(),
+                    principal: req.loan_amount,
+                    interest_rate: req.interest_rate,
+                    duration: req.duration,
+                    status: "pending".to_string(),
+                    updated_at: chrono::Utc::now().timestamp(),
+                }).await);
+            }
+            assign_to_tenant(&state, state.tenants.for_caller(caller.as_deref()), tenants::LOAN, &result.loan_pda).await;
             Ok(Json(CreateLoanResponse {
                 success: true,
                 loan_pda: result.loan_pda,
-                transaction: result.transaction,
+                transaction,
+                unsigned_transaction,
                 asset_id: req.asset_id,
             }))
         },
         Err(e) => {
-            tracing::error!("❌ Failed to create loan: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            tracing::error!("❌ Failed to request loan: {}", e);
+            Err(ApiError::from(e))
         }
     }
 }
 
-pub async fn get_loan(
+#[utoipa::path(
+    post,
+    path = "/loans/preview",
+    tag = "loans",
+    request_body = CreateLoanRequest,
+    responses(
+        (status = 200, body = LoanPreviewResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse)
+    )
+)]
+pub async fn preview_loan(
     State(state): State<AppState>,
-    Path(loan_pda): Path<String>,
-) -> Result<Json<LoanResponse>, (StatusCode, String)> {
-    tracing::info!("🔍 Fetching loan: {}", loan_pda);
+    Json(req): Json<CreateLoanRequest>,
+) -> Result<Json<LoanPreviewResponse>, ApiError> {
+    req.validate()?;
+    tracing::info!("🧪 Previewing loan for asset: {}", req.asset_id);
     
-    let loan_pubkey = Pubkey::from_str(&loan_pda)
-        .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid loan PDA: {}", e)))?;
+    let (borrower, mint, terms) = parse_loan_request(&req)?;
+    match state.solana.preview_loan(&req.asset_id, borrower, terms, mint).await {
+        Ok(preview) => Ok(Json(LoanPreviewResponse { success: true, preview })),
+        Err(e) => {
+            tracing::error!("❌ Failed to preview loan: {}", e);
+            Err(ApiError::from(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/transactions/submit",
+    tag = "transactions",
+    request_body = SubmitTransactionRequest,
+    responses(
+        (status = 200, body = SubmitTransactionResponse),
+        (status = 400, description = "Malformed transaction", body = ErrorResponse),
+        (status = 422, description = "Invalid fields, or rejected by the program", body = ErrorResponse)
+    )
+)]
+pub async fn submit_transaction(
+    State(state): State<AppState>,
+    Json(req): Json<SubmitTransactionRequest>,
+) -> Result<Json<SubmitTransactionResponse>, ApiError> {
+    req.validate()?;
+    tracing::info!("📤 Relaying wallet-signed transaction");
     
-    match state.solana.get_loan(loan_pubkey).await {
-        Ok(loan) => {
-            Ok(Json(LoanResponse {
-                success: true,
-                loan: serde_json::to_value(loan).unwrap(),
-            }))
+    let transaction = state.solana.decode_signed_transaction(&req.transaction)
+        .map_err(|e| ApiError::InvalidRequest(e.to_string()))?;
+    
+    match state.solana.relay_transaction(&transaction).await {
+        Ok(signature) => {
+            tracing::info!("✅ Transaction relayed: {}", signature);
+            Ok(Json(SubmitTransactionResponse { success: true, transaction: signature }))
         },
         Err(e) => {
-            Err((StatusCode::NOT_FOUND, format!("Loan not found: {}", e)))
+            tracing::error!("❌ Failed to relay transaction: {}", e);
+            Err(ApiError::from(e))
         }
     }
 }
 
-pub async fn chainlink_webhook(
-    _state: State<AppState>,  // Prefix with underscore to avoid unused warning
-    Json(req): Json<ChainlinkWebhookRequest>,
-) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
-    tracing::info!("⛓️ Chainlink webhook received for asset: {}", req.asset_id);
+#[utoipa::path(
+    post,
+    path = "/admin/payer/rotate",
+    tag = "admin",
+    request_body = Object,
+    responses(
+        (status = 200, body = PayerRotationResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse),
+        (status = 400, description = "New payer unusable", body = ErrorResponse)
+    )
+)]
+pub async fn rotate_payer(
+    State(state): State<AppState>,
+    Json(source): Json<SignerSource>,
+) -> Result<Json<PayerRotationResponse>, ApiError> {
+    tracing::info!("🔑 Rotating payer");
     
-    // Update risk score from Chainlink
-    // Note: You'll need to implement the Solana update here
-    Ok(Json(serde_json::json!({
-        "success": true,
-        "workflow_id": req.workflow_id,
-        "asset_id": req.asset_id,
-        "risk_score": req.risk_score,
-        "status": "received"
-    })))
+    match state.solana.rotate_payer(&source).await {
+        Ok(rotation) => {
+            tracing::info!("✅ Payer rotated to {}", rotation.payer);
+            Ok(Json(PayerRotationResponse { success: true, rotation }))
+        },
+        Err(e) => {
+            tracing::error!("❌ Failed to rotate payer: {}", e);
+            Err(ApiError::InvalidRequest(e.to_string()))
+        }
+    }
 }
 
-pub async fn get_risk_history(
-    _state: State<AppState>,  // Prefix with underscore to avoid unused warning
-    Path(asset_id): Path<String>,
-) -> Result<Json<RiskHistoryResponse>, (StatusCode, String)> {
-    tracing::info!("📈 Fetching risk history for: {}", asset_id);
+#[utoipa::path(
+    get,
+    path = "/admin/wallet",
+    tag = "admin",
+    responses(
+        (status = 200, body = WalletResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse)
+    )
+)]
+pub async fn get_wallet(
+    State(state): State<AppState>,
+) -> Result<Json<WalletResponse>, ApiError> {
+    match state.wallet.check(&state.solana, &state.webhooks).await {
+        Ok(wallet) => Ok(Json(WalletResponse { success: true, stale: false, wallet })),
+        Err(e) => {
+            tracing::error!("❌ Failed to check payer wallet: {}", e);
+            match state.wallet.latest() {
+                Some(wallet) => Ok(Json(WalletResponse { success: true, stale: true, wallet })),
+                None => Err(ApiError::Unavailable(e.to_string())),
+            }
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/liquidator",
+    tag = "admin",
+    responses(
+        (status = 200, body = LiquidatorResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse)
+    )
+)]
+pub async fn get_liquidator(
+    State(state): State<AppState>,
+) -> Json<LiquidatorResponse> {
+    Json(LiquidatorResponse { success: true, liquidator: state.liquidator.status() })
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/audit",
+    tag = "admin",
+    params(AuditQuery),
+    responses(
+        (status = 200, body = AuditLogResponse),
+        (status = 400, description = "Invalid cursor, limit or outcome", body = ErrorResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse)
+    )
+)]
+pub async fn get_audit_log(
+    State(state): State<AppState>,
+    Query(query): Query<AuditQuery>,
+) -> Result<Json<AuditLogResponse>, ApiError> {
+    let limit = query.limit.unwrap_or(pagination::DEFAULT_LIMIT);
+    if limit == 0 || limit > pagination::MAX_LIMIT {
+        return Err(ApiError::InvalidRequest(format!("limit must be between 1 and {}", pagination::MAX_LIMIT)));
+    }
+    let succeeded = match query.outcome.as_deref() {
+        None => None,
+        Some("success") => Some(true),
+        Some("failure") => Some(false),
+        Some(other) => return Err(ApiError::InvalidRequest(format!("Unknown outcome: {}", other))),
+    };
+    let before = query.cursor
+        .map(|cursor| {
+            let (recorded_at, id) = Cursor::resume(&cursor, "recorded_at", Order::Desc)?;
+            Ok::<_, anyhow::Error>((i64::try_from(recorded_at)?, id.parse::<i64>()?))
+        })
+        .transpose()
+        .map_err(|_| ApiError::InvalidRequest("Malformed cursor".to_string()))?;
     
-    // This would normally query a database
-    // For now, return mock data
-    Ok(Json(RiskHistoryResponse {
-        success: true,
-        asset_id,
-        history: vec![
-            serde_json::json!({
-                "timestamp": chrono::Utc::now().timestamp() - 86400,
-                "risk_score": 45,
-                "source": "ai_model_v1"
-            }),
-            serde_json::json!({
-                "timestamp": chrono::Utc::now().timestamp() - 43200,
-                "risk_score": 52,
-                "source": "chainlink"
-            }),
-            serde_json::json!({
-                "timestamp": chrono::Utc::now().timestamp(),
-                "risk_score": 35,
-                "source": "manual"
-            }),
-        ],
-    }))
+    // One extra tells whether another page follows
+    let filter = AuditFilter {
+        actor: query.actor,
+        action: query.action,
+        resource: query.resource,
+        succeeded,
+        from: query.from,
+        to: query.to,
+        before,
+        limit: limit + 1,
+    };
+    let mut entries = state.storage.audit.list_audit(&filter).await
+        .map_err(|e| {
+            tracing::error!("❌ Failed to load audit log: {}", e);
+            ApiError::Unavailable(format!("Audit log unavailable: {}", e))
+        })?;
+    let next_cursor = (entries.len() > limit).then(|| {
+        let last = &entries[limit - 1];
+        Cursor::new("recorded_at", Order::Desc, last.recorded_at as i128, last.id.to_string()).encode()
+    });
+    entries.truncate(limit);
+    
+    Ok(Json(AuditLogResponse { success: true, entries, next_cursor }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/nonces",
+    tag = "admin",
+    responses(
+        (status = 200, body = NonceAccountResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse)
+    )
+)]
+pub async fn create_nonce_account(
+    State(state): State<AppState>,
+) -> Result<Json<NonceAccountResponse>, ApiError> {
+    match state.solana.create_nonce_account().await {
+        Ok((nonce_account, signature)) => {
+            tracing::info!("✅ Nonce account created: {}", nonce_account.address);
+            Ok(Json(NonceAccountResponse { success: true, nonce_account, transaction: Some(signature) }))
+        },
+        Err(e) => {
+            tracing::error!("❌ Failed to create nonce account: {}", e);
+            Err(ApiError::from(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/nonces/{address}",
+    tag = "admin",
+    params(("address" = String, Path, description = "Nonce account address")),
+    responses(
+        (status = 200, body = NonceAccountResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse),
+        (status = 404, description = "Nonce account not found", body = ErrorResponse)
+    )
+)]
+pub async fn get_nonce_account(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+) -> Result<Json<NonceAccountResponse>, ApiError> {
+    let address = Pubkey::from_str(&address)
+        .map_err(|e| ApiError::InvalidRequest(format!("Invalid address: {}", e)))?;
+    match state.solana.get_nonce_account(&address).await {
+        Ok(nonce_account) => Ok(Json(NonceAccountResponse { success: true, nonce_account, transaction: None })),
+        Err(e) => Err(ApiError::not_found(e)),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/nonces/{address}",
+    tag = "admin",
+    params(("address" = String, Path, description = "Nonce account address")),
+    responses(
+        (status = 200, body = SubmitTransactionResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse)
+    )
+)]
+pub async fn close_nonce_account(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+) -> Result<Json<SubmitTransactionResponse>, ApiError> {
+    let address = Pubkey::from_str(&address)
+        .map_err(|e| ApiError::InvalidRequest(format!("Invalid address: {}", e)))?;
+    match state.solana.close_nonce_account(&address).await {
+        Ok(signature) => {
+            tracing::info!("✅ Nonce account closed: {}", address);
+            Ok(Json(SubmitTransactionResponse { success: true, transaction: signature }))
+        },
+        Err(e) => {
+            tracing::error!("❌ Failed to close nonce account: {}", e);
+            Err(ApiError::from(e))
+        }
+    }
+}
+
+fn parse_addresses(addresses: &[String]) -> Result<Vec<Pubkey>, ApiError> {
+    addresses.iter()
+        .map(|address| Pubkey::from_str(address)
+            .map_err(|e| ApiError::InvalidRequest(format!("Invalid address {}: {}", address, e))))
+        .collect()
+}
+
+#[utoipa::path(
+    post,
+    path = "/lookup-tables",
+    tag = "admin",
+    request_body = LookupTableRequest,
+    responses(
+        (status = 200, body = LookupTableResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse)
+    )
+)]
+pub async fn create_lookup_table(
+    State(state): State<AppState>,
+    body: Option<Json<LookupTableRequest>>,
+) -> Result<Json<LookupTableResponse>, ApiError> {
+    let Json(req) = body.unwrap_or_default();
+    let addresses = parse_addresses(&req.addresses)?;
+    match state.solana.create_lookup_table(addresses).await {
+        Ok((lookup_table, transactions)) => {
+            tracing::info!("✅ Lookup table created: {}", lookup_table.address);
+            Ok(Json(LookupTableResponse { success: true, lookup_table, transactions }))
+        },
+        Err(e) => {
+            tracing::error!("❌ Failed to create lookup table: {}", e);
+            Err(ApiError::from(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/lookup-tables/{address}",
+    tag = "admin",
+    params(("address" = String, Path, description = "Lookup table address")),
+    responses(
+        (status = 200, body = LookupTableResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse),
+        (status = 404, description = "Lookup table not found", body = ErrorResponse)
+    )
+)]
+pub async fn get_lookup_table(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+) -> Result<Json<LookupTableResponse>, ApiError> {
+    let address = Pubkey::from_str(&address)
+        .map_err(|e| ApiError::InvalidRequest(format!("Invalid address: {}", e)))?;
+    match state.solana.get_lookup_table(&address).await {
+        Ok(lookup_table) => Ok(Json(LookupTableResponse { success: true, lookup_table, transactions: Vec::new() })),
+        Err(e) => Err(ApiError::not_found(e)),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/lookup-tables/{address}/extend",
+    tag = "admin",
+    params(("address" = String, Path, description = "Lookup table address")),
+    request_body = LookupTableRequest,
+    responses(
+        (status = 200, body = LookupTableResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse)
+    )
+)]
+pub async fn extend_lookup_table(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+    Json(req): Json<LookupTableRequest>,
+) -> Result<Json<LookupTableResponse>, ApiError> {
+    let address = Pubkey::from_str(&address)
+        .map_err(|e| ApiError::InvalidRequest(format!("Invalid address: {}", e)))?;
+    let addresses = parse_addresses(&req.addresses)?;
+    let result = match state.solana.extend_lookup_table(&address, addresses).await {
+        Ok(transactions) => state.solana.get_lookup_table(&address).await
+            .map(|lookup_table| LookupTableResponse { success: true, lookup_table, transactions }),
+        Err(e) => Err(e),
+    };
+    result.map(Json).map_err(|e| {
+        tracing::error!("❌ Failed to extend lookup table: {}", e);
+        ApiError::from(e)
+    })
+}
+
+#[utoipa::path(
+    post,
+    path = "/devnet/airdrop",
+    tag = "admin",
+    request_body = AirdropRequest,
+    responses(
+        (status = 200, body = AirdropResponse),
+        (status = 400, description = "Not a devnet cluster or amount too large", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse)
+    )
+)]
+pub async fn devnet_airdrop(
+    State(state): State<AppState>,
+    Json(req): Json<AirdropRequest>,
+) -> Result<Json<AirdropResponse>, ApiError> {
+    if !state.solana.airdrop_enabled() {
+        return Err(ApiError::NotFound("Airdrops are only available on devnet".to_string()));
+    }
+    req.validate()?;
+    tracing::info!("🚰 Airdropping to {}", req.wallet);
+    
+    let wallet = Pubkey::from_str(&req.wallet)
+        .map_err(|e| ApiError::InvalidRequest(format!("Invalid wallet: {}", e)))?;
+    
+    match state.solana.airdrop(wallet, req.lamports, req.token_amount).await {
+        Ok(result) => {
+            tracing::info!("✅ Airdropped to {}", req.wallet);
+            Ok(Json(AirdropResponse {
+                success: true,
+                wallet: req.wallet,
+                sol_signature: result.sol_signature,
+                token_signature: result.token_signature,
+            }))
+        },
+        Err(e) => {
+            tracing::error!("❌ Airdrop failed: {}", e);
+            Err(ApiError::from(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/transactions/compose",
+    tag = "transactions",
+    request_body = ComposeRequest,
+    responses(
+        (status = 200, body = ComposeResponse),
+        (status = 400, description = "Invalid step", body = ErrorResponse),
+        (status = 422, description = "Invalid fields, or rejected by the program", body = ErrorResponse)
+    )
+)]
+pub async fn compose_transaction(
+    State(state): State<AppState>,
+    caller: Option<Extension<Caller>>,
+    Json(req): Json<ComposeRequest>,
+) -> Result<Json<ComposeResponse>, ApiError> {
+    req.validate()?;
+    tracing::info!("🧩 Composing {} operations into one transaction", req.steps.len());
+    
+    let parse_loan_pda = |loan_pda: &str| Pubkey::from_str(loan_pda)
+        .map_err(|e| ApiError::InvalidRequest(format!("Invalid loan PDA: {}", e)));
+    let bad_step = |index: usize, e: anyhow::Error| ApiError::invalid(e.context(format!("Step {}", index)));
+    
+    let tenant = state.tenants.for_caller(caller.as_deref());
+    let mut composer = state.solana.compose();
+    let mut addresses = Vec::with_capacity(req.steps.len());
+    // Created by this transaction, for the caller's tenant
+    let mut created = Vec::new();
+    for (index, step) in req.steps.iter().enumerate() {
+        let address = match step {
+            ComposeStep::InitializeAsset(asset) => {
+                if let Some(tenant) = tenant {
+                    tenant.check_asset_type(&asset.asset_type)?;
+                }
+                created.push((tenants::ASSET, asset.asset_id.clone()));
+                let owner = Pubkey::from_str(&asset.owner)
+                    .map_err(|e| ApiError::InvalidRequest(format!("Invalid owner: {}", e)))?;
+                screen_party(&state, &asset.owner, "asset_creation", &asset.asset_id).await?;
+                composer.initialize_asset(&asset.asset_id, &asset.asset_type, asset.valuation, &asset.metadata_uri, owner)
+                    .map_err(|e| bad_step(index, e))?
+            }
+            ComposeStep::UpdateRiskScore { asset_id, risk_score, source } => {
+                if source.as_deref() == Some("chainlink") {
+                    if let Err(e) = state.chainlink.trigger_risk_update(asset_id, *risk_score).await {
+                        tracing::warn!("⚠️ Chainlink workflow failed: {}", e);
+                    }
+                }
+                composer.update_risk_score(asset_id, *risk_score).map_err(|e| bad_step(index, e))?;
+                state.solana.asset_address(asset_id).0
+            }
+            ComposeStep::RequestLoan(loan) => {
+                let (borrower, mint, terms) = parse_loan_request(loan)?;
+                screen_party(&state, &loan.borrower, "loan_creation", &loan.asset_id).await?;
+                let loan_pda = composer.request_loan(&loan.asset_id, borrower, &terms, mint).await
+                    .map_err(|e| bad_step(index, e))?;
+                created.push((tenants::LOAN, loan_pda.to_string()));
+                loan_pda
+            }
+            ComposeStep::ApproveLoan { loan_pda } => {
+                let loan_pda = parse_loan_pda(loan_pda)?;
+                composer.approve_loan(loan_pda).map_err(|e| bad_step(index, e))?;
+                loan_pda
+            }
+            ComposeStep::RepayLoan { loan_pda } => {
+                let loan_pda = parse_loan_pda(loan_pda)?;
+                composer.repay_loan(loan_pda).map_err(|e| bad_step(index, e))?;
+                loan_pda
+            }
+        };
+        addresses.push(address.to_string());
+    }
+    
+    match composer.submit() {
+        Ok(composed) => {
+            let (transaction, unsigned_transaction) = composed.transaction.into_parts();
+            if transaction.is_some() {
+                tracing::info!("✅ Composed transaction sent");
+            }
+            for (kind, id) in &created {
+                assign_to_tenant(&state, tenant, kind, id).await;
+            }
+            Ok(Json(ComposeResponse {
+                success: true,
+                addresses,
+                transaction,
+                unsigned_transaction,
+                signers: composed.signers,
+            }))
+        },
+        Err(e) => {
+            tracing::error!("❌ Failed to compose transaction: {}", e);
+            Err(ApiError::from(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/estimate/asset",
+    tag = "estimates",
+    params(AssetEstimateQuery),
+    responses(
+        (status = 200, body = EstimateResponse),
+        (status = 400, description = "Invalid owner", body = ErrorResponse)
+    )
+)]
+pub async fn estimate_asset(
+    State(state): State<AppState>,
+    Query(query): Query<AssetEstimateQuery>,
+) -> Result<Json<EstimateResponse>, ApiError> {
+    tracing::info!("🧮 Estimating cost of asset: {}", query.asset_id);
+    
+    let owner = Pubkey::from_str(&query.owner)
+        .map_err(|e| ApiError::InvalidRequest(format!("Invalid owner: {}", e)))?;
+    
+    match state.solana.estimate_asset(&query.asset_id, &query.asset_type, owner).await {
+        Ok(estimate) => Ok(Json(EstimateResponse { success: true, estimate })),
+        Err(e) => {
+            tracing::error!("❌ Failed to estimate asset cost: {}", e);
+            Err(ApiError::from(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/estimate/loan",
+    tag = "estimates",
+    params(LoanEstimateQuery),
+    responses(
+        (status = 200, body = EstimateResponse),
+        (status = 400, description = "Invalid borrower", body = ErrorResponse)
+    )
+)]
+pub async fn estimate_loan(
+    State(state): State<AppState>,
+    Query(query): Query<LoanEstimateQuery>,
+) -> Result<Json<EstimateResponse>, ApiError> {
+    tracing::info!("🧮 Estimating cost of loan for asset: {}", query.asset_id);
+    
+    let borrower = Pubkey::from_str(&query.borrower)
+        .map_err(|e| ApiError::InvalidRequest(format!("Invalid borrower: {}", e)))?;
+    
+    match state.solana.estimate_loan(&query.asset_id, borrower).await {
+        Ok(estimate) => Ok(Json(EstimateResponse { success: true, estimate })),
+        Err(e) => {
+            tracing::error!("❌ Failed to estimate loan cost: {}", e);
+            Err(ApiError::from(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/pda/asset/{asset_id}",
+    tag = "pda",
+    params(("asset_id" = String, Path, description = "Asset id")),
+    responses((status = 200, body = PdaResponse))
+)]
+pub async fn asset_pda(
+    State(state): State<AppState>,
+    Path(asset_id): Path<String>,
+) -> Json<PdaResponse> {
+    let (address, bump) = state.solana.asset_address(&asset_id);
+    Json(PdaResponse {
+        success: true,
+        address: address.to_string(),
+        bump,
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/pda/loan",
+    tag = "pda",
+    params(LoanPdaQuery),
+    responses(
+        (status = 200, body = PdaResponse),
+        (status = 400, description = "Invalid borrower", body = ErrorResponse)
+    )
+)]
+pub async fn loan_pda(
+    State(state): State<AppState>,
+    Query(query): Query<LoanPdaQuery>,
+) -> Result<Json<PdaResponse>, ApiError> {
+    let borrower = Pubkey::from_str(&query.borrower)
+        .map_err(|e| ApiError::InvalidRequest(format!("Invalid borrower address: {}", e)))?;
+    
+    let (asset, _) = state.solana.asset_address(&query.asset_id);
+    let (address, bump) = state.solana.loan_address(&asset, &borrower);
+    Ok(Json(PdaResponse {
+        success: true,
+        address: address.to_string(),
+        bump,
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/transactions/{signature}",
+    tag = "transactions",
+    params(("signature" = String, Path, description = "Transaction signature")),
+    responses(
+        (status = 200, body = TransactionStatusResponse),
+        (status = 404, description = "Transaction not found", body = ErrorResponse)
+    )
+)]
+pub async fn get_transaction_status(
+    State(state): State<AppState>,
+    Path(signature): Path<String>,
+) -> Result<Json<TransactionStatusResponse>, ApiError> {
+    tracing::info!("🔍 Fetching transaction: {}", signature);
+    
+    let signature = Signature::from_str(&signature)
+        .map_err(|e| ApiError::InvalidRequest(format!("Invalid signature: {}", e)))?;
+    
+    match state.solana.get_transaction_status(&signature).await {
+        Ok(transaction) => Ok(Json(TransactionStatusResponse {
+            success: true,
+            transaction,
+        })),
+        Err(e) => Err(ApiError::not_found(e)),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/loans/{loan_pda}",
+    tag = "loans",
+    params(("loan_pda" = String, Path, description = "Loan account address"), CommitmentQuery),
+    responses(
+        (status = 200, body = LoanResponse, headers(("x-read-source" = String, description = "Where the answer came from: rpc, indexer or index"))),
+        (status = 404, description = "Loan not found", body = ErrorResponse)
+    )
+)]
+pub async fn get_loan(
+    State(state): State<AppState>,
+    role: Role,
+    Path(loan_pda): Path<String>,
+    Query(query): Query<CommitmentQuery>,
+) -> Result<([(&'static str, &'static str); 1], Json<LoanResponse>), ApiError> {
+    tracing::info!("🔍 Fetching loan: {}", loan_pda);
+    
+    let loan_pubkey = Pubkey::from_str(&loan_pda)
+        .map_err(|e| ApiError::InvalidRequest(format!("Invalid loan PDA: {}", e)))?;
+    
+    if query.commitment.is_none() && state.reads.uses_indexer(ReadEndpoint::Loan) {
+        let now = chrono::Utc::now().timestamp();
+        match state.storage.loans.get_loan(&loan_pda).await {
+            Ok(Some(record)) if state.reads.is_fresh(ReadEndpoint::Loan, record.updated_at, now) => {
+                let mut loan = serde_json::to_value(record).unwrap();
+                role.redact(&mut loan);
+                return Ok((ReadSource::Indexer.header(), Json(LoanResponse {
+                    success: true,
+                    loan,
+                })));
+            },
+            Ok(_) => {},
+            Err(e) => tracing::warn!("⚠️ Indexer read failed, falling back to RPC: {}", e),
+        }
+    }
+    
+    match state.solana.get_loan(loan_pubkey, query.commitment.map(Commitment::config)).await {
+        Ok(loan) => {
+            let mut loan = serde_json::to_value(loan).unwrap();
+            role.redact(&mut loan);
+            Ok((ReadSource::Rpc.header(), Json(LoanResponse {
+                success: true,
+                loan,
+            })))
+        },
+        Err(e) => {
+            Err(ApiError::not_found(e.context("Loan not found")))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/loans",
+    tag = "loans",
+    params(LoanListQuery),
+    responses(
+        (status = 200, body = LoanListResponse),
+        (status = 400, description = "Invalid filter or cursor", body = ErrorResponse),
+        (status = 403, description = "Filter or sort hidden from this role", body = ErrorResponse)
+    )
+)]
+pub async fn list_loans(
+    State(state): State<AppState>,
+    role: Role,
+    caller: Option<Extension<Caller>>,
+    Query(query): Query<LoanListQuery>,
+) -> Result<Json<LoanListResponse>, ApiError> {
+    tracing::info!("📋 Listing loans");
+    
+    if query.borrower.is_some() && !role.can_see("borrower") {
+        return Err(ApiError::Forbidden("Filtering by borrower is restricted for this role".to_string()));
+    }
+    if matches!(query.sort, LoanSort::Principal) && !role.can_see("principal") {
+        return Err(ApiError::Forbidden("Sorting by principal is restricted for this role".to_string()));
+    }
+    let borrower = query.borrower
+        .map(|borrower| Pubkey::from_str(&borrower))
+        .transpose()
+        .map_err(|e| ApiError::InvalidRequest(format!("Invalid borrower: {}", e)))?;
+    let asset = query.asset_id.map(|asset_id| state.solana.asset_address(&asset_id).0);
+    // A tenant's callers see only the loans it created
+    let scope = tenant_scope(&state, state.tenants.for_caller(caller.as_deref()), tenants::LOAN).await?;
+    
+    let listed = match state.index.loans(borrower.as_ref()) {
+        Some(loans) => Ok(loans),
+        None => state.solana.list_loans().await
+            .map(|loans| loans.into_iter().filter(|(_, loan)| borrower.is_none_or(|borrower| loan.borrower == borrower)).collect()),
+    };
+    match listed {
+        Ok(loans) => {
+            let loans: Vec<(String, _)> = loans.into_iter()
+                .filter(|(_, loan)| asset.is_none_or(|asset| loan.asset == asset))
+                .filter(|(_, loan)| query.status.is_none_or(|status| loan.status == status))
+                .map(|(loan_pda, loan)| (loan_pda.to_string(), loan))
+                .filter(|(loan_pda, _)| scope.as_ref().is_none_or(|scope| scope.contains(loan_pda)))
+                .collect();
+            let page = pagination::paginate(loans, query.sort.as_str(), query.order, query.cursor.as_deref(), query.limit, |(loan_pda, loan)| {
+                let key = match query.sort {
+                    LoanSort::StartTime => loan.start_time as i128,
+                    LoanSort::Principal => loan.principal as i128,
+                    LoanSort::RiskScore => loan.risk_score_at_creation as i128,
+                };
+                (key, loan_pda.as_str())
+            }).map_err(|e| ApiError::InvalidRequest(e.to_string()))?;
+            let mut loans = serde_json::Value::Array(page.items.into_iter().map(|(loan_pda, loan)| {
+                let mut loan = serde_json::to_value(crate::solana_client::LoanResponse::from(loan)).unwrap();
+                loan["loan_pda"] = serde_json::Value::String(loan_pda);
+                loan
+            }).collect());
+            role.redact(&mut loans);
+            let serde_json::Value::Array(loans) = loans else { unreachable!() };
+            Ok(Json(LoanListResponse {
+                success: true,
+                total: page.total,
+                next_cursor: page.next_cursor,
+                loans,
+            }))
+        },
+        Err(e) => {
+            tracing::error!("❌ Failed to list loans: {}", e);
+            Err(ApiError::from(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/loans/{loan_pda}/approve",
+    tag = "loans",
+    params(("loan_pda" = String, Path, description = "Loan account address")),
+    responses(
+        (status = 200, body = LoanActionResponse),
+        (status = 422, description = "Rejected by the program", body = ErrorResponse)
+    )
+)]
+pub async fn approve_loan(
+    State(state): State<AppState>,
+    Path(loan_pda): Path<String>,
+) -> Result<Json<LoanActionResponse>, ApiError> {
+    tracing::info!("🖊️ Approving loan: {}", loan_pda);
+    
+    let loan_pubkey = Pubkey::from_str(&loan_pda)
+        .map_err(|e| ApiError::InvalidRequest(format!("Invalid loan PDA: {}", e)))?;
+    
+    match state.solana.approve_loan(loan_pubkey).await {
+        Ok(transaction) => {
+            tracing::info!("✅ Loan approved: {}", loan_pda);
+            log_storage_error("loan", state.storage.loans
+                .update_loan_status(&loan_pda, "active", chrono::Utc::now().timestamp()).await);
+            Ok(Json(LoanActionResponse {
+                success: true,
+                transaction,
+                loan_pda,
+            }))
+        },
+        Err(e) => {
+            tracing::error!("❌ Failed to approve loan: {}", e);
+            Err(ApiError::from(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/loans/{loan_pda}/cancel",
+    tag = "loans",
+    params(("loan_pda" = String, Path, description = "Loan account address")),
+    responses(
+        (status = 200, body = LoanActionResponse),
+        (status = 422, description = "Rejected by the program", body = ErrorResponse)
+    )
+)]
+pub async fn cancel_loan_request(
+    State(state): State<AppState>,
+    Path(loan_pda): Path<String>,
+) -> Result<Json<LoanActionResponse>, ApiError> {
+    tracing::info!("🚫 Cancelling loan request: {}", loan_pda);
+    
+    let loan_pubkey = Pubkey::from_str(&loan_pda)
+        .map_err(|e| ApiError::InvalidRequest(format!("Invalid loan PDA: {}", e)))?;
+    
+    match state.solana.cancel_loan_request(loan_pubkey).await {
+        Ok(transaction) => {
+            tracing::info!("✅ Loan request cancelled: {}", loan_pda);
+            log_storage_error("loan", state.storage.loans
+                .update_loan_status(&loan_pda, "cancelled", chrono::Utc::now().timestamp()).await);
+            Ok(Json(LoanActionResponse {
+                success: true,
+                transaction,
+                loan_pda,
+            }))
+        },
+        Err(e) => {
+            tracing::error!("❌ Failed to cancel loan request: {}", e);
+            Err(ApiError::from(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/loans/{loan_pda}/repay",
+    tag = "loans",
+    params(("loan_pda" = String, Path, description = "Loan account address")),
+    responses(
+        (status = 200, body = RepayLoanResponse),
+        (status = 422, description = "Rejected by the program", body = ErrorResponse)
+    )
+)]
+pub async fn repay_loan(
+    State(state): State<AppState>,
+    Path(loan_pda): Path<String>,
+) -> Result<Json<RepayLoanResponse>, ApiError> {
+    tracing::info!("💸 Repaying loan: {}", loan_pda);
+    
+    let loan_pubkey = Pubkey::from_str(&loan_pda)
+        .map_err(|e| ApiError::InvalidRequest(format!("Invalid loan PDA: {}", e)))?;
+    
+    match state.solana.repay_loan(loan_pubkey).await {
+        Ok(submission) => {
+            let (transaction, unsigned_transaction) = submission.into_parts();
+            // The loan stays open until the borrower's wallet submits
+            if transaction.is_some() {
+                tracing::info!("✅ Loan repaid: {}", loan_pda);
+                log_storage_error("loan", state.storage.loans
+                    .update_loan_status(&loan_pda, "repaid", chrono::Utc::now().timestamp()).await);
+            }
+            Ok(Json(RepayLoanResponse {
+                success: true,
+                loan_pda,
+                transaction,
+                unsigned_transaction,
+            }))
+        },
+        Err(e) => {
+            tracing::error!("❌ Failed to repay loan: {}", e);
+            Err(ApiError::from(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/loans/{loan_pda}/liquidate",
+    tag = "loans",
+    params(("loan_pda" = String, Path, description = "Loan account address")),
+    responses(
+        (status = 200, body = LoanActionResponse),
+        (status = 422, description = "Rejected by the program", body = ErrorResponse)
+    )
+)]
+pub async fn liquidate_loan(
+    State(state): State<AppState>,
+    Path(loan_pda): Path<String>,
+) -> Result<Json<LoanActionResponse>, ApiError> {
+    tracing::info!("⚡ Liquidating loan: {}", loan_pda);
+    
+    let loan_pubkey = Pubkey::from_str(&loan_pda)
+        .map_err(|e| ApiError::InvalidRequest(format!("Invalid loan PDA: {}", e)))?;
+    
+    // Replicas racing to liquidate the same loan would pay for the loser's failed transaction
+    let lock = format!("liquidate:{}", loan_pda);
+    match state.coordination.exclusive(&lock, state.solana.liquidate_loan(loan_pubkey)).await? {
+        Ok(transaction) => {
+            tracing::info!("✅ Loan liquidated: {}", loan_pda);
+            log_storage_error("loan", state.storage.loans
+                .update_loan_status(&loan_pda, "liquidated", chrono::Utc::now().timestamp()).await);
+            Ok(Json(LoanActionResponse {
+                success: true,
+                transaction,
+                loan_pda,
+            }))
+        },
+        Err(e) => {
+            tracing::error!("❌ Failed to liquidate loan: {}", e);
+            Err(ApiError::from(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/loans/{loan_pda}/schedule",
+    tag = "loans",
+    params(("loan_pda" = String, Path, description = "Loan account address"), CommitmentQuery),
+    responses(
+        (status = 200, body = ScheduleResponse),
+        (status = 404, description = "Schedule not found", body = ErrorResponse)
+    )
+)]
+pub async fn get_loan_schedule(
+    State(state): State<AppState>,
+    Path(loan_pda): Path<String>,
+    Query(query): Query<CommitmentQuery>,
+) -> Result<Json<ScheduleResponse>, ApiError> {
+    tracing::info!("📅 Fetching repayment schedule for loan: {}", loan_pda);
+    
+    let loan_pubkey = Pubkey::from_str(&loan_pda)
+        .map_err(|e| ApiError::InvalidRequest(format!("Invalid loan PDA: {}", e)))?;
+    
+    match state.solana.get_repayment_schedule(loan_pubkey, query.commitment.map(Commitment::config)).await {
+        Ok(schedule) => {
+            Ok(Json(ScheduleResponse {
+                success: true,
+                schedule: serde_json::to_value(schedule).unwrap(),
+            }))
+        },
+        Err(e) => {
+            Err(ApiError::not_found(e.context("Schedule not found")))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/owners/{pubkey}/portfolio",
+    tag = "assets",
+    params(("pubkey" = String, Path, description = "Owner wallet")),
+    responses(
+        (status = 200, body = PortfolioResponse),
+        (status = 400, description = "Invalid wallet", body = ErrorResponse),
+        (status = 403, description = "Owners hidden from this role", body = ErrorResponse)
+    )
+)]
+pub async fn get_owner_portfolio(
+    State(state): State<AppState>,
+    role: Role,
+    Path(owner): Path<String>,
+) -> Result<Json<PortfolioResponse>, ApiError> {
+    tracing::info!("💼 Building portfolio for: {}", owner);
+
+    // A portfolio is an owner filter under another name
+    if !role.can_see("owner") {
+        return Err(ApiError::Forbidden("Portfolios are restricted for this role".to_string()));
+    }
+    let owner = Pubkey::from_str(&owner)
+        .map_err(|e| ApiError::InvalidRequest(format!("Invalid owner: {}", e)))?;
+
+    let filter = AssetFilter { owner: Some(owner), ..Default::default() };
+    let assets = match state.index.assets(&filter) {
+        Some(assets) => assets,
+        None => state.solana.list_assets(&filter, None).await?,
+    };
+    let loans = match state.index.loans(Some(&owner)) {
+        Some(loans) => loans,
+        None => state.solana.list_loans().await?,
+    };
+    let now = chrono::Utc::now().timestamp();
+    let portfolio = portfolio::build(&owner, assets, loans, |asset_id| state.solana.asset_address(asset_id).0, now);
+
+    let mut portfolio = serde_json::to_value(portfolio).unwrap();
+    role.redact(&mut portfolio);
+    Ok(Json(PortfolioResponse { success: true, portfolio }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/loans/{loan_pda}/payoff",
+    tag = "loans",
+    params(("loan_pda" = String, Path, description = "Loan account address"), PayoffQuery),
+    responses(
+        (status = 200, body = PayoffResponse),
+        (status = 400, description = "Timestamp in the past", body = ErrorResponse),
+        (status = 404, description = "Loan not found", body = ErrorResponse),
+        (status = 422, description = "Loan is not open", body = ErrorResponse)
+    )
+)]
+pub async fn get_loan_payoff(
+    State(state): State<AppState>,
+    Path(loan_pda): Path<String>,
+    Query(query): Query<PayoffQuery>,
+) -> Result<Json<PayoffResponse>, ApiError> {
+    tracing::info!("💵 Quoting payoff for loan: {}", loan_pda);
+
+    let loan_pubkey = Pubkey::from_str(&loan_pda)
+        .map_err(|e| ApiError::InvalidRequest(format!("Invalid loan PDA: {}", e)))?;
+    // Allow a minute of clock skew; older quotes could never be paid
+    if query.at.is_some_and(|at| at < chrono::Utc::now().timestamp() - 60) {
+        return Err(ApiError::InvalidRequest("at must not be in the past".to_string()));
+    }
+
+    match state.solana.payoff_quote(loan_pubkey, query.at).await {
+        Ok(quote) => Ok(Json(PayoffResponse { success: true, quote })),
+        Err(e) => Err(ApiError::not_found(e)),
+    }
+}
+
+/// Once any webhook signers are registered, webhook bodies must come signed
+/// by one: `X-Oracle-Signer` names the key and `X-Oracle-Signature` carries
+/// its base58 ed25519 signature of the raw body.
+async fn verify_oracle_signature(state: &AppState, headers: &HeaderMap, body: &[u8]) -> Result<(), ApiError> {
+    let signers = state.storage.oracle_signers.list_oracle_signers().await
+        .map_err(|e| ApiError::Unavailable(format!("Oracle signers unavailable: {}", e)))?;
+    if signers.is_empty() {
+        return Ok(());
+    }
+    let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+    let (Some(signer), Some(signature)) = (header("x-oracle-signer"), header("x-oracle-signature")) else {
+        return Err(ApiError::Unauthenticated("Send X-Oracle-Signer and X-Oracle-Signature".to_string()));
+    };
+    if !signers.iter().any(|registered| registered.pubkey == signer) {
+        return Err(ApiError::Unauthenticated(format!("Unknown oracle signer: {}", signer)));
+    }
+    let verified = Pubkey::from_str(signer).ok()
+        .zip(Signature::from_str(signature).ok())
+        .is_some_and(|(signer, signature)| signature.verify(signer.as_ref(), body));
+    match verified {
+        true => Ok(()),
+        false => Err(ApiError::Unauthenticated("Invalid oracle signature".to_string())),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/chainlink/webhook",
+    tag = "risk",
+    request_body = ChainlinkWebhookRequest,
+    responses(
+        (status = 200, body = Object),
+        (status = 401, description = "Missing or invalid oracle signature", body = ErrorResponse),
+        (status = 403, description = "Caller lacks the oracle role", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse)
+    )
+)]
+pub async fn chainlink_webhook(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    verify_oracle_signature(&state, &headers, &body).await?;
+    let req: ChainlinkWebhookRequest = serde_json::from_slice(&body)
+        .map_err(|e| ApiError::InvalidRequest(format!("Invalid body: {}", e)))?;
+    req.validate()?;
+    tracing::info!(
+        "⛓️ Chainlink webhook received for asset: {} (confidence: {}, sources: {:?})",
+        req.asset_id, req.confidence, req.sources
+    );
+    
+    let event = RiskEvent::now(&req.asset_id, req.risk_score, "chainlink", Some(req.confidence), None);
+    log_storage_error("risk event", state.storage.risk_history.record_risk_event(&event).await);
+    
+    // Update risk score from Chainlink
+    // Note: You'll need to implement the Solana update here
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "workflow_id": req.workflow_id,
+        "asset_id": req.asset_id,
+        "risk_score": req.risk_score,
+        "status": "received"
+    })))
+}
+
+#[utoipa::path(
+    get,
+    path = "/assets/{asset_id}/risk/history",
+    tag = "risk",
+    params(("asset_id" = String, Path, description = "Asset id"), RiskHistoryQuery),
+    responses(
+        (status = 200, body = RiskHistoryResponse),
+        (status = 400, description = "Invalid cursor or limit", body = ErrorResponse)
+    )
+)]
+pub async fn get_risk_history(
+    State(state): State<AppState>,
+    role: Role,
+    Path(asset_id): Path<String>,
+    Query(query): Query<RiskHistoryQuery>,
+) -> Result<Json<RiskHistoryResponse>, ApiError> {
+    tracing::info!("📈 Fetching risk history for: {}", asset_id);
+    
+    let limit = query.limit.unwrap_or(pagination::DEFAULT_LIMIT);
+    if limit == 0 || limit > pagination::MAX_LIMIT {
+        return Err(ApiError::InvalidRequest(format!("limit must be between 1 and {}", pagination::MAX_LIMIT)));
+    }
+    let before = query.cursor
+        .map(|cursor| {
+            let (recorded_at, id) = Cursor::resume(&cursor, "recorded_at", Order::Desc)?;
+            Ok::<_, anyhow::Error>((i64::try_from(recorded_at)?, id.parse::<i64>()?))
+        })
+        .transpose()
+        .map_err(|_| ApiError::InvalidRequest("Malformed cursor".to_string()))?;
+    
+    // One extra tells whether another page follows
+    let filter = RiskEventFilter { source: query.source, from: query.from, to: query.to, before, limit: limit + 1 };
+    let mut events = state.storage.risk_history.list_risk_events(&asset_id, &filter).await
+        .map_err(|e| {
+            tracing::error!("❌ Failed to load risk history: {}", e);
+            ApiError::Unavailable(format!("Risk history unavailable: {}", e))
+        })?;
+    let next_cursor = (events.len() > limit).then(|| {
+        let last = &events[limit - 1];
+        Cursor::new("recorded_at", Order::Desc, last.recorded_at as i128, last.id.to_string()).encode()
+    });
+    events.truncate(limit);
+    
+    let history = events
+        .iter()
+        .map(|event| {
+            let mut event = serde_json::to_value(event).unwrap();
+            role.redact(&mut event);
+            event
+        })
+        .collect();
+    Ok(Json(RiskHistoryResponse { success: true, asset_id, history, next_cursor }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/research/loan-book",
+    tag = "research",
+    params(LoanBookQuery),
+    responses((status = 200, body = Object))
+)]
+pub async fn get_research_loan_book(
+    State(state): State<AppState>,
+    Query(query): Query<LoanBookQuery>,
+) -> Result<Json<LoanBookView>, ApiError> {
+    tracing::info!("🔬 Building research loan book view");
+    
+    let loans = match state.index.loans(None) {
+        Some(loans) => Ok(loans),
+        None => state.solana.list_loans().await,
+    };
+    match loans {
+        Ok(loans) => {
+            let loans: Vec<_> = loans.into_iter().map(|(_, loan)| loan).collect();
+            Ok(Json(data_sharing::loan_book_view(&loans, query.k)))
+        },
+        Err(e) => {
+            tracing::error!("❌ Failed to load loan book: {}", e);
+            Err(ApiError::from(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/stats",
+    tag = "system",
+    responses((status = 200, body = StatsResponse))
+)]
+pub async fn get_stats(
+    State(state): State<AppState>,
+) -> Result<Json<StatsResponse>, ApiError> {
+    tracing::info!("📈 Fetching protocol stats");
+    
+    let load_failed = |e: anyhow::Error| {
+        tracing::error!("❌ Failed to load protocol stats: {}", e);
+        ApiError::from(e)
+    };
+    let protocol_revenue = state.solana.revenue_stats().await.map_err(load_failed)?;
+    let protocol = state.solana.protocol_stats().await.map_err(load_failed)?;
+    
+    Ok(Json(StatsResponse { success: true, protocol_revenue, protocol }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/compliance/blacklist",
+    tag = "compliance",
+    responses(
+        (status = 200, body = BlacklistResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse)
+    )
+)]
+pub async fn get_blacklist(
+    State(state): State<AppState>,
+) -> Result<Json<BlacklistResponse>, ApiError> {
+    match state.solana.get_blacklist().await {
+        Ok(addresses) => Ok(Json(BlacklistResponse {
+            success: true,
+            addresses: addresses.iter().map(|address| address.to_string()).collect(),
+        })),
+        Err(e) => {
+            tracing::error!("❌ Failed to load blacklist: {}", e);
+            Err(ApiError::from(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/compliance/blacklist/{address}",
+    tag = "compliance",
+    params(("address" = String, Path, description = "Wallet address")),
+    responses(
+        (status = 200, body = BlacklistStatusResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse)
+    )
+)]
+pub async fn get_blacklist_status(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+) -> Result<Json<BlacklistStatusResponse>, ApiError> {
+    let wallet = Pubkey::from_str(&address)
+        .map_err(|e| ApiError::InvalidRequest(format!("Invalid address: {}", e)))?;
+    
+    match state.solana.get_blacklist().await {
+        Ok(addresses) => Ok(Json(BlacklistStatusResponse {
+            success: true,
+            blacklisted: addresses.contains(&wallet),
+            address,
+        })),
+        Err(e) => {
+            tracing::error!("❌ Failed to load blacklist: {}", e);
+            Err(ApiError::from(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/compliance/blacklist",
+    tag = "compliance",
+    request_body = BlacklistRequest,
+    responses(
+        (status = 200, body = BlacklistUpdateResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse),
+        (status = 422, description = "Invalid fields, or rejected by the program", body = ErrorResponse)
+    )
+)]
+pub async fn add_to_blacklist(
+    State(state): State<AppState>,
+    Json(req): Json<BlacklistRequest>,
+) -> Result<Json<BlacklistUpdateResponse>, ApiError> {
+    req.validate()?;
+    tracing::info!("🚫 Blacklisting {}", req.address);
+    
+    let wallet = Pubkey::from_str(&req.address)
+        .map_err(|e| ApiError::InvalidRequest(format!("Invalid address: {}", e)))?;
+    
+    match state.solana.add_to_blacklist(wallet).await {
+        Ok(transaction) => {
+            tracing::info!("✅ Blacklisted {}", req.address);
+            Ok(Json(BlacklistUpdateResponse {
+                success: true,
+                transaction,
+                address: req.address,
+                blacklisted: true,
+            }))
+        },
+        Err(e) => {
+            tracing::error!("❌ Failed to blacklist: {}", e);
+            Err(ApiError::from(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/compliance/blacklist/{address}",
+    tag = "compliance",
+    params(("address" = String, Path, description = "Wallet address")),
+    responses(
+        (status = 200, body = BlacklistUpdateResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse),
+        (status = 422, description = "Rejected by the program", body = ErrorResponse)
+    )
+)]
+pub async fn remove_from_blacklist(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+) -> Result<Json<BlacklistUpdateResponse>, ApiError> {
+    tracing::info!("♻️ Removing {} from blacklist", address);
+    
+    let wallet = Pubkey::from_str(&address)
+        .map_err(|e| ApiError::InvalidRequest(format!("Invalid address: {}", e)))?;
+    
+    match state.solana.remove_from_blacklist(wallet).await {
+        Ok(transaction) => {
+            tracing::info!("✅ Removed {} from blacklist", address);
+            Ok(Json(BlacklistUpdateResponse {
+                success: true,
+                transaction,
+                address,
+                blacklisted: false,
+            }))
+        },
+        Err(e) => {
+            tracing::error!("❌ Failed to remove from blacklist: {}", e);
+            Err(ApiError::from(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/notifications/subscribers",
+    tag = "admin",
+    responses(
+        (status = 200, body = SubscribersResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse)
+    )
+)]
+pub async fn list_subscribers(State(state): State<AppState>) -> Result<Json<SubscribersResponse>, ApiError> {
+    let subscribers = state.storage.subscribers.list_subscribers().await
+        .map_err(|e| ApiError::Unavailable(format!("Subscribers unavailable: {}", e)))?;
+    Ok(Json(SubscribersResponse {
+        success: true,
+        channels: notifier::CHANNELS.iter().copied().filter(|channel| state.notifier.supports(channel)).collect(),
+        subscribers,
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/notifications/subscribers",
+    tag = "admin",
+    request_body = SubscriberRequest,
+    responses(
+        (status = 200, body = SubscriberUpdateResponse),
+        (status = 400, description = "Channel not configured", body = ErrorResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse),
+        (status = 422, description = "Invalid fields", body = ErrorResponse)
+    )
+)]
+pub async fn add_subscriber(
+    State(state): State<AppState>,
+    Json(req): Json<SubscriberRequest>,
+) -> Result<Json<SubscriberUpdateResponse>, ApiError> {
+    req.validate()?;
+    if !state.notifier.supports(&req.channel) {
+        return Err(ApiError::InvalidRequest(format!("The {} channel is not configured", req.channel)));
+    }
+    tracing::info!("🔔 Subscribing {} by {}", req.id, req.channel);
+    
+    let subscriber = SubscriberRecord {
+        id: req.id.clone(),
+        channel: req.channel,
+        target: req.target,
+        secret: req.secret,
+        events: req.events,
+        risk_threshold: req.risk_threshold,
+        maturity_notice_secs: req.maturity_notice_secs.unwrap_or(notifier::DEFAULT_MATURITY_NOTICE_SECS),
+        asset_id: req.asset_id,
+        borrower: req.borrower,
+        created_at: chrono::Utc::now().timestamp(),
+    };
+    state.storage.subscribers.upsert_subscriber(&subscriber).await
+        .map_err(|e| ApiError::Unavailable(format!("Failed to save subscriber: {}", e)))?;
+    if let Err(e) = state.notifier.reload(&state.storage).await {
+        tracing::warn!("⚠️ Notifier keeps its old subscribers until the next reload: {}", e);
+    }
+    
+    Ok(Json(SubscriberUpdateResponse { success: true, id: subscriber.id, subscribed: true }))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/admin/notifications/subscribers/{id}",
+    tag = "admin",
+    params(("id" = String, Path, description = "Subscriber id")),
+    responses(
+        (status = 200, body = SubscriberUpdateResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse),
+        (status = 404, description = "No such subscriber", body = ErrorResponse)
+    )
+)]
+pub async fn remove_subscriber(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<SubscriberUpdateResponse>, ApiError> {
+    tracing::info!("🔕 Unsubscribing {}", id);
+    
+    match state.storage.subscribers.remove_subscriber(&id).await {
+        Ok(true) => {}
+        Ok(false) => return Err(ApiError::NotFound(format!("No such subscriber: {}", id))),
+        Err(e) => return Err(ApiError::Unavailable(format!("Failed to remove subscriber: {}", e))),
+    }
+    if let Err(e) = state.notifier.reload(&state.storage).await {
+        tracing::warn!("⚠️ Notifier keeps its old subscribers until the next reload: {}", e);
+    }
+    
+    Ok(Json(SubscriberUpdateResponse { success: true, id, subscribed: false }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/oracles",
+    tag = "admin",
+    responses(
+        (status = 200, body = OraclesResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse)
+    )
+)]
+pub async fn list_oracles(State(state): State<AppState>) -> Result<Json<OraclesResponse>, ApiError> {
+    let risk_oracle = state.solana.risk_oracle().await.map_err(ApiError::not_found)?;
+    let bonded = state.solana.list_oracle_bonds().await?;
+    let webhook_signers = state.storage.oracle_signers.list_oracle_signers().await
+        .map_err(|e| ApiError::Unavailable(format!("Oracle signers unavailable: {}", e)))?;
+    Ok(Json(OraclesResponse {
+        success: true,
+        risk_oracle: risk_oracle.to_string(),
+        bonded,
+        webhook_signers,
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/oracles",
+    tag = "admin",
+    request_body = AddOracleRequest,
+    responses(
+        (status = 200, body = OracleUpdateResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse),
+        (status = 422, description = "Invalid fields, or rejected by the program", body = ErrorResponse)
+    )
+)]
+pub async fn add_oracle(
+    State(state): State<AppState>,
+    caller: Option<Extension<Caller>>,
+    Json(req): Json<AddOracleRequest>,
+) -> Result<Json<OracleUpdateResponse>, ApiError> {
+    req.validate()?;
+    tracing::info!("🔮 Adding oracle {}", req.pubkey);
+    
+    let result = async {
+        // The on-chain appointment first, so a rejected one adds no signer
+        let transaction = match req.risk_oracle {
+            true => {
+                let oracle = Pubkey::from_str(&req.pubkey)
+                    .map_err(|e| ApiError::InvalidRequest(format!("Invalid pubkey: {}", e)))?;
+                Some(state.solana.set_risk_oracle(oracle).await?)
+            }
+            false => None,
+        };
+        let signer = OracleSignerRecord {
+            pubkey: req.pubkey.clone(),
+            label: req.label.clone(),
+            added_by: caller.as_deref().map_or("anonymous", |caller| caller.subject.as_str()).to_string(),
+            added_at: chrono::Utc::now().timestamp(),
+        };
+        state.storage.oracle_signers.add_oracle_signer(&signer).await
+            .map_err(|e| ApiError::Unavailable(format!("Failed to add oracle signer: {}", e)))?;
+        Ok::<_, ApiError>(transaction)
+    }
+    .await;
+    
+    match result {
+        Ok(transaction) => {
+            tracing::info!("✅ Oracle {} added", req.pubkey);
+            Ok(Json(OracleUpdateResponse {
+                success: true,
+                pubkey: req.pubkey,
+                transaction,
+                webhook_signer: true,
+                risk_oracle: req.risk_oracle,
+            }))
+        },
+        Err(e) => {
+            tracing::error!("❌ Failed to add oracle: {}", e.message());
+            Err(e)
+        }
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/admin/oracles/{pubkey}",
+    tag = "admin",
+    params(("pubkey" = String, Path, description = "Oracle public key")),
+    responses(
+        (status = 200, body = OracleUpdateResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse),
+        (status = 404, description = "Not a webhook signer", body = ErrorResponse),
+        (status = 409, description = "Still the program's risk oracle", body = ErrorResponse)
+    )
+)]
+pub async fn remove_oracle(
+    State(state): State<AppState>,
+    Path(pubkey): Path<String>,
+) -> Result<Json<OracleUpdateResponse>, ApiError> {
+    tracing::info!("🔮 Removing oracle {}", pubkey);
+    
+    // The program has no way to leave the role empty
+    if state.solana.risk_oracle().await?.to_string() == pubkey {
+        return Err(ApiError::Conflict(format!("{} is the program's risk oracle; appoint another first", pubkey)));
+    }
+    match state.storage.oracle_signers.remove_oracle_signer(&pubkey).await {
+        Ok(true) => {}
+        Ok(false) => return Err(ApiError::NotFound(format!("Not a webhook signer: {}", pubkey))),
+        Err(e) => return Err(ApiError::Unavailable(format!("Failed to remove oracle signer: {}", e))),
+    }
+    tracing::info!("✅ Oracle {} removed", pubkey);
+    Ok(Json(OracleUpdateResponse {
+        success: true,
+        pubkey,
+        transaction: None,
+        webhook_signer: false,
+        risk_oracle: false,
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/screening/{address}",
+    tag = "compliance",
+    params(("address" = String, Path, description = "Wallet address")),
+    responses(
+        (status = 200, body = ScreeningHistoryResponse),
+        (status = 403, description = "Caller lacks the admin role", body = ErrorResponse)
+    )
+)]
+pub async fn get_screening_history(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+) -> Result<Json<ScreeningHistoryResponse>, ApiError> {
+    match state.storage.screenings.list_screenings(&address, 100).await {
+        Ok(screenings) => Ok(Json(ScreeningHistoryResponse {
+            success: true,
+            address,
+            screenings,
+        })),
+        Err(e) => {
+            tracing::error!("❌ Failed to load screening history: {}", e);
+            Err(ApiError::from(e))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/capacity/simulate",
+    tag = "research",
+    request_body = Object,
+    responses(
+        (status = 200, body = Object),
+        (status = 400, description = "Invalid scenario", body = ErrorResponse)
+    )
+)]
+pub async fn simulate_capacity(
+    State(state): State<AppState>,
+    Json(scenario): Json<CapacityScenario>,
+) -> Result<Json<CapacityProjection>, ApiError> {
+    tracing::info!("📊 Simulating capacity for {} assets every {}s", scenario.asset_count, scenario.update_interval_secs);
+    
+    if scenario.update_interval_secs == 0 {
+        return Err(ApiError::InvalidRequest("update_interval_secs must be positive".to_string()));
+    }
+    
+    let fees = match (scenario.lamports_per_signature, scenario.priority_fee_micro_lamports) {
+        (Some(lamports_per_signature), Some(priority_fee_micro_lamports)) => FeeConditions {
+            lamports_per_signature,
+            priority_fee_micro_lamports,
+            source: "request",
+        },
+        (base, priority) => match state.solana.fee_conditions().await {
+            Ok((current_base, current_priority)) => FeeConditions {
+                lamports_per_signature: base.unwrap_or(current_base),
+                priority_fee_micro_lamports: priority.unwrap_or(current_priority),
+                source: "rpc",
+            },
+            Err(e) => {
+                tracing::warn!("⚠️ Using default fees for capacity simulation: {}", e);
+                FeeConditions {
+                    lamports_per_signature: base.unwrap_or(capacity::DEFAULT_LAMPORTS_PER_SIGNATURE),
+                    priority_fee_micro_lamports: priority.unwrap_or(0),
+                    source: "default",
+                }
+            }
+        },
+    };
+    
+    Ok(Json(capacity::project(&scenario, fees)))
+}
+
+// Create router function
+#[utoipa::path(
+    get,
+    path = "/webhooks/events",
+    tag = "webhooks",
+    responses((status = 200, body = Object))
+)]
+pub async fn get_webhook_events() -> Json<serde_json::Value> {
+    Json(serde_json::json!({
+        "success": true,
+        "signature_header": "X-Webhook-Signature",
+        "envelope": webhooks::envelope_schema(),
+        "events": webhooks::catalog(),
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/webhooks/{endpoint_id}/test",
+    tag = "webhooks",
+    params(("endpoint_id" = String, Path, description = "Configured endpoint id")),
+    request_body = TestDeliveryRequest,
+    responses(
+        (status = 200, body = Object),
+        (status = 404, description = "Unknown endpoint", body = ErrorResponse),
+        (status = 502, description = "Delivery failed", body = ErrorResponse)
+    )
+)]
+pub async fn send_test_webhook(
+    State(state): State<AppState>,
+    Path(endpoint_id): Path<String>,
+    req: Option<Json<TestDeliveryRequest>>,
+) -> Result<Json<DeliveryResult>, ApiError> {
+    let endpoint = state.webhooks.endpoint(&endpoint_id)
+        .ok_or_else(|| ApiError::NotFound(format!("Unknown webhook endpoint: {}", endpoint_id)))?;
+    let event = req
+        .and_then(|Json(req)| req.event)
+        .unwrap_or_else(|| webhooks::catalog()[0].event.to_string());
+    if !webhooks::catalog().iter().any(|descriptor| descriptor.event == event) {
+        return Err(ApiError::InvalidRequest(format!("Unknown event type: {}", event)));
+    }
+    
+    tracing::info!("📨 Sending test {} delivery to webhook {}", event, endpoint_id);
+    
+    match state.webhooks.send_test(endpoint, &event).await {
+        Ok(result) => {
+            tracing::info!("✅ Test delivery {} returned {}", result.delivery_id, result.status_code);
+            Ok(Json(result))
+        },
+        Err(e) => {
+            tracing::error!("❌ Test delivery failed: {}", e);
+            Err(ApiError::Unavailable(e.to_string()))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/idl/status",
+    tag = "system",
+    responses((status = 200, body = Object))
+)]
+pub async fn get_idl_status(
+    State(state): State<AppState>,
+) -> Json<IdlSyncStatus> {
+    Json(state.idl_sync.status())
 }
 
-// Create router function
 pub fn create_router(state: AppState) -> Router {
     Router::new()
         .route("/health", get(health_check))
-        .route("/assets", post(create_asset))
+        .route("/health/live", get(health_live))
+        .route("/health/ready", get(health_ready))
+        .route("/assets", get(list_assets).post(create_asset))
+        .route("/assets/batch", post(create_assets))
         .route("/assets/:asset_id", get(get_asset))
+        .route("/assets/:asset_id/deactivate", post(deactivate_asset))
+        .route("/assets/:asset_id/reactivate", post(reactivate_asset))
+        .route("/assets/:asset_id/delegate", post(set_delegate))
+        .route("/assets/:asset_id/delegate/revoke", post(revoke_delegate))
+        .route("/assets/:asset_id/transfer", post(transfer_asset))
         .route("/assets/:asset_id/risk", post(update_risk))
         .route("/assets/:asset_id/risk/latest", get(get_latest_risk))
         .route("/assets/:asset_id/risk/history", get(get_risk_history))
-        .route("/loans", post(create_loan))
+        .route("/stream/assets/:asset_id", get(stream_asset))
+        .route(auth::SOCKET_PATH, get(ws::upgrade))
+        .route("/risk/reports", post(post_risk_report))
+        .route("/risk/reports/:report_id/apply", post(apply_risk_report_entry))
+        .route("/loans", get(list_loans).post(create_loan))
+        .route("/loans/preview", post(preview_loan))
         .route("/loans/:loan_pda", get(get_loan))
+        .route("/loans/:loan_pda/approve", post(approve_loan))
+        .route("/loans/:loan_pda/cancel", post(cancel_loan_request))
+        .route("/loans/:loan_pda/repay", post(repay_loan))
+        .route("/loans/:loan_pda/liquidate", post(liquidate_loan))
+        .route("/loans/:loan_pda/schedule", get(get_loan_schedule))
+        .route("/loans/:loan_pda/payoff", get(get_loan_payoff))
+        .route("/owners/:pubkey/portfolio", get(get_owner_portfolio))
+        .route("/transactions/submit", post(submit_transaction))
+        .route("/transactions/compose", post(compose_transaction))
+        .route("/transactions/:signature", get(get_transaction_status))
+        .route("/estimate/asset", get(estimate_asset))
+        .route("/estimate/loan", get(estimate_loan))
+        .route("/pda/asset/:asset_id", get(asset_pda))
+        .route("/pda/loan", get(loan_pda))
+        .route("/devnet/airdrop", post(devnet_airdrop))
+        .route("/admin/payer/rotate", post(rotate_payer))
+        .route("/admin/wallet", get(get_wallet))
+        .route("/admin/liquidator", get(get_liquidator))
+        .route("/admin/audit", get(get_audit_log))
+        .route("/admin/oracles", get(list_oracles).post(add_oracle))
+        .route("/admin/oracles/:pubkey", delete(remove_oracle))
+        .route("/admin/notifications/subscribers", get(list_subscribers).post(add_subscriber))
+        .route("/admin/notifications/subscribers/:id", delete(remove_subscriber))
+        .route("/nonces", post(create_nonce_account))
+        .route("/lookup-tables", post(create_lookup_table))
+        .route("/lookup-tables/:address", get(get_lookup_table))
+        .route("/lookup-tables/:address/extend", post(extend_lookup_table))
+        .route("/nonces/:address", get(get_nonce_account).delete(close_nonce_account))
         .route("/chainlink/webhook", post(chainlink_webhook))
+        .route("/research/loan-book", get(get_research_loan_book))
+        .route("/stats", get(get_stats))
+        .route("/compliance/blacklist", get(get_blacklist).post(add_to_blacklist))
+        .route("/compliance/blacklist/:address", get(get_blacklist_status).delete(remove_from_blacklist))
+        .route("/screening/:address", get(get_screening_history))
+        .route("/capacity/simulate", post(simulate_capacity))
+        .route("/idl/status", get(get_idl_status))
+        .route("/webhooks/events", get(get_webhook_events))
+        .route("/webhooks/:endpoint_id/test", post(send_test_webhook))
+        .route_layer(middleware::from_fn_with_state(state.clone(), tenants::use_payer))
+        .route_layer(middleware::from_fn_with_state(state.clone(), coordination::cache_reads))
+        .route_layer(middleware::from_fn_with_state(state.clone(), coordination::idempotency))
+        .route_layer(middleware::from_fn_with_state(state.clone(), idl_sync::guard_mutations))
+        .route_layer(middleware::from_fn(auth::authorize))
+        .route_layer(middleware::from_fn_with_state(state.clone(), audit::record_mutations))
+        .route_layer(middleware::from_fn_with_state(state.clone(), rate_limit::limit))
+        .route_layer(middleware::from_fn_with_state(state.clone(), auth::authenticate))
+        // Registered after the layers, so the contract is always readable
+        .merge(openapi::routes())
         .with_state(state)
 }
@@ -0,0 +1,240 @@
+use anyhow::{anyhow, Result};
+use reqwest::Client as HttpClient;
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashSet;
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::storage::{ScreeningRecord, Storage};
+
+const DEFAULT_INTERVAL_SECS: u64 = 24 * 60 * 60;
+const PAGE_SIZE: usize = 100;
+
+// ==================== Screening Policy ====================
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScreeningMode {
+    /// Refuse the request when an address matches.
+    Block,
+    /// Let the request through but record and log the match.
+    Flag,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScreeningDecision {
+    Cleared,
+    Flagged,
+    Blocked,
+}
+
+impl ScreeningDecision {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ScreeningDecision::Cleared => "cleared",
+            ScreeningDecision::Flagged => "flagged",
+            ScreeningDecision::Blocked => "blocked",
+        }
+    }
+}
+
+struct ScreeningOutcome {
+    provider: &'static str,
+    matched: bool,
+    detail: Option<String>,
+}
+
+/// Expected response of the external provider at `SCREENING_API_URL`.
+#[derive(Debug, Deserialize)]
+struct ProviderResponse {
+    sanctioned: bool,
+    reason: Option<String>,
+}
+
+// ==================== Screener ====================
+pub struct Screener {
+    local_list: HashSet<String>,
+    api_url: Option<String>,
+    api_key: Option<String>,
+    mode: ScreeningMode,
+    http_client: HttpClient,
+}
+
+impl Screener {
+    /// Local matches come from `SANCTIONS_LIST` (comma-separated) and
+    /// `SANCTIONS_LIST_PATH` (one address per line, `#` comments); an external
+    /// provider is queried at `SCREENING_API_URL` when set.
+    pub fn from_env() -> Self {
+        let mut local_list: HashSet<String> = env::var("SANCTIONS_LIST")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|address| !address.is_empty())
+            .map(str::to_string)
+            .collect();
+
+        if let Ok(path) = env::var("SANCTIONS_LIST_PATH") {
+            match std::fs::read_to_string(&path) {
+                Ok(contents) => local_list.extend(
+                    contents.lines()
+                        .map(|line| line.split('#').next().unwrap_or_default().trim())
+                        .filter(|address| !address.is_empty())
+                        .map(str::to_string),
+                ),
+                Err(e) => tracing::warn!("⚠️ Could not read SANCTIONS_LIST_PATH {}: {}", path, e),
+            }
+        }
+
+        let mode = match env::var("SCREENING_MODE").as_deref() {
+            Ok("flag") => ScreeningMode::Flag,
+            _ => ScreeningMode::Block,
+        };
+
+        Self {
+            local_list,
+            api_url: env::var("SCREENING_API_URL").ok(),
+            api_key: env::var("SCREENING_API_KEY").ok(),
+            mode,
+            http_client: HttpClient::new(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.local_list.is_empty() || self.api_url.is_some()
+    }
+
+    async fn screen(&self, address: &str) -> Result<ScreeningOutcome> {
+        if self.local_list.contains(address) {
+            return Ok(ScreeningOutcome {
+                provider: "local_list",
+                matched: true,
+                detail: Some("Address is on the local sanctions list".to_string()),
+            });
+        }
+
+        let Some(url) = &self.api_url else {
+            return Ok(ScreeningOutcome { provider: "local_list", matched: false, detail: None });
+        };
+
+        let mut request = self.http_client.post(url).json(&json!({ "address": address }));
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+        let response: ProviderResponse = request
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| anyhow!("Screening provider request failed: {}", e))?
+            .json()
+            .await
+            .map_err(|e| anyhow!("Invalid screening provider response: {}", e))?;
+
+        Ok(ScreeningOutcome {
+            provider: "api",
+            matched: response.sanctioned,
+            detail: response.reason,
+        })
+    }
+
+    /// Screen `address`, record the result, and decide whether the request
+    /// it belongs to may proceed. Provider failures are returned as errors so
+    /// callers fail closed.
+    pub async fn check(
+        &self,
+        storage: &Storage,
+        address: &str,
+        context: &str,
+        subject: &str,
+    ) -> Result<ScreeningDecision> {
+        if !self.is_enabled() {
+            return Ok(ScreeningDecision::Cleared);
+        }
+
+        let outcome = self.screen(address).await?;
+        let decision = match (outcome.matched, self.mode) {
+            (false, _) => ScreeningDecision::Cleared,
+            (true, ScreeningMode::Flag) => ScreeningDecision::Flagged,
+            (true, ScreeningMode::Block) => ScreeningDecision::Blocked,
+        };
+        if outcome.matched {
+            tracing::error!("🚫 Sanctions match for {} ({} {}): {}", address, context, subject, decision.as_str());
+        }
+
+        let record = ScreeningRecord {
+            address: address.to_string(),
+            context: context.to_string(),
+            subject: subject.to_string(),
+            provider: outcome.provider.to_string(),
+            matched: outcome.matched,
+            action: decision.as_str().to_string(),
+            detail: outcome.detail,
+            screened_at: chrono::Utc::now().timestamp(),
+        };
+        if let Err(e) = storage.screenings.record_screening(&record).await {
+            tracing::warn!("⚠️ Failed to persist screening result: {}", e);
+        }
+
+        Ok(decision)
+    }
+
+    /// Re-screen every mirrored asset owner and borrower. Matches on existing
+    /// positions cannot be blocked after the fact, so they are only flagged.
+    async fn rescreen(&self, storage: &Storage) -> Result<()> {
+        let mut seen = HashSet::new();
+        let mut offset = 0;
+        loop {
+            let assets = storage.assets.list_assets(PAGE_SIZE, offset).await?;
+            for asset in &assets {
+                let mut parties = vec![(asset.owner.clone(), asset.asset_id.clone())];
+                for loan in storage.loans.list_loans_for_asset(&asset.asset_id).await? {
+                    parties.push((loan.borrower, loan.loan_pda));
+                }
+                for (address, subject) in parties {
+                    if seen.insert(address.clone()) {
+                        let outcome = self.screen(&address).await?;
+                        if outcome.matched {
+                            tracing::error!("🚫 Scheduled screening matched {} ({})", address, subject);
+                        }
+                        storage.screenings.record_screening(&ScreeningRecord {
+                            address,
+                            context: "scheduled".to_string(),
+                            subject,
+                            provider: outcome.provider.to_string(),
+                            matched: outcome.matched,
+                            action: if outcome.matched { "flagged" } else { "cleared" }.to_string(),
+                            detail: outcome.detail,
+                            screened_at: chrono::Utc::now().timestamp(),
+                        }).await?;
+                    }
+                }
+            }
+            if assets.len() < PAGE_SIZE {
+                break;
+            }
+            offset += PAGE_SIZE;
+        }
+        tracing::info!("✅ Scheduled screening checked {} addresses", seen.len());
+        Ok(())
+    }
+}
+
+/// Re-screen known parties every `SCREENING_INTERVAL_SECS` while screening is enabled.
+pub fn spawn(screener: Arc<Screener>, storage: Arc<Storage>) {
+    if !screener.is_enabled() {
+        return;
+    }
+    let interval = env::var("SCREENING_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_INTERVAL_SECS);
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(interval.max(1)));
+        loop {
+            ticker.tick().await;
+            if let Err(e) = screener.rescreen(&storage).await {
+                tracing::warn!("⚠️ Scheduled screening failed: {}", e);
+            }
+        }
+    });
+}
@@ -0,0 +1,442 @@
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use hmac::{Hmac, Mac};
+use reqwest::Client as HttpClient;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use solana_remote_wallet::locator::Locator;
+use solana_remote_wallet::remote_keypair::generate_remote_keypair;
+use solana_remote_wallet::remote_wallet::maybe_wallet_manager;
+use solana_sdk::derivation_path::DerivationPath;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{read_keypair_file, Keypair, Signature};
+use solana_sdk::signer::{Signer, SignerError};
+use std::collections::HashMap;
+use std::env;
+use std::future::Future;
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+
+const REQUEST_TIMEOUT_SECS: u64 = 10;
+/// DER SubjectPublicKeyInfo header ahead of a raw 32-byte Ed25519 key.
+const ED25519_SPKI_PREFIX: [u8; 12] = [0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00];
+
+/// Signs for the service payer wherever its key lives.
+pub type PayerSigner = Arc<dyn Signer + Send + Sync>;
+
+// ==================== Backend Selection ====================
+/// Where a payer key lives. Unset fields fall back to the environment.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "backend", rename_all = "snake_case")]
+pub enum SignerSource {
+    /// A JSON keypair file; by default `WALLET_PRIVATE_KEY` or
+    /// `~/.config/solana/id.json`.
+    Keypair { path: Option<String> },
+    /// An `ECC_NIST_EDWARDS25519` AWS KMS key; by default `KMS_KEY_ID`.
+    Kms { key_id: Option<String> },
+    /// An `ed25519` HashiCorp Vault transit key; by default `VAULT_TRANSIT_KEY`.
+    Vault { key: Option<String> },
+    /// A Ledger device (requires the `ledger` build feature); by default at
+    /// `LEDGER_DERIVATION_PATH`.
+    Ledger { derivation_path: Option<String> },
+}
+
+impl SignerSource {
+    /// `SIGNER_BACKEND` picks the backend: `keypair` (default), `kms`,
+    /// `vault` or `ledger`.
+    pub fn from_env() -> Result<Self> {
+        let backend = env::var("SIGNER_BACKEND").unwrap_or_else(|_| "keypair".to_string());
+        match backend.as_str() {
+            "keypair" => Ok(Self::Keypair { path: None }),
+            "kms" => Ok(Self::Kms { key_id: None }),
+            "vault" => Ok(Self::Vault { key: None }),
+            "ledger" => Ok(Self::Ledger { derivation_path: None }),
+            other => Err(anyhow!("Unknown SIGNER_BACKEND: {}", other)),
+        }
+    }
+
+    fn backend(&self) -> &'static str {
+        match self {
+            Self::Keypair { .. } => "keypair",
+            Self::Kms { .. } => "kms",
+            Self::Vault { .. } => "vault",
+            Self::Ledger { .. } => "ledger",
+        }
+    }
+}
+
+pub fn load(source: &SignerSource) -> Result<PayerSigner> {
+    let signer: PayerSigner = match source {
+        SignerSource::Keypair { path } => Arc::new(local_keypair(path.as_deref())?),
+        SignerSource::Kms { key_id } => Arc::new(KmsSigner::new(key_or_var(key_id, "KMS_KEY_ID")?)?),
+        SignerSource::Vault { key } => Arc::new(VaultSigner::new(key_or_var(key, "VAULT_TRANSIT_KEY")?)?),
+        SignerSource::Ledger { derivation_path } => Arc::new(LedgerSigner::new(
+            derivation_path.clone().or_else(|| env::var("LEDGER_DERIVATION_PATH").ok()).as_deref(),
+        )?),
+    };
+    tracing::info!("🔑 Payer {} signs with the {} backend", signer.pubkey(), source.backend());
+    Ok(signer)
+}
+
+fn local_keypair(path: Option<&str>) -> Result<Keypair> {
+    if let Some(path) = path {
+        return read_keypair_file(path)
+            .map_err(|e| anyhow!("Failed to read keypair: {}", e));
+    }
+    if let Ok(private_key) = env::var("WALLET_PRIVATE_KEY") {
+        let bytes: Vec<u8> = serde_json::from_str(&private_key)
+            .map_err(|e| anyhow!("Invalid private key format: {}", e))?;
+        Keypair::from_bytes(&bytes)
+            .map_err(|e| anyhow!("Failed to create keypair: {}", e))
+    } else {
+        let home = dirs::home_dir().ok_or_else(|| anyhow!("Could not find home dir"))?;
+        let keypath = home.join(".config/solana/id.json");
+        read_keypair_file(&keypath)
+            .map_err(|e| anyhow!("Failed to read keypair: {}", e))
+    }
+}
+
+fn key_or_var(key: &Option<String>, name: &str) -> Result<String> {
+    match key {
+        Some(key) => Ok(key.clone()),
+        None => required_var(name),
+    }
+}
+
+fn required_var(name: &str) -> Result<String> {
+    env::var(name).map_err(|_| anyhow!("{} must be set", name))
+}
+
+fn http_client() -> Result<HttpClient> {
+    HttpClient::builder()
+        .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| anyhow!("Failed to build HTTP client: {}", e))
+}
+
+// `Signer` is synchronous while the remote backends are reached over async
+// HTTP, so block the worker thread the same way the blocking RpcClient does.
+fn block_on<F: Future>(future: F) -> F::Output {
+    tokio::task::block_in_place(|| tokio::runtime::Handle::current().block_on(future))
+}
+
+fn remote_error(backend: &str, error: impl std::fmt::Display) -> SignerError {
+    SignerError::Custom(format!("{} signing failed: {}", backend, error))
+}
+
+// ==================== AWS KMS ====================
+/// Signs with `Sign` on an AWS KMS Ed25519 key. Credentials come from
+/// `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and optionally
+/// `AWS_SESSION_TOKEN`; requests carry a SigV4 signature.
+pub struct KmsSigner {
+    key_id: String,
+    region: String,
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+    pubkey: Pubkey,
+    http_client: HttpClient,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct KmsPublicKey {
+    public_key: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct KmsSignature {
+    signature: String,
+}
+
+impl KmsSigner {
+    /// `key_id` (key id, ARN or alias) in `AWS_REGION`.
+    pub fn new(key_id: String) -> Result<Self> {
+        let mut signer = Self {
+            key_id,
+            region: required_var("AWS_REGION")?,
+            access_key_id: required_var("AWS_ACCESS_KEY_ID")?,
+            secret_access_key: required_var("AWS_SECRET_ACCESS_KEY")?,
+            session_token: env::var("AWS_SESSION_TOKEN").ok(),
+            pubkey: Pubkey::default(),
+            http_client: http_client()?,
+        };
+
+        let response: KmsPublicKey = block_on(signer.call("GetPublicKey", json!({ "KeyId": signer.key_id })))?;
+        let der = BASE64.decode(&response.public_key)
+            .map_err(|e| anyhow!("Invalid KMS public key: {}", e))?;
+        let raw = der.strip_prefix(&ED25519_SPKI_PREFIX[..])
+            .filter(|raw| raw.len() == 32)
+            .ok_or_else(|| anyhow!("KMS key {} is not an Ed25519 key", signer.key_id))?;
+        signer.pubkey = Pubkey::try_from(raw).map_err(|_| anyhow!("Invalid KMS public key"))?;
+        Ok(signer)
+    }
+
+    async fn call<T: for<'de> Deserialize<'de>>(&self, action: &str, body: Value) -> Result<T> {
+        let host = format!("kms.{}.amazonaws.com", self.region);
+        let target = format!("TrentService.{}", action);
+        let body = body.to_string();
+        let now = chrono::Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+
+        let mut headers = vec![
+            ("content-type", "application/x-amz-json-1.1".to_string()),
+            ("host", host.clone()),
+            ("x-amz-date", amz_date.clone()),
+        ];
+        if let Some(token) = &self.session_token {
+            headers.push(("x-amz-security-token", token.clone()));
+        }
+        headers.push(("x-amz-target", target));
+
+        let signed_headers = headers.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(";");
+        let canonical_headers: String = headers.iter()
+            .map(|(name, value)| format!("{}:{}\n", name, value))
+            .collect();
+        let canonical_request = format!(
+            "POST\n/\n\n{}\n{}\n{}",
+            canonical_headers,
+            signed_headers,
+            hex::encode(Sha256::digest(body.as_bytes())),
+        );
+        let scope = format!("{}/{}/kms/aws4_request", date, self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex::encode(Sha256::digest(canonical_request.as_bytes())),
+        );
+
+        let mut key = format!("AWS4{}", self.secret_access_key).into_bytes();
+        for part in [date.as_str(), self.region.as_str(), "kms", "aws4_request", &string_to_sign] {
+            let mut mac = Hmac::<Sha256>::new_from_slice(&key)
+                .map_err(|e| anyhow!("Invalid signing key: {}", e))?;
+            mac.update(part.as_bytes());
+            key = mac.finalize().into_bytes().to_vec();
+        }
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.access_key_id,
+            scope,
+            signed_headers,
+            hex::encode(key),
+        );
+
+        let mut request = self.http_client
+            .post(format!("https://{}/", host))
+            .header("authorization", authorization)
+            .body(body);
+        for (name, value) in headers.into_iter().filter(|(name, _)| *name != "host") {
+            request = request.header(name, value);
+        }
+
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(anyhow!("KMS {} returned {}: {}", action, status, text));
+        }
+        Ok(response.json().await?)
+    }
+}
+
+impl Signer for KmsSigner {
+    fn try_pubkey(&self) -> Result<Pubkey, SignerError> {
+        Ok(self.pubkey)
+    }
+
+    fn try_sign_message(&self, message: &[u8]) -> Result<Signature, SignerError> {
+        let response: KmsSignature = block_on(self.call("Sign", json!({
+            "KeyId": self.key_id,
+            "Message": BASE64.encode(message),
+            "MessageType": "RAW",
+            "SigningAlgorithm": "ED25519_SHA_512",
+        }))).map_err(|e| remote_error("KMS", e))?;
+        let bytes = BASE64.decode(&response.signature).map_err(|e| remote_error("KMS", e))?;
+        Signature::try_from(bytes.as_slice()).map_err(|e| remote_error("KMS", e))
+    }
+
+    fn is_interactive(&self) -> bool {
+        false
+    }
+}
+
+// ==================== Vault Transit ====================
+/// Signs with the transit engine of a HashiCorp Vault server, pinned to the
+/// key version that was current at startup so the payer never changes
+/// underneath a running service.
+pub struct VaultSigner {
+    address: String,
+    token: String,
+    namespace: Option<String>,
+    mount: String,
+    key_name: String,
+    key_version: u64,
+    pubkey: Pubkey,
+    http_client: HttpClient,
+}
+
+#[derive(Deserialize)]
+struct VaultResponse<T> {
+    data: T,
+}
+
+#[derive(Deserialize)]
+struct VaultKey {
+    latest_version: u64,
+    keys: HashMap<String, VaultKeyVersion>,
+}
+
+#[derive(Deserialize)]
+struct VaultKeyVersion {
+    public_key: String,
+}
+
+#[derive(Deserialize)]
+struct VaultSignature {
+    signature: String,
+}
+
+impl VaultSigner {
+    /// Transit key `key_name` at `VAULT_ADDR`, authenticated by
+    /// `VAULT_TOKEN`, with the engine mounted at `VAULT_TRANSIT_MOUNT`
+    /// (default `transit`) and an optional `VAULT_NAMESPACE`.
+    pub fn new(key_name: String) -> Result<Self> {
+        let mut signer = Self {
+            address: required_var("VAULT_ADDR")?.trim_end_matches('/').to_string(),
+            token: required_var("VAULT_TOKEN")?,
+            namespace: env::var("VAULT_NAMESPACE").ok(),
+            mount: env::var("VAULT_TRANSIT_MOUNT").unwrap_or_else(|_| "transit".to_string()),
+            key_name,
+            key_version: 0,
+            pubkey: Pubkey::default(),
+            http_client: http_client()?,
+        };
+
+        let url = format!("{}/v1/{}/keys/{}", signer.address, signer.mount, signer.key_name);
+        let key: VaultResponse<VaultKey> = block_on(signer.send(signer.http_client.get(url)))?;
+        let version = key.data.keys.get(&key.data.latest_version.to_string())
+            .ok_or_else(|| anyhow!("Vault key {} has no version {}", signer.key_name, key.data.latest_version))?;
+        let raw = BASE64.decode(&version.public_key)
+            .map_err(|e| anyhow!("Invalid Vault public key: {}", e))?;
+        signer.pubkey = Pubkey::try_from(raw.as_slice())
+            .map_err(|_| anyhow!("Vault key {} is not an ed25519 key", signer.key_name))?;
+        signer.key_version = key.data.latest_version;
+        Ok(signer)
+    }
+
+    async fn send<T: for<'de> Deserialize<'de>>(&self, request: reqwest::RequestBuilder) -> Result<T> {
+        let mut request = request.header("X-Vault-Token", &self.token);
+        if let Some(namespace) = &self.namespace {
+            request = request.header("X-Vault-Namespace", namespace);
+        }
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(anyhow!("Vault returned {}: {}", status, text));
+        }
+        Ok(response.json().await?)
+    }
+}
+
+impl Signer for VaultSigner {
+    fn try_pubkey(&self) -> Result<Pubkey, SignerError> {
+        Ok(self.pubkey)
+    }
+
+    fn try_sign_message(&self, message: &[u8]) -> Result<Signature, SignerError> {
+        let url = format!("{}/v1/{}/sign/{}", self.address, self.mount, self.key_name);
+        let request = self.http_client.post(url).json(&json!({
+            "input": BASE64.encode(message),
+            "key_version": self.key_version,
+        }));
+        let response: VaultResponse<VaultSignature> = block_on(self.send(request))
+            .map_err(|e| remote_error("Vault", e))?;
+
+        // "vault:v<version>:<base64 signature>"
+        let encoded = response.data.signature.rsplit(':').next().unwrap_or_default();
+        let bytes = BASE64.decode(encoded).map_err(|e| remote_error("Vault", e))?;
+        Signature::try_from(bytes.as_slice()).map_err(|e| remote_error("Vault", e))
+    }
+
+    fn is_interactive(&self) -> bool {
+        false
+    }
+}
+
+// ==================== Ledger ====================
+type SignRequest = (Vec<u8>, mpsc::Sender<Result<Signature, SignerError>>);
+
+/// Signs on a Ledger device running the Solana app. The device handle is not
+/// thread-safe, so it lives on a dedicated thread that signs one message at a
+/// time; each signature needs approval on the device.
+pub struct LedgerSigner {
+    pubkey: Pubkey,
+    requests: mpsc::Sender<SignRequest>,
+}
+
+impl LedgerSigner {
+    /// `LEDGER_LOCATOR` (default `usb://ledger`) selects the device and
+    /// `derivation_path` (default `m/44'/501'`) the key on it.
+    pub fn new(derivation_path: Option<&str>) -> Result<Self> {
+        let locator = Locator::new_from_path(env::var("LEDGER_LOCATOR").unwrap_or_else(|_| "usb://ledger".to_string()))
+            .map_err(|e| anyhow!("Invalid LEDGER_LOCATOR: {}", e))?;
+        let derivation_path = match derivation_path {
+            Some(path) => DerivationPath::from_absolute_path_str(path)
+                .map_err(|e| anyhow!("Invalid derivation path: {}", e))?,
+            None => DerivationPath::default(),
+        };
+
+        let (requests, incoming) = mpsc::channel::<SignRequest>();
+        let (ready, connected) = mpsc::channel();
+        thread::spawn(move || {
+            let keypair = maybe_wallet_manager()
+                .map_err(|e| anyhow!("Ledger unavailable: {}", e))
+                .and_then(|manager| manager.ok_or_else(|| anyhow!("No Ledger device found")))
+                .and_then(|manager| {
+                    generate_remote_keypair(locator, derivation_path, &manager, false, "payer")
+                        .map_err(|e| anyhow!("Failed to open Ledger: {}", e))
+                });
+            let keypair = match keypair {
+                Ok(keypair) => {
+                    let _ = ready.send(Ok(keypair.pubkey));
+                    keypair
+                }
+                Err(e) => {
+                    let _ = ready.send(Err(e));
+                    return;
+                }
+            };
+            for (message, reply) in incoming {
+                let _ = reply.send(keypair.try_sign_message(&message));
+            }
+        });
+
+        let pubkey = tokio::task::block_in_place(|| connected.recv())
+            .map_err(|_| anyhow!("Ledger thread exited"))??;
+        Ok(Self { pubkey, requests })
+    }
+}
+
+impl Signer for LedgerSigner {
+    fn try_pubkey(&self) -> Result<Pubkey, SignerError> {
+        Ok(self.pubkey)
+    }
+
+    fn try_sign_message(&self, message: &[u8]) -> Result<Signature, SignerError> {
+        let (reply, signature) = mpsc::channel();
+        self.requests.send((message.to_vec(), reply))
+            .map_err(|_| remote_error("Ledger", "device thread exited"))?;
+        tokio::task::block_in_place(|| signature.recv())
+            .map_err(|_| remote_error("Ledger", "device thread exited"))?
+    }
+
+    fn is_interactive(&self) -> bool {
+        true
+    }
+}
@@ -1,27 +1,122 @@
+use solana_client::nonce_utils;
 use solana_client::rpc_client::RpcClient;
+use solana_client::nonblocking::pubsub_client::PubsubClient;
+use solana_client::rpc_config::{
+    RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSimulateTransactionConfig,
+    RpcTransactionConfig, RpcTransactionLogsConfig, RpcTransactionLogsFilter,
+};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_account_decoder::UiAccountEncoding;
+use solana_transaction_status::UiTransactionEncoding;
 use solana_sdk::{
+    account::Account,
     pubkey::Pubkey,
-    signature::{Keypair, read_keypair_file},
+    signature::{Keypair, Signature},
+    hash::Hash,
+    nonce,
     signer::Signer,
     commitment_config::CommitmentConfig,
     system_program,
-    instruction::Instruction,
-    transaction::Transaction,
+    instruction::{CompiledInstruction, Instruction},
+    message::Message,
+    system_instruction,
+    transaction::{Transaction, VersionedTransaction},
+    address_lookup_table::{self, state::AddressLookupTable, AddressLookupTableAccount},
+    message::{v0, VersionedMessage},
+    packet::PACKET_DATA_SIZE,
+    native_token::LAMPORTS_PER_SOL,
+    rent::Rent,
 };
-use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
 use std::str::FromStr;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use serde_json::json;
 use std::env;
+use std::fmt;
+use std::io::Read;
+use std::time::Duration;
+use futures::StreamExt;
+use tokio::sync::broadcast;
 use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use borsh::{BorshDeserialize, BorshSerialize};
+use flate2::read::ZlibDecoder;
+
+use crate::account_cache::AccountCache;
+use crate::jito::JitoClient;
+use crate::network::Network;
+use crate::commitment::{Commitment, WriteCommitments};
+use crate::events::{self, ParsedLogs, ProgramEvent};
+use crate::idl::{self, ProgramError, ProgramIdl};
+use crate::priority_fees::PriorityFees;
+use crate::signer::{self, PayerSigner, SignerSource};
+use crate::risk_reports;
+
+const TOKEN_PROGRAM_ID: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+const TOKEN_2022_PROGRAM_ID: &str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb";
+const ASSOCIATED_TOKEN_PROGRAM_ID: &str = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
+const TOKEN_METADATA_PROGRAM_ID: &str = "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s";
+const DEVNET_GENESIS_HASH: &str = "EtWTRABZaYq6iMfeYKouRu166VU2xqa1wcaWoxPkrZBG";
+/// Balance a new payer needs before `rotate_payer` switches to it, unless
+/// `PAYER_MIN_BALANCE_LAMPORTS` says otherwise.
+const DEFAULT_PAYER_MIN_BALANCE: u64 = LAMPORTS_PER_SOL / 2;
+// Program account sizes, discriminator included; an asset's also grows with
+// the byte lengths of its id and type.
+const ASSET_ACCOUNT_BASE_SPACE: usize = 429;
+const ASSET_ACCOUNT_MAX_SPACE: usize = ASSET_ACCOUNT_BASE_SPACE + 64;
+const LOAN_ACCOUNT_SPACE: usize = 462;
+const TOKEN_ACCOUNT_SPACE: usize = 165;
+const MINT_ACCOUNT_SPACE: usize = 82;
+/// Token metadata accounts are allocated at their maximum length.
+const METADATA_ACCOUNT_SPACE: usize = 679;
+/// Largest single SOL airdrop devnet's faucet grants.
+const MAX_AIRDROP_LAMPORTS: u64 = 2 * LAMPORTS_PER_SOL;
+
+/// Instructions the service builds; the loaded IDL must declare each.
+const OPERATIONS: [&str; 19] = [
+    "initialize_asset",
+    "update_risk_score",
+    "request_loan",
+    "approve_loan",
+    "cancel_loan_request",
+    "deactivate_asset",
+    "reactivate_asset",
+    "set_delegate",
+    "revoke_delegate",
+    "transfer_asset",
+    "post_risk_report",
+    "apply_risk_report_entry",
+    "mint_asset_nft",
+    "sync_asset_nft",
+    "add_to_blacklist",
+    "remove_from_blacklist",
+    "repay_loan",
+    "liquidate_loan",
+    "set_risk_oracle",
+];
 
-const PROGRAM_ID: &str = "3ekhJkk57HSt8Rfj44fmgjhix9UXTJVBi6ZQEz7Hs5Po";
+// Pubsub updates buffered per subscriber before the slowest starts missing them
+const UPDATE_CHANNEL_CAPACITY: usize = 1024;
+const SUBSCRIPTION_MIN_BACKOFF: Duration = Duration::from_secs(1);
+const SUBSCRIPTION_MAX_BACKOFF: Duration = Duration::from_secs(60);
 
-// ==================== CORRECT DISCRIMINATORS FROM IDL ====================
-const DISCRIMINATOR_INITIALIZE_ASSET: [u8; 8] = [214, 153, 49, 248, 95, 248, 208, 179];
-const DISCRIMINATOR_UPDATE_RISK: [u8; 8] = [80, 138, 35, 224, 23, 172, 20, 254];
-const DISCRIMINATOR_CREATE_LOAN: [u8; 8] = [166, 131, 118, 219, 138, 218, 206, 140];
-const DISCRIMINATOR_REPAY_LOAN: [u8; 8] = [224, 93, 144, 77, 61, 17, 137, 54];
-const DISCRIMINATOR_LIQUIDATE_LOAN: [u8; 8] = [111, 249, 185, 54, 161, 147, 178, 24];
+// Addresses per extend_lookup_table instruction that keep it within a legacy packet
+const LOOKUP_TABLE_EXTEND_BATCH: usize = 20;
+
+// How long a sent transaction may take to reach its write commitment
+const CONFIRMATION_TIMEOUT: Duration = Duration::from_secs(90);
+const CONFIRMATION_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+// Offset of `reserve_factor_bps` in ProtocolConfig: discriminator, admin, bump, arbitrator
+const CONFIG_RESERVE_FACTOR_OFFSET: usize = 8 + 32 + 1 + 32;
+// Offset of `max_risk_age`: the reserve factor, compliance authority, risk
+// oracle, jump threshold, breaker cooldown, lender compliance authority,
+// whitelist flag, origination fee and referral share come between
+// Offset of `risk_oracle`: the reserve factor and compliance authority come between
+const CONFIG_RISK_ORACLE_OFFSET: usize = CONFIG_RESERVE_FACTOR_OFFSET + 2 + 32;
+const CONFIG_MAX_RISK_AGE_OFFSET: usize = CONFIG_RESERVE_FACTOR_OFFSET + 2 + 32 + 32 + 1 + 8 + 32 + 1 + 2 + 2;
 
 // ==================== API Response Types ====================
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,6 +129,52 @@ pub struct AssetResponse {
     pub is_active: bool,
     pub risk_score: u8,
     pub last_update: i64,
+    pub active_loans: u32,
+    /// Any loan request, open loan or top-up pledge blocks transfer and deactivation.
+    pub encumbered: bool,
+    /// Country code whose rules apply to loans and transfers, if placed in one.
+    pub jurisdiction: Option<String>,
+    /// Portfolio asset this one is bundled into; bundled assets can't be borrowed against alone.
+    pub portfolio: Option<String>,
+}
+
+impl From<AssetAccount> for AssetResponse {
+    fn from(asset: AssetAccount) -> Self {
+        Self {
+            asset_id: asset.asset_id,
+            asset_type: asset.asset_type,
+            valuation: asset.valuation,
+            metadata_uri: asset.metadata_uri,
+            owner: asset.owner.to_string(),
+            is_active: asset.is_active,
+            risk_score: asset.risk_score,
+            last_update: asset.last_risk_update,
+            active_loans: asset.active_loans,
+            encumbered: asset.active_loans > 0,
+            jurisdiction: (asset.jurisdiction != [0; 2])
+                .then(|| String::from_utf8_lossy(&asset.jurisdiction).into_owned()),
+            portfolio: (asset.portfolio != Pubkey::default())
+                .then(|| asset.portfolio.to_string()),
+        }
+    }
+}
+
+/// Criteria for `SolanaService::list_assets`; unset fields match every asset.
+#[derive(Debug, Clone, Default)]
+pub struct AssetFilter {
+    pub owner: Option<Pubkey>,
+    pub active: Option<bool>,
+    pub min_risk: Option<u8>,
+    pub max_risk: Option<u8>,
+}
+
+impl AssetFilter {
+    pub fn matches(&self, asset: &AssetAccount) -> bool {
+        self.owner.is_none_or(|owner| asset.owner == owner)
+            && self.active.is_none_or(|active| asset.is_active == active)
+            && self.min_risk.is_none_or(|min| asset.risk_score >= min)
+            && self.max_risk.is_none_or(|max| asset.risk_score <= max)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,14 +185,144 @@ pub struct LoanResponse {
     pub interest_rate: u64,
     pub start_time: i64,
     pub end_time: i64,
-    pub is_active: bool,
-    pub liquidated: bool,
-    pub repaid: bool,
+    pub status: LoanStatus,
     pub risk_score_at_creation: u8,
+    pub forbearance_end: i64,
+    pub modification_count: u16,
+    pub lender: String,
+    pub request_expiry: i64,
+    pub repayment_mint: String,
+    pub supplemental_asset: Option<String>,
+    pub written_off_amount: u64,
+    pub interest_mode: InterestMode,
+    pub disputed: bool,
+    pub dispute_outcome: Option<DisputeOutcome>,
+    pub late_fees: u64,
+    pub liquidation_eligible: bool,
+    pub liquidator: Option<String>,
+    pub liquidation_debt: u64,
+    pub referral: Option<String>,
+    pub rollover_count: u16,
+    pub product: LoanProduct,
+    pub floating_rate: Option<FloatingRateTerms>,
+    pub last_repriced_at: i64,
+}
+
+impl From<LoanAccount> for LoanResponse {
+    fn from(loan: LoanAccount) -> Self {
+        Self {
+            borrower: loan.borrower.to_string(),
+            asset: loan.asset.to_string(),
+            principal: loan.principal,
+            interest_rate: loan.interest_rate,
+            start_time: loan.start_time,
+            end_time: loan.end_time,
+            status: loan.status,
+            risk_score_at_creation: loan.risk_score_at_creation,
+            forbearance_end: loan.forbearance_end,
+            modification_count: loan.modification_count,
+            lender: loan.lender.to_string(),
+            request_expiry: loan.request_expiry,
+            repayment_mint: loan.repayment_mint.to_string(),
+            supplemental_asset: (loan.supplemental_asset != Pubkey::default())
+                .then(|| loan.supplemental_asset.to_string()),
+            written_off_amount: loan.written_off_amount,
+            interest_mode: loan.interest_mode,
+            disputed: loan.disputed,
+            dispute_outcome: loan.dispute_outcome,
+            late_fees: loan.late_fees,
+            liquidation_eligible: loan.liquidation_eligible,
+            liquidator: (loan.liquidator != Pubkey::default())
+                .then(|| loan.liquidator.to_string()),
+            liquidation_debt: loan.liquidation_debt,
+            referral: (loan.referral != Pubkey::default())
+                .then(|| loan.referral.to_string()),
+            rollover_count: loan.rollover_count,
+            product: loan.product,
+            floating_rate: loan.floating_rate.map(FloatingRateTerms::from),
+            last_repriced_at: loan.last_repriced_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleResponse {
+    pub loan: String,
+    pub interval: i64,
+    pub installments: Vec<InstallmentAccount>,
+    pub paid_count: u8,
+    pub missed_count: u8,
+    pub late_payments: u8,
+    pub delinquent: bool,
+}
+
+/// What repaying a loan in full costs at `quoted_at`, in its repayment mint.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PayoffQuote {
+    pub loan: String,
+    pub repayment_mint: String,
+    pub quoted_at: i64,
+    /// Principal, interest and late fee owed at `quoted_at`.
+    pub amount: u64,
+    pub principal: u64,
+    /// Interest banked at earlier rates plus interest since the last repricing.
+    pub interest: u64,
+    pub late_fee: u64,
+    /// Part of `amount` routed to the protocol rather than the lender.
+    pub protocol_share: u64,
+    /// Past this the quote is stale; interest or late fees may have grown.
+    pub expires_at: i64,
+    /// What `repay` takes if it lands exactly at `expires_at`.
+    pub amount_at_expiry: u64,
+    /// Floating loans can be repriced by anyone before `expires_at`, which
+    /// changes the interest owed from then on.
+    pub floating: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevenueStats {
+    /// Share of interest paid to the protocol, in basis points.
+    pub reserve_factor_bps: u16,
+    pub mints: Vec<MintRevenue>,
+}
+
+/// Protocol-wide counters from the on-chain `stats` PDA.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
+pub struct ProtocolStats {
+    pub total_assets: u64,
+    pub active_loans: u64,
+    pub total_principal_outstanding: u64,
+    pub cumulative_repaid: u64,
+    pub liquidation_count: u64,
+    #[serde(skip)]
+    pub bump: u8,
+    pub cumulative_written_off: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MintRevenue {
+    pub mint: String,
+    pub vault: String,
+    pub accrued: u64,
+    pub claimed: u64,
+    pub unclaimed: u64,
 }
 
-// ==================== Manual Account Data Structures ====================
+/// An oracle's bond, registered by the oracle itself with `register_oracle`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OracleBond {
+    pub oracle: String,
+    /// Lamports held above rent.
+    pub bonded: u64,
+    pub open_disputes: u16,
+    pub last_report_at: i64,
+    /// Lifetime lamports slashed.
+    pub slashed: u64,
+}
+
+// ==================== Account Data Structures ====================
+// Field order mirrors the program's account structs; Borsh has no field tags.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
 pub struct AssetAccount {
     pub asset_id: String,
     pub asset_type: String,
@@ -60,11 +331,135 @@ pub struct AssetAccount {
     pub owner: Pubkey,
     pub is_active: bool,
     pub risk_score: u8,
-    pub last_update: i64,
     pub bump: u8,
+    pub active_loans: u32,
+    pub delegate: Pubkey,
+    pub valuation_refresh_requested_at: i64,
+    pub last_risk_report: u64,
+    pub risk_frozen_until: i64,
+    pub breaker_confirmers: Vec<Pubkey>,
+    pub last_risk_update: i64,
+    pub jurisdiction: [u8; 2],
+    pub portfolio: Pubkey,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Arguments of the `request_loan` instruction.
+#[derive(Debug, Clone, Copy)]
+pub struct LoanTerms {
+    pub loan_amount: u64,
+    pub interest_rate: u64, // basis points
+    pub duration: i64,      // seconds
+    pub interest_mode: InterestMode,
+    pub product: LoanProduct,
+    pub floating_rate: Option<FloatingRate>,
+}
+
+/// Mirrors the program's `FloatingRate`: benchmark + spread held between a
+/// floor and a cap, all in basis points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
+pub struct FloatingRate {
+    pub benchmark: Pubkey,
+    pub spread_bps: u64,
+    pub floor_bps: u64,
+    pub cap_bps: u64,
+}
+
+/// `FloatingRate` as it appears in API requests and responses.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FloatingRateTerms {
+    pub benchmark: String,
+    pub spread_bps: u64,
+    pub floor_bps: u64,
+    pub cap_bps: u64,
+}
+
+impl FloatingRateTerms {
+    pub fn parse(&self) -> Result<FloatingRate> {
+        Ok(FloatingRate {
+            benchmark: Pubkey::from_str(&self.benchmark)
+                .map_err(|e| anyhow!("Invalid benchmark feed: {}", e))?,
+            spread_bps: self.spread_bps,
+            floor_bps: self.floor_bps,
+            cap_bps: self.cap_bps,
+        })
+    }
+}
+
+impl From<FloatingRate> for FloatingRateTerms {
+    fn from(rate: FloatingRate) -> Self {
+        Self {
+            benchmark: rate.benchmark.to_string(),
+            spread_bps: rate.spread_bps,
+            floor_bps: rate.floor_bps,
+            cap_bps: rate.cap_bps,
+        }
+    }
+}
+
+/// Mirrors the program's `InterestMode`; the variant index is its Borsh tag.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, BorshSerialize, BorshDeserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum InterestMode {
+    #[default]
+    Simple,
+    DailyCompounding,
+    Continuous,
+}
+
+/// Mirrors the program's `LoanProduct`; the variant index is its Borsh tag.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, BorshSerialize, BorshDeserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LoanProduct {
+    #[default]
+    Bullet,
+    InterestOnly,
+    Amortizing,
+}
+
+/// Mirrors the program's `LoanStatus`; the variant index is its Borsh tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, BorshSerialize, BorshDeserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LoanStatus {
+    Pending,
+    Active,
+    Defaulted,
+    Liquidating,
+    Repaid,
+    Liquidated,
+    WrittenOff,
+    Overdue,
+}
+
+impl LoanStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Active => "active",
+            Self::Defaulted => "defaulted",
+            Self::Liquidating => "liquidating",
+            Self::Repaid => "repaid",
+            Self::Liquidated => "liquidated",
+            Self::WrittenOff => "written_off",
+            Self::Overdue => "overdue",
+        }
+    }
+
+    /// Disbursed and not yet settled; mirrors the program's `is_open`.
+    pub fn is_open(self) -> bool {
+        matches!(self, Self::Active | Self::Overdue | Self::Defaulted | Self::Liquidating)
+    }
+}
+
+/// Mirrors the program's `DisputeOutcome`; the variant index is its Borsh tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DisputeOutcome {
+    FavorBorrower,
+    FavorLender,
+    Settled,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
 pub struct LoanAccount {
     pub borrower: Pubkey,
     pub asset: Pubkey,
@@ -72,376 +467,3137 @@ pub struct LoanAccount {
     pub interest_rate: u64,
     pub start_time: i64,
     pub end_time: i64,
-    pub is_active: bool,
-    pub repaid: bool,
-    pub liquidated: bool,
+    pub status: LoanStatus,
     pub risk_score_at_creation: u8,
     pub bump: u8,
+    pub forborne_seconds: i64,
+    pub forbearance_start: i64,
+    pub forbearance_end: i64,
+    pub modification_count: u16,
+    pub lender: Pubkey,
+    pub request_expiry: i64,
+    pub repayment_mint: Pubkey,
+    pub supplemental_asset: Pubkey,
+    pub written_off_amount: u64,
+    pub interest_mode: InterestMode,
+    pub disputed: bool,
+    pub dispute_raised_by: Pubkey,
+    pub dispute_raised_at: i64,
+    pub dispute_outcome: Option<DisputeOutcome>,
+    pub dispute_resolved_at: i64,
+    pub late_fees: u64,
+    pub liquidation_eligible: bool,
+    pub liquidator: Pubkey,
+    pub liquidation_debt: u64,
+    pub referral: Pubkey,
+    pub rollover_count: u16,
+    pub product: LoanProduct,
+    pub floating_rate: Option<FloatingRate>,
+    pub interest_checkpoint: u64,
+    pub checkpoint_seconds: i64,
+    pub last_repriced_at: i64,
+    pub collateral_rules: CollateralRules,
+    pub supplemental_rules: CollateralRules,
+    pub principal_repaid: u64,
 }
 
-// ==================== Borsh-like Serialization/Deserialization ====================
-impl AssetAccount {
-    pub fn from_bytes(data: &[u8]) -> Result<Self> {
-        let mut cursor = 8; // Skip discriminator
-        
-        let asset_id_len = u32::from_le_bytes(data[cursor..cursor+4].try_into()?) as usize;
-        cursor += 4;
-        let asset_id = String::from_utf8(data[cursor..cursor+asset_id_len].to_vec())?;
-        cursor += asset_id_len;
-        
-        let asset_type_len = u32::from_le_bytes(data[cursor..cursor+4].try_into()?) as usize;
-        cursor += 4;
-        let asset_type = String::from_utf8(data[cursor..cursor+asset_type_len].to_vec())?;
-        cursor += asset_type_len;
-        
-        let valuation = u64::from_le_bytes(data[cursor..cursor+8].try_into()?);
-        cursor += 8;
-        
-        let metadata_uri_len = u32::from_le_bytes(data[cursor..cursor+4].try_into()?) as usize;
-        cursor += 4;
-        let metadata_uri = String::from_utf8(data[cursor..cursor+metadata_uri_len].to_vec())?;
-        cursor += metadata_uri_len;
-        
-        let owner = Pubkey::new_from_array(data[cursor..cursor+32].try_into()?);
-        cursor += 32;
-        
-        let is_active = data[cursor] != 0;
-        cursor += 1;
-        
-        let risk_score = data[cursor];
-        cursor += 1;
-        
-        let last_update = i64::from_le_bytes(data[cursor..cursor+8].try_into()?);
-        cursor += 8;
-        
-        let bump = data[cursor];
-        
-        Ok(AssetAccount {
-            asset_id,
-            asset_type,
-            valuation,
-            metadata_uri,
-            owner,
-            is_active,
-            risk_score,
-            last_update,
-            bump,
-        })
+impl LoanAccount {
+    /// Principal not yet paid down by installments.
+    pub fn remaining_principal(&self) -> u64 {
+        self.principal.saturating_sub(self.principal_repaid)
     }
 }
 
-impl LoanAccount {
-    pub fn from_bytes(data: &[u8]) -> Result<Self> {
-        let mut cursor = 8; // Skip discriminator
-        
-        let borrower = Pubkey::new_from_array(data[cursor..cursor+32].try_into()?);
-        cursor += 32;
-        
-        let asset = Pubkey::new_from_array(data[cursor..cursor+32].try_into()?);
-        cursor += 32;
-        
-        let principal = u64::from_le_bytes(data[cursor..cursor+8].try_into()?);
-        cursor += 8;
-        
-        let interest_rate = u64::from_le_bytes(data[cursor..cursor+8].try_into()?);
-        cursor += 8;
-        
-        let start_time = i64::from_le_bytes(data[cursor..cursor+8].try_into()?);
-        cursor += 8;
-        
-        let end_time = i64::from_le_bytes(data[cursor..cursor+8].try_into()?);
-        cursor += 8;
-        
-        let is_active = data[cursor] != 0;
-        cursor += 1;
-        
-        let repaid = data[cursor] != 0;
-        cursor += 1;
-        
-        let liquidated = data[cursor] != 0;
-        cursor += 1;
-        
-        let risk_score_at_creation = data[cursor];
-        cursor += 1;
-        
-        let bump = data[cursor];
-        
-        Ok(LoanAccount {
-            borrower,
-            asset,
-            principal,
-            interest_rate,
-            start_time,
-            end_time,
-            is_active,
-            repaid,
-            liquidated,
-            risk_score_at_creation,
-            bump,
-        })
-    }
+/// Mirrors the program's `CollateralRules`: the asset type and jurisdiction
+/// limits a loan's collateral was accepted under.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
+pub struct CollateralRules {
+    pub max_ltv: u8,
+    pub haircut_bps: u16,
+    /// 0 when the jurisdiction doesn't cap LTV.
+    pub jurisdiction_max_ltv: u8,
 }
 
-// ==================== Solana Service ====================
-pub struct SolanaService {
-    client: Arc<RpcClient>,
-    program_id: Pubkey,
-    payer: Keypair,
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
+pub struct InstallmentAccount {
+    pub due_date: i64,
+    pub amount: u64,
+    pub paid_at: i64,
 }
 
-pub struct InitializeAssetResult {
-    pub asset_pda: String,
-    pub transaction: String,
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
+pub struct ScheduleAccount {
+    pub loan: Pubkey,
+    pub interval: i64,
+    pub installments: Vec<InstallmentAccount>,
+    pub paid_count: u8,
+    pub missed_count: u8,
+    pub late_payments: u8,
+    pub delinquent: bool,
+    pub bump: u8,
 }
 
-pub struct CreateLoanResult {
-    pub loan_pda: String,
-    pub transaction: String,
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
+pub struct ProtocolRevenueAccount {
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub accrued: u64,
+    pub claimed: u64,
+    pub bump: u8,
 }
 
-impl SolanaService {
-    pub async fn new() -> Result<Self> {
-        let rpc_url = env::var("SOLANA_RPC_URL")
-            .unwrap_or_else(|_| "https://api.devnet.solana.com".to_string());
-        
-        let program_id = Pubkey::from_str(PROGRAM_ID)
-            .map_err(|e| anyhow!("Invalid program ID: {}", e))?;
-        
-        let payer = if let Ok(private_key) = env::var("WALLET_PRIVATE_KEY") {
-            let bytes: Vec<u8> = serde_json::from_str(&private_key)
-                .map_err(|e| anyhow!("Invalid private key format: {}", e))?;
-            Keypair::from_bytes(&bytes)
-                .map_err(|e| anyhow!("Failed to create keypair: {}", e))?
-        } else {
-            let home = dirs::home_dir().ok_or_else(|| anyhow!("Could not find home dir"))?;
-            let keypath = home.join(".config/solana/id.json");
-            read_keypair_file(&keypath)
-                .map_err(|e| anyhow!("Failed to read keypair: {}", e))?
-        };
-        
-        let client = Arc::new(RpcClient::new_with_commitment(
-            rpc_url,
-            CommitmentConfig::confirmed(),
-        ));
-        
-        let _ = client.get_latest_blockhash()
-            .map_err(|e| anyhow!("Failed to connect to Solana: {}", e))?;
-        
-        Ok(Self {
-            client,
-            program_id,
-            payer,
-        })
-    }
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct OracleBondAccount {
+    pub oracle: Pubkey,
+    pub bonded: u64,
+    pub open_disputes: u16,
+    pub last_report_at: i64,
+    pub slashed: u64,
+    pub bump: u8,
+}
 
-    pub async fn initialize_asset(
-        &self,
-        asset_id: &str,
-        asset_type: &str,
-        valuation: u64,
-        metadata_uri: &str,
-        owner: Pubkey,
-    ) -> Result<InitializeAssetResult> {
-        let (asset_pda, bump) = Pubkey::find_program_address(
-            &[b"asset", asset_id.as_bytes()],
-            &self.program_id,
-        );
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct BlacklistAccount {
+    pub entries: Vec<Pubkey>,
+    pub bump: u8,
+}
 
-        tracing::info!("Asset PDA: {} with bump: {}", asset_pda, bump);
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct CustodyAttestationAccount {
+    pub asset: Pubkey,
+    pub custodian: Pubkey,
+    pub document_hash: [u8; 32],
+    pub attested_at: i64,
+    pub expires_at: i64,
+    pub bump: u8,
+}
 
-        let mut instruction_data = DISCRIMINATOR_INITIALIZE_ASSET.to_vec();
-        
-        // Serialize parameters (simplified string encoding)
-        let asset_id_bytes = asset_id.as_bytes();
-        instruction_data.extend_from_slice(&(asset_id_bytes.len() as u32).to_le_bytes());
-        instruction_data.extend_from_slice(asset_id_bytes);
-        
-        let asset_type_bytes = asset_type.as_bytes();
-        instruction_data.extend_from_slice(&(asset_type_bytes.len() as u32).to_le_bytes());
-        instruction_data.extend_from_slice(asset_type_bytes);
-        
-        instruction_data.extend_from_slice(&valuation.to_le_bytes());
-        
-        let metadata_bytes = metadata_uri.as_bytes();
-        instruction_data.extend_from_slice(&(metadata_bytes.len() as u32).to_le_bytes());
-        instruction_data.extend_from_slice(metadata_bytes);
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct ReferralAccount {
+    pub agent: Pubkey,
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub accrued: u64,
+    pub claimed: u64,
+    pub referred_loans: u64,
+    pub referred_volume: u64,
+    pub bump: u8,
+}
 
-        let accounts = vec![
-            solana_sdk::instruction::AccountMeta::new(asset_pda, false),
-            solana_sdk::instruction::AccountMeta::new(owner, true),
-            solana_sdk::instruction::AccountMeta::new_readonly(system_program::id(), false),
-        ];
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct AssetTypeConfigAccount {
+    pub asset_type: String,
+    pub max_ltv: u8,
+    pub haircut_bps: u16,
+    pub max_duration: i64,
+    pub bump: u8,
+}
 
-        let instruction = Instruction {
-            program_id: self.program_id,
-            accounts,
-            data: instruction_data,
-        };
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct JurisdictionConfigAccount {
+    pub jurisdiction: [u8; 2],
+    pub allowed_asset_types: Vec<String>,
+    pub max_ltv: u8,
+    pub transfer_restriction: u8, // TransferRestriction tag
+    pub bump: u8,
+}
 
-        let recent_blockhash = self.client.get_latest_blockhash()
-            .map_err(|e| anyhow!("Failed to get blockhash: {}", e))?;
-            
-        let transaction = Transaction::new_signed_with_payer(
-            &[instruction],
-            Some(&owner),
-            &[&self.payer],
-            recent_blockhash,
-        );
+/// Outcome of running instructions through `simulateTransaction`.
+#[derive(Debug, Clone, Serialize)]
+pub struct SimulationOutcome {
+    pub accepted: bool,
+    pub units_consumed: Option<u64>,
+    pub error: Option<String>,
+    /// The program's own error, when `error` is one of its `ErrorCode`s.
+    pub program_error: Option<ProgramError>,
+    pub logs: Vec<String>,
+}
 
-        let signature = self.client.send_and_confirm_transaction(&transaction)
-            .map_err(|e| anyhow!("Transaction failed: {}", e))?;
+/// What the program would make of a loan request, worked out before signing.
+#[derive(Debug, Clone, Serialize)]
+pub struct LoanPreview {
+    pub loan_pda: String,
+    /// Valuation left after the asset type's haircut.
+    pub collateral_value: u64,
+    /// Tightest of the risk-score, asset type and jurisdiction limits, percent.
+    pub max_ltv_percent: u64,
+    pub max_loan: u64,
+    pub simulation: SimulationOutcome,
+}
 
-        Ok(InitializeAssetResult {
-            asset_pda: asset_pda.to_string(),
-            transaction: signature.to_string(),
-        })
+// ==================== Lending Limits ====================
+// Mirrors the program's `max_ltv_for_risk`, `max_loan_in_jurisdiction` and
+// `CollateralRules::max_loan`.
+fn max_ltv_for_risk(risk_score: u8) -> u64 {
+    match risk_score {
+        0..=20 => 70,
+        21..=40 => 60,
+        41..=60 => 50,
+        61..=80 => 35,
+        81..=100 => 20,
+        _ => 0,
     }
+}
 
-    pub async fn update_risk_score(
-        &self,
-        asset_id: &str,
-        risk_score: u8,
-    ) -> Result<String> {
-        let (asset_pda, _) = Pubkey::find_program_address(
-            &[b"asset", asset_id.as_bytes()],
-            &self.program_id,
-        );
+/// Collateral value, effective LTV and resulting loan cap for an asset.
+fn loan_limits(
+    valuation: u64,
+    risk_score: u8,
+    type_config: &AssetTypeConfigAccount,
+    rules: Option<&JurisdictionConfigAccount>,
+) -> (u64, u64, u64) {
+    let collateral_value = valuation as u128 - valuation as u128 * type_config.haircut_bps as u128 / 10_000;
+    let mut max_ltv = max_ltv_for_risk(risk_score).min(type_config.max_ltv as u64);
+    if let Some(rules) = rules.filter(|rules| rules.max_ltv > 0) {
+        max_ltv = max_ltv.min(rules.max_ltv as u64);
+    }
+    (collateral_value as u64, max_ltv, (collateral_value * max_ltv as u128 / 100) as u64)
+}
 
-        let mut instruction_data = DISCRIMINATOR_UPDATE_RISK.to_vec();
-        instruction_data.push(risk_score);
+impl CollateralRules {
+    fn max_loan(&self, valuation: u64, risk_score: u8) -> u64 {
+        let collateral_value = valuation as u128 - valuation as u128 * self.haircut_bps as u128 / 10_000;
+        let mut max_ltv = max_ltv_for_risk(risk_score).min(self.max_ltv as u64);
+        if self.jurisdiction_max_ltv > 0 {
+            max_ltv = max_ltv.min(self.jurisdiction_max_ltv as u64);
+        }
+        (collateral_value * max_ltv as u128 / 100) as u64
+    }
+}
 
-        let accounts = vec![
-            solana_sdk::instruction::AccountMeta::new(asset_pda, false),
-            solana_sdk::instruction::AccountMeta::new_readonly(self.payer.pubkey(), true),
-        ];
+// ==================== Payoff ====================
+// Mirrors the program's `outstanding_debt`, `late_fee` and `reserve_share`,
+// down to the rounding; a quote that differs from `repay_loan` by one unit
+// leaves the borrower short.
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+const SECONDS_PER_YEAR: i64 = 365 * SECONDS_PER_DAY;
+const BPS_YEAR_SECONDS: u128 = 10_000 * SECONDS_PER_YEAR as u128;
+const WAD: u128 = 1_000_000_000_000_000_000;
+const LATE_FEE_BPS_PER_DAY: u128 = 10;
+const MAX_LATE_FEE_BPS: u128 = 1_000;
 
-        let instruction = Instruction {
-            program_id: self.program_id,
-            accounts,
-            data: instruction_data,
-        };
+/// How long a payoff quote is good for.
+pub const PAYOFF_QUOTE_TTL_SECS: i64 = 300;
 
-        let recent_blockhash = self.client.get_latest_blockhash()
-            .map_err(|e| anyhow!("Failed to get blockhash: {}", e))?;
-            
-        let transaction = Transaction::new_signed_with_payer(
-            &[instruction],
-            Some(&self.payer.pubkey()),
-            &[&self.payer],
-            recent_blockhash,
-        );
+fn mul_wad(a: u128, b: u128) -> Option<u128> {
+    a.checked_mul(b).map(|v| v / WAD)
+}
 
-        let signature = self.client.send_and_confirm_transaction(&transaction)
-            .map_err(|e| anyhow!("Update failed: {}", e))?;
+fn pow_wad(mut base: u128, mut exp: u64) -> Option<u128> {
+    let mut result = WAD;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mul_wad(result, base)?;
+        }
+        exp >>= 1;
+        if exp > 0 {
+            base = mul_wad(base, base)?;
+        }
+    }
+    Some(result)
+}
 
-        Ok(signature.to_string())
+fn exp_wad(x: u128) -> Option<u128> {
+    let mut sum = WAD;
+    let mut term = WAD;
+    for k in 1..=64u128 {
+        term = mul_wad(term, x)? / k;
+        if term == 0 {
+            return Some(sum);
+        }
+        sum = sum.checked_add(term)?;
     }
+    None
+}
 
-    pub async fn get_asset(&self, asset_id: &str) -> Result<AssetResponse> {
-        let (asset_pda, _) = Pubkey::find_program_address(
-            &[b"asset", asset_id.as_bytes()],
-            &self.program_id,
-        );
+fn accrued_interest(principal: u64, interest_rate: u64, elapsed: i64, mode: InterestMode) -> Option<u64> {
+    if elapsed <= 0 {
+        return Some(0);
+    }
+    let interest = match mode {
+        InterestMode::Simple => simple_interest(principal, interest_rate, elapsed),
+        InterestMode::DailyCompounding => growth_interest(principal, daily_compound_factor(interest_rate, elapsed)),
+        InterestMode::Continuous => growth_interest(principal, continuous_factor(interest_rate, elapsed)),
+    }?;
+    u64::try_from(interest).ok()
+}
 
-        tracing::info!("Fetching asset from PDA: {}", asset_pda);
+fn simple_interest(principal: u64, interest_rate: u64, elapsed: i64) -> Option<u128> {
+    (principal as u128)
+        .checked_mul(interest_rate as u128)?
+        .checked_mul(elapsed as u128)
+        .map(|v| v / BPS_YEAR_SECONDS)
+}
 
-        let account = self.client.get_account(&asset_pda)
-            .map_err(|e| anyhow!("Asset not found: {}", e))?;
-        
-        let asset_account = AssetAccount::from_bytes(&account.data)?;
-        
-        Ok(AssetResponse {
-            asset_id: asset_account.asset_id,
-            asset_type: asset_account.asset_type,
-            valuation: asset_account.valuation,
-            metadata_uri: asset_account.metadata_uri,
-            owner: asset_account.owner.to_string(),
-            is_active: asset_account.is_active,
-            risk_score: asset_account.risk_score,
-            last_update: asset_account.last_update,
-        })
+fn growth_interest(principal: u64, factor: Option<u128>) -> Option<u128> {
+    let growth = factor?.checked_sub(WAD)?;
+    (principal as u128).checked_mul(growth).map(|v| v / WAD)
+}
+
+fn daily_compound_factor(interest_rate: u64, elapsed: i64) -> Option<u128> {
+    let daily_rate = (interest_rate as u128).checked_mul(WAD)? / (10_000 * 365);
+    let days = (elapsed / SECONDS_PER_DAY) as u64;
+    let remainder = (elapsed % SECONDS_PER_DAY) as u128;
+    let partial = WAD + daily_rate.checked_mul(remainder)? / SECONDS_PER_DAY as u128;
+    mul_wad(pow_wad(WAD + daily_rate, days)?, partial)
+}
+
+fn continuous_factor(interest_rate: u64, elapsed: i64) -> Option<u128> {
+    let exponent = (interest_rate as u128)
+        .checked_mul(elapsed as u128)?
+        .checked_mul(WAD)?
+        / BPS_YEAR_SECONDS;
+    exp_wad(exponent)
+}
+
+fn interest_bearing_seconds(loan: &LoanAccount, now: i64) -> i64 {
+    let elapsed = now - loan.start_time;
+    let current_window = (now.min(loan.forbearance_end) - loan.forbearance_start).max(0);
+    (elapsed - loan.forborne_seconds - current_window).max(0)
+}
+
+fn loan_interest(loan: &LoanAccount, now: i64) -> Option<u64> {
+    let elapsed = interest_bearing_seconds(loan, now) - loan.checkpoint_seconds;
+    accrued_interest(loan.remaining_principal(), loan.interest_rate, elapsed, loan.interest_mode)?
+        .checked_add(loan.interest_checkpoint)
+}
+
+fn late_fee(loan: &LoanAccount, now: i64) -> u64 {
+    if !loan.status.is_open() || now <= loan.end_time {
+        return 0;
     }
+    let days_late = ((now - loan.end_time) / SECONDS_PER_DAY) as u128;
+    let bps = (days_late * LATE_FEE_BPS_PER_DAY).min(MAX_LATE_FEE_BPS);
+    (loan.remaining_principal() as u128 * bps / 10_000) as u64
+}
 
-    pub async fn create_loan(
-        &self,
-        asset_id: &str,
-        borrower: Pubkey,
-        loan_amount: u64,
-        interest_rate: u64,
-        duration: i64,
-    ) -> Result<CreateLoanResult> {
-        let (asset_pda, _) = Pubkey::find_program_address(
-            &[b"asset", asset_id.as_bytes()],
-            &self.program_id,
-        );
+/// Remaining principal, interest and late fee owed at `now`; `None` where the
+/// program would fail with `MathOverflow`.
+fn payoff_breakdown(loan: &LoanAccount, now: i64) -> Option<(u64, u64, u64)> {
+    let interest = loan_interest(loan, now)?;
+    let fee = late_fee(loan, now);
+    let principal = loan.remaining_principal();
+    principal.checked_add(interest)?.checked_add(fee)?;
+    Some((principal, interest, fee))
+}
 
-        let (loan_pda, _) = Pubkey::find_program_address(
-            &[b"loan", asset_pda.as_ref(), borrower.as_ref()],
-            &self.program_id,
-        );
+/// What `repay_loan` would take at `now`.
+pub fn outstanding_debt(loan: &LoanAccount, now: i64) -> Option<u64> {
+    payoff_breakdown(loan, now).map(|(principal, interest, late_fee)| principal + interest + late_fee)
+}
 
-        tracing::info!("Loan PDA: {}", loan_pda);
+fn reserve_share(interest: u64, reserve_factor_bps: u16) -> u64 {
+    (interest as u128 * reserve_factor_bps as u128 / 10_000) as u64
+}
 
-        let mut instruction_data = DISCRIMINATOR_CREATE_LOAN.to_vec();
-        instruction_data.extend_from_slice(&loan_amount.to_le_bytes());
-        instruction_data.extend_from_slice(&interest_rate.to_le_bytes());
-        instruction_data.extend_from_slice(&duration.to_le_bytes());
+// ==================== Liquidation ====================
+// Mirrors the program's `liquidation_trigger` and the checks `liquidate_loan`
+// makes before it.
+const LIQUIDATION_RISK_THRESHOLD: u8 = 80;
+const HEALTH_FACTOR_ONE: u64 = 10_000;
+const LIQUIDATION_THRESHOLD_BPS: u64 = 11_000;
 
-        let accounts = vec![
-            solana_sdk::instruction::AccountMeta::new(loan_pda, false),
-            solana_sdk::instruction::AccountMeta::new(asset_pda, false),
-            solana_sdk::instruction::AccountMeta::new(borrower, true),
-            solana_sdk::instruction::AccountMeta::new_readonly(system_program::id(), false),
-        ];
+/// Why the program would let a loan be liquidated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "trigger", content = "value")]
+pub enum LiquidationTrigger {
+    /// The collateral's risk score is above the liquidation threshold.
+    HighRisk(u8),
+    /// Health factor in basis points, below 10,000.
+    LtvBreach(u64),
+}
 
-        let instruction = Instruction {
-            program_id: self.program_id,
-            accounts,
-            data: instruction_data,
-        };
+fn health_factor_bps(limit: u64, debt: u64) -> u64 {
+    if debt == 0 {
+        return u64::MAX;
+    }
+    u64::try_from(limit as u128 * HEALTH_FACTOR_ONE as u128 / debt as u128).unwrap_or(u64::MAX)
+}
 
-        let recent_blockhash = self.client.get_latest_blockhash()
-            .map_err(|e| anyhow!("Failed to get blockhash: {}", e))?;
-            
-        let transaction = Transaction::new_signed_with_payer(
-            &[instruction],
-            Some(&borrower),
-            &[&self.payer],
-            recent_blockhash,
-        );
+/// The trigger `liquidate_loan` would accept at `now`, or None where it
+/// would fail: closed, disputed, risk-frozen or healthy loans.
+pub fn liquidation_trigger(
+    loan: &LoanAccount,
+    asset: &AssetAccount,
+    supplemental: Option<&AssetAccount>,
+    now: i64,
+) -> Option<LiquidationTrigger> {
+    if !loan.status.is_open() || loan.disputed || now < asset.risk_frozen_until {
+        return None;
+    }
+    if asset.risk_score > LIQUIDATION_RISK_THRESHOLD {
+        return Some(LiquidationTrigger::HighRisk(asset.risk_score));
+    }
+    let extra = supplemental.map_or(0, |s| loan.supplemental_rules.max_loan(s.valuation, s.risk_score));
+    let borrowing_limit = loan.collateral_rules.max_loan(asset.valuation, asset.risk_score).saturating_add(extra);
+    let limit = u64::try_from(borrowing_limit as u128 * LIQUIDATION_THRESHOLD_BPS as u128 / HEALTH_FACTOR_ONE as u128)
+        .unwrap_or(u64::MAX);
+    let health_factor = health_factor_bps(limit, outstanding_debt(loan, now)?);
+    (health_factor < HEALTH_FACTOR_ONE).then_some(LiquidationTrigger::LtvBreach(health_factor))
+}
 
-        let signature = self.client.send_and_confirm_transaction(&transaction)
-            .map_err(|e| anyhow!("Loan creation failed: {}", e))?;
+/// A request the program would reject, caught before anything was sent.
+#[derive(Debug, Clone)]
+pub struct Rejection {
+    /// The error the program would have failed with.
+    pub error: ProgramError,
+    /// Why, with the values involved.
+    pub reason: String,
+}
 
-        Ok(CreateLoanResult {
-            loan_pda: loan_pda.to_string(),
-            transaction: signature.to_string(),
-        })
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.reason)
     }
+}
 
-    pub async fn get_loan(&self, loan_pda: Pubkey) -> Result<LoanResponse> {
-        tracing::info!("Fetching loan from PDA: {}", loan_pda);
+impl std::error::Error for Rejection {}
 
-        let account = self.client.get_account(&loan_pda)
+// ==================== Borsh Deserialization ====================
+/// An Anchor account: an 8-byte discriminator followed by the Borsh-encoded
+/// struct. Accounts are allocated at their maximum size, so bytes left over
+/// after the struct are expected and ignored.
+pub trait ProgramAccount: BorshDeserialize {
+    const NAME: &'static str;
+
+    /// The discriminator the loaded IDL declares for this account.
+    fn discriminator() -> Result<[u8; 8]> {
+        idl::program().account_discriminator(Self::NAME)
+    }
+
+    fn from_bytes(data: &[u8]) -> Result<Self> {
+        let (discriminator, mut body) = data
+            .split_first_chunk::<8>()
+            .ok_or_else(|| anyhow!("{} account data is too short", Self::NAME))?;
+        if *discriminator != Self::discriminator()? {
+            return Err(anyhow!("Account is not a {} account", Self::NAME));
+        }
+        Self::deserialize(&mut body).map_err(|e| anyhow!("Malformed {} account: {}", Self::NAME, e))
+    }
+}
+
+macro_rules! program_accounts {
+    ($($type:ty => $name:literal),* $(,)?) => {
+        $(
+            impl ProgramAccount for $type {
+                const NAME: &'static str = $name;
+            }
+        )*
+
+        /// Accounts the service decodes; the loaded IDL must declare each.
+        const ACCOUNTS: &[&str] = &[$($name),*];
+    };
+}
+
+program_accounts! {
+    AssetAccount => "Asset",
+    LoanAccount => "Loan",
+    ScheduleAccount => "RepaymentSchedule",
+    ProtocolRevenueAccount => "ProtocolRevenue",
+    ProtocolStats => "ProtocolStats",
+    BlacklistAccount => "Blacklist",
+    OracleBondAccount => "OracleBond",
+    CustodyAttestationAccount => "CustodyAttestation",
+    ReferralAccount => "Referral",
+    AssetTypeConfigAccount => "AssetTypeConfig",
+    JurisdictionConfigAccount => "JurisdictionConfig",
+}
+
+/// Pubsub endpoint next to an RPC endpoint: same host over ws(s), and the
+/// validator's default pubsub port beside its default RPC port.
+fn websocket_url(rpc_url: &str) -> String {
+    let url = rpc_url.replacen("https://", "wss://", 1).replacen("http://", "ws://", 1);
+    url.replacen(":8899", ":8900", 1)
+}
+
+/// Keep the pubsub subscriptions open, reconnecting after a backoff whenever
+/// the connection drops.
+pub fn spawn_subscriptions(solana: Arc<SolanaService>) {
+    tokio::spawn(async move {
+        let mut backoff = SUBSCRIPTION_MIN_BACKOFF;
+        loop {
+            let started = tokio::time::Instant::now();
+            if let Err(e) = solana.stream_updates().await {
+                tracing::warn!("⚠️ Pubsub subscriptions dropped: {}", e);
+            }
+            // A connection that held for a while starts over from the shortest wait
+            if started.elapsed() > SUBSCRIPTION_MAX_BACKOFF {
+                backoff = SUBSCRIPTION_MIN_BACKOFF;
+            }
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(SUBSCRIPTION_MAX_BACKOFF);
+        }
+    });
+}
+
+/// `PAYER_MIN_BALANCE_LAMPORTS`, the balance a payer should not fall below.
+pub fn payer_min_balance() -> u64 {
+    env::var("PAYER_MIN_BALANCE_LAMPORTS")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(DEFAULT_PAYER_MIN_BALANCE)
+}
+
+/// Size of the asset account `initialize_asset` data would create, read from
+/// the lengths of the id and type strings that follow the discriminator.
+fn asset_account_space(data: &[u8]) -> Option<usize> {
+    let string_length = |offset: usize| -> Option<usize> {
+        Some(u32::from_le_bytes(data.get(offset..offset + 4)?.try_into().ok()?) as usize)
+    };
+    let id_length = string_length(8)?;
+    let type_length = string_length(12 + id_length)?;
+    Some(ASSET_ACCOUNT_BASE_SPACE + id_length + type_length)
+}
+
+/// Associated token account address for `wallet` and `mint` under `token_program`.
+fn associated_token_address(wallet: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Result<Pubkey> {
+    let ata_program = Pubkey::from_str(ASSOCIATED_TOKEN_PROGRAM_ID)?;
+    let (address, _) = Pubkey::find_program_address(
+        &[wallet.as_ref(), token_program.as_ref(), mint.as_ref()],
+        &ata_program,
+    );
+    Ok(address)
+}
+
+tokio::task_local! {
+    /// A tenant's payer, standing in for the service payer while set.
+    static TENANT_PAYER: PayerSigner;
+}
+
+/// Run `fut` with `payer` signing and paying for its transactions.
+pub async fn with_payer<F: std::future::Future>(payer: PayerSigner, fut: F) -> F::Output {
+    TENANT_PAYER.scope(payer, fut).await
+}
+
+// ==================== Solana Service ====================
+pub struct SolanaService {
+    client: Arc<RpcClient>,
+    program_id: Pubkey,
+    /// Source of instruction discriminators and argument layouts.
+    idl: &'static ProgramIdl,
+    /// Swapped by `rotate_payer`; read it through `payer()`.
+    payer: RwLock<PayerSigner>,
+    /// Held shared by every send, and exclusively while the payer is swapped
+    /// so that no transaction is in flight at the switch.
+    sends: RwLock<()>,
+    /// Asset and loan accounts fetched recently.
+    accounts: AccountCache,
+    fees: PriorityFees,
+    write_commitments: WriteCommitments,
+    /// Accounts the payer funds in sends still in flight, and their rent.
+    pending_creations: AtomicUsize,
+    pending_rent: AtomicU64,
+    /// Durable nonce accounts, authorized by the payer, that back prepared transactions.
+    nonce_accounts: RwLock<Vec<Pubkey>>,
+    next_nonce: AtomicUsize,
+    /// Address lookup tables, authorized by the payer, that v0 transactions draw on.
+    lookup_tables: RwLock<Vec<Pubkey>>,
+    ws_url: String,
+    updates: broadcast::Sender<ChainUpdate>,
+    /// Devnet or a local test validator, where SOL can be airdropped.
+    faucet_cluster: bool,
+    /// Test token minted by `airdrop`, with the payer as mint authority.
+    faucet_mint: Option<Pubkey>,
+    /// Block engine liquidations are bundled through, when configured.
+    jito: Option<JitoClient>,
+}
+
+/// A change pushed by the pubsub subscriptions.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ChainUpdate {
+    Asset { address: String, slot: u64, asset: AssetResponse },
+    Loan { address: String, slot: u64, loan: LoanResponse },
+    /// A transaction that mentioned the program, with the events it emitted.
+    Logs { signature: String, slot: u64, error: Option<String>, events: Vec<ProgramEvent>, logs: Vec<String> },
+}
+
+/// Outcome of a payer rotation.
+#[derive(Debug, Clone, Serialize)]
+pub struct PayerRotation {
+    pub previous_payer: String,
+    pub payer: String,
+    /// New payer's balance in lamports when it took over.
+    pub balance: u64,
+    pub nonce_accounts_reauthorized: usize,
+}
+
+/// The payer's balance and what account creations stand to take out of it.
+#[derive(Debug, Clone, Serialize)]
+pub struct PayerWallet {
+    pub payer: String,
+    pub balance: u64,
+    /// Sends in flight that create accounts with the payer's lamports.
+    pub pending_creations: usize,
+    /// Rent those accounts will lock up once they land.
+    pub pending_rent: u64,
+    /// Rent-exempt minimum of each kind of account the payer funds.
+    pub rent_exemption: Vec<RentCost>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RentCost {
+    pub account: &'static str,
+    pub space: usize,
+    pub lamports: u64,
+}
+
+/// What a user pays to send a transaction that creates accounts: its fee
+/// plus the rent each new account must hold.
+#[derive(Debug, Clone, Serialize)]
+pub struct CostEstimate {
+    pub fee_payer: String,
+    pub signatures: u8,
+    /// Base and priority fee at current prices.
+    pub transaction_fee: u64,
+    pub rent: Vec<RentCost>,
+    pub rent_total: u64,
+    pub total_lamports: u64,
+}
+
+pub struct AirdropResult {
+    pub sol_signature: Option<String>,
+    pub token_signature: Option<String>,
+}
+
+/// A confirmed transaction and what its logs say it did.
+#[derive(Debug, Clone, Serialize)]
+pub struct TransactionStatus {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub fee: Option<u64>,
+    pub error: Option<String>,
+    #[serde(flatten)]
+    pub logs: ParsedLogs,
+}
+
+/// An address lookup table and the addresses it holds.
+#[derive(Debug, Clone, Serialize)]
+pub struct LookupTableInfo {
+    pub address: String,
+    pub authority: Option<String>,
+    pub addresses: Vec<String>,
+    /// Whether oversized transactions compile against this table.
+    pub in_use: bool,
+}
+
+/// A durable nonce account and the blockhash it currently stores.
+#[derive(Debug, Clone, Serialize)]
+pub struct NonceAccountInfo {
+    pub address: String,
+    pub authority: String,
+    pub blockhash: String,
+    pub lamports_per_signature: u64,
+    /// Whether prepared transactions draw on this account.
+    pub in_pool: bool,
+}
+
+pub struct InitializeAssetResult {
+    pub asset_pda: String,
+    pub transaction: Submission,
+}
+
+/// One asset of a batch, as `initialize_asset` takes it.
+pub struct NewAsset {
+    pub asset_id: String,
+    pub asset_type: String,
+    pub valuation: u64,
+    pub metadata_uri: String,
+    pub owner: Pubkey,
+}
+
+/// One transaction of a batch and the assets it creates, by their index in
+/// the batch; they land or fail together.
+pub struct AssetBatchChunk {
+    pub assets: Vec<(usize, Pubkey)>,
+    pub transaction: Result<Submission>,
+}
+
+pub struct CreateLoanResult {
+    pub loan_pda: String,
+    pub transaction: Submission,
+}
+
+/// How a transaction built for a user left the service.
+pub enum Submission {
+    /// Signed by the service payer and confirmed; carries the signature.
+    Sent(String),
+    /// Base64 bincode transaction for the user's wallet to sign and submit,
+    /// already signed by the service payer where it is a signer.
+    Unsigned(String),
+}
+
+impl Submission {
+    /// Signature of a sent transaction and the encoding of an unsigned one.
+    pub fn into_parts(self) -> (Option<String>, Option<String>) {
+        match self {
+            Submission::Sent(signature) => (Some(signature), None),
+            Submission::Unsigned(transaction) => (None, Some(transaction)),
+        }
+    }
+}
+
+/// A composed transaction and the wallets that have yet to sign it.
+pub struct ComposedTransaction {
+    pub transaction: Submission,
+    /// Required signers other than the service payer, fee payer first.
+    pub signers: Vec<String>,
+}
+
+// ==================== Transaction Composer ====================
+/// Collects the instructions of several operations into one transaction, so
+/// that they land or fail together. Start one with [`SolanaService::compose`].
+pub struct TransactionComposer<'a> {
+    service: &'a SolanaService,
+    instructions: Vec<Instruction>,
+}
+
+impl TransactionComposer<'_> {
+    /// Returns the asset PDA.
+    pub fn initialize_asset(
+        &mut self,
+        asset_id: &str,
+        asset_type: &str,
+        valuation: u64,
+        metadata_uri: &str,
+        owner: Pubkey,
+    ) -> Result<Pubkey> {
+        let (asset_pda, instructions) = self.service.initialize_asset_instructions(asset_id, asset_type, valuation, metadata_uri, owner)?;
+        self.instructions.extend(instructions);
+        Ok(asset_pda)
+    }
+
+    pub fn update_risk_score(&mut self, asset_id: &str, risk_score: u8) -> Result<()> {
+        self.instructions.push(self.service.update_risk_score_instruction(asset_id, risk_score)?);
+        Ok(())
+    }
+
+    /// Returns the loan PDA. The asset must already exist on-chain, since its
+    /// type and custody attestation decide the accounts.
+    pub async fn request_loan(
+        &mut self,
+        asset_id: &str,
+        borrower: Pubkey,
+        terms: &LoanTerms,
+        repayment_mint: Pubkey,
+    ) -> Result<Pubkey> {
+        let (loan_pda, _, instructions) = self.service.request_loan_instructions(asset_id, borrower, terms, repayment_mint).await?;
+        self.service.check_lending(&self.service.asset_address(asset_id).0, terms.loan_amount, Some(terms.duration))?;
+        self.instructions.extend(instructions);
+        Ok(loan_pda)
+    }
+
+    pub fn approve_loan(&mut self, loan_pda: Pubkey) -> Result<()> {
+        self.instructions.push(self.service.approve_loan_instruction(loan_pda)?);
+        Ok(())
+    }
+
+    pub fn repay_loan(&mut self, loan_pda: Pubkey) -> Result<()> {
+        self.instructions.extend(self.service.repay_loan_instructions(loan_pda)?.1);
+        Ok(())
+    }
+
+    /// Every account any instruction needs a signature from, in first-use order.
+    pub fn signers(&self) -> Vec<Pubkey> {
+        let mut signers: Vec<Pubkey> = Vec::new();
+        for meta in self.instructions.iter().flat_map(|instruction| &instruction.accounts) {
+            if meta.is_signer && !signers.contains(&meta.pubkey) {
+                signers.push(meta.pubkey);
+            }
+        }
+        signers
+    }
+
+    /// Send when the service payer is the only signer. Otherwise the first
+    /// other signer pays the fee and the transaction comes back, signed by
+    /// the payer where needed, for the remaining wallets to sign.
+    pub fn submit(self) -> Result<ComposedTransaction> {
+        if self.instructions.is_empty() {
+            return Err(anyhow!("Nothing to compose"));
+        }
+        let payer = self.service.payer().pubkey();
+        let signers: Vec<Pubkey> = self.signers().into_iter().filter(|signer| *signer != payer).collect();
+        let fee_payer = signers.first().copied().unwrap_or(payer);
+
+        Ok(ComposedTransaction {
+            transaction: self.service.submit_for(&self.instructions, &fee_payer)?,
+            signers: signers.iter().map(Pubkey::to_string).collect(),
+        })
+    }
+}
+
+impl SolanaService {
+    /// Connect to `network`: its `SOLANA_RPC_URL` (default devnet's public
+    /// endpoint) read at `SOLANA_COMMITMENT` (default confirmed), serving the
+    /// program at `PROGRAM_ID`, which defaults to the address its IDL was
+    /// generated for.
+    pub async fn new(network: &Network) -> Result<Self> {
+        let rpc_url = network.var("SOLANA_RPC_URL")
+            .unwrap_or_else(|| "https://api.devnet.solana.com".to_string());
+        let commitment = network.var("SOLANA_COMMITMENT")
+            .map(|level| level.parse::<Commitment>())
+            .transpose()?
+            .unwrap_or(Commitment::Confirmed);
+        
+        // Each network may run a build of the program with its own IDL
+        let shared_idl = idl::load()?;
+        let idl = match network.var("PROGRAM_IDL_PATH") {
+            Some(path) if env::var("PROGRAM_IDL_PATH").ok().as_deref() != Some(path.as_str()) => idl::load_file(&path)?,
+            _ => shared_idl,
+        };
+        let program_id = match network.var("PROGRAM_ID") {
+            Some(program_id) => Pubkey::from_str(&program_id)
+                .map_err(|e| anyhow!("Invalid program ID: {}", e))?,
+            None => idl.address,
+        };
+        idl.verify(&program_id, &OPERATIONS, ACCOUNTS)?;
+        
+        let payer = signer::load(&network.signer_source()?)?;
+        
+        let ws_url = network.var("SOLANA_WS_URL").unwrap_or_else(|| websocket_url(&rpc_url));
+        let client = Arc::new(RpcClient::new_with_commitment(rpc_url, commitment.config()));
+        
+        let _ = client.get_latest_blockhash()
+            .map_err(|e| anyhow!("Failed to connect to Solana: {}", e))?;
+        let local_validator = ["://localhost", "://127.0.0.1"].iter().any(|host| client.url().contains(host));
+        let faucet_cluster = local_validator || client.get_genesis_hash()
+            .map(|hash| hash.to_string() == DEVNET_GENESIS_HASH)
+            .unwrap_or(false);
+        let faucet_mint = network.var("FAUCET_MINT")
+            .map(|mint| Pubkey::from_str(&mint).map_err(|e| anyhow!("Invalid FAUCET_MINT: {}", e)))
+            .transpose()?;
+        
+        // Each account backs one outstanding prepared transaction at a time
+        let nonce_accounts = network.var("NONCE_ACCOUNTS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|address| !address.is_empty())
+            .map(|address| Pubkey::from_str(address).map_err(|e| anyhow!("Invalid nonce account {}: {}", address, e)))
+            .collect::<Result<Vec<_>>>()?;
+        let lookup_tables = network.var("LOOKUP_TABLES")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|address| !address.is_empty())
+            .map(|address| Pubkey::from_str(address).map_err(|e| anyhow!("Invalid lookup table {}: {}", address, e)))
+            .collect::<Result<Vec<_>>>()?;
+        
+        Ok(Self {
+            client,
+            program_id,
+            idl,
+            payer: RwLock::new(payer),
+            sends: RwLock::new(()),
+            accounts: AccountCache::from_env(),
+            fees: PriorityFees::from_env(),
+            write_commitments: WriteCommitments::from_env()?,
+            pending_creations: AtomicUsize::new(0),
+            pending_rent: AtomicU64::new(0),
+            nonce_accounts: RwLock::new(nonce_accounts),
+            next_nonce: AtomicUsize::new(0),
+            lookup_tables: RwLock::new(lookup_tables),
+            ws_url,
+            updates: broadcast::channel(UPDATE_CHANNEL_CAPACITY).0,
+            faucet_cluster,
+            faucet_mint,
+            jito: JitoClient::for_network(network)?,
+        })
+    }
+
+    pub async fn initialize_asset(
+        &self,
+        asset_id: &str,
+        asset_type: &str,
+        valuation: u64,
+        metadata_uri: &str,
+        owner: Pubkey,
+    ) -> Result<InitializeAssetResult> {
+        let (asset_pda, instructions) = self.initialize_asset_instructions(asset_id, asset_type, valuation, metadata_uri, owner)?;
+        let transaction = self.submit_for(&instructions, &owner)
+            .map_err(|e| anyhow!("Transaction failed: {}", e))?;
+
+        Ok(InitializeAssetResult {
+            asset_pda: asset_pda.to_string(),
+            transaction,
+        })
+    }
+
+    /// Create many assets with as few transactions as fit them: each owner's
+    /// assets, in batch order, are packed into legacy-sized transactions
+    /// that the owner signs. Assets whose instructions can't be built come
+    /// back as chunks of one with the error.
+    pub fn initialize_assets(&self, assets: &[NewAsset]) -> Vec<AssetBatchChunk> {
+        let mut owners: Vec<Pubkey> = Vec::new();
+        for asset in assets {
+            if !owners.contains(&asset.owner) {
+                owners.push(asset.owner);
+            }
+        }
+
+        let mut chunks = Vec::new();
+        for owner in owners {
+            let mut packed: Vec<(usize, Pubkey)> = Vec::new();
+            let mut instructions: Vec<Instruction> = Vec::new();
+            for (index, asset) in assets.iter().enumerate().filter(|(_, asset)| asset.owner == owner) {
+                let built = self.initialize_asset_instructions(
+                    &asset.asset_id, &asset.asset_type, asset.valuation, &asset.metadata_uri, owner,
+                );
+                let (asset_pda, asset_instructions) = match built {
+                    Ok(built) => built,
+                    Err(e) => {
+                        chunks.push(AssetBatchChunk { assets: vec![(index, self.asset_address(&asset.asset_id).0)], transaction: Err(e) });
+                        continue;
+                    }
+                };
+                let candidate = [instructions.as_slice(), asset_instructions.as_slice()].concat();
+                if !packed.is_empty() && !self.fits_in_packet(&candidate, &owner) {
+                    chunks.push(self.submit_chunk(std::mem::take(&mut packed), &std::mem::take(&mut instructions), &owner));
+                    instructions = asset_instructions;
+                } else {
+                    instructions = candidate;
+                }
+                packed.push((index, asset_pda));
+            }
+            if !packed.is_empty() {
+                chunks.push(self.submit_chunk(packed, &instructions, &owner));
+            }
+        }
+        chunks
+    }
+
+    fn submit_chunk(&self, assets: Vec<(usize, Pubkey)>, instructions: &[Instruction], owner: &Pubkey) -> AssetBatchChunk {
+        tracing::info!("📦 Creating {} assets in one transaction", assets.len());
+        let transaction = self.submit_for(instructions, owner)
+            .map_err(|e| anyhow!("Transaction failed: {}", e));
+        AssetBatchChunk { assets, transaction }
+    }
+
+    /// Whether `instructions`, with the nonce and compute budget instructions
+    /// `build_transaction` adds, fit a legacy packet.
+    fn fits_in_packet(&self, instructions: &[Instruction], fee_payer: &Pubkey) -> bool {
+        let payer = self.payer().pubkey();
+        let mut budgeted = Vec::with_capacity(instructions.len() + 3);
+        if *fee_payer != payer {
+            budgeted.push(system_instruction::advance_nonce_account(&Pubkey::default(), &payer));
+        }
+        budgeted.extend(self.fees.instructions(instructions.len(), 0));
+        budgeted.extend_from_slice(instructions);
+        let transaction = Transaction::new_unsigned(Message::new(&budgeted, Some(fee_payer)));
+        bincode::serialized_size(&transaction).is_ok_and(|size| size as usize <= PACKET_DATA_SIZE)
+    }
+
+    /// The asset PDA and the initialize_asset and NFT mint instructions.
+    fn initialize_asset_instructions(
+        &self,
+        asset_id: &str,
+        asset_type: &str,
+        valuation: u64,
+        metadata_uri: &str,
+        owner: Pubkey,
+    ) -> Result<(Pubkey, Vec<Instruction>)> {
+        let (asset_pda, bump) = self.asset_address(asset_id);
+
+        tracing::info!("Asset PDA: {} with bump: {}", asset_pda, bump);
+
+        let instruction_data = self.idl.instruction_data(
+            "initialize_asset",
+            &[json!(asset_id), json!(asset_type), json!(valuation), json!(metadata_uri)],
+        )?;
+
+        let accounts = vec![
+            solana_sdk::instruction::AccountMeta::new(asset_pda, false),
+            solana_sdk::instruction::AccountMeta::new(owner, true),
+            solana_sdk::instruction::AccountMeta::new_readonly(system_program::id(), false),
+            solana_sdk::instruction::AccountMeta::new(self.stats_pda(), false),
+        ];
+
+        let instruction = Instruction {
+            program_id: self.program_id,
+            accounts,
+            data: instruction_data,
+        };
+        // The asset's NFT is minted in the same transaction
+        let mint_nft = self.mint_asset_nft_instruction(asset_pda, owner)?;
+
+        Ok((asset_pda, vec![instruction, mint_nft]))
+    }
+
+    pub async fn update_risk_score(
+        &self,
+        asset_id: &str,
+        risk_score: u8,
+    ) -> Result<String> {
+        let instruction = self.update_risk_score_instruction(asset_id, risk_score)?;
+        let signature = self.send_transaction(&[instruction], &self.payer().pubkey())
+            .map_err(|e| anyhow!("Update failed: {}", e))?;
+
+        Ok(signature.to_string())
+    }
+
+    // The payer signs as the risk oracle
+    fn update_risk_score_instruction(&self, asset_id: &str, risk_score: u8) -> Result<Instruction> {
+        let (asset_pda, _) = self.asset_address(asset_id);
+
+        let instruction_data = self.idl.instruction_data("update_risk_score", &[json!(risk_score)])?;
+
+        let accounts = vec![
+            solana_sdk::instruction::AccountMeta::new(asset_pda, false),
+            solana_sdk::instruction::AccountMeta::new_readonly(self.payer().pubkey(), true),
+            self.history_account(&asset_pda),
+            solana_sdk::instruction::AccountMeta::new_readonly(self.config_pda(), false),
+            solana_sdk::instruction::AccountMeta::new_readonly(self.oracle_bond_pda(&self.payer().pubkey()), false),
+        ];
+
+        Ok(Instruction {
+            program_id: self.program_id,
+            accounts,
+            data: instruction_data,
+        })
+    }
+
+    /// Post the Merkle root of a batch of risk scores with the payer as risk
+    /// oracle. Returns the transaction, the root, and each entry's proof.
+    pub async fn post_risk_report(
+        &self,
+        report_id: u64,
+        entries: &[(String, u8)],
+    ) -> Result<(String, [u8; 32], Vec<Vec<[u8; 32]>>)> {
+        let leaves: Vec<[u8; 32]> = entries
+            .iter()
+            .map(|(asset_id, risk_score)| {
+                let (asset_pda, _) = self.asset_address(asset_id);
+                risk_reports::leaf(&asset_pda, *risk_score)
+            })
+            .collect();
+        let (root, proofs) = risk_reports::build(&leaves);
+
+        let (config_pda, _) = Pubkey::find_program_address(&[b"config"], &self.program_id);
+
+        let instruction_data = self.idl.instruction_data(
+            "post_risk_report",
+            &[json!(report_id), json!(root), json!(leaves.len())],
+        )?;
+
+        let accounts = vec![
+            solana_sdk::instruction::AccountMeta::new(self.risk_report_pda(report_id), false),
+            solana_sdk::instruction::AccountMeta::new_readonly(config_pda, false),
+            solana_sdk::instruction::AccountMeta::new(self.payer().pubkey(), true),
+            solana_sdk::instruction::AccountMeta::new_readonly(system_program::id(), false),
+            // The payer must have registered a bond with `register_oracle`
+            solana_sdk::instruction::AccountMeta::new(self.oracle_bond_pda(&self.payer().pubkey()), false),
+        ];
+
+        let instruction = Instruction {
+            program_id: self.program_id,
+            accounts,
+            data: instruction_data,
+        };
+
+        let signature = self.send_transaction(&[instruction], &self.payer().pubkey())
+            .map_err(|e| anyhow!("Posting risk report failed: {}", e))?;
+
+        Ok((signature.to_string(), root, proofs))
+    }
+
+    /// Apply one asset's score from a posted risk report.
+    pub async fn apply_risk_report_entry(
+        &self,
+        report_id: u64,
+        asset_id: &str,
+        risk_score: u8,
+        proof: &[[u8; 32]],
+    ) -> Result<String> {
+        let (asset_pda, _) = self.asset_address(asset_id);
+
+        let instruction_data = self.idl.instruction_data("apply_risk_report_entry", &[json!(risk_score), json!(proof)])?;
+
+        let accounts = vec![
+            solana_sdk::instruction::AccountMeta::new_readonly(self.risk_report_pda(report_id), false),
+            solana_sdk::instruction::AccountMeta::new(asset_pda, false),
+            self.history_account(&asset_pda),
+            solana_sdk::instruction::AccountMeta::new_readonly(self.config_pda(), false),
+        ];
+
+        let instruction = Instruction {
+            program_id: self.program_id,
+            accounts,
+            data: instruction_data,
+        };
+
+        let signature = self.send_transaction(&[instruction], &self.payer().pubkey())
+            .map_err(|e| anyhow!("Applying risk report entry failed: {}", e))?;
+
+        Ok(signature.to_string())
+    }
+
+    fn risk_report_pda(&self, report_id: u64) -> Pubkey {
+        Pubkey::find_program_address(&[b"risk_report", &report_id.to_le_bytes()], &self.program_id).0
+    }
+
+    // Append to the asset's history when one was created; the program ID
+    // stands in for the optional account otherwise
+    fn history_account(&self, asset_pda: &Pubkey) -> solana_sdk::instruction::AccountMeta {
+        let (history_pda, _) = Pubkey::find_program_address(
+            &[b"asset_history", asset_pda.as_ref()],
+            &self.program_id,
+        );
+        if self.client.get_account(&history_pda).is_ok() {
+            solana_sdk::instruction::AccountMeta::new(history_pda, false)
+        } else {
+            solana_sdk::instruction::AccountMeta::new_readonly(self.program_id, false)
+        }
+    }
+
+    // The lender's whitelist membership when it has one; the program only
+    // requires it while the lender whitelist is enforced
+    fn lender_membership_account(&self, lender: &Pubkey) -> solana_sdk::instruction::AccountMeta {
+        let (membership_pda, _) = Pubkey::find_program_address(
+            &[b"lender_membership", lender.as_ref()],
+            &self.program_id,
+        );
+        if self.client.get_account(&membership_pda).is_ok() {
+            solana_sdk::instruction::AccountMeta::new_readonly(membership_pda, false)
+        } else {
+            solana_sdk::instruction::AccountMeta::new_readonly(self.program_id, false)
+        }
+    }
+
+    // Rules of the asset's jurisdiction; the program requires them once the
+    // asset has been placed in one
+    fn jurisdiction_account(&self, asset: &AssetResponse) -> solana_sdk::instruction::AccountMeta {
+        match &asset.jurisdiction {
+            Some(code) => {
+                let (jurisdiction_pda, _) = Pubkey::find_program_address(
+                    &[b"jurisdiction", code.as_bytes()],
+                    &self.program_id,
+                );
+                solana_sdk::instruction::AccountMeta::new_readonly(jurisdiction_pda, false)
+            }
+            None => solana_sdk::instruction::AccountMeta::new_readonly(self.program_id, false),
+        }
+    }
+
+    // Protocol revenue account and vault for `mint`, or program-id
+    // placeholders when no revenue account has been opened for it
+    fn revenue_accounts(&self, mint: &Pubkey) -> Result<[solana_sdk::instruction::AccountMeta; 2]> {
+        let placeholder = solana_sdk::instruction::AccountMeta::new_readonly(self.program_id, false);
+        let (revenue_pda, _) = Pubkey::find_program_address(&[b"revenue", mint.as_ref()], &self.program_id);
+        match self.client.get_account(&revenue_pda) {
+            Ok(account) => {
+                let revenue = ProtocolRevenueAccount::from_bytes(&account.data)?;
+                Ok([
+                    solana_sdk::instruction::AccountMeta::new(revenue_pda, false),
+                    solana_sdk::instruction::AccountMeta::new(revenue.vault, false),
+                ])
+            }
+            Err(_) => Ok([placeholder.clone(), placeholder]),
+        }
+    }
+
+    // SPL Token or Token-2022, whichever owns the repayment mint
+    fn repayment_token_program(&self, mint: &Pubkey) -> Result<Pubkey> {
+        let token_program = self.client.get_account(mint)
+            .map_err(|e| anyhow!("Repayment mint not found: {}", e))?
+            .owner;
+        let supported = [Pubkey::from_str(TOKEN_PROGRAM_ID)?, Pubkey::from_str(TOKEN_2022_PROGRAM_ID)?];
+        if !supported.contains(&token_program) {
+            return Err(anyhow!("Repayment mint is not owned by a token program"));
+        }
+        Ok(token_program)
+    }
+
+    // Revenue and referral accounts receiving a share of the origination fee;
+    // program-id placeholders where the loan doesn't need them
+    fn origination_fee_accounts(&self, loan: &LoanAccount) -> Result<Vec<solana_sdk::instruction::AccountMeta>> {
+        let placeholder = solana_sdk::instruction::AccountMeta::new_readonly(self.program_id, false);
+        let mut accounts = self.revenue_accounts(&loan.repayment_mint)?.to_vec();
+        accounts.extend([placeholder.clone(), placeholder]);
+
+        if loan.referral != Pubkey::default() {
+            let account = self.client.get_account(&loan.referral)
+                .map_err(|e| anyhow!("Referral account not found: {}", e))?;
+            let vault = ReferralAccount::from_bytes(&account.data)?.vault;
+            accounts[2] = solana_sdk::instruction::AccountMeta::new(loan.referral, false);
+            accounts[3] = solana_sdk::instruction::AccountMeta::new(vault, false);
+        }
+        Ok(accounts)
+    }
+
+    pub async fn get_asset(&self, asset_id: &str) -> Result<AssetResponse> {
+        self.get_asset_at(asset_id, None).await
+    }
+
+    /// `get_asset` as of `commitment`, or the client's default when None.
+    pub async fn get_asset_at(&self, asset_id: &str, commitment: Option<CommitmentConfig>) -> Result<AssetResponse> {
+        let (asset_pda, _) = self.asset_address(asset_id);
+
+        tracing::info!("Fetching asset from PDA: {}", asset_pda);
+
+        let account = self.account_at(&asset_pda, commitment)
+            .map_err(|e| anyhow!("Asset not found: {}", e))?;
+        
+        let asset_account = AssetAccount::from_bytes(&account.data)?;
+        
+        Ok(AssetResponse::from(asset_account))
+    }
+
+    /// Assets matching `filter`, ordered by asset id.
+    pub async fn list_assets(&self, filter: &AssetFilter, commitment: Option<CommitmentConfig>) -> Result<Vec<AssetResponse>> {
+        let mut assets = self.fetch_assets(commitment).await?;
+        assets.retain(|asset| filter.matches(asset));
+        assets.sort_by(|a, b| a.asset_id.cmp(&b.asset_id));
+        Ok(assets.into_iter().map(AssetResponse::from).collect())
+    }
+
+    /// Fetch every Asset account owned by the program.
+    ///
+    /// Asset accounts are sized to their id and type strings, so neither
+    /// dataSize nor memcmp on the fields after them can be pushed to the RPC
+    /// node; it filters on the Asset discriminator alone.
+    pub async fn fetch_assets(&self, commitment: Option<CommitmentConfig>) -> Result<Vec<AssetAccount>> {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                0,
+                AssetAccount::discriminator()?.to_vec(),
+            ))]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                commitment,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let accounts = self.client.get_program_accounts_with_config(&self.program_id, config)
+            .map_err(|e| anyhow!("Failed to fetch assets: {}", e))?;
+
+        accounts
+            .into_iter()
+            .map(|(_, account)| AssetAccount::from_bytes(&account.data))
+            .collect()
+    }
+
+    pub async fn deactivate_asset(&self, asset_id: &str) -> Result<String> {
+        let (asset_pda, _) = self.asset_address(asset_id);
+
+        let accounts = vec![
+            solana_sdk::instruction::AccountMeta::new(asset_pda, false),
+            solana_sdk::instruction::AccountMeta::new_readonly(self.payer().pubkey(), true),
+        ];
+
+        let instruction = Instruction {
+            program_id: self.program_id,
+            accounts,
+            data: self.idl.instruction_data("deactivate_asset", &[])?,
+        };
+
+        let signature = self.send_transaction(&[instruction], &self.payer().pubkey())
+            .map_err(|e| anyhow!("Deactivation failed: {}", e))?;
+
+        Ok(signature.to_string())
+    }
+
+    pub async fn set_delegate(&self, asset_id: &str, delegate: Pubkey) -> Result<String> {
+        let data = self.idl.instruction_data("set_delegate", &[json!(delegate.to_string())])?;
+        self.send_owner_instruction(asset_id, data, Vec::new())
+            .map_err(|e| anyhow!("Setting delegate failed: {}", e))
+    }
+
+    pub async fn revoke_delegate(&self, asset_id: &str) -> Result<String> {
+        let data = self.idl.instruction_data("revoke_delegate", &[])?;
+        self.send_owner_instruction(asset_id, data, Vec::new())
+            .map_err(|e| anyhow!("Revoking delegate failed: {}", e))
+    }
+
+    pub async fn transfer_asset(&self, asset_id: &str, new_owner: Pubkey) -> Result<String> {
+        let data = self.idl.instruction_data("transfer_asset", &[json!(new_owner.to_string())])?;
+        // The payer co-signs as compliance authority, which only satisfies
+        // jurisdictions requiring approval when it holds that role
+        let asset = self.get_asset(asset_id).await?;
+        let extra_accounts = vec![
+            solana_sdk::instruction::AccountMeta::new_readonly(self.config_pda(), false),
+            self.jurisdiction_account(&asset),
+            solana_sdk::instruction::AccountMeta::new_readonly(self.payer().pubkey(), true),
+        ];
+        self.send_owner_instruction(asset_id, data, extra_accounts)
+            .map_err(|e| anyhow!("Asset transfer failed: {}", e))
+    }
+
+    /// The transaction behind the compute budget instructions, priced off
+    /// recent fees so it lands under congestion, ready to be signed. With a
+    /// durable nonce it stays valid until the nonce advances rather than
+    /// expiring with the recent blockhash. Transactions too large for a
+    /// legacy packet are compiled to v0 against the lookup tables.
+    fn build_transaction(
+        &self,
+        instructions: &[Instruction],
+        fee_payer: &Pubkey,
+        durable_nonce: Option<(Pubkey, Hash)>,
+    ) -> Result<VersionedTransaction> {
+        let price = match self.fees.fixed_price() {
+            Some(price) => price,
+            None => {
+                let mut writable: Vec<Pubkey> = instructions.iter()
+                    .flat_map(|instruction| &instruction.accounts)
+                    .filter(|account| account.is_writable)
+                    .map(|account| account.pubkey)
+                    .collect();
+                writable.sort_unstable();
+                writable.dedup();
+                match self.client.get_recent_prioritization_fees(&writable) {
+                    Ok(fees) => self.fees.price_from(fees.into_iter().map(|fee| fee.prioritization_fee).collect()),
+                    Err(e) => {
+                        tracing::warn!("⚠️ Could not fetch prioritization fees, sending without one: {}", e);
+                        0
+                    }
+                }
+            }
+        };
+        let mut budgeted = Vec::with_capacity(instructions.len() + 3);
+        // The runtime only honours a nonce advanced by the first instruction
+        if let Some((nonce_account, _)) = durable_nonce {
+            budgeted.push(system_instruction::advance_nonce_account(&nonce_account, &self.payer().pubkey()));
+        }
+        budgeted.extend(self.fees.instructions(instructions.len(), price));
+        budgeted.extend_from_slice(instructions);
+
+        let recent_blockhash = match durable_nonce {
+            Some((_, blockhash)) => blockhash,
+            None => self.client.get_latest_blockhash()
+                .map_err(|e| anyhow!("Failed to get blockhash: {}", e))?,
+        };
+        let legacy = Message::new_with_blockhash(&budgeted, Some(fee_payer), &recent_blockhash);
+        let legacy_size = bincode::serialized_size(&Transaction::new_unsigned(legacy.clone()))
+            .map_err(|e| anyhow!("Failed to size transaction: {}", e))?;
+        let message = if legacy_size as usize <= PACKET_DATA_SIZE || self.lookup_tables.read().unwrap().is_empty() {
+            VersionedMessage::Legacy(legacy)
+        } else {
+            let tables = self.lookup_table_accounts()?;
+            VersionedMessage::V0(
+                v0::Message::try_compile(fee_payer, &budgeted, &tables, recent_blockhash)
+                    .map_err(|e| anyhow!("Failed to compile v0 transaction: {}", e))?,
+            )
+        };
+
+        Ok(VersionedTransaction {
+            signatures: vec![Signature::default(); message.header().num_required_signatures as usize],
+            message,
+        })
+    }
+
+    /// Sign `transaction` as each of `signers`; other signatures stay empty.
+    fn sign_transaction(transaction: &mut VersionedTransaction, signers: &[&dyn Signer]) -> Result<()> {
+        let message = transaction.message.serialize();
+        let required = transaction.message.header().num_required_signatures as usize;
+        for signer in signers {
+            let index = transaction.message.static_account_keys()[..required]
+                .iter()
+                .position(|key| *key == signer.pubkey())
+                .ok_or_else(|| anyhow!("{} does not sign this transaction", signer.pubkey()))?;
+            transaction.signatures[index] = signer.sign_message(&message);
+        }
+        Ok(())
+    }
+
+    /// Sign with the service payer and send.
+    fn send_transaction(&self, instructions: &[Instruction], fee_payer: &Pubkey) -> Result<Signature> {
+        let mut transaction = self.build_transaction(instructions, fee_payer, None)?;
+        Self::sign_transaction(&mut transaction, &[&*self.payer()])?;
+        if transaction.signatures.contains(&Signature::default()) {
+            return Err(anyhow!("Transaction needs signers the service does not hold"));
+        }
+
+        self.send_and_confirm(&transaction)
+    }
+
+    /// Sign with the service payer and submit through the block engine as a
+    /// tipped bundle, racing other searchers for time-sensitive operations.
+    /// A plain send when no block engine is configured.
+    async fn send_bundled(&self, instructions: &[Instruction]) -> Result<Signature> {
+        let payer = self.payer();
+        let Some(jito) = &self.jito else {
+            return self.send_transaction(instructions, &payer.pubkey());
+        };
+
+        let mut tipped = instructions.to_vec();
+        tipped.push(jito.tip_instruction(&payer.pubkey()).await?);
+        let mut transaction = self.build_transaction(&tipped, &payer.pubkey(), None)?;
+        Self::sign_transaction(&mut transaction, &[&*payer])?;
+        self.deliver(&transaction, Some(jito))
+    }
+
+    /// Send and wait for the commitment its operations are configured for,
+    /// then drop cached copies of the accounts it wrote.
+    fn send_and_confirm(&self, transaction: &VersionedTransaction) -> Result<Signature> {
+        self.deliver(transaction, None)
+    }
+
+    fn deliver(&self, transaction: &VersionedTransaction, bundle: Option<&JitoClient>) -> Result<Signature> {
+        let _in_flight = self.sends.read().unwrap();
+        let commitment = self.write_commitments
+            .for_operations(self.program_instructions(&transaction.message).map(|(operation, _)| operation))
+            .config();
+        let (creations, rent) = self.creation_rent(&transaction.message);
+        self.pending_creations.fetch_add(creations, Ordering::Relaxed);
+        self.pending_rent.fetch_add(rent, Ordering::Relaxed);
+        let result = tokio::task::block_in_place(|| {
+            let signature = match bundle {
+                Some(jito) => {
+                    let bundle_id = tokio::runtime::Handle::current().block_on(jito.send_bundle(&[transaction]))?;
+                    tracing::info!("📦 Bundle {} submitted", bundle_id);
+                    transaction.signatures[0]
+                }
+                None => self.client.send_transaction(transaction)?,
+            };
+            self.confirm_at(signature, commitment)
+        });
+        self.pending_creations.fetch_sub(creations, Ordering::Relaxed);
+        self.pending_rent.fetch_sub(rent, Ordering::Relaxed);
+        // Even a failed confirmation may have landed
+        self.accounts.invalidate_writes(&transaction.message);
+        result
+    }
+
+    /// Accounts `message` creates with the payer's lamports, and the rent
+    /// they will hold: assets the payer owns and loans it borrows.
+    fn creation_rent(&self, message: &VersionedMessage) -> (usize, u64) {
+        let payer = self.payer().pubkey();
+        let keys = message.static_account_keys();
+        let funded_by_payer = |instruction: &CompiledInstruction, position: usize| {
+            instruction.accounts.get(position).and_then(|&index| keys.get(index as usize)) == Some(&payer)
+        };
+        let rent = Rent::default();
+        self.program_instructions(message)
+            .filter_map(|(operation, instruction)| match operation {
+                "initialize_asset" if funded_by_payer(instruction, 1) => asset_account_space(&instruction.data),
+                "request_loan" if funded_by_payer(instruction, 4) => Some(LOAN_ACCOUNT_SPACE),
+                _ => None,
+            })
+            .fold((0, 0), |(count, lamports), space| (count + 1, lamports + rent.minimum_balance(space)))
+    }
+
+    fn confirm_at(&self, signature: Signature, commitment: CommitmentConfig) -> Result<Signature> {
+        let started = std::time::Instant::now();
+        while started.elapsed() < CONFIRMATION_TIMEOUT {
+            if let Some(status) = self.client.get_signature_status_with_commitment(&signature, commitment)? {
+                return status.map(|_| signature).map_err(|e| anyhow!("Transaction {} failed: {}", signature, e));
+            }
+            std::thread::sleep(CONFIRMATION_POLL_INTERVAL);
+        }
+        Err(anyhow!("Transaction {} not {:?} after {}s", signature, commitment.commitment, CONFIRMATION_TIMEOUT.as_secs()))
+    }
+
+    /// The program instructions in `message`, by operation name.
+    fn program_instructions<'a>(&self, message: &'a VersionedMessage) -> impl Iterator<Item = (&'static str, &'a CompiledInstruction)> + 'a {
+        let program_id = self.program_id;
+        let idl = self.idl;
+        message.instructions().iter()
+            .filter(move |instruction| message.static_account_keys().get(instruction.program_id_index as usize) == Some(&program_id))
+            .filter_map(move |instruction| Some((idl.instruction_name(&instruction.data)?, instruction)))
+    }
+
+    /// Asset or loan account, from the cache while it is fresh.
+    fn cached_account(&self, address: &Pubkey) -> Result<Account> {
+        if let Some(account) = self.accounts.get(address) {
+            return Ok(account);
+        }
+        let account = self.client.get_account(address)?;
+        self.accounts.insert(*address, account.clone());
+        Ok(account)
+    }
+
+    /// Account as of `commitment`. The cache only holds accounts read at the
+    /// client's own commitment, so other levels always go to the node.
+    fn account_at(&self, address: &Pubkey, commitment: Option<CommitmentConfig>) -> Result<Account> {
+        match commitment {
+            Some(commitment) if commitment != self.client.commitment() => self.client
+                .get_account_with_commitment(address, commitment)?
+                .value
+                .ok_or_else(|| anyhow!("AccountNotFound: pubkey={}", address)),
+            _ => self.cached_account(address),
+        }
+    }
+
+    /// Send on behalf of `user` when the service holds their key; otherwise
+    /// hand back the transaction for their wallet to sign and submit.
+    fn submit_for(&self, instructions: &[Instruction], user: &Pubkey) -> Result<Submission> {
+        if *user == self.payer().pubkey() {
+            return Ok(Submission::Sent(self.send_transaction(instructions, user)?.to_string()));
+        }
+
+        // The wallet may take longer to sign than a blockhash lives
+        let durable_nonce = self.next_durable_nonce()?;
+        let mut transaction = self.build_transaction(instructions, user, durable_nonce)?;
+        let required = transaction.message.header().num_required_signatures as usize;
+        if transaction.message.static_account_keys()[..required].contains(&self.payer().pubkey()) {
+            Self::sign_transaction(&mut transaction, &[&*self.payer()])?;
+        }
+        let bytes = bincode::serialize(&transaction)
+            .map_err(|e| anyhow!("Failed to serialize transaction: {}", e))?;
+        Ok(Submission::Unsigned(BASE64.encode(bytes)))
+    }
+
+    /// What `user` would pay for `instructions` sent the way `submit_for`
+    /// sends them, with rent for the `created` accounts.
+    fn estimate_cost(&self, instructions: &[Instruction], user: &Pubkey, created: &[(&'static str, usize)]) -> Result<CostEstimate> {
+        // A wallet-signed transaction advances a pooled nonce, which the payer signs for
+        let nonce_account = (*user != self.payer().pubkey())
+            .then(|| self.nonce_accounts.read().unwrap().first().copied())
+            .flatten();
+        let durable_nonce = match nonce_account {
+            Some(nonce_account) => {
+                // The fee is quoted against a live blockhash, not the nonce's
+                let blockhash = self.client.get_latest_blockhash()
+                    .map_err(|e| anyhow!("Failed to get blockhash: {}", e))?;
+                Some((nonce_account, blockhash))
+            }
+            None => None,
+        };
+        let transaction = self.build_transaction(instructions, user, durable_nonce)?;
+        let transaction_fee = match &transaction.message {
+            VersionedMessage::Legacy(message) => self.client.get_fee_for_message(message),
+            VersionedMessage::V0(message) => self.client.get_fee_for_message(message),
+        }
+        .map_err(|e| anyhow!("Failed to get fee: {}", e))?;
+
+        let rent = created.iter()
+            .map(|&(account, space)| {
+                let lamports = self.client.get_minimum_balance_for_rent_exemption(space)
+                    .map_err(|e| anyhow!("Failed to get rent: {}", e))?;
+                Ok(RentCost { account, space, lamports })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let rent_total = rent.iter().map(|cost| cost.lamports).sum::<u64>();
+
+        Ok(CostEstimate {
+            fee_payer: user.to_string(),
+            signatures: transaction.message.header().num_required_signatures,
+            transaction_fee,
+            rent,
+            rent_total,
+            total_lamports: transaction_fee + rent_total,
+        })
+    }
+
+    /// Cost of `initialize_asset` for `owner`: the asset account and its
+    /// NFT's mint, token account and metadata.
+    pub async fn estimate_asset(&self, asset_id: &str, asset_type: &str, owner: Pubkey) -> Result<CostEstimate> {
+        let (_, instructions) = self.initialize_asset_instructions(asset_id, asset_type, 0, "", owner)?;
+        self.estimate_cost(&instructions, &owner, &[
+            ("asset", ASSET_ACCOUNT_BASE_SPACE + asset_id.len() + asset_type.len()),
+            ("nft_mint", MINT_ACCOUNT_SPACE),
+            ("token_account", TOKEN_ACCOUNT_SPACE),
+            ("nft_metadata", METADATA_ACCOUNT_SPACE),
+        ])
+    }
+
+    /// Cost of `request_loan` against `asset_id` for `borrower`: the loan account.
+    pub async fn estimate_loan(&self, asset_id: &str, borrower: Pubkey) -> Result<CostEstimate> {
+        // Neither the terms nor the repayment mint change the fee or the loan's size
+        let terms = LoanTerms {
+            loan_amount: 0,
+            interest_rate: 0,
+            duration: 0,
+            interest_mode: InterestMode::default(),
+            product: LoanProduct::default(),
+            floating_rate: None,
+        };
+        let (_, _, instructions) = self.request_loan_instructions(asset_id, borrower, &terms, Pubkey::default()).await?;
+        self.estimate_cost(&instructions, &borrower, &[("loan", LOAN_ACCOUNT_SPACE)])
+    }
+
+    /// The IDL instructions are encoded with on this network.
+    pub fn idl(&self) -> &'static ProgramIdl {
+        self.idl
+    }
+
+    /// Start composing operations into a single atomic transaction.
+    pub fn compose(&self) -> TransactionComposer<'_> {
+        TransactionComposer { service: self, instructions: Vec::new() }
+    }
+
+    /// The current payer, or the tenant's while one is in scope. A
+    /// transaction whose instructions were built before a rotation names the
+    /// old key and fails to sign rather than going out.
+    fn payer(&self) -> PayerSigner {
+        TENANT_PAYER
+            .try_with(PayerSigner::clone)
+            .unwrap_or_else(|_| self.payer.read().unwrap().clone())
+    }
+
+    /// The node's processed slot and the highest slot it has received
+    /// shreds for; the gap is how far behind the cluster it answers.
+    pub async fn rpc_slots(&self) -> Result<(u64, u64)> {
+        let slot = self.client.get_slot_with_commitment(CommitmentConfig::processed())
+            .map_err(|e| anyhow!("Failed to get slot: {}", e))?;
+        let tip = self.client.get_max_shred_insert_slot()
+            .map_err(|e| anyhow!("Failed to get max shred insert slot: {}", e))?;
+        Ok((slot, tip.max(slot)))
+    }
+
+    /// Balance of the current payer alongside the rent of pending creations.
+    pub async fn payer_wallet(&self) -> Result<PayerWallet> {
+        let payer = self.payer().pubkey();
+        let balance = self.client.get_balance(&payer)
+            .map_err(|e| anyhow!("Failed to read payer balance: {}", e))?;
+        let rent_exemption = [
+            ("asset", ASSET_ACCOUNT_MAX_SPACE),
+            ("loan", LOAN_ACCOUNT_SPACE),
+            ("nonce", nonce::State::size()),
+            ("token_account", TOKEN_ACCOUNT_SPACE),
+        ]
+        .into_iter()
+        .map(|(account, space)| {
+            let lamports = self.client.get_minimum_balance_for_rent_exemption(space)
+                .map_err(|e| anyhow!("Failed to get rent: {}", e))?;
+            Ok(RentCost { account, space, lamports })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+        Ok(PayerWallet {
+            payer: payer.to_string(),
+            balance,
+            pending_creations: self.pending_creations.load(Ordering::Relaxed),
+            pending_rent: self.pending_rent.load(Ordering::Relaxed),
+            rent_exemption,
+        })
+    }
+
+    // ==================== Payer Rotation ====================
+
+    /// Switch the payer to the key at `source` without a restart. The new
+    /// key must hold `PAYER_MIN_BALANCE_LAMPORTS`; pooled nonce accounts are
+    /// re-authorized to it, and the switch waits for in-flight sends to
+    /// confirm. On-chain roles held by the old key don't move with it.
+    pub async fn rotate_payer(&self, source: &SignerSource) -> Result<PayerRotation> {
+        let signer = signer::load(source)?;
+        let previous = self.payer();
+        if signer.pubkey() == previous.pubkey() {
+            return Err(anyhow!("{} is already the payer", signer.pubkey()));
+        }
+
+        let min_balance = payer_min_balance();
+        let balance = self.client.get_balance(&signer.pubkey())
+            .map_err(|e| anyhow!("Failed to read new payer balance: {}", e))?;
+        if balance < min_balance {
+            return Err(anyhow!(
+                "New payer {} holds {} lamports, below the required {}",
+                signer.pubkey(), balance, min_balance,
+            ));
+        }
+
+        // Hand the pool over while the old key can still sign for it
+        let nonce_accounts = self.nonce_accounts.read().unwrap().clone();
+        let mut nonce_accounts_reauthorized = 0;
+        for nonce_account in nonce_accounts {
+            if self.nonce_data(&nonce_account)?.authority != previous.pubkey() {
+                continue;
+            }
+            let instruction = system_instruction::authorize_nonce_account(&nonce_account, &previous.pubkey(), &signer.pubkey());
+            self.send_transaction(&[instruction], &previous.pubkey())
+                .map_err(|e| anyhow!("Failed to re-authorize nonce account {}: {}", nonce_account, e))?;
+            nonce_accounts_reauthorized += 1;
+        }
+
+        {
+            let _drained = tokio::task::block_in_place(|| self.sends.write().unwrap());
+            *self.payer.write().unwrap() = signer.clone();
+        }
+        tracing::info!("🔑 Payer rotated from {} to {}", previous.pubkey(), signer.pubkey());
+
+        Ok(PayerRotation {
+            previous_payer: previous.pubkey().to_string(),
+            payer: signer.pubkey().to_string(),
+            balance,
+            nonce_accounts_reauthorized,
+        })
+    }
+
+    // ==================== Durable Nonces ====================
+
+    /// The next pooled nonce account and its stored blockhash, or None when
+    /// no nonce accounts are configured or a tenant's payer is in scope, since
+    /// the pool is authorized to the service payer.
+    fn next_durable_nonce(&self) -> Result<Option<(Pubkey, Hash)>> {
+        if TENANT_PAYER.try_with(|_| ()).is_ok() {
+            return Ok(None);
+        }
+        let nonce_account = {
+            let accounts = self.nonce_accounts.read().unwrap();
+            if accounts.is_empty() {
+                return Ok(None);
+            }
+            accounts[self.next_nonce.fetch_add(1, Ordering::Relaxed) % accounts.len()]
+        };
+        let data = self.nonce_data(&nonce_account)?;
+        if data.authority != self.payer().pubkey() {
+            return Err(anyhow!("Nonce account {} is not authorized to the payer", nonce_account));
+        }
+        Ok(Some((nonce_account, data.blockhash())))
+    }
+
+    fn nonce_data(&self, address: &Pubkey) -> Result<nonce::state::Data> {
+        let account = nonce_utils::get_account_with_commitment(&self.client, address, self.client.commitment())
+            .map_err(|e| anyhow!("Nonce account not found: {}", e))?;
+        nonce_utils::data_from_account(&account)
+            .map_err(|e| anyhow!("Account is not an initialized nonce account: {}", e))
+    }
+
+    pub async fn get_nonce_account(&self, address: &Pubkey) -> Result<NonceAccountInfo> {
+        let data = self.nonce_data(address)?;
+        Ok(NonceAccountInfo {
+            address: address.to_string(),
+            authority: data.authority.to_string(),
+            blockhash: data.blockhash().to_string(),
+            lamports_per_signature: data.fee_calculator.lamports_per_signature,
+            in_pool: self.nonce_accounts.read().unwrap().contains(address),
+        })
+    }
+
+    /// Create a rent-exempt nonce account authorized to the payer and add it
+    /// to the pool. Add its address to `NONCE_ACCOUNTS` to keep it across restarts.
+    pub async fn create_nonce_account(&self) -> Result<(NonceAccountInfo, String)> {
+        let nonce_keypair = Keypair::new();
+        let rent = self.client.get_minimum_balance_for_rent_exemption(nonce::State::size())
+            .map_err(|e| anyhow!("Failed to get rent: {}", e))?;
+        let instructions = system_instruction::create_nonce_account(
+            &self.payer().pubkey(),
+            &nonce_keypair.pubkey(),
+            &self.payer().pubkey(),
+            rent,
+        );
+
+        let mut transaction = self.build_transaction(&instructions, &self.payer().pubkey(), None)?;
+        Self::sign_transaction(&mut transaction, &[&*self.payer(), &nonce_keypair])?;
+        let signature = self.send_and_confirm(&transaction)
+            .map_err(|e| anyhow!("Nonce account creation failed: {}", e))?;
+
+        self.nonce_accounts.write().unwrap().push(nonce_keypair.pubkey());
+        Ok((self.get_nonce_account(&nonce_keypair.pubkey()).await?, signature.to_string()))
+    }
+
+    /// Drop a nonce account from the pool and withdraw its rent to the payer,
+    /// closing it. Transactions prepared against it can no longer land.
+    pub async fn close_nonce_account(&self, address: &Pubkey) -> Result<String> {
+        let balance = self.client.get_balance(address)
+            .map_err(|e| anyhow!("Failed to get nonce balance: {}", e))?;
+        self.nonce_accounts.write().unwrap().retain(|account| account != address);
+
+        let instruction = system_instruction::withdraw_nonce_account(
+            address,
+            &self.payer().pubkey(),
+            &self.payer().pubkey(),
+            balance,
+        );
+        let signature = self.send_transaction(&[instruction], &self.payer().pubkey())
+            .map_err(|e| anyhow!("Closing nonce account failed: {}", e))?;
+        Ok(signature.to_string())
+    }
+
+    // ==================== Pubsub Subscriptions ====================
+
+    /// Receive Asset and Loan account changes and program logs as they land.
+    pub fn subscribe_updates(&self) -> broadcast::Receiver<ChainUpdate> {
+        self.updates.subscribe()
+    }
+
+    /// Subscribe to the program's accounts and logs and forward decoded
+    /// changes to the update channel until the connection drops.
+    async fn stream_updates(&self) -> Result<()> {
+        let pubsub = PubsubClient::new(&self.ws_url).await
+            .map_err(|e| anyhow!("Failed to connect to {}: {}", self.ws_url, e))?;
+        let commitment = self.client.commitment();
+
+        let account_config = RpcProgramAccountsConfig {
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                commitment: Some(commitment),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let (mut accounts, _unsubscribe_accounts) = pubsub.program_subscribe(&self.program_id, Some(account_config)).await
+            .map_err(|e| anyhow!("Account subscription failed: {}", e))?;
+        let (mut logs, _unsubscribe_logs) = pubsub.logs_subscribe(
+            RpcTransactionLogsFilter::Mentions(vec![self.program_id.to_string()]),
+            RpcTransactionLogsConfig { commitment: Some(commitment) },
+        ).await
+            .map_err(|e| anyhow!("Log subscription failed: {}", e))?;
+        tracing::info!("✅ Subscribed to program accounts and logs at {}", self.ws_url);
+        let asset_discriminator = AssetAccount::discriminator()?;
+        let loan_discriminator = LoanAccount::discriminator()?;
+        // Writes made while disconnected went unseen
+        self.accounts.clear();
+
+        loop {
+            let update = tokio::select! {
+                Some(response) = accounts.next() => {
+                    let slot = response.context.slot;
+                    let keyed = response.value;
+                    if let Ok(address) = Pubkey::from_str(&keyed.pubkey) {
+                        self.accounts.invalidate(&address);
+                    }
+                    let Some(data) = keyed.account.data.decode() else { continue };
+                    match data.get(..8) {
+                        Some(discriminator) if discriminator == asset_discriminator => {
+                            match AssetAccount::from_bytes(&data) {
+                                Ok(asset) => ChainUpdate::Asset { address: keyed.pubkey, slot, asset: asset.into() },
+                                Err(e) => {
+                                    tracing::warn!("⚠️ Undecodable asset update for {}: {}", keyed.pubkey, e);
+                                    continue;
+                                }
+                            }
+                        }
+                        Some(discriminator) if discriminator == loan_discriminator => {
+                            match LoanAccount::from_bytes(&data) {
+                                Ok(loan) => ChainUpdate::Loan { address: keyed.pubkey, slot, loan: loan.into() },
+                                Err(e) => {
+                                    tracing::warn!("⚠️ Undecodable loan update for {}: {}", keyed.pubkey, e);
+                                    continue;
+                                }
+                            }
+                        }
+                        _ => continue,
+                    }
+                }
+                Some(response) = logs.next() => ChainUpdate::Logs {
+                    signature: response.value.signature,
+                    slot: response.context.slot,
+                    error: response.value.err.map(|e| e.to_string()),
+                    events: events::parse_logs(&self.program_id, &response.value.logs).events,
+                    logs: response.value.logs,
+                },
+                else => return Err(anyhow!("Subscriptions closed")),
+            };
+            // Nobody listening is not an error; the update is simply dropped
+            let _ = self.updates.send(update);
+        }
+    }
+
+    // ==================== Devnet Faucet ====================
+
+    /// Whether `airdrop` may be used: never on mainnet or testnet.
+    pub fn airdrop_enabled(&self) -> bool {
+        self.faucet_cluster
+    }
+
+    /// Airdrop SOL to `wallet` and, when `token_amount` is given, mint that
+    /// many base units of the faucet test token to its associated account.
+    pub async fn airdrop(&self, wallet: Pubkey, lamports: u64, token_amount: Option<u64>) -> Result<AirdropResult> {
+        if !self.faucet_cluster {
+            return Err(anyhow!("Airdrops are only available on devnet"));
+        }
+
+        let sol_signature = if lamports > 0 {
+            let signature = self.client.request_airdrop(&wallet, lamports.min(MAX_AIRDROP_LAMPORTS))
+                .map_err(|e| anyhow!("Airdrop request failed: {}", e))?;
+            self.client.poll_for_signature(&signature)
+                .map_err(|e| anyhow!("Airdrop was not confirmed: {}", e))?;
+            Some(signature.to_string())
+        } else {
+            None
+        };
+
+        let token_signature = match token_amount {
+            Some(amount) => {
+                let mint = self.faucet_mint.ok_or_else(|| anyhow!("No FAUCET_MINT configured"))?;
+                let token_program = self.client.get_account(&mint)
+                    .map_err(|e| anyhow!("Faucet mint not found: {}", e))?
+                    .owner;
+                let token_account = associated_token_address(&wallet, &mint, &token_program)?;
+
+                // CreateIdempotent, so repeat airdrops reuse the account
+                let create_account = Instruction {
+                    program_id: Pubkey::from_str(ASSOCIATED_TOKEN_PROGRAM_ID)?,
+                    accounts: vec![
+                        solana_sdk::instruction::AccountMeta::new(self.payer().pubkey(), true),
+                        solana_sdk::instruction::AccountMeta::new(token_account, false),
+                        solana_sdk::instruction::AccountMeta::new_readonly(wallet, false),
+                        solana_sdk::instruction::AccountMeta::new_readonly(mint, false),
+                        solana_sdk::instruction::AccountMeta::new_readonly(system_program::id(), false),
+                        solana_sdk::instruction::AccountMeta::new_readonly(token_program, false),
+                    ],
+                    data: vec![1],
+                };
+                // MintTo, with the payer as mint authority
+                let mut data = vec![7];
+                data.extend_from_slice(&amount.to_le_bytes());
+                let mint_to = Instruction {
+                    program_id: token_program,
+                    accounts: vec![
+                        solana_sdk::instruction::AccountMeta::new(mint, false),
+                        solana_sdk::instruction::AccountMeta::new(token_account, false),
+                        solana_sdk::instruction::AccountMeta::new_readonly(self.payer().pubkey(), true),
+                    ],
+                    data,
+                };
+
+                let signature = self.send_transaction(&[create_account, mint_to], &self.payer().pubkey())
+                    .map_err(|e| anyhow!("Minting test tokens failed: {}", e))?;
+                Some(signature.to_string())
+            }
+            None => None,
+        };
+
+        Ok(AirdropResult { sol_signature, token_signature })
+    }
+
+    // ==================== Address Lookup Tables ====================
+
+    fn lookup_table_accounts(&self) -> Result<Vec<AddressLookupTableAccount>> {
+        let tables = self.lookup_tables.read().unwrap().clone();
+        tables.into_iter()
+            .map(|key| {
+                let account = self.client.get_account(&key)
+                    .map_err(|e| anyhow!("Lookup table {} not found: {}", key, e))?;
+                let table = AddressLookupTable::deserialize(&account.data)
+                    .map_err(|e| anyhow!("Account {} is not a lookup table: {}", key, e))?;
+                Ok(AddressLookupTableAccount { key, addresses: table.addresses.to_vec() })
+            })
+            .collect()
+    }
+
+    pub async fn get_lookup_table(&self, address: &Pubkey) -> Result<LookupTableInfo> {
+        let account = self.client.get_account(address)
+            .map_err(|e| anyhow!("Lookup table not found: {}", e))?;
+        let table = AddressLookupTable::deserialize(&account.data)
+            .map_err(|e| anyhow!("Account is not a lookup table: {}", e))?;
+        Ok(LookupTableInfo {
+            address: address.to_string(),
+            authority: table.meta.authority.map(|authority| authority.to_string()),
+            addresses: table.addresses.iter().map(Pubkey::to_string).collect(),
+            in_use: self.lookup_tables.read().unwrap().contains(address),
+        })
+    }
+
+    /// Accounts nearly every protocol transaction touches; the default
+    /// contents of a new lookup table.
+    fn common_addresses(&self) -> Result<Vec<Pubkey>> {
+        Ok(vec![
+            self.config_pda(),
+            self.stats_pda(),
+            self.blacklist_pda(),
+            system_program::id(),
+            Pubkey::from_str(TOKEN_PROGRAM_ID)?,
+            Pubkey::from_str(TOKEN_2022_PROGRAM_ID)?,
+            Pubkey::from_str(ASSOCIATED_TOKEN_PROGRAM_ID)?,
+            Pubkey::from_str(TOKEN_METADATA_PROGRAM_ID)?,
+        ])
+    }
+
+    /// Create a lookup table authorized to the payer holding `addresses`
+    /// (the common protocol accounts when empty) and start compiling
+    /// oversized transactions against it. Tables are usable a slot after
+    /// each extension; add the address to `LOOKUP_TABLES` to keep it.
+    pub async fn create_lookup_table(&self, addresses: Vec<Pubkey>) -> Result<(LookupTableInfo, Vec<String>)> {
+        let addresses = if addresses.is_empty() { self.common_addresses()? } else { addresses };
+        let recent_slot = self.client.get_slot_with_commitment(CommitmentConfig::finalized())
+            .map_err(|e| anyhow!("Failed to get slot: {}", e))?;
+        let (create, table) = address_lookup_table::instruction::create_lookup_table(
+            self.payer().pubkey(),
+            self.payer().pubkey(),
+            recent_slot,
+        );
+        let signature = self.send_transaction(&[create], &self.payer().pubkey())
+            .map_err(|e| anyhow!("Lookup table creation failed: {}", e))?;
+
+        let mut signatures = vec![signature.to_string()];
+        signatures.extend(self.extend_lookup_table(&table, addresses).await?);
+        self.lookup_tables.write().unwrap().push(table);
+        Ok((self.get_lookup_table(&table).await?, signatures))
+    }
+
+    /// Append `addresses` the table doesn't hold yet, in packet-sized batches.
+    pub async fn extend_lookup_table(&self, table: &Pubkey, addresses: Vec<Pubkey>) -> Result<Vec<String>> {
+        let existing = self.client.get_account(table).ok()
+            .and_then(|account| AddressLookupTable::deserialize(&account.data).ok().map(|t| t.addresses.to_vec()))
+            .unwrap_or_default();
+        let mut new_addresses: Vec<Pubkey> = Vec::new();
+        for address in addresses {
+            if !existing.contains(&address) && !new_addresses.contains(&address) {
+                new_addresses.push(address);
+            }
+        }
+
+        new_addresses
+            .chunks(LOOKUP_TABLE_EXTEND_BATCH)
+            .map(|batch| {
+                let extend = address_lookup_table::instruction::extend_lookup_table(
+                    *table,
+                    self.payer().pubkey(),
+                    Some(self.payer().pubkey()),
+                    batch.to_vec(),
+                );
+                self.send_transaction(&[extend], &self.payer().pubkey())
+                    .map(|signature| signature.to_string())
+                    .map_err(|e| anyhow!("Lookup table extension failed: {}", e))
+            })
+            .collect()
+    }
+
+    /// Decode a wallet-signed transaction and check it is fully signed and
+    /// calls this program, so the relay can't be used for anything else.
+    pub fn decode_signed_transaction(&self, encoded: &str) -> Result<VersionedTransaction> {
+        let bytes = BASE64.decode(encoded.trim())
+            .map_err(|e| anyhow!("Transaction is not valid base64: {}", e))?;
+        let transaction: VersionedTransaction = bincode::deserialize(&bytes)
+            .map_err(|e| anyhow!("Transaction could not be decoded: {}", e))?;
+
+        transaction.sanitize()
+            .map_err(|e| anyhow!("Transaction is malformed: {}", e))?;
+        if !transaction.verify_with_results().into_iter().all(|valid| valid) {
+            return Err(anyhow!("Transaction is missing signatures or they are invalid"));
+        }
+        // Invoked programs are always static keys, even in v0 transactions
+        let keys = transaction.message.static_account_keys();
+        let calls_program = transaction.message.instructions().iter().any(|instruction| {
+            keys.get(instruction.program_id_index as usize) == Some(&self.program_id)
+        });
+        if !calls_program {
+            return Err(anyhow!("Transaction does not call the RWA program"));
+        }
+        Ok(transaction)
+    }
+
+    /// Look up a confirmed transaction and decode the program's events and
+    /// the invocations, CPIs included, from its logs.
+    pub async fn get_transaction_status(&self, signature: &Signature) -> Result<TransactionStatus> {
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Json),
+            commitment: Some(self.client.commitment()),
+            max_supported_transaction_version: Some(0),
+        };
+        let transaction = self.client.get_transaction_with_config(signature, config)
+            .map_err(|e| anyhow!("Transaction not found: {}", e))?;
+        let meta = transaction.transaction.meta;
+        let log_messages = meta.as_ref()
+            .and_then(|meta| Option::<Vec<String>>::from(meta.log_messages.clone()))
+            .unwrap_or_default();
+
+        Ok(TransactionStatus {
+            signature: signature.to_string(),
+            slot: transaction.slot,
+            block_time: transaction.block_time,
+            fee: meta.as_ref().map(|meta| meta.fee),
+            error: meta.and_then(|meta| meta.err).map(|e| e.to_string()),
+            logs: events::parse_logs(&self.program_id, &log_messages),
+        })
+    }
+
+    /// Relay a transaction already signed by its wallet.
+    pub async fn relay_transaction(&self, transaction: &VersionedTransaction) -> Result<String> {
+        let signature = self.send_and_confirm(transaction)
+            .map_err(|e| anyhow!("Transaction failed: {}", e))?;
+        Ok(signature.to_string())
+    }
+
+    // Delegate and transfer instructions take the asset and its owner (the payer)
+    // as signer, followed by any instruction-specific accounts
+    fn send_owner_instruction(
+        &self,
+        asset_id: &str,
+        data: Vec<u8>,
+        extra_accounts: Vec<solana_sdk::instruction::AccountMeta>,
+    ) -> Result<String> {
+        let (asset_pda, _) = self.asset_address(asset_id);
+
+        let mut accounts = vec![
+            solana_sdk::instruction::AccountMeta::new(asset_pda, false),
+            solana_sdk::instruction::AccountMeta::new_readonly(self.payer().pubkey(), true),
+        ];
+        accounts.extend(extra_accounts);
+
+        let instruction = Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        };
+
+        let signature = self.send_transaction(&[instruction], &self.payer().pubkey())?;
+
+        Ok(signature.to_string())
+    }
+
+    pub async fn reactivate_asset(&self, asset_id: &str) -> Result<String> {
+        let (asset_pda, _) = self.asset_address(asset_id);
+
+        let (config_pda, _) = Pubkey::find_program_address(
+            &[b"config"],
+            &self.program_id,
+        );
+
+        let accounts = vec![
+            solana_sdk::instruction::AccountMeta::new(asset_pda, false),
+            solana_sdk::instruction::AccountMeta::new_readonly(config_pda, false),
+            solana_sdk::instruction::AccountMeta::new_readonly(self.payer().pubkey(), true),
+        ];
+
+        let instruction = Instruction {
+            program_id: self.program_id,
+            accounts,
+            data: self.idl.instruction_data("reactivate_asset", &[])?,
+        };
+
+        let signature = self.send_transaction(&[instruction], &self.payer().pubkey())
+            .map_err(|e| anyhow!("Reactivation failed: {}", e))?;
+
+        Ok(signature.to_string())
+    }
+
+    pub async fn request_loan(
+        &self,
+        asset_id: &str,
+        borrower: Pubkey,
+        terms: LoanTerms,
+        repayment_mint: Pubkey,
+    ) -> Result<CreateLoanResult> {
+        let (loan_pda, _, instructions) = self.request_loan_instructions(asset_id, borrower, &terms, repayment_mint).await?;
+        tracing::info!("Loan PDA: {}", loan_pda);
+        self.check_lending(&self.asset_address(asset_id).0, terms.loan_amount, Some(terms.duration))?;
+
+        let transaction = self.submit_for(&instructions, &borrower)
+            .map_err(|e| anyhow!("Loan request failed: {}", e))?;
+
+        Ok(CreateLoanResult {
+            loan_pda: loan_pda.to_string(),
+            transaction,
+        })
+    }
+
+    /// Limits the program would apply to the request and whether it would
+    /// accept it, simulated without signatures so nobody signs anything yet.
+    pub async fn preview_loan(
+        &self,
+        asset_id: &str,
+        borrower: Pubkey,
+        terms: LoanTerms,
+        repayment_mint: Pubkey,
+    ) -> Result<LoanPreview> {
+        let (loan_pda, asset, instructions) = self.request_loan_instructions(asset_id, borrower, &terms, repayment_mint).await?;
+
+        let type_config = self.asset_type_config(&asset.asset_type)?;
+        let rules = self.jurisdiction_rules(asset.jurisdiction.as_deref().map(str::as_bytes))?;
+        let (collateral_value, max_ltv_percent, max_loan) =
+            loan_limits(asset.valuation, asset.risk_score, &type_config, rules.as_ref());
+
+        Ok(LoanPreview {
+            loan_pda: loan_pda.to_string(),
+            collateral_value,
+            max_ltv_percent,
+            max_loan,
+            simulation: self.simulate_instruction(&instructions, &borrower)?,
+        })
+    }
+
+    /// Run instructions through `simulateTransaction` without signatures and
+    /// against the latest blockhash, reporting compute use and any failure.
+    pub fn simulate_instruction(&self, instructions: &[Instruction], fee_payer: &Pubkey) -> Result<SimulationOutcome> {
+        let transaction = self.build_transaction(instructions, fee_payer, None)?;
+        let config = RpcSimulateTransactionConfig {
+            sig_verify: false,
+            replace_recent_blockhash: true,
+            commitment: Some(self.client.commitment()),
+            ..Default::default()
+        };
+        let result = self.client.simulate_transaction_with_config(&transaction, config)
+            .map_err(|e| anyhow!("Simulation failed: {}", e))?
+            .value;
+
+        let error = result.err.map(|e| e.to_string());
+        Ok(SimulationOutcome {
+            accepted: error.is_none(),
+            units_consumed: result.units_consumed,
+            program_error: error.as_deref().and_then(|e| self.idl.decode_error(e)),
+            error,
+            logs: result.logs.unwrap_or_default(),
+        })
+    }
+
+    /// The loan PDA, the asset and the request_loan instructions (plus the
+    /// NFT lock) for a borrower's request.
+    async fn request_loan_instructions(
+        &self,
+        asset_id: &str,
+        borrower: Pubkey,
+        terms: &LoanTerms,
+        repayment_mint: Pubkey,
+    ) -> Result<(Pubkey, AssetResponse, Vec<Instruction>)> {
+        let (asset_pda, _) = self.asset_address(asset_id);
+
+        let (loan_pda, _) = self.loan_address(&asset_pda, &borrower);
+
+        // Lending rules are keyed by the asset's type
+        let asset = self.get_asset(asset_id).await?;
+        let (asset_type_config_pda, _) = Pubkey::find_program_address(
+            &[b"asset_type", asset.asset_type.as_bytes()],
+            &self.program_id,
+        );
+
+        // Loans need an unexpired custody attestation from a registered custodian
+        let (custody_pda, _) = Pubkey::find_program_address(
+            &[b"custody", asset_pda.as_ref()],
+            &self.program_id,
+        );
+        let custody = self.client.get_account(&custody_pda)
+            .map_err(|e| anyhow!("Asset has no custody attestation: {}", e))?;
+        let custodian = CustodyAttestationAccount::from_bytes(&custody.data)?.custodian;
+        let (custodian_pda, _) = Pubkey::find_program_address(
+            &[b"custodian", custodian.as_ref()],
+            &self.program_id,
+        );
+
+        let instruction_data = self.idl.instruction_data("request_loan", &[
+            json!(terms.loan_amount),
+            json!(terms.interest_rate),
+            json!(terms.duration),
+            json!(terms.interest_mode),
+            json!(terms.product),
+            json!(terms.floating_rate.map(FloatingRateTerms::from)),
+        ])?;
+
+        let accounts = vec![
+            solana_sdk::instruction::AccountMeta::new(loan_pda, false),
+            solana_sdk::instruction::AccountMeta::new(asset_pda, false),
+            solana_sdk::instruction::AccountMeta::new_readonly(asset_type_config_pda, false),
+            solana_sdk::instruction::AccountMeta::new_readonly(repayment_mint, false),
+            solana_sdk::instruction::AccountMeta::new(borrower, true),
+            solana_sdk::instruction::AccountMeta::new_readonly(system_program::id(), false),
+            solana_sdk::instruction::AccountMeta::new_readonly(self.blacklist_pda(), false),
+            solana_sdk::instruction::AccountMeta::new_readonly(custody_pda, false),
+            solana_sdk::instruction::AccountMeta::new_readonly(custodian_pda, false),
+            // No referral agent
+            solana_sdk::instruction::AccountMeta::new_readonly(self.program_id, false),
+            solana_sdk::instruction::AccountMeta::new_readonly(self.config_pda(), false),
+            self.jurisdiction_account(&asset),
+        ];
+
+        let mut instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data: instruction_data,
+        }];
+        // Lock the asset NFT now that a loan is open against it
+        instructions.extend(self.sync_asset_nft_instruction(asset_pda)?);
+
+        Ok((loan_pda, asset, instructions))
+    }
+
+    // ==================== Pre-send Validation ====================
+
+    /// The program's `name` error for `reason`, as a `Rejection`.
+    fn reject(&self, name: &str, reason: String) -> anyhow::Error {
+        match self.idl.error_named(name) {
+            Ok(error) => Rejection { error, reason }.into(),
+            Err(_) => anyhow!(reason),
+        }
+    }
+
+    /// The protocol's `max_risk_age`; configs created before it existed
+    /// don't check risk age.
+    fn max_risk_age(&self) -> Result<i64> {
+        let config = self.client.get_account(&self.config_pda())
+            .map_err(|e| anyhow!("Config not found: {}", e))?;
+        Ok(config.data
+            .get(CONFIG_MAX_RISK_AGE_OFFSET..CONFIG_MAX_RISK_AGE_OFFSET + 8)
+            .map_or(0, |bytes| i64::from_le_bytes(bytes.try_into().unwrap())))
+    }
+
+    fn asset_type_config(&self, asset_type: &str) -> Result<AssetTypeConfigAccount> {
+        let (asset_type_config_pda, _) = Pubkey::find_program_address(
+            &[b"asset_type", asset_type.as_bytes()],
+            &self.program_id,
+        );
+        self.client.get_account(&asset_type_config_pda)
+            .map_err(|e| anyhow!("No lending rules for asset type {}: {}", asset_type, e))
+            .and_then(|account| AssetTypeConfigAccount::from_bytes(&account.data))
+    }
+
+    /// Rules of the jurisdiction an asset is placed in, if it has any.
+    fn jurisdiction_rules(&self, jurisdiction: Option<&[u8]>) -> Result<Option<JurisdictionConfigAccount>> {
+        let Some(code) = jurisdiction else { return Ok(None) };
+        let (jurisdiction_pda, _) = Pubkey::find_program_address(&[b"jurisdiction", code], &self.program_id);
+        self.client.get_account(&jurisdiction_pda).ok()
+            .map(|account| JurisdictionConfigAccount::from_bytes(&account.data))
+            .transpose()
+    }
+
+    /// Check lending `loan_amount` against the asset at `asset_pda` the way
+    /// the program will, so a doomed request fails here with the reason
+    /// instead of costing a fee. Both requests and approvals face the
+    /// jurisdiction's LTV cap; new requests, which pass their `duration`, also
+    /// face the type's duration cap, the jurisdiction's asset types and the
+    /// custody attestation.
+    fn check_lending(&self, asset_pda: &Pubkey, loan_amount: u64, duration: Option<i64>) -> Result<()> {
+        let asset = self.cached_account(asset_pda)
+            .map_err(|e| anyhow!("Asset not found: {}", e))
+            .and_then(|account| AssetAccount::from_bytes(&account.data))?;
+        if !asset.is_active {
+            return Err(self.reject("AssetInactive", format!("Asset {} is inactive", asset.asset_id)));
+        }
+
+        let now = chrono::Utc::now().timestamp();
+        if now < asset.risk_frozen_until {
+            return Err(self.reject("AssetRiskFrozen", format!(
+                "Risk score of {} is frozen for another {}s", asset.asset_id, asset.risk_frozen_until - now,
+            )));
+        }
+        let max_risk_age = self.max_risk_age()?;
+        let risk_age = now - asset.last_risk_update;
+        if max_risk_age > 0 && risk_age > max_risk_age {
+            return Err(self.reject("StaleRiskScore", format!(
+                "Risk score of {} is {}s old; loans need one at most {}s old", asset.asset_id, risk_age, max_risk_age,
+            )));
+        }
+
+        let type_config = self.asset_type_config(&asset.asset_type)?;
+        let placed = (asset.jurisdiction != [0; 2]).then_some(&asset.jurisdiction[..]);
+        let rules = self.jurisdiction_rules(placed)?;
+        if let Some(duration) = duration {
+            if asset.portfolio != Pubkey::default() {
+                return Err(self.reject("AssetBundled", format!(
+                    "Asset {} is bundled into portfolio {}", asset.asset_id, asset.portfolio,
+                )));
+            }
+            if duration <= 0 || duration > type_config.max_duration {
+                return Err(self.reject("InvalidLoanDuration", format!(
+                    "Duration of {}s is outside 1..={}s for {} assets", duration, type_config.max_duration, asset.asset_type,
+                )));
+            }
+            if let Some(rules) = rules.as_ref().filter(|rules| {
+                !rules.allowed_asset_types.is_empty() && !rules.allowed_asset_types.contains(&asset.asset_type)
+            }) {
+                return Err(self.reject("AssetTypeNotAllowedInJurisdiction", format!(
+                    "{} assets are not allowed in {}", asset.asset_type, String::from_utf8_lossy(&rules.jurisdiction),
+                )));
+            }
+
+            let (custody_pda, _) = Pubkey::find_program_address(&[b"custody", asset_pda.as_ref()], &self.program_id);
+            let custody = self.cached_account(&custody_pda)
+                .map_err(|e| anyhow!("Asset has no custody attestation: {}", e))
+                .and_then(|account| CustodyAttestationAccount::from_bytes(&account.data))?;
+            if custody.expires_at <= now {
+                return Err(self.reject("CustodyAttestationExpired", format!(
+                    "Custody attestation of {} expired {}s ago", asset.asset_id, now - custody.expires_at,
+                )));
+            }
+        }
+
+        let (collateral_value, max_ltv, max_loan) =
+            loan_limits(asset.valuation, asset.risk_score, &type_config, rules.as_ref());
+        if loan_amount > max_loan {
+            return Err(self.reject("LoanTooHigh", format!(
+                "Loan of {} exceeds the cap of {}: {}% LTV on {} of collateral after haircut",
+                loan_amount, max_loan, max_ltv, collateral_value,
+            )));
+        }
+        Ok(())
+    }
+
+    /// Approve a pending loan with the backend payer as lender, disbursing the
+    /// loan's repayment mint from the payer's associated token account.
+    pub async fn approve_loan(&self, loan_pda: Pubkey) -> Result<String> {
+        let instruction = self.approve_loan_instruction(loan_pda)?;
+        let signature = self.send_transaction(&[instruction], &self.payer().pubkey())
+            .map_err(|e| anyhow!("Loan approval failed: {}", e))?;
+
+        Ok(signature.to_string())
+    }
+
+    fn approve_loan_instruction(&self, loan_pda: Pubkey) -> Result<Instruction> {
+        let account = self.cached_account(&loan_pda)
+            .map_err(|e| anyhow!("Loan not found: {}", e))?;
+        let loan = LoanAccount::from_bytes(&account.data)?;
+
+        if loan.status != LoanStatus::Pending {
+            return Err(self.reject("LoanNotPending", format!("Loan {} is {}, not pending", loan_pda, loan.status.as_str())));
+        }
+        let now = chrono::Utc::now().timestamp();
+        if now > loan.request_expiry {
+            return Err(self.reject("LoanRequestExpired", format!("Loan request {} expired {}s ago", loan_pda, now - loan.request_expiry)));
+        }
+        // The collateral may have been re-scored since the request
+        self.check_lending(&loan.asset, loan.principal, None)?;
+
+        let asset_account = self.cached_account(&loan.asset)
+            .map_err(|e| anyhow!("Asset not found: {}", e))?;
+        let asset = AssetAccount::from_bytes(&asset_account.data)?;
+        let (asset_type_config_pda, _) = Pubkey::find_program_address(
+            &[b"asset_type", asset.asset_type.as_bytes()],
+            &self.program_id,
+        );
+
+        let token_program = self.repayment_token_program(&loan.repayment_mint)?;
+        let lender_token_account = associated_token_address(&self.payer().pubkey(), &loan.repayment_mint, &token_program)?;
+        let borrower_token_account = associated_token_address(&loan.borrower, &loan.repayment_mint, &token_program)?;
+
+        let mut accounts = vec![
+            solana_sdk::instruction::AccountMeta::new(loan_pda, false),
+            solana_sdk::instruction::AccountMeta::new_readonly(loan.asset, false),
+            solana_sdk::instruction::AccountMeta::new_readonly(asset_type_config_pda, false),
+            solana_sdk::instruction::AccountMeta::new_readonly(loan.repayment_mint, false),
+            solana_sdk::instruction::AccountMeta::new(lender_token_account, false),
+            solana_sdk::instruction::AccountMeta::new(borrower_token_account, false),
+            solana_sdk::instruction::AccountMeta::new_readonly(self.payer().pubkey(), true),
+            solana_sdk::instruction::AccountMeta::new_readonly(token_program, false),
+            solana_sdk::instruction::AccountMeta::new_readonly(self.blacklist_pda(), false),
+            solana_sdk::instruction::AccountMeta::new(self.stats_pda(), false),
+            solana_sdk::instruction::AccountMeta::new_readonly(self.config_pda(), false),
+            self.lender_membership_account(&self.payer().pubkey()),
+        ];
+        accounts.extend(self.origination_fee_accounts(&loan)?);
+        accounts.push(self.jurisdiction_account(&AssetResponse::from(asset)));
+
+        Ok(Instruction {
+            program_id: self.program_id,
+            accounts,
+            data: self.idl.instruction_data("approve_loan", &[])?,
+        })
+    }
+
+    pub async fn cancel_loan_request(&self, loan_pda: Pubkey) -> Result<String> {
+        let account = self.cached_account(&loan_pda)
+            .map_err(|e| anyhow!("Loan not found: {}", e))?;
+        let loan = LoanAccount::from_bytes(&account.data)?;
+
+        let accounts = vec![
+            solana_sdk::instruction::AccountMeta::new(loan_pda, false),
+            solana_sdk::instruction::AccountMeta::new(loan.asset, false),
+            solana_sdk::instruction::AccountMeta::new(loan.borrower, false),
+            solana_sdk::instruction::AccountMeta::new_readonly(self.payer().pubkey(), true),
+        ];
+
+        let mut instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data: self.idl.instruction_data("cancel_loan_request", &[])?,
+        }];
+        // Unlock the asset NFT if this was its last open loan
+        instructions.extend(self.sync_asset_nft_instruction(loan.asset)?);
+
+        let signature = self.send_transaction(&instructions, &self.payer().pubkey())
+            .map_err(|e| anyhow!("Loan cancellation failed: {}", e))?;
+
+        Ok(signature.to_string())
+    }
+
+    /// Repay a loan in full from the borrower's associated token account.
+    /// The lender is paid into its associated token account and any reserve
+    /// share goes to the protocol revenue vault for the repayment mint.
+    pub async fn repay_loan(&self, loan_pda: Pubkey) -> Result<Submission> {
+        let (borrower, instructions) = self.repay_loan_instructions(loan_pda)?;
+        self.submit_for(&instructions, &borrower)
+            .map_err(|e| anyhow!("Loan repayment failed: {}", e))
+    }
+
+    /// The borrower, who signs, and the repay_loan instructions (plus the NFT unlock).
+    fn repay_loan_instructions(&self, loan_pda: Pubkey) -> Result<(Pubkey, Vec<Instruction>)> {
+        let account = self.cached_account(&loan_pda)
+            .map_err(|e| anyhow!("Loan not found: {}", e))?;
+        let loan = LoanAccount::from_bytes(&account.data)?;
+
+        let token_program = self.repayment_token_program(&loan.repayment_mint)?;
+        let borrower_token_account = associated_token_address(&loan.borrower, &loan.repayment_mint, &token_program)?;
+        let lender_token_account = associated_token_address(&loan.lender, &loan.repayment_mint, &token_program)?;
+
+        let mut accounts = vec![
+            solana_sdk::instruction::AccountMeta::new(loan_pda, false),
+            solana_sdk::instruction::AccountMeta::new(loan.asset, false),
+            solana_sdk::instruction::AccountMeta::new(loan.borrower, true),
+            solana_sdk::instruction::AccountMeta::new_readonly(loan.repayment_mint, false),
+            solana_sdk::instruction::AccountMeta::new(borrower_token_account, false),
+            solana_sdk::instruction::AccountMeta::new(lender_token_account, false),
+            solana_sdk::instruction::AccountMeta::new_readonly(token_program, false),
+            solana_sdk::instruction::AccountMeta::new_readonly(self.config_pda(), false),
+        ];
+        accounts.extend(self.revenue_accounts(&loan.repayment_mint)?);
+        accounts.push(solana_sdk::instruction::AccountMeta::new(self.stats_pda(), false));
+
+        let mut instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data: self.idl.instruction_data("repay_loan", &[])?,
+        }];
+        // Unlock the asset NFT if this was its last open loan
+        instructions.extend(self.sync_asset_nft_instruction(loan.asset)?);
+
+        Ok((loan.borrower, instructions))
+    }
+
+    /// Liquidate an open loan with the backend payer as liquidator. The
+    /// program rejects loans that are not past due or under-collateralised.
+    pub async fn liquidate_loan(&self, loan_pda: Pubkey) -> Result<String> {
+        let account = self.cached_account(&loan_pda)
+            .map_err(|e| anyhow!("Loan not found: {}", e))?;
+        let loan = LoanAccount::from_bytes(&account.data)?;
+
+        let supplemental_asset = if loan.supplemental_asset == Pubkey::default() {
+            solana_sdk::instruction::AccountMeta::new_readonly(self.program_id, false)
+        } else {
+            solana_sdk::instruction::AccountMeta::new_readonly(loan.supplemental_asset, false)
+        };
+        let accounts = vec![
+            solana_sdk::instruction::AccountMeta::new(loan_pda, false),
+            solana_sdk::instruction::AccountMeta::new(loan.asset, false),
+            supplemental_asset,
+            solana_sdk::instruction::AccountMeta::new_readonly(self.payer().pubkey(), true),
+            solana_sdk::instruction::AccountMeta::new(self.stats_pda(), false),
+        ];
+
+        let mut instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data: self.idl.instruction_data("liquidate_loan", &[])?,
+        }];
+        // Liquidation closes the loan, so the asset NFT may unlock too
+        instructions.extend(self.sync_asset_nft_instruction(loan.asset)?);
+
+        let signature = self.send_bundled(&instructions).await
+            .map_err(|e| anyhow!("Loan liquidation failed: {}", e))?;
+
+        Ok(signature.to_string())
+    }
+
+    pub async fn get_loan(&self, loan_pda: Pubkey, commitment: Option<CommitmentConfig>) -> Result<LoanResponse> {
+        tracing::info!("Fetching loan from PDA: {}", loan_pda);
+
+        let account = self.account_at(&loan_pda, commitment)
             .map_err(|e| anyhow!("Loan not found: {}", e))?;
         
         let loan_account = LoanAccount::from_bytes(&account.data)?;
         
-        Ok(LoanResponse {
-            borrower: loan_account.borrower.to_string(),
-            asset: loan_account.asset.to_string(),
-            principal: loan_account.principal,
-            interest_rate: loan_account.interest_rate,
-            start_time: loan_account.start_time,
-            end_time: loan_account.end_time,
-            is_active: loan_account.is_active,
-            liquidated: loan_account.liquidated,
-            repaid: loan_account.repaid,
-            risk_score_at_creation: loan_account.risk_score_at_creation,
+        Ok(LoanResponse::from(loan_account))
+    }
+
+    /// What repaying `loan_pda` in full costs at `at` (default now), as
+    /// `repay_loan` would compute it.
+    pub async fn payoff_quote(&self, loan_pda: Pubkey, at: Option<i64>) -> Result<PayoffQuote> {
+        let account = self.account_at(&loan_pda, None)
+            .map_err(|e| anyhow!("Loan not found: {}", e))?;
+        let loan = LoanAccount::from_bytes(&account.data)?;
+
+        if !loan.status.is_open() {
+            return Err(self.reject("LoanInactive", format!("Loan {} is {}, nothing is owed", loan_pda, loan.status.as_str())));
+        }
+        let quoted_at = at.unwrap_or_else(|| chrono::Utc::now().timestamp());
+        let expires_at = quoted_at + PAYOFF_QUOTE_TTL_SECS;
+        let overflow = || self.reject("MathOverflow", format!("Debt on loan {} overflows a u64", loan_pda));
+        let (principal, interest, late_fee) = payoff_breakdown(&loan, quoted_at).ok_or_else(overflow)?;
+        let (_, interest_at_expiry, late_fee_at_expiry) = payoff_breakdown(&loan, expires_at).ok_or_else(overflow)?;
+        let amount = principal + interest + late_fee;
+
+        Ok(PayoffQuote {
+            loan: loan_pda.to_string(),
+            repayment_mint: loan.repayment_mint.to_string(),
+            quoted_at,
+            amount,
+            principal,
+            interest,
+            late_fee,
+            protocol_share: reserve_share(amount - principal, self.reserve_factor_bps()?),
+            expires_at,
+            amount_at_expiry: principal + interest_at_expiry + late_fee_at_expiry,
+            floating: loan.floating_rate.is_some(),
+        })
+    }
+
+    pub async fn get_repayment_schedule(&self, loan_pda: Pubkey, commitment: Option<CommitmentConfig>) -> Result<ScheduleResponse> {
+        let (schedule_pda, _) = Pubkey::find_program_address(
+            &[b"schedule", loan_pda.as_ref()],
+            &self.program_id,
+        );
+
+        tracing::info!("Fetching repayment schedule from PDA: {}", schedule_pda);
+
+        let account = self.client.get_account_with_commitment(&schedule_pda, commitment.unwrap_or(self.client.commitment()))?
+            .value
+            .ok_or_else(|| anyhow!("Schedule not found: {}", schedule_pda))?;
+        
+        let schedule = ScheduleAccount::from_bytes(&account.data)?;
+        
+        Ok(ScheduleResponse {
+            loan: schedule.loan.to_string(),
+            interval: schedule.interval,
+            installments: schedule.installments,
+            paid_count: schedule.paid_count,
+            missed_count: schedule.missed_count,
+            late_payments: schedule.late_payments,
+            delinquent: schedule.delinquent,
+        })
+    }
+
+    /// Fetch every Loan account owned by the program.
+    pub async fn list_loans(&self) -> Result<Vec<(Pubkey, LoanAccount)>> {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                0,
+                LoanAccount::discriminator()?.to_vec(),
+            ))]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let accounts = self.client.get_program_accounts_with_config(&self.program_id, config)
+            .map_err(|e| anyhow!("Failed to fetch loans: {}", e))?;
+
+        accounts
+            .into_iter()
+            .map(|(pubkey, account)| Ok((pubkey, LoanAccount::from_bytes(&account.data)?)))
+            .collect()
+    }
+
+    /// Mint the asset's NFT to the owner's associated token account.
+    fn mint_asset_nft_instruction(&self, asset_pda: Pubkey, owner: Pubkey) -> Result<Instruction> {
+        let token_program = Pubkey::from_str(TOKEN_PROGRAM_ID)?;
+        let (nft_mint, metadata) = self.asset_nft_addresses(&asset_pda)?;
+
+        let accounts = vec![
+            solana_sdk::instruction::AccountMeta::new_readonly(asset_pda, false),
+            solana_sdk::instruction::AccountMeta::new(nft_mint, false),
+            solana_sdk::instruction::AccountMeta::new(associated_token_address(&owner, &nft_mint, &token_program)?, false),
+            solana_sdk::instruction::AccountMeta::new(metadata, false),
+            solana_sdk::instruction::AccountMeta::new(owner, true),
+            solana_sdk::instruction::AccountMeta::new_readonly(token_program, false),
+            solana_sdk::instruction::AccountMeta::new_readonly(Pubkey::from_str(ASSOCIATED_TOKEN_PROGRAM_ID)?, false),
+            solana_sdk::instruction::AccountMeta::new_readonly(Pubkey::from_str(TOKEN_METADATA_PROGRAM_ID)?, false),
+            solana_sdk::instruction::AccountMeta::new_readonly(system_program::id(), false),
+        ];
+
+        Ok(Instruction {
+            program_id: self.program_id,
+            accounts,
+            data: self.idl.instruction_data("mint_asset_nft", &[])?,
         })
     }
 
-    pub fn get_payer_pubkey(&self) -> Pubkey {
-        self.payer.pubkey()
+    /// Freeze or thaw the asset's NFT to match its open loans, wherever it is
+    /// held now. None for assets created before NFTs were minted.
+    fn sync_asset_nft_instruction(&self, asset_pda: Pubkey) -> Result<Option<Instruction>> {
+        let (nft_mint, metadata) = self.asset_nft_addresses(&asset_pda)?;
+        if self.client.get_account(&nft_mint).is_err() {
+            return Ok(None);
+        }
+
+        let holder = self.client.get_token_largest_accounts(&nft_mint)
+            .map_err(|e| anyhow!("Failed to find NFT holder: {}", e))?
+            .into_iter()
+            .find(|balance| balance.amount.amount == "1")
+            .ok_or_else(|| anyhow!("Asset NFT has no holder"))?;
+
+        let accounts = vec![
+            solana_sdk::instruction::AccountMeta::new_readonly(asset_pda, false),
+            solana_sdk::instruction::AccountMeta::new_readonly(nft_mint, false),
+            solana_sdk::instruction::AccountMeta::new(Pubkey::from_str(&holder.address)?, false),
+            solana_sdk::instruction::AccountMeta::new(metadata, false),
+            solana_sdk::instruction::AccountMeta::new_readonly(Pubkey::from_str(TOKEN_PROGRAM_ID)?, false),
+            solana_sdk::instruction::AccountMeta::new_readonly(Pubkey::from_str(TOKEN_METADATA_PROGRAM_ID)?, false),
+        ];
+
+        Ok(Some(Instruction {
+            program_id: self.program_id,
+            accounts,
+            data: self.idl.instruction_data("sync_asset_nft", &[])?,
+        }))
+    }
+
+    // NFT mint PDA and its Token Metadata account
+    fn asset_nft_addresses(&self, asset_pda: &Pubkey) -> Result<(Pubkey, Pubkey)> {
+        let metadata_program = Pubkey::from_str(TOKEN_METADATA_PROGRAM_ID)?;
+        let (nft_mint, _) = Pubkey::find_program_address(
+            &[b"asset_nft", asset_pda.as_ref()],
+            &self.program_id,
+        );
+        let (metadata, _) = Pubkey::find_program_address(
+            &[b"metadata", metadata_program.as_ref(), nft_mint.as_ref()],
+            &metadata_program,
+        );
+        Ok((nft_mint, metadata))
+    }
+
+    /// Addresses barred from borrowing and lending.
+    pub async fn get_blacklist(&self) -> Result<Vec<Pubkey>> {
+        let account = self.client.get_account(&self.blacklist_pda())
+            .map_err(|e| anyhow!("Blacklist not found: {}", e))?;
+        BlacklistAccount::from_bytes(&account.data).map(|blacklist| blacklist.entries)
+    }
+
+    pub async fn add_to_blacklist(&self, wallet: Pubkey) -> Result<String> {
+        let data = self.idl.instruction_data("add_to_blacklist", &[json!(wallet.to_string())])?;
+        self.send_blacklist_instruction(data)
+            .map_err(|e| anyhow!("Blacklisting failed: {}", e))
+    }
+
+    pub async fn remove_from_blacklist(&self, wallet: Pubkey) -> Result<String> {
+        let data = self.idl.instruction_data("remove_from_blacklist", &[json!(wallet.to_string())])?;
+        self.send_blacklist_instruction(data)
+            .map_err(|e| anyhow!("Removing from blacklist failed: {}", e))
+    }
+
+    // Both blacklist instructions are signed by the compliance authority (the payer)
+    fn send_blacklist_instruction(&self, data: Vec<u8>) -> Result<String> {
+        let (config_pda, _) = Pubkey::find_program_address(
+            &[b"config"],
+            &self.program_id,
+        );
+
+        let accounts = vec![
+            solana_sdk::instruction::AccountMeta::new(self.blacklist_pda(), false),
+            solana_sdk::instruction::AccountMeta::new_readonly(config_pda, false),
+            solana_sdk::instruction::AccountMeta::new_readonly(self.payer().pubkey(), true),
+        ];
+
+        let instruction = Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        };
+
+        let signature = self.send_transaction(&[instruction], &self.payer().pubkey())?;
+
+        Ok(signature.to_string())
+    }
+
+    /// The oracle the config appoints to post risk reports.
+    pub async fn risk_oracle(&self) -> Result<Pubkey> {
+        let config = self.client.get_account(&self.config_pda())
+            .map_err(|e| anyhow!("Config not found: {}", e))?;
+        let bytes = config.data
+            .get(CONFIG_RISK_ORACLE_OFFSET..CONFIG_RISK_ORACLE_OFFSET + 32)
+            .ok_or_else(|| anyhow!("Malformed ProtocolConfig account: too short for risk_oracle"))?;
+        Ok(Pubkey::try_from(bytes)?)
+    }
+
+    /// Appoint `oracle` to post risk reports; the payer must be the config admin.
+    pub async fn set_risk_oracle(&self, oracle: Pubkey) -> Result<String> {
+        let accounts = vec![
+            solana_sdk::instruction::AccountMeta::new(self.config_pda(), false),
+            solana_sdk::instruction::AccountMeta::new_readonly(self.payer().pubkey(), true),
+        ];
+        let instruction = Instruction {
+            program_id: self.program_id,
+            accounts,
+            data: self.idl.instruction_data("set_risk_oracle", &[json!(oracle.to_string())])?,
+        };
+
+        let signature = self.send_transaction(&[instruction], &self.payer().pubkey())
+            .map_err(|e| anyhow!("Appointing risk oracle failed: {}", e))?;
+        Ok(signature.to_string())
+    }
+
+    /// Every registered oracle bond.
+    pub async fn list_oracle_bonds(&self) -> Result<Vec<OracleBond>> {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                0,
+                OracleBondAccount::discriminator()?.to_vec(),
+            ))]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let accounts = self.client.get_program_accounts_with_config(&self.program_id, config)
+            .map_err(|e| anyhow!("Failed to fetch oracle bonds: {}", e))?;
+
+        accounts
+            .into_iter()
+            .map(|(_, account)| {
+                let bond = OracleBondAccount::from_bytes(&account.data)?;
+                Ok(OracleBond {
+                    oracle: bond.oracle.to_string(),
+                    bonded: bond.bonded,
+                    open_disputes: bond.open_disputes,
+                    last_report_at: bond.last_report_at,
+                    slashed: bond.slashed,
+                })
+            })
+            .collect()
+    }
+
+    fn blacklist_pda(&self) -> Pubkey {
+        Pubkey::find_program_address(&[b"blacklist"], &self.program_id).0
+    }
+
+    fn oracle_bond_pda(&self, oracle: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"oracle_bond", oracle.as_ref()], &self.program_id).0
+    }
+
+    /// Asset PDA and bump for `asset_id`.
+    pub fn asset_address(&self, asset_id: &str) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"asset", asset_id.as_bytes()], &self.program_id)
+    }
+
+    /// Loan PDA and bump for `borrower`'s loan against the asset at `asset_pda`.
+    pub fn loan_address(&self, asset_pda: &Pubkey, borrower: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"loan", asset_pda.as_ref(), borrower.as_ref()], &self.program_id)
+    }
+
+    // Read by score updates for the circuit breaker settings
+    fn config_pda(&self) -> Pubkey {
+        Pubkey::find_program_address(&[b"config"], &self.program_id).0
+    }
+
+    fn stats_pda(&self) -> Pubkey {
+        Pubkey::find_program_address(&[b"stats"], &self.program_id).0
+    }
+
+    /// Protocol-wide counters in a single account fetch, or None until the
+    /// admin has initialized the stats account.
+    pub async fn protocol_stats(&self) -> Result<Option<ProtocolStats>> {
+        let account = self.client
+            .get_account_with_commitment(&self.stats_pda(), self.client.commitment())
+            .map_err(|e| anyhow!("Failed to fetch protocol stats: {}", e))?
+            .value;
+        account.map(|account| ProtocolStats::from_bytes(&account.data)).transpose()
+    }
+
+    /// Share of interest the protocol takes, in basis points.
+    fn reserve_factor_bps(&self) -> Result<u16> {
+        let config = self.client.get_account(&self.config_pda())
+            .map_err(|e| anyhow!("Config not found: {}", e))?;
+        // Configs created before the reserve factor existed behave as 0
+        Ok(config.data
+            .get(CONFIG_RESERVE_FACTOR_OFFSET..CONFIG_RESERVE_FACTOR_OFFSET + 2)
+            .map_or(0, |bytes| u16::from_le_bytes([bytes[0], bytes[1]])))
+    }
+
+    /// Reserve factor and the protocol revenue collected in each repayment mint.
+    pub async fn revenue_stats(&self) -> Result<RevenueStats> {
+        let reserve_factor_bps = self.reserve_factor_bps()?;
+
+        let filter = RpcProgramAccountsConfig {
+            filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                0,
+                ProtocolRevenueAccount::discriminator()?.to_vec(),
+            ))]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let accounts = self.client.get_program_accounts_with_config(&self.program_id, filter)
+            .map_err(|e| anyhow!("Failed to fetch revenue accounts: {}", e))?;
+
+        let mints = accounts
+            .into_iter()
+            .map(|(_, account)| {
+                let revenue = ProtocolRevenueAccount::from_bytes(&account.data)?;
+                Ok(MintRevenue {
+                    mint: revenue.mint.to_string(),
+                    vault: revenue.vault.to_string(),
+                    accrued: revenue.accrued,
+                    claimed: revenue.claimed,
+                    unclaimed: revenue.accrued.saturating_sub(revenue.claimed),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(RevenueStats { reserve_factor_bps, mints })
+    }
+
+    /// Current base fee per signature and the median priority fee (micro-lamports
+    /// per compute unit) recently paid by transactions touching this program.
+    pub async fn fee_conditions(&self) -> Result<(u64, u64)> {
+        let recent_blockhash = self.client.get_latest_blockhash()
+            .map_err(|e| anyhow!("Failed to get blockhash: {}", e))?;
+        let message = Message::new_with_blockhash(
+            &[system_instruction::transfer(&self.payer().pubkey(), &self.payer().pubkey(), 0)],
+            Some(&self.payer().pubkey()),
+            &recent_blockhash,
+        );
+        let lamports_per_signature = self.client.get_fee_for_message(&message)
+            .map_err(|e| anyhow!("Failed to get fee: {}", e))?;
+
+        let mut priority_fees: Vec<u64> = self.client.get_recent_prioritization_fees(&[self.program_id])
+            .map_err(|e| anyhow!("Failed to get prioritization fees: {}", e))?
+            .into_iter()
+            .map(|fee| fee.prioritization_fee)
+            .collect();
+        priority_fees.sort_unstable();
+        let median_priority_fee = priority_fees.get(priority_fees.len() / 2).copied().unwrap_or(0);
+
+        Ok((lamports_per_signature, median_priority_fee))
+    }
+
+    /// Fetch and decompress the IDL that `anchor idl init/upgrade` stored on-chain.
+    pub async fn get_onchain_idl(&self) -> Result<serde_json::Value> {
+        let (base, _) = Pubkey::find_program_address(&[], &self.program_id);
+        let idl_address = Pubkey::create_with_seed(&base, "anchor:idl", &self.program_id)
+            .map_err(|e| anyhow!("Failed to derive IDL address: {}", e))?;
+
+        let account = self.client.get_account(&idl_address)
+            .map_err(|e| anyhow!("IDL account not found: {}", e))?;
+        let data = account.data;
+
+        // 8-byte discriminator, 32-byte authority, then a length-prefixed zlib blob
+        let truncated = || anyhow!("IDL account data is truncated");
+        let len = u32::from_le_bytes(data.get(40..44).ok_or_else(truncated)?.try_into()?) as usize;
+        let compressed = data.get(44..44 + len).ok_or_else(truncated)?;
+
+        let mut json = Vec::new();
+        ZlibDecoder::new(compressed).read_to_end(&mut json)
+            .map_err(|e| anyhow!("Failed to decompress IDL: {}", e))?;
+
+        Ok(serde_json::from_slice(&json)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::hash::hash;
+
+    /// Account bytes as Anchor writes them, padded to the allocated size.
+    fn encode<T: ProgramAccount + BorshSerialize>(account: &T, padding: usize) -> Vec<u8> {
+        let mut data = T::discriminator().unwrap().to_vec();
+        account.serialize(&mut data).unwrap();
+        data.resize(data.len() + padding, 0);
+        data
+    }
+
+    fn discriminator_of(name: &str) -> [u8; 8] {
+        hash(format!("account:{}", name).as_bytes()).to_bytes()[..8].try_into().unwrap()
+    }
+
+    fn sample_asset() -> AssetAccount {
+        AssetAccount {
+            asset_id: "asset-1".to_string(),
+            asset_type: "real_estate".to_string(),
+            valuation: 1_000_000,
+            metadata_uri: "https://example.com/asset-1.json".to_string(),
+            owner: Pubkey::new_unique(),
+            is_active: true,
+            risk_score: 42,
+            bump: 254,
+            active_loans: 2,
+            delegate: Pubkey::new_unique(),
+            valuation_refresh_requested_at: 1_700_000_000,
+            last_risk_report: 7,
+            risk_frozen_until: 0,
+            breaker_confirmers: vec![Pubkey::new_unique()],
+            last_risk_update: 1_700_000_100,
+            jurisdiction: *b"US",
+            portfolio: Pubkey::default(),
+        }
+    }
+
+    fn sample_loan() -> LoanAccount {
+        LoanAccount {
+            borrower: Pubkey::new_unique(),
+            asset: Pubkey::new_unique(),
+            principal: 500_000,
+            interest_rate: 850,
+            start_time: 1_700_000_000,
+            end_time: 1_731_536_000,
+            status: LoanStatus::Active,
+            risk_score_at_creation: 40,
+            bump: 253,
+            forborne_seconds: 0,
+            forbearance_start: 0,
+            forbearance_end: 0,
+            modification_count: 1,
+            lender: Pubkey::new_unique(),
+            request_expiry: 1_700_086_400,
+            repayment_mint: Pubkey::new_unique(),
+            supplemental_asset: Pubkey::default(),
+            written_off_amount: 0,
+            interest_mode: InterestMode::DailyCompounding,
+            disputed: true,
+            dispute_raised_by: Pubkey::new_unique(),
+            dispute_raised_at: 1_700_500_000,
+            dispute_outcome: Some(DisputeOutcome::Settled),
+            dispute_resolved_at: 1_700_600_000,
+            late_fees: 25,
+            liquidation_eligible: false,
+            liquidator: Pubkey::default(),
+            liquidation_debt: 0,
+            referral: Pubkey::new_unique(),
+            rollover_count: 0,
+            product: LoanProduct::Amortizing,
+            floating_rate: Some(FloatingRate {
+                benchmark: Pubkey::new_unique(),
+                spread_bps: 200,
+                floor_bps: 300,
+                cap_bps: 1_500,
+            }),
+            interest_checkpoint: 1_234,
+            checkpoint_seconds: 86_400,
+            last_repriced_at: 1_700_086_400,
+            collateral_rules: CollateralRules { max_ltv: 100, haircut_bps: 0, jurisdiction_max_ltv: 0 },
+            supplemental_rules: CollateralRules::default(),
+            principal_repaid: 0,
+        }
+    }
+
+    #[test]
+    fn discriminators_match_anchor_account_names() {
+        assert_eq!(AssetAccount::discriminator().unwrap(), discriminator_of(AssetAccount::NAME));
+        assert_eq!(LoanAccount::discriminator().unwrap(), discriminator_of(LoanAccount::NAME));
+        assert_eq!(ScheduleAccount::discriminator().unwrap(), discriminator_of(ScheduleAccount::NAME));
+        assert_eq!(ProtocolRevenueAccount::discriminator().unwrap(), discriminator_of(ProtocolRevenueAccount::NAME));
+        assert_eq!(ProtocolStats::discriminator().unwrap(), discriminator_of(ProtocolStats::NAME));
+        assert_eq!(BlacklistAccount::discriminator().unwrap(), discriminator_of(BlacklistAccount::NAME));
+        assert_eq!(CustodyAttestationAccount::discriminator().unwrap(), discriminator_of(CustodyAttestationAccount::NAME));
+        assert_eq!(ReferralAccount::discriminator().unwrap(), discriminator_of(ReferralAccount::NAME));
+    }
+
+    #[test]
+    fn accounts_round_trip_through_padded_account_data() {
+        let asset = sample_asset();
+        assert_eq!(AssetAccount::from_bytes(&encode(&asset, 64)).unwrap(), asset);
+
+        let loan = sample_loan();
+        assert_eq!(LoanAccount::from_bytes(&encode(&loan, 16)).unwrap(), loan);
+
+        let schedule = ScheduleAccount {
+            loan: Pubkey::new_unique(),
+            interval: 2_592_000,
+            installments: vec![
+                InstallmentAccount { due_date: 1_702_592_000, amount: 100, paid_at: 1_702_500_000 },
+                InstallmentAccount { due_date: 1_705_184_000, amount: 100, paid_at: 0 },
+            ],
+            paid_count: 1,
+            missed_count: 0,
+            late_payments: 0,
+            delinquent: false,
+            bump: 255,
+        };
+        assert_eq!(ScheduleAccount::from_bytes(&encode(&schedule, 24 * 10)).unwrap(), schedule);
+
+        let stats = ProtocolStats {
+            total_assets: 3,
+            active_loans: 2,
+            total_principal_outstanding: 900_000,
+            cumulative_repaid: 12_000,
+            liquidation_count: 1,
+            bump: 250,
+            cumulative_written_off: 40_000,
+        };
+        assert_eq!(ProtocolStats::from_bytes(&encode(&stats, 0)).unwrap(), stats);
+
+        let blacklist = BlacklistAccount { entries: vec![Pubkey::new_unique(), Pubkey::new_unique()], bump: 251 };
+        assert_eq!(BlacklistAccount::from_bytes(&encode(&blacklist, 32 * 198)).unwrap(), blacklist);
+    }
+
+    #[test]
+    fn matches_the_program_field_layout() {
+        // Offsets the program and RPC filters rely on: owner follows the
+        // three strings and valuation, custodian follows the asset.
+        let asset = sample_asset();
+        let data = encode(&asset, 0);
+        let owner_offset = 8 + (4 + 7) + (4 + 11) + 8 + (4 + 32);
+        assert_eq!(&data[owner_offset..owner_offset + 32], asset.owner.as_ref());
+
+        let custody = CustodyAttestationAccount {
+            asset: Pubkey::new_unique(),
+            custodian: Pubkey::new_unique(),
+            document_hash: [9; 32],
+            attested_at: 1,
+            expires_at: 2,
+            bump: 255,
+        };
+        let data = encode(&custody, 0);
+        assert_eq!(data.len(), 8 + 32 + 32 + 32 + 8 + 8 + 1);
+        assert_eq!(&data[40..72], custody.custodian.as_ref());
+        assert_eq!(CustodyAttestationAccount::from_bytes(&data).unwrap(), custody);
+    }
+
+    #[test]
+    fn payoff_accrues_interest_and_late_fees_like_the_program() {
+        let mut loan = sample_loan();
+        loan.principal = 1_000_000;
+        loan.interest_rate = 1_000;
+        loan.interest_mode = InterestMode::Simple;
+        loan.start_time = 0;
+        loan.end_time = SECONDS_PER_YEAR;
+        loan.interest_checkpoint = 0;
+        loan.checkpoint_seconds = 0;
+
+        assert_eq!(payoff_breakdown(&loan, SECONDS_PER_YEAR), Some((1_000_000, 100_000, 0)));
+        // Three full days late: 30 bps of principal, interest rounded down
+        let three_days_late = SECONDS_PER_YEAR + 3 * SECONDS_PER_DAY + 600;
+        assert_eq!(payoff_breakdown(&loan, three_days_late), Some((1_000_000, 100_823, 3_000)));
+        // The late fee stops at 1,000 bps
+        assert_eq!(late_fee(&loan, 2 * SECONDS_PER_YEAR), 100_000);
+        loan.status = LoanStatus::Repaid;
+        assert_eq!(late_fee(&loan, 2 * SECONDS_PER_YEAR), 0);
+
+        // A forbearance window stops the clock while it lasts
+        loan.status = LoanStatus::Active;
+        loan.forbearance_start = SECONDS_PER_YEAR / 2;
+        loan.forbearance_end = SECONDS_PER_YEAR;
+        assert_eq!(loan_interest(&loan, SECONDS_PER_YEAR), Some(50_000));
+        assert_eq!(reserve_share(50_000, 1_000), 5_000);
+
+        // Installments paid to mid-term leave interest on the remaining balance only
+        loan.forbearance_start = 0;
+        loan.forbearance_end = 0;
+        loan.principal_repaid = 500_000;
+        loan.checkpoint_seconds = SECONDS_PER_YEAR / 2;
+        assert_eq!(payoff_breakdown(&loan, SECONDS_PER_YEAR), Some((500_000, 25_000, 0)));
+        assert_eq!(late_fee(&loan, SECONDS_PER_YEAR + 3 * SECONDS_PER_DAY), 1_500);
+    }
+
+    #[test]
+    fn liquidation_triggers_follow_the_program() {
+        let mut asset = sample_asset();
+        asset.valuation = 1_000_000;
+        asset.risk_score = 30;
+        let mut loan = sample_loan();
+        loan.disputed = false;
+        loan.interest_rate = 0;
+        loan.interest_checkpoint = 0;
+        loan.end_time = i64::MAX;
+
+        // 60% LTV on 1,000,000 covers the 500,000 principal
+        assert_eq!(liquidation_trigger(&loan, &asset, None, loan.start_time), None);
+        // A loan at the origination limit has headroom for interest
+        loan.principal = 600_000;
+        loan.interest_rate = 1_000;
+        assert_eq!(liquidation_trigger(&loan, &asset, None, loan.start_time + 1), None);
+        loan.interest_rate = 0;
+        // Liquidation starts past 110% of that limit
+        loan.principal = 700_000;
+        assert_eq!(liquidation_trigger(&loan, &asset, None, loan.start_time), Some(LiquidationTrigger::LtvBreach(9_428)));
+        // A top-up's own limit counts towards health
+        let mut top_up = sample_asset();
+        top_up.valuation = 200_000;
+        top_up.risk_score = 10;
+        loan.supplemental_rules = loan.collateral_rules;
+        assert_eq!(liquidation_trigger(&loan, &asset, Some(&top_up), loan.start_time), None);
+        // So do the type and jurisdiction limits the loan was funded under
+        loan.principal = 500_000;
+        loan.collateral_rules.jurisdiction_max_ltv = 40;
+        assert_eq!(liquidation_trigger(&loan, &asset, None, loan.start_time), Some(LiquidationTrigger::LtvBreach(8_800)));
+
+        asset.risk_score = 81;
+        assert_eq!(liquidation_trigger(&loan, &asset, None, loan.start_time), Some(LiquidationTrigger::HighRisk(81)));
+        asset.risk_frozen_until = loan.start_time + 1;
+        assert_eq!(liquidation_trigger(&loan, &asset, None, loan.start_time), None);
+        asset.risk_frozen_until = 0;
+        loan.disputed = true;
+        assert_eq!(liquidation_trigger(&loan, &asset, None, loan.start_time), None);
+    }
+
+    #[test]
+    fn rejects_foreign_and_truncated_accounts() {
+        let loan_data = encode(&sample_loan(), 0);
+        assert!(AssetAccount::from_bytes(&loan_data).is_err());
+        assert!(LoanAccount::from_bytes(&[]).is_err());
+        assert!(LoanAccount::from_bytes(&loan_data[..7]).is_err());
+        for len in [8, 40, loan_data.len() - 1] {
+            assert!(LoanAccount::from_bytes(&loan_data[..len]).is_err());
+        }
+
+        // Unknown enum tags are errors rather than panics
+        let mut bad_status = loan_data.clone();
+        bad_status[8 + 32 + 32 + 8 + 8 + 8 + 8] = 200;
+        assert!(LoanAccount::from_bytes(&bad_status).is_err());
     }
 }
@@ -0,0 +1,250 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use super::{
+    AssetRecord, AssetRepository, AuditFilter, AuditRecord, AuditRepository, HealthRepository, JobRecord,
+    JobRepository, JobStatus, LoanRecord, LoanRepository, OracleSignerRecord, OracleSignerRepository,
+    RiskEvent, RiskEventFilter, RiskHistoryRepository, ScreeningRecord, ScreeningRepository,
+    SubscriberRecord, SubscriberRepository, TenantRepository,
+};
+
+/// Process-local storage for tests and single-node demos; nothing survives a restart.
+#[derive(Default)]
+pub struct MemoryStorage {
+    assets: RwLock<HashMap<String, AssetRecord>>,
+    loans: RwLock<HashMap<String, LoanRecord>>,
+    risk_events: RwLock<Vec<RiskEvent>>,
+    screenings: RwLock<Vec<ScreeningRecord>>,
+    jobs: RwLock<Vec<JobRecord>>,
+    oracle_signers: RwLock<HashMap<String, OracleSignerRecord>>,
+    audit: RwLock<Vec<AuditRecord>>,
+    subscribers: RwLock<HashMap<String, SubscriberRecord>>,
+    /// (kind, id) to the tenant that created it.
+    tenant_resources: RwLock<HashMap<(String, String), String>>,
+}
+
+#[async_trait]
+impl AssetRepository for MemoryStorage {
+    async fn upsert_asset(&self, asset: &AssetRecord) -> Result<()> {
+        self.assets.write().unwrap().insert(asset.asset_id.clone(), asset.clone());
+        Ok(())
+    }
+
+    async fn get_asset(&self, asset_id: &str) -> Result<Option<AssetRecord>> {
+        Ok(self.assets.read().unwrap().get(asset_id).cloned())
+    }
+
+    async fn list_assets(&self, limit: usize, offset: usize) -> Result<Vec<AssetRecord>> {
+        let mut assets: Vec<_> = self.assets.read().unwrap().values().cloned().collect();
+        assets.sort_by(|a, b| a.asset_id.cmp(&b.asset_id));
+        Ok(assets.into_iter().skip(offset).take(limit).collect())
+    }
+}
+
+#[async_trait]
+impl LoanRepository for MemoryStorage {
+    async fn upsert_loan(&self, loan: &LoanRecord) -> Result<()> {
+        self.loans.write().unwrap().insert(loan.loan_pda.clone(), loan.clone());
+        Ok(())
+    }
+
+    async fn get_loan(&self, loan_pda: &str) -> Result<Option<LoanRecord>> {
+        Ok(self.loans.read().unwrap().get(loan_pda).cloned())
+    }
+
+    async fn update_loan_status(&self, loan_pda: &str, status: &str, updated_at: i64) -> Result<()> {
+        if let Some(loan) = self.loans.write().unwrap().get_mut(loan_pda) {
+            loan.status = status.to_string();
+            loan.updated_at = updated_at;
+        }
+        Ok(())
+    }
+
+    async fn list_loans_for_asset(&self, asset_id: &str) -> Result<Vec<LoanRecord>> {
+        Ok(self.loans.read().unwrap()
+            .values()
+            .filter(|loan| loan.asset_id == asset_id)
+            .cloned()
+            .collect())
+    }
+}
+
+#[async_trait]
+impl RiskHistoryRepository for MemoryStorage {
+    async fn record_risk_event(&self, event: &RiskEvent) -> Result<()> {
+        let mut events = self.risk_events.write().unwrap();
+        let id = events.len() as i64 + 1;
+        events.push(RiskEvent { id, ..event.clone() });
+        Ok(())
+    }
+
+    async fn list_risk_events(&self, asset_id: &str, filter: &RiskEventFilter) -> Result<Vec<RiskEvent>> {
+        let mut events: Vec<RiskEvent> = self.risk_events.read().unwrap()
+            .iter()
+            .filter(|event| event.asset_id == asset_id && filter.matches(event))
+            .cloned()
+            .collect();
+        events.sort_by_key(|event| std::cmp::Reverse((event.recorded_at, event.id)));
+        events.truncate(filter.limit);
+        Ok(events)
+    }
+}
+
+#[async_trait]
+impl ScreeningRepository for MemoryStorage {
+    async fn record_screening(&self, record: &ScreeningRecord) -> Result<()> {
+        self.screenings.write().unwrap().push(record.clone());
+        Ok(())
+    }
+
+    async fn list_screenings(&self, address: &str, limit: usize) -> Result<Vec<ScreeningRecord>> {
+        Ok(self.screenings.read().unwrap()
+            .iter()
+            .rev()
+            .filter(|record| record.address == address)
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+}
+
+#[async_trait]
+impl JobRepository for MemoryStorage {
+    async fn enqueue_job(&self, kind: &str, payload: &serde_json::Value, run_at: i64) -> Result<i64> {
+        let mut jobs = self.jobs.write().unwrap();
+        let id = jobs.len() as i64 + 1;
+        jobs.push(JobRecord {
+            id,
+            kind: kind.to_string(),
+            payload: payload.clone(),
+            status: JobStatus::Pending,
+            attempts: 0,
+            run_at,
+            last_error: None,
+            updated_at: run_at,
+        });
+        Ok(id)
+    }
+
+    async fn claim_due_job(&self, now: i64) -> Result<Option<JobRecord>> {
+        let mut jobs = self.jobs.write().unwrap();
+        let next = jobs.iter_mut()
+            .filter(|job| job.status == JobStatus::Pending && job.run_at <= now)
+            .min_by_key(|job| job.run_at);
+
+        Ok(next.map(|job| {
+            job.status = JobStatus::Running;
+            job.attempts += 1;
+            job.updated_at = now;
+            job.clone()
+        }))
+    }
+
+    async fn complete_job(&self, id: i64, now: i64) -> Result<()> {
+        if let Some(job) = self.jobs.write().unwrap().iter_mut().find(|job| job.id == id) {
+            job.status = JobStatus::Completed;
+            job.updated_at = now;
+        }
+        Ok(())
+    }
+
+    async fn fail_job(&self, id: i64, error: &str, retry_at: Option<i64>, now: i64) -> Result<()> {
+        if let Some(job) = self.jobs.write().unwrap().iter_mut().find(|job| job.id == id) {
+            job.last_error = Some(error.to_string());
+            job.updated_at = now;
+            match retry_at {
+                Some(run_at) => {
+                    job.status = JobStatus::Pending;
+                    job.run_at = run_at;
+                }
+                None => job.status = JobStatus::Failed,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl OracleSignerRepository for MemoryStorage {
+    async fn add_oracle_signer(&self, signer: &OracleSignerRecord) -> Result<()> {
+        self.oracle_signers.write().unwrap().insert(signer.pubkey.clone(), signer.clone());
+        Ok(())
+    }
+
+    async fn remove_oracle_signer(&self, pubkey: &str) -> Result<bool> {
+        Ok(self.oracle_signers.write().unwrap().remove(pubkey).is_some())
+    }
+
+    async fn list_oracle_signers(&self) -> Result<Vec<OracleSignerRecord>> {
+        let mut signers: Vec<_> = self.oracle_signers.read().unwrap().values().cloned().collect();
+        signers.sort_by(|a, b| a.pubkey.cmp(&b.pubkey));
+        Ok(signers)
+    }
+}
+
+#[async_trait]
+impl AuditRepository for MemoryStorage {
+    async fn record_audit(&self, record: &AuditRecord) -> Result<()> {
+        let mut audit = self.audit.write().unwrap();
+        let id = audit.len() as i64 + 1;
+        audit.push(AuditRecord { id, ..record.clone() });
+        Ok(())
+    }
+
+    async fn list_audit(&self, filter: &AuditFilter) -> Result<Vec<AuditRecord>> {
+        Ok(self.audit.read().unwrap()
+            .iter()
+            .rev()
+            .filter(|record| filter.matches(record))
+            .take(filter.limit)
+            .cloned()
+            .collect())
+    }
+}
+
+#[async_trait]
+impl SubscriberRepository for MemoryStorage {
+    async fn upsert_subscriber(&self, subscriber: &SubscriberRecord) -> Result<()> {
+        self.subscribers.write().unwrap().insert(subscriber.id.clone(), subscriber.clone());
+        Ok(())
+    }
+
+    async fn remove_subscriber(&self, id: &str) -> Result<bool> {
+        Ok(self.subscribers.write().unwrap().remove(id).is_some())
+    }
+
+    async fn list_subscribers(&self) -> Result<Vec<SubscriberRecord>> {
+        let mut subscribers: Vec<_> = self.subscribers.read().unwrap().values().cloned().collect();
+        subscribers.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(subscribers)
+    }
+}
+
+#[async_trait]
+impl TenantRepository for MemoryStorage {
+    async fn assign_to_tenant(&self, tenant: &str, kind: &str, id: &str, _assigned_at: i64) -> Result<()> {
+        self.tenant_resources.write().unwrap()
+            .entry((kind.to_string(), id.to_string()))
+            .or_insert_with(|| tenant.to_string());
+        Ok(())
+    }
+
+    async fn tenant_resources(&self, tenant: &str, kind: &str) -> Result<Vec<String>> {
+        let mut ids: Vec<_> = self.tenant_resources.read().unwrap()
+            .iter()
+            .filter(|((resource_kind, _), owner)| resource_kind == kind && *owner == tenant)
+            .map(|((_, id), _)| id.clone())
+            .collect();
+        ids.sort();
+        Ok(ids)
+    }
+}
+
+#[async_trait]
+impl HealthRepository for MemoryStorage {
+    async fn ping(&self) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,121 @@
+use anyhow::{anyhow, Result};
+use sqlx::{Database, Pool};
+use sqlx_core::migrate::{Migrate, Migration, MigrationType, Migrator};
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+/// Embeds `backend/migrations/<dir>/<file>` under the version and description
+/// `sqlx::migrate!` would derive from the file name, so databases migrated
+/// before keep matching. Applied migrations are checksummed: change the
+/// schema with a new file, never by editing one that has shipped.
+macro_rules! embedded {
+    ($dir:literal: $($version:literal $file:literal),* $(,)?) => {
+        LazyLock::new(|| embedded_migrator(vec![
+            $(($version, $file, include_str!(concat!("../../migrations/", $dir, "/", $file)))),*
+        ]))
+    };
+}
+
+pub static POSTGRES: LazyLock<Migrator> = embedded!("postgres":
+    1 "0001_initial.sql",
+    2 "0002_webhook_deliveries_and_audit_logs.sql",
+    3 "0003_oracle_signers.sql",
+    4 "0004_notification_subscribers.sql",
+    5 "0005_audit_payload_hash.sql",
+    6 "0006_tenant_resources.sql",
+);
+pub static SQLITE: LazyLock<Migrator> = embedded!("sqlite":
+    1 "0001_initial.sql",
+    2 "0002_webhook_deliveries_and_audit_logs.sql",
+    3 "0003_oracle_signers.sql",
+    4 "0004_notification_subscribers.sql",
+    5 "0005_audit_payload_hash.sql",
+    6 "0006_tenant_resources.sql",
+);
+
+fn embedded_migrator(files: Vec<(i64, &'static str, &'static str)>) -> Migrator {
+    let migrations = files.into_iter()
+        .map(|(version, file, sql)| {
+            let (_, description) = file.trim_end_matches(".sql").split_once('_').unwrap_or_default();
+            Migration::new(
+                version,
+                Cow::Owned(description.replace('_', " ")),
+                MigrationType::Simple,
+                Cow::Borrowed(sql),
+            )
+        })
+        .collect();
+    Migrator { migrations: Cow::Owned(migrations), ignore_missing: false, locking: true }
+}
+
+/// Whether connecting brings the schema up to date or only checks it is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationMode {
+    Apply,
+    Verify,
+}
+
+/// Apply `migrator`'s pending migrations, or with `Verify` refuse a schema
+/// that is missing any, naming them.
+pub(super) async fn run<DB>(migrator: &Migrator, pool: &Pool<DB>, mode: MigrationMode) -> Result<()>
+where
+    DB: Database,
+    DB::Connection: Migrate,
+{
+    if mode == MigrationMode::Apply {
+        migrator.run(pool).await?;
+        return Ok(());
+    }
+
+    let mut conn = pool.acquire().await?;
+    conn.ensure_migrations_table().await?;
+    if let Some(version) = conn.dirty_version().await? {
+        return Err(anyhow!("Migration {} was left half-applied; repair it by hand", version));
+    }
+    let applied: HashSet<i64> = conn.list_applied_migrations().await?
+        .into_iter()
+        .map(|migration| migration.version)
+        .collect();
+    let pending: Vec<String> = migrator.iter()
+        .filter(|migration| !applied.contains(&migration.version))
+        .map(|migration| format!("{} {}", migration.version, migration.description))
+        .collect();
+    match pending.is_empty() {
+        true => Ok(()),
+        false => Err(anyhow!("Pending migrations: {}; run `backend migrate`", pending.join(", "))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn embedded_files(migrator: &Migrator) -> Vec<String> {
+        migrator.iter()
+            .map(|migration| format!("{:04}_{}.sql", migration.version, migration.description.replace(' ', "_")))
+            .collect()
+    }
+
+    fn files_on_disk(dir: &str) -> Vec<String> {
+        let path = format!("{}/migrations/{}", env!("CARGO_MANIFEST_DIR"), dir);
+        let mut files: Vec<String> = std::fs::read_dir(path).unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn every_migration_file_is_embedded() {
+        assert_eq!(embedded_files(&POSTGRES), files_on_disk("postgres"));
+        assert_eq!(embedded_files(&SQLITE), files_on_disk("sqlite"));
+    }
+
+    #[test]
+    fn descriptions_match_the_file_names() {
+        let migration = &SQLITE.migrations[1];
+        assert_eq!(migration.version, 2);
+        assert_eq!(migration.description, "webhook deliveries and audit logs");
+    }
+}
@@ -0,0 +1,464 @@
+// Repositories expose more than the routes consume today
+#![allow(dead_code)]
+
+mod memory;
+mod migrations;
+mod postgres;
+mod sqlite;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::sync::Arc;
+
+use crate::network::Network;
+
+pub use memory::MemoryStorage;
+pub use migrations::MigrationMode;
+pub use postgres::PostgresStorage;
+pub use sqlite::SqliteStorage;
+
+// ==================== Stored Records ====================
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssetRecord {
+    pub asset_id: String,
+    pub asset_pda: String,
+    pub asset_type: String,
+    pub valuation: u64,
+    pub owner: String,
+    pub risk_score: u8,
+    pub is_active: bool,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoanRecord {
+    pub loan_pda: String,
+    pub asset_id: String,
+    pub borrower: String,
+    pub principal: u64,
+    pub interest_rate: u64,
+    pub duration: i64,
+    pub status: String,
+    pub updated_at: i64,
+}
+
+/// A risk score change or assessment, from whichever source reported it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskEvent {
+    /// Assigned by the store; ignored when recording.
+    pub id: i64,
+    pub asset_id: String,
+    pub risk_score: u8,
+    /// api, chainlink, risk_report or chain (observed on-chain, written by
+    /// someone other than this backend).
+    pub source: String,
+    pub confidence: Option<f32>,
+    /// Signature of the transaction that wrote the score, if any.
+    pub transaction: Option<String>,
+    pub recorded_at: i64,
+}
+
+impl RiskEvent {
+    /// An event to record as of now.
+    pub fn now(asset_id: &str, risk_score: u8, source: &str, confidence: Option<f32>, transaction: Option<String>) -> Self {
+        Self {
+            id: 0,
+            asset_id: asset_id.to_string(),
+            risk_score,
+            source: source.to_string(),
+            confidence,
+            transaction,
+            recorded_at: chrono::Utc::now().timestamp(),
+        }
+    }
+}
+
+/// Which of an asset's risk events to list, newest first.
+#[derive(Debug, Clone)]
+pub struct RiskEventFilter {
+    pub source: Option<String>,
+    /// Recorded at or after, unix seconds.
+    pub from: Option<i64>,
+    /// Recorded before, unix seconds.
+    pub to: Option<i64>,
+    /// `(recorded_at, id)` of the last event of the previous page.
+    pub before: Option<(i64, i64)>,
+    pub limit: usize,
+}
+
+impl RiskEventFilter {
+    pub fn latest(limit: usize) -> Self {
+        Self { source: None, from: None, to: None, before: None, limit }
+    }
+
+    /// Whether `event` passes every filter but the limit.
+    pub(super) fn matches(&self, event: &RiskEvent) -> bool {
+        self.source.as_ref().is_none_or(|source| event.source == *source)
+            && self.from.is_none_or(|from| event.recorded_at >= from)
+            && self.to.is_none_or(|to| event.recorded_at < to)
+            && self.before.is_none_or(|before| (event.recorded_at, event.id) < before)
+    }
+}
+
+/// One sanctions screening of an address, kept as compliance evidence.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScreeningRecord {
+    pub address: String,
+    /// What prompted the check: asset_creation, loan_creation or scheduled.
+    pub context: String,
+    /// Asset id or loan PDA the address was screened for.
+    pub subject: String,
+    pub provider: String,
+    pub matched: bool,
+    /// cleared, flagged or blocked.
+    pub action: String,
+    pub detail: Option<String>,
+    pub screened_at: i64,
+}
+
+/// A key the Chainlink webhook accepts signed reports from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OracleSignerRecord {
+    pub pubkey: String,
+    pub label: Option<String>,
+    /// Subject of the admin who added it.
+    pub added_by: String,
+    pub added_at: i64,
+}
+
+/// Someone the notifier tells about risk, maturity and liquidation events,
+/// and how to reach them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubscriberRecord {
+    pub id: String,
+    /// webhook, email, slack or telegram.
+    pub channel: String,
+    /// Webhook URL, email address, Slack incoming webhook URL or Telegram chat id.
+    pub target: String,
+    /// Signs webhook deliveries; unused by the other channels.
+    #[serde(skip_serializing)]
+    pub secret: Option<String>,
+    /// Any of risk_threshold, loan_maturity and liquidation.
+    pub events: Vec<String>,
+    /// Risk score whose crossing, in either direction, is notified.
+    pub risk_threshold: Option<u8>,
+    /// How long before maturity an open loan is notified.
+    pub maturity_notice_secs: i64,
+    /// Only events about this asset, when set.
+    pub asset_id: Option<String>,
+    /// Only loans of this borrower, when set.
+    pub borrower: Option<String>,
+    pub created_at: i64,
+}
+
+/// One change made through the API: who, what, and how it ended.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// Assigned by the store; ignored when recording.
+    pub id: i64,
+    /// Caller subject, or `anonymous`.
+    pub actor: String,
+    pub role: String,
+    /// What was done, e.g. `oracle.add`.
+    pub action: String,
+    /// What it was done to, e.g. `oracle:<pubkey>`.
+    pub resource: String,
+    pub status_code: u16,
+    pub detail: Option<serde_json::Value>,
+    /// Hex SHA-256 of the request body, which isn't kept since it may
+    /// carry secrets.
+    pub payload_hash: Option<String>,
+    pub transaction: Option<String>,
+    pub recorded_at: i64,
+}
+
+impl AuditRecord {
+    /// An entry to record as of now, with its outcome still to fill in.
+    pub fn now(actor: &str, role: &str, action: &str, resource: String) -> Self {
+        Self {
+            id: 0,
+            actor: actor.to_string(),
+            role: role.to_string(),
+            action: action.to_string(),
+            resource,
+            status_code: 0,
+            detail: None,
+            payload_hash: None,
+            transaction: None,
+            recorded_at: chrono::Utc::now().timestamp(),
+        }
+    }
+
+    pub fn succeeded(&self) -> bool {
+        self.status_code < 400
+    }
+}
+
+/// Which audit entries to list, newest first.
+#[derive(Debug, Clone, Default)]
+pub struct AuditFilter {
+    pub actor: Option<String>,
+    /// Actions starting with this, e.g. `POST /loans`.
+    pub action: Option<String>,
+    /// Resources starting with this, e.g. `/loans/<pda>`.
+    pub resource: Option<String>,
+    /// Only calls that succeeded, or only ones that failed.
+    pub succeeded: Option<bool>,
+    /// Recorded at or after, unix seconds.
+    pub from: Option<i64>,
+    /// Recorded before, unix seconds.
+    pub to: Option<i64>,
+    /// `(recorded_at, id)` of the last entry of the previous page.
+    pub before: Option<(i64, i64)>,
+    pub limit: usize,
+}
+
+impl AuditFilter {
+    /// Whether `record` passes every filter but the limit.
+    pub(super) fn matches(&self, record: &AuditRecord) -> bool {
+        self.actor.as_ref().is_none_or(|actor| record.actor == *actor)
+            && self.action.as_ref().is_none_or(|action| record.action.starts_with(action.as_str()))
+            && self.resource.as_ref().is_none_or(|resource| record.resource.starts_with(resource.as_str()))
+            && self.succeeded.is_none_or(|succeeded| record.succeeded() == succeeded)
+            && self.from.is_none_or(|from| record.recorded_at >= from)
+            && self.to.is_none_or(|to| record.recorded_at < to)
+            && self.before.is_none_or(|before| (record.recorded_at, record.id) < before)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+}
+
+impl JobStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            JobStatus::Pending => "pending",
+            JobStatus::Running => "running",
+            JobStatus::Completed => "completed",
+            JobStatus::Failed => "failed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<Self> {
+        match value {
+            "pending" => Ok(JobStatus::Pending),
+            "running" => Ok(JobStatus::Running),
+            "completed" => Ok(JobStatus::Completed),
+            "failed" => Ok(JobStatus::Failed),
+            other => Err(anyhow!("Unknown job status: {}", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobRecord {
+    pub id: i64,
+    pub kind: String,
+    pub payload: serde_json::Value,
+    pub status: JobStatus,
+    pub attempts: u32,
+    pub run_at: i64,
+    pub last_error: Option<String>,
+    pub updated_at: i64,
+}
+
+// ==================== Repository Traits ====================
+#[async_trait]
+pub trait AssetRepository: Send + Sync {
+    async fn upsert_asset(&self, asset: &AssetRecord) -> Result<()>;
+    async fn get_asset(&self, asset_id: &str) -> Result<Option<AssetRecord>>;
+    async fn list_assets(&self, limit: usize, offset: usize) -> Result<Vec<AssetRecord>>;
+}
+
+#[async_trait]
+pub trait LoanRepository: Send + Sync {
+    async fn upsert_loan(&self, loan: &LoanRecord) -> Result<()>;
+    async fn get_loan(&self, loan_pda: &str) -> Result<Option<LoanRecord>>;
+    async fn update_loan_status(&self, loan_pda: &str, status: &str, updated_at: i64) -> Result<()>;
+    async fn list_loans_for_asset(&self, asset_id: &str) -> Result<Vec<LoanRecord>>;
+}
+
+#[async_trait]
+pub trait RiskHistoryRepository: Send + Sync {
+    async fn record_risk_event(&self, event: &RiskEvent) -> Result<()>;
+    /// Most recent events first.
+    async fn list_risk_events(&self, asset_id: &str, filter: &RiskEventFilter) -> Result<Vec<RiskEvent>>;
+}
+
+#[async_trait]
+pub trait ScreeningRepository: Send + Sync {
+    async fn record_screening(&self, record: &ScreeningRecord) -> Result<()>;
+    /// Most recent screenings first.
+    async fn list_screenings(&self, address: &str, limit: usize) -> Result<Vec<ScreeningRecord>>;
+}
+
+#[async_trait]
+pub trait OracleSignerRepository: Send + Sync {
+    /// Add `signer`, or update its label.
+    async fn add_oracle_signer(&self, signer: &OracleSignerRecord) -> Result<()>;
+    /// Whether there was such a signer.
+    async fn remove_oracle_signer(&self, pubkey: &str) -> Result<bool>;
+    async fn list_oracle_signers(&self) -> Result<Vec<OracleSignerRecord>>;
+}
+
+#[async_trait]
+pub trait SubscriberRepository: Send + Sync {
+    /// Add `subscriber`, or replace the one with its id.
+    async fn upsert_subscriber(&self, subscriber: &SubscriberRecord) -> Result<()>;
+    /// Whether there was such a subscriber.
+    async fn remove_subscriber(&self, id: &str) -> Result<bool>;
+    async fn list_subscribers(&self) -> Result<Vec<SubscriberRecord>>;
+}
+
+#[async_trait]
+pub trait TenantRepository: Send + Sync {
+    /// Record that `tenant` created resource `id` of `kind`; the first
+    /// assignment stands.
+    async fn assign_to_tenant(&self, tenant: &str, kind: &str, id: &str, assigned_at: i64) -> Result<()>;
+    /// Ids of the `kind` resources `tenant` created.
+    async fn tenant_resources(&self, tenant: &str, kind: &str) -> Result<Vec<String>>;
+}
+
+#[async_trait]
+pub trait HealthRepository: Send + Sync {
+    /// Round-trip to the backend, failing if it can't serve queries.
+    async fn ping(&self) -> Result<()>;
+}
+
+#[async_trait]
+pub trait AuditRepository: Send + Sync {
+    /// Append `record`; entries are never updated or removed.
+    async fn record_audit(&self, record: &AuditRecord) -> Result<()>;
+    /// Most recent entries first.
+    async fn list_audit(&self, filter: &AuditFilter) -> Result<Vec<AuditRecord>>;
+}
+
+#[async_trait]
+pub trait JobRepository: Send + Sync {
+    async fn enqueue_job(&self, kind: &str, payload: &serde_json::Value, run_at: i64) -> Result<i64>;
+    /// Atomically mark the oldest due pending job as running and return it.
+    async fn claim_due_job(&self, now: i64) -> Result<Option<JobRecord>>;
+    async fn complete_job(&self, id: i64, now: i64) -> Result<()>;
+    /// Record a failure; the job is retried at `retry_at` or marked failed if `None`.
+    async fn fail_job(&self, id: i64, error: &str, retry_at: Option<i64>, now: i64) -> Result<()>;
+}
+
+// ==================== Storage Selection ====================
+#[derive(Clone)]
+pub struct Storage {
+    pub backend: &'static str,
+    pub assets: Arc<dyn AssetRepository>,
+    pub loans: Arc<dyn LoanRepository>,
+    pub risk_history: Arc<dyn RiskHistoryRepository>,
+    pub screenings: Arc<dyn ScreeningRepository>,
+    pub jobs: Arc<dyn JobRepository>,
+    pub oracle_signers: Arc<dyn OracleSignerRepository>,
+    pub audit: Arc<dyn AuditRepository>,
+    pub subscribers: Arc<dyn SubscriberRepository>,
+    pub tenants: Arc<dyn TenantRepository>,
+    pub health: Arc<dyn HealthRepository>,
+}
+
+impl Storage {
+    fn from_backend<T>(backend: &'static str, store: T) -> Self
+    where
+        T: AssetRepository
+            + LoanRepository
+            + RiskHistoryRepository
+            + ScreeningRepository
+            + JobRepository
+            + OracleSignerRepository
+            + AuditRepository
+            + SubscriberRepository
+            + TenantRepository
+            + HealthRepository
+            + 'static,
+    {
+        let store = Arc::new(store);
+        Self {
+            backend,
+            assets: store.clone(),
+            loans: store.clone(),
+            risk_history: store.clone(),
+            screenings: store.clone(),
+            jobs: store.clone(),
+            oracle_signers: store.clone(),
+            audit: store.clone(),
+            subscribers: store.clone(),
+            tenants: store.clone(),
+            health: store,
+        }
+    }
+
+    pub fn memory() -> Self {
+        Self::from_backend("memory", MemoryStorage::default())
+    }
+
+    /// Select `network`'s backend from its `STORAGE_BACKEND` (memory,
+    /// sqlite, postgres), connecting to its `DATABASE_URL` for the SQL
+    /// backends. Networks never share a mirror.
+    ///
+    /// Pending migrations are applied unless `MIGRATE_ON_STARTUP=false`, in
+    /// which case a schema missing any is refused and `backend migrate`
+    /// must apply them first.
+    pub async fn for_network(network: &Network) -> Result<Self> {
+        let mode = match env::var("MIGRATE_ON_STARTUP").as_deref() {
+            Ok("false") => MigrationMode::Verify,
+            _ => MigrationMode::Apply,
+        };
+        Self::connect(network, mode).await
+    }
+
+    pub async fn connect(network: &Network, mode: MigrationMode) -> Result<Self> {
+        let backend = network.var("STORAGE_BACKEND").unwrap_or_else(|| "memory".to_string());
+
+        match backend.as_str() {
+            "memory" => Ok(Self::memory()),
+            "sqlite" => {
+                let url = network.var("DATABASE_URL").unwrap_or_else(|| match network.is_default {
+                    true => "sqlite://rwa_backend.db?mode=rwc".to_string(),
+                    false => format!("sqlite://rwa_backend_{}.db?mode=rwc", network.name),
+                });
+                Ok(Self::from_backend("sqlite", SqliteStorage::connect(&url, mode).await?))
+            }
+            "postgres" => {
+                let url = network.var("DATABASE_URL")
+                    .ok_or_else(|| anyhow!("DATABASE_URL is required for the postgres backend"))?;
+                Ok(Self::from_backend("postgres", PostgresStorage::connect(&url, mode).await?))
+            }
+            other => Err(anyhow!("Unknown STORAGE_BACKEND: {}", other)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn risk_event_filter_resumes_before_the_cursor() {
+        let event = |id, recorded_at, source: &str| RiskEvent {
+            id,
+            recorded_at,
+            ..RiskEvent::now("asset-001", 40, source, None, None)
+        };
+        let filter = RiskEventFilter {
+            source: Some("api".to_string()),
+            from: Some(100),
+            before: Some((200, 7)),
+            ..RiskEventFilter::latest(10)
+        };
+        assert!(filter.matches(&event(6, 200, "api")));
+        assert!(!filter.matches(&event(8, 200, "api")));
+        assert!(!filter.matches(&event(1, 150, "chain")));
+        assert!(!filter.matches(&event(2, 99, "api")));
+    }
+}
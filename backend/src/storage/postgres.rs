@@ -0,0 +1,530 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use sqlx::postgres::{PgPool, PgPoolOptions, PgRow};
+use sqlx::Row;
+
+use super::migrations::{self, MigrationMode};
+use super::{
+    AssetRecord, AssetRepository, AuditFilter, AuditRecord, AuditRepository, HealthRepository, JobRecord,
+    JobRepository, JobStatus, LoanRecord, LoanRepository, OracleSignerRecord, OracleSignerRepository,
+    RiskEvent, RiskEventFilter, RiskHistoryRepository, ScreeningRecord, ScreeningRepository,
+    SubscriberRecord, SubscriberRepository, TenantRepository,
+};
+
+pub struct PostgresStorage {
+    pool: PgPool,
+}
+
+impl PostgresStorage {
+    pub async fn connect(url: &str, mode: MigrationMode) -> Result<Self> {
+        let pool = PgPoolOptions::new().max_connections(10).connect(url).await?;
+        migrations::run(&migrations::POSTGRES, &pool, mode).await?;
+        tracing::info!("🗄️ Connected to Postgres storage");
+        Ok(Self { pool })
+    }
+}
+
+fn asset_from_row(row: &PgRow) -> Result<AssetRecord> {
+    Ok(AssetRecord {
+        asset_id: row.try_get("asset_id")?,
+        asset_pda: row.try_get("asset_pda")?,
+        asset_type: row.try_get("asset_type")?,
+        valuation: row.try_get::<i64, _>("valuation")? as u64,
+        owner: row.try_get("owner")?,
+        risk_score: row.try_get::<i16, _>("risk_score")? as u8,
+        is_active: row.try_get("is_active")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+fn loan_from_row(row: &PgRow) -> Result<LoanRecord> {
+    Ok(LoanRecord {
+        loan_pda: row.try_get("loan_pda")?,
+        asset_id: row.try_get("asset_id")?,
+        borrower: row.try_get("borrower")?,
+        principal: row.try_get::<i64, _>("principal")? as u64,
+        interest_rate: row.try_get::<i64, _>("interest_rate")? as u64,
+        duration: row.try_get("duration")?,
+        status: row.try_get("status")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+fn job_from_row(row: &PgRow) -> Result<JobRecord> {
+    Ok(JobRecord {
+        id: row.try_get("id")?,
+        kind: row.try_get("kind")?,
+        payload: serde_json::from_str(&row.try_get::<String, _>("payload")?)?,
+        status: JobStatus::parse(&row.try_get::<String, _>("status")?)?,
+        attempts: row.try_get::<i32, _>("attempts")? as u32,
+        run_at: row.try_get("run_at")?,
+        last_error: row.try_get("last_error")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+#[async_trait]
+impl AssetRepository for PostgresStorage {
+    async fn upsert_asset(&self, asset: &AssetRecord) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO assets (asset_id, asset_pda, asset_type, valuation, owner, risk_score, is_active, updated_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+             ON CONFLICT (asset_id) DO UPDATE SET
+                asset_pda = EXCLUDED.asset_pda,
+                asset_type = EXCLUDED.asset_type,
+                valuation = EXCLUDED.valuation,
+                owner = EXCLUDED.owner,
+                risk_score = EXCLUDED.risk_score,
+                is_active = EXCLUDED.is_active,
+                updated_at = EXCLUDED.updated_at",
+        )
+        .bind(&asset.asset_id)
+        .bind(&asset.asset_pda)
+        .bind(&asset.asset_type)
+        .bind(asset.valuation as i64)
+        .bind(&asset.owner)
+        .bind(asset.risk_score as i16)
+        .bind(asset.is_active)
+        .bind(asset.updated_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn get_asset(&self, asset_id: &str) -> Result<Option<AssetRecord>> {
+        let row = sqlx::query("SELECT * FROM assets WHERE asset_id = $1")
+            .bind(asset_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(asset_from_row).transpose()
+    }
+
+    async fn list_assets(&self, limit: usize, offset: usize) -> Result<Vec<AssetRecord>> {
+        let rows = sqlx::query("SELECT * FROM assets ORDER BY asset_id LIMIT $1 OFFSET $2")
+            .bind(limit as i64)
+            .bind(offset as i64)
+            .fetch_all(&self.pool)
+            .await?;
+        rows.iter().map(asset_from_row).collect()
+    }
+}
+
+#[async_trait]
+impl LoanRepository for PostgresStorage {
+    async fn upsert_loan(&self, loan: &LoanRecord) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO loans (loan_pda, asset_id, borrower, principal, interest_rate, duration, status, updated_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+             ON CONFLICT (loan_pda) DO UPDATE SET
+                asset_id = EXCLUDED.asset_id,
+                borrower = EXCLUDED.borrower,
+                principal = EXCLUDED.principal,
+                interest_rate = EXCLUDED.interest_rate,
+                duration = EXCLUDED.duration,
+                status = EXCLUDED.status,
+                updated_at = EXCLUDED.updated_at",
+        )
+        .bind(&loan.loan_pda)
+        .bind(&loan.asset_id)
+        .bind(&loan.borrower)
+        .bind(loan.principal as i64)
+        .bind(loan.interest_rate as i64)
+        .bind(loan.duration)
+        .bind(&loan.status)
+        .bind(loan.updated_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn get_loan(&self, loan_pda: &str) -> Result<Option<LoanRecord>> {
+        let row = sqlx::query("SELECT * FROM loans WHERE loan_pda = $1")
+            .bind(loan_pda)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(loan_from_row).transpose()
+    }
+
+    async fn update_loan_status(&self, loan_pda: &str, status: &str, updated_at: i64) -> Result<()> {
+        sqlx::query("UPDATE loans SET status = $2, updated_at = $3 WHERE loan_pda = $1")
+            .bind(loan_pda)
+            .bind(status)
+            .bind(updated_at)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn list_loans_for_asset(&self, asset_id: &str) -> Result<Vec<LoanRecord>> {
+        let rows = sqlx::query("SELECT * FROM loans WHERE asset_id = $1 ORDER BY updated_at DESC")
+            .bind(asset_id)
+            .fetch_all(&self.pool)
+            .await?;
+        rows.iter().map(loan_from_row).collect()
+    }
+}
+
+#[async_trait]
+impl RiskHistoryRepository for PostgresStorage {
+    async fn record_risk_event(&self, event: &RiskEvent) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO risk_events (asset_id, risk_score, source, confidence, tx_signature, recorded_at)
+             VALUES ($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(&event.asset_id)
+        .bind(event.risk_score as i16)
+        .bind(&event.source)
+        .bind(event.confidence)
+        .bind(&event.transaction)
+        .bind(event.recorded_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn list_risk_events(&self, asset_id: &str, filter: &RiskEventFilter) -> Result<Vec<RiskEvent>> {
+        let rows = sqlx::query(
+            "SELECT * FROM risk_events
+             WHERE asset_id = $1
+                AND ($2::TEXT IS NULL OR source = $2)
+                AND ($3::BIGINT IS NULL OR recorded_at >= $3)
+                AND ($4::BIGINT IS NULL OR recorded_at < $4)
+                AND ($5::BIGINT IS NULL OR (recorded_at, id) < ($5, $6))
+             ORDER BY recorded_at DESC, id DESC LIMIT $7",
+        )
+        .bind(asset_id)
+        .bind(&filter.source)
+        .bind(filter.from)
+        .bind(filter.to)
+        .bind(filter.before.map(|(recorded_at, _)| recorded_at))
+        .bind(filter.before.map(|(_, id)| id))
+        .bind(filter.limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(RiskEvent {
+                    id: row.try_get("id")?,
+                    asset_id: row.try_get("asset_id")?,
+                    risk_score: row.try_get::<i16, _>("risk_score")? as u8,
+                    source: row.try_get("source")?,
+                    confidence: row.try_get("confidence")?,
+                    transaction: row.try_get("tx_signature")?,
+                    recorded_at: row.try_get("recorded_at")?,
+                })
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl ScreeningRepository for PostgresStorage {
+    async fn record_screening(&self, record: &ScreeningRecord) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO screenings (address, context, subject, provider, matched, action, detail, screened_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+        )
+        .bind(&record.address)
+        .bind(&record.context)
+        .bind(&record.subject)
+        .bind(&record.provider)
+        .bind(record.matched)
+        .bind(&record.action)
+        .bind(&record.detail)
+        .bind(record.screened_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn list_screenings(&self, address: &str, limit: usize) -> Result<Vec<ScreeningRecord>> {
+        let rows = sqlx::query(
+            "SELECT * FROM screenings WHERE address = $1 ORDER BY screened_at DESC, id DESC LIMIT $2",
+        )
+        .bind(address)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(ScreeningRecord {
+                    address: row.try_get("address")?,
+                    context: row.try_get("context")?,
+                    subject: row.try_get("subject")?,
+                    provider: row.try_get("provider")?,
+                    matched: row.try_get("matched")?,
+                    action: row.try_get("action")?,
+                    detail: row.try_get("detail")?,
+                    screened_at: row.try_get("screened_at")?,
+                })
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl JobRepository for PostgresStorage {
+    async fn enqueue_job(&self, kind: &str, payload: &serde_json::Value, run_at: i64) -> Result<i64> {
+        let row = sqlx::query(
+            "INSERT INTO jobs (kind, payload, status, run_at, updated_at)
+             VALUES ($1, $2, 'pending', $3, $3) RETURNING id",
+        )
+        .bind(kind)
+        .bind(payload.to_string())
+        .bind(run_at)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(row.try_get("id")?)
+    }
+
+    async fn claim_due_job(&self, now: i64) -> Result<Option<JobRecord>> {
+        // SKIP LOCKED lets several backend replicas poll the same queue
+        let row = sqlx::query(
+            "UPDATE jobs SET status = 'running', attempts = attempts + 1, updated_at = $1
+             WHERE id = (
+                SELECT id FROM jobs WHERE status = 'pending' AND run_at <= $1
+                ORDER BY run_at LIMIT 1 FOR UPDATE SKIP LOCKED
+             )
+             RETURNING *",
+        )
+        .bind(now)
+        .fetch_optional(&self.pool)
+        .await?;
+        row.as_ref().map(job_from_row).transpose()
+    }
+
+    async fn complete_job(&self, id: i64, now: i64) -> Result<()> {
+        sqlx::query("UPDATE jobs SET status = 'completed', updated_at = $2 WHERE id = $1")
+            .bind(id)
+            .bind(now)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn fail_job(&self, id: i64, error: &str, retry_at: Option<i64>, now: i64) -> Result<()> {
+        let status = if retry_at.is_some() { JobStatus::Pending } else { JobStatus::Failed };
+        sqlx::query(
+            "UPDATE jobs SET status = $2, last_error = $3, run_at = COALESCE($4, run_at), updated_at = $5
+             WHERE id = $1",
+        )
+        .bind(id)
+        .bind(status.as_str())
+        .bind(error)
+        .bind(retry_at)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl OracleSignerRepository for PostgresStorage {
+    async fn add_oracle_signer(&self, signer: &OracleSignerRecord) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO oracle_signers (pubkey, label, added_by, added_at)
+             VALUES ($1, $2, $3, $4)
+             ON CONFLICT (pubkey) DO UPDATE SET label = EXCLUDED.label",
+        )
+        .bind(&signer.pubkey)
+        .bind(&signer.label)
+        .bind(&signer.added_by)
+        .bind(signer.added_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn remove_oracle_signer(&self, pubkey: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM oracle_signers WHERE pubkey = $1")
+            .bind(pubkey)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn list_oracle_signers(&self) -> Result<Vec<OracleSignerRecord>> {
+        let rows = sqlx::query("SELECT * FROM oracle_signers ORDER BY pubkey")
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(OracleSignerRecord {
+                    pubkey: row.try_get("pubkey")?,
+                    label: row.try_get("label")?,
+                    added_by: row.try_get("added_by")?,
+                    added_at: row.try_get("added_at")?,
+                })
+            })
+            .collect()
+    }
+}
+
+fn audit_from_row(row: &PgRow) -> Result<AuditRecord> {
+    let detail: Option<String> = row.try_get("detail")?;
+    Ok(AuditRecord {
+        id: row.try_get("id")?,
+        actor: row.try_get("actor")?,
+        role: row.try_get("role")?,
+        action: row.try_get("action")?,
+        resource: row.try_get("resource")?,
+        status_code: row.try_get::<i16, _>("status_code")? as u16,
+        detail: detail.map(|detail| serde_json::from_str(&detail)).transpose()?,
+        payload_hash: row.try_get("payload_hash")?,
+        transaction: row.try_get("tx_signature")?,
+        recorded_at: row.try_get("recorded_at")?,
+    })
+}
+
+#[async_trait]
+impl AuditRepository for PostgresStorage {
+    async fn record_audit(&self, record: &AuditRecord) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO audit_logs (actor, role, action, resource, status_code, detail, payload_hash, tx_signature, recorded_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+        )
+        .bind(&record.actor)
+        .bind(&record.role)
+        .bind(&record.action)
+        .bind(&record.resource)
+        .bind(record.status_code as i16)
+        .bind(record.detail.as_ref().map(|detail| detail.to_string()))
+        .bind(&record.payload_hash)
+        .bind(&record.transaction)
+        .bind(record.recorded_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn list_audit(&self, filter: &AuditFilter) -> Result<Vec<AuditRecord>> {
+        let rows = sqlx::query(
+            "SELECT * FROM audit_logs
+             WHERE ($1::TEXT IS NULL OR actor = $1)
+                AND ($2::TEXT IS NULL OR action LIKE $2 || '%')
+                AND ($3::TEXT IS NULL OR resource LIKE $3 || '%')
+                AND ($4::BOOLEAN IS NULL OR (status_code < 400) = $4)
+                AND ($5::BIGINT IS NULL OR recorded_at >= $5)
+                AND ($6::BIGINT IS NULL OR recorded_at < $6)
+                AND ($7::BIGINT IS NULL OR (recorded_at, id) < ($7, $8))
+             ORDER BY recorded_at DESC, id DESC LIMIT $9",
+        )
+        .bind(&filter.actor)
+        .bind(&filter.action)
+        .bind(&filter.resource)
+        .bind(filter.succeeded)
+        .bind(filter.from)
+        .bind(filter.to)
+        .bind(filter.before.map(|(recorded_at, _)| recorded_at))
+        .bind(filter.before.map(|(_, id)| id))
+        .bind(filter.limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(audit_from_row).collect()
+    }
+}
+
+fn subscriber_from_row(row: &PgRow) -> Result<SubscriberRecord> {
+    let events: String = row.try_get("events")?;
+    Ok(SubscriberRecord {
+        id: row.try_get("id")?,
+        channel: row.try_get("channel")?,
+        target: row.try_get("target")?,
+        secret: row.try_get("secret")?,
+        events: events.split(',').filter(|event| !event.is_empty()).map(str::to_string).collect(),
+        risk_threshold: row.try_get::<Option<i16>, _>("risk_threshold")?.map(|threshold| threshold as u8),
+        maturity_notice_secs: row.try_get("maturity_notice_secs")?,
+        asset_id: row.try_get("asset_id")?,
+        borrower: row.try_get("borrower")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
+
+#[async_trait]
+impl SubscriberRepository for PostgresStorage {
+    async fn upsert_subscriber(&self, subscriber: &SubscriberRecord) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO notification_subscribers
+                (id, channel, target, secret, events, risk_threshold, maturity_notice_secs, asset_id, borrower, created_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+             ON CONFLICT (id) DO UPDATE SET
+                channel = EXCLUDED.channel,
+                target = EXCLUDED.target,
+                secret = EXCLUDED.secret,
+                events = EXCLUDED.events,
+                risk_threshold = EXCLUDED.risk_threshold,
+                maturity_notice_secs = EXCLUDED.maturity_notice_secs,
+                asset_id = EXCLUDED.asset_id,
+                borrower = EXCLUDED.borrower",
+        )
+        .bind(&subscriber.id)
+        .bind(&subscriber.channel)
+        .bind(&subscriber.target)
+        .bind(&subscriber.secret)
+        .bind(subscriber.events.join(","))
+        .bind(subscriber.risk_threshold.map(|threshold| threshold as i16))
+        .bind(subscriber.maturity_notice_secs)
+        .bind(&subscriber.asset_id)
+        .bind(&subscriber.borrower)
+        .bind(subscriber.created_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn remove_subscriber(&self, id: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM notification_subscribers WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn list_subscribers(&self) -> Result<Vec<SubscriberRecord>> {
+        let rows = sqlx::query("SELECT * FROM notification_subscribers ORDER BY id")
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter().map(subscriber_from_row).collect()
+    }
+}
+
+#[async_trait]
+impl TenantRepository for PostgresStorage {
+    async fn assign_to_tenant(&self, tenant: &str, kind: &str, id: &str, assigned_at: i64) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO tenant_resources (kind, id, tenant, assigned_at) VALUES ($1, $2, $3, $4)
+             ON CONFLICT (kind, id) DO NOTHING",
+        )
+        .bind(kind)
+        .bind(id)
+        .bind(tenant)
+        .bind(assigned_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn tenant_resources(&self, tenant: &str, kind: &str) -> Result<Vec<String>> {
+        let rows = sqlx::query("SELECT id FROM tenant_resources WHERE tenant = $1 AND kind = $2 ORDER BY id")
+            .bind(tenant)
+            .bind(kind)
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter().map(|row| Ok(row.try_get("id")?)).collect()
+    }
+}
+
+#[async_trait]
+impl HealthRepository for PostgresStorage {
+    async fn ping(&self) -> Result<()> {
+        sqlx::query("SELECT 1").execute(&self.pool).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,171 @@
+use anyhow::{anyhow, Result};
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::env;
+
+use crate::auth::Caller;
+use crate::error::ApiError;
+use crate::routes::AppState;
+use crate::signer::{self, PayerSigner, SignerSource};
+use crate::solana_client;
+use crate::visibility::Role;
+
+/// Resource kinds scoped to the tenant that created them.
+pub const ASSET: &str = "asset";
+pub const LOAN: &str = "loan";
+
+// ==================== Configuration ====================
+#[derive(Debug, Deserialize)]
+struct TenantConfig {
+    id: String,
+    /// API key to role name.
+    api_keys: HashMap<String, String>,
+    reads_per_minute: Option<u32>,
+    writes_per_minute: Option<u32>,
+    asset_types: Option<Vec<String>>,
+    payer: Option<SignerSource>,
+}
+
+/// A custodian or originator sharing the deployment: its callers only list
+/// the assets and loans it created, under its own rate limits.
+pub struct Tenant {
+    pub id: String,
+    /// Overrides `RATE_LIMIT_READS_PER_MINUTE`, shared by the tenant's keys.
+    pub reads_per_minute: Option<u32>,
+    /// Overrides `RATE_LIMIT_WRITES_PER_MINUTE`, shared by the tenant's keys.
+    pub writes_per_minute: Option<u32>,
+    /// Asset types the tenant may create; any when unset.
+    asset_types: Option<HashSet<String>>,
+    /// Signs and pays for the tenant's transactions instead of the service payer.
+    payer: Option<PayerSigner>,
+}
+
+impl Tenant {
+    pub fn check_asset_type(&self, asset_type: &str) -> Result<(), ApiError> {
+        match self.asset_types.as_ref().is_none_or(|types| types.contains(asset_type)) {
+            true => Ok(()),
+            false => Err(ApiError::Forbidden(format!("Tenant {} can't create {} assets", self.id, asset_type))),
+        }
+    }
+}
+
+// ==================== Tenant Registry ====================
+#[derive(Default)]
+pub struct TenantRegistry {
+    tenants: HashMap<String, Tenant>,
+    /// API key to its tenant and role.
+    keys: HashMap<String, (String, Role)>,
+}
+
+impl TenantRegistry {
+    /// Load `TENANTS`, a JSON array of `{id, api_keys, reads_per_minute,
+    /// writes_per_minute, asset_types, payer}` where `api_keys` maps each key
+    /// to its role and `payer` is a signer source as `POST /admin/payer/rotate`
+    /// takes it. Tenant keys take precedence over `API_KEY_ROLES`.
+    pub fn from_env() -> Result<Self> {
+        let Ok(raw) = env::var("TENANTS") else {
+            return Ok(Self::default());
+        };
+        let configs: Vec<TenantConfig> = serde_json::from_str(&raw)
+            .map_err(|e| anyhow!("Invalid TENANTS: {}", e))?;
+        let registry = Self::from_configs(configs)?;
+        tracing::info!("🏢 {} tenants configured", registry.tenants.len());
+        Ok(registry)
+    }
+
+    fn from_configs(configs: Vec<TenantConfig>) -> Result<Self> {
+        let mut registry = Self::default();
+        for config in configs {
+            if registry.tenants.contains_key(&config.id) {
+                return Err(anyhow!("Tenant {} is configured twice", config.id));
+            }
+            for (key, role) in config.api_keys {
+                let role = Role::parse(&role)
+                    .ok_or_else(|| anyhow!("Tenant {} has a key with unknown role {}", config.id, role))?;
+                // Admin routes act on the whole deployment
+                if role == Role::Admin {
+                    return Err(anyhow!("Tenant {} keys can't hold the admin role", config.id));
+                }
+                if registry.keys.insert(key, (config.id.clone(), role)).is_some() {
+                    return Err(anyhow!("Tenant {} reuses another tenant's API key", config.id));
+                }
+            }
+            let payer = config.payer.as_ref().map(signer::load).transpose()
+                .map_err(|e| anyhow!("Failed to load tenant {} payer: {}", config.id, e))?;
+            registry.tenants.insert(config.id.clone(), Tenant {
+                id: config.id,
+                reads_per_minute: config.reads_per_minute,
+                writes_per_minute: config.writes_per_minute,
+                asset_types: config.asset_types.map(|types| types.into_iter().collect()),
+                payer,
+            });
+        }
+        Ok(registry)
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Tenant> {
+        self.tenants.get(id)
+    }
+
+    /// The tenant and role of a tenant's API key.
+    pub fn lookup(&self, api_key: &str) -> Option<(&str, Role)> {
+        self.keys.get(api_key).map(|(tenant, role)| (tenant.as_str(), *role))
+    }
+
+    /// The tenant `caller` acts for, if any.
+    pub fn for_caller(&self, caller: Option<&Caller>) -> Option<&Tenant> {
+        caller.and_then(|caller| caller.tenant.as_deref()).and_then(|tenant| self.get(tenant))
+    }
+}
+
+/// Sign and pay for the request's transactions with its tenant's payer,
+/// when the tenant has one. Runs after `authenticate`.
+pub async fn use_payer(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let payer = state.tenants
+        .for_caller(request.extensions().get::<Caller>())
+        .and_then(|tenant| tenant.payer.clone());
+    match payer {
+        Some(payer) => solana_client::with_payer(payer, next.run(request)).await,
+        None => next.run(request).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(id: &str, keys: &[(&str, &str)]) -> TenantConfig {
+        TenantConfig {
+            id: id.to_string(),
+            api_keys: keys.iter().map(|(key, role)| (key.to_string(), role.to_string())).collect(),
+            reads_per_minute: None,
+            writes_per_minute: Some(10),
+            asset_types: Some(vec!["invoice".to_string()]),
+            payer: None,
+        }
+    }
+
+    #[test]
+    fn keys_resolve_to_their_tenant_and_admin_is_refused() {
+        let registry = TenantRegistry::from_configs(vec![
+            config("acme", &[("acme-1", "borrower"), ("acme-2", "lender")]),
+            config("globex", &[("globex-1", "oracle")]),
+        ]).unwrap();
+        assert_eq!(registry.lookup("acme-2"), Some(("acme", Role::Lender)));
+        assert_eq!(registry.lookup("globex-1"), Some(("globex", Role::Oracle)));
+        assert!(registry.lookup("other").is_none());
+        assert!(registry.get("acme").unwrap().check_asset_type("invoice").is_ok());
+        assert!(registry.get("acme").unwrap().check_asset_type("real_estate").is_err());
+
+        assert!(TenantRegistry::from_configs(vec![config("acme", &[("acme-1", "admin")])]).is_err());
+        assert!(TenantRegistry::from_configs(vec![
+            config("acme", &[("shared", "borrower")]),
+            config("globex", &[("shared", "borrower")]),
+        ]).is_err());
+    }
+}
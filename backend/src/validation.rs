@@ -0,0 +1,370 @@
+use serde::Serialize;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+use url::Url;
+use utoipa::ToSchema;
+
+use crate::error::ApiError;
+use crate::routes::{
+    AddOracleRequest, AirdropRequest, BatchCreateAssetRequest, BlacklistRequest, ChainlinkWebhookRequest, ComposeRequest, ComposeStep,
+    CreateAssetRequest, CreateLoanRequest, ProvenRiskEntry, RiskReportRequest, SetDelegateRequest,
+    SubmitTransactionRequest, SubscriberRequest, TransferAssetRequest, UpdateRiskRequest,
+};
+use crate::notifier;
+
+// The program's own limits; checked here so breaking them costs no fee
+const MAX_ASSET_ID_LEN: usize = 32;
+const MAX_ASSET_TYPE_LEN: usize = 32;
+const MAX_METADATA_URI_LEN: usize = 196;
+const MAX_RISK_SCORE: u8 = 100;
+const MAX_RISK_PROOF_DEPTH: usize = 24;
+const PORTFOLIO_ASSET_TYPE: &str = "portfolio";
+
+// The backend's policy: the program accepts more, but values past these
+// are typos or unit mistakes
+const MAX_VALUATION: u64 = 1_000_000_000_000_000;
+/// 100% a year.
+const MAX_INTEREST_RATE_BPS: u64 = 10_000;
+const MIN_LOAN_DURATION_SECS: i64 = 24 * 60 * 60;
+const MAX_LOAN_DURATION_SECS: i64 = 30 * 365 * 24 * 60 * 60;
+const MAX_COMPOSE_STEPS: usize = 16;
+const MAX_BATCH_ASSETS: usize = 100;
+/// Devnet grants at most 2 SOL per request.
+const MAX_AIRDROP_LAMPORTS: u64 = 2_000_000_000;
+const MAX_ORACLE_LABEL_LEN: usize = 64;
+const MAX_SUBSCRIBER_ID_LEN: usize = 64;
+const METADATA_URI_SCHEMES: &[&str] = &["https", "http", "ipfs", "ar"];
+
+// ==================== Field Errors ====================
+/// One field that failed validation, by its JSON path.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct FieldError {
+    /// e.g. `valuation` or `steps[2].asset_id`.
+    pub field: String,
+    pub message: String,
+}
+
+/// Collects every failing field of a request, so clients fix them in one go.
+#[derive(Debug, Default)]
+pub struct FieldErrors {
+    prefix: String,
+    errors: Vec<FieldError>,
+}
+
+impl FieldErrors {
+    fn add(&mut self, field: &str, message: impl Into<String>) {
+        self.errors.push(FieldError { field: format!("{}{}", self.prefix, field), message: message.into() });
+    }
+
+    /// Check a nested value, its fields reported under `path`.
+    fn nested(&mut self, path: String, value: &impl Validate) {
+        let inner = format!("{}{}.", self.prefix, path);
+        let outer = std::mem::replace(&mut self.prefix, inner);
+        value.check(self);
+        self.prefix = outer;
+    }
+
+    fn asset_id(&mut self, field: &str, value: &str) {
+        if value.is_empty() || value.len() > MAX_ASSET_ID_LEN {
+            self.add(field, format!("Must be 1 to {} characters", MAX_ASSET_ID_LEN));
+        } else if !value.bytes().all(|byte| byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.')) {
+            self.add(field, "Only letters, digits, '-', '_' and '.' are allowed");
+        }
+    }
+
+    fn pubkey(&mut self, field: &str, value: &str) {
+        if Pubkey::from_str(value).is_err() {
+            self.add(field, "Must be a base58 public key");
+        }
+    }
+
+    fn risk_score(&mut self, field: &str, value: u8) {
+        if value > MAX_RISK_SCORE {
+            self.add(field, format!("Must be at most {}", MAX_RISK_SCORE));
+        }
+    }
+
+    fn confidence(&mut self, field: &str, value: Option<f32>) {
+        if value.is_some_and(|value| !(0.0..=1.0).contains(&value)) {
+            self.add(field, "Must be between 0 and 1");
+        }
+    }
+
+    fn metadata_uri(&mut self, field: &str, value: &str) {
+        if value.len() > MAX_METADATA_URI_LEN {
+            return self.add(field, format!("Must be at most {} characters", MAX_METADATA_URI_LEN));
+        }
+        match Url::parse(value) {
+            Ok(url) if !METADATA_URI_SCHEMES.contains(&url.scheme()) => {
+                self.add(field, format!("Scheme must be one of {}", METADATA_URI_SCHEMES.join(", ")));
+            }
+            Ok(url) if url.scheme().starts_with("http") && url.host().is_none() => self.add(field, "Must name a host"),
+            Ok(_) => {}
+            Err(e) => self.add(field, format!("Must be a URL: {}", e)),
+        }
+    }
+
+    fn into_result(self) -> Result<(), ApiError> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ApiError::Validation(self.errors))
+        }
+    }
+}
+
+// ==================== Validate ====================
+/// Checks a request body before anything is sent on-chain.
+pub trait Validate {
+    fn check(&self, errors: &mut FieldErrors);
+
+    /// 422 with every failing field, or Ok.
+    fn validate(&self) -> Result<(), ApiError> {
+        let mut errors = FieldErrors::default();
+        self.check(&mut errors);
+        errors.into_result()
+    }
+}
+
+impl Validate for CreateAssetRequest {
+    fn check(&self, errors: &mut FieldErrors) {
+        errors.asset_id("asset_id", &self.asset_id);
+        if self.asset_type.is_empty() || self.asset_type.len() > MAX_ASSET_TYPE_LEN {
+            errors.add("asset_type", format!("Must be 1 to {} characters", MAX_ASSET_TYPE_LEN));
+        } else if self.asset_type == PORTFOLIO_ASSET_TYPE {
+            errors.add("asset_type", "Portfolios are created by bundling assets");
+        }
+        if !(1..=MAX_VALUATION).contains(&self.valuation) {
+            errors.add("valuation", format!("Must be between 1 and {}", MAX_VALUATION));
+        }
+        errors.metadata_uri("metadata_uri", &self.metadata_uri);
+        errors.pubkey("owner", &self.owner);
+    }
+}
+
+/// Only the batch's shape; each asset is validated on its own and fails
+/// alone.
+impl Validate for BatchCreateAssetRequest {
+    fn check(&self, errors: &mut FieldErrors) {
+        if self.assets.is_empty() || self.assets.len() > MAX_BATCH_ASSETS {
+            errors.add("assets", format!("Must hold 1 to {} assets", MAX_BATCH_ASSETS));
+        }
+        for (index, asset) in self.assets.iter().enumerate() {
+            if let Some(first) = self.assets[..index].iter().position(|other| other.asset_id == asset.asset_id) {
+                errors.add(&format!("assets[{}].asset_id", index), format!("Repeats assets[{}]", first));
+            }
+        }
+    }
+}
+
+impl Validate for UpdateRiskRequest {
+    fn check(&self, errors: &mut FieldErrors) {
+        errors.risk_score("risk_score", self.risk_score);
+        errors.confidence("confidence", self.confidence);
+    }
+}
+
+impl Validate for RiskReportRequest {
+    fn check(&self, errors: &mut FieldErrors) {
+        if self.report_id == 0 {
+            errors.add("report_id", "Must be positive");
+        }
+        if self.entries.is_empty() {
+            errors.add("entries", "A risk report needs at least one entry");
+        }
+        for (index, entry) in self.entries.iter().enumerate() {
+            errors.asset_id(&format!("entries[{}].asset_id", index), &entry.asset_id);
+            errors.risk_score(&format!("entries[{}].risk_score", index), entry.risk_score);
+        }
+    }
+}
+
+impl Validate for ProvenRiskEntry {
+    fn check(&self, errors: &mut FieldErrors) {
+        errors.asset_id("asset_id", &self.asset_id);
+        errors.risk_score("risk_score", self.risk_score);
+        if self.proof.len() > MAX_RISK_PROOF_DEPTH {
+            errors.add("proof", format!("At most {} nodes", MAX_RISK_PROOF_DEPTH));
+        }
+    }
+}
+
+impl Validate for SetDelegateRequest {
+    fn check(&self, errors: &mut FieldErrors) {
+        errors.pubkey("delegate", &self.delegate);
+    }
+}
+
+impl Validate for TransferAssetRequest {
+    fn check(&self, errors: &mut FieldErrors) {
+        errors.pubkey("new_owner", &self.new_owner);
+    }
+}
+
+impl Validate for CreateLoanRequest {
+    fn check(&self, errors: &mut FieldErrors) {
+        errors.asset_id("asset_id", &self.asset_id);
+        errors.pubkey("borrower", &self.borrower);
+        errors.pubkey("mint", &self.mint);
+        if self.loan_amount == 0 {
+            errors.add("loan_amount", "Must be positive");
+        }
+        if self.interest_rate > MAX_INTEREST_RATE_BPS {
+            errors.add("interest_rate", format!("Must be at most {} bps", MAX_INTEREST_RATE_BPS));
+        }
+        if !(MIN_LOAN_DURATION_SECS..=MAX_LOAN_DURATION_SECS).contains(&self.duration) {
+            errors.add(
+                "duration",
+                format!("Must be between {} and {} seconds", MIN_LOAN_DURATION_SECS, MAX_LOAN_DURATION_SECS),
+            );
+        }
+        if let Some(floating) = &self.floating_rate {
+            errors.pubkey("floating_rate.benchmark", &floating.benchmark);
+            if floating.floor_bps > floating.cap_bps {
+                errors.add("floating_rate.floor_bps", "Must not exceed cap_bps");
+            } else if !(floating.floor_bps..=floating.cap_bps).contains(&self.interest_rate) {
+                errors.add("interest_rate", "Must lie between floating_rate.floor_bps and cap_bps");
+            }
+            if floating.cap_bps > MAX_INTEREST_RATE_BPS {
+                errors.add("floating_rate.cap_bps", format!("Must be at most {} bps", MAX_INTEREST_RATE_BPS));
+            }
+        }
+    }
+}
+
+impl Validate for SubmitTransactionRequest {
+    fn check(&self, errors: &mut FieldErrors) {
+        if self.transaction.is_empty() {
+            errors.add("transaction", "Must not be empty");
+        }
+    }
+}
+
+impl Validate for AirdropRequest {
+    fn check(&self, errors: &mut FieldErrors) {
+        errors.pubkey("wallet", &self.wallet);
+        if !(1..=MAX_AIRDROP_LAMPORTS).contains(&self.lamports) {
+            errors.add("lamports", format!("Must be between 1 and {}", MAX_AIRDROP_LAMPORTS));
+        }
+        if self.token_amount == Some(0) {
+            errors.add("token_amount", "Must be positive");
+        }
+    }
+}
+
+impl Validate for ComposeStep {
+    fn check(&self, errors: &mut FieldErrors) {
+        match self {
+            ComposeStep::InitializeAsset(asset) => asset.check(errors),
+            ComposeStep::UpdateRiskScore { asset_id, risk_score, .. } => {
+                errors.asset_id("asset_id", asset_id);
+                errors.risk_score("risk_score", *risk_score);
+            }
+            ComposeStep::RequestLoan(loan) => loan.check(errors),
+            ComposeStep::ApproveLoan { loan_pda } | ComposeStep::RepayLoan { loan_pda } => {
+                errors.pubkey("loan_pda", loan_pda);
+            }
+        }
+    }
+}
+
+impl Validate for ComposeRequest {
+    fn check(&self, errors: &mut FieldErrors) {
+        if self.steps.is_empty() || self.steps.len() > MAX_COMPOSE_STEPS {
+            errors.add("steps", format!("Must hold 1 to {} steps", MAX_COMPOSE_STEPS));
+        }
+        for (index, step) in self.steps.iter().enumerate() {
+            errors.nested(format!("steps[{}]", index), step);
+        }
+    }
+}
+
+impl Validate for ChainlinkWebhookRequest {
+    fn check(&self, errors: &mut FieldErrors) {
+        if self.workflow_id.is_empty() {
+            errors.add("workflow_id", "Must not be empty");
+        }
+        errors.asset_id("asset_id", &self.asset_id);
+        errors.risk_score("risk_score", self.risk_score);
+        errors.confidence("confidence", Some(self.confidence));
+    }
+}
+
+impl Validate for BlacklistRequest {
+    fn check(&self, errors: &mut FieldErrors) {
+        errors.pubkey("address", &self.address);
+    }
+}
+
+impl Validate for AddOracleRequest {
+    fn check(&self, errors: &mut FieldErrors) {
+        errors.pubkey("pubkey", &self.pubkey);
+        if self.label.as_ref().is_some_and(|label| label.len() > MAX_ORACLE_LABEL_LEN) {
+            errors.add("label", format!("Must be at most {} characters", MAX_ORACLE_LABEL_LEN));
+        }
+    }
+}
+
+impl Validate for SubscriberRequest {
+    fn check(&self, errors: &mut FieldErrors) {
+        if self.id.is_empty() || self.id.len() > MAX_SUBSCRIBER_ID_LEN {
+            errors.add("id", format!("Must be 1 to {} characters", MAX_SUBSCRIBER_ID_LEN));
+        }
+        match self.channel.as_str() {
+            "webhook" | "slack" => match Url::parse(&self.target) {
+                Ok(url) if matches!(url.scheme(), "https" | "http") && url.host().is_some() => {}
+                _ => errors.add("target", "Must be an http(s) URL"),
+            },
+            "email" if !self.target.contains('@') => errors.add("target", "Must be an email address"),
+            "telegram" if self.target.is_empty() => errors.add("target", "Must be a chat id"),
+            "email" | "telegram" => {}
+            _ => errors.add("channel", format!("Must be one of {}", notifier::CHANNELS.join(", "))),
+        }
+        if self.events.is_empty() {
+            errors.add("events", "Must name at least one event");
+        }
+        for (i, event) in self.events.iter().enumerate() {
+            if !notifier::EVENTS.contains(&event.as_str()) {
+                errors.add(&format!("events[{}]", i), format!("Must be one of {}", notifier::EVENTS.join(", ")));
+            }
+        }
+        match self.risk_threshold {
+            Some(threshold) => errors.risk_score("risk_threshold", threshold),
+            None if self.events.iter().any(|event| event == "risk_threshold") => {
+                errors.add("risk_threshold", "Required for risk_threshold events");
+            }
+            None => {}
+        }
+        if self.maturity_notice_secs.is_some_and(|secs| secs <= 0) {
+            errors.add("maturity_notice_secs", "Must be positive");
+        }
+        if let Some(asset_id) = &self.asset_id {
+            errors.asset_id("asset_id", asset_id);
+        }
+        if let Some(borrower) = &self.borrower {
+            errors.pubkey("borrower", borrower);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_bad_field_is_reported_by_path() {
+        let request = ComposeRequest {
+            steps: vec![ComposeStep::InitializeAsset(CreateAssetRequest {
+                asset_id: "asset 001".to_string(),
+                asset_type: "real_estate".to_string(),
+                valuation: 0,
+                metadata_uri: "ftp://example.com/asset.json".to_string(),
+                owner: Pubkey::new_unique().to_string(),
+            })],
+        };
+        let mut errors = FieldErrors::default();
+        request.check(&mut errors);
+        let fields: Vec<&str> = errors.errors.iter().map(|error| error.field.as_str()).collect();
+        assert_eq!(fields, ["steps[0].asset_id", "steps[0].valuation", "steps[0].metadata_uri"]);
+    }
+}
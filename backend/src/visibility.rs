@@ -0,0 +1,138 @@
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::request::Parts,
+};
+use std::collections::HashMap;
+use std::env;
+
+use crate::auth::Caller;
+use crate::error::ApiError;
+use crate::routes::AppState;
+
+// ==================== Caller Roles ====================
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Public,
+    Borrower,
+    Lender,
+    /// Risk feeds: the only callers that can move risk scores.
+    Oracle,
+    Admin,
+}
+
+impl Role {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "public" => Some(Role::Public),
+            "borrower" => Some(Role::Borrower),
+            "lender" => Some(Role::Lender),
+            "oracle" => Some(Role::Oracle),
+            "admin" => Some(Role::Admin),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Role::Public => "public",
+            Role::Borrower => "borrower",
+            Role::Lender => "lender",
+            Role::Oracle => "oracle",
+            Role::Admin => "admin",
+        }
+    }
+
+    /// Response fields this role is not allowed to see.
+    fn hidden_fields(self) -> &'static [&'static str] {
+        match self {
+            Role::Public => &["owner", "borrower", "valuation", "metadata_uri", "principal"],
+            Role::Borrower => &["owner"],
+            Role::Lender => &["metadata_uri"],
+            Role::Oracle => &["owner", "borrower", "metadata_uri", "principal"],
+            Role::Admin => &[],
+        }
+    }
+
+    /// Whether this role may see `field`, and so filter on it.
+    pub fn can_see(self, field: &str) -> bool {
+        !self.hidden_fields().contains(&field)
+    }
+
+    /// Strip every field hidden from this role, at any depth of the response.
+    pub fn redact(self, value: &mut serde_json::Value) {
+        let hidden = self.hidden_fields();
+        if hidden.is_empty() {
+            return;
+        }
+        redact_fields(value, hidden);
+    }
+}
+
+fn redact_fields(value: &mut serde_json::Value, hidden: &[&str]) {
+    match value {
+        serde_json::Value::Object(map) => {
+            map.retain(|key, _| !hidden.contains(&key.as_str()));
+            for child in map.values_mut() {
+                redact_fields(child, hidden);
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for child in items {
+                redact_fields(child, hidden);
+            }
+        }
+        _ => {}
+    }
+}
+
+// ==================== Role Registry ====================
+
+/// Maps API keys to roles, loaded from `API_KEY_ROLES` ("key:role,key:role").
+#[derive(Debug, Default)]
+pub struct RoleRegistry {
+    keys: HashMap<String, Role>,
+}
+
+impl RoleRegistry {
+    pub fn from_env() -> Self {
+        let mut keys = HashMap::new();
+        for entry in env::var("API_KEY_ROLES").unwrap_or_default().split(',') {
+            let Some((key, role)) = entry.split_once(':') else { continue };
+            match Role::parse(role) {
+                Some(role) => {
+                    keys.insert(key.trim().to_string(), role);
+                }
+                None => tracing::warn!("⚠️ Ignoring API key with unknown role: {}", role),
+            }
+        }
+        Self { keys }
+    }
+
+    pub fn role_for(&self, api_key: Option<&str>) -> Role {
+        api_key
+            .and_then(|key| self.lookup(key))
+            .unwrap_or(Role::Public)
+    }
+
+    /// The role of a known API key.
+    pub fn lookup(&self, api_key: &str) -> Option<Role> {
+        self.keys.get(api_key).copied()
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for Role {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        if let Some(caller) = parts.extensions.get::<Caller>() {
+            return Ok(caller.role);
+        }
+        let api_key = parts
+            .headers
+            .get("x-api-key")
+            .and_then(|value| value.to_str().ok());
+        Ok(state.roles.role_for(api_key))
+    }
+}
@@ -0,0 +1,103 @@
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::json;
+use std::env;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use crate::solana_client::{self, PayerWallet, SolanaService};
+use crate::webhooks::WebhookRegistry;
+
+const DEFAULT_INTERVAL_SECS: u64 = 60;
+
+// ==================== Wallet Status ====================
+#[derive(Debug, Clone, Serialize)]
+pub struct WalletStatus {
+    #[serde(flatten)]
+    pub wallet: PayerWallet,
+    /// Balance left once pending creations have paid their rent.
+    pub available: u64,
+    pub threshold: u64,
+    pub low_balance: bool,
+    pub checked_at: i64,
+}
+
+pub struct WalletMonitor {
+    threshold: u64,
+    latest: RwLock<Option<WalletStatus>>,
+    /// Whether the last check was below the threshold; a notification goes
+    /// out on each drop, not on every check while it stays low.
+    low: AtomicBool,
+}
+
+impl WalletMonitor {
+    /// `PAYER_LOW_BALANCE_LAMPORTS` defaults to `PAYER_MIN_BALANCE_LAMPORTS`,
+    /// the balance a payer must hold to be rotated in.
+    pub fn from_env() -> Self {
+        let threshold = env::var("PAYER_LOW_BALANCE_LAMPORTS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or_else(solana_client::payer_min_balance);
+        Self {
+            threshold,
+            latest: RwLock::new(None),
+            low: AtomicBool::new(false),
+        }
+    }
+
+    /// Status as of the last check, if one has succeeded.
+    pub fn latest(&self) -> Option<WalletStatus> {
+        self.latest.read().unwrap().clone()
+    }
+
+    pub async fn check(&self, solana: &SolanaService, webhooks: &WebhookRegistry) -> Result<WalletStatus> {
+        let wallet = solana.payer_wallet().await?;
+        let available = wallet.balance.saturating_sub(wallet.pending_rent);
+        let low_balance = available < self.threshold;
+        let status = WalletStatus {
+            wallet,
+            available,
+            threshold: self.threshold,
+            low_balance,
+            checked_at: chrono::Utc::now().timestamp(),
+        };
+        *self.latest.write().unwrap() = Some(status.clone());
+
+        let was_low = self.low.swap(low_balance, Ordering::Relaxed);
+        if low_balance && !was_low {
+            tracing::warn!(
+                "⚠️ Payer {} has {} lamports available, below {}",
+                status.wallet.payer, available, self.threshold,
+            );
+            webhooks.publish("wallet.low_balance", json!({
+                "payer": status.wallet.payer,
+                "balance": status.wallet.balance,
+                "pending_rent": status.wallet.pending_rent,
+                "available": available,
+                "threshold": self.threshold,
+            })).await;
+        } else if was_low && !low_balance {
+            tracing::info!("✅ Payer {} is back above {} lamports", status.wallet.payer, self.threshold);
+        }
+        Ok(status)
+    }
+}
+
+/// Check the payer every `WALLET_MONITOR_INTERVAL_SECS` (default 60).
+pub fn spawn(monitor: Arc<WalletMonitor>, solana: Arc<SolanaService>, webhooks: Arc<WebhookRegistry>) {
+    let interval = env::var("WALLET_MONITOR_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_INTERVAL_SECS);
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(interval.max(1)));
+        loop {
+            ticker.tick().await;
+            if let Err(e) = monitor.check(&solana, &webhooks).await {
+                tracing::warn!("⚠️ Wallet check failed: {}", e);
+            }
+        }
+    });
+}
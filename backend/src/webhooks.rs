@@ -0,0 +1,277 @@
+use anyhow::{anyhow, Result};
+use hmac::{Hmac, Mac};
+use reqwest::Client as HttpClient;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::env;
+
+// ==================== Event Catalog ====================
+#[derive(Debug, Clone, Serialize)]
+pub struct EventDescriptor {
+    pub event: &'static str,
+    pub description: &'static str,
+    /// JSON Schema of the envelope's `data` field for this event.
+    pub schema: Value,
+    pub sample: Value,
+}
+
+fn object_schema(properties: Value, required: &[&str]) -> Value {
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false
+    })
+}
+
+/// Every event type delivered to webhook subscribers.
+pub fn catalog() -> Vec<EventDescriptor> {
+    let pubkey = json!({ "type": "string", "description": "Base58 public key" });
+    let signature = json!({ "type": "string", "description": "Base58 transaction signature" });
+
+    vec![
+        EventDescriptor {
+            event: "asset.created",
+            description: "A real-world asset was registered on-chain",
+            schema: object_schema(
+                json!({
+                    "asset_id": { "type": "string" },
+                    "asset_pda": pubkey,
+                    "asset_type": { "type": "string" },
+                    "valuation": { "type": "integer", "minimum": 0 },
+                    "owner": pubkey,
+                    "transaction": signature
+                }),
+                &["asset_id", "asset_pda", "asset_type", "valuation", "owner", "transaction"],
+            ),
+            sample: json!({
+                "asset_id": "asset-001",
+                "asset_pda": "7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU",
+                "asset_type": "real_estate",
+                "valuation": 50000000,
+                "owner": "AQ68XzKR3fjGypbKi6Ai23vUBTTbEhuKg6EY4uBqAfVY",
+                "transaction": "5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnbJLgp8uirBgmQpjKhoR4tjF3ZpRzrFmBV6UjKdiSZkQUW"
+            }),
+        },
+        EventDescriptor {
+            event: "asset.risk_updated",
+            description: "An asset's risk score changed",
+            schema: object_schema(
+                json!({
+                    "asset_id": { "type": "string" },
+                    "risk_score": { "type": "integer", "minimum": 0, "maximum": 100 },
+                    "source": { "type": "string" },
+                    "transaction": signature
+                }),
+                &["asset_id", "risk_score", "source", "transaction"],
+            ),
+            sample: json!({
+                "asset_id": "asset-001",
+                "risk_score": 35,
+                "source": "chainlink",
+                "transaction": "5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnbJLgp8uirBgmQpjKhoR4tjF3ZpRzrFmBV6UjKdiSZkQUW"
+            }),
+        },
+        EventDescriptor {
+            event: "asset.status_changed",
+            description: "An asset was deactivated or reactivated",
+            schema: object_schema(
+                json!({
+                    "asset_id": { "type": "string" },
+                    "is_active": { "type": "boolean" },
+                    "transaction": signature
+                }),
+                &["asset_id", "is_active", "transaction"],
+            ),
+            sample: json!({
+                "asset_id": "asset-001",
+                "is_active": false,
+                "transaction": "5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnbJLgp8uirBgmQpjKhoR4tjF3ZpRzrFmBV6UjKdiSZkQUW"
+            }),
+        },
+        EventDescriptor {
+            event: "loan.requested",
+            description: "A borrower applied for a loan; nothing is disbursed yet",
+            schema: object_schema(
+                json!({
+                    "loan_pda": pubkey,
+                    "asset_id": { "type": "string" },
+                    "borrower": pubkey,
+                    "principal": { "type": "integer", "minimum": 0 },
+                    "interest_rate": { "type": "integer", "minimum": 0, "description": "Basis points per year" },
+                    "duration": { "type": "integer", "minimum": 1, "description": "Seconds" },
+                    "interest_mode": { "type": "string", "enum": ["simple", "daily_compounding", "continuous"] },
+                    "transaction": signature
+                }),
+                &["loan_pda", "asset_id", "borrower", "principal", "interest_rate", "duration", "interest_mode", "transaction"],
+            ),
+            sample: json!({
+                "loan_pda": "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin",
+                "asset_id": "asset-001",
+                "borrower": "AQ68XzKR3fjGypbKi6Ai23vUBTTbEhuKg6EY4uBqAfVY",
+                "principal": 17500000,
+                "interest_rate": 500,
+                "duration": 2592000,
+                "interest_mode": "simple",
+                "transaction": "5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnbJLgp8uirBgmQpjKhoR4tjF3ZpRzrFmBV6UjKdiSZkQUW"
+            }),
+        },
+        EventDescriptor {
+            event: "loan.status_changed",
+            description: "A loan was approved and disbursed, or its request was cancelled",
+            schema: object_schema(
+                json!({
+                    "loan_pda": pubkey,
+                    "status": { "type": "string", "enum": ["active", "cancelled"] },
+                    "transaction": signature
+                }),
+                &["loan_pda", "status", "transaction"],
+            ),
+            sample: json!({
+                "loan_pda": "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin",
+                "status": "active",
+                "transaction": "5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnbJLgp8uirBgmQpjKhoR4tjF3ZpRzrFmBV6UjKdiSZkQUW"
+            }),
+        },
+        EventDescriptor {
+            event: "wallet.low_balance",
+            description: "The payer's balance, less rent owed by pending account creations, fell below the threshold",
+            schema: object_schema(
+                json!({
+                    "payer": pubkey,
+                    "balance": { "type": "integer", "minimum": 0, "description": "Lamports" },
+                    "pending_rent": { "type": "integer", "minimum": 0, "description": "Lamports" },
+                    "available": { "type": "integer", "minimum": 0, "description": "Lamports" },
+                    "threshold": { "type": "integer", "minimum": 0, "description": "Lamports" }
+                }),
+                &["payer", "balance", "pending_rent", "available", "threshold"],
+            ),
+            sample: json!({
+                "payer": "AQ68XzKR3fjGypbKi6Ai23vUBTTbEhuKg6EY4uBqAfVY",
+                "balance": 412000000,
+                "pending_rent": 4106880,
+                "available": 407893120,
+                "threshold": 500000000
+            }),
+        },
+    ]
+}
+
+/// Schema shared by every delivery body.
+pub fn envelope_schema() -> Value {
+    object_schema(
+        json!({
+            "id": { "type": "string" },
+            "event": { "type": "string" },
+            "created_at": { "type": "integer", "description": "Unix seconds" },
+            "test": { "type": "boolean" },
+            "data": { "type": "object" }
+        }),
+        &["id", "event", "created_at", "test", "data"],
+    )
+}
+
+// ==================== Subscribers ====================
+#[derive(Debug, Clone, Deserialize)]
+pub struct WebhookEndpoint {
+    pub id: String,
+    pub url: String,
+    pub secret: String,
+}
+
+pub struct WebhookRegistry {
+    endpoints: Vec<WebhookEndpoint>,
+    http_client: HttpClient,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DeliveryResult {
+    pub delivery_id: String,
+    pub event: String,
+    pub status_code: u16,
+    pub delivered: bool,
+}
+
+impl WebhookRegistry {
+    /// Load subscribers from `WEBHOOK_ENDPOINTS`, a JSON array of `{id, url, secret}`.
+    pub fn from_env() -> Self {
+        let endpoints = match env::var("WEBHOOK_ENDPOINTS") {
+            Ok(raw) => serde_json::from_str(&raw).unwrap_or_else(|e| {
+                tracing::warn!("⚠️ Ignoring invalid WEBHOOK_ENDPOINTS: {}", e);
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+        Self {
+            endpoints,
+            http_client: HttpClient::new(),
+        }
+    }
+
+    pub fn endpoint(&self, id: &str) -> Option<&WebhookEndpoint> {
+        self.endpoints.iter().find(|endpoint| endpoint.id == id)
+    }
+
+    /// Send the catalog sample for `event` to one subscriber, flagged as a test.
+    pub async fn send_test(&self, endpoint: &WebhookEndpoint, event: &str) -> Result<DeliveryResult> {
+        let descriptor = catalog()
+            .into_iter()
+            .find(|descriptor| descriptor.event == event)
+            .ok_or_else(|| anyhow!("Unknown event type: {}", event))?;
+
+        self.post(endpoint, descriptor.event, &descriptor.sample, true).await
+    }
+
+    /// Deliver `event` to every subscriber. Failures are logged, not retried.
+    pub async fn publish(&self, event: &'static str, data: Value) {
+        for endpoint in &self.endpoints {
+            match self.post(endpoint, event, &data, false).await {
+                Ok(result) if result.delivered => {},
+                Ok(result) => tracing::warn!("⚠️ Webhook {} answered {} to {}", endpoint.id, result.status_code, event),
+                Err(e) => tracing::warn!("⚠️ Webhook {} missed {}: {}", endpoint.id, event, e),
+            }
+        }
+    }
+
+    async fn post(&self, endpoint: &WebhookEndpoint, event: &str, data: &Value, test: bool) -> Result<DeliveryResult> {
+        let now = chrono::Utc::now();
+        let prefix = if test { "test" } else { "evt" };
+        let delivery_id = format!("{}_{}", prefix, now.timestamp_nanos_opt().unwrap_or_default());
+        let body = json!({
+            "id": delivery_id,
+            "event": event,
+            "created_at": now.timestamp(),
+            "test": test,
+            "data": data,
+        })
+        .to_string();
+
+        let response = self.http_client
+            .post(&endpoint.url)
+            .header("Content-Type", "application/json")
+            .header("X-Webhook-Id", &delivery_id)
+            .header("X-Webhook-Event", event)
+            .header("X-Webhook-Signature", sign(&endpoint.secret, now.timestamp(), &body)?)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| anyhow!("Delivery failed: {}", e))?;
+
+        Ok(DeliveryResult {
+            delivery_id,
+            event: event.to_string(),
+            status_code: response.status().as_u16(),
+            delivered: response.status().is_success(),
+        })
+    }
+}
+
+/// `t=<unix>,v1=<hex HMAC-SHA256 of "<unix>.<body>">`; receivers recompute it
+/// with their secret and reject stale timestamps to prevent replays.
+pub fn sign(secret: &str, timestamp: i64, body: &str) -> Result<String> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .map_err(|e| anyhow!("Invalid webhook secret: {}", e))?;
+    mac.update(format!("{}.{}", timestamp, body).as_bytes());
+    Ok(format!("t={},v1={}", timestamp, hex::encode(mac.finalize().into_bytes())))
+}
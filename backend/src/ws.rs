@@ -0,0 +1,224 @@
+use axum::{
+    extract::{
+        ws::{close_code, CloseFrame, Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    response::Response,
+    Extension,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::{interval, timeout, Instant, MissedTickBehavior};
+
+use crate::auth::{Caller, RouteGroup};
+use crate::live_events::Channel;
+use crate::routes::AppState;
+use crate::visibility::Role;
+
+const HEARTBEAT_INTERVAL_SECS: u64 = 30;
+/// A client silent this long, pongs included, is gone.
+const IDLE_TIMEOUT_SECS: u64 = 75;
+/// Time an unauthenticated socket has to send its credentials.
+const AUTH_TIMEOUT_SECS: u64 = 10;
+/// A client that takes longer than this to accept a message is stalled.
+const SEND_TIMEOUT_SECS: u64 = 10;
+const MAX_CHANNELS: usize = 100;
+
+// ==================== Protocol ====================
+#[derive(Debug, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum ClientMessage {
+    /// Credentials, for clients that couldn't send headers on the upgrade.
+    Auth { api_key: Option<String>, token: Option<String> },
+    Subscribe { channels: Vec<String> },
+    Unsubscribe { channels: Vec<String> },
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ServerMessage<'a> {
+    Authenticated { role: &'static str },
+    /// Every channel the socket is now subscribed to.
+    Subscribed { channels: Vec<&'a str> },
+    Unsubscribed { channels: Vec<&'a str> },
+    /// Events dropped because the client read too slowly.
+    Lagged { missed: u64 },
+    Error { message: String },
+}
+
+/// A live event, tagged with the subscribed channels it matched.
+#[derive(Serialize)]
+struct Delivery<'a> {
+    channels: Vec<&'a str>,
+    #[serde(flatten)]
+    event: serde_json::Value,
+}
+
+// ==================== Sockets ====================
+/// Upgrade to a socket subscribing to live events by channel: `asset:{id}`,
+/// `borrower:{pubkey}` or `liquidations`. Clients send JSON with an `op`
+/// of auth, subscribe or unsubscribe; the server sends JSON with a `type`,
+/// events being those of the SSE streams plus the channels they matched.
+///
+/// Where reads need credentials and none came with the upgrade, the first
+/// message must be an auth op.
+#[utoipa::path(
+    get,
+    path = "/ws",
+    tag = "assets",
+    responses(
+        (status = 101, description = "Switching to the WebSocket protocol"),
+        (status = 401, description = "Invalid credentials on the upgrade", body = ErrorResponse)
+    )
+)]
+pub async fn upgrade(
+    State(state): State<AppState>,
+    caller: Option<Extension<Caller>>,
+    socket: WebSocketUpgrade,
+) -> Response {
+    let caller = caller.map(|Extension(caller)| caller);
+    socket.on_upgrade(move |socket| serve(socket, state, caller))
+}
+
+async fn send(socket: &mut WebSocket, message: &impl Serialize) -> bool {
+    let text = serde_json::to_string(message).unwrap();
+    matches!(
+        timeout(Duration::from_secs(SEND_TIMEOUT_SECS), socket.send(Message::Text(text))).await,
+        Ok(Ok(()))
+    )
+}
+
+async fn close(mut socket: WebSocket, code: u16, reason: &'static str) {
+    let _ = socket.send(Message::Close(Some(CloseFrame { code, reason: reason.into() }))).await;
+}
+
+/// Check credentials sent in an auth message against the reads group.
+fn authenticate(state: &AppState, api_key: Option<String>, token: Option<String>) -> Result<Caller, String> {
+    let caller = state.auth
+        .identify(&state.roles, &state.tenants, api_key.as_deref(), token.as_deref())
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Send an api_key or a token".to_string())?;
+    state.auth.admit(RouteGroup::Reads, Some(&caller)).map_err(|e| e.to_string())?;
+    Ok(caller)
+}
+
+async fn serve(mut socket: WebSocket, state: AppState, mut caller: Option<Caller>) {
+    if caller.is_none() && state.auth.admit(RouteGroup::Reads, None).is_err() {
+        let message = timeout(Duration::from_secs(AUTH_TIMEOUT_SECS), socket.recv()).await;
+        let Ok(Some(Ok(Message::Text(text)))) = message else {
+            return close(socket, close_code::POLICY, "Authentication required").await;
+        };
+        let authenticated = match serde_json::from_str(&text) {
+            Ok(ClientMessage::Auth { api_key, token }) => authenticate(&state, api_key, token),
+            _ => Err("Authentication required".to_string()),
+        };
+        match authenticated {
+            Ok(authenticated) => {
+                if !send(&mut socket, &ServerMessage::Authenticated { role: authenticated.role.name() }).await {
+                    return;
+                }
+                caller = Some(authenticated);
+            }
+            Err(message) => {
+                let _ = send(&mut socket, &ServerMessage::Error { message }).await;
+                return close(socket, close_code::POLICY, "Authentication failed").await;
+            }
+        }
+    }
+    let mut role = caller.as_ref().map_or(Role::Public, |caller| caller.role);
+    tracing::info!("🔌 Socket connected ({})", role.name());
+
+    let mut events = state.events.subscribe();
+    let mut channels: BTreeMap<String, Channel> = BTreeMap::new();
+    let mut heartbeat = interval(Duration::from_secs(HEARTBEAT_INTERVAL_SECS));
+    heartbeat.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut last_seen = Instant::now();
+
+    loop {
+        tokio::select! {
+            message = socket.recv() => {
+                let text = match message {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | None | Some(Err(_)) => break,
+                    Some(Ok(_)) => {
+                        last_seen = Instant::now();
+                        continue;
+                    }
+                };
+                last_seen = Instant::now();
+                let reply = match serde_json::from_str(&text) {
+                    Ok(ClientMessage::Auth { api_key, token }) => match authenticate(&state, api_key, token) {
+                        Ok(authenticated) => {
+                            role = authenticated.role;
+                            channels.retain(|_, channel| !matches!(channel, Channel::Borrower(_)) || role.can_see("borrower"));
+                            ServerMessage::Authenticated { role: role.name() }
+                        }
+                        Err(message) => ServerMessage::Error { message },
+                    },
+                    Ok(ClientMessage::Subscribe { channels: names }) => {
+                        let parsed = names.into_iter()
+                            .map(|name| Channel::parse(&name, &state, role).map(|channel| (name, channel)))
+                            .collect::<Result<Vec<_>, _>>();
+                        match parsed {
+                            Err(message) => ServerMessage::Error { message },
+                            Ok(parsed) => {
+                                channels.extend(parsed);
+                                if channels.len() > MAX_CHANNELS {
+                                    let message = format!("At most {} channels per socket", MAX_CHANNELS);
+                                    let _ = send(&mut socket, &ServerMessage::Error { message }).await;
+                                    return close(socket, close_code::POLICY, "Too many channels").await;
+                                }
+                                ServerMessage::Subscribed { channels: channels.keys().map(String::as_str).collect() }
+                            }
+                        }
+                    }
+                    Ok(ClientMessage::Unsubscribe { channels: names }) => {
+                        for name in &names {
+                            channels.remove(name);
+                        }
+                        ServerMessage::Unsubscribed { channels: channels.keys().map(String::as_str).collect() }
+                    }
+                    Err(e) => ServerMessage::Error { message: format!("Invalid message: {}", e) },
+                };
+                if !send(&mut socket, &reply).await {
+                    break;
+                }
+            }
+            event = events.recv() => {
+                let delivered = match event {
+                    Ok(event) => {
+                        let matched: Vec<&str> = channels.iter()
+                            .filter(|(_, channel)| channel.matches(&event))
+                            .map(|(name, _)| name.as_str())
+                            .collect();
+                        if matched.is_empty() {
+                            continue;
+                        }
+                        let mut data = serde_json::to_value(&event).unwrap();
+                        role.redact(&mut data);
+                        send(&mut socket, &Delivery { channels: matched, event: data }).await
+                    }
+                    // The broadcast buffer absorbed what it could; say what was dropped
+                    Err(RecvError::Lagged(missed)) => send(&mut socket, &ServerMessage::Lagged { missed }).await,
+                    Err(RecvError::Closed) => break,
+                };
+                if !delivered {
+                    tracing::warn!("⚠️ Dropping stalled socket ({})", role.name());
+                    return close(socket, close_code::AGAIN, "Client too slow").await;
+                }
+            }
+            _ = heartbeat.tick() => {
+                if last_seen.elapsed() > Duration::from_secs(IDLE_TIMEOUT_SECS) {
+                    return close(socket, close_code::AWAY, "Heartbeat timeout").await;
+                }
+                let sent = timeout(Duration::from_secs(SEND_TIMEOUT_SECS), socket.send(Message::Ping(Vec::new()))).await;
+                if !matches!(sent, Ok(Ok(()))) {
+                    break;
+                }
+            }
+        }
+    }
+    tracing::info!("🔌 Socket closed ({})", role.name());
+}
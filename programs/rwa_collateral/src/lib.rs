@@ -1,4 +1,11 @@
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::{invoke, invoke_signed},
+};
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
+use solana_sha256_hasher::hashv;
 
 declare_id!("3ekhJkk57HSt8Rfj44fmgjhix9UXTJVBi6ZQEz7Hs5Po");
 
@@ -6,233 +13,7822 @@ declare_id!("3ekhJkk57HSt8Rfj44fmgjhix9UXTJVBi6ZQEz7Hs5Po");
 pub mod rwa_collateral {
     use super::*;
 
-    // Initialize a new RWA asset
-    pub fn initialize_asset(
-        ctx: Context<InitializeAsset>,
-        asset_id: String,
-        asset_type: String,
-        valuation: u64,
-        metadata_uri: String,
+    // Initialize protocol config; the signer becomes the admin
+    pub fn initialize_config(ctx: Context<InitializeConfig>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        
+        config.admin = *ctx.accounts.admin.key;
+        config.bump = ctx.bumps.config;
+        config.arbitrator = *ctx.accounts.admin.key;
+        config.reserve_factor_bps = 0;
+        config.compliance_authority = *ctx.accounts.admin.key;
+        config.risk_oracle = *ctx.accounts.admin.key;
+        config.risk_jump_threshold = 0;
+        config.breaker_cooldown = 0;
+        config.lender_compliance_authority = *ctx.accounts.admin.key;
+        config.lender_whitelist_required = false;
+        config.origination_fee_bps = 0;
+        config.referral_share_bps = 0;
+        config.max_risk_age = 0;
+        
+        msg!("Protocol config initialized, admin: {}", config.admin);
+        Ok(())
+    }
+
+    // Appoint the authority that resolves loan disputes (admin only)
+    pub fn set_arbitrator(ctx: Context<SetArbitrator>, arbitrator: Pubkey) -> Result<()> {
+        require!(arbitrator != Pubkey::default(), ErrorCode::InvalidArbitrator);
+        
+        ctx.accounts.config.arbitrator = arbitrator;
+        
+        msg!("Arbitrator set: {}", arbitrator);
+        Ok(())
+    }
+
+    // Appoint the oracle that posts batched risk reports (admin only)
+    pub fn set_risk_oracle(ctx: Context<SetRiskOracle>, risk_oracle: Pubkey) -> Result<()> {
+        require!(risk_oracle != Pubkey::default(), ErrorCode::InvalidRiskOracle);
+        
+        ctx.accounts.config.risk_oracle = risk_oracle;
+        
+        msg!("Risk oracle set: {}", risk_oracle);
+        Ok(())
+    }
+
+    // Approve a custodian to attest to asset documents (admin only)
+    pub fn register_custodian(ctx: Context<RegisterCustodian>, custodian: Pubkey) -> Result<()> {
+        let record = &mut ctx.accounts.custodian_record;
+        record.custodian = custodian;
+        record.bump = ctx.bumps.custodian_record;
+        
+        msg!("Custodian registered: {}", custodian);
+        Ok(())
+    }
+
+    // Withdraw a custodian's approval; its attestations stop backing new loans (admin only)
+    pub fn deregister_custodian(ctx: Context<DeregisterCustodian>) -> Result<()> {
+        msg!("Custodian deregistered: {}", ctx.accounts.custodian_record.custodian);
+        Ok(())
+    }
+
+    // Record the custody documents for an asset (registered custodian only)
+    pub fn attest_custody(
+        ctx: Context<AttestCustody>,
+        document_hash: [u8; 32],
+        expires_at: i64,
     ) -> Result<()> {
-        let asset = &mut ctx.accounts.asset;
+        let now = Clock::get()?.unix_timestamp;
+        require!(expires_at > now, ErrorCode::InvalidAttestationExpiry);
         
-        asset.asset_id = asset_id;
-        asset.asset_type = asset_type;
-        asset.valuation = valuation;
-        asset.metadata_uri = metadata_uri;
-        asset.owner = *ctx.accounts.owner.key;
-        asset.is_active = true;
-        asset.risk_score = 50; // Default medium risk
-        asset.bump = ctx.bumps.asset;
+        let attestation = &mut ctx.accounts.custody_attestation;
+        attestation.asset = ctx.accounts.asset.key();
+        attestation.custodian = ctx.accounts.custodian.key();
+        attestation.document_hash = document_hash;
+        attestation.attested_at = now;
+        attestation.expires_at = expires_at;
+        attestation.bump = ctx.bumps.custody_attestation;
         
-        msg!("Asset created: {}", asset.asset_id);
+        msg!("Custody attested for {} until {}", ctx.accounts.asset.asset_id, expires_at);
         Ok(())
     }
 
-    // Update risk score (called by AI oracle)
-    pub fn update_risk_score(
-        ctx: Context<UpdateRiskScore>,
-        new_risk_score: u8,
+    // Replace the documents or extend the expiry of an attestation (attesting custodian only)
+    pub fn renew_custody_attestation(
+        ctx: Context<RenewCustodyAttestation>,
+        document_hash: [u8; 32],
+        expires_at: i64,
     ) -> Result<()> {
-        let asset = &mut ctx.accounts.asset;
+        let now = Clock::get()?.unix_timestamp;
+        require!(expires_at > now, ErrorCode::InvalidAttestationExpiry);
         
-        require!(asset.is_active, ErrorCode::AssetInactive);
-        require!(new_risk_score <= 100, ErrorCode::InvalidRiskScore);
+        let attestation = &mut ctx.accounts.custody_attestation;
+        attestation.document_hash = document_hash;
+        attestation.attested_at = now;
+        attestation.expires_at = expires_at;
+        
+        msg!("Custody attestation renewed until {}", expires_at);
+        Ok(())
+    }
+
+    // Appoint the authority that manages the borrower blacklist (admin only)
+    pub fn set_compliance_authority(ctx: Context<SetComplianceAuthority>, compliance_authority: Pubkey) -> Result<()> {
+        require!(compliance_authority != Pubkey::default(), ErrorCode::InvalidComplianceAuthority);
         
-        asset.risk_score = new_risk_score;
+        ctx.accounts.config.compliance_authority = compliance_authority;
         
-        msg!("Risk score updated to: {}", new_risk_score);
+        msg!("Compliance authority set: {}", compliance_authority);
         Ok(())
     }
 
-    // Create loan against RWA
-    pub fn create_loan(
-        ctx: Context<CreateLoan>,
-        loan_amount: u64,
-        interest_rate: u64, // basis points (1% = 100)
-        duration: i64,      // in seconds
+    // Create the empty blacklist (admin only)
+    pub fn initialize_blacklist(ctx: Context<InitializeBlacklist>) -> Result<()> {
+        let blacklist = &mut ctx.accounts.blacklist;
+        blacklist.entries = Vec::new();
+        blacklist.bump = ctx.bumps.blacklist;
+        
+        msg!("Blacklist initialized");
+        Ok(())
+    }
+
+    // Bar an address from borrowing or lending (compliance authority only)
+    pub fn add_to_blacklist(ctx: Context<ManageBlacklist>, wallet: Pubkey) -> Result<()> {
+        let blacklist = &mut ctx.accounts.blacklist;
+        
+        require!(!blacklist.contains(&wallet), ErrorCode::AlreadyBlacklisted);
+        require!(blacklist.entries.len() < MAX_BLACKLIST_LEN, ErrorCode::BlacklistFull);
+        blacklist.entries.push(wallet);
+        
+        msg!("Blacklisted: {}", wallet);
+        Ok(())
+    }
+
+    // Lift a blacklisting (compliance authority only)
+    pub fn remove_from_blacklist(ctx: Context<ManageBlacklist>, wallet: Pubkey) -> Result<()> {
+        let blacklist = &mut ctx.accounts.blacklist;
+        
+        let index = blacklist.entries
+            .iter()
+            .position(|entry| *entry == wallet)
+            .ok_or(ErrorCode::NotBlacklisted)?;
+        blacklist.entries.swap_remove(index);
+        
+        msg!("Removed from blacklist: {}", wallet);
+        Ok(())
+    }
+
+    // Choose who manages lender memberships and whether lenders must hold one (admin only)
+    pub fn configure_lender_whitelist(
+        ctx: Context<ConfigureLenderWhitelist>,
+        lender_compliance_authority: Pubkey,
+        required: bool,
     ) -> Result<()> {
-        let loan = &mut ctx.accounts.loan;
-        let asset = &ctx.accounts.asset;
+        require!(lender_compliance_authority != Pubkey::default(), ErrorCode::InvalidComplianceAuthority);
         
-        // Calculate max loan based on risk score
-        let max_ltv = match asset.risk_score {
-            0..=20 => 70,  // Low risk: 70% LTV
-            21..=40 => 60, // Medium-low: 60% LTV
-            41..=60 => 50, // Medium: 50% LTV
-            61..=80 => 35, // Medium-high: 35% LTV
-            81..=100 => 20, // High risk: 20% LTV
-            _ => 0,
-        };
+        let config = &mut ctx.accounts.config;
+        config.lender_compliance_authority = lender_compliance_authority;
+        config.lender_whitelist_required = required;
         
-        let max_loan = (asset.valuation as u128 * max_ltv as u128 / 100) as u64;
-        require!(loan_amount <= max_loan, ErrorCode::LoanTooHigh);
+        msg!("Lender whitelist {}, authority: {}", if required { "required" } else { "optional" }, lender_compliance_authority);
+        Ok(())
+    }
+
+    // Admit an accredited lender until `expires_at` (lender compliance authority only)
+    pub fn grant_lender_membership(
+        ctx: Context<GrantLenderMembership>,
+        lender: Pubkey,
+        expires_at: i64,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(expires_at > now, ErrorCode::InvalidMembershipExpiry);
         
-        loan.borrower = *ctx.accounts.borrower.key;
-        loan.asset = asset.key();
-        loan.principal = loan_amount;
-        loan.interest_rate = interest_rate;
-        loan.start_time = Clock::get()?.unix_timestamp;
-        loan.end_time = loan.start_time + duration;
-        loan.is_active = true;
-        loan.risk_score_at_creation = asset.risk_score;
-        loan.bump = ctx.bumps.loan;
+        let membership = &mut ctx.accounts.lender_membership;
+        membership.lender = lender;
+        membership.granted_at = now;
+        membership.expires_at = expires_at;
+        membership.bump = ctx.bumps.lender_membership;
         
-        msg!("Loan created: {} for asset {}", loan_amount, asset.asset_id);
+        msg!("Lender whitelisted: {} until {}", lender, expires_at);
         Ok(())
     }
 
-    // Repay loan
-    pub fn repay_loan(ctx: Context<RepayLoan>) -> Result<()> {
-        let loan = &mut ctx.accounts.loan;
+    // Move a lender's membership expiry (lender compliance authority only)
+    pub fn renew_lender_membership(ctx: Context<ManageLenderMembership>, expires_at: i64) -> Result<()> {
+        require!(expires_at > Clock::get()?.unix_timestamp, ErrorCode::InvalidMembershipExpiry);
+        
+        let membership = &mut ctx.accounts.lender_membership;
+        membership.expires_at = expires_at;
         
-        require!(loan.is_active, ErrorCode::LoanInactive);
+        msg!("Lender membership renewed: {} until {}", membership.lender, expires_at);
+        Ok(())
+    }
+
+    // Remove a lender from the whitelist (lender compliance authority only)
+    pub fn revoke_lender_membership(ctx: Context<RevokeLenderMembership>) -> Result<()> {
+        msg!("Lender membership revoked: {}", ctx.accounts.lender_membership.lender);
+        Ok(())
+    }
+
+    // Set the share of interest paid to the protocol treasury (admin only)
+    pub fn set_reserve_factor(ctx: Context<SetReserveFactor>, reserve_factor_bps: u16) -> Result<()> {
+        require!(reserve_factor_bps <= MAX_RESERVE_FACTOR_BPS, ErrorCode::InvalidReserveFactor);
         
-        loan.is_active = false;
-        loan.repaid = true;
+        ctx.accounts.config.reserve_factor_bps = reserve_factor_bps;
         
-        msg!("Loan repaid");
+        msg!("Reserve factor set: {} bps", reserve_factor_bps);
         Ok(())
     }
 
-    // Liquidate loan if risk too high
-    pub fn liquidate_loan(ctx: Context<LiquidateLoan>) -> Result<()> {
-        let loan = &mut ctx.accounts.loan;
-        let asset = &ctx.accounts.asset;
+    // Set the fee withheld from each disbursement and the slice of it paid to
+    // the loan's referral agent, if any (admin only)
+    pub fn set_origination_fee(
+        ctx: Context<SetOriginationFee>,
+        origination_fee_bps: u16,
+        referral_share_bps: u16,
+    ) -> Result<()> {
+        require!(
+            origination_fee_bps <= MAX_ORIGINATION_FEE_BPS && referral_share_bps <= 10_000,
+            ErrorCode::InvalidOriginationFee
+        );
+        
+        let config = &mut ctx.accounts.config;
+        config.origination_fee_bps = origination_fee_bps;
+        config.referral_share_bps = referral_share_bps;
         
-        require!(loan.is_active, ErrorCode::LoanInactive);
-        require!(asset.risk_score > 80, ErrorCode::NotEligibleForLiquidation);
+        msg!("Origination fee set: {} bps, {} bps to referrers", origination_fee_bps, referral_share_bps);
+        Ok(())
+    }
+
+    // Require risk scores no older than `max_risk_age` seconds for new loans
+    // (admin only). 0 disables the check.
+    pub fn set_max_risk_age(ctx: Context<SetMaxRiskAge>, max_risk_age: i64) -> Result<()> {
+        require!(max_risk_age >= 0, ErrorCode::InvalidRiskAge);
         
-        loan.is_active = false;
-        loan.liquidated = true;
+        ctx.accounts.config.max_risk_age = max_risk_age;
         
-        msg!("Loan liquidated due to high risk: {}", asset.risk_score);
+        msg!("Max risk score age set: {}s", max_risk_age);
         Ok(())
     }
-}
 
-#[derive(Accounts)]
-#[instruction(asset_id: String)]
-pub struct InitializeAsset<'info> {
-    #[account(
-        init,
-        payer = owner,
-        space = 8 + 32 + 32 + 8 + 200 + 32 + 1 + 1 + 1,
-        seeds = [b"asset", asset_id.as_bytes()],
-        bump
-    )]
-    pub asset: Account<'info, Asset>,
-    
-    #[account(mut)]
-    pub owner: Signer<'info>,
-    
-    pub system_program: Program<'info, System>,
-}
+    // Configure the risk circuit breaker (admin only): a single score update
+    // moving more than `risk_jump_threshold` points freezes the asset's loan
+    // origination and liquidation for `breaker_cooldown` seconds. 0 disables it.
+    pub fn set_circuit_breaker(
+        ctx: Context<SetCircuitBreaker>,
+        risk_jump_threshold: u8,
+        breaker_cooldown: i64,
+    ) -> Result<()> {
+        require!(
+            risk_jump_threshold < 100 && (risk_jump_threshold == 0 || breaker_cooldown > 0),
+            ErrorCode::InvalidCircuitBreaker
+        );
+        
+        let config = &mut ctx.accounts.config;
+        config.risk_jump_threshold = risk_jump_threshold;
+        config.breaker_cooldown = breaker_cooldown;
+        
+        msg!("Circuit breaker set: {} points, {}s cool-down", risk_jump_threshold, breaker_cooldown);
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct UpdateRiskScore<'info> {
-    #[account(
-        mut,
-        seeds = [b"asset", asset.asset_id.as_bytes()],
-        bump = asset.bump
-    )]
-    pub asset: Account<'info, Asset>,
-    
-    pub authority: Signer<'info>, // Oracle authority
-}
+    // Lift an asset's risk freeze before its cool-down ends (admin only)
+    pub fn lift_circuit_breaker(ctx: Context<LiftCircuitBreaker>) -> Result<()> {
+        let asset = &mut ctx.accounts.asset;
+        
+        lift_risk_freeze(asset);
+        
+        msg!("Circuit breaker lifted for {} by admin", asset.asset_id);
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct CreateLoan<'info> {
-    #[account(
-        init,
-        payer = borrower,
-        space = 8 + 32 + 32 + 8 + 8 + 8 + 8 + 1 + 1 + 1 + 1 + 8,
-        seeds = [b"loan", asset.key().as_ref(), borrower.key().as_ref()],
-        bump
-    )]
-    pub loan: Account<'info, Loan>,
-    
-    #[account(
-        mut,
-        seeds = [b"asset", asset.asset_id.as_bytes()],
-        bump = asset.bump
-    )]
-    pub asset: Account<'info, Asset>,
-    
-    #[account(mut)]
-    pub borrower: Signer<'info>,
-    
-    pub system_program: Program<'info, System>,
-}
+    // Confirm a tripped asset's new score (bonded oracles, once each); the
+    // freeze lifts once REPORT_DISPUTE_QUORUM oracles have confirmed
+    pub fn confirm_risk_jump(ctx: Context<ConfirmRiskJump>) -> Result<()> {
+        let asset = &mut ctx.accounts.asset;
+        let oracle = ctx.accounts.oracle.key();
+        let now = Clock::get()?.unix_timestamp;
+        
+        require!(asset.is_risk_frozen(now), ErrorCode::AssetNotFrozen);
+        require!(!asset.breaker_confirmers.contains(&oracle), ErrorCode::AlreadyVoted);
+        
+        asset.breaker_confirmers.push(oracle);
+        if asset.breaker_confirmers.len() >= REPORT_DISPUTE_QUORUM as usize {
+            lift_risk_freeze(asset);
+            msg!("Circuit breaker lifted for {} by oracle quorum", asset.asset_id);
+        } else {
+            msg!("Risk jump confirmed for {} by {}", asset.asset_id, oracle);
+        }
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct RepayLoan<'info> {
-    #[account(
-        mut,
-        seeds = [b"loan", loan.asset.as_ref(), loan.borrower.as_ref()],
-        bump = loan.bump
-    )]
-    pub loan: Account<'info, Loan>,
-    
-    #[account(mut)]
-    pub borrower: Signer<'info>,
-}
+    // Register the treasury vault that collects protocol revenue in one
+    // repayment mint (admin only). The vault is a token account owned by the
+    // revenue PDA, e.g. the PDA's associated token account.
+    pub fn initialize_revenue_account(ctx: Context<InitializeRevenueAccount>) -> Result<()> {
+        let revenue = &mut ctx.accounts.revenue;
+        
+        revenue.mint = ctx.accounts.repayment_mint.key();
+        revenue.vault = ctx.accounts.vault.key();
+        revenue.accrued = 0;
+        revenue.claimed = 0;
+        revenue.bump = ctx.bumps.revenue;
+        
+        msg!("Revenue account initialized for mint: {}", revenue.mint);
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct LiquidateLoan<'info> {
-    #[account(
-        mut,
-        seeds = [b"loan", loan.asset.as_ref(), loan.borrower.as_ref()],
-        bump = loan.bump
-    )]
-    pub loan: Account<'info, Loan>,
-    
-    #[account(
-        seeds = [b"asset", asset.asset_id.as_bytes()],
-        bump = asset.bump
-    )]
-    pub asset: Account<'info, Asset>,
-    
-    pub liquidator: Signer<'info>,
-}
+    // Create the protocol-wide statistics account (admin only). Counters start
+    // at zero, so assets and loans created before it exist are not reflected.
+    pub fn initialize_protocol_stats(ctx: Context<InitializeProtocolStats>) -> Result<()> {
+        let stats = &mut ctx.accounts.stats;
+        
+        stats.total_assets = 0;
+        stats.active_loans = 0;
+        stats.total_principal_outstanding = 0;
+        stats.cumulative_repaid = 0;
+        stats.liquidation_count = 0;
+        stats.bump = ctx.bumps.stats;
+        stats.cumulative_written_off = 0;
+        
+        msg!("Protocol stats initialized");
+        Ok(())
+    }
 
-#[account]
-pub struct Asset {
-    pub asset_id: String,        // 32 bytes
-    pub asset_type: String,      // 32 bytes
-    pub valuation: u64,          // 8 bytes
-    pub metadata_uri: String,    // 200 bytes
-    pub owner: Pubkey,           // 32 bytes
-    pub is_active: bool,         // 1 byte
-    pub risk_score: u8,          // 1 byte
-    pub bump: u8,                // 1 byte
-}
+    // Open a referral account for an agent in one mint; its vault must be a
+    // token account owned by the new PDA
+    pub fn register_referrer(ctx: Context<RegisterReferrer>) -> Result<()> {
+        let referral = &mut ctx.accounts.referral;
+        
+        referral.agent = ctx.accounts.agent.key();
+        referral.mint = ctx.accounts.repayment_mint.key();
+        referral.vault = ctx.accounts.vault.key();
+        referral.accrued = 0;
+        referral.claimed = 0;
+        referral.referred_loans = 0;
+        referral.referred_volume = 0;
+        referral.bump = ctx.bumps.referral;
+        
+        msg!("Referrer registered: {} for mint {}", referral.agent, referral.mint);
+        Ok(())
+    }
 
-#[account]
-pub struct Loan {
-    pub borrower: Pubkey,        // 32 bytes
-    pub asset: Pubkey,           // 32 bytes
-    pub principal: u64,          // 8 bytes
-    pub interest_rate: u64,      // 8 bytes
-    pub start_time: i64,         // 8 bytes
-    pub end_time: i64,           // 8 bytes
-    pub is_active: bool,         // 1 byte
-    pub repaid: bool,            // 1 byte
-    pub liquidated: bool,        // 1 byte
-    pub risk_score_at_creation: u8, // 1 byte
-    pub bump: u8,                // 1 byte
-}
+    // Sweep an agent's accrued referral fees to a token account (agent only)
+    pub fn claim_referral_fees(ctx: Context<ClaimReferralFees>) -> Result<()> {
+        let referral = &mut ctx.accounts.referral;
+        let amount = ctx.accounts.vault.amount;
+        require!(amount > 0, ErrorCode::NothingToClaim);
+        
+        let (agent, mint) = (referral.agent, referral.mint);
+        let seeds: &[&[u8]] = &[b"referral", agent.as_ref(), mint.as_ref(), &[referral.bump]];
+        transfer_checked(
+            &ctx.accounts.token_program,
+            &ctx.accounts.repayment_mint,
+            &ctx.accounts.vault.to_account_info(),
+            &ctx.accounts.destination.to_account_info(),
+            &referral.to_account_info(),
+            amount,
+            &[seeds],
+        )?;
+        referral.claimed = referral.claimed.checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
+        
+        msg!("Referral fees claimed: {}", amount);
+        Ok(())
+    }
 
-#[error_code]
-pub enum ErrorCode {
-    #[msg("Asset is not active")]
-    AssetInactive,
-    #[msg("Invalid risk score")]
-    InvalidRiskScore,
-    #[msg("Loan amount exceeds maximum LTV")]
-    LoanTooHigh,
-    #[msg("Loan is not active")]
-    LoanInactive,
-    #[msg("Not eligible for liquidation")]
-    NotEligibleForLiquidation,
-}
\ No newline at end of file
+    // Sweep the revenue vault's balance to an admin token account (admin only)
+    pub fn claim_revenue(ctx: Context<ClaimRevenue>) -> Result<()> {
+        let revenue = &mut ctx.accounts.revenue;
+        let amount = ctx.accounts.vault.amount;
+        require!(amount > 0, ErrorCode::NothingToClaim);
+        
+        let mint = revenue.mint;
+        let seeds: &[&[u8]] = &[b"revenue", mint.as_ref(), &[revenue.bump]];
+        transfer_checked(
+            &ctx.accounts.token_program,
+            &ctx.accounts.repayment_mint,
+            &ctx.accounts.vault.to_account_info(),
+            &ctx.accounts.destination.to_account_info(),
+            &revenue.to_account_info(),
+            amount,
+            &[seeds],
+        )?;
+        revenue.claimed = revenue.claimed.checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
+        
+        msg!("Protocol revenue claimed: {}", amount);
+        Ok(())
+    }
+
+    // Hand protocol admin authority to an M-of-N multisig (admin only). The
+    // config admin becomes the multisig's signer PDA, so every admin action
+    // afterwards has to go through propose / approve / execute.
+    pub fn create_multisig(ctx: Context<CreateMultisig>, owners: Vec<Pubkey>, threshold: u8) -> Result<()> {
+        validate_multisig_owners(&owners, threshold)?;
+        
+        let multisig = &mut ctx.accounts.multisig;
+        multisig.owners = owners;
+        multisig.threshold = threshold;
+        multisig.owner_set_seqno = 0;
+        multisig.proposal_count = 0;
+        multisig.signer_bump = ctx.bumps.multisig_signer;
+        multisig.bump = ctx.bumps.multisig;
+        
+        ctx.accounts.config.admin = ctx.accounts.multisig_signer.key();
+        
+        msg!("Admin handed to {}-of-{} multisig", threshold, multisig.owners.len());
+        Ok(())
+    }
+
+    // Replace the owner set and threshold; only callable by the multisig itself
+    // through an executed proposal. Pending proposals become unexecutable.
+    pub fn set_multisig_owners(ctx: Context<SetMultisigOwners>, owners: Vec<Pubkey>, threshold: u8) -> Result<()> {
+        validate_multisig_owners(&owners, threshold)?;
+        
+        let multisig = &mut ctx.accounts.multisig;
+        multisig.owners = owners;
+        multisig.threshold = threshold;
+        multisig.owner_set_seqno = multisig.owner_set_seqno
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        msg!("Multisig owners updated: {}-of-{}", threshold, multisig.owners.len());
+        Ok(())
+    }
+
+    // Propose an instruction for the multisig signer to execute (owners only);
+    // the proposer's approval is recorded immediately
+    pub fn propose(
+        ctx: Context<Propose>,
+        program_id: Pubkey,
+        accounts: Vec<ProposalAccount>,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        let multisig = &mut ctx.accounts.multisig;
+        let proposal = &mut ctx.accounts.proposal;
+        
+        let owner_index = multisig.owner_index(&ctx.accounts.proposer.key())?;
+        require!(accounts.len() <= MAX_PROPOSAL_ACCOUNTS, ErrorCode::InvalidProposal);
+        require!(data.len() <= MAX_PROPOSAL_DATA_LEN, ErrorCode::InvalidProposal);
+        
+        proposal.multisig = multisig.key();
+        proposal.index = multisig.proposal_count;
+        proposal.proposer = ctx.accounts.proposer.key();
+        proposal.program_id = program_id;
+        proposal.accounts = accounts;
+        proposal.data = data;
+        proposal.approvals = vec![false; multisig.owners.len()];
+        proposal.approvals[owner_index] = true;
+        proposal.owner_set_seqno = multisig.owner_set_seqno;
+        proposal.executed = false;
+        proposal.created_at = Clock::get()?.unix_timestamp;
+        proposal.bump = ctx.bumps.proposal;
+        
+        multisig.proposal_count = multisig.proposal_count
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        msg!("Proposal {} created", proposal.index);
+        Ok(())
+    }
+
+    // Approve a pending proposal (owners only)
+    pub fn approve_proposal(ctx: Context<ApproveProposal>) -> Result<()> {
+        let multisig = &ctx.accounts.multisig;
+        let proposal = &mut ctx.accounts.proposal;
+        
+        let owner_index = multisig.owner_index(&ctx.accounts.owner.key())?;
+        require!(!proposal.executed, ErrorCode::ProposalAlreadyExecuted);
+        require!(proposal.owner_set_seqno == multisig.owner_set_seqno, ErrorCode::StaleProposal);
+        
+        proposal.approvals[owner_index] = true;
+        
+        msg!("Proposal {} approved by {}", proposal.index, ctx.accounts.owner.key());
+        Ok(())
+    }
+
+    // Execute a proposal once it has reached the threshold (owners only). The
+    // target program and every account it references are passed as remaining
+    // accounts; the multisig signer PDA signs the inner instruction.
+    pub fn execute_proposal<'info>(ctx: Context<'_, '_, '_, 'info, ExecuteProposal<'info>>) -> Result<()> {
+        let multisig = &ctx.accounts.multisig;
+        let proposal = &mut ctx.accounts.proposal;
+        
+        multisig.owner_index(&ctx.accounts.executor.key())?;
+        require!(!proposal.executed, ErrorCode::ProposalAlreadyExecuted);
+        require!(proposal.owner_set_seqno == multisig.owner_set_seqno, ErrorCode::StaleProposal);
+        
+        let approvals = proposal.approvals.iter().filter(|approved| **approved).count();
+        require!(approvals >= multisig.threshold as usize, ErrorCode::ThresholdNotMet);
+        
+        // Persist the executed flag before the inner call so it cannot be replayed
+        proposal.executed = true;
+        proposal.exit(ctx.program_id)?;
+        
+        let signer = ctx.accounts.multisig_signer.key();
+        let instruction = Instruction {
+            program_id: proposal.program_id,
+            accounts: proposal.accounts.iter()
+                .map(|account| AccountMeta {
+                    pubkey: account.pubkey,
+                    is_signer: account.is_signer || account.pubkey == signer,
+                    is_writable: account.is_writable,
+                })
+                .collect(),
+            data: proposal.data.clone(),
+        };
+        
+        let multisig_key = multisig.key();
+        let seeds: &[&[u8]] = &[b"multisig_signer", multisig_key.as_ref(), &[multisig.signer_bump]];
+        let mut account_infos = ctx.remaining_accounts.to_vec();
+        account_infos.push(ctx.accounts.multisig_signer.to_account_info());
+        invoke_signed(&instruction, &account_infos, &[seeds])?;
+        
+        msg!("Proposal {} executed", proposal.index);
+        Ok(())
+    }
+
+    // Register lending rules for an asset type (admin only)
+    pub fn initialize_asset_type_config(
+        ctx: Context<InitializeAssetTypeConfig>,
+        asset_type: String,
+        max_ltv: u8,
+        haircut_bps: u16,
+        max_duration: i64,
+    ) -> Result<()> {
+        require!(asset_type.len() <= MAX_ASSET_TYPE_LEN, ErrorCode::InvalidAssetTypeConfig);
+        validate_asset_type_rules(max_ltv, haircut_bps, max_duration)?;
+        
+        let type_config = &mut ctx.accounts.asset_type_config;
+        
+        type_config.asset_type = asset_type;
+        type_config.max_ltv = max_ltv;
+        type_config.haircut_bps = haircut_bps;
+        type_config.max_duration = max_duration;
+        type_config.bump = ctx.bumps.asset_type_config;
+        
+        msg!("Asset type config created: {}", type_config.asset_type);
+        Ok(())
+    }
+
+    // Update lending rules for an asset type (admin only)
+    pub fn update_asset_type_config(
+        ctx: Context<UpdateAssetTypeConfig>,
+        max_ltv: u8,
+        haircut_bps: u16,
+        max_duration: i64,
+    ) -> Result<()> {
+        validate_asset_type_rules(max_ltv, haircut_bps, max_duration)?;
+        
+        let type_config = &mut ctx.accounts.asset_type_config;
+        
+        type_config.max_ltv = max_ltv;
+        type_config.haircut_bps = haircut_bps;
+        type_config.max_duration = max_duration;
+        
+        msg!("Asset type config updated: {}", type_config.asset_type);
+        Ok(())
+    }
+
+    // Create the regulatory rules for a country code (admin only)
+    pub fn initialize_jurisdiction_config(
+        ctx: Context<InitializeJurisdictionConfig>,
+        jurisdiction: [u8; 2],
+        allowed_asset_types: Vec<String>,
+        max_ltv: u8,
+        transfer_restriction: TransferRestriction,
+    ) -> Result<()> {
+        require!(is_jurisdiction_code(&jurisdiction), ErrorCode::InvalidJurisdiction);
+        validate_jurisdiction_rules(&allowed_asset_types, max_ltv)?;
+        
+        let rules = &mut ctx.accounts.jurisdiction_config;
+        
+        rules.jurisdiction = jurisdiction;
+        rules.allowed_asset_types = allowed_asset_types;
+        rules.max_ltv = max_ltv;
+        rules.transfer_restriction = transfer_restriction;
+        rules.bump = ctx.bumps.jurisdiction_config;
+        
+        msg!("Jurisdiction config created: {}", String::from_utf8_lossy(&jurisdiction));
+        Ok(())
+    }
+
+    // Update a jurisdiction's rules (admin only)
+    pub fn update_jurisdiction_config(
+        ctx: Context<UpdateJurisdictionConfig>,
+        allowed_asset_types: Vec<String>,
+        max_ltv: u8,
+        transfer_restriction: TransferRestriction,
+    ) -> Result<()> {
+        validate_jurisdiction_rules(&allowed_asset_types, max_ltv)?;
+        
+        let rules = &mut ctx.accounts.jurisdiction_config;
+        
+        rules.allowed_asset_types = allowed_asset_types;
+        rules.max_ltv = max_ltv;
+        rules.transfer_restriction = transfer_restriction;
+        
+        msg!("Jurisdiction config updated: {}", String::from_utf8_lossy(&rules.jurisdiction));
+        Ok(())
+    }
+
+    // Place an asset under a configured jurisdiction (compliance authority only)
+    pub fn set_asset_jurisdiction(ctx: Context<SetAssetJurisdiction>) -> Result<()> {
+        let asset = &mut ctx.accounts.asset;
+        
+        asset.jurisdiction = ctx.accounts.jurisdiction_config.jurisdiction;
+        
+        msg!("Asset {} placed in jurisdiction {}", asset.asset_id, String::from_utf8_lossy(&asset.jurisdiction));
+        Ok(())
+    }
+
+    // Bind an asset to a Switchboard aggregator (admin only)
+    pub fn initialize_asset_oracle(
+        ctx: Context<InitializeAssetOracle>,
+        aggregator: Pubkey,
+        max_staleness: i64,
+        max_variance_bps: u16,
+        use_switchboard: bool,
+    ) -> Result<()> {
+        validate_oracle_rules(max_staleness, max_variance_bps)?;
+        
+        let oracle = &mut ctx.accounts.asset_oracle;
+        
+        oracle.asset = ctx.accounts.asset.key();
+        oracle.aggregator = aggregator;
+        oracle.max_staleness = max_staleness;
+        oracle.max_variance_bps = max_variance_bps;
+        oracle.use_switchboard = use_switchboard;
+        oracle.bump = ctx.bumps.asset_oracle;
+        
+        msg!("Oracle configured for {}: {}", ctx.accounts.asset.asset_id, aggregator);
+        Ok(())
+    }
+
+    // Change an asset's aggregator, limits, or active risk source (admin only)
+    pub fn update_asset_oracle(
+        ctx: Context<UpdateAssetOracle>,
+        aggregator: Pubkey,
+        max_staleness: i64,
+        max_variance_bps: u16,
+        use_switchboard: bool,
+    ) -> Result<()> {
+        validate_oracle_rules(max_staleness, max_variance_bps)?;
+        
+        let oracle = &mut ctx.accounts.asset_oracle;
+        
+        oracle.aggregator = aggregator;
+        oracle.max_staleness = max_staleness;
+        oracle.max_variance_bps = max_variance_bps;
+        oracle.use_switchboard = use_switchboard;
+        
+        msg!("Oracle updated: {}", aggregator);
+        Ok(())
+    }
+
+    // Accept `mint` for repayments, priced by a Switchboard feed (admin only)
+    pub fn add_accepted_mint(
+        ctx: Context<AddAcceptedMint>,
+        price_feed: Pubkey,
+        max_staleness: i64,
+        max_variance_bps: u16,
+        haircut_bps: u16,
+    ) -> Result<()> {
+        validate_oracle_rules(max_staleness, max_variance_bps)?;
+        require!(haircut_bps <= MAX_REPAYMENT_HAIRCUT_BPS, ErrorCode::InvalidHaircut);
+        
+        let accepted = &mut ctx.accounts.accepted_mint;
+        
+        accepted.mint = ctx.accounts.mint.key();
+        accepted.price_feed = price_feed;
+        accepted.max_staleness = max_staleness;
+        accepted.max_variance_bps = max_variance_bps;
+        accepted.haircut_bps = haircut_bps;
+        accepted.enabled = true;
+        accepted.bump = ctx.bumps.accepted_mint;
+        
+        msg!("Accepted repayment mint: {}", accepted.mint);
+        Ok(())
+    }
+
+    // Change an accepted mint's feed, limits or haircut, or stop accepting it (admin only)
+    pub fn update_accepted_mint(
+        ctx: Context<UpdateAcceptedMint>,
+        price_feed: Pubkey,
+        max_staleness: i64,
+        max_variance_bps: u16,
+        haircut_bps: u16,
+        enabled: bool,
+    ) -> Result<()> {
+        validate_oracle_rules(max_staleness, max_variance_bps)?;
+        require!(haircut_bps <= MAX_REPAYMENT_HAIRCUT_BPS, ErrorCode::InvalidHaircut);
+        
+        let accepted = &mut ctx.accounts.accepted_mint;
+        
+        accepted.price_feed = price_feed;
+        accepted.max_staleness = max_staleness;
+        accepted.max_variance_bps = max_variance_bps;
+        accepted.haircut_bps = haircut_bps;
+        accepted.enabled = enabled;
+        
+        msg!("Accepted repayment mint updated: {}", accepted.mint);
+        Ok(())
+    }
+
+    // Register a Switchboard benchmark rate feed floating-rate loans can track (admin only)
+    pub fn add_benchmark_feed(
+        ctx: Context<AddBenchmarkFeed>,
+        feed: Pubkey,
+        max_staleness: i64,
+        max_variance_bps: u16,
+    ) -> Result<()> {
+        validate_oracle_rules(max_staleness, max_variance_bps)?;
+        
+        let benchmark = &mut ctx.accounts.benchmark;
+        
+        benchmark.feed = feed;
+        benchmark.max_staleness = max_staleness;
+        benchmark.max_variance_bps = max_variance_bps;
+        benchmark.bump = ctx.bumps.benchmark;
+        
+        msg!("Benchmark feed registered: {}", feed);
+        Ok(())
+    }
+
+    // Change a benchmark feed's staleness and variance limits (admin only)
+    pub fn update_benchmark_feed(
+        ctx: Context<UpdateBenchmarkFeed>,
+        max_staleness: i64,
+        max_variance_bps: u16,
+    ) -> Result<()> {
+        validate_oracle_rules(max_staleness, max_variance_bps)?;
+        
+        let benchmark = &mut ctx.accounts.benchmark;
+        
+        benchmark.max_staleness = max_staleness;
+        benchmark.max_variance_bps = max_variance_bps;
+        
+        msg!("Benchmark feed updated: {}", benchmark.feed);
+        Ok(())
+    }
+
+    // Pull the risk score from the asset's Switchboard aggregator (permissionless)
+    pub fn refresh_risk_from_switchboard(ctx: Context<RefreshRiskFromSwitchboard>) -> Result<()> {
+        let asset = &mut ctx.accounts.asset;
+        let oracle = &ctx.accounts.asset_oracle;
+        
+        require!(oracle.use_switchboard, ErrorCode::OracleNotSelected);
+        require!(asset.is_active, ErrorCode::AssetInactive);
+        
+        let data = ctx.accounts.aggregator.try_borrow_data()?;
+        let round = read_switchboard_round(&data)?;
+        
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now - round.open_timestamp <= oracle.max_staleness,
+            ErrorCode::StaleOracleRound
+        );
+        require!(
+            within_variance(&round.result, &round.std_deviation, oracle.max_variance_bps),
+            ErrorCode::OracleVarianceTooHigh
+        );
+        
+        let risk_score = round.result.to_integer()
+            .filter(|score| (0..=100).contains(score))
+            .ok_or(ErrorCode::InvalidRiskScore)? as u8;
+        
+        set_risk_score(asset, &ctx.accounts.config, risk_score, now);
+        
+        if let Some(history) = &ctx.accounts.history {
+            history.load_mut()?.record(now, asset.valuation, risk_score, HISTORY_SOURCE_SWITCHBOARD);
+        }
+        
+        msg!("Risk score refreshed from Switchboard: {}", risk_score);
+        Ok(())
+    }
+
+    // Create the asset's zero-copy risk and appraisal history ring buffer;
+    // risk updates append to it whenever it is passed along
+    pub fn initialize_asset_history(ctx: Context<InitializeAssetHistory>) -> Result<()> {
+        let asset = &ctx.accounts.asset;
+        let mut history = ctx.accounts.history.load_init()?;
+        
+        history.asset = asset.key();
+        history.bump = ctx.bumps.history;
+        history.record(
+            Clock::get()?.unix_timestamp,
+            asset.valuation,
+            asset.risk_score,
+            HISTORY_SOURCE_SNAPSHOT,
+        );
+        
+        msg!("History initialized for asset: {}", asset.asset_id);
+        Ok(())
+    }
+
+    // Initialize a new RWA asset
+    pub fn initialize_asset(
+        ctx: Context<InitializeAsset>,
+        asset_id: String,
+        asset_type: String,
+        valuation: u64,
+        metadata_uri: String,
+    ) -> Result<()> {
+        require!(
+            asset_id.len() <= MAX_ASSET_ID_LEN && asset_type.len() <= MAX_ASSET_TYPE_LEN,
+            ErrorCode::AssetFieldTooLong
+        );
+        require!(metadata_uri.len() <= MAX_METADATA_URI_LEN, ErrorCode::MetadataUriTooLong);
+        require!(asset_type != PORTFOLIO_ASSET_TYPE, ErrorCode::ReservedAssetType);
+        
+        let asset = &mut ctx.accounts.asset;
+        
+        asset.asset_id = asset_id;
+        asset.asset_type = asset_type;
+        asset.valuation = valuation;
+        asset.metadata_uri = metadata_uri;
+        asset.owner = *ctx.accounts.owner.key;
+        asset.is_active = true;
+        asset.risk_score = 50; // Default medium risk
+        asset.bump = ctx.bumps.asset;
+        asset.active_loans = 0;
+        asset.delegate = Pubkey::default();
+        asset.valuation_refresh_requested_at = 0;
+        asset.last_risk_report = 0;
+        asset.risk_frozen_until = 0;
+        asset.breaker_confirmers = Vec::new();
+        // Not yet scored by an oracle
+        asset.last_risk_update = 0;
+        // Assigned by the compliance authority
+        asset.jurisdiction = NO_JURISDICTION;
+        asset.portfolio = Pubkey::default();
+        
+        ctx.accounts.stats.total_assets = ctx.accounts.stats.total_assets.saturating_add(1);
+        
+        msg!("Asset created: {}", asset.asset_id);
+        Ok(())
+    }
+
+    // Update risk score (bonded risk oracle only)
+    pub fn update_risk_score(
+        ctx: Context<UpdateRiskScore>,
+        new_risk_score: u8,
+    ) -> Result<()> {
+        let asset = &mut ctx.accounts.asset;
+        
+        require!(asset.is_active, ErrorCode::AssetInactive);
+        require!(new_risk_score <= 100, ErrorCode::InvalidRiskScore);
+        
+        let now = Clock::get()?.unix_timestamp;
+        set_risk_score(asset, &ctx.accounts.config, new_risk_score, now);
+        
+        if let Some(history) = &ctx.accounts.history {
+            history.load_mut()?.record(now, asset.valuation, new_risk_score, HISTORY_SOURCE_RISK_AUTHORITY);
+        }
+        
+        msg!("Risk score updated to: {}", new_risk_score);
+        Ok(())
+    }
+
+    // Anchor the Merkle root of a batch of risk scores (risk oracle only)
+    pub fn post_risk_report(
+        ctx: Context<PostRiskReport>,
+        report_id: u64,
+        root: [u8; 32],
+        leaf_count: u32,
+    ) -> Result<()> {
+        require!(report_id > 0 && leaf_count > 0, ErrorCode::InvalidRiskReport);
+        
+        let report = &mut ctx.accounts.report;
+        report.report_id = report_id;
+        report.root = root;
+        report.leaf_count = leaf_count;
+        report.posted_at = Clock::get()?.unix_timestamp;
+        report.bump = ctx.bumps.report;
+        report.oracle = ctx.accounts.risk_oracle.key();
+        report.overturned = false;
+        
+        // Keeps the bond locked until the report's dispute window has passed
+        ctx.accounts.oracle_bond.last_report_at = report.posted_at;
+        
+        msg!("Risk report {} posted with {} entries", report_id, leaf_count);
+        Ok(())
+    }
+
+    // Apply one asset's score from a posted report, proven against its root (permissionless)
+    pub fn apply_risk_report_entry(
+        ctx: Context<ApplyRiskReportEntry>,
+        risk_score: u8,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let report = &ctx.accounts.report;
+        let asset = &mut ctx.accounts.asset;
+        let now = Clock::get()?.unix_timestamp;
+        
+        require!(asset.is_active, ErrorCode::AssetInactive);
+        require!(risk_score <= 100, ErrorCode::InvalidRiskScore);
+        require!(proof.len() <= MAX_RISK_PROOF_DEPTH, ErrorCode::InvalidRiskProof);
+        require!(!report.overturned, ErrorCode::ReportOverturned);
+        require!(now - report.posted_at <= MAX_RISK_REPORT_AGE, ErrorCode::StaleRiskReport);
+        // Reports apply in order, so an older batch cannot overwrite a newer score
+        require!(report.report_id > asset.last_risk_report, ErrorCode::StaleRiskReport);
+        require!(
+            verify_merkle_proof(risk_report_leaf(&asset.key(), risk_score), &proof, report.root),
+            ErrorCode::InvalidRiskProof
+        );
+        
+        set_risk_score(asset, &ctx.accounts.config, risk_score, now);
+        asset.last_risk_report = report.report_id;
+        
+        if let Some(history) = &ctx.accounts.history {
+            history.load_mut()?.record(now, asset.valuation, risk_score, HISTORY_SOURCE_RISK_REPORT);
+        }
+        
+        msg!("Risk score updated to {} from report {}", risk_score, report.report_id);
+        Ok(())
+    }
+
+    // Register the signer as a risk oracle by bonding at least MIN_ORACLE_BOND
+    // lamports into its bond PDA. Only bonded oracles can post risk reports or
+    // vote on report disputes.
+    pub fn register_oracle(ctx: Context<RegisterOracle>, amount: u64) -> Result<()> {
+        require!(amount >= MIN_ORACLE_BOND, ErrorCode::InsufficientOracleBond);
+        
+        deposit_bond(&ctx.accounts.oracle, &ctx.accounts.bond, &ctx.accounts.system_program, amount)?;
+        
+        let bond = &mut ctx.accounts.bond;
+        bond.oracle = ctx.accounts.oracle.key();
+        bond.bonded = amount;
+        bond.open_disputes = 0;
+        bond.last_report_at = 0;
+        bond.slashed = 0;
+        bond.bump = ctx.bumps.bond;
+        
+        msg!("Oracle {} registered with bond {}", bond.oracle, amount);
+        Ok(())
+    }
+
+    // Add lamports to an oracle's bond (oracle only)
+    pub fn bond_oracle(ctx: Context<BondOracle>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidBondAmount);
+        
+        deposit_bond(&ctx.accounts.oracle, &ctx.accounts.bond, &ctx.accounts.system_program, amount)?;
+        
+        let bond = &mut ctx.accounts.bond;
+        bond.bonded = bond.bonded.checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
+        
+        msg!("Oracle bond increased to {}", bond.bonded);
+        Ok(())
+    }
+
+    // Withdraw bonded lamports (oracle only). Locked while a dispute against the
+    // oracle is open or its latest report can still be disputed.
+    pub fn unbond_oracle(ctx: Context<BondOracle>, amount: u64) -> Result<()> {
+        let bond = &mut ctx.accounts.bond;
+        let now = Clock::get()?.unix_timestamp;
+        
+        require!(amount > 0 && amount <= bond.bonded, ErrorCode::InvalidBondAmount);
+        require!(
+            bond.open_disputes == 0 && now > bond.last_report_at + ORACLE_DISPUTE_WINDOW,
+            ErrorCode::OracleBondLocked
+        );
+        
+        bond.bonded -= amount;
+        **bond.to_account_info().try_borrow_mut_lamports()? -= amount;
+        **ctx.accounts.oracle.to_account_info().try_borrow_mut_lamports()? += amount;
+        
+        msg!("Oracle unbonded {}, remaining {}", amount, bond.bonded);
+        Ok(())
+    }
+
+    // Challenge a posted risk report within its dispute window (anyone). Bonded
+    // oracles other than the reporter then vote to overturn or uphold it.
+    pub fn dispute_risk_report(ctx: Context<DisputeRiskReport>) -> Result<()> {
+        let report = &ctx.accounts.report;
+        let now = Clock::get()?.unix_timestamp;
+        
+        require!(now - report.posted_at <= ORACLE_DISPUTE_WINDOW, ErrorCode::DisputeWindowClosed);
+        
+        let dispute = &mut ctx.accounts.dispute;
+        dispute.report = report.key();
+        dispute.oracle = report.oracle;
+        dispute.challenger = ctx.accounts.challenger.key();
+        dispute.opened_at = now;
+        dispute.voters = Vec::new();
+        dispute.overturn_votes = 0;
+        dispute.uphold_votes = 0;
+        dispute.resolved = false;
+        dispute.bump = ctx.bumps.dispute;
+        
+        let bond = &mut ctx.accounts.oracle_bond;
+        bond.open_disputes = bond.open_disputes.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+        
+        msg!("Risk report {} disputed by {}", report.report_id, dispute.challenger);
+        Ok(())
+    }
+
+    // Vote on an open report dispute (bonded oracles other than the reporter, once each)
+    pub fn vote_report_dispute(ctx: Context<VoteReportDispute>, overturn: bool) -> Result<()> {
+        let dispute = &mut ctx.accounts.dispute;
+        let voter = ctx.accounts.voter.key();
+        let now = Clock::get()?.unix_timestamp;
+        
+        require!(!dispute.resolved, ErrorCode::DisputeResolved);
+        require!(now <= dispute.opened_at + DISPUTE_VOTING_PERIOD, ErrorCode::DisputeWindowClosed);
+        require!(voter != dispute.oracle, ErrorCode::Unauthorized);
+        require!(!dispute.voters.contains(&voter), ErrorCode::AlreadyVoted);
+        require!(dispute.voters.len() < MAX_DISPUTE_VOTERS, ErrorCode::DisputeVotersFull);
+        
+        dispute.voters.push(voter);
+        if overturn {
+            dispute.overturn_votes += 1;
+        } else {
+            dispute.uphold_votes += 1;
+        }
+        
+        msg!("Dispute vote recorded: overturn {}, uphold {}", dispute.overturn_votes, dispute.uphold_votes);
+        Ok(())
+    }
+
+    // Overturn a report whose dispute reached quorum (permissionless): the report
+    // can no longer be applied and ORACLE_SLASH_BPS of the oracle's bond goes to
+    // the challenger
+    pub fn slash_oracle(ctx: Context<SlashOracle>) -> Result<()> {
+        let dispute = &mut ctx.accounts.dispute;
+        let bond = &mut ctx.accounts.oracle_bond;
+        
+        require!(!dispute.resolved, ErrorCode::DisputeResolved);
+        require!(dispute.is_overturned(), ErrorCode::DisputeQuorumNotReached);
+        
+        let slashed = slash_amount(bond.bonded);
+        bond.bonded -= slashed;
+        bond.slashed = bond.slashed.saturating_add(slashed);
+        bond.open_disputes = bond.open_disputes.saturating_sub(1);
+        **bond.to_account_info().try_borrow_mut_lamports()? -= slashed;
+        **ctx.accounts.challenger.to_account_info().try_borrow_mut_lamports()? += slashed;
+        
+        dispute.resolved = true;
+        ctx.accounts.report.overturned = true;
+        
+        emit!(OracleSlashed {
+            oracle: bond.oracle,
+            report: dispute.report,
+            challenger: dispute.challenger,
+            amount: slashed,
+            slashed_at: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Oracle {} slashed {}", bond.oracle, slashed);
+        Ok(())
+    }
+
+    // Close a dispute that did not reach an overturn quorum by the end of its
+    // voting period (permissionless), releasing the oracle's bond lock
+    pub fn dismiss_report_dispute(ctx: Context<DismissReportDispute>) -> Result<()> {
+        let dispute = &mut ctx.accounts.dispute;
+        let now = Clock::get()?.unix_timestamp;
+        
+        require!(!dispute.resolved, ErrorCode::DisputeResolved);
+        require!(now > dispute.opened_at + DISPUTE_VOTING_PERIOD, ErrorCode::DisputeVotingOpen);
+        require!(!dispute.is_overturned(), ErrorCode::DisputeVotingOpen);
+        
+        dispute.resolved = true;
+        let bond = &mut ctx.accounts.oracle_bond;
+        bond.open_disputes = bond.open_disputes.saturating_sub(1);
+        
+        msg!("Report dispute dismissed");
+        Ok(())
+    }
+
+    // Apply for a loan against an RWA; funds move only once a lender approves
+    pub fn request_loan(
+        ctx: Context<RequestLoan>,
+        loan_amount: u64,
+        interest_rate: u64, // basis points (1% = 100)
+        duration: i64,      // in seconds
+        interest_mode: InterestMode,
+        product: LoanProduct,
+        floating_rate: Option<FloatingRate>,
+    ) -> Result<()> {
+        validate_floating_rate(interest_rate, floating_rate.as_ref())?;
+        
+        let loan = &mut ctx.accounts.loan;
+        let asset = &mut ctx.accounts.asset;
+        let type_config = &ctx.accounts.asset_type_config;
+        
+        require!(asset.is_active, ErrorCode::AssetInactive);
+        require!(asset.portfolio == Pubkey::default(), ErrorCode::AssetBundled);
+        require!(
+            duration > 0 && duration <= type_config.max_duration,
+            ErrorCode::InvalidLoanDuration
+        );
+        
+        let rules = jurisdiction_rules(asset, &ctx.accounts.jurisdiction_config)?;
+        if let Some(rules) = rules {
+            require!(rules.allows_asset_type(&asset.asset_type), ErrorCode::AssetTypeNotAllowedInJurisdiction);
+        }
+        
+        // Calculate max loan based on risk score, capped by asset type and jurisdiction rules
+        let max_loan = max_loan_in_jurisdiction(asset.valuation, asset.risk_score, type_config, rules);
+        require!(loan_amount <= max_loan, ErrorCode::LoanTooHigh);
+        
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            ctx.accounts.custody_attestation.expires_at > now,
+            ErrorCode::CustodyAttestationExpired
+        );
+        require!(!asset.is_risk_frozen(now), ErrorCode::AssetRiskFrozen);
+        require!(!asset.is_risk_stale(now, ctx.accounts.config.max_risk_age), ErrorCode::StaleRiskScore);
+        
+        loan.borrower = *ctx.accounts.borrower.key;
+        loan.asset = asset.key();
+        loan.principal = loan_amount;
+        loan.interest_rate = interest_rate;
+        // Provisional term; re-anchored to the approval time on disbursement
+        loan.start_time = now;
+        loan.end_time = now + duration;
+        loan.status = LoanStatus::Pending;
+        loan.risk_score_at_creation = asset.risk_score;
+        loan.bump = ctx.bumps.loan;
+        loan.forborne_seconds = 0;
+        loan.forbearance_start = 0;
+        loan.forbearance_end = 0;
+        loan.modification_count = 0;
+        loan.lender = Pubkey::default();
+        loan.request_expiry = now + LOAN_REQUEST_TTL;
+        loan.repayment_mint = ctx.accounts.repayment_mint.key();
+        loan.supplemental_asset = Pubkey::default();
+        loan.written_off_amount = 0;
+        loan.interest_mode = interest_mode;
+        loan.disputed = false;
+        loan.dispute_raised_by = Pubkey::default();
+        loan.dispute_raised_at = 0;
+        loan.dispute_outcome = None;
+        loan.dispute_resolved_at = 0;
+        loan.late_fees = 0;
+        loan.liquidation_eligible = false;
+        loan.liquidator = Pubkey::default();
+        loan.liquidation_debt = 0;
+        loan.rollover_count = 0;
+        loan.product = product;
+        // Floating loans accrue at `interest_rate` until their first repricing
+        loan.floating_rate = floating_rate;
+        loan.interest_checkpoint = 0;
+        loan.checkpoint_seconds = 0;
+        loan.last_repriced_at = 0;
+        loan.collateral_rules = CollateralRules::new(type_config, rules);
+        loan.supplemental_rules = CollateralRules::default();
+        loan.principal_repaid = 0;
+        loan.referral = match &ctx.accounts.referral {
+            Some(referral) => {
+                require_keys_neq!(referral.agent, loan.borrower, ErrorCode::SelfReferral);
+                referral.key()
+            }
+            None => Pubkey::default(),
+        };
+        
+        asset.active_loans = asset.active_loans
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        msg!("Loan requested: {} for asset {}", loan_amount, asset.asset_id);
+        Ok(())
+    }
+
+    // Underwrite a pending request and disburse the principal from the lender
+    pub fn approve_loan(ctx: Context<ApproveLoan>) -> Result<()> {
+        let loan = &mut ctx.accounts.loan;
+        let asset = &ctx.accounts.asset;
+        let type_config = &ctx.accounts.asset_type_config;
+        
+        require!(loan.status == LoanStatus::Pending, ErrorCode::LoanNotPending);
+        
+        let now = Clock::get()?.unix_timestamp;
+        require!(now <= loan.request_expiry, ErrorCode::LoanRequestExpired);
+        require!(asset.is_active, ErrorCode::AssetInactive);
+        require!(!asset.is_risk_frozen(now), ErrorCode::AssetRiskFrozen);
+        require!(!asset.is_risk_stale(now, ctx.accounts.config.max_risk_age), ErrorCode::StaleRiskScore);
+        if ctx.accounts.config.lender_whitelist_required {
+            let membership = ctx.accounts.lender_membership.as_ref().ok_or(ErrorCode::LenderNotWhitelisted)?;
+            require!(membership.expires_at > now, ErrorCode::LenderMembershipExpired);
+        }
+        
+        // Collateral may have been re-scored, or its jurisdiction's cap tightened,
+        // since the application
+        let rules = jurisdiction_rules(asset, &ctx.accounts.jurisdiction_config)?;
+        let max_loan = max_loan_in_jurisdiction(asset.valuation, asset.risk_score, type_config, rules);
+        require!(loan.principal <= max_loan, ErrorCode::LoanTooHigh);
+        // Health is measured against the rules the loan was funded under
+        loan.collateral_rules = CollateralRules::new(type_config, rules);
+        
+        // The borrower owes the full principal but receives it net of the fee
+        let fee = origination_fee(loan.principal, ctx.accounts.config.origination_fee_bps);
+        let referral_fee = if loan.referral == Pubkey::default() {
+            0
+        } else {
+            reserve_share(fee, ctx.accounts.config.referral_share_bps)
+        };
+        
+        transfer_net(
+            &ctx.accounts.token_program,
+            &ctx.accounts.repayment_mint,
+            &ctx.accounts.lender_token_account.to_account_info(),
+            &ctx.accounts.borrower_token_account.to_account_info(),
+            &ctx.accounts.lender.to_account_info(),
+            loan.principal - fee,
+        )?;
+        collect_protocol_share(
+            &mut ctx.accounts.revenue,
+            &ctx.accounts.revenue_vault,
+            &ctx.accounts.token_program,
+            &ctx.accounts.repayment_mint,
+            &ctx.accounts.lender_token_account.to_account_info(),
+            &ctx.accounts.lender.to_account_info(),
+            fee - referral_fee,
+        )?;
+        if loan.referral != Pubkey::default() {
+            let (Some(referral), Some(vault)) = (ctx.accounts.referral.as_mut(), ctx.accounts.referral_vault.as_ref()) else {
+                return err!(ErrorCode::ReferralAccountMissing);
+            };
+            require_keys_eq!(referral.key(), loan.referral, ErrorCode::ReferralAccountMissing);
+            require_keys_eq!(vault.key(), referral.vault, ErrorCode::ReferralAccountMissing);
+            
+            if referral_fee > 0 {
+                transfer_net(
+                    &ctx.accounts.token_program,
+                    &ctx.accounts.repayment_mint,
+                    &ctx.accounts.lender_token_account.to_account_info(),
+                    &vault.to_account_info(),
+                    &ctx.accounts.lender.to_account_info(),
+                    referral_fee,
+                )?;
+            }
+            referral.record_referral(loan.principal, referral_fee)?;
+        }
+        
+        let duration = loan.end_time - loan.start_time;
+        loan.start_time = now;
+        loan.end_time = now + duration;
+        loan.risk_score_at_creation = asset.risk_score;
+        loan.lender = *ctx.accounts.lender.key;
+        loan.transition(LoanStatus::Active)?;
+        ctx.accounts.stats.record_disbursement(loan.principal);
+        
+        msg!("Loan approved and disbursed: {} for asset {}", loan.principal, asset.asset_id);
+        Ok(())
+    }
+
+    // Withdraw a pending request (borrower), or clear an expired one (anyone)
+    pub fn cancel_loan_request(ctx: Context<CancelLoanRequest>) -> Result<()> {
+        let loan = &ctx.accounts.loan;
+        let asset = &mut ctx.accounts.asset;
+        
+        require!(loan.status == LoanStatus::Pending, ErrorCode::LoanNotPending);
+        
+        let now = Clock::get()?.unix_timestamp;
+        let is_borrower = ctx.accounts.signer.key() == loan.borrower;
+        require!(is_borrower || now > loan.request_expiry, ErrorCode::Unauthorized);
+        
+        asset.active_loans = asset.active_loans.saturating_sub(1);
+        
+        msg!("Loan request cancelled for asset {}", asset.asset_id);
+        Ok(())
+    }
+
+    // Repay loan: remaining principal plus accrued interest back to the lender.
+    // On a loan with a repayment schedule this settles whatever installments
+    // have not yet paid down
+    pub fn repay_loan(ctx: Context<RepayLoan>) -> Result<()> {
+        let loan = &mut ctx.accounts.loan;
+        let asset = &mut ctx.accounts.asset;
+        
+        require!(loan.status.is_open(), ErrorCode::LoanInactive);
+        
+        let now = Clock::get()?.unix_timestamp;
+        let debt = outstanding_debt(loan, now)?;
+        let protocol_share = reserve_share(debt - loan.remaining_principal(), ctx.accounts.config.reserve_factor_bps);
+        
+        transfer_net(
+            &ctx.accounts.token_program,
+            &ctx.accounts.repayment_mint,
+            &ctx.accounts.borrower_token_account.to_account_info(),
+            &ctx.accounts.lender_token_account.to_account_info(),
+            &ctx.accounts.borrower.to_account_info(),
+            debt - protocol_share,
+        )?;
+        collect_protocol_share(
+            &mut ctx.accounts.revenue,
+            &ctx.accounts.revenue_vault,
+            &ctx.accounts.token_program,
+            &ctx.accounts.repayment_mint,
+            &ctx.accounts.borrower_token_account.to_account_info(),
+            &ctx.accounts.borrower.to_account_info(),
+            protocol_share,
+        )?;
+        
+        loan.transition(LoanStatus::Repaid)?;
+        asset.active_loans = asset.active_loans.saturating_sub(1);
+        ctx.accounts.stats.record_repayment(debt);
+        ctx.accounts.stats.record_closure(loan.remaining_principal());
+        
+        msg!("Loan repaid");
+        Ok(())
+    }
+
+    // Repay in another accepted mint, converted from the loan's mint at the
+    // current feed prices and grossed up by the paying mint's haircut
+    pub fn repay_loan_with_mint(ctx: Context<RepayLoanWithMint>) -> Result<()> {
+        require!(ctx.accounts.loan.status.is_open(), ErrorCode::LoanInactive);
+        require!(ctx.accounts.payment_mint_config.enabled, ErrorCode::MintNotAccepted);
+        
+        let now = Clock::get()?.unix_timestamp;
+        let loan_price = read_mint_price(&ctx.accounts.loan_mint_config, &ctx.accounts.loan_price_feed, now)?;
+        let payment_price = read_mint_price(&ctx.accounts.payment_mint_config, &ctx.accounts.payment_price_feed, now)?;
+        let convert = |amount: u64| {
+            convert_repayment(
+                amount,
+                &loan_price,
+                ctx.accounts.loan_mint.decimals,
+                &payment_price,
+                ctx.accounts.payment_mint.decimals,
+                ctx.accounts.payment_mint_config.haircut_bps,
+            )
+            .ok_or(ErrorCode::MathOverflow)
+        };
+        
+        let debt = outstanding_debt(&ctx.accounts.loan, now)?;
+        let protocol_share = reserve_share(
+            debt - ctx.accounts.loan.remaining_principal(),
+            ctx.accounts.config.reserve_factor_bps,
+        );
+        let lender_amount = convert(debt - protocol_share)?;
+        let share_amount = convert(protocol_share)?;
+        
+        transfer_net(
+            &ctx.accounts.token_program,
+            &ctx.accounts.payment_mint,
+            &ctx.accounts.borrower_token_account.to_account_info(),
+            &ctx.accounts.lender_token_account.to_account_info(),
+            &ctx.accounts.borrower.to_account_info(),
+            lender_amount,
+        )?;
+        collect_protocol_share(
+            &mut ctx.accounts.revenue,
+            &ctx.accounts.revenue_vault,
+            &ctx.accounts.token_program,
+            &ctx.accounts.payment_mint,
+            &ctx.accounts.borrower_token_account.to_account_info(),
+            &ctx.accounts.borrower.to_account_info(),
+            share_amount,
+        )?;
+        
+        let loan = &mut ctx.accounts.loan;
+        loan.transition(LoanStatus::Repaid)?;
+        ctx.accounts.asset.active_loans = ctx.accounts.asset.active_loans.saturating_sub(1);
+        ctx.accounts.stats.record_repayment(debt);
+        ctx.accounts.stats.record_closure(loan.remaining_principal());
+        
+        emit!(RepaidInAcceptedMint {
+            loan: loan.key(),
+            payment_mint: ctx.accounts.payment_mint.key(),
+            debt,
+            amount_paid: lender_amount + share_amount,
+            haircut_bps: ctx.accounts.payment_mint_config.haircut_bps,
+        });
+        
+        msg!("Loan repaid in accepted mint");
+        Ok(())
+    }
+
+    // Split an active loan into N amortizing installments over its term
+    pub fn create_repayment_schedule(
+        ctx: Context<CreateRepaymentSchedule>,
+        installment_count: u8,
+    ) -> Result<()> {
+        let loan = &ctx.accounts.loan;
+        let schedule = &mut ctx.accounts.schedule;
+        
+        require!(loan.status.is_open(), ErrorCode::LoanInactive);
+        // Bullet loans are settled in one payment with repay_loan
+        require!(loan.product != LoanProduct::Bullet, ErrorCode::ScheduleNotSupported);
+        require!(
+            installment_count > 0 && installment_count as usize <= MAX_INSTALLMENTS,
+            ErrorCode::InvalidInstallmentCount
+        );
+        
+        let interval = (loan.end_time - loan.start_time) / installment_count as i64;
+        require!(interval > 0, ErrorCode::InvalidInstallmentCount);
+        
+        schedule.loan = loan.key();
+        schedule.interval = interval;
+        schedule.installments = build_installments(loan, interval, installment_count as usize)?;
+        schedule.paid_count = 0;
+        schedule.missed_count = 0;
+        schedule.late_payments = 0;
+        schedule.delinquent = false;
+        schedule.bump = ctx.bumps.schedule;
+        
+        msg!("Repayment schedule created: {} installments every {}s", installment_count, interval);
+        Ok(())
+    }
+
+    // Pay the next outstanding installment, plus a late fee once past its grace
+    // period; its principal comes off the loan and the final one closes it
+    pub fn pay_installment(ctx: Context<PayInstallment>) -> Result<()> {
+        let schedule = &mut ctx.accounts.schedule;
+        let loan = &mut ctx.accounts.loan;
+        let asset = &mut ctx.accounts.asset;
+        
+        require!(loan.status.is_open(), ErrorCode::LoanInactive);
+        
+        let now = Clock::get()?.unix_timestamp;
+        let index = schedule.paid_count as usize;
+        let installment = schedule.installments
+            .get_mut(index)
+            .ok_or(ErrorCode::ScheduleComplete)?;
+        
+        installment.paid_at = now;
+        let due_date = installment.due_date;
+        let fee = installment_late_fee(installment, now);
+        let amount = installment.amount.checked_add(fee).ok_or(ErrorCode::MathOverflow)?;
+        let late = now > due_date + INSTALLMENT_GRACE_PERIOD;
+        
+        let principal_part = loan.product.installment_principal(loan.principal, schedule.installments.len(), index);
+        let interest = amount.saturating_sub(principal_part);
+        let protocol_share = reserve_share(interest, ctx.accounts.config.reserve_factor_bps);
+        
+        transfer_net(
+            &ctx.accounts.token_program,
+            &ctx.accounts.repayment_mint,
+            &ctx.accounts.borrower_token_account.to_account_info(),
+            &ctx.accounts.lender_token_account.to_account_info(),
+            &ctx.accounts.borrower.to_account_info(),
+            amount - protocol_share,
+        )?;
+        collect_protocol_share(
+            &mut ctx.accounts.revenue,
+            &ctx.accounts.revenue_vault,
+            &ctx.accounts.token_program,
+            &ctx.accounts.repayment_mint,
+            &ctx.accounts.borrower_token_account.to_account_info(),
+            &ctx.accounts.borrower.to_account_info(),
+            protocol_share,
+        )?;
+        
+        if late {
+            schedule.late_payments = schedule.late_payments.saturating_add(1);
+        }
+        schedule.paid_count += 1;
+        refresh_delinquency(schedule, now);
+        loan.apply_installment(principal_part, due_date)?;
+        ctx.accounts.stats.record_repayment(amount);
+        ctx.accounts.stats.record_principal_repaid(principal_part);
+        
+        if schedule.paid_count as usize == schedule.installments.len() {
+            loan.transition(LoanStatus::Repaid)?;
+            asset.active_loans = asset.active_loans.saturating_sub(1);
+            ctx.accounts.stats.record_closure(loan.remaining_principal());
+            msg!("Final installment paid, loan repaid");
+        } else {
+            msg!("Installment {} paid: {}, late fee {}", index + 1, amount, fee);
+        }
+        Ok(())
+    }
+
+    // Permissionless check that flags schedules with missed installments
+    pub fn check_delinquency(ctx: Context<CheckDelinquency>) -> Result<()> {
+        let schedule = &mut ctx.accounts.schedule;
+        let now = Clock::get()?.unix_timestamp;
+        
+        refresh_delinquency(schedule, now);
+        
+        msg!("Missed installments: {}, delinquent: {}", schedule.missed_count, schedule.delinquent);
+        Ok(())
+    }
+
+    // Liquidate loan if risk too high or the position breaches its LTV limit
+    pub fn liquidate_loan(ctx: Context<LiquidateLoan>) -> Result<()> {
+        let loan = &mut ctx.accounts.loan;
+        let asset = &mut ctx.accounts.asset;
+        
+        require!(loan.status.is_open(), ErrorCode::LoanInactive);
+        require!(!loan.disputed, ErrorCode::LoanDisputed);
+        
+        let supplemental = supplemental_collateral(loan, &ctx.accounts.supplemental_asset)?;
+        let now = Clock::get()?.unix_timestamp;
+        require!(!asset.is_risk_frozen(now), ErrorCode::AssetRiskFrozen);
+        let trigger = liquidation_trigger(loan, asset, supplemental, now)?
+            .ok_or(ErrorCode::NotEligibleForLiquidation)?;
+        
+        liquidate(loan, asset, &mut ctx.accounts.stats, ctx.accounts.liquidator.key(), trigger, now)
+    }
+
+    // Settle a liquidated loan by handing its collateral to the liquidator, who
+    // signs and pays for the settlement record. The collateral value is compared
+    // with the debt at liquidation to record any surplus owed to the borrower or
+    // deficiency left to the lender. Each loan can be settled once.
+    pub fn seize_collateral(ctx: Context<SeizeCollateral>) -> Result<()> {
+        let loan = &ctx.accounts.loan;
+        let asset = &mut ctx.accounts.asset;
+        let settlement = &mut ctx.accounts.settlement;
+        
+        // Other liens on the asset would otherwise pass to the new owner
+        require!(!asset.is_encumbered(), ErrorCode::AssetEncumbered);
+        
+        let now = Clock::get()?.unix_timestamp;
+        let previous_owner = asset.owner;
+        asset.owner = ctx.accounts.recipient.key();
+        asset.delegate = Pubkey::default();
+        
+        settlement.loan = loan.key();
+        settlement.asset = asset.key();
+        settlement.previous_owner = previous_owner;
+        settlement.recipient = asset.owner;
+        settlement.debt = loan.liquidation_debt;
+        settlement.collateral_value = asset.valuation;
+        settlement.surplus = asset.valuation.saturating_sub(loan.liquidation_debt);
+        settlement.deficiency = loan.liquidation_debt.saturating_sub(asset.valuation);
+        settlement.settled_at = now;
+        settlement.bump = ctx.bumps.settlement;
+        
+        emit!(CollateralSeized {
+            loan: settlement.loan,
+            asset: settlement.asset,
+            previous_owner,
+            recipient: settlement.recipient,
+            surplus: settlement.surplus,
+            deficiency: settlement.deficiency,
+            settled_at: now,
+        });
+        
+        msg!(
+            "Collateral {} seized, surplus {}, deficiency {}",
+            asset.asset_id,
+            settlement.surplus,
+            settlement.deficiency
+        );
+        Ok(())
+    }
+
+    // Create the liquidation queue (admin only); anyone may fund it with SOL
+    // to pay crank rewards
+    pub fn initialize_liquidation_queue(ctx: Context<InitializeLiquidationQueue>) -> Result<()> {
+        let queue = &mut ctx.accounts.queue;
+        queue.entries = Vec::new();
+        queue.bump = ctx.bumps.queue;
+        
+        msg!("Liquidation queue initialized");
+        Ok(())
+    }
+
+    // Queue a loan that is currently eligible for liquidation (permissionless)
+    pub fn enqueue_liquidation(ctx: Context<EnqueueLiquidation>) -> Result<()> {
+        let queue = &mut ctx.accounts.queue;
+        let loan = &mut ctx.accounts.loan;
+        
+        require!(loan.status.is_open(), ErrorCode::LoanInactive);
+        require!(!loan.disputed, ErrorCode::LoanDisputed);
+        require!(
+            !queue.entries.iter().any(|entry| entry.loan == loan.key()),
+            ErrorCode::AlreadyQueued
+        );
+        require!(queue.entries.len() < MAX_LIQUIDATION_QUEUE_LEN, ErrorCode::LiquidationQueueFull);
+        
+        let supplemental = supplemental_collateral(loan, &ctx.accounts.supplemental_asset)?;
+        let now = Clock::get()?.unix_timestamp;
+        require!(!ctx.accounts.asset.is_risk_frozen(now), ErrorCode::AssetRiskFrozen);
+        liquidation_trigger(loan, &ctx.accounts.asset, supplemental, now)?
+            .ok_or(ErrorCode::NotEligibleForLiquidation)?;
+        
+        if matches!(loan.status, LoanStatus::Active | LoanStatus::Overdue) {
+            loan.transition(LoanStatus::Liquidating)?;
+        }
+        queue.entries.push(QueuedLiquidation { loan: loan.key(), enqueued_at: now });
+        
+        msg!("Loan queued for liquidation, position {}", queue.entries.len());
+        Ok(())
+    }
+
+    // Process up to `max_items` queued loans in order (permissionless). For each
+    // entry pass the loan, its asset and, if it has one, its top-up asset as
+    // remaining accounts. Loans that were cured, closed or disputed meanwhile
+    // are dropped; each liquidation pays the cranker from the queue's balance.
+    pub fn crank_liquidations<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CrankLiquidations<'info>>,
+        max_items: u8,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let mut accounts = ctx.remaining_accounts.iter();
+        let mut processed = 0usize;
+        let mut liquidated = 0u64;
+        
+        while processed < max_items as usize && processed < ctx.accounts.queue.entries.len() {
+            let entry = ctx.accounts.queue.entries[processed];
+            let loan_info = accounts.next().ok_or(ErrorCode::CrankAccountsMissing)?;
+            let asset_info = accounts.next().ok_or(ErrorCode::CrankAccountsMissing)?;
+            require_keys_eq!(loan_info.key(), entry.loan, ErrorCode::LoanAssetMismatch);
+            
+            let mut loan = Account::<Loan>::try_from(loan_info)?;
+            let mut asset = Account::<Asset>::try_from(asset_info)?;
+            require_keys_eq!(asset.key(), loan.asset, ErrorCode::LoanAssetMismatch);
+            let supplemental = if loan.supplemental_asset == Pubkey::default() {
+                None
+            } else {
+                let info = accounts.next().ok_or(ErrorCode::CrankAccountsMissing)?;
+                require_keys_eq!(info.key(), loan.supplemental_asset, ErrorCode::LoanAssetMismatch);
+                Some(Account::<Asset>::try_from(info)?)
+            };
+            processed += 1;
+            
+            // Frozen assets are dropped like cured loans and can be re-queued later
+            if !loan.status.is_open() || loan.disputed || asset.is_risk_frozen(now) {
+                continue;
+            }
+            let Some(trigger) = liquidation_trigger(&loan, &asset, supplemental.as_deref(), now)? else {
+                continue;
+            };
+            liquidate(&mut loan, &mut asset, &mut ctx.accounts.stats, ctx.accounts.cranker.key(), trigger, now)?;
+            loan.exit(ctx.program_id)?;
+            asset.exit(ctx.program_id)?;
+            liquidated += 1;
+        }
+        
+        ctx.accounts.queue.entries.drain(..processed);
+        
+        // Rewards stop, but processing continues, once the queue runs dry
+        let queue_info = ctx.accounts.queue.to_account_info();
+        let rent_floor = Rent::get()?.minimum_balance(queue_info.data_len());
+        let available = queue_info.lamports().saturating_sub(rent_floor);
+        let reward = liquidated.saturating_mul(LIQUIDATION_CRANK_REWARD).min(available);
+        if reward > 0 {
+            **queue_info.try_borrow_mut_lamports()? -= reward;
+            **ctx.accounts.cranker.to_account_info().try_borrow_mut_lamports()? += reward;
+        }
+        
+        msg!("Crank processed {} entries, liquidated {}, reward {}", processed, liquidated, reward);
+        Ok(())
+    }
+
+    // Re-evaluate an open loan (permissionless): Defaulted once past maturity plus
+    // grace, otherwise Liquidating while its health factor is below 1 and
+    // liquidation isn't paused, else Overdue past maturity or Active
+    pub fn check_loan_health(ctx: Context<CheckLoanHealth>) -> Result<()> {
+        let loan = &mut ctx.accounts.loan;
+        
+        require!(loan.status.is_open(), ErrorCode::LoanInactive);
+        
+        let supplemental = supplemental_collateral(loan, &ctx.accounts.supplemental_asset)?;
+        let now = Clock::get()?.unix_timestamp;
+        let (status, health_factor) = health_status(loan, &ctx.accounts.asset, supplemental, now)?;
+        loan.transition(status)?;
+        
+        msg!("Health factor (bps): {}, status: {:?}", health_factor, loan.status);
+        Ok(())
+    }
+
+    // Move a loan past maturity to Overdue or Defaulted, record its late fees
+    // and whether it can be liquidated (permissionless)
+    pub fn crank_loan_state(ctx: Context<CrankLoanState>) -> Result<()> {
+        let loan = &mut ctx.accounts.loan;
+        
+        require!(loan.status.is_open(), ErrorCode::LoanInactive);
+        
+        let now = Clock::get()?.unix_timestamp;
+        let previous_status = loan.status;
+        let status = scheduled_status(loan, now);
+        if status != previous_status {
+            loan.transition(status)?;
+        }
+        loan.late_fees = late_fee(loan, now);
+        
+        let supplemental = supplemental_collateral(loan, &ctx.accounts.supplemental_asset)?;
+        loan.liquidation_eligible = !is_liquidation_paused(loan, &ctx.accounts.asset, now)
+            && liquidation_trigger(loan, &ctx.accounts.asset, supplemental, now)?.is_some();
+        
+        emit!(LoanStateCranked {
+            loan: loan.key(),
+            previous_status,
+            status: loan.status,
+            late_fees: loan.late_fees,
+            liquidation_eligible: loan.liquidation_eligible,
+            cranked_at: now,
+        });
+        
+        msg!("Loan state: {:?}, late fees: {}", loan.status, loan.late_fees);
+        Ok(())
+    }
+
+    // Quote the loan's health factor, accrued interest and payoff amount as of
+    // now without modifying it; the quote is returned via `set_return_data`
+    pub fn quote_loan(ctx: Context<QuoteLoan>) -> Result<LoanQuote> {
+        let loan = &ctx.accounts.loan;
+        
+        require!(loan.status.is_open(), ErrorCode::LoanInactive);
+        
+        let supplemental = supplemental_collateral(loan, &ctx.accounts.supplemental_asset)?;
+        let now = Clock::get()?.unix_timestamp;
+        let payoff_amount = outstanding_debt(loan, now)?;
+        
+        Ok(LoanQuote {
+            health_factor_bps: health_factor_bps(collateral_limit(loan, &ctx.accounts.asset, supplemental), payoff_amount),
+            accrued_interest: payoff_amount - loan.remaining_principal(),
+            payoff_amount,
+            quoted_at: now,
+        })
+    }
+
+    // Pledge a second asset owned by the borrower to cure an unhealthy loan
+    pub fn top_up_collateral(ctx: Context<TopUpCollateral>) -> Result<()> {
+        let loan = &mut ctx.accounts.loan;
+        let supplemental = &mut ctx.accounts.supplemental_asset;
+        
+        require!(loan.status.is_open(), ErrorCode::LoanInactive);
+        require!(loan.supplemental_asset == Pubkey::default(), ErrorCode::InvalidTopUp);
+        require!(supplemental.is_active, ErrorCode::AssetInactive);
+        require!(supplemental.portfolio == Pubkey::default(), ErrorCode::AssetBundled);
+        
+        let rules = jurisdiction_rules(supplemental, &ctx.accounts.jurisdiction_config)?;
+        
+        supplemental.active_loans = supplemental.active_loans
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        loan.supplemental_asset = supplemental.key();
+        loan.supplemental_rules = CollateralRules::new(&ctx.accounts.asset_type_config, rules);
+        
+        let now = Clock::get()?.unix_timestamp;
+        let debt = outstanding_debt(loan, now)?;
+        let health_factor = health_factor_bps(
+            collateral_limit(loan, &ctx.accounts.asset, Some(supplemental)),
+            debt,
+        );
+        if health_factor >= HEALTH_FACTOR_ONE && loan.status == LoanStatus::Liquidating {
+            loan.transition(LoanStatus::Active)?;
+        }
+        
+        msg!("Collateral topped up with {}, health factor (bps): {}", supplemental.asset_id, health_factor);
+        Ok(())
+    }
+
+    // Free a top-up asset once its loan is closed (permissionless)
+    pub fn release_collateral(ctx: Context<ReleaseCollateral>) -> Result<()> {
+        let loan = &mut ctx.accounts.loan;
+        let supplemental = &mut ctx.accounts.supplemental_asset;
+        
+        require!(loan.status.is_closed(), ErrorCode::LoanStillOpen);
+        
+        supplemental.active_loans = supplemental.active_loans.saturating_sub(1);
+        loan.supplemental_asset = Pubkey::default();
+        loan.supplemental_rules = CollateralRules::default();
+        
+        msg!("Collateral released: {}", supplemental.asset_id);
+        Ok(())
+    }
+
+    // Book the unrecovered part of a liquidated loan as a loss (admin only): the
+    // debt at liquidation less the collateral value its settlement recovered.
+    // Loans that were never liquidated and seized, or whose collateral covered
+    // the debt, cannot be written off.
+    pub fn write_off_loan(ctx: Context<WriteOffLoan>) -> Result<()> {
+        let loan = &mut ctx.accounts.loan;
+        
+        let now = Clock::get()?.unix_timestamp;
+        let amount = write_off_amount(loan, ctx.accounts.settlement.as_deref())?;
+        
+        loan.transition(LoanStatus::WrittenOff)?;
+        loan.written_off_amount = amount;
+        ctx.accounts.stats.record_write_off(amount);
+        
+        emit!(LoanWrittenOff {
+            loan: loan.key(),
+            asset: ctx.accounts.asset.key(),
+            amount,
+            written_off_by: ctx.accounts.admin.key(),
+            written_off_at: now,
+        });
+        
+        msg!("Loan written off: {}", amount);
+        Ok(())
+    }
+
+    // Flag an active loan as disputed (borrower or lender); liquidation is paused
+    // until the arbitrator rules. A loan can be disputed only once.
+    pub fn raise_dispute(ctx: Context<RaiseDispute>) -> Result<()> {
+        let loan = &mut ctx.accounts.loan;
+        let party = ctx.accounts.party.key();
+        
+        require!(party == loan.borrower || party == loan.lender, ErrorCode::Unauthorized);
+        require!(loan.status.is_open(), ErrorCode::LoanInactive);
+        require!(!loan.disputed && loan.dispute_outcome.is_none(), ErrorCode::DisputeAlreadyRaised);
+        
+        loan.disputed = true;
+        loan.dispute_raised_by = party;
+        loan.dispute_raised_at = Clock::get()?.unix_timestamp;
+        
+        msg!("Loan disputed by {}", party);
+        Ok(())
+    }
+
+    // Record the arbitrator's ruling on a disputed loan and lift the liquidation pause
+    pub fn resolve_dispute(ctx: Context<ResolveDispute>, outcome: DisputeOutcome) -> Result<()> {
+        let loan = &mut ctx.accounts.loan;
+        
+        require!(loan.disputed, ErrorCode::LoanNotDisputed);
+        
+        loan.disputed = false;
+        loan.dispute_outcome = Some(outcome);
+        loan.dispute_resolved_at = Clock::get()?.unix_timestamp;
+        
+        msg!("Dispute resolved: {:?}", outcome);
+        Ok(())
+    }
+
+    // Grant forbearance on a troubled loan (admin acting as lender); suspends
+    // interest accrual and/or pushes out maturity, recording the modification
+    pub fn grant_forbearance(
+        ctx: Context<GrantForbearance>,
+        pause_duration: i64,
+        maturity_extension: i64,
+        reason: String,
+    ) -> Result<()> {
+        let loan = &mut ctx.accounts.loan;
+        let modification = &mut ctx.accounts.modification;
+        
+        require!(loan.status.is_open(), ErrorCode::LoanInactive);
+        require!(pause_duration >= 0 && maturity_extension >= 0, ErrorCode::InvalidForbearance);
+        require!(pause_duration > 0 || maturity_extension > 0, ErrorCode::InvalidForbearance);
+        require!(reason.len() <= MAX_REASON_LEN, ErrorCode::InvalidForbearance);
+        
+        let now = Clock::get()?.unix_timestamp;
+        
+        if pause_duration > 0 {
+            require!(now >= loan.forbearance_end, ErrorCode::ForbearanceActive);
+            // Fold the finished window into the running total before opening a new one
+            loan.forborne_seconds = loan.forborne_seconds
+                .checked_add(loan.forbearance_end - loan.forbearance_start)
+                .ok_or(ErrorCode::MathOverflow)?;
+            loan.forbearance_start = now;
+            loan.forbearance_end = now
+                .checked_add(pause_duration)
+                .ok_or(ErrorCode::MathOverflow)?;
+        }
+        
+        loan.end_time = loan.end_time
+            .checked_add(maturity_extension)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        modification.loan = loan.key();
+        modification.index = loan.modification_count;
+        modification.granted_by = *ctx.accounts.admin.key;
+        modification.granted_at = now;
+        modification.interest_paused_until = if pause_duration > 0 { loan.forbearance_end } else { 0 };
+        modification.maturity_extension = maturity_extension;
+        modification.new_end_time = loan.end_time;
+        modification.reason = reason;
+        modification.bump = ctx.bumps.modification;
+        
+        loan.modification_count = loan.modification_count
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        msg!("Forbearance granted: pause {}s, maturity +{}s", pause_duration, maturity_extension);
+        Ok(())
+    }
+
+    // Replace a maturing loan with a successor term agreed by borrower and
+    // lender: unpaid interest and late fees are capitalized into the new
+    // principal, which must fit the asset's current LTV limit, jurisdiction cap included. The loan account
+    // carries on as the successor; the old terms are kept in a rollover record.
+    pub fn rollover_loan(ctx: Context<RolloverLoan>, interest_rate: u64, duration: i64) -> Result<()> {
+        let loan = &mut ctx.accounts.loan;
+        let asset = &ctx.accounts.asset;
+        let type_config = &ctx.accounts.asset_type_config;
+        
+        require!(
+            matches!(loan.status, LoanStatus::Active | LoanStatus::Overdue) && !loan.disputed,
+            ErrorCode::LoanNotRollable
+        );
+        // Installments are pinned to the old term
+        require!(ctx.accounts.schedule.data_is_empty(), ErrorCode::LoanNotRollable);
+        require!(
+            duration > 0 && duration <= type_config.max_duration,
+            ErrorCode::InvalidLoanDuration
+        );
+        
+        let now = Clock::get()?.unix_timestamp;
+        require!(asset.is_active, ErrorCode::AssetInactive);
+        require!(!asset.is_risk_frozen(now), ErrorCode::AssetRiskFrozen);
+        
+        validate_floating_rate(interest_rate, loan.floating_rate.as_ref())?;
+        
+        let previous_principal = loan.remaining_principal();
+        let previous_end_time = loan.end_time;
+        let new_principal = loan.roll_over(now, interest_rate, duration)?;
+        let rules = jurisdiction_rules(asset, &ctx.accounts.jurisdiction_config)?;
+        require!(
+            new_principal <= max_loan_in_jurisdiction(asset.valuation, asset.risk_score, type_config, rules),
+            ErrorCode::LoanTooHigh
+        );
+        loan.collateral_rules = CollateralRules::new(type_config, rules);
+        loan.risk_score_at_creation = asset.risk_score;
+        
+        let fee = origination_fee(new_principal, ctx.accounts.config.origination_fee_bps);
+        collect_protocol_share(
+            &mut ctx.accounts.revenue,
+            &ctx.accounts.revenue_vault,
+            &ctx.accounts.token_program,
+            &ctx.accounts.repayment_mint,
+            &ctx.accounts.borrower_token_account.to_account_info(),
+            &ctx.accounts.borrower.to_account_info(),
+            fee,
+        )?;
+        
+        let rollover = &mut ctx.accounts.rollover;
+        rollover.loan = loan.key();
+        rollover.index = loan.rollover_count;
+        rollover.rolled_at = now;
+        rollover.previous_principal = previous_principal;
+        rollover.previous_end_time = previous_end_time;
+        rollover.capitalized = new_principal - previous_principal;
+        rollover.new_principal = new_principal;
+        rollover.new_end_time = loan.end_time;
+        rollover.interest_rate = interest_rate;
+        rollover.fee = fee;
+        rollover.bump = ctx.bumps.rollover;
+        
+        loan.rollover_count = loan.rollover_count
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        ctx.accounts.stats.record_closure(previous_principal);
+        ctx.accounts.stats.record_disbursement(new_principal);
+        
+        msg!("Loan rolled over: principal {} -> {}, fee {}", previous_principal, new_principal, fee);
+        Ok(())
+    }
+
+    // Reset a floating-rate loan to benchmark + spread within its floor and cap
+    // (permissionless). Interest up to now is banked at the old rate first.
+    pub fn reprice_loan(ctx: Context<RepriceLoan>) -> Result<()> {
+        let loan = &mut ctx.accounts.loan;
+        let benchmark = &ctx.accounts.benchmark;
+        
+        require!(loan.status.is_open(), ErrorCode::LoanInactive);
+        let terms = loan.floating_rate.ok_or(ErrorCode::NotFloatingRate)?;
+        require_keys_eq!(benchmark.feed, terms.benchmark, ErrorCode::OracleMismatch);
+        
+        let now = Clock::get()?.unix_timestamp;
+        let benchmark_bps = read_benchmark_rate(benchmark, &ctx.accounts.feed, now)?;
+        let previous_rate = loan.interest_rate;
+        
+        loan.checkpoint_interest(now)?;
+        loan.interest_rate = terms.rate_for(benchmark_bps);
+        loan.last_repriced_at = now;
+        
+        emit!(LoanRepriced {
+            loan: loan.key(),
+            benchmark_bps,
+            previous_rate,
+            interest_rate: loan.interest_rate,
+            repriced_at: now,
+        });
+        
+        msg!("Loan repriced: {} -> {} bps", previous_rate, loan.interest_rate);
+        Ok(())
+    }
+
+    // Deactivate an asset (owner only); fails while loans are open against it
+    pub fn deactivate_asset(ctx: Context<DeactivateAsset>) -> Result<()> {
+        let asset = &mut ctx.accounts.asset;
+        
+        require!(asset.is_active, ErrorCode::AssetInactive);
+        require!(!asset.is_encumbered(), ErrorCode::AssetHasActiveLoans);
+        
+        asset.is_active = false;
+        
+        msg!("Asset deactivated: {}", asset.asset_id);
+        Ok(())
+    }
+
+    // Hand the asset to a new owner (owner only). Blocked while any loan request,
+    // loan or top-up pledge encumbers it; the previous owner's delegate is cleared.
+    pub fn transfer_asset(ctx: Context<TransferAsset>, new_owner: Pubkey) -> Result<()> {
+        if let Some(rules) = jurisdiction_rules(&ctx.accounts.asset, &ctx.accounts.jurisdiction_config)? {
+            match rules.transfer_restriction {
+                TransferRestriction::Unrestricted => {}
+                TransferRestriction::ComplianceApproval => require!(
+                    ctx.accounts.compliance_authority.as_ref()
+                        .is_some_and(|signer| signer.key() == ctx.accounts.config.compliance_authority),
+                    ErrorCode::TransferRestricted
+                ),
+                TransferRestriction::Prohibited => return err!(ErrorCode::TransferRestricted),
+            }
+        }
+        
+        let asset = &mut ctx.accounts.asset;
+        
+        require!(!asset.is_encumbered(), ErrorCode::AssetEncumbered);
+        require!(
+            new_owner != Pubkey::default() && new_owner != asset.owner,
+            ErrorCode::InvalidNewOwner
+        );
+        
+        let previous_owner = asset.owner;
+        asset.owner = new_owner;
+        asset.delegate = Pubkey::default();
+        
+        emit!(AssetTransferred {
+            asset: asset.key(),
+            previous_owner,
+            new_owner,
+            transferred_at: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Asset {} transferred to {}", asset.asset_id, new_owner);
+        Ok(())
+    }
+
+    // Appoint a delegate that can manage the asset but never own or borrow against it
+    pub fn set_delegate(ctx: Context<ManageDelegate>, delegate: Pubkey) -> Result<()> {
+        let asset = &mut ctx.accounts.asset;
+        
+        require!(
+            delegate != Pubkey::default() && delegate != asset.owner,
+            ErrorCode::InvalidDelegate
+        );
+        
+        asset.delegate = delegate;
+        
+        msg!("Delegate set for {}: {}", asset.asset_id, delegate);
+        Ok(())
+    }
+
+    // Remove the current delegate (owner only)
+    pub fn revoke_delegate(ctx: Context<ManageDelegate>) -> Result<()> {
+        let asset = &mut ctx.accounts.asset;
+        
+        asset.delegate = Pubkey::default();
+        
+        msg!("Delegate revoked for {}", asset.asset_id);
+        Ok(())
+    }
+
+    // Point the asset at new off-chain metadata (owner or delegate)
+    pub fn update_asset_metadata(ctx: Context<ManageAsset>, metadata_uri: String) -> Result<()> {
+        let asset = &mut ctx.accounts.asset;
+        
+        require!(metadata_uri.len() <= MAX_METADATA_URI_LEN, ErrorCode::MetadataUriTooLong);
+        
+        asset.metadata_uri = metadata_uri;
+        
+        msg!("Metadata updated for {}", asset.asset_id);
+        Ok(())
+    }
+
+    // Mint the asset's Metaplex NFT to the owner's associated token account (owner only)
+    pub fn mint_asset_nft(ctx: Context<MintAssetNft>) -> Result<()> {
+        let asset = &ctx.accounts.asset;
+        let asset_seeds: &[&[u8]] = &[b"asset", asset.asset_id.as_bytes(), &[asset.bump]];
+        let asset_key = asset.key();
+        let mint_seeds: &[&[u8]] = &[b"asset_nft", asset_key.as_ref(), &[ctx.bumps.nft_mint]];
+        
+        // Zero-decimal mint at a PDA so each asset has exactly one NFT
+        anchor_lang::system_program::create_account(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::CreateAccount {
+                    from: ctx.accounts.owner.to_account_info(),
+                    to: ctx.accounts.nft_mint.to_account_info(),
+                },
+                &[mint_seeds],
+            ),
+            Rent::get()?.minimum_balance(Mint::LEN),
+            Mint::LEN as u64,
+            &token::ID,
+        )?;
+        token::initialize_mint2(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                token::InitializeMint2 { mint: ctx.accounts.nft_mint.to_account_info() },
+            ),
+            0,
+            &asset_key,
+            Some(&asset_key),
+        )?;
+        
+        invoke(
+            &Instruction {
+                program_id: ASSOCIATED_TOKEN_PROGRAM_ID,
+                accounts: vec![
+                    AccountMeta::new(ctx.accounts.owner.key(), true),
+                    AccountMeta::new(ctx.accounts.owner_nft_account.key(), false),
+                    AccountMeta::new_readonly(ctx.accounts.owner.key(), false),
+                    AccountMeta::new_readonly(ctx.accounts.nft_mint.key(), false),
+                    AccountMeta::new_readonly(ctx.accounts.system_program.key(), false),
+                    AccountMeta::new_readonly(ctx.accounts.token_program.key(), false),
+                ],
+                data: vec![CREATE_IDEMPOTENT_TAG],
+            },
+            &[
+                ctx.accounts.owner.to_account_info(),
+                ctx.accounts.owner_nft_account.to_account_info(),
+                ctx.accounts.nft_mint.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.associated_token_program.to_account_info(),
+            ],
+        )?;
+        
+        token::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::MintTo {
+                    mint: ctx.accounts.nft_mint.to_account_info(),
+                    to: ctx.accounts.owner_nft_account.to_account_info(),
+                    authority: asset.to_account_info(),
+                },
+                &[asset_seeds],
+            ),
+            1,
+        )?;
+        
+        let mut data = vec![CREATE_METADATA_ACCOUNT_V3_TAG];
+        data.extend(nft_metadata_data(asset)?);
+        data.push(1); // is_mutable, so the URI can follow the asset
+        data.push(0); // collection_details: None
+        invoke_signed(
+            &Instruction {
+                program_id: TOKEN_METADATA_PROGRAM_ID,
+                accounts: vec![
+                    AccountMeta::new(ctx.accounts.metadata.key(), false),
+                    AccountMeta::new_readonly(ctx.accounts.nft_mint.key(), false),
+                    AccountMeta::new_readonly(asset.key(), true),
+                    AccountMeta::new(ctx.accounts.owner.key(), true),
+                    AccountMeta::new_readonly(asset.key(), true),
+                    AccountMeta::new_readonly(ctx.accounts.system_program.key(), false),
+                ],
+                data,
+            },
+            &[
+                ctx.accounts.metadata.to_account_info(),
+                ctx.accounts.nft_mint.to_account_info(),
+                asset.to_account_info(),
+                ctx.accounts.owner.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                ctx.accounts.token_metadata_program.to_account_info(),
+            ],
+            &[asset_seeds],
+        )?;
+        
+        // Metadata creation needs the mint authority; afterwards supply is fixed
+        // at one and the asset keeps only the freeze authority
+        token::set_authority(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::SetAuthority {
+                    current_authority: asset.to_account_info(),
+                    account_or_mint: ctx.accounts.nft_mint.to_account_info(),
+                },
+                &[asset_seeds],
+            ),
+            token::spl_token::instruction::AuthorityType::MintTokens,
+            None,
+        )?;
+        
+        if asset.is_encumbered() {
+            set_nft_frozen(
+                &ctx.accounts.token_program,
+                &ctx.accounts.owner_nft_account.to_account_info(),
+                &ctx.accounts.nft_mint.to_account_info(),
+                asset,
+                true,
+            )?;
+        }
+        
+        msg!("Asset NFT minted for {}: {}", asset.asset_id, ctx.accounts.nft_mint.key());
+        Ok(())
+    }
+
+    // Freeze the NFT while loans are open, thaw it once they close, and mirror
+    // the metadata URI (permissionless)
+    pub fn sync_asset_nft(ctx: Context<SyncAssetNft>) -> Result<()> {
+        let asset = &ctx.accounts.asset;
+        let asset_seeds: &[&[u8]] = &[b"asset", asset.asset_id.as_bytes(), &[asset.bump]];
+        
+        let locked = asset.is_encumbered();
+        if ctx.accounts.nft_account.is_frozen() != locked {
+            set_nft_frozen(
+                &ctx.accounts.token_program,
+                &ctx.accounts.nft_account.to_account_info(),
+                &ctx.accounts.nft_mint.to_account_info(),
+                asset,
+                locked,
+            )?;
+        }
+        
+        let mut data = vec![UPDATE_METADATA_ACCOUNT_V2_TAG, 1];
+        data.extend(nft_metadata_data(asset)?);
+        data.extend([0, 0, 0]); // update_authority, primary_sale_happened, is_mutable unchanged
+        invoke_signed(
+            &Instruction {
+                program_id: TOKEN_METADATA_PROGRAM_ID,
+                accounts: vec![
+                    AccountMeta::new(ctx.accounts.metadata.key(), false),
+                    AccountMeta::new_readonly(asset.key(), true),
+                ],
+                data,
+            },
+            &[
+                ctx.accounts.metadata.to_account_info(),
+                asset.to_account_info(),
+                ctx.accounts.token_metadata_program.to_account_info(),
+            ],
+            &[asset_seeds],
+        )?;
+        
+        msg!("Asset NFT synced for {} (locked: {})", asset.asset_id, locked);
+        Ok(())
+    }
+
+    // Flag the asset for re-appraisal by the valuation oracle (owner or delegate)
+    pub fn request_valuation_refresh(ctx: Context<ManageAsset>) -> Result<()> {
+        let asset = &mut ctx.accounts.asset;
+        
+        require!(asset.is_active, ErrorCode::AssetInactive);
+        
+        asset.valuation_refresh_requested_at = Clock::get()?.unix_timestamp;
+        
+        msg!("Valuation refresh requested for {}", asset.asset_id);
+        Ok(())
+    }
+
+    // Reactivate a previously deactivated asset (admin only)
+    pub fn reactivate_asset(ctx: Context<ReactivateAsset>) -> Result<()> {
+        let asset = &mut ctx.accounts.asset;
+        
+        require!(!asset.is_active, ErrorCode::AssetAlreadyActive);
+        
+        asset.is_active = true;
+        
+        msg!("Asset reactivated: {}", asset.asset_id);
+        Ok(())
+    }
+
+    // Bundle the owner's unencumbered assets, passed as remaining accounts, into
+    // one portfolio asset that collateralizes loans like any other. Each member
+    // keeps a lien until the portfolio is unbundled.
+    pub fn bundle_assets<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BundleAssets<'info>>,
+        portfolio_id: String,
+        metadata_uri: String,
+    ) -> Result<()> {
+        require!(portfolio_id.len() <= MAX_ASSET_ID_LEN, ErrorCode::AssetFieldTooLong);
+        require!(metadata_uri.len() <= MAX_METADATA_URI_LEN, ErrorCode::MetadataUriTooLong);
+        require!(
+            (2..=MAX_PORTFOLIO_ASSETS).contains(&ctx.remaining_accounts.len()),
+            ErrorCode::InvalidPortfolio
+        );
+        
+        let owner = ctx.accounts.owner.key();
+        let mut members: Vec<Account<Asset>> = Vec::with_capacity(ctx.remaining_accounts.len());
+        for info in ctx.remaining_accounts {
+            let member = Account::<Asset>::try_from(info)?;
+            require_keys_eq!(member.owner, owner, ErrorCode::Unauthorized);
+            require!(member.is_active, ErrorCode::AssetInactive);
+            require!(!member.is_encumbered(), ErrorCode::AssetEncumbered);
+            require!(
+                member.portfolio == Pubkey::default() && member.asset_type != PORTFOLIO_ASSET_TYPE,
+                ErrorCode::AssetBundled
+            );
+            // Members share a jurisdiction, which the portfolio inherits
+            require!(
+                members.iter().all(|other| other.key() != member.key() && other.jurisdiction == member.jurisdiction),
+                ErrorCode::InvalidPortfolio
+            );
+            members.push(member);
+        }
+        
+        let metrics = portfolio_metrics(members.iter().map(|member| &**member))?;
+        let portfolio_asset = &mut ctx.accounts.portfolio_asset;
+        portfolio_asset.set_inner(Asset {
+            asset_id: portfolio_id,
+            asset_type: PORTFOLIO_ASSET_TYPE.to_string(),
+            valuation: metrics.valuation,
+            metadata_uri,
+            owner,
+            is_active: true,
+            risk_score: metrics.risk_score,
+            bump: ctx.bumps.portfolio_asset,
+            active_loans: 0,
+            delegate: Pubkey::default(),
+            valuation_refresh_requested_at: 0,
+            last_risk_report: 0,
+            risk_frozen_until: metrics.risk_frozen_until,
+            breaker_confirmers: Vec::new(),
+            last_risk_update: metrics.last_risk_update,
+            jurisdiction: members[0].jurisdiction,
+            portfolio: Pubkey::default(),
+        });
+        
+        for member in members.iter_mut() {
+            member.portfolio = portfolio_asset.key();
+            member.active_loans = member.active_loans
+                .checked_add(1)
+                .ok_or(ErrorCode::MathOverflow)?;
+            member.exit(ctx.program_id)?;
+        }
+        
+        let portfolio = &mut ctx.accounts.portfolio;
+        portfolio.asset = portfolio_asset.key();
+        portfolio.members = members.iter().map(|member| member.key()).collect();
+        portfolio.bundled_at = Clock::get()?.unix_timestamp;
+        portfolio.bump = ctx.bumps.portfolio;
+        
+        ctx.accounts.stats.total_assets = ctx.accounts.stats.total_assets.saturating_add(1);
+        
+        msg!(
+            "Portfolio {} bundles {} assets, valuation {}, risk score {}",
+            portfolio_asset.asset_id,
+            portfolio.members.len(),
+            metrics.valuation,
+            metrics.risk_score
+        );
+        Ok(())
+    }
+
+    // Re-derive a portfolio's valuation and risk score from its members, passed
+    // as remaining accounts in bundling order (permissionless)
+    pub fn refresh_portfolio<'info>(ctx: Context<'_, '_, 'info, 'info, RefreshPortfolio<'info>>) -> Result<()> {
+        let members = portfolio_members(ctx.remaining_accounts, &ctx.accounts.portfolio.members)?;
+        let metrics = portfolio_metrics(members.iter().map(|member| &**member))?;
+        
+        let portfolio_asset = &mut ctx.accounts.portfolio_asset;
+        metrics.apply_to(portfolio_asset);
+        
+        msg!(
+            "Portfolio {} refreshed, valuation {}, risk score {}",
+            portfolio_asset.asset_id,
+            metrics.valuation,
+            metrics.risk_score
+        );
+        Ok(())
+    }
+
+    // Dissolve a portfolio without liens (owner only), releasing its members,
+    // passed as remaining accounts in bundling order, to the portfolio's owner
+    pub fn unbundle_assets<'info>(ctx: Context<'_, '_, 'info, 'info, UnbundleAssets<'info>>) -> Result<()> {
+        require!(!ctx.accounts.portfolio_asset.is_encumbered(), ErrorCode::AssetEncumbered);
+        
+        let owner = ctx.accounts.owner.key();
+        let mut members = portfolio_members(ctx.remaining_accounts, &ctx.accounts.portfolio.members)?;
+        for member in members.iter_mut() {
+            member.portfolio = Pubkey::default();
+            member.active_loans = member.active_loans
+                .checked_sub(1)
+                .ok_or(ErrorCode::MathOverflow)?;
+            // The portfolio may have changed hands since it was bundled
+            if member.owner != owner {
+                member.owner = owner;
+                member.delegate = Pubkey::default();
+            }
+            member.exit(ctx.program_id)?;
+        }
+        
+        ctx.accounts.stats.total_assets = ctx.accounts.stats.total_assets.saturating_sub(1);
+        
+        msg!("Portfolio {} unbundled into {} assets", ctx.accounts.portfolio_asset.asset_id, members.len());
+        Ok(())
+    }
+}
+
+// ==================== Switchboard ====================
+
+pub const SWITCHBOARD_PROGRAM_ID: Pubkey = pubkey!("SW1TCH7qEPTdLsDHRgPuMQjbQxKdH2aBStViMFnt64f");
+
+// sha256("account:AggregatorAccountData")[..8]
+const AGGREGATOR_DISCRIMINATOR: [u8; 8] = [217, 230, 65, 101, 201, 162, 27, 125];
+
+// Offset of `latest_confirmed_round` in the packed v2 AggregatorAccountData
+const LATEST_ROUND_OFFSET: usize = 341;
+const ROUND_LEN: usize = 65;
+
+/// Switchboard's fixed-point decimal: `mantissa / 10^scale`.
+pub struct SwitchboardDecimal {
+    pub mantissa: i128,
+    pub scale: u32,
+}
+
+impl SwitchboardDecimal {
+    fn read(data: &[u8]) -> Self {
+        Self {
+            mantissa: i128::from_le_bytes(data[0..16].try_into().unwrap()),
+            scale: u32::from_le_bytes(data[16..20].try_into().unwrap()),
+        }
+    }
+
+    /// Integer part, or `None` if the scale is out of range.
+    pub fn to_integer(&self) -> Option<i128> {
+        Some(self.mantissa / 10i128.checked_pow(self.scale)?)
+    }
+
+    /// A percentage as basis points, truncated toward zero.
+    pub fn percent_to_bps(&self) -> Option<i128> {
+        Some(self.mantissa.checked_mul(100)? / 10i128.checked_pow(self.scale)?)
+    }
+
+    /// Absolute mantissa rescaled to `scale` (which must be >= `self.scale`).
+    fn rescaled(&self, scale: u32) -> Option<i128> {
+        self.mantissa.checked_abs()?.checked_mul(10i128.checked_pow(scale - self.scale)?)
+    }
+}
+
+pub struct SwitchboardRound {
+    pub num_success: u32,
+    pub open_timestamp: i64,
+    pub result: SwitchboardDecimal,
+    pub std_deviation: SwitchboardDecimal,
+}
+
+/// Decode the latest confirmed round from raw aggregator account data.
+pub fn read_switchboard_round(data: &[u8]) -> Result<SwitchboardRound> {
+    require!(
+        data.len() >= LATEST_ROUND_OFFSET + ROUND_LEN && data[..8] == AGGREGATOR_DISCRIMINATOR,
+        ErrorCode::InvalidOracleAccount
+    );
+    
+    let round = &data[LATEST_ROUND_OFFSET..LATEST_ROUND_OFFSET + ROUND_LEN];
+    let parsed = SwitchboardRound {
+        num_success: u32::from_le_bytes(round[0..4].try_into().unwrap()),
+        open_timestamp: i64::from_le_bytes(round[17..25].try_into().unwrap()),
+        result: SwitchboardDecimal::read(&round[25..45]),
+        std_deviation: SwitchboardDecimal::read(&round[45..65]),
+    };
+    require!(parsed.num_success > 0, ErrorCode::StaleOracleRound);
+    Ok(parsed)
+}
+
+/// Whether the round's standard deviation is within `max_variance_bps` of its result.
+pub fn within_variance(
+    result: &SwitchboardDecimal,
+    std_deviation: &SwitchboardDecimal,
+    max_variance_bps: u16,
+) -> bool {
+    let scale = result.scale.max(std_deviation.scale);
+    let (Some(value), Some(deviation)) = (result.rescaled(scale), std_deviation.rescaled(scale)) else {
+        return false;
+    };
+    match (deviation.checked_mul(10_000), value.checked_mul(max_variance_bps as i128)) {
+        (Some(lhs), Some(rhs)) => lhs <= rhs,
+        _ => false,
+    }
+}
+
+pub fn validate_oracle_rules(max_staleness: i64, max_variance_bps: u16) -> Result<()> {
+    require!(
+        max_staleness > 0 && max_variance_bps <= 10_000,
+        ErrorCode::InvalidOracleConfig
+    );
+    Ok(())
+}
+
+// ==================== Token Programs ====================
+
+pub const TOKEN_2022_PROGRAM_ID: Pubkey = pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+static TOKEN_PROGRAM_IDS: [Pubkey; 2] = [token::ID, TOKEN_2022_PROGRAM_ID];
+
+// Base layouts shared by both programs; Token-2022 appends an account type
+// byte and TLV extensions after the 165-byte account length
+const TOKEN_ACCOUNT_LEN: usize = 165;
+const MINT_LEN: usize = 82;
+const ACCOUNT_TYPE_MINT: u8 = 1;
+
+// Token-2022 extension type tags
+const EXTENSION_TRANSFER_FEE_CONFIG: u16 = 1;
+const EXTENSION_NON_TRANSFERABLE: u16 = 9;
+const EXTENSION_PERMANENT_DELEGATE: u16 = 12;
+const EXTENSION_TRANSFER_HOOK: u16 = 14;
+
+/// Extensions that would stop repayments from reaching the lender: the token
+/// can't move, the issuer can pull it back, or transfers need extra accounts.
+/// Transfer fees are grossed up and interest-bearing mints only change the
+/// displayed amount, so both are supported.
+const UNSUPPORTED_MINT_EXTENSIONS: [u16; 3] = [
+    EXTENSION_NON_TRANSFERABLE,
+    EXTENSION_PERMANENT_DELEGATE,
+    EXTENSION_TRANSFER_HOOK,
+];
+
+// TokenInstruction::TransferChecked, understood by both programs
+const TRANSFER_CHECKED_TAG: u8 = 12;
+
+/// SPL Token or Token-2022, chosen per instruction by the mint's owner.
+#[derive(Clone)]
+pub struct TokenPrograms;
+
+impl anchor_lang::Ids for TokenPrograms {
+    fn ids() -> &'static [Pubkey] {
+        &TOKEN_PROGRAM_IDS
+    }
+}
+
+/// The fields of a token account this program reads, from either token program.
+#[derive(Clone)]
+pub struct RepaymentTokenAccount {
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+}
+
+impl AccountDeserialize for RepaymentTokenAccount {
+    fn try_deserialize_unchecked(buf: &mut &[u8]) -> Result<Self> {
+        let data: &[u8] = buf;
+        // The state byte is 0 for uninitialized accounts
+        require!(data.len() >= TOKEN_ACCOUNT_LEN && data[108] != 0, ErrorCode::InvalidTokenAccount);
+        Ok(Self {
+            mint: Pubkey::new_from_array(data[0..32].try_into().unwrap()),
+            owner: Pubkey::new_from_array(data[32..64].try_into().unwrap()),
+            amount: u64::from_le_bytes(data[64..72].try_into().unwrap()),
+        })
+    }
+}
+
+impl AccountSerialize for RepaymentTokenAccount {}
+
+impl anchor_lang::Owners for RepaymentTokenAccount {
+    fn owners() -> &'static [Pubkey] {
+        &TOKEN_PROGRAM_IDS
+    }
+}
+
+/// Transfer fee in effect from `epoch` on.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TransferFee {
+    pub epoch: u64,
+    pub maximum_fee: u64,
+    pub basis_points: u16,
+}
+
+impl TransferFee {
+    fn read(data: &[u8]) -> Self {
+        Self {
+            epoch: u64::from_le_bytes(data[0..8].try_into().unwrap()),
+            maximum_fee: u64::from_le_bytes(data[8..16].try_into().unwrap()),
+            basis_points: u16::from_le_bytes(data[16..18].try_into().unwrap()),
+        }
+    }
+
+    /// Fee Token-2022 withholds when `amount` is sent.
+    pub fn fee(&self, amount: u64) -> Option<u64> {
+        let fee = (amount as u128 * self.basis_points as u128).div_ceil(10_000);
+        Some(u64::try_from(fee).ok()?.min(self.maximum_fee))
+    }
+
+    /// Smallest amount to send so that `net` arrives after the fee, or `None`
+    /// if no amount does.
+    pub fn gross_amount(&self, net: u64) -> Option<u64> {
+        if self.basis_points == 0 || net == 0 {
+            return Some(net);
+        }
+        if self.basis_points >= 10_000 {
+            return None;
+        }
+        let gross = (net as u128 * 10_000).div_ceil(10_000 - self.basis_points as u128);
+        if gross - net as u128 >= self.maximum_fee as u128 {
+            net.checked_add(self.maximum_fee)
+        } else {
+            u64::try_from(gross).ok()
+        }
+    }
+}
+
+/// The fields of a mint this program reads, from either token program.
+#[derive(Clone)]
+pub struct RepaymentMint {
+    pub decimals: u8,
+    /// Older and newer fee of the transfer-fee extension, if present.
+    pub transfer_fees: Option<(TransferFee, TransferFee)>,
+    /// First extension the protocol can't settle loans in, if any.
+    pub unsupported_extension: Option<u16>,
+}
+
+impl RepaymentMint {
+    /// Amount to send for `net` to arrive under the fee in effect at `epoch`.
+    pub fn gross_amount(&self, net: u64, epoch: u64) -> Result<u64> {
+        let Some((older, newer)) = self.transfer_fees else {
+            return Ok(net);
+        };
+        let fee = if epoch >= newer.epoch { newer } else { older };
+        fee.gross_amount(net).ok_or(ErrorCode::MathOverflow.into())
+    }
+}
+
+impl AccountDeserialize for RepaymentMint {
+    fn try_deserialize_unchecked(buf: &mut &[u8]) -> Result<Self> {
+        let data: &[u8] = buf;
+        // Byte 45 is `is_initialized`
+        require!(data.len() >= MINT_LEN && data[45] != 0, ErrorCode::InvalidTokenAccount);
+        let mut mint = Self {
+            decimals: data[44],
+            transfer_fees: None,
+            unsupported_extension: None,
+        };
+        if data.len() <= TOKEN_ACCOUNT_LEN {
+            return Ok(mint);
+        }
+        
+        require!(data[TOKEN_ACCOUNT_LEN] == ACCOUNT_TYPE_MINT, ErrorCode::InvalidTokenAccount);
+        let mut cursor = TOKEN_ACCOUNT_LEN + 1;
+        while cursor + 4 <= data.len() {
+            let extension = u16::from_le_bytes(data[cursor..cursor + 2].try_into().unwrap());
+            let len = u16::from_le_bytes(data[cursor + 2..cursor + 4].try_into().unwrap()) as usize;
+            let value = data.get(cursor + 4..cursor + 4 + len).ok_or(ErrorCode::InvalidTokenAccount)?;
+            match extension {
+                // Uninitialized: the rest of the account is padding
+                0 => break,
+                EXTENSION_TRANSFER_FEE_CONFIG => {
+                    require!(len >= 108, ErrorCode::InvalidTokenAccount);
+                    mint.transfer_fees = Some((TransferFee::read(&value[72..90]), TransferFee::read(&value[90..108])));
+                }
+                tag if UNSUPPORTED_MINT_EXTENSIONS.contains(&tag) => {
+                    mint.unsupported_extension.get_or_insert(tag);
+                }
+                _ => {}
+            }
+            cursor += 4 + len;
+        }
+        Ok(mint)
+    }
+}
+
+impl AccountSerialize for RepaymentMint {}
+
+impl anchor_lang::Owners for RepaymentMint {
+    fn owners() -> &'static [Pubkey] {
+        &TOKEN_PROGRAM_IDS
+    }
+}
+
+/// Move tokens so that exactly `net` arrives at `to`, grossing up for any
+/// Token-2022 transfer fee; the sender bears the fee. Uses `TransferChecked`,
+/// which both token programs accept and Token-2022 requires for fee mints.
+pub fn transfer_net<'info>(
+    token_program: &Interface<'info, TokenPrograms>,
+    mint: &InterfaceAccount<'info, RepaymentMint>,
+    from: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    net: u64,
+) -> Result<()> {
+    let amount = mint.gross_amount(net, Clock::get()?.epoch)?;
+    transfer_checked(token_program, mint, from, to, authority, amount, &[])
+}
+
+/// `TransferChecked` of exactly `amount`, signed by `signer_seeds` when the
+/// authority is a PDA of this program.
+pub fn transfer_checked<'info>(
+    token_program: &Interface<'info, TokenPrograms>,
+    mint: &InterfaceAccount<'info, RepaymentMint>,
+    from: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let mut data = Vec::with_capacity(10);
+    data.push(TRANSFER_CHECKED_TAG);
+    data.extend_from_slice(&amount.to_le_bytes());
+    data.push(mint.decimals);
+    
+    let mint_info = mint.to_account_info();
+    let instruction = Instruction {
+        program_id: token_program.key(),
+        accounts: vec![
+            AccountMeta::new(from.key(), false),
+            AccountMeta::new_readonly(mint_info.key(), false),
+            AccountMeta::new(to.key(), false),
+            AccountMeta::new_readonly(authority.key(), true),
+        ],
+        data,
+    };
+    invoke_signed(
+        &instruction,
+        &[from.clone(), mint_info, to.clone(), authority.clone(), token_program.to_account_info()],
+        signer_seeds,
+    )?;
+    Ok(())
+}
+
+// ==================== Accepted Repayment Mints ====================
+
+/// Largest slippage haircut an accepted mint may carry, in basis points.
+pub const MAX_REPAYMENT_HAIRCUT_BPS: u16 = 1_000;
+
+/// Current price of an accepted mint from its feed, held to the mint's
+/// staleness and variance limits.
+pub fn read_mint_price(accepted: &AcceptedMint, feed: &AccountInfo, now: i64) -> Result<SwitchboardDecimal> {
+    let data = feed.try_borrow_data()?;
+    let round = read_switchboard_round(&data)?;
+    require!(
+        now - round.open_timestamp <= accepted.max_staleness,
+        ErrorCode::StaleOracleRound
+    );
+    require!(
+        within_variance(&round.result, &round.std_deviation, accepted.max_variance_bps),
+        ErrorCode::OracleVarianceTooHigh
+    );
+    require!(round.result.mantissa > 0, ErrorCode::InvalidOraclePrice);
+    Ok(round.result)
+}
+
+/// Benchmark rate in basis points from its feed, which quotes a percentage.
+/// It may be negative; the loan's floor decides how low the rate goes.
+pub fn read_benchmark_rate(benchmark: &BenchmarkFeed, feed: &AccountInfo, now: i64) -> Result<i64> {
+    let data = feed.try_borrow_data()?;
+    let round = read_switchboard_round(&data)?;
+    require!(
+        now - round.open_timestamp <= benchmark.max_staleness,
+        ErrorCode::StaleOracleRound
+    );
+    require!(
+        within_variance(&round.result, &round.std_deviation, benchmark.max_variance_bps),
+        ErrorCode::OracleVarianceTooHigh
+    );
+    round.result
+        .percent_to_bps()
+        .and_then(|bps| i64::try_from(bps).ok())
+        .ok_or(ErrorCode::InvalidOracleAccount.into())
+}
+
+/// Floating terms must name a benchmark, keep the floor under the cap and
+/// start the loan at a rate between them.
+pub fn validate_floating_rate(interest_rate: u64, terms: Option<&FloatingRate>) -> Result<()> {
+    if let Some(terms) = terms {
+        require!(terms.benchmark != Pubkey::default(), ErrorCode::InvalidFloatingRate);
+        require!(
+            (terms.floor_bps..=terms.cap_bps).contains(&interest_rate),
+            ErrorCode::InvalidFloatingRate
+        );
+    }
+    Ok(())
+}
+
+/// Amount of the paying mint worth `amount` of the loan's mint at the two
+/// prices, grossed up by `haircut_bps`. Rounded up in the lender's favour;
+/// `None` on a non-positive price or overflow.
+pub fn convert_repayment(
+    amount: u64,
+    loan_price: &SwitchboardDecimal,
+    loan_decimals: u8,
+    payment_price: &SwitchboardDecimal,
+    payment_decimals: u8,
+    haircut_bps: u16,
+) -> Option<u64> {
+    if loan_price.mantissa <= 0 || payment_price.mantissa <= 0 || haircut_bps >= 10_000 {
+        return None;
+    }
+    let scale = loan_price.scale.max(payment_price.scale);
+    let mut numerator = (amount as u128)
+        .checked_mul(loan_price.rescaled(scale)? as u128)?
+        .checked_mul(10_000)?;
+    let mut denominator = (payment_price.rescaled(scale)? as u128)
+        .checked_mul(10_000 - haircut_bps as u128)?;
+    // Only the difference in decimals is applied, to keep the product small
+    if payment_decimals >= loan_decimals {
+        numerator = numerator.checked_mul(10u128.checked_pow((payment_decimals - loan_decimals) as u32)?)?;
+    } else {
+        denominator = denominator.checked_mul(10u128.checked_pow((loan_decimals - payment_decimals) as u32)?)?;
+    }
+    u64::try_from(numerator.div_ceil(denominator)).ok()
+}
+
+// ==================== Risk Reports ====================
+
+/// How long after posting a risk report's entries may still be applied.
+pub const MAX_RISK_REPORT_AGE: i64 = 7 * SECONDS_PER_DAY;
+/// Deepest proof accepted, enough for batches of 2^24 assets.
+pub const MAX_RISK_PROOF_DEPTH: usize = 24;
+
+// Domain separation so a leaf can never be passed off as an inner node
+const MERKLE_LEAF_PREFIX: &[u8] = &[0];
+const MERKLE_NODE_PREFIX: &[u8] = &[1];
+
+/// Leaf committing to one asset's score in a risk report.
+pub fn risk_report_leaf(asset: &Pubkey, risk_score: u8) -> [u8; 32] {
+    hashv(&[MERKLE_LEAF_PREFIX, asset.as_ref(), &[risk_score]]).to_bytes()
+}
+
+/// Fold a proof up to the root. Sibling pairs are hashed in sorted order, so
+/// proofs need no left/right flags.
+pub fn verify_merkle_proof(leaf: [u8; 32], proof: &[[u8; 32]], root: [u8; 32]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        let (first, second) = if node <= *sibling { (node, *sibling) } else { (*sibling, node) };
+        hashv(&[MERKLE_NODE_PREFIX, &first, &second]).to_bytes()
+    });
+    computed == root
+}
+
+// ==================== Circuit Breaker ====================
+
+/// Set a new risk score, tripping the asset's circuit breaker when the move
+/// exceeds the configured threshold.
+pub fn set_risk_score(asset: &mut Account<Asset>, config: &ProtocolConfig, risk_score: u8, now: i64) {
+    let previous_score = asset.risk_score;
+    asset.risk_score = risk_score;
+    asset.last_risk_update = now;
+    
+    if config.risk_jump_threshold == 0 || previous_score.abs_diff(risk_score) <= config.risk_jump_threshold {
+        return;
+    }
+    asset.risk_frozen_until = now.saturating_add(config.breaker_cooldown);
+    asset.breaker_confirmers.clear();
+    
+    emit!(RiskCircuitBreakerTripped {
+        asset: asset.key(),
+        previous_score,
+        risk_score,
+        frozen_until: asset.risk_frozen_until,
+    });
+    msg!("Circuit breaker tripped: risk score {} -> {}", previous_score, risk_score);
+}
+
+pub fn lift_risk_freeze(asset: &mut Asset) {
+    asset.risk_frozen_until = 0;
+    asset.breaker_confirmers.clear();
+}
+
+// ==================== Oracle Staking ====================
+
+/// Least bond, in lamports, an oracle needs to post reports or vote on disputes.
+pub const MIN_ORACLE_BOND: u64 = 1_000_000_000;
+/// How long after posting a risk report can be disputed.
+pub const ORACLE_DISPUTE_WINDOW: i64 = 2 * SECONDS_PER_DAY;
+/// How long after opening a dispute stays open for votes.
+pub const DISPUTE_VOTING_PERIOD: i64 = 3 * SECONDS_PER_DAY;
+/// Overturn votes needed, and more than uphold votes, to slash the oracle.
+pub const REPORT_DISPUTE_QUORUM: u8 = 2;
+/// Most oracles that can vote on one dispute.
+pub const MAX_DISPUTE_VOTERS: usize = 16;
+/// Share of the bond paid to the challenger when a report is overturned.
+pub const ORACLE_SLASH_BPS: u64 = 5_000;
+
+pub fn slash_amount(bonded: u64) -> u64 {
+    (bonded as u128 * ORACLE_SLASH_BPS as u128 / 10_000) as u64
+}
+
+/// Move `amount` lamports from the oracle into its bond PDA.
+pub fn deposit_bond<'info>(
+    oracle: &Signer<'info>,
+    bond: &Account<'info, OracleBond>,
+    system: &Program<'info, System>,
+    amount: u64,
+) -> Result<()> {
+    system_program::transfer(
+        CpiContext::new(
+            system.to_account_info(),
+            system_program::Transfer {
+                from: oracle.to_account_info(),
+                to: bond.to_account_info(),
+            },
+        ),
+        amount,
+    )
+}
+
+// ==================== Asset NFT ====================
+
+pub const TOKEN_METADATA_PROGRAM_ID: Pubkey = pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey = pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+/// Symbol every asset NFT carries.
+pub const ASSET_NFT_SYMBOL: &str = "RWA";
+
+// Token Metadata caps names at 32 bytes and URIs at 200
+const _: () = assert!(MAX_ASSET_ID_LEN <= 32 && MAX_METADATA_URI_LEN <= 200);
+
+const CREATE_IDEMPOTENT_TAG: u8 = 1;
+const UPDATE_METADATA_ACCOUNT_V2_TAG: u8 = 15;
+const CREATE_METADATA_ACCOUNT_V3_TAG: u8 = 33;
+
+/// Token Metadata `DataV2` fields this program sets; the optional creators,
+/// collection and uses that follow are always None.
+#[derive(AnchorSerialize)]
+struct NftMetadata<'a> {
+    name: &'a str,
+    symbol: &'a str,
+    uri: &'a str,
+    seller_fee_basis_points: u16,
+}
+
+/// Borsh-encoded `DataV2` for an asset's NFT: named after the asset id, with
+/// the asset's metadata URI.
+fn nft_metadata_data(asset: &Asset) -> Result<Vec<u8>> {
+    let mut data = NftMetadata {
+        name: &asset.asset_id,
+        symbol: ASSET_NFT_SYMBOL,
+        uri: &asset.metadata_uri,
+        seller_fee_basis_points: 0,
+    }
+    .try_to_vec()?;
+    data.extend([0, 0, 0]); // creators, collection, uses
+    Ok(data)
+}
+
+pub fn nft_metadata_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"metadata", TOKEN_METADATA_PROGRAM_ID.as_ref(), mint.as_ref()],
+        &TOKEN_METADATA_PROGRAM_ID,
+    ).0
+}
+
+pub fn associated_token_address(wallet: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[wallet.as_ref(), token::ID.as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    ).0
+}
+
+/// Freeze or thaw the holder's NFT account; the asset PDA is the freeze authority.
+fn set_nft_frozen<'info>(
+    token_program: &Program<'info, Token>,
+    nft_account: &AccountInfo<'info>,
+    nft_mint: &AccountInfo<'info>,
+    asset: &Account<'info, Asset>,
+    frozen: bool,
+) -> Result<()> {
+    let asset_seeds: &[&[u8]] = &[b"asset", asset.asset_id.as_bytes(), &[asset.bump]];
+    let program = token_program.to_account_info();
+    let account = nft_account.clone();
+    let mint = nft_mint.clone();
+    let authority = asset.to_account_info();
+    if frozen {
+        token::freeze_account(CpiContext::new_with_signer(
+            program,
+            token::FreezeAccount { account, mint, authority },
+            &[asset_seeds],
+        ))
+    } else {
+        token::thaw_account(CpiContext::new_with_signer(
+            program,
+            token::ThawAccount { account, mint, authority },
+            &[asset_seeds],
+        ))
+    }
+}
+
+// ==================== Compliance ====================
+
+/// Most addresses the blacklist holds.
+pub const MAX_BLACKLIST_LEN: usize = 200;
+
+// ==================== Protocol Revenue ====================
+
+/// Highest share of interest the protocol may keep, in basis points.
+pub const MAX_RESERVE_FACTOR_BPS: u16 = 5_000;
+
+/// Protocol's cut of `interest` under `reserve_factor_bps`, rounded down in
+/// the lender's favour.
+pub fn reserve_share(interest: u64, reserve_factor_bps: u16) -> u64 {
+    (interest as u128 * reserve_factor_bps as u128 / 10_000) as u64
+}
+
+/// Highest origination fee, in basis points of principal.
+pub const MAX_ORIGINATION_FEE_BPS: u16 = 500;
+
+/// Fee withheld from a disbursement of `principal`, rounded down in the
+/// borrower's favour.
+pub fn origination_fee(principal: u64, origination_fee_bps: u16) -> u64 {
+    (principal as u128 * origination_fee_bps as u128 / 10_000) as u64
+}
+
+/// Principal portion of installment `index` out of `count`: equal parts, with
+/// the remainder in the last one.
+pub fn installment_principal(principal: u64, count: usize, index: usize) -> u64 {
+    let base = principal / count as u64;
+    if index + 1 == count {
+        principal - base * (count as u64 - 1)
+    } else {
+        base
+    }
+}
+
+/// Installments for `loan` over `count` periods of `interval` seconds, shaped
+/// by its product: principal as the product repays it, plus interest on the
+/// balance still outstanding over each period.
+pub fn build_installments(loan: &Loan, interval: i64, count: usize) -> Result<Vec<Installment>> {
+    let mut remaining = loan.principal;
+    let mut installments = Vec::with_capacity(count);
+    for i in 0..count {
+        let principal_part = loan.product.installment_principal(loan.principal, count, i);
+        let interest = accrued_interest(remaining, loan.interest_rate, interval, loan.interest_mode)?;
+        installments.push(Installment {
+            due_date: loan.start_time + interval * (i as i64 + 1),
+            amount: principal_part.checked_add(interest).ok_or(ErrorCode::MathOverflow)?,
+            paid_at: 0,
+        });
+        remaining -= principal_part;
+    }
+    Ok(installments)
+}
+
+/// Send the protocol's share of an interest payment to the revenue vault for
+/// the repayment mint. Both revenue accounts are optional and only required
+/// when there is a share to collect.
+pub fn collect_protocol_share<'info>(
+    revenue: &mut Option<Account<'info, ProtocolRevenue>>,
+    vault: &Option<InterfaceAccount<'info, RepaymentTokenAccount>>,
+    token_program: &Interface<'info, TokenPrograms>,
+    mint: &InterfaceAccount<'info, RepaymentMint>,
+    from: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    share: u64,
+) -> Result<()> {
+    if share == 0 {
+        return Ok(());
+    }
+    let (Some(revenue), Some(vault)) = (revenue.as_mut(), vault.as_ref()) else {
+        return err!(ErrorCode::RevenueAccountMissing);
+    };
+    require_keys_eq!(vault.key(), revenue.vault, ErrorCode::RevenueAccountMissing);
+    
+    transfer_net(token_program, mint, from, &vault.to_account_info(), authority, share)?;
+    revenue.accrued = revenue.accrued.checked_add(share).ok_or(ErrorCode::MathOverflow)?;
+    
+    msg!("Protocol revenue accrued: {}", share);
+    Ok(())
+}
+
+// ==================== Asset History ====================
+
+/// Entries kept per asset before the oldest are overwritten.
+pub const ASSET_HISTORY_LEN: usize = 256;
+
+pub const HISTORY_SOURCE_SNAPSHOT: u8 = 0;
+pub const HISTORY_SOURCE_RISK_AUTHORITY: u8 = 1;
+pub const HISTORY_SOURCE_SWITCHBOARD: u8 = 2;
+pub const HISTORY_SOURCE_RISK_REPORT: u8 = 3;
+
+impl AssetHistory {
+    /// Append an entry, overwriting the oldest once the buffer is full. Only
+    /// the written slot is touched, so the cost does not grow with the buffer.
+    pub fn record(&mut self, timestamp: i64, valuation: u64, risk_score: u8, source: u8) {
+        self.entries[self.head as usize] = HistoryEntry {
+            timestamp,
+            valuation,
+            risk_score,
+            source,
+            _padding: [0; 6],
+        };
+        self.head = (self.head + 1) % ASSET_HISTORY_LEN as u32;
+        self.len = (self.len + 1).min(ASSET_HISTORY_LEN as u32);
+    }
+    
+    /// Entries from oldest to newest.
+    pub fn iter(&self) -> impl Iterator<Item = &HistoryEntry> {
+        let start = (self.head + ASSET_HISTORY_LEN as u32 - self.len) as usize % ASSET_HISTORY_LEN;
+        (0..self.len as usize).map(move |i| &self.entries[(start + i) % ASSET_HISTORY_LEN])
+    }
+}
+
+// ==================== Liquidation ====================
+
+/// Lamports paid to the cranker per loan liquidated from the queue.
+pub const LIQUIDATION_CRANK_REWARD: u64 = 10_000;
+
+/// Most loans the liquidation queue holds at once.
+pub const MAX_LIQUIDATION_QUEUE_LEN: usize = 64;
+
+#[derive(Clone, Copy, Debug)]
+pub enum LiquidationTrigger {
+    HighRisk(u8),
+    LtvBreach(u64),
+}
+
+/// Why the loan may be liquidated right now, if at all: its collateral's risk
+/// score is above the threshold or its health factor has fallen below 1.
+pub fn liquidation_trigger(
+    loan: &Loan,
+    asset: &Asset,
+    supplemental: Option<&Asset>,
+    now: i64,
+) -> Result<Option<LiquidationTrigger>> {
+    if asset.risk_score > LIQUIDATION_RISK_THRESHOLD {
+        return Ok(Some(LiquidationTrigger::HighRisk(asset.risk_score)));
+    }
+    let debt = outstanding_debt(loan, now)?;
+    let health_factor = health_factor_bps(collateral_limit(loan, asset, supplemental), debt);
+    Ok((health_factor < HEALTH_FACTOR_ONE).then_some(LiquidationTrigger::LtvBreach(health_factor)))
+}
+
+/// Loss booked when writing off a loan: its debt at liquidation less the
+/// collateral value recovered by seizure. Only liquidated loans whose
+/// settlement left a deficiency qualify.
+pub fn write_off_amount(loan: &Loan, settlement: Option<&CollateralSettlement>) -> Result<u64> {
+    require!(loan.status == LoanStatus::Liquidated, ErrorCode::NotEligibleForWriteOff);
+    let settlement = settlement.ok_or(ErrorCode::NotEligibleForWriteOff)?;
+    let amount = loan.liquidation_debt.saturating_sub(settlement.collateral_value);
+    require!(amount > 0, ErrorCode::NotEligibleForWriteOff);
+    Ok(amount)
+}
+
+/// Whether liquidation is on hold: the loan is disputed or its collateral's
+/// risk score is frozen by the circuit breaker.
+pub fn is_liquidation_paused(loan: &Loan, asset: &Asset, now: i64) -> bool {
+    loan.disputed || asset.is_risk_frozen(now)
+}
+
+/// Status `check_loan_health` moves an open loan to, with its health factor.
+/// An unhealthy loan only becomes Liquidating when it could be liquidated.
+pub fn health_status(
+    loan: &Loan,
+    asset: &Asset,
+    supplemental: Option<&Asset>,
+    now: i64,
+) -> Result<(LoanStatus, u64)> {
+    let debt = outstanding_debt(loan, now)?;
+    let health_factor = health_factor_bps(collateral_limit(loan, asset, supplemental), debt);
+    let status = if is_in_default(loan, now) {
+        LoanStatus::Defaulted
+    } else if health_factor < HEALTH_FACTOR_ONE && !is_liquidation_paused(loan, asset, now) {
+        LoanStatus::Liquidating
+    } else if now > loan.end_time {
+        LoanStatus::Overdue
+    } else {
+        LoanStatus::Active
+    };
+    Ok((status, health_factor))
+}
+
+/// Close an open loan as liquidated, recording who liquidated it and the debt
+/// outstanding at that moment for the collateral settlement.
+pub fn liquidate(
+    loan: &mut Loan,
+    asset: &mut Asset,
+    stats: &mut ProtocolStats,
+    liquidator: Pubkey,
+    trigger: LiquidationTrigger,
+    now: i64,
+) -> Result<()> {
+    loan.liquidation_debt = outstanding_debt(loan, now)?;
+    loan.liquidator = liquidator;
+    loan.transition(LoanStatus::Liquidated)?;
+    asset.active_loans = asset.active_loans.saturating_sub(1);
+    stats.record_closure(loan.remaining_principal());
+    stats.liquidation_count = stats.liquidation_count.saturating_add(1);
+    
+    match trigger {
+        LiquidationTrigger::HighRisk(risk_score) => msg!("Loan liquidated due to high risk: {}", risk_score),
+        LiquidationTrigger::LtvBreach(health_factor) => {
+            msg!("Loan liquidated due to LTV breach, health factor (bps): {}", health_factor)
+        }
+    }
+    Ok(())
+}
+
+// ==================== Multisig ====================
+
+/// Most owners a multisig can have.
+pub const MAX_MULTISIG_OWNERS: usize = 10;
+
+/// Most accounts a proposed instruction can reference.
+pub const MAX_PROPOSAL_ACCOUNTS: usize = 16;
+
+/// Largest proposed instruction data, in bytes.
+pub const MAX_PROPOSAL_DATA_LEN: usize = 512;
+
+pub fn validate_multisig_owners(owners: &[Pubkey], threshold: u8) -> Result<()> {
+    require!(
+        !owners.is_empty() && owners.len() <= MAX_MULTISIG_OWNERS,
+        ErrorCode::InvalidMultisigConfig
+    );
+    require!(
+        threshold > 0 && threshold as usize <= owners.len(),
+        ErrorCode::InvalidMultisigConfig
+    );
+    for (i, owner) in owners.iter().enumerate() {
+        require!(!owners[..i].contains(owner), ErrorCode::InvalidMultisigConfig);
+    }
+    Ok(())
+}
+
+impl Multisig {
+    pub fn owner_index(&self, key: &Pubkey) -> Result<usize> {
+        self.owners
+            .iter()
+            .position(|owner| owner == key)
+            .ok_or_else(|| ErrorCode::NotMultisigOwner.into())
+    }
+}
+
+// ==================== Risk & Interest Math ====================
+
+/// Risk score above which a loan can be liquidated regardless of its health factor.
+pub const LIQUIDATION_RISK_THRESHOLD: u8 = 80;
+
+/// A health factor of 1.0 expressed in basis points.
+pub const HEALTH_FACTOR_ONE: u64 = 10_000;
+
+/// Liquidation threshold as a share of the borrowing limit, in basis points.
+/// It sits above the origination limit so a loan opened at its maximum LTV
+/// can accrue interest before it becomes liquidatable.
+pub const LIQUIDATION_THRESHOLD_BPS: u64 = 11_000;
+
+pub const SECONDS_PER_YEAR: i64 = 365 * 24 * 60 * 60;
+
+/// Maximum loan-to-value (in percent) allowed for a given risk score.
+pub fn max_ltv_for_risk(risk_score: u8) -> u64 {
+    match risk_score {
+        0..=20 => 70,  // Low risk: 70% LTV
+        21..=40 => 60, // Medium-low: 60% LTV
+        41..=60 => 50, // Medium: 50% LTV
+        61..=80 => 35, // Medium-high: 35% LTV
+        81..=100 => 20, // High risk: 20% LTV
+        _ => 0,
+    }
+}
+
+/// Largest accepted asset identifier, in bytes; it is also a PDA seed.
+pub const MAX_ASSET_ID_LEN: usize = 32;
+
+/// Largest accepted asset type identifier, in bytes.
+pub const MAX_ASSET_TYPE_LEN: usize = 32;
+
+/// Longest metadata URI that fits the space reserved at asset creation.
+pub const MAX_METADATA_URI_LEN: usize = 196;
+
+/// Whether `authority` may manage the asset's metadata: its owner or a non-default delegate.
+pub fn is_asset_manager(asset: &Asset, authority: &Pubkey) -> bool {
+    *authority == asset.owner || (asset.delegate != Pubkey::default() && *authority == asset.delegate)
+}
+
+/// Seconds a loan request stays open for approval.
+pub const LOAN_REQUEST_TTL: i64 = 7 * 24 * 60 * 60;
+
+/// Largest accepted loan modification reason, in bytes.
+pub const MAX_REASON_LEN: usize = 64;
+
+/// Maximum loan after applying the asset type's haircut and LTV ceiling on top
+/// of the risk-score table.
+pub fn max_loan_for_type(valuation: u64, risk_score: u8, type_config: &AssetTypeConfig) -> u64 {
+    let collateral_value = haircut_value(valuation, type_config.haircut_bps);
+    let max_ltv = max_ltv_for_risk(risk_score).min(type_config.max_ltv as u64);
+    (collateral_value * max_ltv as u128 / 100) as u64
+}
+
+/// Collateral value left after the asset type's haircut.
+fn haircut_value(valuation: u64, haircut_bps: u16) -> u128 {
+    valuation as u128 - valuation as u128 * haircut_bps as u128 / 10_000
+}
+
+fn validate_asset_type_rules(max_ltv: u8, haircut_bps: u16, max_duration: i64) -> Result<()> {
+    require!(max_ltv <= 100, ErrorCode::InvalidAssetTypeConfig);
+    require!(haircut_bps <= 10_000, ErrorCode::InvalidAssetTypeConfig);
+    require!(max_duration > 0, ErrorCode::InvalidAssetTypeConfig);
+    Ok(())
+}
+
+/// Jurisdiction of an asset no compliance authority has placed yet.
+pub const NO_JURISDICTION: [u8; 2] = [0; 2];
+
+/// Most asset types a jurisdiction can list as allowed.
+pub const MAX_JURISDICTION_ASSET_TYPES: usize = 8;
+
+/// Whether `code` looks like an ISO 3166-1 alpha-2 country code.
+pub fn is_jurisdiction_code(code: &[u8; 2]) -> bool {
+    code.iter().all(u8::is_ascii_uppercase)
+}
+
+fn validate_jurisdiction_rules(allowed_asset_types: &[String], max_ltv: u8) -> Result<()> {
+    require!(allowed_asset_types.len() <= MAX_JURISDICTION_ASSET_TYPES, ErrorCode::InvalidJurisdictionConfig);
+    require!(
+        allowed_asset_types.iter().all(|asset_type| asset_type.len() <= MAX_ASSET_TYPE_LEN),
+        ErrorCode::InvalidJurisdictionConfig
+    );
+    require!(max_ltv <= 100, ErrorCode::InvalidJurisdictionConfig);
+    Ok(())
+}
+
+/// Rules that apply to `asset`, if it has been placed in a jurisdiction. The
+/// seeds tie the optional account to the asset's code, so it only has to be
+/// present, which stops callers from dodging the rules by omitting it.
+pub fn jurisdiction_rules<'a>(
+    asset: &Asset,
+    jurisdiction_config: &'a Option<Account<JurisdictionConfig>>,
+) -> Result<Option<&'a JurisdictionConfig>> {
+    if asset.jurisdiction == NO_JURISDICTION {
+        return Ok(None);
+    }
+    match jurisdiction_config {
+        Some(rules) => Ok(Some(rules)),
+        None => err!(ErrorCode::JurisdictionConfigMissing),
+    }
+}
+
+/// `max_loan_for_type` further capped by the jurisdiction's LTV override.
+pub fn max_loan_in_jurisdiction(
+    valuation: u64,
+    risk_score: u8,
+    type_config: &AssetTypeConfig,
+    rules: Option<&JurisdictionConfig>,
+) -> u64 {
+    CollateralRules::new(type_config, rules).max_loan(valuation, risk_score)
+}
+
+// ==================== Portfolios ====================
+
+/// Asset type of portfolio assets; reserved so only bundling creates them.
+pub const PORTFOLIO_ASSET_TYPE: &str = "portfolio";
+
+/// Most assets a portfolio can bundle.
+pub const MAX_PORTFOLIO_ASSETS: usize = 10;
+
+/// Collateral figures a portfolio asset takes from its members.
+#[derive(Debug, PartialEq, Eq)]
+pub struct PortfolioMetrics {
+    pub valuation: u64,
+    pub risk_score: u8,
+    pub last_risk_update: i64,  // oldest member score, so staleness carries over
+    pub risk_frozen_until: i64, // latest member freeze, so tripped breakers carry over
+}
+
+impl PortfolioMetrics {
+    pub fn apply_to(&self, portfolio: &mut Asset) {
+        portfolio.valuation = self.valuation;
+        portfolio.risk_score = self.risk_score;
+        portfolio.last_risk_update = self.last_risk_update;
+        portfolio.risk_frozen_until = self.risk_frozen_until;
+    }
+}
+
+/// Total valuation and valuation-weighted risk score of `members`. The score
+/// rounds up so bundling never makes collateral look safer than its parts;
+/// without any value to weigh by, the riskiest member's score is used.
+pub fn portfolio_metrics<'a>(members: impl IntoIterator<Item = &'a Asset>) -> Result<PortfolioMetrics> {
+    let mut valuation = 0u128;
+    let mut weighted_risk = 0u128;
+    let mut max_risk = 0u8;
+    let mut last_risk_update = i64::MAX;
+    let mut risk_frozen_until = 0i64;
+    for member in members {
+        valuation += member.valuation as u128;
+        weighted_risk += member.valuation as u128 * member.risk_score as u128;
+        max_risk = max_risk.max(member.risk_score);
+        last_risk_update = last_risk_update.min(member.last_risk_update);
+        risk_frozen_until = risk_frozen_until.max(member.risk_frozen_until);
+    }
+    let risk_score = match valuation {
+        0 => max_risk,
+        _ => weighted_risk.div_ceil(valuation) as u8,
+    };
+    Ok(PortfolioMetrics {
+        valuation: u64::try_from(valuation).map_err(|_| ErrorCode::MathOverflow)?,
+        risk_score,
+        last_risk_update,
+        risk_frozen_until,
+    })
+}
+
+/// Member assets from `infos`, which must be exactly `expected` in order.
+pub fn portfolio_members<'info>(
+    infos: &'info [AccountInfo<'info>],
+    expected: &[Pubkey],
+) -> Result<Vec<Account<'info, Asset>>> {
+    require!(infos.len() == expected.len(), ErrorCode::PortfolioMembersMismatch);
+    infos
+        .iter()
+        .zip(expected)
+        .map(|(info, key)| {
+            require_keys_eq!(info.key(), *key, ErrorCode::PortfolioMembersMismatch);
+            Account::<Asset>::try_from(info)
+        })
+        .collect()
+}
+
+/// Most installments a repayment schedule can hold.
+pub const MAX_INSTALLMENTS: usize = 24;
+
+/// Seconds after a due date before an unpaid installment counts as missed.
+pub const INSTALLMENT_GRACE_PERIOD: i64 = 3 * 24 * 60 * 60;
+
+/// Seconds past maturity before an unpaid active loan is in default.
+pub const DEFAULT_GRACE_PERIOD: i64 = 30 * 24 * 60 * 60;
+
+/// Late fee charged per full day past maturity, in basis points of principal.
+pub const LATE_FEE_BPS_PER_DAY: u64 = 10;
+/// Ceiling on accumulated late fees, in basis points of principal.
+pub const MAX_LATE_FEE_BPS: u64 = 1_000;
+
+pub fn is_in_default(loan: &Loan, now: i64) -> bool {
+    loan.status.is_open() && now > loan.end_time + DEFAULT_GRACE_PERIOD
+}
+
+/// Recount unpaid installments past their grace period.
+pub fn refresh_delinquency(schedule: &mut RepaymentSchedule, now: i64) {
+    let missed = schedule.installments
+        .iter()
+        .filter(|i| i.paid_at == 0 && now > i.due_date + INSTALLMENT_GRACE_PERIOD)
+        .count();
+    schedule.missed_count = missed as u8;
+    schedule.delinquent = missed > 0;
+}
+
+/// Interest accrued on `principal` at `interest_rate` basis points per year
+/// over `elapsed` seconds, compounded according to `mode`.
+pub fn accrued_interest(principal: u64, interest_rate: u64, elapsed: i64, mode: InterestMode) -> Result<u64> {
+    if elapsed <= 0 {
+        return Ok(0);
+    }
+    let interest = match mode {
+        InterestMode::Simple => simple_interest(principal, interest_rate, elapsed),
+        InterestMode::DailyCompounding => growth_interest(principal, daily_compound_factor(interest_rate, elapsed)),
+        InterestMode::Continuous => growth_interest(principal, continuous_factor(interest_rate, elapsed)),
+    }
+    .ok_or(ErrorCode::MathOverflow)?;
+    u64::try_from(interest).map_err(|_| ErrorCode::MathOverflow.into())
+}
+
+// ==================== Fixed-Point Helpers ====================
+
+/// 1.0 in the 18-decimal fixed-point representation used for growth factors.
+pub const WAD: u128 = 1_000_000_000_000_000_000;
+
+pub const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+const BPS_YEAR_SECONDS: u128 = 10_000 * SECONDS_PER_YEAR as u128;
+
+pub fn mul_wad(a: u128, b: u128) -> Option<u128> {
+    a.checked_mul(b).map(|v| v / WAD)
+}
+
+/// `base^exp` for a WAD-scaled base, by repeated squaring.
+pub fn pow_wad(mut base: u128, mut exp: u64) -> Option<u128> {
+    let mut result = WAD;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mul_wad(result, base)?;
+        }
+        exp >>= 1;
+        if exp > 0 {
+            base = mul_wad(base, base)?;
+        }
+    }
+    Some(result)
+}
+
+/// `e^x` for a WAD-scaled `x`, summing the Taylor series until terms vanish.
+pub fn exp_wad(x: u128) -> Option<u128> {
+    let mut sum = WAD;
+    let mut term = WAD;
+    for k in 1..=64u128 {
+        term = mul_wad(term, x)? / k;
+        if term == 0 {
+            return Some(sum);
+        }
+        sum = sum.checked_add(term)?;
+    }
+    // Series has not converged: the exponent is too large to represent
+    None
+}
+
+fn simple_interest(principal: u64, interest_rate: u64, elapsed: i64) -> Option<u128> {
+    (principal as u128)
+        .checked_mul(interest_rate as u128)?
+        .checked_mul(elapsed as u128)
+        .map(|v| v / BPS_YEAR_SECONDS)
+}
+
+/// Interest implied by growing `principal` by a WAD-scaled `factor`.
+fn growth_interest(principal: u64, factor: Option<u128>) -> Option<u128> {
+    let growth = factor?.checked_sub(WAD)?;
+    (principal as u128).checked_mul(growth).map(|v| v / WAD)
+}
+
+/// `(1 + r/365)^days`, with any partial final day accruing simply.
+fn daily_compound_factor(interest_rate: u64, elapsed: i64) -> Option<u128> {
+    let daily_rate = (interest_rate as u128).checked_mul(WAD)? / (10_000 * 365);
+    let days = (elapsed / SECONDS_PER_DAY) as u64;
+    let remainder = (elapsed % SECONDS_PER_DAY) as u128;
+    let partial = WAD + daily_rate.checked_mul(remainder)? / SECONDS_PER_DAY as u128;
+    mul_wad(pow_wad(WAD + daily_rate, days)?, partial)
+}
+
+/// `e^(r * t)` with `r` per year and `t` in years.
+fn continuous_factor(interest_rate: u64, elapsed: i64) -> Option<u128> {
+    let exponent = (interest_rate as u128)
+        .checked_mul(elapsed as u128)?
+        .checked_mul(WAD)?
+        / BPS_YEAR_SECONDS;
+    exp_wad(exponent)
+}
+
+/// Seconds between loan start and `now` during which interest accrues,
+/// excluding any forbearance windows.
+pub fn interest_bearing_seconds(loan: &Loan, now: i64) -> i64 {
+    let elapsed = now - loan.start_time;
+    let current_window = (now.min(loan.forbearance_end) - loan.forbearance_start).max(0);
+    (elapsed - loan.forborne_seconds - current_window).max(0)
+}
+
+/// Interest accrued from loan start until `now`: whatever was banked at
+/// earlier rates plus the current rate since the last repricing.
+pub fn loan_interest(loan: &Loan, now: i64) -> Result<u64> {
+    let elapsed = interest_bearing_seconds(loan, now) - loan.checkpoint_seconds;
+    accrued_interest(loan.remaining_principal(), loan.interest_rate, elapsed, loan.interest_mode)?
+        .checked_add(loan.interest_checkpoint)
+        .ok_or(ErrorCode::MathOverflow.into())
+}
+
+/// Remaining principal plus interest accrued until `now` and any late fee.
+pub fn outstanding_debt(loan: &Loan, now: i64) -> Result<u64> {
+    let interest = loan_interest(loan, now)?;
+    loan.remaining_principal()
+        .checked_add(interest)
+        .and_then(|debt| debt.checked_add(late_fee(loan, now)))
+        .ok_or(ErrorCode::MathOverflow.into())
+}
+
+/// Late fee owed on a loan: a daily charge on its remaining principal for each
+/// full day past maturity, capped at MAX_LATE_FEE_BPS of that principal.
+pub fn late_fee(loan: &Loan, now: i64) -> u64 {
+    if !loan.status.is_open() {
+        return 0;
+    }
+    overdue_charge(loan.remaining_principal(), loan.end_time, now)
+}
+
+/// Late fee on an installment paid after its grace period: the loan late fee
+/// rate applied to the installment amount from its due date.
+pub fn installment_late_fee(installment: &Installment, now: i64) -> u64 {
+    if now <= installment.due_date + INSTALLMENT_GRACE_PERIOD {
+        return 0;
+    }
+    overdue_charge(installment.amount, installment.due_date, now)
+}
+
+/// LATE_FEE_BPS_PER_DAY of `amount` for each full day past `due`, capped at
+/// MAX_LATE_FEE_BPS.
+fn overdue_charge(amount: u64, due: i64, now: i64) -> u64 {
+    if now <= due {
+        return 0;
+    }
+    let days_late = ((now - due) / SECONDS_PER_DAY) as u128;
+    let bps = (days_late * LATE_FEE_BPS_PER_DAY as u128).min(MAX_LATE_FEE_BPS as u128);
+    (amount as u128 * bps / 10_000) as u64
+}
+
+/// Status a loan's age alone puts it in. Health-driven states are left to
+/// `check_loan_health`, except that an overdue loan whose maturity was
+/// extended returns to Active.
+pub fn scheduled_status(loan: &Loan, now: i64) -> LoanStatus {
+    if is_in_default(loan, now) {
+        LoanStatus::Defaulted
+    } else if now > loan.end_time {
+        LoanStatus::Overdue
+    } else if loan.status == LoanStatus::Overdue {
+        LoanStatus::Active
+    } else {
+        loan.status
+    }
+}
+
+/// Liquidation limit of a loan's primary collateral plus any top-up asset: their
+/// borrowing limit under the asset type and jurisdiction rules recorded on the
+/// loan, raised to `LIQUIDATION_THRESHOLD_BPS`.
+pub fn collateral_limit(loan: &Loan, asset: &Asset, supplemental: Option<&Asset>) -> u64 {
+    let extra = supplemental.map_or(0, |s| loan.supplemental_rules.max_loan(s.valuation, s.risk_score));
+    let borrowing_limit = loan.collateral_rules.max_loan(asset.valuation, asset.risk_score).saturating_add(extra);
+    u64::try_from(borrowing_limit as u128 * LIQUIDATION_THRESHOLD_BPS as u128 / HEALTH_FACTOR_ONE as u128)
+        .unwrap_or(u64::MAX)
+}
+
+/// The loan's top-up asset, which must be supplied whenever one is pledged so
+/// health can't be understated by leaving it out.
+pub fn supplemental_collateral<'a>(
+    loan: &Loan,
+    supplied: &'a Option<Account<'_, Asset>>,
+) -> Result<Option<&'a Asset>> {
+    if loan.supplemental_asset == Pubkey::default() {
+        return Ok(None);
+    }
+    let asset = supplied.as_ref().ok_or(ErrorCode::SupplementalAssetMissing)?;
+    Ok(Some(asset))
+}
+
+/// Health factor in basis points: liquidation limit at current valuation and risk
+/// divided by outstanding debt. Anything below `HEALTH_FACTOR_ONE` is undercollateralized.
+pub fn health_factor_bps(limit: u64, debt: u64) -> u64 {
+    if debt == 0 {
+        return u64::MAX;
+    }
+    let hf = limit as u128 * HEALTH_FACTOR_ONE as u128 / debt as u128;
+    u64::try_from(hf).unwrap_or(u64::MAX)
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + ProtocolConfig::INIT_SPACE,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetRiskOracle<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(report_id: u64)]
+pub struct PostRiskReport<'info> {
+    #[account(
+        init,
+        payer = risk_oracle,
+        space = 8 + RiskReport::INIT_SPACE,
+        seeds = [b"risk_report", report_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub report: Account<'info, RiskReport>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = risk_oracle @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    #[account(mut)]
+    pub risk_oracle: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        mut,
+        seeds = [b"oracle_bond", risk_oracle.key().as_ref()],
+        bump = oracle_bond.bump,
+        constraint = oracle_bond.bonded >= MIN_ORACLE_BOND @ ErrorCode::InsufficientOracleBond
+    )]
+    pub oracle_bond: Account<'info, OracleBond>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterOracle<'info> {
+    #[account(
+        init,
+        payer = oracle,
+        space = 8 + OracleBond::INIT_SPACE,
+        seeds = [b"oracle_bond", oracle.key().as_ref()],
+        bump
+    )]
+    pub bond: Account<'info, OracleBond>,
+    
+    #[account(mut)]
+    pub oracle: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct BondOracle<'info> {
+    #[account(
+        mut,
+        seeds = [b"oracle_bond", oracle.key().as_ref()],
+        bump = bond.bump,
+        has_one = oracle @ ErrorCode::Unauthorized
+    )]
+    pub bond: Account<'info, OracleBond>,
+    
+    #[account(mut)]
+    pub oracle: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DisputeRiskReport<'info> {
+    #[account(
+        seeds = [b"risk_report", report.report_id.to_le_bytes().as_ref()],
+        bump = report.bump,
+        constraint = !report.overturned @ ErrorCode::ReportOverturned
+    )]
+    pub report: Account<'info, RiskReport>,
+    
+    // Reports posted before bonding record no oracle and cannot be disputed
+    #[account(
+        mut,
+        seeds = [b"oracle_bond", report.oracle.as_ref()],
+        bump = oracle_bond.bump
+    )]
+    pub oracle_bond: Account<'info, OracleBond>,
+    
+    #[account(
+        init,
+        payer = challenger,
+        space = 8 + ReportDispute::INIT_SPACE,
+        seeds = [b"report_dispute", report.key().as_ref()],
+        bump
+    )]
+    pub dispute: Account<'info, ReportDispute>,
+    
+    #[account(mut)]
+    pub challenger: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct VoteReportDispute<'info> {
+    #[account(
+        mut,
+        seeds = [b"report_dispute", dispute.report.as_ref()],
+        bump = dispute.bump
+    )]
+    pub dispute: Account<'info, ReportDispute>,
+    
+    #[account(
+        seeds = [b"oracle_bond", voter.key().as_ref()],
+        bump = voter_bond.bump,
+        constraint = voter_bond.bonded >= MIN_ORACLE_BOND @ ErrorCode::InsufficientOracleBond
+    )]
+    pub voter_bond: Account<'info, OracleBond>,
+    
+    pub voter: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SlashOracle<'info> {
+    #[account(
+        mut,
+        seeds = [b"report_dispute", report.key().as_ref()],
+        bump = dispute.bump,
+        has_one = report @ ErrorCode::InvalidRiskReport,
+        has_one = challenger @ ErrorCode::Unauthorized
+    )]
+    pub dispute: Account<'info, ReportDispute>,
+    
+    #[account(
+        mut,
+        seeds = [b"risk_report", report.report_id.to_le_bytes().as_ref()],
+        bump = report.bump
+    )]
+    pub report: Account<'info, RiskReport>,
+    
+    #[account(
+        mut,
+        seeds = [b"oracle_bond", dispute.oracle.as_ref()],
+        bump = oracle_bond.bump
+    )]
+    pub oracle_bond: Account<'info, OracleBond>,
+    
+    /// CHECK: receives the slashed lamports; must be the dispute's challenger
+    #[account(mut)]
+    pub challenger: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DismissReportDispute<'info> {
+    #[account(
+        mut,
+        seeds = [b"report_dispute", dispute.report.as_ref()],
+        bump = dispute.bump
+    )]
+    pub dispute: Account<'info, ReportDispute>,
+    
+    #[account(
+        mut,
+        seeds = [b"oracle_bond", dispute.oracle.as_ref()],
+        bump = oracle_bond.bump
+    )]
+    pub oracle_bond: Account<'info, OracleBond>,
+}
+
+#[derive(Accounts)]
+pub struct ApplyRiskReportEntry<'info> {
+    #[account(
+        seeds = [b"risk_report", report.report_id.to_le_bytes().as_ref()],
+        bump = report.bump
+    )]
+    pub report: Account<'info, RiskReport>,
+    
+    #[account(
+        mut,
+        seeds = [b"asset", asset.asset_id.as_bytes()],
+        bump = asset.bump
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        mut,
+        seeds = [b"asset_history", asset.key().as_ref()],
+        bump = history.load()?.bump
+    )]
+    pub history: Option<AccountLoader<'info, AssetHistory>>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetArbitrator<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(custodian: Pubkey)]
+pub struct RegisterCustodian<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Custodian::INIT_SPACE,
+        seeds = [b"custodian", custodian.as_ref()],
+        bump
+    )]
+    pub custodian_record: Account<'info, Custodian>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DeregisterCustodian<'info> {
+    #[account(
+        mut,
+        seeds = [b"custodian", custodian_record.custodian.as_ref()],
+        bump = custodian_record.bump,
+        close = admin
+    )]
+    pub custodian_record: Account<'info, Custodian>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AttestCustody<'info> {
+    #[account(
+        init,
+        payer = custodian,
+        space = 8 + CustodyAttestation::INIT_SPACE,
+        seeds = [b"custody", asset.key().as_ref()],
+        bump
+    )]
+    pub custody_attestation: Account<'info, CustodyAttestation>,
+    
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"custodian", custodian.key().as_ref()],
+        bump = custodian_record.bump
+    )]
+    pub custodian_record: Account<'info, Custodian>,
+    
+    #[account(mut)]
+    pub custodian: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RenewCustodyAttestation<'info> {
+    #[account(
+        mut,
+        seeds = [b"custody", custody_attestation.asset.as_ref()],
+        bump = custody_attestation.bump,
+        has_one = custodian @ ErrorCode::Unauthorized
+    )]
+    pub custody_attestation: Account<'info, CustodyAttestation>,
+    
+    #[account(
+        seeds = [b"custodian", custodian.key().as_ref()],
+        bump = custodian_record.bump
+    )]
+    pub custodian_record: Account<'info, Custodian>,
+    
+    pub custodian: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetComplianceAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeBlacklist<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Blacklist::INIT_SPACE,
+        seeds = [b"blacklist"],
+        bump
+    )]
+    pub blacklist: Account<'info, Blacklist>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ManageBlacklist<'info> {
+    #[account(
+        mut,
+        seeds = [b"blacklist"],
+        bump = blacklist.bump
+    )]
+    pub blacklist: Account<'info, Blacklist>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = compliance_authority @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    pub compliance_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetCircuitBreaker<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct LiftCircuitBreaker<'info> {
+    #[account(
+        mut,
+        seeds = [b"asset", asset.asset_id.as_bytes()],
+        bump = asset.bump
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfirmRiskJump<'info> {
+    #[account(
+        mut,
+        seeds = [b"asset", asset.asset_id.as_bytes()],
+        bump = asset.bump
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"oracle_bond", oracle.key().as_ref()],
+        bump = oracle_bond.bump,
+        constraint = oracle_bond.bonded >= MIN_ORACLE_BOND @ ErrorCode::InsufficientOracleBond
+    )]
+    pub oracle_bond: Account<'info, OracleBond>,
+    
+    pub oracle: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureLenderWhitelist<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(lender: Pubkey)]
+pub struct GrantLenderMembership<'info> {
+    #[account(
+        init,
+        payer = lender_compliance_authority,
+        space = 8 + LenderMembership::INIT_SPACE,
+        seeds = [b"lender_membership", lender.as_ref()],
+        bump
+    )]
+    pub lender_membership: Account<'info, LenderMembership>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = lender_compliance_authority @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    #[account(mut)]
+    pub lender_compliance_authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ManageLenderMembership<'info> {
+    #[account(
+        mut,
+        seeds = [b"lender_membership", lender_membership.lender.as_ref()],
+        bump = lender_membership.bump
+    )]
+    pub lender_membership: Account<'info, LenderMembership>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = lender_compliance_authority @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    pub lender_compliance_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeLenderMembership<'info> {
+    #[account(
+        mut,
+        seeds = [b"lender_membership", lender_membership.lender.as_ref()],
+        bump = lender_membership.bump,
+        close = lender_compliance_authority
+    )]
+    pub lender_membership: Account<'info, LenderMembership>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = lender_compliance_authority @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    #[account(mut)]
+    pub lender_compliance_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetOriginationFee<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetMaxRiskAge<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetReserveFactor<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeProtocolStats<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + ProtocolStats::INIT_SPACE,
+        seeds = [b"stats"],
+        bump
+    )]
+    pub stats: Account<'info, ProtocolStats>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeRevenueAccount<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + ProtocolRevenue::INIT_SPACE,
+        seeds = [b"revenue", repayment_mint.key().as_ref()],
+        bump
+    )]
+    pub revenue: Account<'info, ProtocolRevenue>,
+    
+    pub repayment_mint: InterfaceAccount<'info, RepaymentMint>,
+    
+    #[account(
+        constraint = vault.mint == repayment_mint.key() @ ErrorCode::RepaymentMintMismatch,
+        constraint = vault.owner == revenue.key() @ ErrorCode::Unauthorized
+    )]
+    pub vault: InterfaceAccount<'info, RepaymentTokenAccount>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterReferrer<'info> {
+    #[account(
+        init,
+        payer = agent,
+        space = 8 + Referral::INIT_SPACE,
+        seeds = [b"referral", agent.key().as_ref(), repayment_mint.key().as_ref()],
+        bump
+    )]
+    pub referral: Account<'info, Referral>,
+    
+    pub repayment_mint: InterfaceAccount<'info, RepaymentMint>,
+    
+    #[account(
+        constraint = vault.mint == repayment_mint.key() @ ErrorCode::RepaymentMintMismatch,
+        constraint = vault.owner == referral.key() @ ErrorCode::Unauthorized
+    )]
+    pub vault: InterfaceAccount<'info, RepaymentTokenAccount>,
+    
+    #[account(mut)]
+    pub agent: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimReferralFees<'info> {
+    #[account(
+        mut,
+        seeds = [b"referral", agent.key().as_ref(), referral.mint.as_ref()],
+        bump = referral.bump,
+        has_one = agent @ ErrorCode::Unauthorized
+    )]
+    pub referral: Account<'info, Referral>,
+    
+    #[account(address = referral.mint @ ErrorCode::RepaymentMintMismatch)]
+    pub repayment_mint: InterfaceAccount<'info, RepaymentMint>,
+    
+    #[account(mut, address = referral.vault @ ErrorCode::ReferralAccountMissing)]
+    pub vault: InterfaceAccount<'info, RepaymentTokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = destination.mint == referral.mint @ ErrorCode::RepaymentMintMismatch
+    )]
+    pub destination: InterfaceAccount<'info, RepaymentTokenAccount>,
+    
+    pub agent: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenPrograms>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRevenue<'info> {
+    #[account(
+        mut,
+        seeds = [b"revenue", revenue.mint.as_ref()],
+        bump = revenue.bump
+    )]
+    pub revenue: Account<'info, ProtocolRevenue>,
+    
+    #[account(address = revenue.mint @ ErrorCode::RepaymentMintMismatch)]
+    pub repayment_mint: InterfaceAccount<'info, RepaymentMint>,
+    
+    #[account(mut, address = revenue.vault @ ErrorCode::RevenueAccountMissing)]
+    pub vault: InterfaceAccount<'info, RepaymentTokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = destination.mint == revenue.mint @ ErrorCode::RepaymentMintMismatch
+    )]
+    pub destination: InterfaceAccount<'info, RepaymentTokenAccount>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    pub admin: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenPrograms>,
+}
+
+#[derive(Accounts)]
+pub struct CreateMultisig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Multisig::INIT_SPACE,
+        seeds = [b"multisig"],
+        bump
+    )]
+    pub multisig: Account<'info, Multisig>,
+    
+    /// CHECK: data-less PDA that signs executed proposals and becomes the config admin
+    #[account(
+        seeds = [b"multisig_signer", multisig.key().as_ref()],
+        bump
+    )]
+    pub multisig_signer: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetMultisigOwners<'info> {
+    #[account(
+        mut,
+        seeds = [b"multisig"],
+        bump = multisig.bump
+    )]
+    pub multisig: Account<'info, Multisig>,
+    
+    #[account(
+        seeds = [b"multisig_signer", multisig.key().as_ref()],
+        bump = multisig.signer_bump
+    )]
+    pub multisig_signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Propose<'info> {
+    #[account(
+        mut,
+        seeds = [b"multisig"],
+        bump = multisig.bump
+    )]
+    pub multisig: Account<'info, Multisig>,
+    
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + Proposal::INIT_SPACE,
+        seeds = [b"proposal", multisig.key().as_ref(), &multisig.proposal_count.to_le_bytes()],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+    
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveProposal<'info> {
+    #[account(
+        seeds = [b"multisig"],
+        bump = multisig.bump
+    )]
+    pub multisig: Account<'info, Multisig>,
+    
+    #[account(
+        mut,
+        seeds = [b"proposal", multisig.key().as_ref(), &proposal.index.to_le_bytes()],
+        bump = proposal.bump,
+        has_one = multisig
+    )]
+    pub proposal: Account<'info, Proposal>,
+    
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteProposal<'info> {
+    #[account(
+        seeds = [b"multisig"],
+        bump = multisig.bump
+    )]
+    pub multisig: Account<'info, Multisig>,
+    
+    /// CHECK: PDA signer for the proposed instruction, validated by seeds
+    #[account(
+        seeds = [b"multisig_signer", multisig.key().as_ref()],
+        bump = multisig.signer_bump
+    )]
+    pub multisig_signer: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"proposal", multisig.key().as_ref(), &proposal.index.to_le_bytes()],
+        bump = proposal.bump,
+        has_one = multisig
+    )]
+    pub proposal: Account<'info, Proposal>,
+    
+    pub executor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(asset_type: String)]
+pub struct InitializeAssetTypeConfig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = AssetTypeConfig::space(&asset_type),
+        seeds = [b"asset_type", asset_type.as_bytes()],
+        bump
+    )]
+    pub asset_type_config: Account<'info, AssetTypeConfig>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateAssetTypeConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"asset_type", asset_type_config.asset_type.as_bytes()],
+        bump = asset_type_config.bump
+    )]
+    pub asset_type_config: Account<'info, AssetTypeConfig>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(jurisdiction: [u8; 2])]
+pub struct InitializeJurisdictionConfig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + JurisdictionConfig::INIT_SPACE,
+        seeds = [b"jurisdiction", jurisdiction.as_ref()],
+        bump
+    )]
+    pub jurisdiction_config: Account<'info, JurisdictionConfig>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateJurisdictionConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"jurisdiction", jurisdiction_config.jurisdiction.as_ref()],
+        bump = jurisdiction_config.bump
+    )]
+    pub jurisdiction_config: Account<'info, JurisdictionConfig>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetAssetJurisdiction<'info> {
+    #[account(
+        mut,
+        seeds = [b"asset", asset.asset_id.as_bytes()],
+        bump = asset.bump
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    // Must exist, so every placed asset has rules to enforce
+    #[account(
+        seeds = [b"jurisdiction", jurisdiction_config.jurisdiction.as_ref()],
+        bump = jurisdiction_config.bump
+    )]
+    pub jurisdiction_config: Account<'info, JurisdictionConfig>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = compliance_authority @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    pub compliance_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeAssetOracle<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + AssetOracle::INIT_SPACE,
+        seeds = [b"oracle", asset.key().as_ref()],
+        bump
+    )]
+    pub asset_oracle: Account<'info, AssetOracle>,
+    
+    #[account(
+        seeds = [b"asset", asset.asset_id.as_bytes()],
+        bump = asset.bump
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateAssetOracle<'info> {
+    #[account(
+        mut,
+        seeds = [b"oracle", asset_oracle.asset.as_ref()],
+        bump = asset_oracle.bump
+    )]
+    pub asset_oracle: Account<'info, AssetOracle>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AddAcceptedMint<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + AcceptedMint::INIT_SPACE,
+        seeds = [b"accepted_mint", mint.key().as_ref()],
+        bump
+    )]
+    pub accepted_mint: Account<'info, AcceptedMint>,
+    
+    #[account(constraint = mint.unsupported_extension.is_none() @ ErrorCode::UnsupportedMintExtension)]
+    pub mint: InterfaceAccount<'info, RepaymentMint>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateAcceptedMint<'info> {
+    #[account(
+        mut,
+        seeds = [b"accepted_mint", accepted_mint.mint.as_ref()],
+        bump = accepted_mint.bump
+    )]
+    pub accepted_mint: Account<'info, AcceptedMint>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(feed: Pubkey)]
+pub struct AddBenchmarkFeed<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + BenchmarkFeed::INIT_SPACE,
+        seeds = [b"benchmark", feed.as_ref()],
+        bump
+    )]
+    pub benchmark: Account<'info, BenchmarkFeed>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateBenchmarkFeed<'info> {
+    #[account(
+        mut,
+        seeds = [b"benchmark", benchmark.feed.as_ref()],
+        bump = benchmark.bump
+    )]
+    pub benchmark: Account<'info, BenchmarkFeed>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RefreshRiskFromSwitchboard<'info> {
+    #[account(
+        mut,
+        seeds = [b"asset", asset.asset_id.as_bytes()],
+        bump = asset.bump
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"oracle", asset.key().as_ref()],
+        bump = asset_oracle.bump,
+        has_one = asset,
+        has_one = aggregator @ ErrorCode::OracleMismatch
+    )]
+    pub asset_oracle: Account<'info, AssetOracle>,
+    
+    /// CHECK: owner is pinned to Switchboard and the layout is verified on read
+    #[account(owner = SWITCHBOARD_PROGRAM_ID @ ErrorCode::OracleMismatch)]
+    pub aggregator: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"asset_history", asset.key().as_ref()],
+        bump = history.load()?.bump
+    )]
+    pub history: Option<AccountLoader<'info, AssetHistory>>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(asset_id: String, asset_type: String)]
+pub struct InitializeAsset<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = Asset::space(&asset_id, &asset_type),
+        seeds = [b"asset", asset_id.as_bytes()],
+        bump
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.bump
+    )]
+    pub stats: Account<'info, ProtocolStats>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateRiskScore<'info> {
+    #[account(
+        mut,
+        seeds = [b"asset", asset.asset_id.as_bytes()],
+        bump = asset.bump
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    pub authority: Signer<'info>, // Oracle authority
+    
+    #[account(
+        mut,
+        seeds = [b"asset_history", asset.key().as_ref()],
+        bump = history.load()?.bump
+    )]
+    pub history: Option<AccountLoader<'info, AssetHistory>>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.risk_oracle == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,    
+    // Direct writes answer to the same bond as reported scores
+    #[account(
+        seeds = [b"oracle_bond", authority.key().as_ref()],
+        bump = oracle_bond.bump,
+        constraint = oracle_bond.bonded >= MIN_ORACLE_BOND @ ErrorCode::InsufficientOracleBond
+    )]
+    pub oracle_bond: Account<'info, OracleBond>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeAssetHistory<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + std::mem::size_of::<AssetHistory>(),
+        seeds = [b"asset_history", asset.key().as_ref()],
+        bump
+    )]
+    pub history: AccountLoader<'info, AssetHistory>,
+    
+    #[account(
+        seeds = [b"asset", asset.asset_id.as_bytes()],
+        bump = asset.bump
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RequestLoan<'info> {
+    #[account(
+        init,
+        payer = borrower,
+        space = 8 + Loan::INIT_SPACE,
+        seeds = [b"loan", asset.key().as_ref(), borrower.key().as_ref()],
+        bump
+    )]
+    pub loan: Account<'info, Loan>,
+    
+    // Only the owner borrows against the asset: a request counts as a lien,
+    // so anyone else could lock it. Delegates manage metadata, not credit.
+    #[account(
+        mut,
+        seeds = [b"asset", asset.asset_id.as_bytes()],
+        bump = asset.bump,
+        constraint = asset.owner == borrower.key() @ ErrorCode::Unauthorized
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"asset_type", asset.asset_type.as_bytes()],
+        bump = asset_type_config.bump
+    )]
+    pub asset_type_config: Account<'info, AssetTypeConfig>,
+    
+    // Currency the loan is disbursed and repaid in, an SPL Token or Token-2022 mint
+    #[account(
+        constraint = repayment_mint.unsupported_extension.is_none() @ ErrorCode::UnsupportedMintExtension
+    )]
+    pub repayment_mint: InterfaceAccount<'info, RepaymentMint>,
+    
+    #[account(mut)]
+    pub borrower: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        seeds = [b"blacklist"],
+        bump = blacklist.bump,
+        constraint = !blacklist.contains(&borrower.key()) @ ErrorCode::Blacklisted
+    )]
+    pub blacklist: Account<'info, Blacklist>,
+    
+    #[account(
+        seeds = [b"custody", asset.key().as_ref()],
+        bump = custody_attestation.bump
+    )]
+    pub custody_attestation: Account<'info, CustodyAttestation>,
+    
+    // Closed on deregistration, which invalidates the custodian's attestations
+    #[account(
+        seeds = [b"custodian", custody_attestation.custodian.as_ref()],
+        bump = custodian_record.bump
+    )]
+    pub custodian_record: Account<'info, Custodian>,
+    
+    // Agent who introduced the borrower, if any
+    #[account(
+        seeds = [b"referral", referral.agent.as_ref(), repayment_mint.key().as_ref()],
+        bump = referral.bump
+    )]
+    pub referral: Option<Account<'info, Referral>>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    // Required once the asset is placed in a jurisdiction
+    #[account(
+        seeds = [b"jurisdiction", asset.jurisdiction.as_ref()],
+        bump = jurisdiction_config.bump
+    )]
+    pub jurisdiction_config: Option<Account<'info, JurisdictionConfig>>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveLoan<'info> {
+    #[account(
+        mut,
+        seeds = [b"loan", loan.asset.as_ref(), loan.borrower.as_ref()],
+        bump = loan.bump,
+        has_one = asset @ ErrorCode::LoanAssetMismatch
+    )]
+    pub loan: Account<'info, Loan>,
+    
+    #[account(
+        seeds = [b"asset", asset.asset_id.as_bytes()],
+        bump = asset.bump
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"asset_type", asset.asset_type.as_bytes()],
+        bump = asset_type_config.bump
+    )]
+    pub asset_type_config: Account<'info, AssetTypeConfig>,
+    
+    #[account(address = loan.repayment_mint @ ErrorCode::RepaymentMintMismatch)]
+    pub repayment_mint: InterfaceAccount<'info, RepaymentMint>,
+    
+    #[account(
+        mut,
+        constraint = lender_token_account.owner == lender.key() @ ErrorCode::Unauthorized,
+        constraint = lender_token_account.mint == loan.repayment_mint @ ErrorCode::RepaymentMintMismatch
+    )]
+    pub lender_token_account: InterfaceAccount<'info, RepaymentTokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = borrower_token_account.owner == loan.borrower @ ErrorCode::BorrowerMismatch,
+        constraint = borrower_token_account.mint == loan.repayment_mint @ ErrorCode::RepaymentMintMismatch
+    )]
+    pub borrower_token_account: InterfaceAccount<'info, RepaymentTokenAccount>,
+    
+    pub lender: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenPrograms>,
+    
+    // The borrower may have been listed since applying
+    #[account(
+        seeds = [b"blacklist"],
+        bump = blacklist.bump,
+        constraint = !blacklist.contains(&lender.key()) @ ErrorCode::Blacklisted,
+        constraint = !blacklist.contains(&loan.borrower) @ ErrorCode::Blacklisted
+    )]
+    pub blacklist: Account<'info, Blacklist>,
+    
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.bump
+    )]
+    pub stats: Account<'info, ProtocolStats>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    // Required only while the config demands whitelisted lenders
+    #[account(
+        seeds = [b"lender_membership", lender.key().as_ref()],
+        bump = lender_membership.bump
+    )]
+    pub lender_membership: Option<Account<'info, LenderMembership>>,
+    
+    // Only needed when an origination fee is charged
+    #[account(
+        mut,
+        seeds = [b"revenue", loan.repayment_mint.as_ref()],
+        bump = revenue.bump
+    )]
+    pub revenue: Option<Account<'info, ProtocolRevenue>>,
+    
+    #[account(mut)]
+    pub revenue_vault: Option<InterfaceAccount<'info, RepaymentTokenAccount>>,
+    
+    // Only needed for referred loans
+    #[account(mut)]
+    pub referral: Option<Account<'info, Referral>>,
+    
+    #[account(mut)]
+    pub referral_vault: Option<InterfaceAccount<'info, RepaymentTokenAccount>>,
+    
+    // Required once the asset is placed in a jurisdiction
+    #[account(
+        seeds = [b"jurisdiction", asset.jurisdiction.as_ref()],
+        bump = jurisdiction_config.bump
+    )]
+    pub jurisdiction_config: Option<Account<'info, JurisdictionConfig>>,
+}
+
+#[derive(Accounts)]
+pub struct CancelLoanRequest<'info> {
+    #[account(
+        mut,
+        seeds = [b"loan", loan.asset.as_ref(), loan.borrower.as_ref()],
+        bump = loan.bump,
+        has_one = asset @ ErrorCode::LoanAssetMismatch,
+        has_one = borrower @ ErrorCode::BorrowerMismatch,
+        close = borrower
+    )]
+    pub loan: Account<'info, Loan>,
+    
+    #[account(
+        mut,
+        seeds = [b"asset", asset.asset_id.as_bytes()],
+        bump = asset.bump
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    /// CHECK: rent refund destination, validated against `loan.borrower`
+    #[account(mut)]
+    pub borrower: UncheckedAccount<'info>,
+    
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RepayLoan<'info> {
+    #[account(
+        mut,
+        seeds = [b"loan", loan.asset.as_ref(), loan.borrower.as_ref()],
+        bump = loan.bump,
+        has_one = asset @ ErrorCode::LoanAssetMismatch,
+        has_one = borrower @ ErrorCode::BorrowerMismatch
+    )]
+    pub loan: Account<'info, Loan>,
+    
+    #[account(
+        mut,
+        seeds = [b"asset", asset.asset_id.as_bytes()],
+        bump = asset.bump
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(mut)]
+    pub borrower: Signer<'info>,
+    
+    #[account(address = loan.repayment_mint @ ErrorCode::RepaymentMintMismatch)]
+    pub repayment_mint: InterfaceAccount<'info, RepaymentMint>,
+    
+    #[account(
+        mut,
+        constraint = borrower_token_account.owner == borrower.key() @ ErrorCode::BorrowerMismatch,
+        constraint = borrower_token_account.mint == loan.repayment_mint @ ErrorCode::RepaymentMintMismatch
+    )]
+    pub borrower_token_account: InterfaceAccount<'info, RepaymentTokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = lender_token_account.mint == loan.repayment_mint @ ErrorCode::RepaymentMintMismatch,
+        constraint = lender_token_account.owner == loan.lender @ ErrorCode::Unauthorized
+    )]
+    pub lender_token_account: InterfaceAccount<'info, RepaymentTokenAccount>,
+    
+    pub token_program: Interface<'info, TokenPrograms>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    // Only needed when the reserve factor leaves a protocol share
+    #[account(
+        mut,
+        seeds = [b"revenue", loan.repayment_mint.as_ref()],
+        bump = revenue.bump
+    )]
+    pub revenue: Option<Account<'info, ProtocolRevenue>>,
+    
+    #[account(mut)]
+    pub revenue_vault: Option<InterfaceAccount<'info, RepaymentTokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.bump
+    )]
+    pub stats: Account<'info, ProtocolStats>,
+}
+
+#[derive(Accounts)]
+pub struct RepayLoanWithMint<'info> {
+    #[account(
+        mut,
+        seeds = [b"loan", loan.asset.as_ref(), loan.borrower.as_ref()],
+        bump = loan.bump,
+        has_one = asset @ ErrorCode::LoanAssetMismatch,
+        has_one = borrower @ ErrorCode::BorrowerMismatch
+    )]
+    pub loan: Account<'info, Loan>,
+    
+    #[account(
+        mut,
+        seeds = [b"asset", asset.asset_id.as_bytes()],
+        bump = asset.bump
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(mut)]
+    pub borrower: Signer<'info>,
+    
+    // The loan's own mint, read for its decimals
+    #[account(address = loan.repayment_mint @ ErrorCode::RepaymentMintMismatch)]
+    pub loan_mint: InterfaceAccount<'info, RepaymentMint>,
+    
+    #[account(
+        seeds = [b"accepted_mint", loan.repayment_mint.as_ref()],
+        bump = loan_mint_config.bump
+    )]
+    pub loan_mint_config: Account<'info, AcceptedMint>,
+    
+    /// CHECK: owner is pinned to Switchboard and the layout is verified on read
+    #[account(
+        address = loan_mint_config.price_feed @ ErrorCode::OracleMismatch,
+        owner = SWITCHBOARD_PROGRAM_ID @ ErrorCode::OracleMismatch
+    )]
+    pub loan_price_feed: UncheckedAccount<'info>,
+    
+    pub payment_mint: InterfaceAccount<'info, RepaymentMint>,
+    
+    #[account(
+        seeds = [b"accepted_mint", payment_mint.key().as_ref()],
+        bump = payment_mint_config.bump
+    )]
+    pub payment_mint_config: Account<'info, AcceptedMint>,
+    
+    /// CHECK: owner is pinned to Switchboard and the layout is verified on read
+    #[account(
+        address = payment_mint_config.price_feed @ ErrorCode::OracleMismatch,
+        owner = SWITCHBOARD_PROGRAM_ID @ ErrorCode::OracleMismatch
+    )]
+    pub payment_price_feed: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        constraint = borrower_token_account.owner == borrower.key() @ ErrorCode::BorrowerMismatch,
+        constraint = borrower_token_account.mint == payment_mint.key() @ ErrorCode::RepaymentMintMismatch
+    )]
+    pub borrower_token_account: InterfaceAccount<'info, RepaymentTokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = lender_token_account.mint == payment_mint.key() @ ErrorCode::RepaymentMintMismatch,
+        constraint = lender_token_account.owner == loan.lender @ ErrorCode::Unauthorized
+    )]
+    pub lender_token_account: InterfaceAccount<'info, RepaymentTokenAccount>,
+    
+    pub token_program: Interface<'info, TokenPrograms>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    // Revenue for the paying mint; only needed when there is a protocol share
+    #[account(
+        mut,
+        seeds = [b"revenue", payment_mint.key().as_ref()],
+        bump = revenue.bump
+    )]
+    pub revenue: Option<Account<'info, ProtocolRevenue>>,
+    
+    #[account(mut)]
+    pub revenue_vault: Option<InterfaceAccount<'info, RepaymentTokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.bump
+    )]
+    pub stats: Account<'info, ProtocolStats>,
+}
+
+#[derive(Accounts)]
+pub struct CreateRepaymentSchedule<'info> {
+    #[account(
+        init,
+        payer = borrower,
+        space = 8 + RepaymentSchedule::INIT_SPACE,
+        seeds = [b"schedule", loan.key().as_ref()],
+        bump
+    )]
+    pub schedule: Account<'info, RepaymentSchedule>,
+    
+    #[account(
+        seeds = [b"loan", loan.asset.as_ref(), loan.borrower.as_ref()],
+        bump = loan.bump,
+        has_one = borrower @ ErrorCode::BorrowerMismatch
+    )]
+    pub loan: Account<'info, Loan>,
+    
+    #[account(mut)]
+    pub borrower: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PayInstallment<'info> {
+    #[account(
+        mut,
+        seeds = [b"schedule", loan.key().as_ref()],
+        bump = schedule.bump,
+        has_one = loan
+    )]
+    pub schedule: Account<'info, RepaymentSchedule>,
+    
+    #[account(
+        mut,
+        seeds = [b"loan", loan.asset.as_ref(), loan.borrower.as_ref()],
+        bump = loan.bump,
+        has_one = asset @ ErrorCode::LoanAssetMismatch,
+        has_one = borrower @ ErrorCode::BorrowerMismatch
+    )]
+    pub loan: Account<'info, Loan>,
+    
+    #[account(
+        mut,
+        seeds = [b"asset", asset.asset_id.as_bytes()],
+        bump = asset.bump
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    pub borrower: Signer<'info>,
+    
+    #[account(address = loan.repayment_mint @ ErrorCode::RepaymentMintMismatch)]
+    pub repayment_mint: InterfaceAccount<'info, RepaymentMint>,
+    
+    #[account(
+        mut,
+        constraint = borrower_token_account.owner == borrower.key() @ ErrorCode::BorrowerMismatch,
+        constraint = borrower_token_account.mint == loan.repayment_mint @ ErrorCode::RepaymentMintMismatch
+    )]
+    pub borrower_token_account: InterfaceAccount<'info, RepaymentTokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = lender_token_account.mint == loan.repayment_mint @ ErrorCode::RepaymentMintMismatch,
+        constraint = lender_token_account.owner == loan.lender @ ErrorCode::Unauthorized
+    )]
+    pub lender_token_account: InterfaceAccount<'info, RepaymentTokenAccount>,
+    
+    pub token_program: Interface<'info, TokenPrograms>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    // Only needed when the reserve factor leaves a protocol share
+    #[account(
+        mut,
+        seeds = [b"revenue", loan.repayment_mint.as_ref()],
+        bump = revenue.bump
+    )]
+    pub revenue: Option<Account<'info, ProtocolRevenue>>,
+    
+    #[account(mut)]
+    pub revenue_vault: Option<InterfaceAccount<'info, RepaymentTokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.bump
+    )]
+    pub stats: Account<'info, ProtocolStats>,
+}
+
+#[derive(Accounts)]
+pub struct CheckDelinquency<'info> {
+    #[account(
+        mut,
+        seeds = [b"schedule", schedule.loan.as_ref()],
+        bump = schedule.bump
+    )]
+    pub schedule: Account<'info, RepaymentSchedule>,
+}
+
+#[derive(Accounts)]
+pub struct LiquidateLoan<'info> {
+    #[account(
+        mut,
+        seeds = [b"loan", loan.asset.as_ref(), loan.borrower.as_ref()],
+        bump = loan.bump,
+        has_one = asset @ ErrorCode::LoanAssetMismatch
+    )]
+    pub loan: Account<'info, Loan>,
+    
+    #[account(
+        mut,
+        seeds = [b"asset", asset.asset_id.as_bytes()],
+        bump = asset.bump
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        constraint = supplemental_asset.key() == loan.supplemental_asset @ ErrorCode::LoanAssetMismatch
+    )]
+    pub supplemental_asset: Option<Account<'info, Asset>>,
+    
+    pub liquidator: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.bump
+    )]
+    pub stats: Account<'info, ProtocolStats>,
+}
+
+#[derive(Accounts)]
+pub struct SeizeCollateral<'info> {
+    #[account(
+        seeds = [b"loan", loan.asset.as_ref(), loan.borrower.as_ref()],
+        bump = loan.bump,
+        has_one = asset @ ErrorCode::LoanAssetMismatch,
+        constraint = loan.status == LoanStatus::Liquidated @ ErrorCode::LoanNotLiquidated,
+        constraint = loan.liquidator == recipient.key() @ ErrorCode::Unauthorized
+    )]
+    pub loan: Account<'info, Loan>,
+    
+    #[account(
+        mut,
+        seeds = [b"asset", asset.asset_id.as_bytes()],
+        bump = asset.bump
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        init,
+        payer = recipient,
+        space = 8 + CollateralSettlement::INIT_SPACE,
+        seeds = [b"settlement", loan.key().as_ref()],
+        bump
+    )]
+    pub settlement: Account<'info, CollateralSettlement>,
+    
+    #[account(mut)]
+    pub recipient: Signer<'info>, // The loan's liquidator
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeLiquidationQueue<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + LiquidationQueue::INIT_SPACE,
+        seeds = [b"liquidation_queue"],
+        bump
+    )]
+    pub queue: Account<'info, LiquidationQueue>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct EnqueueLiquidation<'info> {
+    #[account(
+        mut,
+        seeds = [b"liquidation_queue"],
+        bump = queue.bump
+    )]
+    pub queue: Account<'info, LiquidationQueue>,
+    
+    #[account(
+        mut,
+        seeds = [b"loan", loan.asset.as_ref(), loan.borrower.as_ref()],
+        bump = loan.bump,
+        has_one = asset @ ErrorCode::LoanAssetMismatch
+    )]
+    pub loan: Account<'info, Loan>,
+    
+    #[account(
+        seeds = [b"asset", asset.asset_id.as_bytes()],
+        bump = asset.bump
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        constraint = supplemental_asset.key() == loan.supplemental_asset @ ErrorCode::LoanAssetMismatch
+    )]
+    pub supplemental_asset: Option<Account<'info, Asset>>,
+}
+
+#[derive(Accounts)]
+pub struct CrankLiquidations<'info> {
+    #[account(
+        mut,
+        seeds = [b"liquidation_queue"],
+        bump = queue.bump
+    )]
+    pub queue: Account<'info, LiquidationQueue>,
+    
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.bump
+    )]
+    pub stats: Account<'info, ProtocolStats>,
+}
+
+#[derive(Accounts)]
+pub struct CheckLoanHealth<'info> {
+    #[account(
+        mut,
+        seeds = [b"loan", loan.asset.as_ref(), loan.borrower.as_ref()],
+        bump = loan.bump,
+        has_one = asset @ ErrorCode::LoanAssetMismatch
+    )]
+    pub loan: Account<'info, Loan>,
+    
+    #[account(
+        seeds = [b"asset", asset.asset_id.as_bytes()],
+        bump = asset.bump
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        constraint = supplemental_asset.key() == loan.supplemental_asset @ ErrorCode::LoanAssetMismatch
+    )]
+    pub supplemental_asset: Option<Account<'info, Asset>>,
+}
+
+#[derive(Accounts)]
+pub struct CrankLoanState<'info> {
+    #[account(
+        mut,
+        seeds = [b"loan", loan.asset.as_ref(), loan.borrower.as_ref()],
+        bump = loan.bump,
+        has_one = asset @ ErrorCode::LoanAssetMismatch
+    )]
+    pub loan: Account<'info, Loan>,
+    
+    #[account(
+        seeds = [b"asset", asset.asset_id.as_bytes()],
+        bump = asset.bump
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        constraint = supplemental_asset.key() == loan.supplemental_asset @ ErrorCode::LoanAssetMismatch
+    )]
+    pub supplemental_asset: Option<Account<'info, Asset>>,
+}
+
+#[derive(Accounts)]
+pub struct QuoteLoan<'info> {
+    #[account(
+        seeds = [b"loan", loan.asset.as_ref(), loan.borrower.as_ref()],
+        bump = loan.bump,
+        has_one = asset @ ErrorCode::LoanAssetMismatch
+    )]
+    pub loan: Account<'info, Loan>,
+    
+    #[account(
+        seeds = [b"asset", asset.asset_id.as_bytes()],
+        bump = asset.bump
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        constraint = supplemental_asset.key() == loan.supplemental_asset @ ErrorCode::LoanAssetMismatch
+    )]
+    pub supplemental_asset: Option<Account<'info, Asset>>,
+}
+
+#[derive(Accounts)]
+pub struct TopUpCollateral<'info> {
+    #[account(
+        mut,
+        seeds = [b"loan", loan.asset.as_ref(), loan.borrower.as_ref()],
+        bump = loan.bump,
+        has_one = asset @ ErrorCode::LoanAssetMismatch,
+        has_one = borrower @ ErrorCode::BorrowerMismatch
+    )]
+    pub loan: Account<'info, Loan>,
+    
+    #[account(
+        seeds = [b"asset", asset.asset_id.as_bytes()],
+        bump = asset.bump
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        mut,
+        seeds = [b"asset", supplemental_asset.asset_id.as_bytes()],
+        bump = supplemental_asset.bump,
+        constraint = supplemental_asset.owner == borrower.key() @ ErrorCode::Unauthorized,
+        constraint = supplemental_asset.key() != asset.key() @ ErrorCode::InvalidTopUp
+    )]
+    pub supplemental_asset: Account<'info, Asset>,
+    
+    // Type rules of the top-up asset, which health applies to it from now on
+    #[account(
+        seeds = [b"asset_type", supplemental_asset.asset_type.as_bytes()],
+        bump = asset_type_config.bump
+    )]
+    pub asset_type_config: Account<'info, AssetTypeConfig>,
+    
+    pub borrower: Signer<'info>,
+    
+    // Required once the top-up asset is placed in a jurisdiction
+    #[account(
+        seeds = [b"jurisdiction", supplemental_asset.jurisdiction.as_ref()],
+        bump = jurisdiction_config.bump
+    )]
+    pub jurisdiction_config: Option<Account<'info, JurisdictionConfig>>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseCollateral<'info> {
+    #[account(
+        mut,
+        seeds = [b"loan", loan.asset.as_ref(), loan.borrower.as_ref()],
+        bump = loan.bump
+    )]
+    pub loan: Account<'info, Loan>,
+    
+    #[account(
+        mut,
+        address = loan.supplemental_asset @ ErrorCode::LoanAssetMismatch
+    )]
+    pub supplemental_asset: Account<'info, Asset>,
+}
+
+#[derive(Accounts)]
+pub struct WriteOffLoan<'info> {
+    #[account(
+        mut,
+        seeds = [b"loan", loan.asset.as_ref(), loan.borrower.as_ref()],
+        bump = loan.bump,
+        has_one = asset @ ErrorCode::LoanAssetMismatch
+    )]
+    pub loan: Account<'info, Loan>,
+    
+    #[account(
+        seeds = [b"asset", asset.asset_id.as_bytes()],
+        bump = asset.bump
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    pub admin: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.bump
+    )]
+    pub stats: Account<'info, ProtocolStats>,
+    
+    // Created by `seize_collateral`; without it nothing has been recovered yet
+    #[account(
+        seeds = [b"settlement", loan.key().as_ref()],
+        bump = settlement.bump
+    )]
+    pub settlement: Option<Account<'info, CollateralSettlement>>,
+}
+
+#[derive(Accounts)]
+pub struct RaiseDispute<'info> {
+    #[account(
+        mut,
+        seeds = [b"loan", loan.asset.as_ref(), loan.borrower.as_ref()],
+        bump = loan.bump
+    )]
+    pub loan: Account<'info, Loan>,
+    
+    pub party: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveDispute<'info> {
+    #[account(
+        mut,
+        seeds = [b"loan", loan.asset.as_ref(), loan.borrower.as_ref()],
+        bump = loan.bump
+    )]
+    pub loan: Account<'info, Loan>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = arbitrator @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    pub arbitrator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(pause_duration: i64, maturity_extension: i64, reason: String)]
+pub struct GrantForbearance<'info> {
+    #[account(
+        mut,
+        seeds = [b"loan", loan.asset.as_ref(), loan.borrower.as_ref()],
+        bump = loan.bump
+    )]
+    pub loan: Account<'info, Loan>,
+    
+    #[account(
+        init,
+        payer = admin,
+        space = LoanModification::space(&reason),
+        seeds = [b"modification", loan.key().as_ref(), &loan.modification_count.to_le_bytes()],
+        bump
+    )]
+    pub modification: Account<'info, LoanModification>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RepriceLoan<'info> {
+    #[account(
+        mut,
+        seeds = [b"loan", loan.asset.as_ref(), loan.borrower.as_ref()],
+        bump = loan.bump
+    )]
+    pub loan: Account<'info, Loan>,
+    
+    #[account(
+        seeds = [b"benchmark", benchmark.feed.as_ref()],
+        bump = benchmark.bump
+    )]
+    pub benchmark: Account<'info, BenchmarkFeed>,
+    
+    /// CHECK: owner is pinned to Switchboard and the layout is verified on read
+    #[account(
+        address = benchmark.feed @ ErrorCode::OracleMismatch,
+        owner = SWITCHBOARD_PROGRAM_ID @ ErrorCode::OracleMismatch
+    )]
+    pub feed: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RolloverLoan<'info> {
+    #[account(
+        mut,
+        seeds = [b"loan", loan.asset.as_ref(), loan.borrower.as_ref()],
+        bump = loan.bump,
+        has_one = asset @ ErrorCode::LoanAssetMismatch,
+        has_one = borrower @ ErrorCode::BorrowerMismatch,
+        has_one = lender @ ErrorCode::Unauthorized
+    )]
+    pub loan: Account<'info, Loan>,
+    
+    #[account(
+        init,
+        payer = borrower,
+        space = 8 + LoanRollover::INIT_SPACE,
+        seeds = [b"rollover", loan.key().as_ref(), &loan.rollover_count.to_le_bytes()],
+        bump
+    )]
+    pub rollover: Account<'info, LoanRollover>,
+    
+    #[account(
+        seeds = [b"asset", asset.asset_id.as_bytes()],
+        bump = asset.bump
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"asset_type", asset.asset_type.as_bytes()],
+        bump = asset_type_config.bump
+    )]
+    pub asset_type_config: Account<'info, AssetTypeConfig>,
+    
+    /// CHECK: must not exist; only its address is derived
+    #[account(
+        seeds = [b"schedule", loan.key().as_ref()],
+        bump
+    )]
+    pub schedule: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub borrower: Signer<'info>,
+    
+    // The lender agrees to the new rate and term
+    pub lender: Signer<'info>,
+    
+    #[account(address = loan.repayment_mint @ ErrorCode::RepaymentMintMismatch)]
+    pub repayment_mint: InterfaceAccount<'info, RepaymentMint>,
+    
+    #[account(
+        mut,
+        constraint = borrower_token_account.owner == borrower.key() @ ErrorCode::BorrowerMismatch,
+        constraint = borrower_token_account.mint == loan.repayment_mint @ ErrorCode::RepaymentMintMismatch
+    )]
+    pub borrower_token_account: InterfaceAccount<'info, RepaymentTokenAccount>,
+    
+    pub token_program: Interface<'info, TokenPrograms>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    // Only needed when an origination fee is charged
+    #[account(
+        mut,
+        seeds = [b"revenue", loan.repayment_mint.as_ref()],
+        bump = revenue.bump
+    )]
+    pub revenue: Option<Account<'info, ProtocolRevenue>>,
+    
+    #[account(mut)]
+    pub revenue_vault: Option<InterfaceAccount<'info, RepaymentTokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.bump
+    )]
+    pub stats: Account<'info, ProtocolStats>,
+    
+    pub system_program: Program<'info, System>,
+    
+    // Required once the asset is placed in a jurisdiction
+    #[account(
+        seeds = [b"jurisdiction", asset.jurisdiction.as_ref()],
+        bump = jurisdiction_config.bump
+    )]
+    pub jurisdiction_config: Option<Account<'info, JurisdictionConfig>>,
+}
+
+#[derive(Accounts)]
+pub struct DeactivateAsset<'info> {
+    #[account(
+        mut,
+        seeds = [b"asset", asset.asset_id.as_bytes()],
+        bump = asset.bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct TransferAsset<'info> {
+    #[account(
+        mut,
+        seeds = [b"asset", asset.asset_id.as_bytes()],
+        bump = asset.bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    pub owner: Signer<'info>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    // Required once the asset is placed in a jurisdiction
+    #[account(
+        seeds = [b"jurisdiction", asset.jurisdiction.as_ref()],
+        bump = jurisdiction_config.bump
+    )]
+    pub jurisdiction_config: Option<Account<'info, JurisdictionConfig>>,
+    
+    // Co-signs transfers in jurisdictions that require compliance approval
+    pub compliance_authority: Option<Signer<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct ManageDelegate<'info> {
+    #[account(
+        mut,
+        seeds = [b"asset", asset.asset_id.as_bytes()],
+        bump = asset.bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ManageAsset<'info> {
+    #[account(
+        mut,
+        seeds = [b"asset", asset.asset_id.as_bytes()],
+        bump = asset.bump,
+        constraint = is_asset_manager(&asset, authority.key) @ ErrorCode::Unauthorized
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    pub authority: Signer<'info>, // Owner or delegate
+}
+
+#[derive(Accounts)]
+pub struct MintAssetNft<'info> {
+    #[account(
+        seeds = [b"asset", asset.asset_id.as_bytes()],
+        bump = asset.bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    /// CHECK: created and initialized as a mint by this instruction
+    #[account(
+        mut,
+        seeds = [b"asset_nft", asset.key().as_ref()],
+        bump
+    )]
+    pub nft_mint: UncheckedAccount<'info>,
+    
+    /// CHECK: created by the associated token program
+    #[account(mut, address = associated_token_address(&owner.key(), &nft_mint.key()))]
+    pub owner_nft_account: UncheckedAccount<'info>,
+    
+    /// CHECK: created by the token metadata program
+    #[account(mut, address = nft_metadata_address(&nft_mint.key()))]
+    pub metadata: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    
+    /// CHECK: program id checked by address
+    #[account(address = ASSOCIATED_TOKEN_PROGRAM_ID)]
+    pub associated_token_program: UncheckedAccount<'info>,
+    
+    /// CHECK: program id checked by address
+    #[account(address = TOKEN_METADATA_PROGRAM_ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SyncAssetNft<'info> {
+    #[account(
+        seeds = [b"asset", asset.asset_id.as_bytes()],
+        bump = asset.bump
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"asset_nft", asset.key().as_ref()],
+        bump
+    )]
+    pub nft_mint: Account<'info, Mint>,
+    
+    // Whichever account currently holds the NFT
+    #[account(
+        mut,
+        constraint = nft_account.mint == nft_mint.key() @ ErrorCode::InvalidTokenAccount,
+        constraint = nft_account.amount == 1 @ ErrorCode::InvalidTokenAccount
+    )]
+    pub nft_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: owned and validated by the token metadata program
+    #[account(mut, address = nft_metadata_address(&nft_mint.key()))]
+    pub metadata: UncheckedAccount<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    
+    /// CHECK: program id checked by address
+    #[account(address = TOKEN_METADATA_PROGRAM_ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReactivateAsset<'info> {
+    #[account(
+        mut,
+        seeds = [b"asset", asset.asset_id.as_bytes()],
+        bump = asset.bump
+    )]
+    pub asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(portfolio_id: String)]
+pub struct BundleAssets<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = Asset::space(&portfolio_id, PORTFOLIO_ASSET_TYPE),
+        seeds = [b"asset", portfolio_id.as_bytes()],
+        bump
+    )]
+    pub portfolio_asset: Account<'info, Asset>,
+    
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + PortfolioAsset::INIT_SPACE,
+        seeds = [b"portfolio", portfolio_asset.key().as_ref()],
+        bump
+    )]
+    pub portfolio: Account<'info, PortfolioAsset>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.bump
+    )]
+    pub stats: Account<'info, ProtocolStats>,
+}
+
+#[derive(Accounts)]
+pub struct RefreshPortfolio<'info> {
+    #[account(
+        mut,
+        seeds = [b"asset", portfolio_asset.asset_id.as_bytes()],
+        bump = portfolio_asset.bump
+    )]
+    pub portfolio_asset: Account<'info, Asset>,
+    
+    #[account(
+        seeds = [b"portfolio", portfolio_asset.key().as_ref()],
+        bump = portfolio.bump
+    )]
+    pub portfolio: Account<'info, PortfolioAsset>,
+}
+
+#[derive(Accounts)]
+pub struct UnbundleAssets<'info> {
+    #[account(
+        mut,
+        seeds = [b"asset", portfolio_asset.asset_id.as_bytes()],
+        bump = portfolio_asset.bump,
+        has_one = owner @ ErrorCode::Unauthorized,
+        close = owner
+    )]
+    pub portfolio_asset: Account<'info, Asset>,
+    
+    #[account(
+        mut,
+        seeds = [b"portfolio", portfolio_asset.key().as_ref()],
+        bump = portfolio.bump,
+        close = owner
+    )]
+    pub portfolio: Account<'info, PortfolioAsset>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.bump
+    )]
+    pub stats: Account<'info, ProtocolStats>,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Asset {
+    #[max_len(MAX_ASSET_ID_LEN)]
+    pub asset_id: String,        // 4 + up to 32 bytes, sized to the id at creation
+    #[max_len(MAX_ASSET_TYPE_LEN)]
+    pub asset_type: String,      // 4 + up to 32 bytes, sized to the type at creation
+    pub valuation: u64,          // 8 bytes
+    #[max_len(MAX_METADATA_URI_LEN)]
+    pub metadata_uri: String,    // 4 + 196 bytes
+    pub owner: Pubkey,           // 32 bytes
+    pub is_active: bool,         // 1 byte
+    pub risk_score: u8,          // 1 byte
+    pub bump: u8,                // 1 byte
+    pub active_loans: u32,       // 4 bytes, liens: loan requests, open loans and top-up pledges
+    pub delegate: Pubkey,        // 32 bytes, default if none
+    pub valuation_refresh_requested_at: i64, // 8 bytes, 0 if never requested
+    pub last_risk_report: u64,   // 8 bytes, id of the last Merkle risk report applied
+    pub risk_frozen_until: i64,  // 8 bytes, circuit breaker freeze end, 0 if not tripped
+    #[max_len(REPORT_DISPUTE_QUORUM)]
+    pub breaker_confirmers: Vec<Pubkey>, // 4 + 32 * 2 bytes, oracles confirming the last jump
+    pub last_risk_update: i64,   // 8 bytes, when an oracle last set the score, 0 if never
+    pub jurisdiction: [u8; 2],   // 2 bytes, ISO 3166-1 alpha-2 country code, zeroed if unplaced
+    pub portfolio: Pubkey,       // 32 bytes, portfolio asset bundling this one, default if none
+}
+
+// Membership of a portfolio asset, which shares its collateral fields with Asset
+#[account]
+#[derive(InitSpace)]
+pub struct PortfolioAsset {
+    pub asset: Pubkey,           // 32 bytes, the portfolio's Asset account
+    #[max_len(MAX_PORTFOLIO_ASSETS)]
+    pub members: Vec<Pubkey>,    // 4 + 32 * 10 bytes, in bundling order
+    pub bundled_at: i64,         // 8 bytes
+    pub bump: u8,                // 1 byte
+}
+
+/// Ring buffer of an asset's risk scores and valuations. Zero-copy so updates
+/// write one entry in place instead of deserializing the whole buffer.
+#[account(zero_copy)]
+pub struct AssetHistory {
+    pub asset: Pubkey,           // 32 bytes
+    pub head: u32,               // 4 bytes, slot the next entry is written to
+    pub len: u32,                // 4 bytes
+    pub bump: u8,                // 1 byte
+    pub _padding: [u8; 7],       // 7 bytes
+    pub entries: [HistoryEntry; ASSET_HISTORY_LEN], // 24 * 256 bytes
+}
+
+#[zero_copy]
+pub struct HistoryEntry {
+    pub timestamp: i64,          // 8 bytes
+    pub valuation: u64,          // 8 bytes
+    pub risk_score: u8,          // 1 byte
+    pub source: u8,              // 1 byte, HISTORY_SOURCE_*
+    pub _padding: [u8; 6],       // 6 bytes
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ProtocolConfig {
+    pub admin: Pubkey,           // 32 bytes
+    pub bump: u8,                // 1 byte
+    pub arbitrator: Pubkey,      // 32 bytes, resolves loan disputes
+    pub reserve_factor_bps: u16, // 2 bytes, protocol share of interest
+    pub compliance_authority: Pubkey, // 32 bytes, manages the blacklist
+    pub risk_oracle: Pubkey,     // 32 bytes, posts risk scores and Merkle risk reports
+    pub risk_jump_threshold: u8, // 1 byte, score move that trips the circuit breaker, 0 if off
+    pub breaker_cooldown: i64,   // 8 bytes, seconds a tripped asset stays frozen
+    pub lender_compliance_authority: Pubkey, // 32 bytes, manages lender memberships
+    pub lender_whitelist_required: bool, // 1 byte, lenders need a live membership to fund loans
+    pub origination_fee_bps: u16, // 2 bytes, withheld from each disbursement
+    pub referral_share_bps: u16, // 2 bytes, slice of the origination fee paid to referrers
+    pub max_risk_age: i64,       // 8 bytes, oldest risk score new loans accept, 0 if unchecked
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct RiskReport {
+    pub report_id: u64,          // 8 bytes
+    pub root: [u8; 32],          // 32 bytes, Merkle root of (asset, risk score) leaves
+    pub leaf_count: u32,         // 4 bytes, informational
+    pub posted_at: i64,          // 8 bytes
+    pub bump: u8,                // 1 byte
+    pub oracle: Pubkey,          // 32 bytes, bonded oracle that posted it
+    pub overturned: bool,        // 1 byte, set when a dispute slashes the oracle
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct OracleBond {
+    pub oracle: Pubkey,          // 32 bytes
+    pub bonded: u64,             // 8 bytes, lamports held above rent
+    pub open_disputes: u16,      // 2 bytes, unresolved disputes against this oracle
+    pub last_report_at: i64,     // 8 bytes
+    pub slashed: u64,            // 8 bytes, lifetime amount slashed
+    pub bump: u8,                // 1 byte
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ReportDispute {
+    pub report: Pubkey,          // 32 bytes
+    pub oracle: Pubkey,          // 32 bytes, the report's poster
+    pub challenger: Pubkey,      // 32 bytes, receives the slashed bond
+    pub opened_at: i64,          // 8 bytes
+    #[max_len(MAX_DISPUTE_VOTERS)]
+    pub voters: Vec<Pubkey>,     // 4 + 32 * 16 bytes
+    pub overturn_votes: u8,      // 1 byte
+    pub uphold_votes: u8,        // 1 byte
+    pub resolved: bool,          // 1 byte
+    pub bump: u8,                // 1 byte
+}
+
+impl ReportDispute {
+    pub fn is_overturned(&self) -> bool {
+        self.overturn_votes >= REPORT_DISPUTE_QUORUM && self.overturn_votes > self.uphold_votes
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Blacklist {
+    #[max_len(MAX_BLACKLIST_LEN)]
+    pub entries: Vec<Pubkey>,    // 4 + 32 * 200 bytes
+    pub bump: u8,                // 1 byte
+}
+
+impl Blacklist {
+    pub fn contains(&self, wallet: &Pubkey) -> bool {
+        self.entries.contains(wallet)
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Custodian {
+    pub custodian: Pubkey,       // 32 bytes
+    pub bump: u8,                // 1 byte
+}
+
+// Accredited-lender membership, separate from the borrower-side blacklist
+#[account]
+#[derive(InitSpace)]
+pub struct LenderMembership {
+    pub lender: Pubkey,          // 32 bytes
+    pub granted_at: i64,         // 8 bytes
+    pub expires_at: i64,         // 8 bytes
+    pub bump: u8,                // 1 byte
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct CustodyAttestation {
+    pub asset: Pubkey,           // 32 bytes
+    pub custodian: Pubkey,       // 32 bytes
+    pub document_hash: [u8; 32], // 32 bytes, e.g. SHA-256 of the deed or custody agreement
+    pub attested_at: i64,        // 8 bytes
+    pub expires_at: i64,         // 8 bytes
+    pub bump: u8,                // 1 byte
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ProtocolRevenue {
+    pub mint: Pubkey,            // 32 bytes
+    pub vault: Pubkey,           // 32 bytes, token account owned by this PDA
+    pub accrued: u64,            // 8 bytes, lifetime protocol share collected
+    pub claimed: u64,            // 8 bytes, lifetime amount swept to the admin
+    pub bump: u8,                // 1 byte
+}
+
+/// Protocol-wide counters kept current by every instruction that creates an
+/// asset or opens, repays, liquidates or writes off a loan. Active loans and
+/// outstanding principal count disbursed loans only, at their original
+/// principal; updates saturate so stats can never block a loan operation.
+#[account]
+#[derive(InitSpace)]
+pub struct ProtocolStats {
+    pub total_assets: u64,                // 8 bytes
+    pub active_loans: u64,                // 8 bytes
+    pub total_principal_outstanding: u64, // 8 bytes
+    pub cumulative_repaid: u64,           // 8 bytes, repayments and installments, gross of the protocol share
+    pub liquidation_count: u64,           // 8 bytes
+    pub bump: u8,                         // 1 byte
+    pub cumulative_written_off: u64,      // 8 bytes, losses booked by write_off_loan
+}
+
+impl ProtocolStats {
+    pub fn record_disbursement(&mut self, principal: u64) {
+        self.active_loans = self.active_loans.saturating_add(1);
+        self.total_principal_outstanding = self.total_principal_outstanding.saturating_add(principal);
+    }
+
+    pub fn record_repayment(&mut self, amount: u64) {
+        self.cumulative_repaid = self.cumulative_repaid.saturating_add(amount);
+    }
+
+    pub fn record_principal_repaid(&mut self, principal: u64) {
+        self.total_principal_outstanding = self.total_principal_outstanding.saturating_sub(principal);
+    }
+
+    pub fn record_closure(&mut self, principal: u64) {
+        self.active_loans = self.active_loans.saturating_sub(1);
+        self.total_principal_outstanding = self.total_principal_outstanding.saturating_sub(principal);
+    }
+
+    pub fn record_write_off(&mut self, amount: u64) {
+        self.cumulative_written_off = self.cumulative_written_off.saturating_add(amount);
+    }
+}
+
+/// Fees and volume earned by an agent who introduces borrowers, per mint.
+#[account]
+#[derive(InitSpace)]
+pub struct Referral {
+    pub agent: Pubkey,           // 32 bytes
+    pub mint: Pubkey,            // 32 bytes
+    pub vault: Pubkey,           // 32 bytes, token account owned by this PDA
+    pub accrued: u64,            // 8 bytes, lifetime referral fees received
+    pub claimed: u64,            // 8 bytes, lifetime amount swept to the agent
+    pub referred_loans: u64,     // 8 bytes, disbursed loans carrying this referral
+    pub referred_volume: u64,    // 8 bytes, principal of those loans
+    pub bump: u8,                // 1 byte
+}
+
+impl Referral {
+    pub fn record_referral(&mut self, principal: u64, fee: u64) -> Result<()> {
+        self.accrued = self.accrued.checked_add(fee).ok_or(ErrorCode::MathOverflow)?;
+        self.referred_loans = self.referred_loans.saturating_add(1);
+        self.referred_volume = self.referred_volume.saturating_add(principal);
+        Ok(())
+    }
+}
+
+/// Return data of `quote_loan`, computed with the same math the loan's
+/// repayment and liquidation instructions use.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct LoanQuote {
+    pub health_factor_bps: u64,  // 8 bytes, u64::MAX when nothing is owed
+    pub accrued_interest: u64,   // 8 bytes
+    pub payoff_amount: u64,      // 8 bytes, principal plus accrued interest
+    pub quoted_at: i64,          // 8 bytes
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct LiquidationQueue {
+    #[max_len(MAX_LIQUIDATION_QUEUE_LEN)]
+    pub entries: Vec<QueuedLiquidation>, // 4 + 40 * 64 bytes, oldest first
+    pub bump: u8,                // 1 byte
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct QueuedLiquidation {
+    pub loan: Pubkey,            // 32 bytes
+    pub enqueued_at: i64,        // 8 bytes
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Multisig {
+    #[max_len(MAX_MULTISIG_OWNERS)]
+    pub owners: Vec<Pubkey>,     // 4 + 32 * 10 bytes
+    pub threshold: u8,           // 1 byte
+    pub owner_set_seqno: u32,    // 4 bytes, bumped whenever owners change
+    pub proposal_count: u64,     // 8 bytes
+    pub signer_bump: u8,         // 1 byte
+    pub bump: u8,                // 1 byte
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Proposal {
+    pub multisig: Pubkey,        // 32 bytes
+    pub index: u64,              // 8 bytes
+    pub proposer: Pubkey,        // 32 bytes
+    pub program_id: Pubkey,      // 32 bytes
+    #[max_len(MAX_PROPOSAL_ACCOUNTS)]
+    pub accounts: Vec<ProposalAccount>, // 4 + 34 * 16 bytes
+    #[max_len(MAX_PROPOSAL_DATA_LEN)]
+    pub data: Vec<u8>,           // 4 + 512 bytes
+    #[max_len(MAX_MULTISIG_OWNERS)]
+    pub approvals: Vec<bool>,    // 4 + 10 bytes, indexed like the owners
+    pub owner_set_seqno: u32,    // 4 bytes
+    pub executed: bool,          // 1 byte
+    pub created_at: i64,         // 8 bytes
+    pub bump: u8,                // 1 byte
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct ProposalAccount {
+    pub pubkey: Pubkey,          // 32 bytes
+    pub is_signer: bool,         // 1 byte
+    pub is_writable: bool,       // 1 byte
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct AssetTypeConfig {
+    #[max_len(MAX_ASSET_TYPE_LEN)]
+    pub asset_type: String,      // 4 + up to 32 bytes, sized to the type at creation
+    pub max_ltv: u8,             // 1 byte, percent
+    pub haircut_bps: u16,        // 2 bytes
+    pub max_duration: i64,       // 8 bytes, seconds
+    pub bump: u8,                // 1 byte
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct JurisdictionConfig {
+    pub jurisdiction: [u8; 2],   // 2 bytes, ISO 3166-1 alpha-2 country code
+    #[max_len(MAX_JURISDICTION_ASSET_TYPES, MAX_ASSET_TYPE_LEN)]
+    pub allowed_asset_types: Vec<String>, // 4 + 8 * (4 + 32) bytes, empty allows every type
+    pub max_ltv: u8,             // 1 byte, percent, 0 if no override
+    pub transfer_restriction: TransferRestriction, // 1 byte
+    pub bump: u8,                // 1 byte
+}
+
+impl JurisdictionConfig {
+    pub fn allows_asset_type(&self, asset_type: &str) -> bool {
+        self.allowed_asset_types.is_empty()
+            || self.allowed_asset_types.iter().any(|allowed| allowed == asset_type)
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct AssetOracle {
+    pub asset: Pubkey,           // 32 bytes
+    pub aggregator: Pubkey,      // 32 bytes
+    pub max_staleness: i64,      // 8 bytes, seconds since the round opened
+    pub max_variance_bps: u16,   // 2 bytes, std deviation relative to result
+    pub use_switchboard: bool,   // 1 byte, enables the Switchboard refresh
+    pub bump: u8,                // 1 byte
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct AcceptedMint {
+    pub mint: Pubkey,            // 32 bytes
+    pub price_feed: Pubkey,      // 32 bytes, Switchboard aggregator quoting the mint
+    pub max_staleness: i64,      // 8 bytes, seconds since the round opened
+    pub max_variance_bps: u16,   // 2 bytes, std deviation relative to result
+    pub haircut_bps: u16,        // 2 bytes, extra charged when paying in this mint
+    pub enabled: bool,           // 1 byte, accepted as payment
+    pub bump: u8,                // 1 byte
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct BenchmarkFeed {
+    pub feed: Pubkey,            // 32 bytes, Switchboard aggregator quoting the rate in percent
+    pub max_staleness: i64,      // 8 bytes, seconds since the round opened
+    pub max_variance_bps: u16,   // 2 bytes, std deviation relative to result
+    pub bump: u8,                // 1 byte
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Loan {
+    pub borrower: Pubkey,        // 32 bytes
+    pub asset: Pubkey,           // 32 bytes
+    pub principal: u64,          // 8 bytes
+    pub interest_rate: u64,      // 8 bytes
+    pub start_time: i64,         // 8 bytes
+    pub end_time: i64,           // 8 bytes
+    pub status: LoanStatus,      // 1 byte
+    pub risk_score_at_creation: u8, // 1 byte
+    pub bump: u8,                // 1 byte
+    pub forborne_seconds: i64,   // 8 bytes, completed forbearance windows
+    pub forbearance_start: i64,  // 8 bytes
+    pub forbearance_end: i64,    // 8 bytes
+    pub modification_count: u16, // 2 bytes
+    pub lender: Pubkey,          // 32 bytes, default until approved
+    pub request_expiry: i64,     // 8 bytes
+    pub repayment_mint: Pubkey,  // 32 bytes
+    pub supplemental_asset: Pubkey, // 32 bytes, top-up collateral or default
+    pub written_off_amount: u64, // 8 bytes, debt booked as a loss
+    pub interest_mode: InterestMode, // 1 byte
+    pub disputed: bool,          // 1 byte, pauses liquidation while set
+    pub dispute_raised_by: Pubkey, // 32 bytes, default if never disputed
+    pub dispute_raised_at: i64,  // 8 bytes
+    pub dispute_outcome: Option<DisputeOutcome>, // 1 + 1 bytes, final ruling
+    pub dispute_resolved_at: i64, // 8 bytes
+    pub late_fees: u64,          // 8 bytes, as of the last state crank
+    pub liquidation_eligible: bool, // 1 byte, as of the last state crank
+    pub liquidator: Pubkey,      // 32 bytes, default until liquidated
+    pub liquidation_debt: u64,   // 8 bytes, outstanding debt when liquidated
+    pub referral: Pubkey,        // 32 bytes, referral account or default
+    pub rollover_count: u16,     // 2 bytes
+    pub product: LoanProduct,    // 1 byte, repayment structure
+    pub floating_rate: Option<FloatingRate>, // 1 + 56 bytes, None for fixed-rate loans
+    pub interest_checkpoint: u64, // 8 bytes, interest banked at superseded rates
+    pub checkpoint_seconds: i64, // 8 bytes, interest-bearing seconds the checkpoint covers
+    pub last_repriced_at: i64,   // 8 bytes, 0 until first repriced
+    pub collateral_rules: CollateralRules, // 4 bytes, limits the asset was lent against
+    pub supplemental_rules: CollateralRules, // 4 bytes, limits of the top-up asset, default without one
+    pub principal_repaid: u64,   // 8 bytes, paid down by installments
+}
+
+impl Asset {
+    /// Account size for an asset with these identifiers. They never change, so
+    /// only their actual length is reserved; the metadata URI can be updated
+    /// later and always gets its maximum.
+    pub fn space(asset_id: &str, asset_type: &str) -> usize {
+        8 + Asset::INIT_SPACE
+            - MAX_ASSET_ID_LEN.saturating_sub(asset_id.len())
+            - MAX_ASSET_TYPE_LEN.saturating_sub(asset_type.len())
+    }
+
+    /// Whether the circuit breaker is blocking origination and liquidation.
+    pub fn is_risk_frozen(&self, now: i64) -> bool {
+        now < self.risk_frozen_until
+    }
+
+    /// Whether the score is older than `max_risk_age` seconds (0 never is).
+    pub fn is_risk_stale(&self, now: i64, max_risk_age: i64) -> bool {
+        max_risk_age > 0 && now - self.last_risk_update > max_risk_age
+    }
+
+    /// Whether any loan holds a lien on the asset. `active_loans` counts every
+    /// request, open loan and top-up pledge, so no loan accounts need scanning.
+    pub fn is_encumbered(&self) -> bool {
+        self.active_loans > 0
+    }
+}
+
+impl AssetTypeConfig {
+    pub fn space(asset_type: &str) -> usize {
+        8 + AssetTypeConfig::INIT_SPACE - MAX_ASSET_TYPE_LEN.saturating_sub(asset_type.len())
+    }
+}
+
+// One per rollover, preserving the terms the successor replaced
+#[account]
+#[derive(InitSpace)]
+pub struct LoanRollover {
+    pub loan: Pubkey,            // 32 bytes
+    pub index: u16,              // 2 bytes
+    pub rolled_at: i64,          // 8 bytes
+    pub previous_principal: u64, // 8 bytes
+    pub previous_end_time: i64,  // 8 bytes
+    pub capitalized: u64,        // 8 bytes, unpaid interest and late fees added to principal
+    pub new_principal: u64,      // 8 bytes
+    pub new_end_time: i64,       // 8 bytes
+    pub interest_rate: u64,      // 8 bytes, basis points
+    pub fee: u64,                // 8 bytes, paid to the protocol treasury
+    pub bump: u8,                // 1 byte
+}
+
+impl LoanModification {
+    pub fn space(reason: &str) -> usize {
+        8 + LoanModification::INIT_SPACE - MAX_REASON_LEN.saturating_sub(reason.len())
+    }
+}
+
+impl Loan {
+    /// Principal not yet paid down by installments.
+    pub fn remaining_principal(&self) -> u64 {
+        self.principal.saturating_sub(self.principal_repaid)
+    }
+
+    /// Move to `next`, rejecting transitions the lifecycle does not allow.
+    pub fn transition(&mut self, next: LoanStatus) -> Result<()> {
+        require!(self.status.can_transition_to(next), ErrorCode::InvalidStatusTransition);
+        self.status = next;
+        Ok(())
+    }
+
+    /// Restart the loan as a fresh term from `now`, capitalizing everything
+    /// owed. Returns the new principal.
+    pub fn roll_over(&mut self, now: i64, interest_rate: u64, duration: i64) -> Result<u64> {
+        self.principal = outstanding_debt(self, now)?;
+        self.principal_repaid = 0;
+        self.interest_rate = interest_rate;
+        self.interest_checkpoint = 0;
+        self.checkpoint_seconds = 0;
+        self.start_time = now;
+        self.end_time = now.checked_add(duration).ok_or(ErrorCode::MathOverflow)?;
+        self.forborne_seconds = 0;
+        self.forbearance_start = 0;
+        self.forbearance_end = 0;
+        self.late_fees = 0;
+        self.liquidation_eligible = false;
+        self.transition(LoanStatus::Active)?;
+        Ok(self.principal)
+    }
+
+    /// Record an installment: `principal_part` comes off the balance and the
+    /// interest it carried settles everything accrued up to its due date, after
+    /// which interest runs on the reduced balance.
+    pub fn apply_installment(&mut self, principal_part: u64, due_date: i64) -> Result<()> {
+        self.principal_repaid = self.principal_repaid
+            .checked_add(principal_part)
+            .filter(|repaid| *repaid <= self.principal)
+            .ok_or(ErrorCode::MathOverflow)?;
+        self.interest_checkpoint = 0;
+        self.checkpoint_seconds = interest_bearing_seconds(self, due_date).max(self.checkpoint_seconds);
+        Ok(())
+    }
+
+    /// Bank the interest accrued up to `now` so the rate can change without
+    /// repricing the past.
+    pub fn checkpoint_interest(&mut self, now: i64) -> Result<()> {
+        self.interest_checkpoint = loan_interest(self, now)?;
+        self.checkpoint_seconds = interest_bearing_seconds(self, now).max(self.checkpoint_seconds);
+        Ok(())
+    }
+}
+
+/// Benchmark + spread pricing of a floating-rate loan, in basis points.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct FloatingRate {
+    pub benchmark: Pubkey,       // 32 bytes, Switchboard feed registered with add_benchmark_feed
+    pub spread_bps: u64,         // 8 bytes
+    pub floor_bps: u64,          // 8 bytes
+    pub cap_bps: u64,            // 8 bytes
+}
+
+impl FloatingRate {
+    /// Benchmark plus spread, held between the floor and the cap.
+    pub fn rate_for(&self, benchmark_bps: i64) -> u64 {
+        let rate = benchmark_bps as i128 + self.spread_bps as i128;
+        rate.clamp(self.floor_bps as i128, self.cap_bps as i128) as u64
+    }
+}
+
+/// Asset type and jurisdiction limits a loan's collateral was accepted under,
+/// so health checks apply them without passing the config accounts.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace)]
+pub struct CollateralRules {
+    pub max_ltv: u8,             // 1 byte, asset type ceiling
+    pub haircut_bps: u16,        // 2 bytes
+    pub jurisdiction_max_ltv: u8, // 1 byte, 0 when uncapped
+}
+
+impl CollateralRules {
+    pub fn new(type_config: &AssetTypeConfig, rules: Option<&JurisdictionConfig>) -> Self {
+        Self {
+            max_ltv: type_config.max_ltv,
+            haircut_bps: type_config.haircut_bps,
+            jurisdiction_max_ltv: rules.map_or(0, |rules| rules.max_ltv),
+        }
+    }
+
+    /// Maximum loan against `valuation` at `risk_score`: the risk-score table
+    /// capped by the asset type's ceiling and any jurisdiction override,
+    /// applied to the value left after the haircut.
+    pub fn max_loan(&self, valuation: u64, risk_score: u8) -> u64 {
+        let mut max_ltv = max_ltv_for_risk(risk_score).min(self.max_ltv as u64);
+        if self.jurisdiction_max_ltv > 0 {
+            max_ltv = max_ltv.min(self.jurisdiction_max_ltv as u64);
+        }
+        (haircut_value(valuation, self.haircut_bps) * max_ltv as u128 / 100) as u64
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum LoanStatus {
+    Pending,     // requested, awaiting approval
+    Active,
+    Defaulted,   // unpaid past maturity plus the default grace period
+    Liquidating, // health factor below 1, open to liquidation
+    Repaid,
+    Liquidated,
+    WrittenOff,
+    Overdue,     // past maturity but still inside the default grace period
+}
+
+impl LoanStatus {
+    /// Disbursed and not yet settled; the collateral is encumbered.
+    pub fn is_open(self) -> bool {
+        matches!(
+            self,
+            LoanStatus::Active | LoanStatus::Overdue | LoanStatus::Defaulted | LoanStatus::Liquidating
+        )
+    }
+
+    /// Settled one way or another; nothing further is owed on the loan.
+    pub fn is_closed(self) -> bool {
+        matches!(self, LoanStatus::Repaid | LoanStatus::Liquidated | LoanStatus::WrittenOff)
+    }
+
+    pub fn can_transition_to(self, next: LoanStatus) -> bool {
+        use LoanStatus::*;
+        match (self, next) {
+            (Pending, Active) => true,
+            // Health and default checks move open loans between open states
+            (from, to) if from.is_open() && to.is_open() => true,
+            (from, Repaid | Liquidated) => from.is_open(),
+            (Defaulted | Liquidated, WrittenOff) => true,
+            _ => false,
+        }
+    }
+}
+
+/// How a loan's principal is repaid.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum LoanProduct {
+    Bullet,       // principal and interest in one payment at maturity
+    InterestOnly, // periodic interest, principal with the last installment
+    Amortizing,   // equal principal each installment plus interest on the balance
+}
+
+impl LoanProduct {
+    /// Principal repaid by installment `index` out of `count`.
+    pub fn installment_principal(self, principal: u64, count: usize, index: usize) -> u64 {
+        match self {
+            LoanProduct::Amortizing => installment_principal(principal, count, index),
+            LoanProduct::Bullet | LoanProduct::InterestOnly if index + 1 == count => principal,
+            LoanProduct::Bullet | LoanProduct::InterestOnly => 0,
+        }
+    }
+}
+
+/// Who may move ownership of assets placed in a jurisdiction.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum TransferRestriction {
+    Unrestricted,
+    ComplianceApproval, // the compliance authority co-signs each transfer
+    Prohibited,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum InterestMode {
+    Simple,
+    DailyCompounding,
+    Continuous,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum DisputeOutcome {
+    FavorBorrower,
+    FavorLender,
+    Settled,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct RepaymentSchedule {
+    pub loan: Pubkey,            // 32 bytes
+    pub interval: i64,           // 8 bytes
+    #[max_len(MAX_INSTALLMENTS)]
+    pub installments: Vec<Installment>, // 4 + 24 * 24 bytes
+    pub paid_count: u8,          // 1 byte
+    pub missed_count: u8,        // 1 byte
+    pub late_payments: u8,       // 1 byte
+    pub delinquent: bool,        // 1 byte
+    pub bump: u8,                // 1 byte
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct Installment {
+    pub due_date: i64,           // 8 bytes
+    pub amount: u64,             // 8 bytes
+    pub paid_at: i64,            // 8 bytes, 0 while unpaid
+}
+
+/// Outcome of seizing a liquidated loan's collateral.
+#[account]
+#[derive(InitSpace)]
+pub struct CollateralSettlement {
+    pub loan: Pubkey,            // 32 bytes
+    pub asset: Pubkey,           // 32 bytes
+    pub previous_owner: Pubkey,  // 32 bytes
+    pub recipient: Pubkey,       // 32 bytes, new owner of the asset
+    pub debt: u64,               // 8 bytes, outstanding when liquidated
+    pub collateral_value: u64,   // 8 bytes, asset valuation when seized
+    pub surplus: u64,            // 8 bytes, value above the debt, owed to the borrower
+    pub deficiency: u64,         // 8 bytes, debt the collateral did not cover
+    pub settled_at: i64,         // 8 bytes
+    pub bump: u8,                // 1 byte
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct LoanModification {
+    pub loan: Pubkey,            // 32 bytes
+    pub index: u16,              // 2 bytes
+    pub granted_by: Pubkey,      // 32 bytes
+    pub granted_at: i64,         // 8 bytes
+    pub interest_paused_until: i64, // 8 bytes, 0 if interest was not paused
+    pub maturity_extension: i64, // 8 bytes
+    pub new_end_time: i64,       // 8 bytes
+    #[max_len(MAX_REASON_LEN)]
+    pub reason: String,          // 4 + up to 64 bytes, sized to the reason given
+    pub bump: u8,                // 1 byte
+}
+
+#[event]
+pub struct LoanWrittenOff {
+    pub loan: Pubkey,
+    pub asset: Pubkey,
+    pub amount: u64,
+    pub written_off_by: Pubkey,
+    pub written_off_at: i64,
+}
+
+#[event]
+pub struct LoanRepriced {
+    pub loan: Pubkey,
+    pub benchmark_bps: i64,
+    pub previous_rate: u64,
+    pub interest_rate: u64,
+    pub repriced_at: i64,
+}
+
+#[event]
+pub struct RepaidInAcceptedMint {
+    pub loan: Pubkey,
+    pub payment_mint: Pubkey,
+    pub debt: u64,
+    pub amount_paid: u64,
+    pub haircut_bps: u16,
+}
+
+#[event]
+pub struct RiskCircuitBreakerTripped {
+    pub asset: Pubkey,
+    pub previous_score: u8,
+    pub risk_score: u8,
+    pub frozen_until: i64,
+}
+
+#[event]
+pub struct OracleSlashed {
+    pub oracle: Pubkey,
+    pub report: Pubkey,
+    pub challenger: Pubkey,
+    pub amount: u64,
+    pub slashed_at: i64,
+}
+
+#[event]
+pub struct CollateralSeized {
+    pub loan: Pubkey,
+    pub asset: Pubkey,
+    pub previous_owner: Pubkey,
+    pub recipient: Pubkey,
+    pub surplus: u64,
+    pub deficiency: u64,
+    pub settled_at: i64,
+}
+
+#[event]
+pub struct AssetTransferred {
+    pub asset: Pubkey,
+    pub previous_owner: Pubkey,
+    pub new_owner: Pubkey,
+    pub transferred_at: i64,
+}
+
+#[event]
+pub struct LoanStateCranked {
+    pub loan: Pubkey,
+    pub previous_status: LoanStatus,
+    pub status: LoanStatus,
+    pub late_fees: u64,
+    pub liquidation_eligible: bool,
+    pub cranked_at: i64,
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Asset is not active")]
+    AssetInactive,
+    #[msg("Invalid risk score")]
+    InvalidRiskScore,
+    #[msg("Loan amount exceeds maximum LTV")]
+    LoanTooHigh,
+    #[msg("Loan is not active")]
+    LoanInactive,
+    #[msg("Not eligible for liquidation")]
+    NotEligibleForLiquidation,
+    #[msg("Arithmetic overflow")]
+    MathOverflow,
+    #[msg("Asset still has active loans")]
+    AssetHasActiveLoans,
+    #[msg("Asset is already active")]
+    AssetAlreadyActive,
+    #[msg("Signer is not authorized for this action")]
+    Unauthorized,
+    #[msg("Asset account does not match the loan")]
+    LoanAssetMismatch,
+    #[msg("Borrower does not match the loan")]
+    BorrowerMismatch,
+    #[msg("Invalid asset type configuration")]
+    InvalidAssetTypeConfig,
+    #[msg("Loan duration is outside the asset type limits")]
+    InvalidLoanDuration,
+    #[msg("Invalid forbearance terms")]
+    InvalidForbearance,
+    #[msg("A forbearance window is already in effect")]
+    ForbearanceActive,
+    #[msg("Invalid number of installments")]
+    InvalidInstallmentCount,
+    #[msg("All installments have been paid")]
+    ScheduleComplete,
+    #[msg("Loan is not awaiting approval")]
+    LoanNotPending,
+    #[msg("Loan request has expired")]
+    LoanRequestExpired,
+    #[msg("Delegate must be a key other than the owner")]
+    InvalidDelegate,
+    #[msg("Metadata URI is too long")]
+    MetadataUriTooLong,
+    #[msg("Token account mint does not match the loan's repayment mint")]
+    RepaymentMintMismatch,
+    #[msg("Invalid oracle configuration")]
+    InvalidOracleConfig,
+    #[msg("Oracle account does not match the asset's configuration")]
+    OracleMismatch,
+    #[msg("Account is not a Switchboard aggregator")]
+    InvalidOracleAccount,
+    #[msg("Switchboard is not the selected risk source for this asset")]
+    OracleNotSelected,
+    #[msg("Oracle round is too old")]
+    StaleOracleRound,
+    #[msg("Oracle round variance exceeds the configured limit")]
+    OracleVarianceTooHigh,
+    #[msg("Loan already has top-up collateral, or the asset cannot be used")]
+    InvalidTopUp,
+    #[msg("The loan's top-up collateral account must be provided")]
+    SupplementalAssetMissing,
+    #[msg("Loan is still open")]
+    LoanStillOpen,
+    #[msg("Loan must be liquidated and its seized collateral short of the debt to be written off")]
+    NotEligibleForWriteOff,
+    #[msg("Arbitrator must be a valid key")]
+    InvalidArbitrator,
+    #[msg("Loan is under dispute")]
+    LoanDisputed,
+    #[msg("Loan has already been disputed")]
+    DisputeAlreadyRaised,
+    #[msg("Loan is not under dispute")]
+    LoanNotDisputed,
+    #[msg("Loan cannot move to that status from its current one")]
+    InvalidStatusTransition,
+    #[msg("Invalid multisig owners or threshold")]
+    InvalidMultisigConfig,
+    #[msg("Signer is not a multisig owner")]
+    NotMultisigOwner,
+    #[msg("Proposed instruction is too large")]
+    InvalidProposal,
+    #[msg("Proposal has already been executed")]
+    ProposalAlreadyExecuted,
+    #[msg("Multisig owners changed since the proposal was created")]
+    StaleProposal,
+    #[msg("Proposal does not have enough approvals")]
+    ThresholdNotMet,
+    #[msg("Loan is already in the liquidation queue")]
+    AlreadyQueued,
+    #[msg("Liquidation queue is full")]
+    LiquidationQueueFull,
+    #[msg("Accounts for a queued loan were not provided")]
+    CrankAccountsMissing,
+    #[msg("Asset identifier or type is too long")]
+    AssetFieldTooLong,
+    #[msg("Token account or mint is not a valid SPL Token or Token-2022 account")]
+    InvalidTokenAccount,
+    #[msg("Repayment mint uses a Token-2022 extension that loans cannot be settled in")]
+    UnsupportedMintExtension,
+    #[msg("Reserve factor exceeds the maximum")]
+    InvalidReserveFactor,
+    #[msg("Revenue account and vault for the repayment mint are required")]
+    RevenueAccountMissing,
+    #[msg("No protocol revenue to claim")]
+    NothingToClaim,
+    #[msg("Address is blacklisted")]
+    Blacklisted,
+    #[msg("Address is already blacklisted")]
+    AlreadyBlacklisted,
+    #[msg("Address is not blacklisted")]
+    NotBlacklisted,
+    #[msg("Blacklist is full")]
+    BlacklistFull,
+    #[msg("Compliance authority cannot be the default pubkey")]
+    InvalidComplianceAuthority,
+    #[msg("Custody attestation has expired")]
+    CustodyAttestationExpired,
+    #[msg("Attestation expiry must be in the future")]
+    InvalidAttestationExpiry,
+    #[msg("Risk oracle cannot be the default pubkey")]
+    InvalidRiskOracle,
+    #[msg("Risk report needs a positive id and at least one entry")]
+    InvalidRiskReport,
+    #[msg("Merkle proof does not match the risk report root")]
+    InvalidRiskProof,
+    #[msg("Risk report is too old or older than the asset's last applied report")]
+    StaleRiskReport,
+    #[msg("Asset is encumbered by a loan")]
+    AssetEncumbered,
+    #[msg("New owner must differ from the current owner")]
+    InvalidNewOwner,
+    #[msg("Loan has not been liquidated")]
+    LoanNotLiquidated,
+    #[msg("Oracle bond is below the minimum")]
+    InsufficientOracleBond,
+    #[msg("Bond amount must be positive and within the bonded balance")]
+    InvalidBondAmount,
+    #[msg("Oracle bond is locked by an open dispute or a recent report")]
+    OracleBondLocked,
+    #[msg("Dispute window has closed")]
+    DisputeWindowClosed,
+    #[msg("Oracle has already voted on this dispute")]
+    AlreadyVoted,
+    #[msg("Dispute has reached its maximum number of voters")]
+    DisputeVotersFull,
+    #[msg("Dispute is already resolved")]
+    DisputeResolved,
+    #[msg("Dispute has not reached an overturn quorum")]
+    DisputeQuorumNotReached,
+    #[msg("Dispute voting is still open or the report was overturned")]
+    DisputeVotingOpen,
+    #[msg("Risk report was overturned")]
+    ReportOverturned,
+    #[msg("Threshold must be below 100 with a positive cool-down, or 0 to disable")]
+    InvalidCircuitBreaker,
+    #[msg("Asset is frozen by the risk circuit breaker")]
+    AssetRiskFrozen,
+    #[msg("Asset is not frozen by the risk circuit breaker")]
+    AssetNotFrozen,
+    #[msg("Mint is not accepted for repayment")]
+    MintNotAccepted,
+    #[msg("Repayment haircut is above the maximum")]
+    InvalidHaircut,
+    #[msg("Oracle price must be positive")]
+    InvalidOraclePrice,
+    #[msg("Lender is not on the lender whitelist")]
+    LenderNotWhitelisted,
+    #[msg("Lender membership has expired")]
+    LenderMembershipExpired,
+    #[msg("Membership expiry must be in the future")]
+    InvalidMembershipExpiry,
+    #[msg("Origination fee or referral share is above the maximum")]
+    InvalidOriginationFee,
+    #[msg("Borrowers cannot refer their own loans")]
+    SelfReferral,
+    #[msg("Referral account or vault is missing or does not match the loan")]
+    ReferralAccountMissing,
+    #[msg("Only active or overdue, undisputed loans without a repayment schedule can be rolled over")]
+    LoanNotRollable,
+    #[msg("Bullet loans are repaid in one payment and take no schedule")]
+    ScheduleNotSupported,
+    #[msg("Max risk age cannot be negative")]
+    InvalidRiskAge,
+    #[msg("Risk score is older than the protocol allows for new loans")]
+    StaleRiskScore,
+    #[msg("Jurisdiction must be a two-letter uppercase country code")]
+    InvalidJurisdiction,
+    #[msg("Invalid jurisdiction rules")]
+    InvalidJurisdictionConfig,
+    #[msg("Asset's jurisdiction config is missing")]
+    JurisdictionConfigMissing,
+    #[msg("Asset type is not allowed in the asset's jurisdiction")]
+    AssetTypeNotAllowedInJurisdiction,
+    #[msg("Asset's jurisdiction restricts this transfer")]
+    TransferRestricted,
+    #[msg("Asset type is reserved for portfolios")]
+    ReservedAssetType,
+    #[msg("Portfolios bundle 2 to 10 distinct assets from one jurisdiction")]
+    InvalidPortfolio,
+    #[msg("Asset is bundled into a portfolio")]
+    AssetBundled,
+    #[msg("Remaining accounts must be the portfolio's members in bundling order")]
+    PortfolioMembersMismatch,
+    #[msg("Floating rate needs a benchmark and an interest rate between its floor and cap")]
+    InvalidFloatingRate,
+    #[msg("Loan has a fixed rate")]
+    NotFloatingRate,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRINCIPAL: u64 = 1_000_000;
+    const DAY: i64 = SECONDS_PER_DAY;
+    const YEAR: i64 = SECONDS_PER_YEAR;
+
+    // (rate bps, elapsed seconds, simple, daily compounding, continuous) with
+    // reference values computed in f64 and floored
+    const REFERENCE: [(u64, i64, u64, u64, u64); 5] = [
+        (1_000, YEAR, 100_000, 105_155, 105_170),
+        (500, 30 * DAY, 4_109, 4_117, 4_118),
+        (1_200, 2 * YEAR, 240_000, 271_199, 271_249),
+        (2_500, 3 * YEAR, 750_000, 1_116_456, 1_117_000),
+        (0, YEAR, 0, 0, 0),
+    ];
+
+    fn assert_close(actual: u64, expected: u64, label: &str) {
+        assert!(
+            actual.abs_diff(expected) <= 1,
+            "{}: got {}, expected {}",
+            label,
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn matches_reference_values() {
+        for (rate, elapsed, simple, daily, continuous) in REFERENCE {
+            let label = format!("{} bps over {}s", rate, elapsed);
+            assert_close(accrued_interest(PRINCIPAL, rate, elapsed, InterestMode::Simple).unwrap(), simple, &label);
+            assert_close(accrued_interest(PRINCIPAL, rate, elapsed, InterestMode::DailyCompounding).unwrap(), daily, &label);
+            assert_close(accrued_interest(PRINCIPAL, rate, elapsed, InterestMode::Continuous).unwrap(), continuous, &label);
+        }
+    }
+
+    #[test]
+    fn no_interest_without_elapsed_time() {
+        for mode in [InterestMode::Simple, InterestMode::DailyCompounding, InterestMode::Continuous] {
+            assert_eq!(accrued_interest(PRINCIPAL, 1_000, 0, mode).unwrap(), 0);
+            assert_eq!(accrued_interest(PRINCIPAL, 1_000, -DAY, mode).unwrap(), 0);
+        }
+    }
+
+    #[test]
+    fn partial_day_accrues_simply_when_compounding_daily() {
+        for elapsed in [1, 3_600, DAY - 1] {
+            assert_eq!(
+                accrued_interest(PRINCIPAL, 1_000, elapsed, InterestMode::DailyCompounding).unwrap(),
+                accrued_interest(PRINCIPAL, 1_000, elapsed, InterestMode::Simple).unwrap(),
+            );
+        }
+    }
+
+    #[test]
+    fn compounding_never_accrues_less_than_simple() {
+        for elapsed in [DAY, 7 * DAY, 90 * DAY, YEAR, 5 * YEAR] {
+            let simple = accrued_interest(PRINCIPAL, 1_500, elapsed, InterestMode::Simple).unwrap();
+            let daily = accrued_interest(PRINCIPAL, 1_500, elapsed, InterestMode::DailyCompounding).unwrap();
+            let continuous = accrued_interest(PRINCIPAL, 1_500, elapsed, InterestMode::Continuous).unwrap();
+            assert!(simple <= daily && daily <= continuous, "{}s: {} {} {}", elapsed, simple, daily, continuous);
+        }
+    }
+
+    #[test]
+    fn fixed_point_helpers() {
+        assert_eq!(mul_wad(2 * WAD, 3 * WAD), Some(6 * WAD));
+        assert_eq!(pow_wad(2 * WAD, 5), Some(32 * WAD));
+        assert_eq!(pow_wad(WAD + WAD / 10, 3), Some(1_331_000_000_000_000_000));
+        assert_eq!(pow_wad(5 * WAD, 0), Some(WAD));
+        assert_eq!(exp_wad(0), Some(WAD));
+        // e = 2.718281828459045235...
+        assert!(exp_wad(WAD).unwrap().abs_diff(2_718_281_828_459_045_235) < 100);
+    }
+
+    #[test]
+    fn loan_status_transitions() {
+        use LoanStatus::*;
+        let all = [Pending, Active, Defaulted, Liquidating, Repaid, Liquidated, WrittenOff, Overdue];
+        let allowed = [
+            (Pending, Active),
+            (Active, Active), (Active, Defaulted), (Active, Liquidating), (Active, Repaid), (Active, Liquidated),
+            (Active, Overdue),
+            (Defaulted, Active), (Defaulted, Defaulted), (Defaulted, Liquidating), (Defaulted, Repaid),
+            (Defaulted, Liquidated), (Defaulted, WrittenOff), (Defaulted, Overdue),
+            (Liquidating, Active), (Liquidating, Defaulted), (Liquidating, Liquidating), (Liquidating, Repaid),
+            (Liquidating, Liquidated), (Liquidating, Overdue),
+            (Liquidated, WrittenOff),
+            (Overdue, Active), (Overdue, Defaulted), (Overdue, Liquidating), (Overdue, Repaid), (Overdue, Liquidated),
+            (Overdue, Overdue),
+        ];
+        for from in all {
+            for to in all {
+                assert_eq!(
+                    from.can_transition_to(to),
+                    allowed.contains(&(from, to)),
+                    "{:?} -> {:?}",
+                    from,
+                    to
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn overflow_is_reported() {
+        for mode in [InterestMode::Simple, InterestMode::DailyCompounding, InterestMode::Continuous] {
+            assert!(accrued_interest(u64::MAX, u64::MAX, i64::MAX, mode).is_err());
+        }
+        assert!(accrued_interest(u64::MAX, 10_000, 10 * YEAR, InterestMode::Continuous).is_err());
+    }
+
+    #[test]
+    fn asset_history_wraps_oldest_first() {
+        let mut history: AssetHistory = bytemuck::Zeroable::zeroed();
+        assert_eq!(std::mem::size_of::<AssetHistory>(), 48 + 24 * ASSET_HISTORY_LEN);
+
+        for i in 0..(ASSET_HISTORY_LEN + 3) {
+            history.record(i as i64, 1_000, (i % 100) as u8, HISTORY_SOURCE_RISK_AUTHORITY);
+        }
+
+        assert_eq!(history.len as usize, ASSET_HISTORY_LEN);
+        let timestamps: Vec<i64> = history.iter().map(|entry| entry.timestamp).collect();
+        assert_eq!(timestamps.first(), Some(&3));
+        assert_eq!(timestamps.last(), Some(&(ASSET_HISTORY_LEN as i64 + 2)));
+        assert!(timestamps.windows(2).all(|pair| pair[0] + 1 == pair[1]));
+    }
+
+    fn serialized_len<T: AnchorSerialize>(account: &T) -> usize {
+        8 + account.try_to_vec().unwrap().len()
+    }
+
+    #[test]
+    fn asset_space_follows_its_identifiers() {
+        for (asset_id, asset_type) in [("a".repeat(MAX_ASSET_ID_LEN), "t".repeat(MAX_ASSET_TYPE_LEN)), ("a-1".into(), "invoice".into())] {
+            let asset = Asset {
+                asset_id: asset_id.clone(),
+                asset_type: asset_type.clone(),
+                valuation: u64::MAX,
+                metadata_uri: "u".repeat(MAX_METADATA_URI_LEN),
+                owner: Pubkey::new_unique(),
+                is_active: true,
+                risk_score: 100,
+                bump: 255,
+                active_loans: u32::MAX,
+                delegate: Pubkey::new_unique(),
+                valuation_refresh_requested_at: i64::MAX,
+                last_risk_report: u64::MAX,
+                risk_frozen_until: i64::MAX,
+                breaker_confirmers: vec![Pubkey::new_unique(); REPORT_DISPUTE_QUORUM as usize],
+                last_risk_update: i64::MAX,
+                jurisdiction: *b"US",
+                portfolio: Pubkey::new_unique(),
+            };
+            assert_eq!(serialized_len(&asset), Asset::space(&asset_id, &asset_type));
+        }
+    }
+
+    #[test]
+    fn loan_accounts_fit_their_space() {
+        let loan = Loan {
+            borrower: Pubkey::new_unique(),
+            asset: Pubkey::new_unique(),
+            principal: u64::MAX,
+            interest_rate: u64::MAX,
+            start_time: i64::MAX,
+            end_time: i64::MAX,
+            status: LoanStatus::WrittenOff,
+            risk_score_at_creation: 100,
+            bump: 255,
+            forborne_seconds: i64::MAX,
+            forbearance_start: i64::MAX,
+            forbearance_end: i64::MAX,
+            modification_count: u16::MAX,
+            lender: Pubkey::new_unique(),
+            request_expiry: i64::MAX,
+            repayment_mint: Pubkey::new_unique(),
+            supplemental_asset: Pubkey::new_unique(),
+            written_off_amount: u64::MAX,
+            interest_mode: InterestMode::Continuous,
+            disputed: true,
+            dispute_raised_by: Pubkey::new_unique(),
+            dispute_raised_at: i64::MAX,
+            dispute_outcome: Some(DisputeOutcome::Settled),
+            dispute_resolved_at: i64::MAX,
+            late_fees: u64::MAX,
+            liquidation_eligible: true,
+            liquidator: Pubkey::new_unique(),
+            liquidation_debt: u64::MAX,
+            referral: Pubkey::new_unique(),
+            rollover_count: u16::MAX,
+            product: LoanProduct::Amortizing,
+            floating_rate: Some(FloatingRate {
+                benchmark: Pubkey::new_unique(),
+                spread_bps: u64::MAX,
+                floor_bps: u64::MAX,
+                cap_bps: u64::MAX,
+            }),
+            interest_checkpoint: u64::MAX,
+            checkpoint_seconds: i64::MAX,
+            last_repriced_at: i64::MAX,
+            collateral_rules: CollateralRules { max_ltv: u8::MAX, haircut_bps: u16::MAX, jurisdiction_max_ltv: u8::MAX },
+            supplemental_rules: CollateralRules { max_ltv: u8::MAX, haircut_bps: u16::MAX, jurisdiction_max_ltv: u8::MAX },
+            principal_repaid: u64::MAX,
+        };
+        assert_eq!(serialized_len(&loan), 8 + Loan::INIT_SPACE);
+
+        let installment = Installment { due_date: i64::MAX, amount: u64::MAX, paid_at: i64::MAX };
+        let schedule = RepaymentSchedule {
+            loan: Pubkey::new_unique(),
+            interval: i64::MAX,
+            installments: vec![installment; MAX_INSTALLMENTS],
+            paid_count: u8::MAX,
+            missed_count: u8::MAX,
+            late_payments: u8::MAX,
+            delinquent: true,
+            bump: 255,
+        };
+        assert_eq!(serialized_len(&schedule), 8 + RepaymentSchedule::INIT_SPACE);
+
+        for reason in ["r".repeat(MAX_REASON_LEN), String::new()] {
+            let modification = LoanModification {
+                loan: Pubkey::new_unique(),
+                index: u16::MAX,
+                granted_by: Pubkey::new_unique(),
+                granted_at: i64::MAX,
+                interest_paused_until: i64::MAX,
+                maturity_extension: i64::MAX,
+                new_end_time: i64::MAX,
+                reason: reason.clone(),
+                bump: 255,
+            };
+            assert_eq!(serialized_len(&modification), LoanModification::space(&reason));
+        }
+    }
+
+    #[test]
+    fn admin_accounts_fit_their_space() {
+        let multisig = Multisig {
+            owners: vec![Pubkey::new_unique(); MAX_MULTISIG_OWNERS],
+            threshold: u8::MAX,
+            owner_set_seqno: u32::MAX,
+            proposal_count: u64::MAX,
+            signer_bump: 255,
+            bump: 255,
+        };
+        assert_eq!(serialized_len(&multisig), 8 + Multisig::INIT_SPACE);
+
+        let account = ProposalAccount { pubkey: Pubkey::new_unique(), is_signer: true, is_writable: true };
+        let proposal = Proposal {
+            multisig: Pubkey::new_unique(),
+            index: u64::MAX,
+            proposer: Pubkey::new_unique(),
+            program_id: Pubkey::new_unique(),
+            accounts: vec![account; MAX_PROPOSAL_ACCOUNTS],
+            data: vec![u8::MAX; MAX_PROPOSAL_DATA_LEN],
+            approvals: vec![true; MAX_MULTISIG_OWNERS],
+            owner_set_seqno: u32::MAX,
+            executed: true,
+            created_at: i64::MAX,
+            bump: 255,
+        };
+        assert_eq!(serialized_len(&proposal), 8 + Proposal::INIT_SPACE);
+
+        let queue = LiquidationQueue {
+            entries: vec![QueuedLiquidation { loan: Pubkey::new_unique(), enqueued_at: i64::MAX }; MAX_LIQUIDATION_QUEUE_LEN],
+            bump: 255,
+        };
+        assert_eq!(serialized_len(&queue), 8 + LiquidationQueue::INIT_SPACE);
+
+        let asset_type = "t".repeat(MAX_ASSET_TYPE_LEN);
+        let type_config = AssetTypeConfig {
+            asset_type: asset_type.clone(),
+            max_ltv: 100,
+            haircut_bps: u16::MAX,
+            max_duration: i64::MAX,
+            bump: 255,
+        };
+        assert_eq!(serialized_len(&type_config), AssetTypeConfig::space(&asset_type));
+
+        let rules = JurisdictionConfig {
+            jurisdiction: *b"DE",
+            allowed_asset_types: vec![asset_type; MAX_JURISDICTION_ASSET_TYPES],
+            max_ltv: 100,
+            transfer_restriction: TransferRestriction::Prohibited,
+            bump: 255,
+        };
+        assert_eq!(serialized_len(&rules), 8 + JurisdictionConfig::INIT_SPACE);
+
+        let portfolio = PortfolioAsset {
+            asset: Pubkey::new_unique(),
+            members: vec![Pubkey::new_unique(); MAX_PORTFOLIO_ASSETS],
+            bundled_at: i64::MAX,
+            bump: 255,
+        };
+        assert_eq!(serialized_len(&portfolio), 8 + PortfolioAsset::INIT_SPACE);
+    }
+
+    #[test]
+    fn transfer_fee_gross_up_delivers_net_amount() {
+        let fee = TransferFee { epoch: 0, maximum_fee: 5_000, basis_points: 150 };
+        for net in [0, 1, 99, 1_000_000, 10_000_000, u64::MAX / 20_000] {
+            let gross = fee.gross_amount(net).unwrap();
+            assert_eq!(gross - fee.fee(gross).unwrap(), net, "net {}", net);
+        }
+        // Large transfers hit the fee cap
+        assert_eq!(fee.gross_amount(1_000_000_000).unwrap(), 1_000_005_000);
+        assert_eq!(TransferFee { basis_points: 10_000, ..fee }.gross_amount(1), None);
+    }
+
+    #[test]
+    fn repayment_mint_reads_token_2022_extensions() {
+        let mut data = vec![0u8; MINT_LEN];
+        data[44] = 6;
+        data[45] = 1;
+        let plain = RepaymentMint::try_deserialize_unchecked(&mut data.as_slice()).unwrap();
+        assert_eq!(plain.decimals, 6);
+        assert!(plain.transfer_fees.is_none() && plain.unsupported_extension.is_none());
+
+        data.resize(TOKEN_ACCOUNT_LEN, 0);
+        data.push(ACCOUNT_TYPE_MINT);
+        let mut fee_config = vec![0u8; 108];
+        fee_config[72..90].copy_from_slice(&[&0u64.to_le_bytes()[..], &10u64.to_le_bytes()[..], &25u16.to_le_bytes()[..]].concat());
+        fee_config[90..108].copy_from_slice(&[&7u64.to_le_bytes()[..], &20u64.to_le_bytes()[..], &50u16.to_le_bytes()[..]].concat());
+        for (tag, value) in [(EXTENSION_TRANSFER_FEE_CONFIG, fee_config), (10, vec![0u8; 52])] {
+            data.extend_from_slice(&tag.to_le_bytes());
+            data.extend_from_slice(&(value.len() as u16).to_le_bytes());
+            data.extend_from_slice(&value);
+        }
+        let mint = RepaymentMint::try_deserialize_unchecked(&mut data.as_slice()).unwrap();
+        let (older, newer) = mint.transfer_fees.unwrap();
+        assert_eq!(older, TransferFee { epoch: 0, maximum_fee: 10, basis_points: 25 });
+        assert_eq!(newer, TransferFee { epoch: 7, maximum_fee: 20, basis_points: 50 });
+        assert_eq!(mint.gross_amount(1_000, 6).unwrap(), 1_003);
+        assert_eq!(mint.gross_amount(1_000, 7).unwrap(), 1_006);
+        assert!(mint.unsupported_extension.is_none());
+
+        data.extend_from_slice(&EXTENSION_TRANSFER_HOOK.to_le_bytes());
+        data.extend_from_slice(&64u16.to_le_bytes());
+        data.extend_from_slice(&[0u8; 64]);
+        let hooked = RepaymentMint::try_deserialize_unchecked(&mut data.as_slice()).unwrap();
+        assert_eq!(hooked.unsupported_extension, Some(EXTENSION_TRANSFER_HOOK));
+    }
+
+    #[test]
+    fn nft_metadata_is_borsh_data_v2() {
+        let asset = Asset {
+            asset_id: "deed-7".into(),
+            metadata_uri: "ipfs://x".into(),
+            ..asset(1, 50)
+        };
+        let expected = [
+            &6u32.to_le_bytes()[..], b"deed-7",
+            &3u32.to_le_bytes()[..], b"RWA",
+            &8u32.to_le_bytes()[..], b"ipfs://x",
+            &0u16.to_le_bytes()[..],
+            &[0, 0, 0],
+        ]
+        .concat();
+        assert_eq!(nft_metadata_data(&asset).unwrap(), expected);
+    }
+
+    #[test]
+    fn merkle_proofs_verify_against_sorted_pair_root() {
+        let node = |a: [u8; 32], b: [u8; 32]| {
+            let (first, second) = if a <= b { (a, b) } else { (b, a) };
+            hashv(&[MERKLE_NODE_PREFIX, &first, &second]).to_bytes()
+        };
+        let assets: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        let leaves: Vec<[u8; 32]> = assets.iter().zip([10, 55, 90]).map(|(asset, score)| risk_report_leaf(asset, score)).collect();
+        // The odd third leaf is carried up unchanged
+        let root = node(node(leaves[0], leaves[1]), leaves[2]);
+
+        assert!(verify_merkle_proof(leaves[0], &[leaves[1], leaves[2]], root));
+        assert!(verify_merkle_proof(leaves[1], &[leaves[0], leaves[2]], root));
+        assert!(verify_merkle_proof(leaves[2], &[node(leaves[0], leaves[1])], root));
+
+        assert!(!verify_merkle_proof(risk_report_leaf(&assets[0], 11), &[leaves[1], leaves[2]], root));
+    }
+
+    fn asset(valuation: u64, risk_score: u8) -> Asset {
+        Asset {
+            asset_id: "deed-1".into(),
+            asset_type: "real_estate".into(),
+            valuation,
+            metadata_uri: String::new(),
+            owner: Pubkey::new_unique(),
+            is_active: true,
+            risk_score,
+            bump: 255,
+            active_loans: 0,
+            delegate: Pubkey::default(),
+            valuation_refresh_requested_at: 0,
+            last_risk_report: 0,
+            risk_frozen_until: 0,
+            breaker_confirmers: Vec::new(),
+            last_risk_update: 0,
+            jurisdiction: NO_JURISDICTION,
+            portfolio: Pubkey::default(),
+        }
+    }
+
+    fn active_loan(end_time: i64) -> Loan {
+        Loan {
+            borrower: Pubkey::new_unique(),
+            asset: Pubkey::new_unique(),
+            principal: PRINCIPAL,
+            interest_rate: 1_000,
+            start_time: 0,
+            end_time,
+            status: LoanStatus::Active,
+            risk_score_at_creation: 10,
+            bump: 255,
+            forborne_seconds: 0,
+            forbearance_start: 0,
+            forbearance_end: 0,
+            modification_count: 0,
+            lender: Pubkey::new_unique(),
+            request_expiry: 0,
+            repayment_mint: Pubkey::new_unique(),
+            supplemental_asset: Pubkey::default(),
+            written_off_amount: 0,
+            interest_mode: InterestMode::Simple,
+            disputed: false,
+            dispute_raised_by: Pubkey::default(),
+            dispute_raised_at: 0,
+            dispute_outcome: None,
+            dispute_resolved_at: 0,
+            late_fees: 0,
+            liquidation_eligible: false,
+            liquidator: Pubkey::default(),
+            liquidation_debt: 0,
+            referral: Pubkey::default(),
+            rollover_count: 0,
+            product: LoanProduct::Amortizing,
+            floating_rate: None,
+            interest_checkpoint: 0,
+            checkpoint_seconds: 0,
+            last_repriced_at: 0,
+            collateral_rules: CollateralRules { max_ltv: 100, haircut_bps: 0, jurisdiction_max_ltv: 0 },
+            supplemental_rules: CollateralRules::default(),
+            principal_repaid: 0,
+        }
+    }
+
+    #[test]
+    fn floating_rates_bank_interest_across_repricings() {
+        let terms = FloatingRate {
+            benchmark: Pubkey::new_unique(),
+            spread_bps: 200,
+            floor_bps: 300,
+            cap_bps: 1_200,
+        };
+        // 5.31% and -2.5% as quoted by the feed
+        let sofr = SwitchboardDecimal { mantissa: 531, scale: 2 };
+        let negative = SwitchboardDecimal { mantissa: -25, scale: 1 };
+        assert_eq!(sofr.percent_to_bps(), Some(531));
+        assert_eq!(negative.percent_to_bps(), Some(-250));
+        assert_eq!(terms.rate_for(531), 731);
+        assert_eq!(terms.rate_for(-250), 300);
+        assert_eq!(terms.rate_for(5_000), 1_200);
+
+        assert!(validate_floating_rate(1_000, Some(&terms)).is_ok());
+        assert!(validate_floating_rate(1_300, Some(&terms)).is_err());
+        assert!(validate_floating_rate(200, Some(&terms)).is_err());
+        assert!(validate_floating_rate(500, Some(&FloatingRate { benchmark: Pubkey::default(), ..terms })).is_err());
+        assert!(validate_floating_rate(5_000, None).is_ok());
+
+        // Half a year at 10%, then half a year at 5%
+        let mut loan = active_loan(YEAR);
+        loan.floating_rate = Some(terms);
+        loan.checkpoint_interest(YEAR / 2).unwrap();
+        assert_eq!(loan.interest_checkpoint, 50_000);
+        loan.interest_rate = 500;
+        assert_eq!(loan_interest(&loan, YEAR / 2).unwrap(), 50_000);
+        assert_eq!(outstanding_debt(&loan, YEAR).unwrap(), PRINCIPAL + 75_000);
+
+        // Rolling over capitalizes the banked interest and starts afresh
+        loan.roll_over(YEAR, 500, YEAR).unwrap();
+        assert_eq!((loan.principal, loan.interest_checkpoint, loan.checkpoint_seconds), (PRINCIPAL + 75_000, 0, 0));
+    }
+
+    #[test]
+    fn report_disputes_need_an_overturn_quorum() {
+        let mut dispute = ReportDispute {
+            report: Pubkey::new_unique(),
+            oracle: Pubkey::new_unique(),
+            challenger: Pubkey::new_unique(),
+            opened_at: 0,
+            voters: Vec::new(),
+            overturn_votes: 1,
+            uphold_votes: 0,
+            resolved: false,
+            bump: 255,
+        };
+        assert!(!dispute.is_overturned());
+        dispute.overturn_votes = REPORT_DISPUTE_QUORUM;
+        assert!(dispute.is_overturned());
+        // A tie upholds the report
+        dispute.uphold_votes = REPORT_DISPUTE_QUORUM;
+        assert!(!dispute.is_overturned());
+
+        assert_eq!(slash_amount(MIN_ORACLE_BOND), MIN_ORACLE_BOND / 2);
+        assert_eq!(slash_amount(u64::MAX), u64::MAX / 2);
+    }
+
+    #[test]
+    fn late_fees_and_scheduled_status_follow_maturity() {
+        let mut loan = active_loan(100 * DAY);
+
+        assert_eq!(late_fee(&loan, loan.end_time), 0);
+        assert_eq!(scheduled_status(&loan, loan.end_time), LoanStatus::Active);
+        // Partial days are not charged
+        assert_eq!(late_fee(&loan, loan.end_time + DAY - 1), 0);
+        assert_eq!(late_fee(&loan, loan.end_time + 3 * DAY), 3_000);
+        assert_eq!(scheduled_status(&loan, loan.end_time + 1), LoanStatus::Overdue);
+        assert_eq!(late_fee(&loan, loan.end_time + 365 * DAY), 100_000);
+        assert_eq!(scheduled_status(&loan, loan.end_time + DEFAULT_GRACE_PERIOD + 1), LoanStatus::Defaulted);
+
+        // An extended maturity brings an overdue loan back to Active
+        loan.status = LoanStatus::Overdue;
+        assert_eq!(scheduled_status(&loan, loan.end_time - 1), LoanStatus::Active);
+        // Health-driven states are left alone before maturity
+        loan.status = LoanStatus::Liquidating;
+        assert_eq!(scheduled_status(&loan, loan.end_time - 1), LoanStatus::Liquidating);
+
+        loan.status = LoanStatus::Repaid;
+        assert_eq!(late_fee(&loan, loan.end_time + 3 * DAY), 0);
+    }
+
+    #[test]
+    fn rollover_capitalizes_interest_and_late_fees() {
+        let mut loan = active_loan(YEAR);
+        loan.status = LoanStatus::Overdue;
+        loan.late_fees = 1;
+        loan.forbearance_start = DAY;
+        loan.forbearance_end = 2 * DAY;
+        let now = YEAR + 3 * DAY;
+        let owed = outstanding_debt(&loan, now).unwrap();
+        assert!(owed > PRINCIPAL + late_fee(&loan, now));
+
+        assert_eq!(loan.roll_over(now, 800, 90 * DAY).unwrap(), owed);
+        assert_eq!(loan.principal, owed);
+        assert_eq!((loan.start_time, loan.end_time), (now, now + 90 * DAY));
+        assert_eq!(loan.interest_rate, 800);
+        assert_eq!(loan.status, LoanStatus::Active);
+        assert_eq!((loan.forbearance_start, loan.forbearance_end, loan.late_fees), (0, 0, 0));
+        // The successor starts with nothing accrued
+        assert_eq!(outstanding_debt(&loan, now).unwrap(), owed);
+    }
+
+    #[test]
+    fn installments_follow_the_loan_product() {
+        let mut loan = active_loan(YEAR);
+        let interval = YEAR / 4;
+        let quarter_interest = accrued_interest(PRINCIPAL, loan.interest_rate, interval, loan.interest_mode).unwrap();
+
+        loan.product = LoanProduct::InterestOnly;
+        let amounts: Vec<u64> = build_installments(&loan, interval, 4).unwrap().iter().map(|i| i.amount).collect();
+        assert_eq!(amounts, vec![quarter_interest, quarter_interest, quarter_interest, PRINCIPAL + quarter_interest]);
+
+        // Interest shrinks with the balance
+        loan.product = LoanProduct::Amortizing;
+        let installments = build_installments(&loan, interval, 4).unwrap();
+        assert_eq!(installments[0].amount, PRINCIPAL / 4 + quarter_interest);
+        assert!(installments.windows(2).all(|pair| pair[1].amount < pair[0].amount));
+        assert_eq!(installments[3].due_date, YEAR);
+
+        let principal: u64 = (0..4).map(|i| LoanProduct::InterestOnly.installment_principal(PRINCIPAL, 4, i)).sum();
+        assert_eq!(principal, PRINCIPAL);
+        assert_eq!(LoanProduct::Bullet.installment_principal(PRINCIPAL, 1, 0), PRINCIPAL);
+    }
+
+    #[test]
+    fn installments_pay_down_debt_and_health() {
+        let asset = asset(PRINCIPAL, 50);
+        let mut loan = active_loan(YEAR);
+        loan.principal = collateral_limit(&loan, &asset, None) + 1;
+        let installments = build_installments(&loan, YEAR / 4, 4).unwrap();
+        let part = |i| loan.product.installment_principal(loan.principal, 4, i);
+        let (first, second) = (part(0), part(1));
+        assert!(liquidation_trigger(&loan, &asset, None, 0).unwrap().is_some());
+
+        // Paid early, the first installment's interest covers its whole period
+        loan.apply_installment(first, installments[0].due_date).unwrap();
+        assert_eq!(outstanding_debt(&loan, 0).unwrap(), loan.principal - first);
+        assert_eq!(outstanding_debt(&loan, YEAR / 4).unwrap(), loan.principal - first);
+        assert!(liquidation_trigger(&loan, &asset, None, YEAR / 4).unwrap().is_none());
+
+        // Interest then runs on the remaining balance only
+        loan.apply_installment(second, installments[1].due_date).unwrap();
+        let remaining = loan.principal - first - second;
+        assert_eq!(loan.remaining_principal(), remaining);
+        let interest = accrued_interest(remaining, loan.interest_rate, YEAR / 4, loan.interest_mode).unwrap();
+        assert_eq!(outstanding_debt(&loan, 3 * YEAR / 4).unwrap(), remaining + interest);
+        // Past maturity the late fee is charged on what is left
+        assert_eq!(late_fee(&loan, YEAR + 3 * DAY), remaining * 30 / 10_000);
+
+        // Installments can never repay more than was lent
+        assert!(loan.apply_installment(loan.principal, YEAR).is_err());
+    }
+
+    #[test]
+    fn late_installments_pay_a_late_fee() {
+        let installment = Installment { due_date: 10 * DAY, amount: 100_000, paid_at: 0 };
+
+        assert_eq!(installment_late_fee(&installment, 10 * DAY), 0);
+        // Within the grace period nothing is charged
+        assert_eq!(installment_late_fee(&installment, 10 * DAY + INSTALLMENT_GRACE_PERIOD), 0);
+        // After it, every full day since the due date counts
+        assert_eq!(installment_late_fee(&installment, 14 * DAY), 400);
+        assert_eq!(installment_late_fee(&installment, 10 * DAY + 365 * DAY), 10_000);
+    }
+
+    #[test]
+    fn risk_scores_go_stale_after_max_age() {
+        let mut asset = asset(PRINCIPAL, 50);
+
+        // Never scored: stale as soon as the gate is on
+        assert!(!asset.is_risk_stale(DAY, 0));
+        assert!(asset.is_risk_stale(DAY, DAY - 1));
+
+        asset.last_risk_update = 10 * DAY;
+        assert!(!asset.is_risk_stale(11 * DAY, DAY));
+        assert!(asset.is_risk_stale(11 * DAY + 1, DAY));
+    }
+
+    #[test]
+    fn max_ltv_loans_have_room_to_accrue_interest() {
+        let asset = asset(2_000_000_000_000, 50);
+        let mut loan = active_loan(YEAR);
+        loan.principal = loan.collateral_rules.max_loan(asset.valuation, asset.risk_score);
+
+        // One second of interest already puts the debt over the origination limit
+        assert!(outstanding_debt(&loan, 1).unwrap() > loan.principal);
+        assert!(liquidation_trigger(&loan, &asset, None, 1).unwrap().is_none());
+        assert!(liquidation_trigger(&loan, &asset, None, YEAR).unwrap().is_none());
+
+        // Past the threshold the same collateral no longer covers it
+        loan.principal = collateral_limit(&loan, &asset, None) + 1;
+        assert!(matches!(
+            liquidation_trigger(&loan, &asset, None, 0).unwrap(),
+            Some(LiquidationTrigger::LtvBreach(9_999))
+        ));
+    }
+
+    #[test]
+    fn health_applies_the_rules_the_loan_was_funded_under() {
+        let asset = Asset { jurisdiction: *b"SG", ..asset(2 * PRINCIPAL, 10) };
+        let type_config = AssetTypeConfig {
+            asset_type: "invoice".into(),
+            max_ltv: 60,
+            haircut_bps: 2_500,
+            max_duration: DAY,
+            bump: 255,
+        };
+        let rules = JurisdictionConfig {
+            jurisdiction: *b"SG",
+            allowed_asset_types: Vec::new(),
+            max_ltv: 40,
+            transfer_restriction: TransferRestriction::Unrestricted,
+            bump: 255,
+        };
+        let mut loan = active_loan(YEAR);
+        loan.collateral_rules = CollateralRules::new(&type_config, Some(&rules));
+
+        // 75% of value survives the haircut and the jurisdiction allows 40% of it,
+        // where the risk score alone would allow 70% of the full valuation
+        let max_loan = max_loan_in_jurisdiction(asset.valuation, asset.risk_score, &type_config, Some(&rules));
+        assert_eq!(max_loan, 600_000);
+        assert_eq!(loan.collateral_rules.max_loan(asset.valuation, asset.risk_score), max_loan);
+        assert_eq!(collateral_limit(&loan, &asset, None), 660_000);
+
+        loan.principal = 700_000;
+        assert!(matches!(
+            liquidation_trigger(&loan, &asset, None, 0).unwrap(),
+            Some(LiquidationTrigger::LtvBreach(9_428))
+        ));
+
+        // A top-up counts under its own rules
+        let mut top_up = asset.clone();
+        top_up.jurisdiction = NO_JURISDICTION;
+        loan.supplemental_rules = CollateralRules::new(&type_config, None);
+        assert_eq!(collateral_limit(&loan, &asset, Some(&top_up)), 660_000 + 990_000);
+        assert!(liquidation_trigger(&loan, &asset, Some(&top_up), 0).unwrap().is_none());
+    }
+
+    #[test]
+    fn unhealthy_loans_only_liquidate_when_liquidation_can_run() {
+        let mut asset = asset(2 * PRINCIPAL, 50);
+        let mut loan = active_loan(YEAR);
+        loan.principal = 2 * PRINCIPAL;
+        loan.interest_rate = 0;
+        assert_eq!(health_status(&loan, &asset, None, 0).unwrap(), (LoanStatus::Liquidating, 5_500));
+
+        // `liquidate_loan` refuses disputed loans and frozen collateral
+        loan.disputed = true;
+        assert_eq!(health_status(&loan, &asset, None, 0).unwrap().0, LoanStatus::Active);
+        loan.disputed = false;
+        asset.risk_frozen_until = DAY;
+        assert_eq!(health_status(&loan, &asset, None, 0).unwrap().0, LoanStatus::Active);
+        assert_eq!(health_status(&loan, &asset, None, DAY).unwrap().0, LoanStatus::Liquidating);
+    }
+
+    #[test]
+    fn jurisdictions_filter_asset_types_and_cap_ltv() {
+        assert!(is_jurisdiction_code(b"SG"));
+        assert!(!is_jurisdiction_code(b"sg"));
+        assert!(!is_jurisdiction_code(&NO_JURISDICTION));
+
+        let type_config = AssetTypeConfig {
+            asset_type: "invoice".into(),
+            max_ltv: 80,
+            haircut_bps: 1_000,
+            max_duration: DAY,
+            bump: 255,
+        };
+        let mut rules = JurisdictionConfig {
+            jurisdiction: *b"SG",
+            allowed_asset_types: Vec::new(),
+            max_ltv: 0,
+            transfer_restriction: TransferRestriction::Unrestricted,
+            bump: 255,
+        };
+        assert!(rules.allows_asset_type("invoice"));
+
+        // No override leaves the asset type's rules in charge
+        let uncapped = max_loan_for_type(PRINCIPAL, 0, &type_config);
+        assert_eq!(max_loan_in_jurisdiction(PRINCIPAL, 0, &type_config, None), uncapped);
+        assert_eq!(max_loan_in_jurisdiction(PRINCIPAL, 0, &type_config, Some(&rules)), uncapped);
+
+        // 90% of value survives the haircut, half of that may be lent
+        rules.max_ltv = 50;
+        assert_eq!(max_loan_in_jurisdiction(PRINCIPAL, 0, &type_config, Some(&rules)), 450_000);
+        // A looser override never raises the limit
+        rules.max_ltv = 95;
+        assert_eq!(max_loan_in_jurisdiction(PRINCIPAL, 0, &type_config, Some(&rules)), uncapped);
+
+        rules.allowed_asset_types = vec!["real_estate".into()];
+        assert!(!rules.allows_asset_type("invoice"));
+        assert!(rules.allows_asset_type("real_estate"));
+    }
+
+    #[test]
+    fn portfolios_weight_risk_by_valuation() {
+        let member = |valuation: u64, risk_score: u8, last_risk_update: i64, risk_frozen_until: i64| Asset {
+            risk_frozen_until,
+            last_risk_update,
+            ..asset(valuation, risk_score)
+        };
+
+        // (3 * 20 + 1 * 61) / 4 = 30.25, rounded up
+        let members = [member(3 * PRINCIPAL, 20, 5 * DAY, 0), member(PRINCIPAL, 61, 2 * DAY, 9 * DAY)];
+        let metrics = portfolio_metrics(&members).unwrap();
+        assert_eq!(
+            metrics,
+            PortfolioMetrics {
+                valuation: 4 * PRINCIPAL,
+                risk_score: 31,
+                last_risk_update: 2 * DAY,
+                risk_frozen_until: 9 * DAY,
+            }
+        );
+
+        // Nothing to weigh by: the riskiest member decides
+        let worthless = [member(0, 20, DAY, 0), member(0, 70, DAY, 0)];
+        assert_eq!(portfolio_metrics(&worthless).unwrap().risk_score, 70);
+
+        let too_valuable = [member(u64::MAX, 10, DAY, 0), member(1, 10, DAY, 0)];
+        assert!(portfolio_metrics(&too_valuable).is_err());
+
+        let mut portfolio = member(0, 0, 0, 0);
+        metrics.apply_to(&mut portfolio);
+        assert_eq!((portfolio.valuation, portfolio.risk_score), (4 * PRINCIPAL, 31));
+    }
+
+    #[test]
+    fn protocol_stats_follow_loan_lifecycle() {
+        let mut stats = ProtocolStats {
+            total_assets: 1,
+            active_loans: 0,
+            total_principal_outstanding: 0,
+            cumulative_repaid: 0,
+            liquidation_count: 0,
+            bump: 255,
+            cumulative_written_off: 0,
+        };
+        let mut loan = active_loan(YEAR);
+        let mut asset = Asset {
+            active_loans: 1,
+            ..asset(10 * PRINCIPAL, 90)
+        };
+
+        stats.record_disbursement(loan.principal);
+        stats.record_disbursement(2 * PRINCIPAL);
+        assert_eq!((stats.active_loans, stats.total_principal_outstanding), (2, 3 * PRINCIPAL));
+
+        let liquidator = Pubkey::new_unique();
+        liquidate(&mut loan, &mut asset, &mut stats, liquidator, LiquidationTrigger::HighRisk(90), YEAR / 2).unwrap();
+        assert_eq!(stats.liquidation_count, 1);
+        assert_eq!(loan.liquidator, liquidator);
+        assert_eq!(loan.liquidation_debt, outstanding_debt(&active_loan(YEAR), YEAR / 2).unwrap());
+        assert_eq!((stats.active_loans, stats.total_principal_outstanding), (1, 2 * PRINCIPAL));
+
+        // Write-offs add to the loss total without touching the open book
+        stats.record_write_off(30_000);
+        stats.record_write_off(20_000);
+        assert_eq!(stats.cumulative_written_off, 50_000);
+        assert_eq!((stats.active_loans, stats.total_principal_outstanding), (1, 2 * PRINCIPAL));
+
+        stats.record_repayment(2 * PRINCIPAL + 1_000);
+        stats.record_closure(2 * PRINCIPAL);
+        assert_eq!(stats.cumulative_repaid, 2 * PRINCIPAL + 1_000);
+        assert_eq!((stats.active_loans, stats.total_principal_outstanding), (0, 0));
+
+        // Loans opened before the stats account existed must not underflow it
+        stats.record_closure(PRINCIPAL);
+        assert_eq!((stats.active_loans, stats.total_principal_outstanding), (0, 0));
+    }
+
+    #[test]
+    fn write_offs_book_the_deficiency_left_by_seizure() {
+        let mut loan = active_loan(YEAR);
+        let mut settlement = CollateralSettlement {
+            loan: Pubkey::new_unique(),
+            asset: loan.asset,
+            previous_owner: loan.borrower,
+            recipient: Pubkey::new_unique(),
+            debt: 0,
+            collateral_value: 700_000,
+            surplus: 0,
+            deficiency: 0,
+            settled_at: YEAR,
+            bump: 255,
+        };
+
+        // Defaulted but never liquidated: nothing has been tried to recover the debt
+        loan.status = LoanStatus::Defaulted;
+        assert!(write_off_amount(&loan, None).is_err());
+        assert!(write_off_amount(&loan, Some(&settlement)).is_err());
+
+        // Liquidated, but the collateral hasn't been seized yet
+        loan.status = LoanStatus::Liquidated;
+        loan.liquidation_debt = 1_100_000;
+        assert!(write_off_amount(&loan, None).is_err());
+
+        // Only the debt the seized collateral did not cover is lost
+        assert_eq!(write_off_amount(&loan, Some(&settlement)).unwrap(), 400_000);
+
+        // Collateral worth the whole debt leaves nothing to write off
+        settlement.collateral_value = 1_100_000;
+        assert!(write_off_amount(&loan, Some(&settlement)).is_err());
+    }
+
+    #[test]
+    fn reserve_share_and_installment_principal() {
+        assert_eq!(reserve_share(1_000, 1_000), 100);
+        assert_eq!(reserve_share(999, 1_000), 99);
+        assert_eq!(reserve_share(u64::MAX, MAX_RESERVE_FACTOR_BPS), u64::MAX / 2);
+        assert_eq!(reserve_share(1_000, 0), 0);
+
+        let parts: Vec<u64> = (0..3).map(|i| installment_principal(1_000, 3, i)).collect();
+        assert_eq!(parts, vec![333, 333, 334]);
+
+        // A 1% fee on 1,000,000, of which a 20% referral slice
+        let fee = origination_fee(1_000_000, 100);
+        assert_eq!(fee, 10_000);
+        assert_eq!(reserve_share(fee, 2_000), 2_000);
+        assert_eq!(origination_fee(u64::MAX, MAX_ORIGINATION_FEE_BPS), u64::MAX / 20);
+    }
+
+    #[test]
+    fn repayments_convert_between_mints_with_haircut() {
+        let usd = SwitchboardDecimal { mantissa: 1_000_000, scale: 6 };
+        let usdt = SwitchboardDecimal { mantissa: 9_990, scale: 4 };
+        let sol = SwitchboardDecimal { mantissa: 150, scale: 0 };
+
+        // Same price and decimals: only the haircut applies
+        assert_eq!(convert_repayment(1_000_000, &usd, 6, &usd, 6, 0), Some(1_000_000));
+        assert_eq!(convert_repayment(1_000_000, &usd, 6, &usd, 6, 100), Some(1_010_102));
+        // A slightly cheaper mint needs slightly more, rounded up
+        assert_eq!(convert_repayment(999_000, &usd, 6, &usdt, 6, 0), Some(1_000_000));
+        assert_eq!(convert_repayment(1, &usd, 6, &usdt, 6, 0), Some(2));
+        // 300 USDC (6 decimals) is 2 SOL (9 decimals), and back
+        assert_eq!(convert_repayment(300_000_000, &usd, 6, &sol, 9, 0), Some(2_000_000_000));
+        assert_eq!(convert_repayment(2_000_000_000, &sol, 9, &usd, 6, 0), Some(300_000_000));
+
+        let zero = SwitchboardDecimal { mantissa: 0, scale: 0 };
+        assert_eq!(convert_repayment(1, &usd, 6, &zero, 6, 0), None);
+        assert_eq!(convert_repayment(u64::MAX, &usd, 6, &sol, 9, 0), None);
+    }
+}